@@ -142,7 +142,11 @@ impl AssetItem {
                     sender.send(Message::OpenMaterialEditor(material));
                 }
             }
-        } else if self.path.extension().map_or(false, |ext| ext == "tileset") {
+        } else if self
+            .path
+            .extension()
+            .map_or(false, |ext| ext == "tileset" || ext == "tsx")
+        {
             if let Ok(path) = make_relative_path(&self.path) {
                 if let Ok(tile_set) = block_on(resource_manager.request::<TileSet>(path)) {
                     sender.send(Message::OpenTileSetEditor(tile_set));
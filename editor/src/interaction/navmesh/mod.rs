@@ -35,6 +35,7 @@ use crate::fyrox::{
         grid::{Column, GridBuilder, Row},
         message::{KeyCode, MessageDirection, UiMessage},
         stack_panel::StackPanelBuilder,
+        utils::make_simple_tooltip,
         widget::{WidgetBuilder, WidgetMessage},
         window::{WindowBuilder, WindowMessage, WindowTitle},
         BuildContext, Orientation, Thickness, UiNode, UserInterface,
@@ -58,7 +59,7 @@ use crate::{
         commands::{
             navmesh::{
                 AddNavmeshEdgeCommand, ConnectNavmeshEdgesCommand, DeleteNavmeshVertexCommand,
-                MoveNavmeshVertexCommand,
+                GenerateNavmeshCommand, MoveNavmeshVertexCommand,
             },
             ChangeSelectionCommand,
         },
@@ -76,6 +77,7 @@ pub mod selection;
 pub struct NavmeshPanel {
     pub window: Handle<UiNode>,
     connect_edges: Handle<UiNode>,
+    generate: Handle<UiNode>,
     sender: MessageSender,
     scene_frame: Handle<UiNode>,
 }
@@ -93,20 +95,38 @@ fn fetch_selection(editor_selection: &Selection) -> Option<NavmeshSelection> {
 impl NavmeshPanel {
     pub fn new(scene_frame: Handle<UiNode>, ctx: &mut BuildContext, sender: MessageSender) -> Self {
         let connect_edges;
+        let generate;
         let window = WindowBuilder::new(WidgetBuilder::new().with_name("NavmeshPanel"))
             .open(false)
             .with_title(WindowTitle::text("Navmesh"))
             .with_content(
                 GridBuilder::new(
                     WidgetBuilder::new().with_child(
-                        StackPanelBuilder::new(WidgetBuilder::new().with_child({
-                            connect_edges = ButtonBuilder::new(
-                                WidgetBuilder::new().with_margin(Thickness::uniform(1.0)),
-                            )
-                            .with_text("Connect Edges")
-                            .build(ctx);
-                            connect_edges
-                        }))
+                        StackPanelBuilder::new(
+                            WidgetBuilder::new()
+                                .with_child({
+                                    connect_edges = ButtonBuilder::new(
+                                        WidgetBuilder::new().with_margin(Thickness::uniform(1.0)),
+                                    )
+                                    .with_text("Connect Edges")
+                                    .build(ctx);
+                                    connect_edges
+                                })
+                                .with_child({
+                                    generate = ButtonBuilder::new(
+                                        WidgetBuilder::new()
+                                            .with_margin(Thickness::uniform(1.0))
+                                            .with_tooltip(make_simple_tooltip(
+                                                ctx,
+                                                "Generates navmesh from the geometry of \
+                                                generation sources using generation settings.",
+                                            )),
+                                    )
+                                    .with_text("Generate")
+                                    .build(ctx);
+                                    generate
+                                }),
+                        )
                         .with_orientation(Orientation::Horizontal)
                         .build(ctx),
                     ),
@@ -121,6 +141,7 @@ impl NavmeshPanel {
             window,
             sender,
             connect_edges,
+            generate,
             scene_frame,
         }
     }
@@ -146,6 +167,11 @@ impl NavmeshPanel {
                         [vertices[0], vertices[1]],
                     ));
                 }
+            } else if message.destination() == self.generate {
+                if let Some(selection) = fetch_selection(editor_selection) {
+                    self.sender
+                        .do_command(GenerateNavmeshCommand::new(selection.navmesh_node()));
+                }
             }
         }
     }
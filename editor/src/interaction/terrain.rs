@@ -32,12 +32,17 @@ use crate::fyrox::{
         log::{Log, MessageKind},
         math::vector_to_quat,
         pool::Handle,
+        reflect::prelude::*,
     },
     engine::Engine,
     gui::{
+        button::{ButtonBuilder, ButtonMessage},
+        grid::{Column, GridBuilder, Row},
         inspector::{
             editors::{
-                enumeration::EnumPropertyEditorDefinition, PropertyEditorDefinitionContainer,
+                enumeration::EnumPropertyEditorDefinition,
+                inspectable::InspectablePropertyEditorDefinition,
+                PropertyEditorDefinitionContainer,
             },
             Inspector, InspectorBuilder, InspectorContext, InspectorMessage, PropertyAction,
         },
@@ -56,7 +61,10 @@ use crate::fyrox::{
             MeshBuilder, RenderPath,
         },
         node::Node,
-        terrain::brushstroke::{Brush, BrushMode, BrushShape, BrushStroke, BrushTarget},
+        terrain::brushstroke::{Brush, BrushMode, BrushShape, BrushStroke, BrushTarget, ChunkData},
+        terrain::generator::{
+            HydraulicErosion, NoiseGenerator, NoiseKind, TerrainGenerator, ThermalErosion,
+        },
         terrain::{Terrain, TerrainRayCastResult},
     },
 };
@@ -69,7 +77,8 @@ use crate::{
     message::MessageSender,
     scene::{
         commands::terrain::{
-            ModifyTerrainHeightCommand, ModifyTerrainHolesCommand, ModifyTerrainLayerMaskCommand,
+            ModifyTerrainFoliageMaskCommand, ModifyTerrainHeightCommand, ModifyTerrainHolesCommand,
+            ModifyTerrainLayerMaskCommand,
         },
         GameScene, Selection,
     },
@@ -99,6 +108,9 @@ fn handle_undo_chunks(undo_chunks: UndoData, sender: &MessageSender) {
             undo_chunks.chunks,
             layer,
         )),
+        BrushTarget::FoliageMask { layer } => sender.do_command(
+            ModifyTerrainFoliageMaskCommand::new(undo_chunks.node, undo_chunks.chunks, layer),
+        ),
     }
 }
 
@@ -112,6 +124,7 @@ pub struct TerrainInteractionMode {
     brush_value: f32,
     brush: Brush,
     brush_panel: BrushPanel,
+    generator_panel: GeneratorPanel,
     scene_viewer_frame: Handle<UiNode>,
 }
 
@@ -133,11 +146,14 @@ impl TerrainInteractionMode {
 
         let brush_panel =
             BrushPanel::new(&mut engine.user_interfaces.first_mut().build_ctx(), &brush);
+        let generator_panel =
+            GeneratorPanel::new(&mut engine.user_interfaces.first_mut().build_ctx());
 
         Self {
             message_sender,
             brush_sender: None,
             brush_panel,
+            generator_panel,
             brush_gizmo: BrushGizmo::new(game_scene, engine),
             interacting: false,
             brush,
@@ -174,10 +190,10 @@ impl TerrainInteractionMode {
     fn start_stroke(&self, terrain: &mut Terrain, handle: Handle<Node>, shift: bool) {
         let mut brush = self.brush.clone();
         // Ignore stroke with a non-existent layer index.
-        if let BrushTarget::LayerMask { layer } = brush.target {
-            if layer >= terrain.layers().len() {
-                return;
-            }
+        match brush.target {
+            BrushTarget::LayerMask { layer } if layer >= terrain.layers().len() => return,
+            BrushTarget::FoliageMask { layer } if layer >= terrain.foliage_layers().len() => return,
+            _ => (),
         }
         // Reverse the behavior of a brush when shift is held.
         if shift {
@@ -204,12 +220,16 @@ impl TerrainInteractionMode {
         };
         let position = match self.brush.target {
             BrushTarget::HeightMap => terrain.local_to_height_pixel(position),
-            BrushTarget::LayerMask { .. } => terrain.local_to_mask_pixel(position),
+            BrushTarget::LayerMask { .. } | BrushTarget::FoliageMask { .. } => {
+                terrain.local_to_mask_pixel(position)
+            }
             BrushTarget::HoleMask { .. } => terrain.local_to_hole_pixel(position),
         };
         let scale = match self.brush.target {
             BrushTarget::HeightMap => terrain.height_grid_scale(),
-            BrushTarget::LayerMask { .. } => terrain.mask_grid_scale(),
+            BrushTarget::LayerMask { .. } | BrushTarget::FoliageMask { .. } => {
+                terrain.mask_grid_scale()
+            }
             BrushTarget::HoleMask { .. } => terrain.hole_grid_scale(),
         };
         if let Some(sender) = &self.brush_sender {
@@ -434,6 +454,19 @@ impl InteractionMode for TerrainInteractionMode {
                 false,
                 false,
             ));
+        engine
+            .user_interfaces
+            .first_mut()
+            .send_message(WindowMessage::open_and_align(
+                self.generator_panel.window,
+                MessageDirection::ToWidget,
+                self.scene_viewer_frame,
+                HorizontalAlignment::Right,
+                VerticalAlignment::Bottom,
+                Thickness::bottom_right(5.0),
+                false,
+                false,
+            ));
     }
 
     fn deactivate(&mut self, controller: &dyn SceneController, engine: &mut Engine) {
@@ -453,18 +486,46 @@ impl InteractionMode for TerrainInteractionMode {
                 self.brush_panel.window,
                 MessageDirection::ToWidget,
             ));
+        engine
+            .user_interfaces
+            .first_mut()
+            .send_message(WindowMessage::close(
+                self.generator_panel.window,
+                MessageDirection::ToWidget,
+            ));
     }
 
     fn handle_ui_message(
         &mut self,
         message: &UiMessage,
         editor_selection: &Selection,
-        _controller: &mut dyn SceneController,
-        _engine: &mut Engine,
+        controller: &mut dyn SceneController,
+        engine: &mut Engine,
     ) {
         if let Some(selection) = editor_selection.as_graph() {
             if selection.is_single_selection() {
                 self.brush_panel.handle_ui_message(message, &mut self.brush);
+
+                if self.generator_panel.handle_ui_message(message) {
+                    let Some(game_scene) = controller.downcast_mut::<GameScene>() else {
+                        return;
+                    };
+                    let handle = selection.nodes()[0];
+                    if let Some(terrain) =
+                        engine.scenes[game_scene.scene].graph[handle].cast_mut::<Terrain>()
+                    {
+                        let heightmaps = terrain
+                            .chunks_ref()
+                            .iter()
+                            .map(|c| ChunkData::from_texture(c.grid_position(), c.heightmap()))
+                            .collect::<Vec<_>>();
+                        self.generator_panel.settings.generator.apply(terrain);
+                        // The command holds the old height maps and skips its first execution,
+                        // so it only has to swap them on undo/redo.
+                        self.message_sender
+                            .do_command(ModifyTerrainHeightCommand::new(handle, heightmaps));
+                    }
+                }
             }
         }
     }
@@ -477,6 +538,13 @@ impl InteractionMode for TerrainInteractionMode {
                 self.brush_panel.window,
                 MessageDirection::ToWidget,
             ));
+        engine
+            .user_interfaces
+            .first_mut()
+            .send_message(WidgetMessage::remove(
+                self.generator_panel.window,
+                MessageDirection::ToWidget,
+            ));
     }
 
     fn on_hot_key_pressed(
@@ -523,12 +591,16 @@ impl InteractionMode for TerrainInteractionMode {
             self.modify_brush_opacity(1.0);
             processed = true;
         } else if hotkey == &key_bindings.prev_layer {
-            if let BrushTarget::LayerMask { layer, .. } = &mut self.brush.target {
+            if let BrushTarget::LayerMask { layer, .. } | BrushTarget::FoliageMask { layer } =
+                &mut self.brush.target
+            {
                 *layer = layer.saturating_sub(1);
             }
             processed = true;
         } else if hotkey == &key_bindings.next_layer {
-            if let BrushTarget::LayerMask { layer, .. } = &mut self.brush.target {
+            if let BrushTarget::LayerMask { layer, .. } | BrushTarget::FoliageMask { layer } =
+                &mut self.brush.target
+            {
                 *layer = layer.saturating_add(1);
             }
             processed = true;
@@ -598,18 +670,21 @@ fn make_brush_target_enum_property_editor_definition() -> EnumPropertyEditorDefi
             0 => BrushTarget::HeightMap,
             1 => BrushTarget::LayerMask { layer: 0 },
             2 => BrushTarget::HoleMask,
+            3 => BrushTarget::FoliageMask { layer: 0 },
             _ => unreachable!(),
         },
         index_generator: |v| match v {
             BrushTarget::HeightMap => 0,
             BrushTarget::LayerMask { .. } => 1,
             BrushTarget::HoleMask => 2,
+            BrushTarget::FoliageMask { .. } => 3,
         },
         names_generator: || {
             vec![
                 "Height Map".to_string(),
                 "Layer Mask".to_string(),
                 "Holes".to_string(),
+                "Foliage Density".to_string(),
             ]
         },
     }
@@ -703,3 +778,141 @@ impl BrushPanel {
         Some(())
     }
 }
+
+#[derive(Reflect, Default, Debug)]
+struct GeneratorSettings {
+    generator: TerrainGenerator,
+}
+
+struct GeneratorPanel {
+    window: Handle<UiNode>,
+    inspector: Handle<UiNode>,
+    apply: Handle<UiNode>,
+    settings: GeneratorSettings,
+}
+
+fn make_terrain_generator_enum_property_editor_definition(
+) -> EnumPropertyEditorDefinition<TerrainGenerator> {
+    EnumPropertyEditorDefinition {
+        variant_generator: |i| match i {
+            0 => TerrainGenerator::Noise(Default::default()),
+            1 => TerrainGenerator::ThermalErosion(Default::default()),
+            2 => TerrainGenerator::HydraulicErosion(Default::default()),
+            _ => unreachable!(),
+        },
+        index_generator: |v| match v {
+            TerrainGenerator::Noise(_) => 0,
+            TerrainGenerator::ThermalErosion(_) => 1,
+            TerrainGenerator::HydraulicErosion(_) => 2,
+        },
+        names_generator: || {
+            vec![
+                "Noise".to_string(),
+                "Thermal Erosion".to_string(),
+                "Hydraulic Erosion".to_string(),
+            ]
+        },
+    }
+}
+
+fn make_noise_kind_enum_property_editor_definition() -> EnumPropertyEditorDefinition<NoiseKind> {
+    EnumPropertyEditorDefinition {
+        variant_generator: |i| match i {
+            0 => NoiseKind::Fbm,
+            1 => NoiseKind::Ridged,
+            _ => unreachable!(),
+        },
+        index_generator: |v| match v {
+            NoiseKind::Fbm => 0,
+            NoiseKind::Ridged => 1,
+        },
+        names_generator: || vec!["fBm".to_string(), "Ridged".to_string()],
+    }
+}
+
+impl GeneratorPanel {
+    fn new(ctx: &mut BuildContext) -> Self {
+        let property_editors = PropertyEditorDefinitionContainer::with_default_editors();
+        property_editors.insert(make_terrain_generator_enum_property_editor_definition());
+        property_editors.insert(make_noise_kind_enum_property_editor_definition());
+        property_editors.insert(InspectablePropertyEditorDefinition::<NoiseGenerator>::new());
+        property_editors.insert(InspectablePropertyEditorDefinition::<ThermalErosion>::new());
+        property_editors.insert(InspectablePropertyEditorDefinition::<HydraulicErosion>::new());
+
+        let settings = GeneratorSettings::default();
+
+        let context = InspectorContext::from_object(
+            &settings,
+            ctx,
+            Arc::new(property_editors),
+            None,
+            MSG_SYNC_FLAG,
+            0,
+            true,
+            Default::default(),
+            150.0,
+        );
+
+        let inspector;
+        let apply;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(300.0).with_height(400.0))
+            .can_minimize(false)
+            .can_maximize(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child({
+                            inspector = InspectorBuilder::new(WidgetBuilder::new().on_row(0))
+                                .with_context(context)
+                                .build(ctx);
+                            inspector
+                        })
+                        .with_child({
+                            apply = ButtonBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_text("Apply")
+                            .build(ctx);
+                            apply
+                        }),
+                )
+                .add_column(Column::stretch())
+                .add_row(Row::stretch())
+                .add_row(Row::strict(24.0))
+                .build(ctx),
+            )
+            .open(false)
+            .with_title(WindowTitle::text("Terrain Generators"))
+            .build(ctx);
+
+        Self {
+            window,
+            inspector,
+            apply,
+            settings,
+        }
+    }
+
+    /// Returns `true` if the selected generator must be applied to the terrain.
+    fn handle_ui_message(&mut self, message: &UiMessage) -> bool {
+        if message.destination() == self.inspector
+            && message.direction() == MessageDirection::FromWidget
+        {
+            if let Some(InspectorMessage::PropertyChanged(msg)) = message.data::<InspectorMessage>()
+            {
+                PropertyAction::from_field_kind(&msg.value).apply(
+                    &msg.path(),
+                    &mut self.settings,
+                    &mut |result| {
+                        Log::verify(result);
+                    },
+                );
+            }
+        } else if let Some(ButtonMessage::Click) = message.data() {
+            return message.destination() == self.apply;
+        }
+        false
+    }
+}
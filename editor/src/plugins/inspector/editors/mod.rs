@@ -86,30 +86,44 @@ use crate::{
             node::Node,
             particle_system::CoordinateSystem,
             particle_system::{
+                collision::{ParticleCollision, ParticleCollisionResponse},
                 emitter::{
-                    base::BaseEmitter, cuboid::CuboidEmitter, cylinder::CylinderEmitter,
-                    sphere::SphereEmitter, Emitter,
+                    base::BaseEmitter,
+                    cuboid::CuboidEmitter,
+                    cylinder::CylinderEmitter,
+                    mesh::{MeshEmitter, MeshEmitterMode},
+                    sphere::SphereEmitter,
+                    Emitter,
                 },
+                sub_emitter::{SubEmitter, SubEmitterTrigger},
+                trail::ParticleTrail,
                 ParticleSystemRng,
             },
             ragdoll::Limb,
             rigidbody::RigidBodyType,
             sound::{
                 self,
+                chorus::Chorus,
+                compressor::{Compressor, Limiter},
+                delay::Delay,
+                distortion::Distortion,
                 filter::{
                     AllPassFilterEffect, BandPassFilterEffect, HighPassFilterEffect,
                     HighShelfFilterEffect, LowPassFilterEffect, LowShelfFilterEffect,
                 },
+                occlusion::SoundOcclusion,
                 reverb::Reverb,
                 Attenuate, AudioBus, Biquad, DistanceModel, Effect, SoundBuffer,
                 SoundBufferResource, Status,
             },
-            terrain::{Chunk, Layer},
+            terrain::{foliage::FoliageLayer, Chunk, Layer},
+            tilemap::autotile::{Terrain, TerrainKind, TerrainMask, TileTerrain},
             tilemap::brush::{TileMapBrush, TileMapBrushResource},
             tilemap::tileset::TileCollider,
             tilemap::{tileset::TileSet, Tile},
             transform::Transform,
         },
+        utils::navmesh::generator::NavmeshGenerationSettings,
     },
     message::MessageSender,
 };
@@ -240,7 +254,11 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.register_inheritable_vec_collection::<Layer>();
     container.register_inheritable_inspectable::<Layer>();
 
+    container.register_inheritable_vec_collection::<FoliageLayer>();
+    container.register_inheritable_inspectable::<FoliageLayer>();
+
     container.register_inheritable_vec_collection::<Emitter>();
+    container.register_inheritable_vec_collection::<SubEmitter>();
 
     container.register_inheritable_vec_collection::<LevelOfDetail>();
     container.register_inheritable_inspectable::<LevelOfDetail>();
@@ -324,6 +342,8 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
 
     container.register_inheritable_inspectable::<ColorGradingLut>();
     container.register_inheritable_inspectable::<InteractionGroups>();
+    container.register_inheritable_inspectable::<SoundOcclusion>();
+    container.register_inheritable_inspectable::<NavmeshGenerationSettings>();
 
     container.register_inheritable_enum::<JointParams, _>();
     container.register_inheritable_enum::<dim2::joint::JointParams, _>();
@@ -350,6 +370,11 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.insert(InspectablePropertyEditorDefinition::<LowShelfFilterEffect>::new());
     container.insert(InspectablePropertyEditorDefinition::<HighShelfFilterEffect>::new());
     container.insert(InspectablePropertyEditorDefinition::<Reverb>::new());
+    container.insert(InspectablePropertyEditorDefinition::<Compressor>::new());
+    container.insert(InspectablePropertyEditorDefinition::<Limiter>::new());
+    container.insert(InspectablePropertyEditorDefinition::<Delay>::new());
+    container.insert(InspectablePropertyEditorDefinition::<Chorus>::new());
+    container.insert(InspectablePropertyEditorDefinition::<Distortion>::new());
 
     container.register_inheritable_enum::<Emitter, _>();
 
@@ -358,6 +383,8 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.register_inheritable_inspectable::<BaseEmitter>();
     container.register_inheritable_inspectable::<SphereEmitter>();
     container.register_inheritable_inspectable::<CylinderEmitter>();
+    container.register_inheritable_inspectable::<MeshEmitter>();
+    container.register_inheritable_enum::<MeshEmitterMode, _>();
     container.register_inheritable_inspectable::<CuboidEmitter>();
     container.register_inheritable_inspectable::<PerspectiveProjection>();
     container.register_inheritable_inspectable::<OrthographicProjection>();
@@ -394,6 +421,11 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.register_inheritable_enum::<sound::Renderer, _>();
     container.register_inheritable_enum::<RenderPath, _>();
     container.register_inheritable_enum::<CoordinateSystem, _>();
+    container.register_inheritable_enum::<ParticleCollisionResponse, _>();
+    container.register_inheritable_inspectable::<ParticleCollision>();
+    container.register_inheritable_enum::<SubEmitterTrigger, _>();
+    container.register_inheritable_inspectable::<SubEmitter>();
+    container.register_inheritable_inspectable::<ParticleTrail>();
 
     container.insert(EnumPropertyEditorDefinition::<Vec<ScriptRecord>>::new_optional());
     container.insert(VecCollectionPropertyEditorDefinition::<ScriptRecord>::new());
@@ -401,6 +433,7 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.insert(ScriptPropertyEditorDefinition {});
 
     container.insert(BitFieldPropertyEditorDefinition::<BitMask>::new());
+    container.insert(InheritablePropertyEditorDefinition::<BitMask>::new());
 
     container.register_inheritable_inspectable::<BallShape>();
     container.register_inheritable_inspectable::<dim2::collider::BallShape>();
@@ -472,6 +505,12 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.register_inheritable_vec_collection::<Tile>();
 
     container.register_inheritable_enum::<TileCollider, _>();
+    container.register_inheritable_enum::<TerrainKind, _>();
+    container.register_inheritable_inspectable::<Terrain>();
+    container.register_inheritable_vec_collection::<Terrain>();
+    container.register_inheritable_inspectable::<TerrainMask>();
+    container.register_inheritable_inspectable::<TileTerrain>();
+    container.register_inheritable_option::<TileTerrain>();
 
     container
 }
@@ -27,7 +27,10 @@ use crate::fyrox::{
 };
 use crate::scene::commands::{GameSceneContext, RevertSceneNodePropertyCommand};
 use crate::{
-    scene::commands::terrain::{AddTerrainLayerCommand, DeleteTerrainLayerCommand},
+    scene::commands::terrain::{
+        AddTerrainFoliageLayerCommand, AddTerrainLayerCommand, DeleteTerrainFoliageLayerCommand,
+        DeleteTerrainLayerCommand,
+    },
     Command,
 };
 use std::any::TypeId;
@@ -55,6 +58,21 @@ impl SceneNodePropertyChangedHandler {
                 },
                 _ => None,
             }
+        } else if args.path() == Terrain::FOLIAGE_LAYERS
+            && args.owner_type_id == TypeId::of::<Terrain>()
+        {
+            match args.value {
+                FieldKind::Collection(ref collection_changed) => match **collection_changed {
+                    CollectionChanged::Add(_) => {
+                        Some(Command::new(AddTerrainFoliageLayerCommand::new(handle)))
+                    }
+                    CollectionChanged::Remove(index) => Some(Command::new(
+                        DeleteTerrainFoliageLayerCommand::new(handle, index),
+                    )),
+                    CollectionChanged::ItemChanged { .. } => None,
+                },
+                _ => None,
+            }
         } else {
             None
         }
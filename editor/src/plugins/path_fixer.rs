@@ -25,8 +25,10 @@ use crate::{
     fyrox::{
         asset::{manager::ResourceManager, untyped::ResourceKind, untyped::UntypedResource},
         core::{
-            futures::executor::block_on, pool::Handle, replace_slashes, some_or_return,
-            visitor::Visitor,
+            futures::executor::block_on,
+            pool::Handle,
+            replace_slashes, some_or_return,
+            visitor::{Visitor, VisitorFormat},
         },
         engine::SerializationContext,
         graph::{BaseSceneGraph, SceneGraph},
@@ -413,7 +415,10 @@ impl PathFixer {
                         .save("Scene", &mut visitor)
                         .expect("Unable to visit a scene!");
                     visitor
-                        .save_binary(&self.scene_path_value)
+                        .save_to_file(
+                            &self.scene_path_value,
+                            VisitorFormat::of_file(&self.scene_path_value).unwrap_or_default(),
+                        )
                         .expect("Unable to save a scene!");
                 }
 
@@ -42,15 +42,13 @@ impl CommandTrait for AddTileCommand {
     }
 
     fn execute(&mut self, _context: &mut dyn CommandContext) {
-        self.handle = self
-            .tile_set
-            .data_ref()
-            .tiles
-            .spawn(self.tile.take().unwrap());
+        self.handle = self.tile_set.data_ref().add_tile(self.tile.take().unwrap());
     }
 
     fn revert(&mut self, _context: &mut dyn CommandContext) {
-        self.tile = self.tile_set.data_ref().tiles.try_free(self.handle);
+        let mut tile_set = self.tile_set.data_ref();
+        tile_set.mark_modified();
+        self.tile = tile_set.tiles.try_free(self.handle);
     }
 }
 
@@ -67,15 +65,13 @@ impl CommandTrait for RemoveTileCommand {
     }
 
     fn execute(&mut self, _context: &mut dyn CommandContext) {
-        self.tile = self.tile_set.data_ref().tiles.try_free(self.handle);
+        let mut tile_set = self.tile_set.data_ref();
+        tile_set.mark_modified();
+        self.tile = tile_set.tiles.try_free(self.handle);
     }
 
     fn revert(&mut self, _context: &mut dyn CommandContext) {
-        self.handle = self
-            .tile_set
-            .data_ref()
-            .tiles
-            .spawn(self.tile.take().unwrap());
+        self.handle = self.tile_set.data_ref().add_tile(self.tile.take().unwrap());
     }
 }
 
@@ -108,6 +108,10 @@ pub enum DrawingMode {
     Line {
         click_grid_position: Option<Vector2<i32>>,
     },
+    Terrain {
+        index: u32,
+        erase: bool,
+    },
 }
 
 struct InteractionContext {
@@ -186,6 +190,13 @@ impl InteractionMode for TileMapInteractionMode {
                 DrawingMode::FloodFill => {
                     tile_map.tiles.flood_fill(grid_coord, &brush);
                 }
+                DrawingMode::Terrain { index, erase } => {
+                    if erase {
+                        tile_map.erase_terrain(grid_coord);
+                    } else {
+                        tile_map.draw_terrain(grid_coord, index);
+                    }
+                }
                 DrawingMode::RectFill {
                     ref mut click_grid_position,
                 }
@@ -243,7 +254,7 @@ impl InteractionMode for TileMapInteractionMode {
                                     ..(selected_rect.position.x + selected_rect.size.x)
                                 {
                                     let position = Vector2::new(x, y);
-                                    if let Some(tile) = tile_map.tiles().get(&position) {
+                                    if let Some(tile) = tile_map.tiles().get(position) {
                                         brush.tiles.push(BrushTile {
                                             definition_handle: tile.definition_handle,
                                             local_position: position - selected_rect.position,
@@ -337,6 +348,13 @@ impl InteractionMode for TileMapInteractionMode {
                     DrawingMode::Erase => {
                         tile_map.tiles.erase(grid_coord, &brush);
                     }
+                    DrawingMode::Terrain { index, erase } => {
+                        if erase {
+                            tile_map.erase_terrain(grid_coord);
+                        } else {
+                            tile_map.draw_terrain(grid_coord, index);
+                        }
+                    }
                     _ => {
                         // Do nothing
                     }
@@ -393,7 +411,7 @@ impl InteractionMode for TileMapInteractionMode {
                     Color::RED,
                 );
             }
-            DrawingMode::FloodFill => {
+            DrawingMode::FloodFill | DrawingMode::Terrain { .. } => {
                 scene.drawing_context.draw_rectangle(
                     0.5,
                     0.5,
@@ -475,7 +493,7 @@ impl InteractionMode for TileMapInteractionMode {
                     tile_map.overlay_tiles.insert(tile);
                 }
             }
-            DrawingMode::Pick { .. } => {}
+            DrawingMode::Pick { .. } | DrawingMode::Terrain { .. } => {}
             DrawingMode::RectFill {
                 click_grid_position,
             } => {
@@ -579,7 +597,11 @@ impl InteractionMode for TileMapInteractionMode {
                     return true;
                 }
                 KeyCode::ShiftLeft => {
-                    self.drawing_mode = DrawingMode::Erase;
+                    if let DrawingMode::Terrain { ref mut erase, .. } = self.drawing_mode {
+                        *erase = true;
+                    } else {
+                        self.drawing_mode = DrawingMode::Erase;
+                    }
                     return true;
                 }
                 KeyCode::ControlLeft => {
@@ -613,6 +635,9 @@ impl InteractionMode for TileMapInteractionMode {
                     if matches!(self.drawing_mode, DrawingMode::Erase) {
                         self.drawing_mode = DrawingMode::Draw;
                         return true;
+                    } else if let DrawingMode::Terrain { ref mut erase, .. } = self.drawing_mode {
+                        *erase = false;
+                        return true;
                     }
                 }
                 KeyCode::ControlLeft => {
@@ -74,6 +74,7 @@ pub struct TileMapPanel {
     pub window: Handle<UiNode>,
     pub palette: Handle<UiNode>,
     active_brush_selector: Handle<UiNode>,
+    terrain_selector: Handle<UiNode>,
     edit: Handle<UiNode>,
     pub drawing_modes_panel: Handle<UiNode>,
     draw_button: Handle<UiNode>,
@@ -114,6 +115,21 @@ fn make_brush_entries(tile_map: &TileMap, ctx: &mut BuildContext) -> Vec<Handle<
         .collect::<Vec<_>>()
 }
 
+fn make_terrain_entries(tile_map: &TileMap, ctx: &mut BuildContext) -> Vec<Handle<UiNode>> {
+    tile_map
+        .tile_set()
+        .filter(|tile_set| tile_set.is_ok())
+        .map(|tile_set| {
+            tile_set
+                .data_ref()
+                .terrains
+                .iter()
+                .map(|terrain| make_dropdown_list_option(ctx, &terrain.name))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default()
+}
+
 fn selected_brush_index(tile_map: &TileMap) -> Option<usize> {
     tile_map
         .brushes()
@@ -185,6 +201,18 @@ impl TileMapPanel {
                 .with_items(make_brush_entries(tile_map, ctx))
                 .build(ctx);
 
+        let terrain_selector = DropdownListBuilder::new(
+            WidgetBuilder::new()
+                .with_width(250.0)
+                .with_height(20.0)
+                .with_tooltip(make_simple_tooltip(
+                    ctx,
+                    "Draw with a terrain. Edge and corner tiles are selected automatically.",
+                )),
+        )
+        .with_items(make_terrain_entries(tile_map, ctx))
+        .build(ctx);
+
         let edit = ButtonBuilder::new(WidgetBuilder::new().with_width(45.0).with_height(26.0))
             .with_text("Edit")
             .build(ctx);
@@ -267,7 +295,8 @@ impl TileMapPanel {
                 .on_row(0)
                 .with_child(edit)
                 .with_child(drawing_modes_panel)
-                .with_child(active_brush_selector),
+                .with_child(active_brush_selector)
+                .with_child(terrain_selector),
         )
         .with_orientation(Orientation::Horizontal)
         .build(ctx);
@@ -303,6 +332,7 @@ impl TileMapPanel {
             window,
             palette,
             active_brush_selector,
+            terrain_selector,
             edit,
             drawing_modes_panel,
             draw_button,
@@ -446,6 +476,19 @@ impl TileMapPanel {
                         ));
                     }
                 }
+            } else if message.destination() == self.terrain_selector
+                && message.direction() == MessageDirection::FromWidget
+            {
+                if let Some(interaction_mode) = editor_scene.and_then(|entry| {
+                    entry
+                        .interaction_modes
+                        .of_type_mut::<TileMapInteractionMode>()
+                }) {
+                    interaction_mode.drawing_mode = DrawingMode::Terrain {
+                        index: *index as u32,
+                        erase: false,
+                    };
+                }
             }
         } else if let Some(ButtonMessage::Click) = message.data() {
             if let Some(interaction_mode) = editor_scene.and_then(|entry| {
@@ -546,6 +589,9 @@ impl TileMapPanel {
                 DrawingMode::Line { .. } => {
                     highlight_all_except(self.line_button, &buttons, true, ui);
                 }
+                DrawingMode::Terrain { .. } => {
+                    highlight_all_except(Handle::NONE, &buttons, true, ui);
+                }
             }
         }
     }
@@ -558,6 +604,13 @@ impl TileMapPanel {
             items,
         ));
 
+        let items = make_terrain_entries(tile_map, &mut ui.build_ctx());
+        ui.send_message(DropdownListMessage::items(
+            self.terrain_selector,
+            MessageDirection::ToWidget,
+            items,
+        ));
+
         let Some(active_brush) = tile_map.active_brush() else {
             return;
         };
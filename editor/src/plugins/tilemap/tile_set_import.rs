@@ -212,6 +212,7 @@ impl TileSetImporter {
                         color: Default::default(),
                         position: Vector2::new(x as i32, y as i32),
                         properties: Default::default(),
+                        terrain: None,
                     });
                 }
             }
@@ -54,7 +54,10 @@ use crate::{
         },
         material::{Material, MaterialResource},
         resource::texture::Texture,
-        scene::tilemap::tileset::{TileDefinition, TileSet, TileSetResource},
+        scene::tilemap::{
+            autotile::Terrain,
+            tileset::{TileDefinition, TileSet, TileSetResource},
+        },
     },
     message::MessageSender,
     plugins::tilemap::{
@@ -72,6 +75,21 @@ use std::{
     sync::Arc,
 };
 
+/// A view of the terrains of a tile set. It is used to edit the terrains in the inspector, the
+/// field names must match the respective fields of [`TileSet`].
+#[derive(Debug, Reflect)]
+struct TerrainsView {
+    terrains: Vec<Terrain>,
+}
+
+impl TerrainsView {
+    fn new(tile_set: &TileSet) -> Self {
+        Self {
+            terrains: tile_set.terrains.clone(),
+        }
+    }
+}
+
 pub struct TileSetEditor {
     window: Handle<UiNode>,
     tiles: Handle<UiNode>,
@@ -79,7 +97,9 @@ pub struct TileSetEditor {
     import: Handle<UiNode>,
     remove: Handle<UiNode>,
     remove_all: Handle<UiNode>,
+    terrains: Handle<UiNode>,
     selection: Option<TileDefinitionHandle>,
+    inspect_terrains: bool,
     need_save: bool,
     tile_set_importer: Option<TileSetImporter>,
     inspector: Handle<UiNode>,
@@ -90,6 +110,7 @@ impl TileSetEditor {
         let import;
         let remove;
         let remove_all;
+        let terrains;
         let buttons = StackPanelBuilder::new(
             WidgetBuilder::new()
                 .on_row(0)
@@ -109,6 +130,15 @@ impl TileSetEditor {
                 .with_child({
                     remove_all = make_button("Remove All", "Remove all tiles.", true, ctx);
                     remove_all
+                })
+                .with_child({
+                    terrains = make_button(
+                        "Terrains",
+                        "Edit terrains (auto-tiling rules) of the tile set.",
+                        true,
+                        ctx,
+                    );
+                    terrains
                 }),
         )
         .with_orientation(Orientation::Horizontal)
@@ -179,7 +209,9 @@ impl TileSetEditor {
             import,
             remove,
             remove_all,
+            terrains,
             selection: Default::default(),
+            inspect_terrains: false,
             need_save: false,
             tile_set_importer: None,
             inspector,
@@ -271,6 +303,25 @@ impl TileSetEditor {
                     }
                 }
             }
+        } else if self.inspect_terrains {
+            let ctx = ui
+                .node(self.inspector)
+                .cast::<Inspector>()
+                .unwrap()
+                .context()
+                .clone();
+
+            if let Err(sync_errors) = ctx.sync(
+                &TerrainsView::new(&tile_set),
+                ui,
+                0,
+                true,
+                Default::default(),
+            ) {
+                for error in sync_errors {
+                    Log::err(format!("Failed to sync property. Reason: {error:?}"))
+                }
+            }
         }
     }
 
@@ -337,6 +388,7 @@ impl TileSetEditor {
                                 color: Default::default(),
                                 position,
                                 properties: Default::default(),
+                                terrain: None,
                             }),
                             handle: Default::default(),
                         });
@@ -356,6 +408,7 @@ impl TileSetEditor {
                                 color: Default::default(),
                                 position,
                                 properties: Default::default(),
+                                terrain: None,
                             }),
                             handle: Default::default(),
                         });
@@ -375,6 +428,45 @@ impl TileSetEditor {
                     });
                     self.need_save = true;
                 }
+            } else if message.destination() == self.terrains {
+                self.selection = None;
+                self.inspect_terrains = true;
+
+                ui.send_message(ListViewMessage::selection(
+                    self.tiles,
+                    MessageDirection::ToWidget,
+                    vec![],
+                ));
+                ui.send_message(WidgetMessage::visibility(
+                    self.inspector,
+                    MessageDirection::ToWidget,
+                    true,
+                ));
+
+                let env = Arc::new(EditorEnvironment {
+                    resource_manager: resource_manager.clone(),
+                    serialization_context,
+                    available_animations: Default::default(),
+                    sender: sender.clone(),
+                });
+
+                let context = InspectorContext::from_object(
+                    &TerrainsView::new(&self.tile_set.data_ref()),
+                    &mut ui.build_ctx(),
+                    property_editors,
+                    Some(env),
+                    1,
+                    0,
+                    true,
+                    Default::default(),
+                    80.0,
+                );
+
+                ui.send_message(InspectorMessage::context(
+                    self.inspector,
+                    MessageDirection::ToWidget,
+                    context,
+                ));
             } else if message.destination() == self.remove_all {
                 let mut commands = Vec::new();
 
@@ -403,6 +495,9 @@ impl TileSetEditor {
                     .map(|view| view.definition_handle);
 
                 self.selection = selection;
+                if self.selection.is_some() {
+                    self.inspect_terrains = false;
+                }
 
                 ui.send_message(WidgetMessage::enabled(
                     self.remove,
@@ -413,7 +508,7 @@ impl TileSetEditor {
                 ui.send_message(WidgetMessage::visibility(
                     self.inspector,
                     MessageDirection::ToWidget,
-                    self.selection.is_some(),
+                    self.selection.is_some() || self.inspect_terrains,
                 ));
 
                 if let Some(selection) = selection {
@@ -469,11 +564,29 @@ impl TileSetEditor {
                                     &mut *tile_set.data_ref(),
                                 )
                             };
+                            tile_set.mark_modified();
 
                             &mut tile_set.tiles[selection]
                         })
                         .unwrap(),
                     ));
+                } else if self.inspect_terrains {
+                    let tile_set = self.tile_set.clone();
+                    sender.send(Message::DoCommand(
+                        make_command(args, move |_| {
+                            // FIXME: HACK!
+                            let tile_set = unsafe {
+                                std::mem::transmute::<&'_ mut TileSet, &'static mut TileSet>(
+                                    &mut *tile_set.data_ref(),
+                                )
+                            };
+                            tile_set.mark_modified();
+
+                            tile_set
+                        })
+                        .unwrap(),
+                    ));
+                    self.need_save = true;
                 }
             }
         }
@@ -341,3 +341,43 @@ impl CommandTrait for MoveNavmeshVertexCommand {
         self.set_position(fetch_navmesh(context, self.navmesh_node), position);
     }
 }
+
+#[derive(Debug)]
+pub struct GenerateNavmeshCommand {
+    navmesh_node: Handle<Node>,
+    navmesh: Option<Navmesh>,
+}
+
+impl GenerateNavmeshCommand {
+    pub fn new(navmesh_node: Handle<Node>) -> Self {
+        Self {
+            navmesh_node,
+            navmesh: None,
+        }
+    }
+
+    fn swap(&mut self, context: &mut GameSceneContext) {
+        let navmesh = match self.navmesh.take() {
+            Some(navmesh) => navmesh,
+            None => context.scene.graph[self.navmesh_node]
+                .as_navigational_mesh()
+                .generate_navmesh(&context.scene.graph),
+        };
+        let mut current = fetch_navmesh(context, self.navmesh_node);
+        self.navmesh = Some(std::mem::replace(&mut *current, navmesh));
+    }
+}
+
+impl CommandTrait for GenerateNavmeshCommand {
+    fn name(&mut self, _context: &dyn CommandContext) -> String {
+        "Generate Navmesh".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut dyn CommandContext) {
+        self.swap(context.get_mut::<GameSceneContext>());
+    }
+
+    fn revert(&mut self, context: &mut dyn CommandContext) {
+        self.swap(context.get_mut::<GameSceneContext>());
+    }
+}
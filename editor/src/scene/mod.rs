@@ -270,7 +270,7 @@ impl GameScene {
         let mut visitor = Visitor::new();
         pure_scene.save("Scene", &mut visitor).unwrap();
 
-        if let Err(e) = visitor.save_binary(path) {
+        if let Err(e) = visitor.save_to_file(path, settings.general.scene_format(path)) {
             Err(format!("Failed to save scene! Reason: {e}"))
         } else {
             if settings.debugging.save_scene_in_text_form {
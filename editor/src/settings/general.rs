@@ -18,8 +18,11 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use crate::fyrox::core::{reflect::prelude::*, type_traits::prelude::*, uuid_provider};
+use crate::fyrox::core::{
+    reflect::prelude::*, type_traits::prelude::*, uuid_provider, visitor::VisitorFormat,
+};
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 use strum_macros::{AsRefStr, EnumString, VariantNames};
 
 #[derive(
@@ -87,6 +90,26 @@ pub struct GeneralSettings {
 
     #[serde(default = "default_style")]
     pub style: EditorStyle,
+
+    #[reflect(
+        description = "Saves new scenes in human-readable text form instead of binary. \
+    Text form is slower to load, but it can be diffed and merged by version control systems. Existing \
+    files always keep the format they were saved in."
+    )]
+    #[serde(default)]
+    pub save_new_scenes_in_text_form: bool,
+}
+
+impl GeneralSettings {
+    /// Returns a format that should be used to save a scene to the given path. Existing files keep
+    /// their format, new files use the format specified in the settings.
+    pub fn scene_format(&self, path: &Path) -> VisitorFormat {
+        VisitorFormat::of_file(path).unwrap_or(if self.save_new_scenes_in_text_form {
+            VisitorFormat::Text
+        } else {
+            VisitorFormat::Binary
+        })
+    }
 }
 
 fn default_style() -> EditorStyle {
@@ -149,6 +172,7 @@ impl Default for GeneralSettings {
             generate_previews: default_generate_previews(),
             max_log_entries: default_max_log_entries(),
             style: EditorStyle::Dark,
+            save_new_scenes_in_text_form: false,
         }
     }
 }
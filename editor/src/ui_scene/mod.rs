@@ -271,7 +271,10 @@ impl SceneController for UiScene {
         settings: &Settings,
         _engine: &mut Engine,
     ) -> Result<String, String> {
-        match self.ui.save(path) {
+        match self
+            .ui
+            .save_with_format(path, settings.general.scene_format(path))
+        {
             Ok(visitor) => {
                 if settings.debugging.save_scene_in_text_form {
                     let text = visitor.save_text();
@@ -20,7 +20,7 @@
 
 use crate::fyrox::graph::BaseSceneGraph;
 use crate::fyrox::{
-    core::{algebra::Vector2, pool::ErasedHandle, pool::Handle, visitor::Visitor},
+    core::{algebra::Vector2, pool::ErasedHandle, pool::Handle, visitor::VisitorFormat},
     gui::{
         file_browser::{FileBrowserMode, FileSelectorBuilder, Filter},
         message::MessageDirection,
@@ -29,7 +29,7 @@ use crate::fyrox::{
         BuildContext, UiNode, UserInterface,
     },
 };
-use std::{fs::File, io::Read, path::Path};
+use std::path::Path;
 
 pub mod doc;
 
@@ -143,13 +143,7 @@ where
 }
 
 pub fn is_native_scene(path: &Path) -> bool {
-    if let Ok(mut file) = File::open(path) {
-        let mut magic: [u8; 4] = Default::default();
-        if file.read_exact(&mut magic).is_ok() {
-            return magic.eq(Visitor::MAGIC.as_bytes());
-        }
-    }
-    false
+    VisitorFormat::of_file(path).is_some()
 }
 
 #[cfg(test)]
@@ -0,0 +1,471 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Lightweight CPU profiler. Use [`crate::scope_profile`] macro to measure time spent in a scope.
+//! Measurements are collected per thread with minimal synchronization and then gathered once per
+//! frame by [`end_frame`], which returns a per-frame summary. Collected measurements could be
+//! exported in Chrome trace format (see [`write_chrome_trace`]) and viewed in `chrome://tracing`,
+//! [Perfetto](https://ui.perfetto.dev) or any other compatible viewer.
+//!
+//! The instrumentation macro is compiled only when `enable_profiler` feature is active, so it has
+//! zero cost otherwise. The rest of the API is always available, which allows you to record custom
+//! spans via [`record_span`] even without the feature (recording must be enabled via
+//! [`set_recording`] in this case).
+
+use crate::{instant::Instant, parking_lot::Mutex};
+use fxhash::FxHashMap;
+use lazy_static::lazy_static;
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    fmt::{Display, Formatter},
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Default maximum amount of events, that are kept in the trace history.
+pub const DEFAULT_HISTORY_CAPACITY: usize = 1 << 18;
+
+/// A single measured span of time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProfileEvent {
+    /// Name of the span.
+    pub name: &'static str,
+    /// Optional details of the span (for example - a path of a resource).
+    pub detail: Option<String>,
+    /// An id of the thread, that has recorded the event.
+    pub thread: u64,
+    /// Start time of the span relative to the profiler initialization time.
+    pub start: Duration,
+    /// Duration of the span.
+    pub duration: Duration,
+}
+
+/// Aggregated statistics of a scope within a frame.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ScopeSummary {
+    /// Name of the scope.
+    pub name: &'static str,
+    /// Amount of times the scope was entered.
+    pub calls: u32,
+    /// Total time spent in the scope (including nested scopes).
+    pub total: Duration,
+    /// Maximum time of a single call.
+    pub max: Duration,
+}
+
+/// Summary of a frame, produced by [`end_frame`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FrameSummary {
+    /// Time between the previous and the current call of [`end_frame`].
+    pub frame_time: Duration,
+    /// Statistics of every scope, that was entered during the frame (on any thread), sorted by
+    /// total time in descending order.
+    pub scopes: Vec<ScopeSummary>,
+}
+
+impl FrameSummary {
+    /// Tries to find a summary of a scope with the given name.
+    pub fn scope(&self, name: &str) -> Option<&ScopeSummary> {
+        self.scopes.iter().find(|s| s.name == name)
+    }
+}
+
+impl Display for FrameSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Profiler (frame time {:?}):", self.frame_time)?;
+        for scope in self.scopes.iter() {
+            writeln!(
+                f,
+                "\t{}: {:?} ({} calls, max {:?})",
+                scope.name, scope.total, scope.calls, scope.max
+            )?;
+        }
+        Ok(())
+    }
+}
+
+struct ThreadBuffer {
+    id: u64,
+    name: String,
+    events: Vec<ProfileEvent>,
+}
+
+struct Registry {
+    threads: Vec<Arc<Mutex<ThreadBuffer>>>,
+    history: VecDeque<ProfileEvent>,
+    history_capacity: usize,
+    frames: VecDeque<Duration>,
+    last_frame_end: Option<Instant>,
+}
+
+lazy_static! {
+    static ref ORIGIN: Instant = Instant::now();
+    static ref REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+        threads: Default::default(),
+        history: Default::default(),
+        history_capacity: DEFAULT_HISTORY_CAPACITY,
+        frames: Default::default(),
+        last_frame_end: None,
+    });
+}
+
+static RECORDING: AtomicBool = AtomicBool::new(cfg!(feature = "enable_profiler"));
+static NEXT_THREAD_ID: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    static THREAD_BUFFER: RefCell<Option<Arc<Mutex<ThreadBuffer>>>> = const { RefCell::new(None) };
+}
+
+fn with_thread_buffer<F: FnOnce(&mut ThreadBuffer)>(func: F) {
+    THREAD_BUFFER.with(|cell| {
+        let mut cell = cell.borrow_mut();
+        let buffer = cell.get_or_insert_with(|| {
+            let current = std::thread::current();
+            let id = NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed);
+            let buffer = Arc::new(Mutex::new(ThreadBuffer {
+                id,
+                name: current
+                    .name()
+                    .map(|n| n.to_string())
+                    .unwrap_or_else(|| format!("Thread {id}")),
+                events: Default::default(),
+            }));
+            REGISTRY.lock().threads.push(buffer.clone());
+            buffer
+        });
+        // The lock is uncontended most of the time, it is locked by other threads only when a
+        // frame ends.
+        func(&mut buffer.lock());
+    })
+}
+
+/// Enables or disables recording of spans. Recording is enabled by default if `enable_profiler`
+/// feature is active.
+pub fn set_recording(recording: bool) {
+    RECORDING.store(recording, Ordering::Relaxed);
+}
+
+/// Returns `true` if the profiler records spans.
+pub fn is_recording() -> bool {
+    RECORDING.load(Ordering::Relaxed)
+}
+
+/// Sets maximum amount of events, that are kept in the trace history. Oldest events are discarded
+/// first.
+pub fn set_history_capacity(capacity: usize) {
+    let mut registry = REGISTRY.lock();
+    registry.history_capacity = capacity;
+    while registry.history.len() > capacity {
+        registry.history.pop_front();
+    }
+}
+
+/// Records a span of time with the given name. Prefer [`crate::scope_profile`] macro to measure
+/// time spent in a scope, this method is useful to measure something, that cannot be expressed as
+/// a scope (asynchronous tasks, for example).
+pub fn record_span(name: &'static str, detail: Option<String>, start: Instant, end: Instant) {
+    if !is_recording() {
+        return;
+    }
+
+    let origin = *ORIGIN;
+    with_thread_buffer(|buffer| {
+        let thread = buffer.id;
+        buffer.events.push(ProfileEvent {
+            name,
+            detail,
+            thread,
+            start: start.saturating_duration_since(origin),
+            duration: end.saturating_duration_since(start),
+        })
+    });
+}
+
+/// A guard, that measures time between its creation and destruction. Usually it is created by
+/// [`crate::scope_profile`] macro.
+pub struct ScopeGuard {
+    name: &'static str,
+    start: Instant,
+}
+
+impl ScopeGuard {
+    /// Starts measuring a scope with the given name.
+    #[inline]
+    pub fn new(name: &'static str) -> Self {
+        // Make sure that the origin is initialized before the first measurement.
+        lazy_static::initialize(&ORIGIN);
+        Self {
+            name,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Drop for ScopeGuard {
+    #[inline]
+    fn drop(&mut self) {
+        record_span(self.name, None, self.start, Instant::now());
+    }
+}
+
+/// Gathers spans recorded by every thread since the previous call, moves them to the trace
+/// history and returns a summary of the frame. Usually it is called by the engine once per frame.
+pub fn end_frame() -> FrameSummary {
+    let now = Instant::now();
+    let mut registry = REGISTRY.lock();
+
+    let frame_time = registry
+        .last_frame_end
+        .map(|last| now.saturating_duration_since(last))
+        .unwrap_or_default();
+    registry.last_frame_end = Some(now);
+    let frame_end = now.saturating_duration_since(*ORIGIN);
+    registry.frames.push_back(frame_end);
+
+    let mut scopes = FxHashMap::<&'static str, ScopeSummary>::default();
+    let threads = registry.threads.clone();
+    for thread in threads.iter() {
+        let events = std::mem::take(&mut thread.lock().events);
+        for event in events {
+            let scope = scopes.entry(event.name).or_insert_with(|| ScopeSummary {
+                name: event.name,
+                ..Default::default()
+            });
+            scope.calls += 1;
+            scope.total += event.duration;
+            scope.max = scope.max.max(event.duration);
+
+            registry.history.push_back(event);
+        }
+    }
+
+    while registry.history.len() > registry.history_capacity {
+        registry.history.pop_front();
+    }
+    let oldest = registry
+        .history
+        .front()
+        .map(|e| e.start)
+        .unwrap_or(frame_end);
+    while registry.frames.front().is_some_and(|f| *f < oldest) {
+        registry.frames.pop_front();
+    }
+
+    let mut scopes = scopes.into_values().collect::<Vec<_>>();
+    scopes.sort_by(|a, b| b.total.cmp(&a.total).then(a.name.cmp(b.name)));
+
+    FrameSummary { frame_time, scopes }
+}
+
+/// Removes every event from the trace history.
+pub fn clear() {
+    let mut registry = REGISTRY.lock();
+    registry.history.clear();
+    registry.frames.clear();
+}
+
+/// Returns a copy of the trace history.
+pub fn history() -> Vec<ProfileEvent> {
+    REGISTRY.lock().history.iter().cloned().collect()
+}
+
+fn write_json_string<W: Write>(writer: &mut W, string: &str) -> io::Result<()> {
+    write!(writer, "\"")?;
+    for c in string.chars() {
+        match c {
+            '"' => write!(writer, "\\\"")?,
+            '\\' => write!(writer, "\\\\")?,
+            c if (c as u32) < 0x20 => write!(writer, "\\u{:04x}", c as u32)?,
+            c => write!(writer, "{c}")?,
+        }
+    }
+    write!(writer, "\"")
+}
+
+/// Writes the trace history in Chrome trace event format (JSON). Spans, that were recorded after
+/// the last call of [`end_frame`] are not included.
+pub fn write_chrome_trace<W: Write>(mut writer: W) -> io::Result<()> {
+    let registry = REGISTRY.lock();
+
+    write!(writer, "{{\"traceEvents\":[")?;
+    let mut first = true;
+    let mut separator = |writer: &mut W| {
+        if std::mem::take(&mut first) {
+            Ok(())
+        } else {
+            write!(writer, ",")
+        }
+    };
+
+    for thread in registry.threads.iter() {
+        let thread = thread.lock();
+        separator(&mut writer)?;
+        write!(
+            writer,
+            "{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":1,\"tid\":{},\"args\":{{\"name\":",
+            thread.id
+        )?;
+        write_json_string(&mut writer, &thread.name)?;
+        write!(writer, "}}}}")?;
+    }
+
+    for frame in registry.frames.iter() {
+        separator(&mut writer)?;
+        write!(
+            writer,
+            "{{\"name\":\"Frame\",\"ph\":\"i\",\"s\":\"g\",\"pid\":1,\"tid\":0,\"ts\":{:.3}}}",
+            frame.as_secs_f64() * 1_000_000.0
+        )?;
+    }
+
+    for event in registry.history.iter() {
+        separator(&mut writer)?;
+        write!(writer, "{{\"name\":")?;
+        write_json_string(&mut writer, event.name)?;
+        write!(
+            writer,
+            ",\"cat\":\"fyrox\",\"ph\":\"X\",\"pid\":1,\"tid\":{},\"ts\":{:.3},\"dur\":{:.3}",
+            event.thread,
+            event.start.as_secs_f64() * 1_000_000.0,
+            event.duration.as_secs_f64() * 1_000_000.0
+        )?;
+        if let Some(detail) = event.detail.as_ref() {
+            write!(writer, ",\"args\":{{\"detail\":")?;
+            write_json_string(&mut writer, detail)?;
+            write!(writer, "}}")?;
+        }
+        write!(writer, "}}")?;
+    }
+
+    write!(writer, "]}}")?;
+    writer.flush()
+}
+
+/// Saves the trace history in Chrome trace event format to the given file. See
+/// [`write_chrome_trace`] for more info.
+pub fn save_chrome_trace<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    write_chrome_trace(BufWriter::new(File::create(path)?))
+}
+
+/// Measures time spent in the current scope (until the end of the enclosing block) and records it
+/// with the given name. The macro expands to nothing if `enable_profiler` feature is not active.
+///
+/// ```rust
+/// use fyrox_core::scope_profile;
+///
+/// fn update() {
+///     scope_profile!("update");
+///
+///     // Do something.
+/// }
+/// ```
+#[cfg(feature = "enable_profiler")]
+#[macro_export]
+macro_rules! scope_profile {
+    ($name:expr) => {
+        let _profiler_scope_guard = $crate::profiler::ScopeGuard::new($name);
+    };
+}
+
+/// Measures time spent in the current scope (until the end of the enclosing block) and records it
+/// with the given name. The macro expands to nothing if `enable_profiler` feature is not active.
+///
+/// ```rust
+/// use fyrox_core::scope_profile;
+///
+/// fn update() {
+///     scope_profile!("update");
+///
+///     // Do something.
+/// }
+/// ```
+#[cfg(not(feature = "enable_profiler"))]
+#[macro_export]
+macro_rules! scope_profile {
+    ($name:expr) => {};
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        instant::Instant,
+        profiler::{self, ScopeGuard},
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn test_profiler() {
+        profiler::set_recording(true);
+        profiler::clear();
+        profiler::end_frame();
+
+        {
+            let _guard = ScopeGuard::new("outer \"scope\"");
+            for _ in 0..3 {
+                let _guard = ScopeGuard::new("inner");
+            }
+        }
+        let start = Instant::now();
+        profiler::record_span(
+            "custom",
+            Some("path/to/resource".to_string()),
+            start,
+            start + Duration::from_millis(2),
+        );
+        std::thread::spawn(|| {
+            let _guard = ScopeGuard::new("worker");
+        })
+        .join()
+        .unwrap();
+
+        let summary = profiler::end_frame();
+        assert_eq!(summary.scope("inner").unwrap().calls, 3);
+        assert_eq!(summary.scope("outer \"scope\"").unwrap().calls, 1);
+        assert_eq!(summary.scope("worker").unwrap().calls, 1);
+        let custom = summary.scope("custom").unwrap();
+        assert_eq!(custom.total, Duration::from_millis(2));
+        assert_eq!(custom.max, Duration::from_millis(2));
+
+        // Everything was moved to the history.
+        assert!(profiler::end_frame().scope("inner").is_none());
+        assert!(profiler::history().len() >= 6);
+
+        let mut trace = Vec::new();
+        profiler::write_chrome_trace(&mut trace).unwrap();
+        let trace = String::from_utf8(trace).unwrap();
+        assert!(trace.starts_with("{\"traceEvents\":["));
+        assert!(trace.ends_with("]}"));
+        assert!(trace.contains("\"name\":\"outer \\\"scope\\\"\""));
+        assert!(trace.contains("\"args\":{\"detail\":\"path/to/resource\"}"));
+        assert!(trace.contains("\"ph\":\"M\""));
+        assert!(trace.contains("\"name\":\"Frame\""));
+
+        profiler::set_history_capacity(2);
+        assert_eq!(profiler::history().len(), 2);
+        profiler::set_history_capacity(profiler::DEFAULT_HISTORY_CAPACITY);
+    }
+}
@@ -35,6 +35,8 @@ pub mod prelude {
     pub use super::{Visit, VisitError, VisitResult, Visitor};
 }
 
+mod text;
+
 use crate::{
     algebra::{
         Complex, Const, Matrix, Matrix2, Matrix3, Matrix4, Quaternion, RawStorage, RawStorageMut,
@@ -643,6 +645,13 @@ pub enum VisitError {
     PoisonedMutex,
     /// A FileLoadError was encountered while trying to decode Visitor data from a file.
     FileLoadError(FileLoadError),
+    /// Visitor data in text form is malformed. See [Visitor::load_text] for more info.
+    InvalidText {
+        /// Line number (starting from 1) at which the error was found.
+        line: usize,
+        /// Description of the error.
+        message: String,
+    },
 }
 
 impl Error for VisitError {}
@@ -667,6 +676,9 @@ impl Display for VisitError {
             Self::UnexpectedRcNullIndex => write!(f, "unexpected rc null index"),
             Self::PoisonedMutex => write!(f, "attempt to lock poisoned mutex"),
             Self::FileLoadError(e) => write!(f, "file load error: {e:?}"),
+            Self::InvalidText { line, message } => {
+                write!(f, "invalid text data at line {line}: {message}")
+            }
         }
     }
 }
@@ -1230,6 +1242,46 @@ bitflags! {
     }
 }
 
+/// Encoding of the [Visitor] data. Both formats store exactly the same information, so
+/// the format can be chosen for every file separately. [Visitor::load_from_memory] and
+/// [Visitor::load_binary] detect the format automatically.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum VisitorFormat {
+    /// Compact, fast to read and write, non-human-readable format. See [Visitor::save_binary].
+    #[default]
+    Binary,
+    /// Human-readable format, that is suitable for version control systems - it can be
+    /// diffed and merged as any other text file. See [Visitor::save_text].
+    Text,
+}
+
+impl VisitorFormat {
+    /// Tries to detect the format of the given visitor data by its header. Returns `None`
+    /// if the data is neither in binary nor in text form.
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        if data.starts_with(Visitor::TEXT_MAGIC.as_bytes()) {
+            Some(Self::Text)
+        } else if data.starts_with(Visitor::MAGIC.as_bytes()) {
+            Some(Self::Binary)
+        } else {
+            None
+        }
+    }
+
+    /// Tries to detect the format of the visitor data stored in the file at the given path.
+    /// Returns `None` if there is no such file, or it does not contain visitor data. This
+    /// method could be used to preserve the format of an existing file when overwriting it.
+    pub fn of_file<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let mut header = Vec::with_capacity(Visitor::TEXT_MAGIC.len());
+        File::open(path)
+            .ok()?
+            .take(Visitor::TEXT_MAGIC.len() as u64)
+            .read_to_end(&mut header)
+            .ok()?;
+        Self::detect(&header)
+    }
+}
+
 /// A collection of nodes that stores data that can be read or write values of types with the [Visit] trait.
 ///
 /// Instead of calling methods of the visitor in order to read or write the visitor's data, reading
@@ -1247,6 +1299,7 @@ pub struct Visitor {
     nodes: Pool<VisitorNode>,
     rc_map: FxHashMap<u64, Rc<dyn Any>>,
     arc_map: FxHashMap<u64, Arc<dyn Any + Send + Sync>>,
+    shared_ids: FxHashMap<usize, u64>,
     reading: bool,
     current_node: Handle<VisitorNode>,
     root: Handle<VisitorNode>,
@@ -1306,6 +1359,17 @@ impl Default for Visitor {
     }
 }
 
+impl std::fmt::Debug for Visitor {
+    /// Prints all the data of this Visitor in a compact form with each node on its own line
+    /// and tabs to indent child nodes. Use [Visitor::save_text] to get a text that could be
+    /// loaded back.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut out_string = String::new();
+        self.print_node(self.root, 0, &mut out_string);
+        f.write_str(&out_string)
+    }
+}
+
 impl Visitor {
     /// Sequence of bytes that is automatically written at the start when a visitor
     /// is encoded into bytes. It is written by [Visitor::save_binary], [Visitor::save_binary_to_memory],
@@ -1316,6 +1380,10 @@ impl Visitor {
     /// of the given slice.
     pub const MAGIC: &'static str = "RG3D";
 
+    /// Sequence of bytes that is written at the start of the visitor data in text form. It is written
+    /// by [Visitor::save_text] and [Visitor::save_text_to_file].
+    pub const TEXT_MAGIC: &'static str = "FyroxVisitorText";
+
     /// Creates a Visitor containing only a single node called "`__ROOT__`" which will be the
     /// current region of the visitor.
     pub fn new() -> Self {
@@ -1325,6 +1393,7 @@ impl Visitor {
             nodes,
             rc_map: FxHashMap::default(),
             arc_map: FxHashMap::default(),
+            shared_ids: FxHashMap::default(),
             reading: false,
             current_node: root,
             root,
@@ -1418,6 +1487,17 @@ impl Visitor {
         }
     }
 
+    /// Returns an id of a shared value (such as the content of [Rc] or [Arc]) that is being written.
+    /// Ids are given sequentially in the order of visiting, instead of using raw pointers, so the
+    /// output does not change between runs.
+    fn shared_value_id<T>(&mut self, ptr: *const T) -> u64 {
+        let next_id = self.shared_ids.len() as u64 + 1;
+        *self
+            .shared_ids
+            .entry(ptr as *const u8 as usize)
+            .or_insert(next_id)
+    }
+
     fn print_node(
         &self,
         node_handle: Handle<VisitorNode>,
@@ -1445,13 +1525,33 @@ impl Visitor {
         }
     }
 
-    /// Create a String containing all the data of this Visitor.
-    /// The String is formatted to be human-readable with each node on its own line
-    /// and tabs to indent child nodes.
+    /// Create a String containing all the data of this Visitor, so that the data can be
+    /// reconstructed using [Visitor::load_text].
+    /// The String is formatted to be human-readable and diff-friendly with each region and
+    /// each field on its own line and spaces to indent child nodes. The order of regions and
+    /// fields is the same as the order in which they were visited, so saving the same data
+    /// twice produces the same text.
+    /// The result starts with [Visitor::TEXT_MAGIC], so [Visitor::load_from_memory] and
+    /// [Visitor::load_binary] are able to tell it apart from the binary form.
     pub fn save_text(&self) -> String {
-        let mut out_string = String::new();
-        self.print_node(self.root, 0, &mut out_string);
-        out_string
+        text::write(self)
+    }
+
+    /// Create a file at the given path and write the data of this visitor in
+    /// text form (see [Visitor::save_text]) into it.
+    pub fn save_text_to_file<P: AsRef<Path>>(&self, path: P) -> VisitResult {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(self.save_text().as_bytes())?;
+        Ok(())
+    }
+
+    /// Create a file at the given path and write the data of this visitor
+    /// into it using the specified format.
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P, format: VisitorFormat) -> VisitResult {
+        match format {
+            VisitorFormat::Binary => self.save_binary(path),
+            VisitorFormat::Text => self.save_text_to_file(path),
+        }
     }
 
     /// Write the data of this Visitor to the given writer.
@@ -1528,17 +1628,40 @@ impl Visitor {
     }
 
     /// Create a visitor by reading data from the file at the given path,
-    /// assuming that the file was created using [Visitor::save_binary].
-    /// Return a [VisitError::NotSupportedFormat] if [Visitor::MAGIC] is not the first bytes read from the file.
+    /// assuming that the file was created using [Visitor::save_binary] or [Visitor::save_text_to_file].
+    /// Return a [VisitError::NotSupportedFormat] if neither [Visitor::MAGIC] nor [Visitor::TEXT_MAGIC]
+    /// are the first bytes read from the file.
     pub async fn load_binary<P: AsRef<Path>>(path: P) -> Result<Self, VisitError> {
         Self::load_from_memory(&io::load_file(path).await?)
     }
 
+    /// Create a visitor by decoding data from the given string, assuming that the string
+    /// is in the format that would be produced by [Visitor::save_text].
+    /// Return a [VisitError::NotSupportedFormat] if [Visitor::TEXT_MAGIC] is not at the beginning
+    /// of the string, or [VisitError::InvalidText] if the text is malformed.
+    pub fn load_text(text: &str) -> Result<Self, VisitError> {
+        text::read(text)
+    }
+
     /// Create a visitor by decoding data from the given byte slice,
     /// assuming that the bytes are in the format that would be produced
-    /// by [Visitor::save_binary_to_vec].
-    /// Return a [VisitError::NotSupportedFormat] if [Visitor::MAGIC] is not the first bytes read from the slice.
+    /// by [Visitor::save_binary_to_vec] or [Visitor::save_text]. The format is detected
+    /// automatically, see [VisitorFormat::detect].
+    /// Return a [VisitError::NotSupportedFormat] if neither [Visitor::MAGIC] nor [Visitor::TEXT_MAGIC]
+    /// are the first bytes read from the slice.
     pub fn load_from_memory(data: &[u8]) -> Result<Self, VisitError> {
+        if VisitorFormat::detect(data) == Some(VisitorFormat::Text) {
+            let text = std::str::from_utf8(data).map_err(|err| VisitError::InvalidText {
+                line: data[..err.valid_up_to()]
+                    .iter()
+                    .filter(|b| **b == b'\n')
+                    .count()
+                    + 1,
+                message: format!("text is not valid UTF-8: {err}"),
+            })?;
+            return Self::load_text(text);
+        }
+
         let mut reader = Cursor::new(data);
         let mut magic: [u8; 4] = Default::default();
         reader.read_exact(&mut magic)?;
@@ -1549,6 +1672,7 @@ impl Visitor {
             nodes: Pool::new(),
             rc_map: Default::default(),
             arc_map: Default::default(),
+            shared_ids: Default::default(),
             reading: true,
             current_node: Handle::NONE,
             root: Handle::NONE,
@@ -1732,7 +1856,7 @@ where
             let raw = rc_to_raw(self);
 
             // Save it as id.
-            let mut index = raw as u64;
+            let mut index = region.shared_value_id(raw);
             index.visit("Id", &mut region)?;
 
             if let Entry::Vacant(entry) = region.rc_map.entry(index) {
@@ -1820,7 +1944,7 @@ where
             let raw = arc_to_raw(self);
 
             // Save it as id.
-            let mut index = raw as u64;
+            let mut index = region.shared_value_id(raw);
             index.visit("Id", &mut region)?;
 
             if let Entry::Vacant(entry) = region.arc_map.entry(index) {
@@ -1867,7 +1991,7 @@ where
             let raw = rc_to_raw(&rc);
 
             // Save it as id.
-            let mut index = raw as u64;
+            let mut index = region.shared_value_id(raw);
             index.visit("Id", &mut region)?;
 
             if let Entry::Vacant(entry) = region.rc_map.entry(index) {
@@ -1917,7 +2041,7 @@ where
             let raw = arc_to_raw(&arc);
 
             // Save it as id.
-            let mut index = raw as u64;
+            let mut index = region.shared_value_id(raw);
             index.visit("Id", &mut region)?;
 
             if let Entry::Vacant(entry) = region.arc_map.entry(index) {
@@ -2218,6 +2342,120 @@ mod test {
         }
     }
 
+    #[derive(Visit, Default, PartialEq, Debug)]
+    struct TextRoundTrip {
+        flag: bool,
+        int: i64,
+        float: f32,
+        double: f64,
+        name: String,
+        position: Vector3<f32>,
+        rotation: UnitQuaternion<f32>,
+        transform: Matrix4<f32>,
+        id: Uuid,
+        bytes: Vec<u8>,
+        items: Vec<u32>,
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let mut original = TextRoundTrip {
+            flag: true,
+            int: -1234567890123,
+            float: 0.1,
+            double: -1.0e-300,
+            name: "A \"quoted\" name\nwith\tescapes\\ and unicode ✓\u{1}".to_string(),
+            position: Vector3::new(1.5, f32::MAX, -0.0),
+            rotation: UnitQuaternion::from_euler_angles(0.1, 0.2, 0.3),
+            transform: Matrix4::new(
+                1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0, 13.0, 14.0, 15.0,
+                16.0,
+            ),
+            id: Uuid::new_v4(),
+            bytes: vec![0, 159, 146, 150, 255],
+            items: vec![1, 2, 3],
+        };
+        let mut shared = Rc::new(Resource::new(ResourceKind::Model(Model { data: 555 })));
+        let mut objects = vec![Foo::new(shared.clone()), Foo::new(shared.clone())];
+
+        let mut visitor = Visitor::new();
+        original.visit("Data", &mut visitor).unwrap();
+        shared.visit("SharedResource", &mut visitor).unwrap();
+        objects.visit("Objects", &mut visitor).unwrap();
+        let text = visitor.save_text();
+
+        let mut visitor = Visitor::load_text(&text).unwrap();
+        // Saving loaded data must produce exactly the same text.
+        assert_eq!(visitor.save_text(), text);
+
+        let mut loaded = TextRoundTrip::default();
+        loaded.visit("Data", &mut visitor).unwrap();
+        assert_eq!(loaded, original);
+
+        let mut shared: Rc<Resource> = Rc::new(Default::default());
+        shared.visit("SharedResource", &mut visitor).unwrap();
+        let mut objects: Vec<Foo> = Vec::new();
+        objects.visit("Objects", &mut visitor).unwrap();
+        assert_eq!(objects.len(), 2);
+        for object in objects {
+            assert!(Rc::ptr_eq(
+                object.shared_resource.as_ref().unwrap(),
+                &shared
+            ));
+        }
+
+        // Format detection.
+        assert_eq!(
+            VisitorFormat::detect(text.as_bytes()),
+            Some(VisitorFormat::Text)
+        );
+        let mut visitor = Visitor::load_from_memory(text.as_bytes()).unwrap();
+        let mut loaded = TextRoundTrip::default();
+        loaded.visit("Data", &mut visitor).unwrap();
+        assert_eq!(loaded, original);
+    }
+
+    #[test]
+    fn text_is_stable() {
+        let save = || {
+            let mut visitor = Visitor::new();
+            let mut shared = Rc::new(Resource::new(ResourceKind::Unknown));
+            let mut objects = vec![Foo::new(shared.clone()), Foo::new(shared.clone())];
+            shared.visit("SharedResource", &mut visitor).unwrap();
+            objects.visit("Objects", &mut visitor).unwrap();
+            visitor.save_text()
+        };
+        // Shared values must not be identified by their addresses.
+        assert_eq!(save(), save());
+    }
+
+    #[test]
+    fn text_errors() {
+        assert!(matches!(
+            Visitor::load_text("Foo"),
+            Err(VisitError::NotSupportedFormat)
+        ));
+        let text = format!(
+            "{} 1\n\"__ROOT__\" {{\n    \"Field\": u32 foo\n}}\n",
+            Visitor::TEXT_MAGIC
+        );
+        assert!(matches!(
+            Visitor::load_text(&text),
+            Err(VisitError::InvalidText { line: 3, .. })
+        ));
+        let text = format!("{} 1\n\"__ROOT__\" {{\n", Visitor::TEXT_MAGIC);
+        assert!(matches!(
+            Visitor::load_text(&text),
+            Err(VisitError::InvalidText { .. })
+        ));
+        let mut data = format!("{} 1\n\"__ROOT__\" {{\n", Visitor::TEXT_MAGIC).into_bytes();
+        data.extend_from_slice(&[0xFF, 0xFE]);
+        assert!(matches!(
+            Visitor::load_from_memory(&data),
+            Err(VisitError::InvalidText { line: 3, .. })
+        ));
+    }
+
     #[test]
     fn pod_vec_view_from_pod_vec() {
         // Pod for u8
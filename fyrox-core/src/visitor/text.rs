@@ -0,0 +1,501 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Text representation of the [`Visitor`] data.
+//!
+//! The format is line-based, so it plays nicely with version control systems - a change of a
+//! single value results in a change of a single line. It looks like this:
+//!
+//! ```text
+//! FyroxVisitorText 1
+//! "__ROOT__" {
+//!     "Name": str "Cube"
+//!     "Position": vec3f32 1.0 2.0 -3.5
+//!     "Transform" {
+//!         "Visible": bool true
+//!     }
+//! }
+//! ```
+//!
+//! Each region is written as a quoted name followed by its content in curly braces, each field
+//! is written as a quoted name, a colon, a type tag and a value. Floating-point numbers are
+//! written using the shortest representation that survives the round trip, so saving and
+//! loading the data does not change it.
+
+use crate::{
+    algebra::{
+        Complex, Matrix2, Matrix3, Matrix4, Quaternion, SVector, Scalar, UnitComplex,
+        UnitQuaternion,
+    },
+    pool::{Handle, Pool},
+    visitor::{Blackboard, Field, FieldKind, VisitError, Visitor, VisitorFlags, VisitorNode},
+};
+use base64::Engine;
+use std::{fmt::Debug, fmt::Write, str::FromStr};
+use uuid::Uuid;
+
+/// Current version of the text format, it is written right after [`Visitor::TEXT_MAGIC`].
+const VERSION: u32 = 1;
+
+fn escape(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{{{:x}}}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn write_values<'a, T, I>(out: &mut String, tag: &str, values: I)
+where
+    T: Debug + 'a,
+    I: IntoIterator<Item = &'a T>,
+{
+    out.push_str(tag);
+    for value in values {
+        // Debug representation of floats is the shortest one that can be parsed back to the
+        // exact same value. For integers it is the same as Display.
+        let _ = write!(out, " {value:?}");
+    }
+}
+
+fn write_field_kind(kind: &FieldKind, out: &mut String) {
+    match kind {
+        FieldKind::Bool(v) => write_values(out, "bool", [v]),
+        FieldKind::U8(v) => write_values(out, "u8", [v]),
+        FieldKind::I8(v) => write_values(out, "i8", [v]),
+        FieldKind::U16(v) => write_values(out, "u16", [v]),
+        FieldKind::I16(v) => write_values(out, "i16", [v]),
+        FieldKind::U32(v) => write_values(out, "u32", [v]),
+        FieldKind::I32(v) => write_values(out, "i32", [v]),
+        FieldKind::U64(v) => write_values(out, "u64", [v]),
+        FieldKind::I64(v) => write_values(out, "i64", [v]),
+        FieldKind::F32(v) => write_values(out, "f32", [v]),
+        FieldKind::F64(v) => write_values(out, "f64", [v]),
+        FieldKind::UnitQuaternion(v) => write_values(out, "quat", [&v.i, &v.j, &v.k, &v.w]),
+        FieldKind::Matrix4(v) => write_values(out, "mat4", v.iter()),
+        FieldKind::Matrix3(v) => write_values(out, "mat3", v.iter()),
+        FieldKind::Matrix2(v) => write_values(out, "mat2", v.iter()),
+        FieldKind::BinaryBlob(v) => match std::str::from_utf8(v) {
+            Ok(s) => {
+                out.push_str("str ");
+                escape(s, out);
+            }
+            Err(_) => {
+                out.push_str("data ");
+                escape(&base64::engine::general_purpose::STANDARD.encode(v), out);
+            }
+        },
+        FieldKind::Uuid(v) => {
+            let _ = write!(out, "uuid {v}");
+        }
+        FieldKind::UnitComplex(v) => write_values(out, "complex", [&v.re, &v.im]),
+        FieldKind::PodArray {
+            type_id,
+            element_size,
+            bytes,
+        } => {
+            let _ = write!(out, "podarray {type_id} {element_size} ");
+            escape(
+                &base64::engine::general_purpose::STANDARD.encode(bytes),
+                out,
+            );
+        }
+        FieldKind::Vector2F32(v) => write_values(out, "vec2f32", v.iter()),
+        FieldKind::Vector3F32(v) => write_values(out, "vec3f32", v.iter()),
+        FieldKind::Vector4F32(v) => write_values(out, "vec4f32", v.iter()),
+        FieldKind::Vector2F64(v) => write_values(out, "vec2f64", v.iter()),
+        FieldKind::Vector3F64(v) => write_values(out, "vec3f64", v.iter()),
+        FieldKind::Vector4F64(v) => write_values(out, "vec4f64", v.iter()),
+        FieldKind::Vector2U8(v) => write_values(out, "vec2u8", v.iter()),
+        FieldKind::Vector3U8(v) => write_values(out, "vec3u8", v.iter()),
+        FieldKind::Vector4U8(v) => write_values(out, "vec4u8", v.iter()),
+        FieldKind::Vector2I8(v) => write_values(out, "vec2i8", v.iter()),
+        FieldKind::Vector3I8(v) => write_values(out, "vec3i8", v.iter()),
+        FieldKind::Vector4I8(v) => write_values(out, "vec4i8", v.iter()),
+        FieldKind::Vector2U16(v) => write_values(out, "vec2u16", v.iter()),
+        FieldKind::Vector3U16(v) => write_values(out, "vec3u16", v.iter()),
+        FieldKind::Vector4U16(v) => write_values(out, "vec4u16", v.iter()),
+        FieldKind::Vector2I16(v) => write_values(out, "vec2i16", v.iter()),
+        FieldKind::Vector3I16(v) => write_values(out, "vec3i16", v.iter()),
+        FieldKind::Vector4I16(v) => write_values(out, "vec4i16", v.iter()),
+        FieldKind::Vector2U32(v) => write_values(out, "vec2u32", v.iter()),
+        FieldKind::Vector3U32(v) => write_values(out, "vec3u32", v.iter()),
+        FieldKind::Vector4U32(v) => write_values(out, "vec4u32", v.iter()),
+        FieldKind::Vector2I32(v) => write_values(out, "vec2i32", v.iter()),
+        FieldKind::Vector3I32(v) => write_values(out, "vec3i32", v.iter()),
+        FieldKind::Vector4I32(v) => write_values(out, "vec4i32", v.iter()),
+        FieldKind::Vector2U64(v) => write_values(out, "vec2u64", v.iter()),
+        FieldKind::Vector3U64(v) => write_values(out, "vec3u64", v.iter()),
+        FieldKind::Vector4U64(v) => write_values(out, "vec4u64", v.iter()),
+        FieldKind::Vector2I64(v) => write_values(out, "vec2i64", v.iter()),
+        FieldKind::Vector3I64(v) => write_values(out, "vec3i64", v.iter()),
+        FieldKind::Vector4I64(v) => write_values(out, "vec4i64", v.iter()),
+    }
+}
+
+fn write_node(
+    nodes: &Pool<VisitorNode>,
+    handle: Handle<VisitorNode>,
+    nesting: usize,
+    out: &mut String,
+) {
+    let node = nodes.borrow(handle);
+    let indent = "    ".repeat(nesting);
+
+    out.push_str(&indent);
+    escape(&node.name, out);
+    out.push_str(" {\n");
+
+    for field in node.fields.iter() {
+        out.push_str(&indent);
+        out.push_str("    ");
+        escape(&field.name, out);
+        out.push_str(": ");
+        write_field_kind(&field.kind, out);
+        out.push('\n');
+    }
+
+    for child in node.children.iter() {
+        write_node(nodes, *child, nesting + 1, out);
+    }
+
+    out.push_str(&indent);
+    out.push_str("}\n");
+}
+
+pub(super) fn write(visitor: &Visitor) -> String {
+    let mut out = format!("{} {}\n", Visitor::TEXT_MAGIC, VERSION);
+    write_node(&visitor.nodes, visitor.root, 0, &mut out);
+    out
+}
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    String(String),
+    Word(&'a str),
+    OpenBrace,
+    CloseBrace,
+    Colon,
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    position: usize,
+    line: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            position: 0,
+            line: 1,
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> VisitError {
+        VisitError::InvalidText {
+            line: self.line,
+            message: message.into(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.source[self.position..];
+        let trimmed = rest.trim_start();
+        self.line += rest[..rest.len() - trimmed.len()]
+            .bytes()
+            .filter(|b| *b == b'\n')
+            .count();
+        self.position += rest.len() - trimmed.len();
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token<'a>>, VisitError> {
+        self.skip_whitespace();
+        let rest = &self.source[self.position..];
+        let Some(first) = rest.chars().next() else {
+            return Ok(None);
+        };
+        let token = match first {
+            '{' => {
+                self.position += 1;
+                Token::OpenBrace
+            }
+            '}' => {
+                self.position += 1;
+                Token::CloseBrace
+            }
+            ':' => {
+                self.position += 1;
+                Token::Colon
+            }
+            '"' => Token::String(self.read_string()?),
+            _ => {
+                let len = rest
+                    .find(|c: char| c.is_whitespace() || matches!(c, '{' | '}' | ':' | '"'))
+                    .unwrap_or(rest.len());
+                self.position += len;
+                Token::Word(&rest[..len])
+            }
+        };
+        Ok(Some(token))
+    }
+
+    fn read_string(&mut self) -> Result<String, VisitError> {
+        let mut chars = self.source[self.position..].char_indices();
+        // Skip opening quote.
+        chars.next();
+        let mut string = String::new();
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.position += offset + 1;
+                    return Ok(string);
+                }
+                '\\' => match chars.next().map(|(_, c)| c) {
+                    Some('"') => string.push('"'),
+                    Some('\\') => string.push('\\'),
+                    Some('n') => string.push('\n'),
+                    Some('r') => string.push('\r'),
+                    Some('t') => string.push('\t'),
+                    Some('u') => {
+                        let mut code = String::new();
+                        if chars.next().map(|(_, c)| c) != Some('{') {
+                            return Err(self.error("malformed unicode escape sequence"));
+                        }
+                        loop {
+                            match chars.next().map(|(_, c)| c) {
+                                Some('}') => break,
+                                Some(c) => code.push(c),
+                                None => return Err(self.error("unterminated string")),
+                            }
+                        }
+                        let c = u32::from_str_radix(&code, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| self.error("malformed unicode escape sequence"))?;
+                        string.push(c);
+                    }
+                    _ => return Err(self.error("unknown escape sequence")),
+                },
+                '\n' => {
+                    self.line += 1;
+                    string.push(c);
+                }
+                c => string.push(c),
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+
+    fn expect_token(&mut self) -> Result<Token<'a>, VisitError> {
+        self.next_token()?
+            .ok_or_else(|| self.error("unexpected end of data"))
+    }
+
+    fn expect_word(&mut self) -> Result<&'a str, VisitError> {
+        match self.expect_token()? {
+            Token::Word(word) => Ok(word),
+            token => Err(self.error(format!("expected a value, got {token:?}"))),
+        }
+    }
+
+    fn expect_string(&mut self) -> Result<String, VisitError> {
+        match self.expect_token()? {
+            Token::String(string) => Ok(string),
+            token => Err(self.error(format!("expected a string, got {token:?}"))),
+        }
+    }
+
+    fn value<T: FromStr>(&mut self) -> Result<T, VisitError> {
+        let word = self.expect_word()?;
+        word.parse()
+            .map_err(|_| self.error(format!("unable to parse value {word}")))
+    }
+
+    fn values<T: FromStr, const N: usize>(&mut self) -> Result<[T; N], VisitError> {
+        let mut values = Vec::with_capacity(N);
+        for _ in 0..N {
+            values.push(self.value::<T>()?);
+        }
+        values
+            .try_into()
+            .map_err(|_| self.error("invalid values count"))
+    }
+
+    fn vector<T: FromStr + Scalar, const N: usize>(&mut self) -> Result<SVector<T, N>, VisitError> {
+        Ok(SVector::from(self.values::<T, N>()?))
+    }
+
+    fn base64(&mut self) -> Result<Vec<u8>, VisitError> {
+        let string = self.expect_string()?;
+        base64::engine::general_purpose::STANDARD
+            .decode(string)
+            .map_err(|e| self.error(format!("invalid base64 data: {e}")))
+    }
+
+    fn field_kind(&mut self) -> Result<FieldKind, VisitError> {
+        let tag = self.expect_word()?;
+        Ok(match tag {
+            "bool" => FieldKind::Bool(self.value()?),
+            "u8" => FieldKind::U8(self.value()?),
+            "i8" => FieldKind::I8(self.value()?),
+            "u16" => FieldKind::U16(self.value()?),
+            "i16" => FieldKind::I16(self.value()?),
+            "u32" => FieldKind::U32(self.value()?),
+            "i32" => FieldKind::I32(self.value()?),
+            "u64" => FieldKind::U64(self.value()?),
+            "i64" => FieldKind::I64(self.value()?),
+            "f32" => FieldKind::F32(self.value()?),
+            "f64" => FieldKind::F64(self.value()?),
+            "quat" => {
+                let [i, j, k, w] = self.values::<f32, 4>()?;
+                FieldKind::UnitQuaternion(UnitQuaternion::new_unchecked(Quaternion::new(
+                    w, i, j, k,
+                )))
+            }
+            "mat4" => FieldKind::Matrix4(Matrix4::from_column_slice(&self.values::<f32, 16>()?)),
+            "mat3" => FieldKind::Matrix3(Matrix3::from_column_slice(&self.values::<f32, 9>()?)),
+            "mat2" => FieldKind::Matrix2(Matrix2::from_column_slice(&self.values::<f32, 4>()?)),
+            "str" => FieldKind::BinaryBlob(self.expect_string()?.into_bytes()),
+            "data" => FieldKind::BinaryBlob(self.base64()?),
+            "uuid" => FieldKind::Uuid(self.value::<Uuid>()?),
+            "complex" => {
+                let [re, im] = self.values::<f32, 2>()?;
+                FieldKind::UnitComplex(UnitComplex::new_unchecked(Complex::new(re, im)))
+            }
+            "podarray" => FieldKind::PodArray {
+                type_id: self.value()?,
+                element_size: self.value()?,
+                bytes: self.base64()?,
+            },
+            "vec2f32" => FieldKind::Vector2F32(self.vector()?),
+            "vec3f32" => FieldKind::Vector3F32(self.vector()?),
+            "vec4f32" => FieldKind::Vector4F32(self.vector()?),
+            "vec2f64" => FieldKind::Vector2F64(self.vector()?),
+            "vec3f64" => FieldKind::Vector3F64(self.vector()?),
+            "vec4f64" => FieldKind::Vector4F64(self.vector()?),
+            "vec2u8" => FieldKind::Vector2U8(self.vector()?),
+            "vec3u8" => FieldKind::Vector3U8(self.vector()?),
+            "vec4u8" => FieldKind::Vector4U8(self.vector()?),
+            "vec2i8" => FieldKind::Vector2I8(self.vector()?),
+            "vec3i8" => FieldKind::Vector3I8(self.vector()?),
+            "vec4i8" => FieldKind::Vector4I8(self.vector()?),
+            "vec2u16" => FieldKind::Vector2U16(self.vector()?),
+            "vec3u16" => FieldKind::Vector3U16(self.vector()?),
+            "vec4u16" => FieldKind::Vector4U16(self.vector()?),
+            "vec2i16" => FieldKind::Vector2I16(self.vector()?),
+            "vec3i16" => FieldKind::Vector3I16(self.vector()?),
+            "vec4i16" => FieldKind::Vector4I16(self.vector()?),
+            "vec2u32" => FieldKind::Vector2U32(self.vector()?),
+            "vec3u32" => FieldKind::Vector3U32(self.vector()?),
+            "vec4u32" => FieldKind::Vector4U32(self.vector()?),
+            "vec2i32" => FieldKind::Vector2I32(self.vector()?),
+            "vec3i32" => FieldKind::Vector3I32(self.vector()?),
+            "vec4i32" => FieldKind::Vector4I32(self.vector()?),
+            "vec2u64" => FieldKind::Vector2U64(self.vector()?),
+            "vec3u64" => FieldKind::Vector3U64(self.vector()?),
+            "vec4u64" => FieldKind::Vector4U64(self.vector()?),
+            "vec2i64" => FieldKind::Vector2I64(self.vector()?),
+            "vec3i64" => FieldKind::Vector3I64(self.vector()?),
+            "vec4i64" => FieldKind::Vector4I64(self.vector()?),
+            _ => return Err(self.error(format!("unknown field type {tag}"))),
+        })
+    }
+
+    /// Reads the content of a node, assuming that its name and the opening brace were already read.
+    fn node(
+        &mut self,
+        nodes: &mut Pool<VisitorNode>,
+        name: String,
+        parent: Handle<VisitorNode>,
+    ) -> Result<Handle<VisitorNode>, VisitError> {
+        let handle = nodes.spawn(VisitorNode::new(&name, parent));
+        loop {
+            match self.expect_token()? {
+                Token::CloseBrace => return Ok(handle),
+                Token::String(name) => match self.expect_token()? {
+                    Token::Colon => {
+                        let kind = self.field_kind()?;
+                        nodes.borrow_mut(handle).fields.push(Field { name, kind });
+                    }
+                    Token::OpenBrace => {
+                        let child = self.node(nodes, name, handle)?;
+                        nodes.borrow_mut(handle).children.push(child);
+                    }
+                    token => {
+                        return Err(self.error(format!(
+                            "expected a colon or an opening brace, got {token:?}"
+                        )))
+                    }
+                },
+                token => {
+                    return Err(self.error(format!(
+                        "expected a field, a region or a closing brace, got {token:?}"
+                    )))
+                }
+            }
+        }
+    }
+}
+
+pub(super) fn read(source: &str) -> Result<Visitor, VisitError> {
+    let mut parser = Parser::new(source);
+
+    if parser.next_token()? != Some(Token::Word(Visitor::TEXT_MAGIC)) {
+        return Err(VisitError::NotSupportedFormat);
+    }
+    let version = parser.value::<u32>()?;
+    if version != VERSION {
+        return Err(parser.error(format!("unsupported format version {version}")));
+    }
+
+    let mut nodes = Pool::new();
+    let name = parser.expect_string()?;
+    if parser.expect_token()? != Token::OpenBrace {
+        return Err(parser.error("expected an opening brace"));
+    }
+    let root = parser.node(&mut nodes, name, Handle::NONE)?;
+    if let Some(token) = parser.next_token()? {
+        return Err(parser.error(format!("unexpected data after the root region: {token:?}")));
+    }
+
+    Ok(Visitor {
+        nodes,
+        rc_map: Default::default(),
+        arc_map: Default::default(),
+        shared_ids: Default::default(),
+        reading: true,
+        current_node: root,
+        root,
+        blackboard: Blackboard::new(),
+        flags: VisitorFlags::NONE,
+    })
+}
@@ -372,4 +372,13 @@ vec2 S_RotateVec2(vec2 v, float angle)
     float s = sin(angle);
     mat2 m = mat2(c, -s, s, c);
     return m * v;
-}
\ No newline at end of file
+}
+// Computes screen-space velocity (in texture coordinates units) of a fragment using its current and
+// previous clip-space positions. Current position must be jittered by the given sub-pixel offset
+// (in NDC units), the jitter is removed so a static object has zero velocity.
+vec2 S_ComputeVelocity(vec4 currentClipPosition, vec4 previousClipPosition, vec2 jitter)
+{
+    vec2 current = currentClipPosition.xy / currentClipPosition.w - jitter;
+    vec2 previous = previousClipPosition.xy / previousClipPosition.w;
+    return (current - previous) * 0.5;
+}
@@ -183,6 +183,7 @@ impl PixelKind {
             PixelKind::RGBA32F => (glow::FLOAT, glow::RGBA, glow::RGBA32F, None),
             PixelKind::RGBA16F => (glow::HALF_FLOAT, glow::RGBA, glow::RGBA16F, None),
             PixelKind::RGB16F => (glow::HALF_FLOAT, glow::RGB, glow::RGB16F, None),
+            PixelKind::RG16F => (glow::HALF_FLOAT, glow::RG, glow::RG16F, None),
             PixelKind::R11G11B10F => (glow::FLOAT, glow::RGB, glow::R11F_G11F_B10F, None),
             PixelKind::L8 => (
                 glow::UNSIGNED_BYTE,
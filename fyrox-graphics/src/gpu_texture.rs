@@ -48,6 +48,7 @@ pub enum PixelKind {
     R32F,
     R32UI,
     R16F,
+    RG16F,
     D32F,
     D16,
     D24S8,
@@ -107,6 +108,7 @@ impl PixelKind {
             | Self::D32F
             | Self::R32F
             | Self::R32UI
+            | Self::RG16F
             | Self::RGB10A2 => Some(4),
             Self::RG8 | Self::LA8 | Self::D16 | Self::R16F | Self::L16 | Self::R16 => Some(2),
             Self::R8
@@ -154,6 +156,7 @@ impl PixelKind {
             | Self::RG8
             | Self::D16
             | Self::R16F
+            | Self::RG16F
             | Self::R8
             | Self::R8UI
             | Self::RGB32F
@@ -171,6 +174,7 @@ impl PixelKind {
         match self {
             Self::R32F
             | Self::R16F
+            | Self::RG16F
             | Self::RGB32F
             | Self::RGBA32F
             | Self::RGBA16F
@@ -232,6 +236,7 @@ pub fn image_3d_size_bytes(
         | PixelKind::D32F
         | PixelKind::R32F
         | PixelKind::R32UI
+        | PixelKind::RG16F
         | PixelKind::R11G11B10F
         | PixelKind::RGB10A2 => 4 * pixel_count,
         PixelKind::RGB8 | PixelKind::SRGB8 | PixelKind::BGR8 => 3 * pixel_count,
@@ -269,6 +274,7 @@ pub fn image_2d_size_bytes(pixel_kind: PixelKind, width: usize, height: usize) -
         | PixelKind::D32F
         | PixelKind::R32F
         | PixelKind::R32UI
+        | PixelKind::RG16F
         | PixelKind::R11G11B10F
         | PixelKind::RGB10A2 => 4 * pixel_count,
         PixelKind::RGB8 | PixelKind::SRGB8 | PixelKind::BGR8 => 3 * pixel_count,
@@ -305,6 +311,7 @@ pub fn image_1d_size_bytes(pixel_kind: PixelKind, length: usize) -> usize {
         | PixelKind::D32F
         | PixelKind::R32F
         | PixelKind::R32UI
+        | PixelKind::RG16F
         | PixelKind::R11G11B10F
         | PixelKind::RGB10A2 => 4 * length,
         PixelKind::RGB8 | PixelKind::SRGB8 | PixelKind::BGR8 => 3 * length,
@@ -37,6 +37,8 @@ pub mod gpu_texture;
 pub mod query;
 pub mod read_buffer;
 pub mod server;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod soft;
 pub mod stats;
 pub mod uniform;
 
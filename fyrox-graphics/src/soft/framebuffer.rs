@@ -0,0 +1,695 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector4},
+        color::Color,
+        math::Rect,
+    },
+    error::FrameworkError,
+    framebuffer::{
+        Attachment, AttachmentKind, BufferDataUsage, BufferLocation, FrameBuffer,
+        ResourceBindGroup, ResourceBinding, TextureShaderLocation,
+    },
+    geometry_buffer::{DrawCallStatistics, GeometryBuffer},
+    gpu_program::GpuProgram,
+    gpu_texture::{image_2d_size_bytes, CubeMapFace, GpuTexture, GpuTextureKind},
+    soft::{
+        buffer::SoftBuffer,
+        geometry_buffer::SoftGeometryBuffer,
+        program::{SoftProgram, SoftUniformType},
+        rasterizer::{Rasterizer, Shading, Surface},
+        server::SoftGraphicsServer,
+        texture::{self, SoftTexture},
+    },
+    DrawParameters, ElementKind, ElementRange, PolygonFillMode,
+};
+use std::{
+    any::Any,
+    cell::{Ref, RefCell},
+    rc::{Rc, Weak},
+};
+
+pub struct SoftFrameBuffer {
+    state: Weak<SoftGraphicsServer>,
+    depth_attachment: Option<Attachment>,
+    color_attachments: Vec<Attachment>,
+    cube_faces: Vec<usize>,
+}
+
+fn face_index(face: CubeMapFace) -> usize {
+    match face {
+        CubeMapFace::PositiveX => 0,
+        CubeMapFace::NegativeX => 1,
+        CubeMapFace::PositiveY => 2,
+        CubeMapFace::NegativeY => 3,
+        CubeMapFace::PositiveZ => 4,
+        CubeMapFace::NegativeZ => 5,
+    }
+}
+
+/// Creates a render target view for the given texture. Cube maps are rendered into the given
+/// face, volume textures - into their first slice.
+fn make_surface(texture: &mut dyn GpuTexture, face: usize) -> Option<Surface<'_>> {
+    let texture = texture.as_any_mut().downcast_mut::<SoftTexture>()?;
+    let pixel_kind = texture.pixel_kind();
+    let texel_size = texture::texel_size(pixel_kind)?;
+    let kind = texture.kind();
+    let (width, height) = texture.size_2d();
+    let layer_size = image_2d_size_bytes(pixel_kind, width, height);
+    let data = texture.mip_data_mut(0)?;
+    let data = match kind {
+        GpuTextureKind::Cube { .. } => data.get_mut(face * layer_size..(face + 1) * layer_size)?,
+        GpuTextureKind::Volume { .. } => data.get_mut(..layer_size)?,
+        GpuTextureKind::Line { .. } | GpuTextureKind::Rectangle { .. } => data,
+    };
+    Some(Surface {
+        pixel_kind,
+        data,
+        width,
+        height,
+        texel_size,
+    })
+}
+
+fn read_matrix4(data: &[u8], offset: usize) -> Option<Matrix4<f32>> {
+    let bytes = data.get(offset..offset + 64)?;
+    let mut matrix = Matrix4::identity();
+    for (i, chunk) in bytes.chunks_exact(4).enumerate() {
+        matrix[i] = f32::from_ne_bytes(chunk.try_into().unwrap());
+    }
+    Some(matrix)
+}
+
+fn read_vector4(data: &[u8], offset: usize) -> Option<Vector4<f32>> {
+    let bytes = data.get(offset..offset + 16)?;
+    let mut vector = Vector4::zeros();
+    for (i, chunk) in bytes.chunks_exact(4).enumerate() {
+        vector[i] = f32::from_ne_bytes(chunk.try_into().unwrap());
+    }
+    Some(vector)
+}
+
+struct BoundResources {
+    /// Contents of bound uniform buffers (or their segments) indexed by uniform block index.
+    blocks: Vec<Option<Vec<u8>>>,
+    diffuse_texture: Option<Rc<RefCell<dyn GpuTexture>>>,
+}
+
+fn collect_resources(program: &SoftProgram, resources: &[ResourceBindGroup]) -> BoundResources {
+    let mut blocks = vec![None; program.blocks.len()];
+    let mut diffuse_texture = None;
+
+    let diffuse_texture_index = program
+        .uniforms
+        .iter()
+        .position(|u| u.name == "diffuseTexture");
+
+    for bind_group in resources {
+        for binding in bind_group.bindings {
+            match binding {
+                ResourceBinding::Texture {
+                    texture,
+                    shader_location,
+                } => {
+                    let Some(diffuse_texture_index) = diffuse_texture_index else {
+                        continue;
+                    };
+                    let is_diffuse = match shader_location {
+                        TextureShaderLocation::Uniform(uniform) => {
+                            uniform.id.0 as usize == diffuse_texture_index
+                        }
+                        TextureShaderLocation::ExplicitBinding(binding) => {
+                            program.uniforms[diffuse_texture_index].binding == *binding
+                        }
+                    };
+                    if is_diffuse {
+                        diffuse_texture = Some(texture.clone());
+                    }
+                }
+                ResourceBinding::Buffer {
+                    buffer,
+                    binding,
+                    data_usage,
+                } => {
+                    let Some(buffer) = buffer.as_any().downcast_ref::<SoftBuffer>() else {
+                        continue;
+                    };
+                    let block_index = match binding {
+                        BufferLocation::Auto { shader_location } => Some(*shader_location),
+                        BufferLocation::Explicit { binding } => {
+                            program.blocks.iter().position(|b| b.binding == *binding)
+                        }
+                    };
+                    let Some(slot) = block_index.and_then(|i| blocks.get_mut(i)) else {
+                        continue;
+                    };
+                    let data = buffer.data();
+                    *slot = match data_usage {
+                        BufferDataUsage::UseEverything => Some(data.clone()),
+                        BufferDataUsage::UseSegment { offset, size } => {
+                            data.get(*offset..*offset + *size).map(|s| s.to_vec())
+                        }
+                    };
+                }
+            }
+        }
+    }
+
+    BoundResources {
+        blocks,
+        diffuse_texture,
+    }
+}
+
+impl SoftFrameBuffer {
+    pub fn new(
+        server: &SoftGraphicsServer,
+        depth_attachment: Option<Attachment>,
+        color_attachments: Vec<Attachment>,
+    ) -> Result<Self, FrameworkError> {
+        if let Some(depth_attachment) = depth_attachment.as_ref() {
+            if depth_attachment.kind == AttachmentKind::Color {
+                panic!("Attempt to use color attachment as depth/stencil!")
+            }
+        }
+
+        for attachment in depth_attachment.iter().chain(color_attachments.iter()) {
+            if !attachment.texture.borrow().as_any().is::<SoftTexture>() {
+                return Err(FrameworkError::FailedToConstructFBO);
+            }
+        }
+
+        for color_attachment in color_attachments.iter() {
+            assert_eq!(color_attachment.kind, AttachmentKind::Color);
+        }
+
+        Ok(Self {
+            state: server.weak(),
+            cube_faces: vec![0; color_attachments.len()],
+            depth_attachment,
+            color_attachments,
+        })
+    }
+
+    pub fn backbuffer(server: &SoftGraphicsServer) -> Self {
+        let state = server.state.borrow();
+        Self {
+            state: server.weak(),
+            depth_attachment: Some(Attachment {
+                kind: AttachmentKind::DepthStencil,
+                texture: state.back_buffer_depth_stencil.clone(),
+            }),
+            color_attachments: vec![Attachment {
+                kind: AttachmentKind::Color,
+                texture: state.back_buffer_color.clone(),
+            }],
+            cube_faces: vec![0],
+        }
+    }
+
+    fn draw_internal(
+        &mut self,
+        geometry: &SoftGeometryBuffer,
+        viewport: Rect<i32>,
+        program: &dyn GpuProgram,
+        params: &DrawParameters,
+        resources: &[ResourceBindGroup],
+        triangles: std::ops::Range<usize>,
+    ) {
+        let Some(server) = self.state.upgrade() else {
+            return;
+        };
+
+        let state = server.state.borrow();
+        if geometry.element_kind != ElementKind::Triangle
+            || state.polygon_fill_mode != PolygonFillMode::Fill
+        {
+            return;
+        }
+
+        let Some(program) = program.as_any().downcast_ref::<SoftProgram>() else {
+            return;
+        };
+
+        let resources = collect_resources(program, resources);
+        let block_data = |index: usize, _: &_| resources.blocks[index].as_deref();
+
+        let world_view_projection = program
+            .find_member("worldViewProjection", SoftUniformType::Mat4, block_data)
+            .and_then(|(data, offset)| read_matrix4(data, offset))
+            .unwrap_or_else(Matrix4::identity);
+        let diffuse_color = program
+            .find_member("diffuseColor", SoftUniformType::Vec4, block_data)
+            .and_then(|(data, offset)| read_vector4(data, offset))
+            .unwrap_or_else(|| Vector4::repeat(1.0));
+
+        // The texture could be used as a render target at the same time (which is an error), in
+        // this case the texture is not sampled.
+        let diffuse_texture_guard: Option<Ref<dyn GpuTexture>> = resources
+            .diffuse_texture
+            .as_ref()
+            .and_then(|t| t.try_borrow().ok());
+
+        let mut color_guard = self
+            .color_attachments
+            .first()
+            .and_then(|a| a.texture.try_borrow_mut().ok());
+        let mut depth_guard = self
+            .depth_attachment
+            .as_ref()
+            .and_then(|a| a.texture.try_borrow_mut().ok());
+
+        let face = self.cube_faces.first().cloned().unwrap_or_default();
+
+        let mut rasterizer = Rasterizer {
+            color: color_guard
+                .as_deref_mut()
+                .and_then(|texture| make_surface(texture, face)),
+            depth_stencil: depth_guard
+                .as_deref_mut()
+                .and_then(|texture| make_surface(texture, 0)),
+            viewport,
+            params,
+            shading: Shading {
+                world_view_projection,
+                diffuse_color,
+                diffuse_texture: diffuse_texture_guard
+                    .as_deref()
+                    .and_then(|t| t.as_any().downcast_ref::<SoftTexture>()),
+            },
+            samples_passed: 0,
+        };
+
+        rasterizer.draw_triangles(geometry, triangles, 0);
+
+        if let Some(active_query) = state.active_query.as_ref() {
+            active_query.set(active_query.get() + rasterizer.samples_passed);
+        }
+    }
+}
+
+impl FrameBuffer for SoftFrameBuffer {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn color_attachments(&self) -> &[Attachment] {
+        &self.color_attachments
+    }
+
+    fn depth_attachment(&self) -> Option<&Attachment> {
+        self.depth_attachment.as_ref()
+    }
+
+    fn set_cubemap_face(&mut self, attachment_index: usize, face: CubeMapFace) {
+        if let Some(cube_face) = self.cube_faces.get_mut(attachment_index) {
+            *cube_face = face_index(face);
+        }
+    }
+
+    fn blit_to(
+        &self,
+        dest: &dyn FrameBuffer,
+        src_x0: i32,
+        src_y0: i32,
+        src_x1: i32,
+        src_y1: i32,
+        dst_x0: i32,
+        dst_y0: i32,
+        dst_x1: i32,
+        dst_y1: i32,
+        copy_color: bool,
+        copy_depth: bool,
+        copy_stencil: bool,
+    ) {
+        let dest = dest.as_any().downcast_ref::<SoftFrameBuffer>().unwrap();
+
+        let mut pairs = Vec::new();
+        if copy_color {
+            if let (Some(src), Some(dst)) = (
+                self.color_attachments.first(),
+                dest.color_attachments.first(),
+            ) {
+                pairs.push((src.texture.clone(), dst.texture.clone()));
+            }
+        }
+        if copy_depth || copy_stencil {
+            if let (Some(src), Some(dst)) = (
+                self.depth_attachment.as_ref(),
+                dest.depth_attachment.as_ref(),
+            ) {
+                pairs.push((src.texture.clone(), dst.texture.clone()));
+            }
+        }
+
+        let dst_width = dst_x1 - dst_x0;
+        let dst_height = dst_y1 - dst_y0;
+        if dst_width == 0 || dst_height == 0 {
+            return;
+        }
+
+        for (src, dst) in pairs {
+            if Rc::ptr_eq(&src, &dst) {
+                continue;
+            }
+
+            let mut src = src.borrow_mut();
+            let mut dst = dst.borrow_mut();
+            let (Some(src), Some(dst)) = (make_surface(&mut *src, 0), make_surface(&mut *dst, 0))
+            else {
+                continue;
+            };
+
+            for y in dst_y0.min(dst_y1)..dst_y0.max(dst_y1) {
+                for x in dst_x0.min(dst_x1)..dst_x0.max(dst_x1) {
+                    if x < 0 || y < 0 || x as usize >= dst.width || y as usize >= dst.height {
+                        continue;
+                    }
+                    // Nearest filtering, as in OpenGL server.
+                    let sx = src_x0
+                        + (((x - dst_x0) as f32 + 0.5) * (src_x1 - src_x0) as f32
+                            / dst_width as f32)
+                            .floor() as i32;
+                    let sy = src_y0
+                        + (((y - dst_y0) as f32 + 0.5) * (src_y1 - src_y0) as f32
+                            / dst_height as f32)
+                            .floor() as i32;
+                    if sx < 0 || sy < 0 || sx as usize >= src.width || sy as usize >= src.height {
+                        continue;
+                    }
+                    let src_offset = (sy as usize * src.width + sx as usize) * src.texel_size;
+                    let dst_offset = (y as usize * dst.width + x as usize) * dst.texel_size;
+                    if src.pixel_kind == dst.pixel_kind {
+                        dst.data[dst_offset..dst_offset + dst.texel_size]
+                            .copy_from_slice(&src.data[src_offset..src_offset + src.texel_size]);
+                    } else if let Some(texel) =
+                        texture::read_texel(src.pixel_kind, src.data, src_offset)
+                    {
+                        texture::write_texel(dst.pixel_kind, dst.data, dst_offset, texel);
+                    } else if let Some(depth) =
+                        texture::read_depth(src.pixel_kind, src.data, src_offset)
+                    {
+                        texture::write_depth(dst.pixel_kind, dst.data, dst_offset, depth);
+                    }
+                }
+            }
+        }
+    }
+
+    fn clear(
+        &mut self,
+        _viewport: Rect<i32>,
+        color: Option<Color>,
+        depth: Option<f32>,
+        stencil: Option<i32>,
+    ) {
+        if let Some(depth_stencil) = self.depth_attachment.as_ref() {
+            let mut guard = depth_stencil.texture.borrow_mut();
+            if let Some(surface) = make_surface(&mut *guard, 0) {
+                for offset in (0..surface.data.len()).step_by(surface.texel_size) {
+                    if let Some(depth) = depth {
+                        texture::write_depth(surface.pixel_kind, surface.data, offset, depth);
+                    }
+                    if let Some(stencil) = stencil {
+                        texture::write_stencil(
+                            surface.pixel_kind,
+                            surface.data,
+                            offset,
+                            stencil as u32,
+                        );
+                    }
+                }
+            }
+        }
+
+        if let Some(color) = color {
+            let value = color.as_frgba();
+            for (attachment, face) in self.color_attachments.iter().zip(self.cube_faces.iter()) {
+                let mut guard = attachment.texture.borrow_mut();
+                if let Some(surface) = make_surface(&mut *guard, *face) {
+                    for offset in (0..surface.data.len()).step_by(surface.texel_size) {
+                        texture::write_texel(surface.pixel_kind, surface.data, offset, value);
+                    }
+                }
+            }
+        }
+    }
+
+    fn draw(
+        &mut self,
+        geometry: &dyn GeometryBuffer,
+        viewport: Rect<i32>,
+        program: &dyn GpuProgram,
+        params: &DrawParameters,
+        resources: &[ResourceBindGroup],
+        element_range: ElementRange,
+    ) -> Result<DrawCallStatistics, FrameworkError> {
+        let geometry = geometry
+            .as_any()
+            .downcast_ref::<SoftGeometryBuffer>()
+            .unwrap();
+
+        let element_count = geometry.element_count();
+
+        let (offset, count) = match element_range {
+            ElementRange::Full => (0, element_count),
+            ElementRange::Specific { offset, count } => (offset, count),
+        };
+
+        let last_triangle_index = offset + count;
+
+        if last_triangle_index > element_count {
+            Err(FrameworkError::InvalidElementRange {
+                start: offset,
+                end: last_triangle_index,
+                total: element_count,
+            })
+        } else {
+            self.draw_internal(
+                geometry,
+                viewport,
+                program,
+                params,
+                resources,
+                offset..last_triangle_index,
+            );
+
+            Ok(DrawCallStatistics { triangles: count })
+        }
+    }
+
+    fn draw_instances(
+        &mut self,
+        count: usize,
+        geometry: &dyn GeometryBuffer,
+        viewport: Rect<i32>,
+        program: &dyn GpuProgram,
+        params: &DrawParameters,
+        resources: &[ResourceBindGroup],
+    ) -> DrawCallStatistics {
+        let geometry = geometry
+            .as_any()
+            .downcast_ref::<SoftGeometryBuffer>()
+            .unwrap();
+
+        let element_count = geometry.element_count();
+
+        // Fixed-function pipeline does not use per-instance data, so every instance will be
+        // rendered at the same place. It is enough to draw just one instance.
+        if count > 0 {
+            self.draw_internal(
+                geometry,
+                viewport,
+                program,
+                params,
+                resources,
+                0..element_count,
+            );
+        }
+
+        DrawCallStatistics {
+            triangles: element_count * count,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        buffer::{BufferKind, BufferUsage},
+        core::{
+            algebra::{Matrix4, Vector3},
+            color::Color,
+            math::{Rect, TriangleDefinition},
+            sstorage::ImmutableString,
+        },
+        framebuffer::{BufferLocation, ResourceBindGroup, ResourceBinding},
+        geometry_buffer::{
+            AttributeDefinition, AttributeKind, GeometryBufferDescriptor, VertexBufferData,
+            VertexBufferDescriptor,
+        },
+        gpu_texture::PixelKind,
+        server::GraphicsServer,
+        soft::server::SoftGraphicsServer,
+        DrawParameters, ElementKind, ElementRange,
+    };
+
+    const VERTEX_SOURCE: &str = r#"
+        layout (location = 0) in vec3 vertexPosition;
+        layout (std140) uniform Uniforms {
+            mat4 worldViewProjection;
+            vec4 diffuseColor;
+        };
+        void main() {
+            gl_Position = worldViewProjection * vec4(vertexPosition, 1.0);
+        }
+    "#;
+
+    fn pixel(pixels: &[u8], width: usize, x: usize, y: usize) -> [u8; 4] {
+        let offset = (y * width + x) * 4;
+        pixels[offset..offset + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn test_draw_triangle() {
+        let server = SoftGraphicsServer::new((8, 8)).unwrap();
+
+        let vertices = [
+            Vector3::new(-1.0f32, -1.0, 0.0),
+            Vector3::new(1.0, -1.0, 0.0),
+            Vector3::new(-1.0, 1.0, 0.0),
+        ];
+        let geometry = server
+            .create_geometry_buffer(GeometryBufferDescriptor {
+                element_kind: ElementKind::Triangle,
+                buffers: &[VertexBufferDescriptor {
+                    usage: BufferUsage::StaticDraw,
+                    attributes: &[AttributeDefinition {
+                        location: 0,
+                        kind: AttributeKind::Float,
+                        component_count: 3,
+                        normalized: false,
+                        divisor: 0,
+                    }],
+                    data: VertexBufferData::new(Some(&vertices)),
+                }],
+            })
+            .unwrap();
+        geometry.set_triangles(&[TriangleDefinition([0, 1, 2])]);
+
+        let program = server
+            .create_program("Test", VERTEX_SOURCE, "void main() {}")
+            .unwrap();
+
+        let uniforms = server
+            .create_buffer(0, BufferKind::Uniform, BufferUsage::DynamicDraw)
+            .unwrap();
+        let mut data = Matrix4::<f32>::identity().as_slice().to_vec();
+        data.extend_from_slice(&[1.0, 0.0, 0.0, 1.0]);
+        uniforms.write_data_of_type(&data).unwrap();
+
+        let mut back_buffer = server.back_buffer();
+        back_buffer.clear(
+            Rect::new(0, 0, 8, 8),
+            Some(Color::opaque(0, 0, 255)),
+            Some(1.0),
+            Some(0),
+        );
+
+        let stats = back_buffer
+            .draw(
+                &*geometry,
+                Rect::new(0, 0, 8, 8),
+                &*program,
+                &DrawParameters::default(),
+                &[ResourceBindGroup {
+                    bindings: &[ResourceBinding::Buffer {
+                        buffer: &*uniforms,
+                        binding: BufferLocation::Auto {
+                            shader_location: program
+                                .uniform_block_index(&ImmutableString::new("Uniforms"))
+                                .unwrap(),
+                        },
+                        data_usage: Default::default(),
+                    }],
+                }],
+                ElementRange::Full,
+            )
+            .unwrap();
+        assert_eq!(stats.triangles, 1);
+
+        let pixels = server.read_back_buffer();
+        assert_eq!(pixels.len(), 8 * 8 * 4);
+        // Lower-left half is covered by the triangle.
+        assert_eq!(pixel(&pixels, 8, 0, 0), [255, 0, 0, 255]);
+        assert_eq!(pixel(&pixels, 8, 6, 0), [255, 0, 0, 255]);
+        // Upper-right half is not.
+        assert_eq!(pixel(&pixels, 8, 7, 7), [0, 0, 255, 255]);
+        assert_eq!(pixel(&pixels, 8, 4, 6), [0, 0, 255, 255]);
+
+        // The same triangle with clockwise winding is culled.
+        geometry.set_triangles(&[TriangleDefinition([0, 2, 1])]);
+        back_buffer.clear(Rect::new(0, 0, 8, 8), Some(Color::BLACK), Some(1.0), None);
+        back_buffer
+            .draw(
+                &*geometry,
+                Rect::new(0, 0, 8, 8),
+                &*program,
+                &DrawParameters::default(),
+                &[],
+                ElementRange::Full,
+            )
+            .unwrap();
+        let pixels = server.read_back_buffer();
+        assert!(pixels.chunks(4).all(|p| p == [0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_render_target_read_back() {
+        let server = SoftGraphicsServer::new((4, 4)).unwrap();
+        let texture = server
+            .create_2d_render_target(PixelKind::RGBA32F, 2, 2)
+            .unwrap();
+        let mut frame_buffer = server
+            .create_frame_buffer(
+                None,
+                vec![crate::framebuffer::Attachment {
+                    kind: crate::framebuffer::AttachmentKind::Color,
+                    texture: texture.clone(),
+                }],
+            )
+            .unwrap();
+        frame_buffer.clear(
+            Rect::new(0, 0, 2, 2),
+            Some(Color::opaque(255, 0, 0)),
+            None,
+            None,
+        );
+        let pixels = texture.borrow().read_pixels_of_type::<f32>();
+        assert_eq!(pixels.len(), 16);
+        assert_eq!(&pixels[0..4], &[1.0, 0.0, 0.0, 1.0]);
+    }
+}
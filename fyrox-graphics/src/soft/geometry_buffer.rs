@@ -0,0 +1,181 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{
+    core::{algebra::Vector4, math::TriangleDefinition},
+    error::FrameworkError,
+    geometry_buffer::{AttributeKind, GeometryBuffer, GeometryBufferDescriptor},
+    ElementKind,
+};
+use std::{any::Any, cell::RefCell};
+
+pub struct SoftAttribute {
+    pub location: u32,
+    pub kind: AttributeKind,
+    pub component_count: usize,
+    pub normalized: bool,
+    pub divisor: u32,
+    pub offset: usize,
+}
+
+pub struct SoftVertexBuffer {
+    pub element_size: usize,
+    pub attributes: Vec<SoftAttribute>,
+    pub data: RefCell<Vec<u8>>,
+}
+
+pub struct SoftGeometryBuffer {
+    pub buffers: Vec<SoftVertexBuffer>,
+    pub elements: RefCell<Vec<u32>>,
+    pub element_kind: ElementKind,
+}
+
+impl SoftGeometryBuffer {
+    pub fn new(desc: GeometryBufferDescriptor) -> Result<Self, FrameworkError> {
+        let mut buffers = Vec::new();
+        for buffer in desc.buffers {
+            let mut attributes = Vec::new();
+            let mut offset = 0usize;
+            for definition in buffer.attributes {
+                attributes.push(SoftAttribute {
+                    location: definition.location,
+                    kind: definition.kind,
+                    component_count: definition.component_count,
+                    normalized: definition.normalized,
+                    divisor: definition.divisor,
+                    offset,
+                });
+
+                offset += definition.kind.size() * definition.component_count;
+
+                if offset > buffer.data.element_size {
+                    return Err(FrameworkError::InvalidAttributeDescriptor);
+                }
+            }
+
+            buffers.push(SoftVertexBuffer {
+                element_size: buffer.data.element_size,
+                attributes,
+                data: RefCell::new(buffer.data.bytes.map(|b| b.to_vec()).unwrap_or_default()),
+            });
+        }
+
+        Ok(Self {
+            buffers,
+            elements: Default::default(),
+            element_kind: desc.element_kind,
+        })
+    }
+
+    /// Fetches a value of the attribute at the given location for the given vertex and instance.
+    /// Missing components are filled with `(0, 0, 0, 1)`, just like OpenGL does.
+    pub fn fetch_attribute(
+        &self,
+        location: u32,
+        vertex_index: usize,
+        instance_index: usize,
+    ) -> Option<Vector4<f32>> {
+        for buffer in self.buffers.iter() {
+            for attribute in buffer.attributes.iter() {
+                if attribute.location != location {
+                    continue;
+                }
+
+                let element_index = if attribute.divisor == 0 {
+                    vertex_index
+                } else {
+                    instance_index / attribute.divisor as usize
+                };
+
+                let data = buffer.data.borrow();
+                let base = element_index * buffer.element_size + attribute.offset;
+                let component_size = attribute.kind.size();
+                if base + component_size * attribute.component_count > data.len() {
+                    return None;
+                }
+
+                let mut value = Vector4::new(0.0, 0.0, 0.0, 1.0);
+                for i in 0..attribute.component_count.min(4) {
+                    let position = base + i * component_size;
+                    let bytes = &data[position..position + component_size];
+                    value[i] = match attribute.kind {
+                        AttributeKind::Float => f32::from_ne_bytes(bytes.try_into().unwrap()),
+                        AttributeKind::UnsignedByte => {
+                            let v = bytes[0] as f32;
+                            if attribute.normalized {
+                                v / u8::MAX as f32
+                            } else {
+                                v
+                            }
+                        }
+                        AttributeKind::UnsignedShort => {
+                            let v = u16::from_ne_bytes(bytes.try_into().unwrap()) as f32;
+                            if attribute.normalized {
+                                v / u16::MAX as f32
+                            } else {
+                                v
+                            }
+                        }
+                        AttributeKind::UnsignedInt => {
+                            let v = u32::from_ne_bytes(bytes.try_into().unwrap()) as f32;
+                            if attribute.normalized {
+                                v / u32::MAX as f32
+                            } else {
+                                v
+                            }
+                        }
+                    };
+                }
+                return Some(value);
+            }
+        }
+        None
+    }
+}
+
+impl GeometryBuffer for SoftGeometryBuffer {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn set_buffer_data(&self, buffer: usize, data: &[u8]) {
+        let mut storage = self.buffers[buffer].data.borrow_mut();
+        storage.clear();
+        storage.extend_from_slice(data);
+    }
+
+    fn element_count(&self) -> usize {
+        self.elements.borrow().len() / self.element_kind.index_per_element()
+    }
+
+    fn set_triangles(&self, triangles: &[TriangleDefinition]) {
+        assert_eq!(self.element_kind, ElementKind::Triangle);
+        *self.elements.borrow_mut() = triangles.iter().flat_map(|t| t.0).collect();
+    }
+
+    fn set_lines(&self, lines: &[[u32; 2]]) {
+        assert_eq!(self.element_kind, ElementKind::Line);
+        *self.elements.borrow_mut() = lines.iter().flatten().copied().collect();
+    }
+}
@@ -0,0 +1,42 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Software (CPU-only) implementation of the graphics server. It does not require any GPU or
+//! windowing system and is mainly intended for headless environments, such as CI machines, where
+//! it could be used to run the renderer and to produce images for golden-image tests.
+//!
+//! # Limitations
+//!
+//! The software server cannot execute GLSL code, instead it uses a fixed-function pipeline that
+//! mimics the flat and forward shaders: vertex positions (attribute location 0) are transformed
+//! by the `worldViewProjection` matrix from a bound uniform block (if any), and the fragment color
+//! is `diffuseTexture` (sampled using attribute location 1 as texture coordinates) modulated by
+//! `diffuseColor` uniform. Only triangles are rasterized, lines and points are counted in the
+//! statistics, but not drawn. Triangles that cross the near clipping plane are discarded.
+
+pub mod buffer;
+pub mod framebuffer;
+pub mod geometry_buffer;
+pub mod program;
+pub mod query;
+pub mod rasterizer;
+pub mod read_buffer;
+pub mod server;
+pub mod texture;
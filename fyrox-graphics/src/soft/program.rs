@@ -0,0 +1,581 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{
+    core::sstorage::ImmutableString,
+    error::FrameworkError,
+    gpu_program::{
+        GpuProgram, ShaderPropertyKind, ShaderResourceDefinition, ShaderResourceKind,
+        UniformLocation,
+    },
+};
+use fxhash::FxHashMap;
+use std::{any::Any, marker::PhantomData, ops::Deref};
+
+/// A type of a uniform block member. Only the types that could be used in std140 uniform blocks
+/// are listed here.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SoftUniformType {
+    Float,
+    Int,
+    UInt,
+    Bool,
+    Vec2,
+    Vec3,
+    Vec4,
+    Mat2,
+    Mat3,
+    Mat4,
+}
+
+impl SoftUniformType {
+    fn from_glsl(name: &str) -> Option<Self> {
+        match name {
+            "float" => Some(Self::Float),
+            "int" => Some(Self::Int),
+            "uint" => Some(Self::UInt),
+            "bool" => Some(Self::Bool),
+            "vec2" | "ivec2" | "uvec2" | "bvec2" => Some(Self::Vec2),
+            "vec3" | "ivec3" | "uvec3" | "bvec3" => Some(Self::Vec3),
+            "vec4" | "ivec4" | "uvec4" | "bvec4" => Some(Self::Vec4),
+            "mat2" => Some(Self::Mat2),
+            "mat3" => Some(Self::Mat3),
+            "mat4" => Some(Self::Mat4),
+            _ => None,
+        }
+    }
+
+    fn from_property(kind: &ShaderPropertyKind) -> (Self, Option<usize>) {
+        match kind {
+            ShaderPropertyKind::Float(_) => (Self::Float, None),
+            ShaderPropertyKind::FloatArray { max_len, .. } => (Self::Float, Some(*max_len)),
+            ShaderPropertyKind::Int(_) => (Self::Int, None),
+            ShaderPropertyKind::IntArray { max_len, .. } => (Self::Int, Some(*max_len)),
+            ShaderPropertyKind::UInt(_) => (Self::UInt, None),
+            ShaderPropertyKind::UIntArray { max_len, .. } => (Self::UInt, Some(*max_len)),
+            ShaderPropertyKind::Bool(_) => (Self::Bool, None),
+            ShaderPropertyKind::Vector2(_) => (Self::Vec2, None),
+            ShaderPropertyKind::Vector2Array { max_len, .. } => (Self::Vec2, Some(*max_len)),
+            ShaderPropertyKind::Vector3(_) => (Self::Vec3, None),
+            ShaderPropertyKind::Vector3Array { max_len, .. } => (Self::Vec3, Some(*max_len)),
+            ShaderPropertyKind::Vector4(_) | ShaderPropertyKind::Color { .. } => (Self::Vec4, None),
+            ShaderPropertyKind::Vector4Array { max_len, .. } => (Self::Vec4, Some(*max_len)),
+            ShaderPropertyKind::Matrix2(_) => (Self::Mat2, None),
+            ShaderPropertyKind::Matrix2Array { max_len, .. } => (Self::Mat2, Some(*max_len)),
+            ShaderPropertyKind::Matrix3(_) => (Self::Mat3, None),
+            ShaderPropertyKind::Matrix3Array { max_len, .. } => (Self::Mat3, Some(*max_len)),
+            ShaderPropertyKind::Matrix4(_) => (Self::Mat4, None),
+            ShaderPropertyKind::Matrix4Array { max_len, .. } => (Self::Mat4, Some(*max_len)),
+        }
+    }
+
+    /// Returns base alignment and size of the type according to std140 layout rules.
+    fn std140_alignment_and_size(self) -> (usize, usize) {
+        match self {
+            Self::Float | Self::Int | Self::UInt | Self::Bool => (4, 4),
+            Self::Vec2 => (8, 8),
+            Self::Vec3 => (16, 12),
+            Self::Vec4 => (16, 16),
+            // Matrices are stored as arrays of column vectors, each column is padded to vec4.
+            Self::Mat2 => (16, 32),
+            Self::Mat3 => (16, 48),
+            Self::Mat4 => (16, 64),
+        }
+    }
+}
+
+fn align_up(value: usize, alignment: usize) -> usize {
+    value.div_ceil(alignment) * alignment
+}
+
+#[derive(Clone, Debug)]
+pub struct SoftUniformMember {
+    /// Full name of the member, nested struct members are separated by dots.
+    pub name: String,
+    pub kind: SoftUniformType,
+    pub array_len: Option<usize>,
+    /// Offset of the member (in bytes) from the beginning of the uniform block.
+    pub offset: usize,
+}
+
+impl SoftUniformMember {
+    /// Returns `true` if the last component of the member name is equal to the given name.
+    pub fn has_name(&self, name: &str) -> bool {
+        self.name.rsplit('.').next() == Some(name)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SoftUniformBlock {
+    pub name: String,
+    pub binding: usize,
+    pub members: Vec<SoftUniformMember>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SoftUniform {
+    pub name: String,
+    pub binding: usize,
+}
+
+#[derive(Clone, Debug)]
+enum FieldType {
+    Plain(SoftUniformType),
+    Struct(String),
+}
+
+#[derive(Clone, Debug)]
+struct Field {
+    ty: FieldType,
+    name: String,
+    array_len: Option<usize>,
+}
+
+fn strip_comments(source: &str) -> String {
+    let mut result = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '/' && chars.peek() == Some(&'/') {
+            for c in chars.by_ref() {
+                if c == '\n' {
+                    result.push('\n');
+                    break;
+                }
+            }
+        } else if c == '/' && chars.peek() == Some(&'*') {
+            chars.next();
+            let mut previous = ' ';
+            for c in chars.by_ref() {
+                if previous == '*' && c == '/' {
+                    break;
+                }
+                previous = c;
+            }
+            result.push(' ');
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+fn tokenize(source: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    for c in source.chars() {
+        if c.is_alphanumeric() || c == '_' {
+            current.push(c);
+        } else {
+            if !current.is_empty() {
+                tokens.push(std::mem::take(&mut current));
+            }
+            if !c.is_whitespace() {
+                tokens.push(c.to_string());
+            }
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+fn is_qualifier(token: &str) -> bool {
+    matches!(
+        token,
+        "highp" | "mediump" | "lowp" | "flat" | "smooth" | "const" | "row_major" | "column_major"
+    )
+}
+
+/// Parses a list of fields in `{ type name; type name[N]; }` form. `cursor` must point to the
+/// token right after the opening brace, after the call it points to the token right after the
+/// closing brace.
+fn parse_fields(tokens: &[String], cursor: &mut usize) -> Vec<Field> {
+    let mut fields = Vec::new();
+    while *cursor < tokens.len() && tokens[*cursor] != "}" {
+        while *cursor < tokens.len() && is_qualifier(&tokens[*cursor]) {
+            *cursor += 1;
+        }
+        let Some(type_name) = tokens.get(*cursor).cloned() else {
+            break;
+        };
+        *cursor += 1;
+        let ty = match SoftUniformType::from_glsl(&type_name) {
+            Some(ty) => FieldType::Plain(ty),
+            None => FieldType::Struct(type_name),
+        };
+        // There could be multiple declarations separated by comma: `float a, b;`
+        while *cursor < tokens.len() && tokens[*cursor] != ";" && tokens[*cursor] != "}" {
+            if tokens[*cursor] == "," {
+                *cursor += 1;
+                continue;
+            }
+            let name = tokens[*cursor].clone();
+            *cursor += 1;
+            let mut array_len = None;
+            if tokens.get(*cursor).map(|t| t.as_str()) == Some("[") {
+                array_len = tokens
+                    .get(*cursor + 1)
+                    .and_then(|t| t.parse::<usize>().ok());
+                while *cursor < tokens.len() && tokens[*cursor] != "]" {
+                    *cursor += 1;
+                }
+                *cursor += 1;
+            }
+            fields.push(Field {
+                ty: ty.clone(),
+                name,
+                array_len,
+            });
+        }
+        if tokens.get(*cursor).map(|t| t.as_str()) == Some(";") {
+            *cursor += 1;
+        }
+    }
+    // Skip closing brace.
+    *cursor += 1;
+    fields
+}
+
+/// Lays out the fields according to std140 rules, starting from the given offset. Returns the
+/// size of the layout (not rounded).
+fn layout_fields(
+    prefix: &str,
+    fields: &[Field],
+    structs: &FxHashMap<String, Vec<Field>>,
+    mut offset: usize,
+    members: &mut Vec<SoftUniformMember>,
+) -> usize {
+    for field in fields {
+        let name = if prefix.is_empty() {
+            field.name.clone()
+        } else {
+            format!("{prefix}.{}", field.name)
+        };
+        match &field.ty {
+            FieldType::Plain(kind) => {
+                let (alignment, size) = kind.std140_alignment_and_size();
+                match field.array_len {
+                    Some(len) => {
+                        // Array elements are always aligned to vec4.
+                        let stride = align_up(size, 16);
+                        offset = align_up(offset, 16);
+                        members.push(SoftUniformMember {
+                            name,
+                            kind: *kind,
+                            array_len: Some(len),
+                            offset,
+                        });
+                        offset += stride * len;
+                    }
+                    None => {
+                        offset = align_up(offset, alignment);
+                        members.push(SoftUniformMember {
+                            name,
+                            kind: *kind,
+                            array_len: None,
+                            offset,
+                        });
+                        offset += size;
+                    }
+                }
+            }
+            FieldType::Struct(struct_name) => {
+                let Some(struct_fields) = structs.get(struct_name) else {
+                    continue;
+                };
+                for _ in 0..field.array_len.unwrap_or(1) {
+                    offset = align_up(offset, 16);
+                    let end = layout_fields(&name, struct_fields, structs, offset, members);
+                    offset = align_up(end, 16);
+                }
+            }
+        }
+    }
+    offset
+}
+
+/// Extracts uniform blocks and plain uniforms (samplers mostly) from the given GLSL source.
+fn parse_declarations(
+    source: &str,
+    blocks: &mut Vec<SoftUniformBlock>,
+    uniforms: &mut Vec<SoftUniform>,
+) {
+    let tokens = tokenize(&strip_comments(source));
+    let mut structs = FxHashMap::default();
+    let mut depth = 0usize;
+    let mut cursor = 0;
+    while cursor < tokens.len() {
+        let token = tokens[cursor].as_str();
+        match token {
+            "{" => {
+                depth += 1;
+                cursor += 1;
+            }
+            "}" => {
+                depth = depth.saturating_sub(1);
+                cursor += 1;
+            }
+            "struct" if depth == 0 => {
+                let Some(name) = tokens.get(cursor + 1).cloned() else {
+                    break;
+                };
+                cursor += 3;
+                let fields = parse_fields(&tokens, &mut cursor);
+                structs.insert(name, fields);
+            }
+            "uniform" if depth == 0 => {
+                cursor += 1;
+                while cursor < tokens.len() && is_qualifier(&tokens[cursor]) {
+                    cursor += 1;
+                }
+                let Some(name) = tokens.get(cursor).cloned() else {
+                    break;
+                };
+                if tokens.get(cursor + 1).map(|t| t.as_str()) == Some("{") {
+                    // Uniform block.
+                    cursor += 2;
+                    let fields = parse_fields(&tokens, &mut cursor);
+                    // Optional instance name.
+                    let mut prefix = String::new();
+                    if tokens.get(cursor).map(|t| t.as_str()) != Some(";") {
+                        if let Some(instance_name) = tokens.get(cursor) {
+                            prefix = instance_name.clone();
+                        }
+                    }
+                    let mut members = Vec::new();
+                    layout_fields(&prefix, &fields, &structs, 0, &mut members);
+                    if !blocks.iter().any(|b| b.name == name) {
+                        blocks.push(SoftUniformBlock {
+                            binding: blocks.len(),
+                            name,
+                            members,
+                        });
+                    }
+                } else if let Some(uniform_name) = tokens.get(cursor + 1) {
+                    // Plain uniform: `uniform sampler2D name;`
+                    if !uniforms.iter().any(|u| &u.name == uniform_name) {
+                        uniforms.push(SoftUniform {
+                            binding: uniforms.len(),
+                            name: uniform_name.clone(),
+                        });
+                    }
+                    cursor += 2;
+                }
+            }
+            _ => cursor += 1,
+        }
+    }
+}
+
+/// GPU program of the software server. It does not execute any code, instead it extracts all
+/// the uniform blocks and uniforms from the source code, so the fixed-function pipeline of the
+/// server could find the data it needs.
+pub struct SoftProgram {
+    pub name: String,
+    pub blocks: Vec<SoftUniformBlock>,
+    pub uniforms: Vec<SoftUniform>,
+}
+
+impl SoftProgram {
+    pub fn from_source(name: &str, vertex_source: &str, fragment_source: &str) -> Self {
+        let mut blocks = Vec::new();
+        let mut uniforms = Vec::new();
+        parse_declarations(vertex_source, &mut blocks, &mut uniforms);
+        parse_declarations(fragment_source, &mut blocks, &mut uniforms);
+        Self {
+            name: name.to_string(),
+            blocks,
+            uniforms,
+        }
+    }
+
+    pub fn from_source_and_properties(
+        name: &str,
+        vertex_source: &str,
+        fragment_source: &str,
+        resources: &[ShaderResourceDefinition],
+    ) -> Result<Self, FrameworkError> {
+        let mut program = Self::from_source(name, vertex_source, fragment_source);
+
+        for resource in resources {
+            match resource.kind {
+                ShaderResourceKind::Texture { .. } => {
+                    if program
+                        .uniforms
+                        .iter()
+                        .any(|u| u.name == resource.name.deref())
+                    {
+                        return Err(FrameworkError::Custom(format!(
+                            "There are two or more resources with same name {} \
+                                in the {name} GPU program.",
+                            resource.name
+                        )));
+                    }
+                    program.uniforms.push(SoftUniform {
+                        name: resource.name.to_string(),
+                        binding: resource.binding,
+                    });
+                }
+                ShaderResourceKind::PropertyGroup(ref fields) => {
+                    let mut offset = 0;
+                    let mut members = Vec::new();
+                    for field in fields {
+                        let (kind, array_len) = SoftUniformType::from_property(&field.kind);
+                        offset = layout_fields(
+                            resource.name.deref(),
+                            &[Field {
+                                ty: FieldType::Plain(kind),
+                                name: field.name.to_string(),
+                                array_len,
+                            }],
+                            &Default::default(),
+                            offset,
+                            &mut members,
+                        );
+                    }
+                    program.blocks.push(SoftUniformBlock {
+                        name: format!("U{}", resource.name),
+                        binding: resource.binding,
+                        members,
+                    });
+                }
+            }
+        }
+
+        Ok(program)
+    }
+
+    /// Searches for a uniform block member with the given name and type in the blocks for which
+    /// the given closure returns some data. Returns the data of the block and the member offset.
+    pub fn find_member<'a>(
+        &self,
+        name: &str,
+        kind: SoftUniformType,
+        mut block_data: impl FnMut(usize, &SoftUniformBlock) -> Option<&'a [u8]>,
+    ) -> Option<(&'a [u8], usize)> {
+        for (index, block) in self.blocks.iter().enumerate() {
+            let Some(member) = block
+                .members
+                .iter()
+                .find(|m| m.kind == kind && m.has_name(name))
+            else {
+                continue;
+            };
+            if let Some(data) = block_data(index, block) {
+                return Some((data, member.offset));
+            }
+        }
+        None
+    }
+}
+
+impl GpuProgram for SoftProgram {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn uniform_location(&self, name: &ImmutableString) -> Result<UniformLocation, FrameworkError> {
+        self.uniforms
+            .iter()
+            .position(|u| u.name == name.deref())
+            .map(|index| UniformLocation {
+                id: glow::NativeUniformLocation(index as u32),
+                thread_mark: PhantomData,
+            })
+            .ok_or_else(|| FrameworkError::UnableToFindShaderUniform(name.deref().to_owned()))
+    }
+
+    fn uniform_block_index(&self, name: &ImmutableString) -> Result<usize, FrameworkError> {
+        self.blocks
+            .iter()
+            .position(|b| b.name == name.deref())
+            .ok_or_else(|| FrameworkError::UnableToFindShaderUniformBlock(name.deref().to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::soft::program::{SoftProgram, SoftUniformType};
+
+    #[test]
+    fn test_uniform_block_layout() {
+        let vertex_source = r#"
+            // uniform FakeBlock { mat4 commented; };
+            struct TData {
+                vec3 position;
+                float scale;
+                vec2 offsets[2];
+                mat4 worldViewProjection;
+            };
+            layout(std140) uniform UData { TData data; };
+            layout (std140) uniform Uniforms {
+                float a;
+                vec3 b;
+                mat3 c;
+                vec4 diffuseColor;
+            };
+            uniform sampler2D diffuseTexture;
+            void main() { }
+        "#;
+
+        let program = SoftProgram::from_source("Test", vertex_source, "");
+
+        assert_eq!(program.blocks.len(), 2);
+        assert_eq!(program.uniforms.len(), 1);
+        assert_eq!(program.uniforms[0].name, "diffuseTexture");
+
+        let offsets = |block: usize| {
+            program.blocks[block]
+                .members
+                .iter()
+                .map(|m| (m.name.as_str(), m.offset))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            offsets(0),
+            [
+                ("data.position", 0),
+                ("data.scale", 12),
+                ("data.offsets", 16),
+                ("data.worldViewProjection", 48),
+            ]
+        );
+        assert_eq!(
+            offsets(1),
+            [("a", 0), ("b", 16), ("c", 32), ("diffuseColor", 80)]
+        );
+
+        let data = [0u8; 128];
+        assert_eq!(
+            program
+                .find_member("worldViewProjection", SoftUniformType::Mat4, |_, _| Some(
+                    &data[..]
+                ))
+                .map(|(_, offset)| offset),
+            Some(48)
+        );
+    }
+}
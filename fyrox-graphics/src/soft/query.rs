@@ -0,0 +1,97 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{
+    query::{Query, QueryKind, QueryResult},
+    soft::server::SoftGraphicsServer,
+};
+use std::{
+    any::Any,
+    cell::Cell,
+    rc::{Rc, Weak},
+};
+
+/// Occlusion query of the software server. Since rendering is done synchronously, the result of
+/// the query is available right after [`Query::end`] call.
+#[derive(Debug)]
+pub struct SoftQuery {
+    server: Weak<SoftGraphicsServer>,
+    samples_passed: Rc<Cell<u32>>,
+    active_query: Cell<Option<QueryKind>>,
+    finished: Cell<bool>,
+}
+
+impl SoftQuery {
+    pub fn new(server: &SoftGraphicsServer) -> Self {
+        Self {
+            server: server.weak(),
+            samples_passed: Default::default(),
+            active_query: Default::default(),
+            finished: Default::default(),
+        }
+    }
+}
+
+impl Query for SoftQuery {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn begin(&self, kind: QueryKind) {
+        if let Some(server) = self.server.upgrade() {
+            self.samples_passed.set(0);
+            self.finished.set(false);
+            self.active_query.set(Some(kind));
+            server.state.borrow_mut().active_query = Some(self.samples_passed.clone());
+        }
+    }
+
+    fn end(&self) {
+        if self.active_query.get().is_some() {
+            if let Some(server) = self.server.upgrade() {
+                let mut state = server.state.borrow_mut();
+                if state
+                    .active_query
+                    .as_ref()
+                    .is_some_and(|active| Rc::ptr_eq(active, &self.samples_passed))
+                {
+                    state.active_query = None;
+                }
+            }
+            self.finished.set(true);
+        }
+    }
+
+    fn try_get_result(&self) -> Option<QueryResult> {
+        let active_query = self.active_query.get()?;
+        if !self.finished.get() {
+            return None;
+        }
+        let samples_passed = self.samples_passed.get();
+        match active_query {
+            QueryKind::SamplesPassed => Some(QueryResult::SamplesPassed(samples_passed)),
+            QueryKind::AnySamplesPassed => Some(QueryResult::AnySamplesPassed(samples_passed > 0)),
+        }
+    }
+}
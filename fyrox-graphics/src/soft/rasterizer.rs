@@ -0,0 +1,380 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Fixed-function triangle rasterizer of the software graphics server.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector2, Vector3, Vector4},
+        math::Rect,
+    },
+    gpu_texture::PixelKind,
+    soft::{
+        geometry_buffer::SoftGeometryBuffer,
+        texture::{self, SoftTexture},
+    },
+    BlendFactor, BlendMode, BlendParameters, CompareFunc, CullFace, DrawParameters, StencilAction,
+};
+
+/// A mutable view to a single layer of a texture used as a render target.
+pub struct Surface<'a> {
+    pub pixel_kind: PixelKind,
+    pub data: &'a mut [u8],
+    pub width: usize,
+    pub height: usize,
+    pub texel_size: usize,
+}
+
+/// All the data that is used to compute the color of fragments.
+pub struct Shading<'a> {
+    pub world_view_projection: Matrix4<f32>,
+    pub diffuse_color: Vector4<f32>,
+    pub diffuse_texture: Option<&'a SoftTexture>,
+}
+
+impl Shading<'_> {
+    fn fragment_color(&self, tex_coord: Vector2<f32>) -> Vector4<f32> {
+        let texel = match self.diffuse_texture {
+            Some(texture) => texture.sample_nearest(tex_coord.x, tex_coord.y),
+            None => Vector4::new(1.0, 1.0, 1.0, 1.0),
+        };
+        texel.component_mul(&self.diffuse_color)
+    }
+}
+
+struct ScreenVertex {
+    position: Vector3<f32>,
+    inv_w: f32,
+    tex_coord: Vector2<f32>,
+}
+
+pub struct Rasterizer<'a, 'b> {
+    pub color: Option<Surface<'a>>,
+    pub depth_stencil: Option<Surface<'a>>,
+    pub viewport: Rect<i32>,
+    pub params: &'b DrawParameters,
+    pub shading: Shading<'b>,
+    /// Amount of fragments that passed depth and stencil tests.
+    pub samples_passed: u32,
+}
+
+fn compare(func: CompareFunc, incoming: f32, stored: f32) -> bool {
+    match func {
+        CompareFunc::Never => false,
+        CompareFunc::Less => incoming < stored,
+        CompareFunc::Equal => incoming == stored,
+        CompareFunc::LessOrEqual => incoming <= stored,
+        CompareFunc::Greater => incoming > stored,
+        CompareFunc::NotEqual => incoming != stored,
+        CompareFunc::GreaterOrEqual => incoming >= stored,
+        CompareFunc::Always => true,
+    }
+}
+
+fn apply_stencil_action(action: StencilAction, value: u32, ref_value: u32) -> u32 {
+    match action {
+        StencilAction::Keep => value,
+        StencilAction::Zero => 0,
+        StencilAction::Replace => ref_value,
+        StencilAction::Incr => (value + 1).min(0xFF),
+        StencilAction::IncrWrap => (value + 1) & 0xFF,
+        StencilAction::Decr => value.saturating_sub(1),
+        StencilAction::DecrWrap => value.wrapping_sub(1) & 0xFF,
+        StencilAction::Invert => !value & 0xFF,
+    }
+}
+
+fn blend_factor(factor: BlendFactor, src: Vector4<f32>, dst: Vector4<f32>) -> Vector4<f32> {
+    let one = Vector4::repeat(1.0);
+    match factor {
+        BlendFactor::Zero | BlendFactor::ConstantColor | BlendFactor::ConstantAlpha => {
+            Vector4::zeros()
+        }
+        BlendFactor::One
+        | BlendFactor::OneMinusConstantColor
+        | BlendFactor::OneMinusConstantAlpha => one,
+        BlendFactor::SrcColor | BlendFactor::Src1Color => src,
+        BlendFactor::OneMinusSrcColor | BlendFactor::OneMinusSrc1Color => one - src,
+        BlendFactor::DstColor => dst,
+        BlendFactor::OneMinusDstColor => one - dst,
+        BlendFactor::SrcAlpha | BlendFactor::Src1Alpha => Vector4::repeat(src.w),
+        BlendFactor::OneMinusSrcAlpha | BlendFactor::OneMinusSrc1Alpha => {
+            Vector4::repeat(1.0 - src.w)
+        }
+        BlendFactor::DstAlpha => Vector4::repeat(dst.w),
+        BlendFactor::OneMinusDstAlpha => Vector4::repeat(1.0 - dst.w),
+        BlendFactor::SrcAlphaSaturate => {
+            let f = src.w.min(1.0 - dst.w);
+            Vector4::new(f, f, f, 1.0)
+        }
+    }
+}
+
+fn blend_equation(mode: BlendMode, src: f32, src_factor: f32, dst: f32, dst_factor: f32) -> f32 {
+    match mode {
+        BlendMode::Add => src * src_factor + dst * dst_factor,
+        BlendMode::Subtract => src * src_factor - dst * dst_factor,
+        BlendMode::ReverseSubtract => dst * dst_factor - src * src_factor,
+        BlendMode::Min => src.min(dst),
+        BlendMode::Max => src.max(dst),
+    }
+}
+
+fn blend(blend: &BlendParameters, src: Vector4<f32>, dst: Vector4<f32>) -> Vector4<f32> {
+    let src_rgb = blend_factor(blend.func.sfactor, src, dst);
+    let dst_rgb = blend_factor(blend.func.dfactor, src, dst);
+    let src_alpha = blend_factor(blend.func.alpha_sfactor, src, dst);
+    let dst_alpha = blend_factor(blend.func.alpha_dfactor, src, dst);
+    Vector4::new(
+        blend_equation(blend.equation.rgb, src.x, src_rgb.x, dst.x, dst_rgb.x),
+        blend_equation(blend.equation.rgb, src.y, src_rgb.y, dst.y, dst_rgb.y),
+        blend_equation(blend.equation.rgb, src.z, src_rgb.z, dst.z, dst_rgb.z),
+        blend_equation(blend.equation.alpha, src.w, src_alpha.w, dst.w, dst_alpha.w),
+    )
+}
+
+fn edge(a: Vector2<f32>, b: Vector2<f32>, p: Vector2<f32>) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+impl Rasterizer<'_, '_> {
+    fn transform_vertex(
+        &self,
+        geometry: &SoftGeometryBuffer,
+        vertex_index: usize,
+        instance_index: usize,
+    ) -> Option<ScreenVertex> {
+        let position = geometry.fetch_attribute(0, vertex_index, instance_index)?;
+        let tex_coord = geometry
+            .fetch_attribute(1, vertex_index, instance_index)
+            .map(|t| t.xy())
+            .unwrap_or_default();
+        let clip = self.shading.world_view_projection
+            * Vector4::new(position.x, position.y, position.z, 1.0);
+        // Near plane clipping is not supported, such triangles are discarded.
+        if clip.w <= f32::EPSILON {
+            return None;
+        }
+        let inv_w = 1.0 / clip.w;
+        let ndc = clip.xyz() * inv_w;
+        let viewport = self.viewport;
+        Some(ScreenVertex {
+            position: Vector3::new(
+                viewport.position.x as f32 + (ndc.x + 1.0) * 0.5 * viewport.size.x as f32,
+                viewport.position.y as f32 + (ndc.y + 1.0) * 0.5 * viewport.size.y as f32,
+                (ndc.z + 1.0) * 0.5,
+            ),
+            inv_w,
+            tex_coord,
+        })
+    }
+
+    /// Rasterizes all the triangles of the given geometry in the given range of triangles.
+    pub fn draw_triangles(
+        &mut self,
+        geometry: &SoftGeometryBuffer,
+        triangles: std::ops::Range<usize>,
+        instance_index: usize,
+    ) {
+        let elements = geometry.elements.borrow();
+        for triangle in triangles {
+            let Some(indices) = elements.get(triangle * 3..triangle * 3 + 3) else {
+                break;
+            };
+            let (Some(a), Some(b), Some(c)) = (
+                self.transform_vertex(geometry, indices[0] as usize, instance_index),
+                self.transform_vertex(geometry, indices[1] as usize, instance_index),
+                self.transform_vertex(geometry, indices[2] as usize, instance_index),
+            ) else {
+                continue;
+            };
+            self.rasterize_triangle(&a, &b, &c);
+        }
+    }
+
+    fn clip_rect(&self) -> Option<(i32, i32, i32, i32)> {
+        let mut min_x = self.viewport.position.x;
+        let mut min_y = self.viewport.position.y;
+        let mut max_x = self.viewport.position.x + self.viewport.size.x;
+        let mut max_y = self.viewport.position.y + self.viewport.size.y;
+
+        if let Some(scissor) = self.params.scissor_box {
+            min_x = min_x.max(scissor.x);
+            min_y = min_y.max(scissor.y);
+            max_x = max_x.min(scissor.x + scissor.width);
+            max_y = max_y.min(scissor.y + scissor.height);
+        }
+
+        for surface in [self.color.as_ref(), self.depth_stencil.as_ref()]
+            .into_iter()
+            .flatten()
+        {
+            max_x = max_x.min(surface.width as i32);
+            max_y = max_y.min(surface.height as i32);
+        }
+
+        min_x = min_x.max(0);
+        min_y = min_y.max(0);
+
+        if min_x >= max_x || min_y >= max_y {
+            None
+        } else {
+            Some((min_x, min_y, max_x, max_y))
+        }
+    }
+
+    fn rasterize_triangle(&mut self, a: &ScreenVertex, b: &ScreenVertex, c: &ScreenVertex) {
+        let pa = a.position.xy();
+        let pb = b.position.xy();
+        let pc = c.position.xy();
+
+        let area = edge(pa, pb, pc);
+        if area.abs() <= f32::EPSILON {
+            return;
+        }
+
+        // Counter-clockwise triangles are front-facing.
+        let is_front = area > 0.0;
+        match self.params.cull_face {
+            Some(CullFace::Back) if !is_front => return,
+            Some(CullFace::Front) if is_front => return,
+            _ => (),
+        }
+
+        let Some((clip_min_x, clip_min_y, clip_max_x, clip_max_y)) = self.clip_rect() else {
+            return;
+        };
+
+        let min_x = (pa.x.min(pb.x).min(pc.x).floor() as i32).max(clip_min_x);
+        let min_y = (pa.y.min(pb.y).min(pc.y).floor() as i32).max(clip_min_y);
+        let max_x = (pa.x.max(pb.x).max(pc.x).ceil() as i32).min(clip_max_x);
+        let max_y = (pa.y.max(pb.y).max(pc.y).ceil() as i32).min(clip_max_y);
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let p = Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+                let w0 = edge(pb, pc, p) / area;
+                let w1 = edge(pc, pa, p) / area;
+                let w2 = edge(pa, pb, p) / area;
+                if w0 < 0.0 || w1 < 0.0 || w2 < 0.0 {
+                    continue;
+                }
+
+                let depth = w0 * a.position.z + w1 * b.position.z + w2 * c.position.z;
+
+                // Perspective-correct interpolation of texture coordinates.
+                let inv_w = w0 * a.inv_w + w1 * b.inv_w + w2 * c.inv_w;
+                let tex_coord = (a.tex_coord * (w0 * a.inv_w)
+                    + b.tex_coord * (w1 * b.inv_w)
+                    + c.tex_coord * (w2 * c.inv_w))
+                    / inv_w;
+
+                self.process_fragment(x as usize, y as usize, depth, tex_coord);
+            }
+        }
+    }
+
+    fn process_fragment(&mut self, x: usize, y: usize, depth: f32, tex_coord: Vector2<f32>) {
+        let params = self.params;
+
+        if let Some(depth_stencil) = self.depth_stencil.as_mut() {
+            let offset = (y * depth_stencil.width + x) * depth_stencil.texel_size;
+            let pixel_kind = depth_stencil.pixel_kind;
+
+            if let Some(stencil_func) = params.stencil_test {
+                if let Some(stored) = texture::read_stencil(pixel_kind, depth_stencil.data, offset)
+                {
+                    let masked_ref = stencil_func.ref_value & stencil_func.mask;
+                    let masked_stored = stored & stencil_func.mask;
+                    let stencil_passed =
+                        compare(stencil_func.func, masked_ref as f32, masked_stored as f32);
+
+                    let depth_passed = !stencil_passed
+                        || match params.depth_test {
+                            Some(func) => {
+                                texture::read_depth(pixel_kind, depth_stencil.data, offset)
+                                    .is_none_or(|stored| compare(func, depth, stored))
+                            }
+                            None => true,
+                        };
+
+                    let action = if !stencil_passed {
+                        params.stencil_op.fail
+                    } else if !depth_passed {
+                        params.stencil_op.zfail
+                    } else {
+                        params.stencil_op.zpass
+                    };
+                    let new_value = apply_stencil_action(action, stored, stencil_func.ref_value);
+                    let write_mask = params.stencil_op.write_mask;
+                    let new_value = (stored & !write_mask) | (new_value & write_mask);
+                    texture::write_stencil(pixel_kind, depth_stencil.data, offset, new_value);
+
+                    if !stencil_passed {
+                        return;
+                    }
+                }
+            }
+
+            if let Some(func) = params.depth_test {
+                if let Some(stored) = texture::read_depth(pixel_kind, depth_stencil.data, offset) {
+                    if !compare(func, depth, stored) {
+                        return;
+                    }
+                }
+            }
+
+            if params.depth_write && params.depth_test.is_some() {
+                texture::write_depth(pixel_kind, depth_stencil.data, offset, depth);
+            }
+        }
+
+        self.samples_passed += 1;
+
+        if let Some(color) = self.color.as_mut() {
+            let offset = (y * color.width + x) * color.texel_size;
+            let mut fragment = self.shading.fragment_color(tex_coord);
+
+            let destination = texture::read_texel(color.pixel_kind, color.data, offset);
+
+            if let (Some(blend_params), Some(destination)) = (params.blend.as_ref(), destination) {
+                fragment = blend(blend_params, fragment, destination);
+            }
+
+            if let Some(destination) = destination {
+                let mask = params.color_write;
+                if !mask.red {
+                    fragment.x = destination.x;
+                }
+                if !mask.green {
+                    fragment.y = destination.y;
+                }
+                if !mask.blue {
+                    fragment.z = destination.z;
+                }
+                if !mask.alpha {
+                    fragment.w = destination.w;
+                }
+            }
+
+            texture::write_texel(color.pixel_kind, color.data, offset, fragment);
+        }
+    }
+}
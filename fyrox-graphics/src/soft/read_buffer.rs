@@ -0,0 +1,134 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{
+    core::math::Rect,
+    error::FrameworkError,
+    framebuffer::FrameBuffer,
+    gpu_texture::{image_2d_size_bytes, GpuTextureKind},
+    read_buffer::AsyncReadBuffer,
+    soft::{framebuffer::SoftFrameBuffer, texture},
+};
+
+/// Pixel read buffer of the software server. Since there is no actual GPU, the transfer is done
+/// immediately and the result is available on the next [`AsyncReadBuffer::try_read`] call.
+pub struct SoftAsyncReadBuffer {
+    pixels: Option<Vec<u8>>,
+    pixel_count: usize,
+    pixel_size: usize,
+}
+
+impl SoftAsyncReadBuffer {
+    pub fn new(pixel_size: usize, pixel_count: usize) -> Self {
+        Self {
+            pixels: None,
+            pixel_count,
+            pixel_size,
+        }
+    }
+}
+
+impl AsyncReadBuffer for SoftAsyncReadBuffer {
+    fn schedule_pixels_transfer(
+        &mut self,
+        framebuffer: &dyn FrameBuffer,
+        color_buffer_index: u32,
+        rect: Option<Rect<i32>>,
+    ) -> Result<(), FrameworkError> {
+        if self.pixels.is_some() {
+            return Ok(());
+        }
+
+        let framebuffer = framebuffer
+            .as_any()
+            .downcast_ref::<SoftFrameBuffer>()
+            .unwrap();
+
+        let color_attachment = &framebuffer
+            .color_attachments()
+            .get(color_buffer_index as usize)
+            .ok_or_else(|| {
+                FrameworkError::Custom(format!(
+                    "Framebuffer does not have {} color attachment!",
+                    color_buffer_index
+                ))
+            })?
+            .texture;
+
+        let color_attachment = color_attachment.borrow();
+        let pixel_kind = color_attachment.pixel_kind();
+
+        let (width, height) =
+            if let GpuTextureKind::Rectangle { width, height } = color_attachment.kind() {
+                (width, height)
+            } else {
+                return Err(FrameworkError::Custom(
+                    "Only rectangular textures can be read from GPU!".to_string(),
+                ));
+            };
+
+        let actual_size = image_2d_size_bytes(pixel_kind, width, height);
+        let self_bytes_count = self.pixel_count * self.pixel_size;
+        if actual_size != self_bytes_count {
+            return Err(FrameworkError::Custom(format!(
+                "Pixel buffer size {} does not match the size {} of the color \
+                attachment {} of the frame buffer",
+                self_bytes_count, actual_size, color_buffer_index,
+            )));
+        }
+
+        let target_rect = rect.unwrap_or_else(|| Rect::new(0, 0, width as i32, height as i32));
+
+        let texel_size = texture::texel_size(pixel_kind).ok_or_else(|| {
+            FrameworkError::Custom(format!("Unsupported pixel kind {pixel_kind:?}"))
+        })?;
+
+        let source = color_attachment.get_image(0);
+        let mut pixels = vec![0; self_bytes_count];
+        // Pixels are tightly packed into the buffer, just like glReadPixels does it.
+        let mut destination = 0;
+        for y in target_rect.position.y..target_rect.position.y + target_rect.size.y {
+            for x in target_rect.position.x..target_rect.position.x + target_rect.size.x {
+                if x >= 0 && y >= 0 && (x as usize) < width && (y as usize) < height {
+                    let source_offset = (y as usize * width + x as usize) * texel_size;
+                    if let (Some(src), Some(dst)) = (
+                        source.get(source_offset..source_offset + texel_size),
+                        pixels.get_mut(destination..destination + texel_size),
+                    ) {
+                        dst.copy_from_slice(src);
+                    }
+                }
+                destination += texel_size;
+            }
+        }
+
+        self.pixels = Some(pixels);
+
+        Ok(())
+    }
+
+    fn is_request_running(&self) -> bool {
+        self.pixels.is_some()
+    }
+
+    fn try_read(&mut self) -> Option<Vec<u8>> {
+        self.pixels.take()
+    }
+}
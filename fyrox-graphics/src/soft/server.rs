@@ -0,0 +1,330 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{
+    buffer::{Buffer, BufferKind, BufferUsage},
+    core::log::Log,
+    error::FrameworkError,
+    framebuffer::{Attachment, FrameBuffer},
+    geometry_buffer::{GeometryBuffer, GeometryBufferDescriptor},
+    gpu_program::{GpuProgram, ShaderResourceDefinition},
+    gpu_texture::{
+        GpuTexture, GpuTextureDescriptor, GpuTextureKind, MagnificationFilter, MinificationFilter,
+        PixelKind, WrapMode,
+    },
+    query::Query,
+    read_buffer::AsyncReadBuffer,
+    server::{GraphicsServer, ServerCapabilities},
+    soft::{
+        buffer::SoftBuffer, framebuffer::SoftFrameBuffer, geometry_buffer::SoftGeometryBuffer,
+        program::SoftProgram, query::SoftQuery, read_buffer::SoftAsyncReadBuffer,
+        texture::SoftTexture,
+    },
+    stats::PipelineStatistics,
+    PolygonFace, PolygonFillMode,
+};
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    rc::{Rc, Weak},
+};
+
+pub(crate) struct InnerState {
+    pub(crate) back_buffer_color: Rc<RefCell<dyn GpuTexture>>,
+    pub(crate) back_buffer_depth_stencil: Rc<RefCell<dyn GpuTexture>>,
+    pub(crate) polygon_fill_mode: PolygonFillMode,
+    pub(crate) polygon_face: PolygonFace,
+    pub(crate) active_query: Option<Rc<Cell<u32>>>,
+    frame_statistics: PipelineStatistics,
+}
+
+/// Graphics server that does all the work on CPU. See [module docs](crate::soft) for more info.
+pub struct SoftGraphicsServer {
+    pub(crate) state: RefCell<InnerState>,
+    this: RefCell<Option<Weak<SoftGraphicsServer>>>,
+}
+
+/// Maximum width or height of the back buffer. Larger frames would take gigabytes of memory.
+pub const MAX_FRAME_SIZE: u32 = 16384;
+
+fn back_buffer_size(frame_size: (u32, u32)) -> Result<(usize, usize), FrameworkError> {
+    let (width, height) = frame_size;
+    if width > MAX_FRAME_SIZE || height > MAX_FRAME_SIZE {
+        Err(FrameworkError::Custom(format!(
+            "Back buffer size {width}x{height} exceeds the maximum size of \
+            {MAX_FRAME_SIZE}x{MAX_FRAME_SIZE}"
+        )))
+    } else {
+        // Zero-sized back buffer is not allowed.
+        Ok((width.max(1) as usize, height.max(1) as usize))
+    }
+}
+
+fn make_back_buffer_texture(
+    pixel_kind: PixelKind,
+    width: usize,
+    height: usize,
+) -> Result<SoftTexture, FrameworkError> {
+    SoftTexture::new(GpuTextureDescriptor {
+        kind: GpuTextureKind::Rectangle { width, height },
+        pixel_kind,
+        min_filter: MinificationFilter::Nearest,
+        mag_filter: MagnificationFilter::Nearest,
+        mip_count: 1,
+        s_wrap_mode: WrapMode::ClampToEdge,
+        t_wrap_mode: WrapMode::ClampToEdge,
+        r_wrap_mode: WrapMode::ClampToEdge,
+        anisotropy: 1.0,
+        data: None,
+    })
+}
+
+impl SoftGraphicsServer {
+    /// Creates new software graphics server with a back buffer of the given size. The back buffer
+    /// has RGBA8 color attachment and D24S8 depth-stencil attachment. Zero size is replaced with
+    /// 1x1, sizes larger than [`MAX_FRAME_SIZE`] are rejected.
+    pub fn new(frame_size: (u32, u32)) -> Result<Rc<Self>, FrameworkError> {
+        let (width, height) = back_buffer_size(frame_size)?;
+
+        let state = Self {
+            state: RefCell::new(InnerState {
+                back_buffer_color: Rc::new(RefCell::new(make_back_buffer_texture(
+                    PixelKind::RGBA8,
+                    width,
+                    height,
+                )?)),
+                back_buffer_depth_stencil: Rc::new(RefCell::new(make_back_buffer_texture(
+                    PixelKind::D24S8,
+                    width,
+                    height,
+                )?)),
+                polygon_fill_mode: PolygonFillMode::Fill,
+                polygon_face: PolygonFace::FrontAndBack,
+                active_query: None,
+                frame_statistics: Default::default(),
+            }),
+            this: Default::default(),
+        };
+
+        let shared = Rc::new(state);
+
+        *shared.this.borrow_mut() = Some(Rc::downgrade(&shared));
+
+        Ok(shared)
+    }
+
+    pub fn weak(&self) -> Weak<Self> {
+        self.this.borrow().as_ref().unwrap().clone()
+    }
+
+    /// Returns current size of the back buffer.
+    pub fn frame_size(&self) -> (u32, u32) {
+        match self.state.borrow().back_buffer_color.borrow().kind() {
+            GpuTextureKind::Rectangle { width, height } => (width as u32, height as u32),
+            _ => (0, 0),
+        }
+    }
+
+    /// Returns a copy of the back buffer pixels in RGBA8 format. The first row of the image is
+    /// the bottom one, just like in OpenGL.
+    pub fn read_back_buffer(&self) -> Vec<u8> {
+        self.state.borrow().back_buffer_color.borrow().read_pixels()
+    }
+}
+
+impl GraphicsServer for SoftGraphicsServer {
+    fn create_buffer(
+        &self,
+        size: usize,
+        buffer_kind: BufferKind,
+        buffer_usage: BufferUsage,
+    ) -> Result<Box<dyn Buffer>, FrameworkError> {
+        Ok(Box::new(SoftBuffer::new(size, buffer_kind, buffer_usage)))
+    }
+
+    fn create_texture(
+        &self,
+        desc: GpuTextureDescriptor,
+    ) -> Result<Rc<RefCell<dyn GpuTexture>>, FrameworkError> {
+        Ok(Rc::new(RefCell::new(SoftTexture::new(desc)?)))
+    }
+
+    fn create_frame_buffer(
+        &self,
+        depth_attachment: Option<Attachment>,
+        color_attachments: Vec<Attachment>,
+    ) -> Result<Box<dyn FrameBuffer>, FrameworkError> {
+        Ok(Box::new(SoftFrameBuffer::new(
+            self,
+            depth_attachment,
+            color_attachments,
+        )?))
+    }
+
+    fn back_buffer(&self) -> Box<dyn FrameBuffer> {
+        Box::new(SoftFrameBuffer::backbuffer(self))
+    }
+
+    fn create_query(&self) -> Result<Box<dyn Query>, FrameworkError> {
+        Ok(Box::new(SoftQuery::new(self)))
+    }
+
+    fn create_program(
+        &self,
+        name: &str,
+        vertex_source: &str,
+        fragment_source: &str,
+    ) -> Result<Box<dyn GpuProgram>, FrameworkError> {
+        Ok(Box::new(SoftProgram::from_source(
+            name,
+            vertex_source,
+            fragment_source,
+        )))
+    }
+
+    fn create_program_with_properties(
+        &self,
+        name: &str,
+        vertex_source: &str,
+        fragment_source: &str,
+        properties: &[ShaderResourceDefinition],
+    ) -> Result<Box<dyn GpuProgram>, FrameworkError> {
+        Ok(Box::new(SoftProgram::from_source_and_properties(
+            name,
+            vertex_source,
+            fragment_source,
+            properties,
+        )?))
+    }
+
+    fn create_async_read_buffer(
+        &self,
+        pixel_size: usize,
+        pixel_count: usize,
+    ) -> Result<Box<dyn AsyncReadBuffer>, FrameworkError> {
+        Ok(Box::new(SoftAsyncReadBuffer::new(pixel_size, pixel_count)))
+    }
+
+    fn create_geometry_buffer(
+        &self,
+        desc: GeometryBufferDescriptor,
+    ) -> Result<Box<dyn GeometryBuffer>, FrameworkError> {
+        Ok(Box::new(SoftGeometryBuffer::new(desc)?))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn weak(self: Rc<Self>) -> Weak<dyn GraphicsServer> {
+        self.this.borrow().as_ref().unwrap().clone()
+    }
+
+    fn flush(&self) {
+        // Everything is done synchronously.
+    }
+
+    fn finish(&self) {
+        // Everything is done synchronously.
+    }
+
+    fn invalidate_resource_bindings_cache(&self) {
+        self.state.borrow_mut().frame_statistics = Default::default();
+    }
+
+    fn pipeline_statistics(&self) -> PipelineStatistics {
+        self.state.borrow().frame_statistics
+    }
+
+    fn swap_buffers(&self) -> Result<(), FrameworkError> {
+        // There is no front buffer, the back buffer is accessible directly.
+        Ok(())
+    }
+
+    fn set_frame_size(&self, new_size: (u32, u32)) {
+        let (width, height) = match back_buffer_size(new_size) {
+            Ok(size) => size,
+            Err(err) => {
+                // Keep the current back buffer, it is still valid.
+                Log::err(format!("Unable to resize the back buffer: {err}"));
+                return;
+            }
+        };
+        let state = self.state.borrow();
+        for (texture, pixel_kind) in [
+            (&state.back_buffer_color, PixelKind::RGBA8),
+            (&state.back_buffer_depth_stencil, PixelKind::D24S8),
+        ] {
+            // Textures are resized in-place, so existing back buffer frame buffers remain valid.
+            if let Err(err) = texture.borrow_mut().set_data(
+                GpuTextureKind::Rectangle { width, height },
+                pixel_kind,
+                1,
+                None,
+            ) {
+                Log::err(format!("Unable to resize the back buffer: {err}"));
+            }
+        }
+    }
+
+    fn capabilities(&self) -> ServerCapabilities {
+        ServerCapabilities {
+            max_uniform_block_size: 65536,
+            uniform_buffer_offset_alignment: 256,
+        }
+    }
+
+    fn set_polygon_fill_mode(&self, polygon_face: PolygonFace, polygon_fill_mode: PolygonFillMode) {
+        let mut state = self.state.borrow_mut();
+        state.polygon_fill_mode = polygon_fill_mode;
+        state.polygon_face = polygon_face;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        server::GraphicsServer,
+        soft::server::{SoftGraphicsServer, MAX_FRAME_SIZE},
+    };
+
+    #[test]
+    fn test_set_frame_size() {
+        assert!(SoftGraphicsServer::new((MAX_FRAME_SIZE + 1, 1)).is_err());
+
+        let server = SoftGraphicsServer::new((4, 4)).unwrap();
+
+        server.set_frame_size((16, 8));
+        assert_eq!(server.frame_size(), (16, 8));
+        assert_eq!(server.read_back_buffer().len(), 16 * 8 * 4);
+
+        // Minimized window.
+        server.set_frame_size((0, 0));
+        assert_eq!(server.frame_size(), (1, 1));
+
+        // Invalid size must keep the current back buffer.
+        server.set_frame_size((32, u32::MAX));
+        assert_eq!(server.frame_size(), (1, 1));
+    }
+}
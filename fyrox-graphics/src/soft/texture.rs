@@ -0,0 +1,480 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{
+    core::{algebra::Vector4, color::Color},
+    error::FrameworkError,
+    gpu_texture::{
+        image_1d_size_bytes, image_2d_size_bytes, image_3d_size_bytes, Coordinate, GpuTexture,
+        GpuTextureDescriptor, GpuTextureKind, MagnificationFilter, MinificationFilter, PixelKind,
+        WrapMode,
+    },
+};
+use std::any::Any;
+
+pub struct SoftTexture {
+    kind: GpuTextureKind,
+    pixel_kind: PixelKind,
+    min_filter: MinificationFilter,
+    mag_filter: MagnificationFilter,
+    s_wrap_mode: WrapMode,
+    t_wrap_mode: WrapMode,
+    r_wrap_mode: WrapMode,
+    anisotropy: f32,
+    border_color: Color,
+    mips: Vec<Vec<u8>>,
+}
+
+fn mip_size_bytes(kind: GpuTextureKind, pixel_kind: PixelKind, mip: usize) -> Option<usize> {
+    let mip = mip as u32;
+    match kind {
+        GpuTextureKind::Line { length } => length
+            .checked_shr(mip)
+            .map(|length| image_1d_size_bytes(pixel_kind, length)),
+        GpuTextureKind::Rectangle { width, height } => {
+            let (width, height) = (width.checked_shr(mip)?, height.checked_shr(mip)?);
+            Some(image_2d_size_bytes(pixel_kind, width, height))
+        }
+        GpuTextureKind::Cube { width, height } => {
+            let (width, height) = (width.checked_shr(mip)?, height.checked_shr(mip)?);
+            Some(6 * image_2d_size_bytes(pixel_kind, width, height))
+        }
+        GpuTextureKind::Volume {
+            width,
+            height,
+            depth,
+        } => {
+            let (width, height, depth) = (
+                width.checked_shr(mip)?,
+                height.checked_shr(mip)?,
+                depth.checked_shr(mip)?,
+            );
+            Some(image_3d_size_bytes(pixel_kind, width, height, depth))
+        }
+    }
+}
+
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((bits >> 10) & 0x1F) as i32;
+    let mantissa = (bits & 0x3FF) as f32;
+    match exponent {
+        0 => sign * mantissa * 2.0f32.powi(-24),
+        31 => {
+            if mantissa == 0.0 {
+                sign * f32::INFINITY
+            } else {
+                f32::NAN
+            }
+        }
+        _ => sign * (1.0 + mantissa / 1024.0) * 2.0f32.powi(exponent - 15),
+    }
+}
+
+fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    if value.is_nan() {
+        return 0x7E00;
+    }
+    let abs = value.abs();
+    if abs >= 65520.0 {
+        // Overflow, clamp to infinity.
+        sign | 0x7C00
+    } else if abs < 2.0f32.powi(-14) {
+        // Subnormal or zero.
+        sign | (abs / 2.0f32.powi(-24)).round() as u16
+    } else {
+        let exponent = ((bits >> 23) & 0xFF) as i32 - 127 + 15;
+        let mantissa = ((bits & 0x7F_FFFF) + 0x1000) >> 13;
+        // Rounding could overflow mantissa into exponent, which is the correct behaviour.
+        sign | (((exponent as u32) << 10) + mantissa) as u16
+    }
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_ne_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_f32(bytes: &[u8], offset: usize) -> f32 {
+    f32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+/// Returns size of a single texel in bytes for the given pixel kind, or [`None`] if the pixel
+/// kind is either compressed or not supported by the software server.
+pub fn texel_size(pixel_kind: PixelKind) -> Option<usize> {
+    if pixel_kind.is_compressed() {
+        None
+    } else {
+        Some(image_1d_size_bytes(pixel_kind, 1))
+    }
+}
+
+/// Decodes a texel at the given byte offset into a normalized RGBA value. Returns [`None`] for
+/// unsupported pixel kinds.
+pub fn read_texel(pixel_kind: PixelKind, bytes: &[u8], offset: usize) -> Option<Vector4<f32>> {
+    let n8 = |i: usize| bytes[offset + i] as f32 / 255.0;
+    let n16 = |i: usize| read_u16(bytes, offset + 2 * i) as f32 / 65535.0;
+    let h16 = |i: usize| f16_to_f32(read_u16(bytes, offset + 2 * i));
+    let f32 = |i: usize| read_f32(bytes, offset + 4 * i);
+    let value = match pixel_kind {
+        PixelKind::RGBA8 | PixelKind::SRGBA8 => Vector4::new(n8(0), n8(1), n8(2), n8(3)),
+        PixelKind::RGB8 | PixelKind::SRGB8 => Vector4::new(n8(0), n8(1), n8(2), 1.0),
+        PixelKind::BGRA8 => Vector4::new(n8(2), n8(1), n8(0), n8(3)),
+        PixelKind::BGR8 => Vector4::new(n8(2), n8(1), n8(0), 1.0),
+        PixelKind::RG8 => Vector4::new(n8(0), n8(1), 0.0, 1.0),
+        PixelKind::LA8 => Vector4::new(n8(0), n8(0), n8(0), n8(1)),
+        PixelKind::R8 => Vector4::new(n8(0), 0.0, 0.0, 1.0),
+        PixelKind::L8 => Vector4::new(n8(0), n8(0), n8(0), 1.0),
+        PixelKind::R8UI => Vector4::new(bytes[offset] as f32, 0.0, 0.0, 1.0),
+        PixelKind::R16 => Vector4::new(n16(0), 0.0, 0.0, 1.0),
+        PixelKind::L16 => Vector4::new(n16(0), n16(0), n16(0), 1.0),
+        PixelKind::LA16 => Vector4::new(n16(0), n16(0), n16(0), n16(1)),
+        PixelKind::RG16 => Vector4::new(n16(0), n16(1), 0.0, 1.0),
+        PixelKind::RGB16 => Vector4::new(n16(0), n16(1), n16(2), 1.0),
+        PixelKind::RGBA16 => Vector4::new(n16(0), n16(1), n16(2), n16(3)),
+        PixelKind::R16F => Vector4::new(h16(0), 0.0, 0.0, 1.0),
+        PixelKind::RG16F => Vector4::new(h16(0), h16(1), 0.0, 1.0),
+        PixelKind::RGB16F => Vector4::new(h16(0), h16(1), h16(2), 1.0),
+        PixelKind::RGBA16F => Vector4::new(h16(0), h16(1), h16(2), h16(3)),
+        PixelKind::R32F => Vector4::new(f32(0), 0.0, 0.0, 1.0),
+        PixelKind::RGB32F => Vector4::new(f32(0), f32(1), f32(2), 1.0),
+        PixelKind::RGBA32F => Vector4::new(f32(0), f32(1), f32(2), f32(3)),
+        PixelKind::R32UI => Vector4::new(read_u32(bytes, offset) as f32, 0.0, 0.0, 1.0),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Encodes the given RGBA value and writes it at the given byte offset. Unsupported pixel kinds
+/// are silently ignored.
+pub fn write_texel(pixel_kind: PixelKind, bytes: &mut [u8], offset: usize, value: Vector4<f32>) {
+    fn n8(v: f32) -> u8 {
+        (v.clamp(0.0, 1.0) * 255.0).round() as u8
+    }
+
+    fn n16(v: f32) -> [u8; 2] {
+        ((v.clamp(0.0, 1.0) * 65535.0).round() as u16).to_ne_bytes()
+    }
+
+    fn h16(v: f32) -> [u8; 2] {
+        f32_to_f16(v).to_ne_bytes()
+    }
+
+    let mut put = |components: &[&[u8]]| {
+        let mut position = offset;
+        for component in components {
+            bytes[position..position + component.len()].copy_from_slice(component);
+            position += component.len();
+        }
+    };
+
+    let [r, g, b, a] = [value.x, value.y, value.z, value.w];
+    match pixel_kind {
+        PixelKind::RGBA8 | PixelKind::SRGBA8 => put(&[&[n8(r), n8(g), n8(b), n8(a)]]),
+        PixelKind::RGB8 | PixelKind::SRGB8 => put(&[&[n8(r), n8(g), n8(b)]]),
+        PixelKind::BGRA8 => put(&[&[n8(b), n8(g), n8(r), n8(a)]]),
+        PixelKind::BGR8 => put(&[&[n8(b), n8(g), n8(r)]]),
+        PixelKind::RG8 => put(&[&[n8(r), n8(g)]]),
+        PixelKind::LA8 => put(&[&[n8(r), n8(a)]]),
+        PixelKind::R8 | PixelKind::L8 => put(&[&[n8(r)]]),
+        PixelKind::R8UI => put(&[&[r.clamp(0.0, 255.0) as u8]]),
+        PixelKind::R16 | PixelKind::L16 => put(&[&n16(r)]),
+        PixelKind::LA16 => put(&[&n16(r), &n16(a)]),
+        PixelKind::RG16 => put(&[&n16(r), &n16(g)]),
+        PixelKind::RGB16 => put(&[&n16(r), &n16(g), &n16(b)]),
+        PixelKind::RGBA16 => put(&[&n16(r), &n16(g), &n16(b), &n16(a)]),
+        PixelKind::R16F => put(&[&h16(r)]),
+        PixelKind::RG16F => put(&[&h16(r), &h16(g)]),
+        PixelKind::RGB16F => put(&[&h16(r), &h16(g), &h16(b)]),
+        PixelKind::RGBA16F => put(&[&h16(r), &h16(g), &h16(b), &h16(a)]),
+        PixelKind::R32F => put(&[&r.to_ne_bytes()]),
+        PixelKind::RGB32F => put(&[&r.to_ne_bytes(), &g.to_ne_bytes(), &b.to_ne_bytes()]),
+        PixelKind::RGBA32F => put(&[
+            &r.to_ne_bytes(),
+            &g.to_ne_bytes(),
+            &b.to_ne_bytes(),
+            &a.to_ne_bytes(),
+        ]),
+        PixelKind::R32UI => put(&[&(r.max(0.0) as u32).to_ne_bytes()]),
+        _ => (),
+    }
+}
+
+/// Reads depth value at the given byte offset of a depth (or depth-stencil) texture.
+pub fn read_depth(pixel_kind: PixelKind, bytes: &[u8], offset: usize) -> Option<f32> {
+    match pixel_kind {
+        PixelKind::D32F => Some(read_f32(bytes, offset)),
+        PixelKind::D16 => Some(read_u16(bytes, offset) as f32 / 65535.0),
+        PixelKind::D24S8 => Some((read_u32(bytes, offset) >> 8) as f32 / 16_777_215.0),
+        _ => None,
+    }
+}
+
+/// Writes depth value at the given byte offset of a depth (or depth-stencil) texture. Stencil
+/// part of the texel is preserved.
+pub fn write_depth(pixel_kind: PixelKind, bytes: &mut [u8], offset: usize, depth: f32) {
+    let depth = depth.clamp(0.0, 1.0);
+    match pixel_kind {
+        PixelKind::D32F => bytes[offset..offset + 4].copy_from_slice(&depth.to_ne_bytes()),
+        PixelKind::D16 => bytes[offset..offset + 2]
+            .copy_from_slice(&((depth * 65535.0).round() as u16).to_ne_bytes()),
+        PixelKind::D24S8 => {
+            let stencil = read_u32(bytes, offset) & 0xFF;
+            let packed = (((depth * 16_777_215.0).round() as u32) << 8) | stencil;
+            bytes[offset..offset + 4].copy_from_slice(&packed.to_ne_bytes());
+        }
+        _ => (),
+    }
+}
+
+/// Reads stencil value at the given byte offset of a depth-stencil texture. Returns [`None`] if
+/// the texture does not have stencil part.
+pub fn read_stencil(pixel_kind: PixelKind, bytes: &[u8], offset: usize) -> Option<u32> {
+    match pixel_kind {
+        PixelKind::D24S8 => Some(read_u32(bytes, offset) & 0xFF),
+        _ => None,
+    }
+}
+
+/// Writes stencil value at the given byte offset of a depth-stencil texture. Depth part of the
+/// texel is preserved.
+pub fn write_stencil(pixel_kind: PixelKind, bytes: &mut [u8], offset: usize, stencil: u32) {
+    if let PixelKind::D24S8 = pixel_kind {
+        let packed = (read_u32(bytes, offset) & !0xFF) | (stencil & 0xFF);
+        bytes[offset..offset + 4].copy_from_slice(&packed.to_ne_bytes());
+    }
+}
+
+fn wrap_coordinate(coordinate: f32, size: usize, wrap_mode: WrapMode) -> Option<usize> {
+    let size_f = size as f32;
+    let texel = (coordinate * size_f).floor();
+    let index = match wrap_mode {
+        WrapMode::Repeat => texel.rem_euclid(size_f),
+        WrapMode::ClampToEdge | WrapMode::MirrorClampToEdge => texel.clamp(0.0, size_f - 1.0),
+        WrapMode::ClampToBorder => {
+            if texel < 0.0 || texel >= size_f {
+                return None;
+            }
+            texel
+        }
+        WrapMode::MirroredRepeat => {
+            let period = texel.rem_euclid(2.0 * size_f);
+            if period >= size_f {
+                2.0 * size_f - 1.0 - period
+            } else {
+                period
+            }
+        }
+    };
+    Some(index as usize)
+}
+
+impl SoftTexture {
+    pub fn new(desc: GpuTextureDescriptor) -> Result<Self, FrameworkError> {
+        let mut result = Self {
+            kind: desc.kind,
+            pixel_kind: desc.pixel_kind,
+            min_filter: desc.min_filter,
+            mag_filter: desc.mag_filter,
+            s_wrap_mode: desc.s_wrap_mode,
+            t_wrap_mode: desc.t_wrap_mode,
+            r_wrap_mode: desc.r_wrap_mode,
+            anisotropy: desc.anisotropy,
+            border_color: Color::TRANSPARENT,
+            mips: Default::default(),
+        };
+
+        result.set_data(desc.kind, desc.pixel_kind, desc.mip_count, desc.data)?;
+
+        Ok(result)
+    }
+
+    /// Returns a reference to the raw data of the given mip level.
+    pub fn mip_data(&self, level: usize) -> Option<&[u8]> {
+        self.mips.get(level).map(|mip| mip.as_slice())
+    }
+
+    /// Returns a reference to the raw data of the given mip level.
+    pub fn mip_data_mut(&mut self, level: usize) -> Option<&mut [u8]> {
+        self.mips.get_mut(level).map(|mip| mip.as_mut_slice())
+    }
+
+    /// Returns width and height of the first mip level of rectangle and cube textures, `(length, 1)`
+    /// for line textures and `(width, height)` of a single slice for volume textures.
+    pub fn size_2d(&self) -> (usize, usize) {
+        match self.kind {
+            GpuTextureKind::Line { length } => (length, 1),
+            GpuTextureKind::Rectangle { width, height }
+            | GpuTextureKind::Cube { width, height }
+            | GpuTextureKind::Volume { width, height, .. } => (width, height),
+        }
+    }
+
+    /// Samples the first mip level of the texture at the given texture coordinates using
+    /// nearest-neighbour filtering. Cube map textures are sampled using their first face, volume
+    /// textures - using their first slice.
+    pub fn sample_nearest(&self, u: f32, v: f32) -> Vector4<f32> {
+        let border = Vector4::new(
+            self.border_color.r as f32 / 255.0,
+            self.border_color.g as f32 / 255.0,
+            self.border_color.b as f32 / 255.0,
+            self.border_color.a as f32 / 255.0,
+        );
+        let (width, height) = self.size_2d();
+        let (Some(texel_size), Some(data)) = (texel_size(self.pixel_kind), self.mip_data(0)) else {
+            return border;
+        };
+        if width == 0 || height == 0 {
+            return border;
+        }
+        let (Some(x), Some(y)) = (
+            wrap_coordinate(u, width, self.s_wrap_mode),
+            wrap_coordinate(v, height, self.t_wrap_mode),
+        ) else {
+            return border;
+        };
+        read_texel(self.pixel_kind, data, (y * width + x) * texel_size).unwrap_or(border)
+    }
+}
+
+impl GpuTexture for SoftTexture {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn set_anisotropy(&mut self, anisotropy: f32) {
+        self.anisotropy = anisotropy.max(1.0);
+    }
+
+    fn anisotropy(&self) -> f32 {
+        self.anisotropy
+    }
+
+    fn set_minification_filter(&mut self, min_filter: MinificationFilter) {
+        self.min_filter = min_filter;
+    }
+
+    fn minification_filter(&self) -> MinificationFilter {
+        self.min_filter
+    }
+
+    fn set_magnification_filter(&mut self, mag_filter: MagnificationFilter) {
+        self.mag_filter = mag_filter;
+    }
+
+    fn magnification_filter(&self) -> MagnificationFilter {
+        self.mag_filter
+    }
+
+    fn set_wrap(&mut self, coordinate: Coordinate, wrap: WrapMode) {
+        match coordinate {
+            Coordinate::S => self.s_wrap_mode = wrap,
+            Coordinate::T => self.t_wrap_mode = wrap,
+            Coordinate::R => self.r_wrap_mode = wrap,
+        }
+    }
+
+    fn wrap_mode(&self, coordinate: Coordinate) -> WrapMode {
+        match coordinate {
+            Coordinate::S => self.s_wrap_mode,
+            Coordinate::T => self.t_wrap_mode,
+            Coordinate::R => self.r_wrap_mode,
+        }
+    }
+
+    fn set_border_color(&mut self, color: Color) {
+        self.border_color = color;
+    }
+
+    fn set_data(
+        &mut self,
+        kind: GpuTextureKind,
+        pixel_kind: PixelKind,
+        mip_count: usize,
+        data: Option<&[u8]>,
+    ) -> Result<(), FrameworkError> {
+        let mip_count = mip_count.max(1);
+
+        let mut mip_sizes = Vec::with_capacity(mip_count);
+        for mip in 0..mip_count {
+            match mip_size_bytes(kind, pixel_kind, mip) {
+                // No need to add degenerated mips (0x1, 0x2, 4x0, etc).
+                Some(size) if size > 0 || mip == 0 => mip_sizes.push(size),
+                _ => break,
+            }
+        }
+
+        let desired_byte_count = mip_sizes.iter().sum::<usize>();
+
+        if let Some(data) = data {
+            let actual_data_size = data.len();
+            if actual_data_size != desired_byte_count {
+                return Err(FrameworkError::InvalidTextureData {
+                    expected_data_size: desired_byte_count,
+                    actual_data_size,
+                });
+            }
+        }
+
+        self.kind = kind;
+        self.pixel_kind = pixel_kind;
+        self.mips.clear();
+
+        let mut offset = 0;
+        for size in mip_sizes {
+            match data {
+                Some(data) => self.mips.push(data[offset..offset + size].to_vec()),
+                None => self.mips.push(vec![0; size]),
+            }
+            offset += size;
+        }
+
+        Ok(())
+    }
+
+    fn get_image(&self, level: usize) -> Vec<u8> {
+        self.mips.get(level).cloned().unwrap_or_default()
+    }
+
+    fn read_pixels(&self) -> Vec<u8> {
+        if let GpuTextureKind::Rectangle { .. } = self.kind {
+            self.get_image(0)
+        } else {
+            Default::default()
+        }
+    }
+
+    fn kind(&self) -> GpuTextureKind {
+        self.kind
+    }
+
+    fn pixel_kind(&self) -> PixelKind {
+        self.pixel_kind
+    }
+}
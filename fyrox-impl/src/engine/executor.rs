@@ -56,6 +56,7 @@ pub struct Executor {
     engine: Engine,
     desired_update_rate: f32,
     headless: bool,
+    headless_frame_size: Option<(u32, u32)>,
     throttle_threshold: f32,
     throttle_frame_interval: usize,
     resource_hot_reloading: bool,
@@ -109,6 +110,7 @@ impl Executor {
             engine,
             desired_update_rate: Self::DEFAULT_UPDATE_RATE,
             headless: false,
+            headless_frame_size: None,
             throttle_threshold: 2.0 * Self::DEFAULT_TIME_STEP,
             throttle_frame_interval: 5,
             resource_hot_reloading: true,
@@ -160,6 +162,20 @@ impl Executor {
         self.headless
     }
 
+    /// Sets the size of frames, that will be rendered in headless mode. If the size is set and the
+    /// headless mode is on, the executor will initialize a headless graphics context (see
+    /// [`Engine::initialize_headless_graphics_context`]) that uses software rendering and will
+    /// render a frame after each update. [`None`] (default) disables rendering in headless mode.
+    pub fn set_headless_frame_size(&mut self, frame_size: Option<(u32, u32)>) {
+        self.headless_frame_size = frame_size;
+    }
+
+    /// Returns the size of frames rendered in headless mode. See [`Self::set_headless_frame_size`]
+    /// docs for more info.
+    pub fn headless_frame_size(&self) -> Option<(u32, u32)> {
+        self.headless_frame_size
+    }
+
     /// Sets the desired throttle threshold (in seconds), at which the engine will stop trying to
     /// stabilize the update rate of the game logic and will increase the time step. This option
     /// could be useful to prevent potential hang up of the game if its logic or rendering takes too
@@ -218,6 +234,8 @@ impl Executor {
         let mut engine = self.engine;
         let event_loop = self.event_loop;
         let headless = self.headless;
+        #[cfg(not(target_arch = "wasm32"))]
+        let headless_frame_size = self.headless_frame_size;
         let throttle_threshold = self.throttle_threshold;
         let throttle_frame_interval = self.throttle_frame_interval;
 
@@ -248,7 +266,12 @@ impl Executor {
         let mut last_throttle_frame_number = 0usize;
 
         run_executor(event_loop, move |event, window_target| {
-            window_target.set_control_flow(ControlFlow::Wait);
+            // There's no window in headless mode, that could wake up the event loop.
+            window_target.set_control_flow(if headless {
+                ControlFlow::Poll
+            } else {
+                ControlFlow::Wait
+            });
 
             engine.handle_os_event_by_plugins(&event, fixed_time_step, window_target, &mut lag);
 
@@ -278,6 +301,22 @@ impl Executor {
                         &mut lag,
                     );
                 }
+                #[cfg(not(target_arch = "wasm32"))]
+                Event::Resumed => {
+                    if let (Some(frame_size), GraphicsContext::Uninitialized(_)) =
+                        (headless_frame_size, &engine.graphics_context)
+                    {
+                        engine
+                            .initialize_headless_graphics_context(frame_size)
+                            .expect("Unable to initialize headless graphics context!");
+
+                        engine.handle_graphics_context_created_by_plugins(
+                            fixed_time_step,
+                            window_target,
+                            &mut lag,
+                        );
+                    }
+                }
                 Event::Suspended if !headless => {
                     engine
                         .destroy_graphics_context()
@@ -325,8 +364,22 @@ impl Executor {
                         }
                     }
 
-                    if let GraphicsContext::Initialized(ref ctx) = engine.graphics_context {
-                        ctx.window.request_redraw();
+                    match engine.graphics_context {
+                        GraphicsContext::Initialized(ref ctx) => ctx.window.request_redraw(),
+                        #[cfg(not(target_arch = "wasm32"))]
+                        GraphicsContext::Headless(_) => {
+                            // There's no window that could request redrawing, so render right away.
+                            engine.handle_before_rendering_by_plugins(
+                                fixed_time_step,
+                                window_target,
+                                &mut lag,
+                            );
+
+                            engine.render().unwrap();
+
+                            frame_counter += 1;
+                        }
+                        GraphicsContext::Uninitialized(_) => (),
                     }
                 }
                 Event::WindowEvent { event, .. } => {
@@ -0,0 +1,195 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Gamepad abstraction layer. The engine does not talk to gamepad drivers directly, instead it
+//! pulls events from one or more [`GamepadSource`]s. See [`GamepadSource`] docs for more info.
+//! The engine ships with a `gilrs`-based source (`GilrsGamepadSource`), which is enabled by the
+//! `gilrs` feature and registered automatically.
+
+use crate::core::{reflect::prelude::*, visitor::prelude::*};
+use fxhash::{FxHashMap, FxHashSet};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Unique identifier of a gamepad. Identifiers are assigned by a [`GamepadSource`] and must be
+/// unique across all sources registered in the same input system.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GamepadId(pub u32);
+
+/// A set of gamepad buttons. Face buttons are named by their position, so the same binding works
+/// for every controller layout.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum GamepadButton {
+    /// Bottom face button (A on Xbox controllers, Cross on PlayStation controllers).
+    #[default]
+    South,
+    /// Right face button (B on Xbox controllers, Circle on PlayStation controllers).
+    East,
+    /// Top face button (Y on Xbox controllers, Triangle on PlayStation controllers).
+    North,
+    /// Left face button (X on Xbox controllers, Square on PlayStation controllers).
+    West,
+    /// Left shoulder button.
+    LeftBumper,
+    /// Right shoulder button.
+    RightBumper,
+    /// Left trigger, when it is reported as a digital button.
+    LeftTrigger,
+    /// Right trigger, when it is reported as a digital button.
+    RightTrigger,
+    /// Select (Back, Share, View) button.
+    Select,
+    /// Start (Options, Menu) button.
+    Start,
+    /// Vendor-specific button in the middle of the controller (Xbox, PS, Home, etc.).
+    Mode,
+    /// Left stick press.
+    LeftThumb,
+    /// Right stick press.
+    RightThumb,
+    /// Up direction of the directional pad.
+    DPadUp,
+    /// Down direction of the directional pad.
+    DPadDown,
+    /// Left direction of the directional pad.
+    DPadLeft,
+    /// Right direction of the directional pad.
+    DPadRight,
+}
+
+/// A set of gamepad axes. Stick axes are in `[-1; 1]` range (positive values are right and up),
+/// trigger axes are in `[0; 1]` range.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum GamepadAxis {
+    /// Horizontal axis of the left stick.
+    #[default]
+    LeftStickX,
+    /// Vertical axis of the left stick.
+    LeftStickY,
+    /// Horizontal axis of the right stick.
+    RightStickX,
+    /// Vertical axis of the right stick.
+    RightStickY,
+    /// Left analog trigger.
+    LeftTrigger,
+    /// Right analog trigger.
+    RightTrigger,
+}
+
+/// An event produced by a [`GamepadSource`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GamepadEvent {
+    /// A new gamepad was connected.
+    Connected(GamepadId),
+    /// A gamepad was disconnected. Its state will be discarded.
+    Disconnected(GamepadId),
+    /// A button of a gamepad was pressed.
+    ButtonPressed(GamepadId, GamepadButton),
+    /// A button of a gamepad was released.
+    ButtonReleased(GamepadId, GamepadButton),
+    /// An axis of a gamepad has changed its value.
+    AxisChanged(GamepadId, GamepadAxis, f32),
+}
+
+impl GamepadEvent {
+    /// Returns an id of the gamepad that produced the event.
+    pub fn gamepad(&self) -> GamepadId {
+        match self {
+            GamepadEvent::Connected(id)
+            | GamepadEvent::Disconnected(id)
+            | GamepadEvent::ButtonPressed(id, _)
+            | GamepadEvent::ButtonReleased(id, _)
+            | GamepadEvent::AxisChanged(id, _, _) => *id,
+        }
+    }
+}
+
+/// Gamepad source is a bridge between a platform-specific gamepad backend and the input system.
+/// The engine polls every registered source once per update and applies the events it returned.
+/// This allows you to plug in any gamepad library you like, or to feed synthetic devices in tests:
+///
+/// ```rust
+/// use fyrox_impl::engine::input::gamepad::{
+///     GamepadButton, GamepadEvent, GamepadId, GamepadSource,
+/// };
+///
+/// #[derive(Default)]
+/// struct ScriptedGamepad {
+///     queue: Vec<GamepadEvent>,
+/// }
+///
+/// impl GamepadSource for ScriptedGamepad {
+///     fn poll_events(&mut self, events: &mut Vec<GamepadEvent>) {
+///         events.append(&mut self.queue);
+///     }
+/// }
+///
+/// let mut gamepad = ScriptedGamepad::default();
+/// gamepad.queue.push(GamepadEvent::Connected(GamepadId(0)));
+/// gamepad.queue.push(GamepadEvent::ButtonPressed(GamepadId(0), GamepadButton::South));
+/// ```
+pub trait GamepadSource: 'static {
+    /// Appends every event, that happened since the last call, to the given list.
+    fn poll_events(&mut self, events: &mut Vec<GamepadEvent>);
+}
+
+/// Current state of a gamepad.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GamepadState {
+    pub(super) buttons: FxHashSet<GamepadButton>,
+    pub(super) axes: FxHashMap<GamepadAxis, f32>,
+}
+
+impl GamepadState {
+    /// Returns `true` if the given button is pressed.
+    pub fn is_button_pressed(&self, button: GamepadButton) -> bool {
+        self.buttons.contains(&button)
+    }
+
+    /// Returns the raw value (without any dead zone applied) of the given axis.
+    pub fn axis_value(&self, axis: GamepadAxis) -> f32 {
+        self.axes.get(&axis).cloned().unwrap_or_default()
+    }
+}
@@ -0,0 +1,111 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Gamepad source that uses [gilrs](https://crates.io/crates/gilrs) library to talk to gamepads.
+//! It is available only if `gilrs` feature is enabled. The engine registers this source
+//! automatically, so usually there's no need to create it manually.
+
+use crate::engine::input::gamepad::{
+    GamepadAxis, GamepadButton, GamepadEvent, GamepadId, GamepadSource,
+};
+use ::gilrs::{Axis, Button, EventType, Gilrs};
+
+/// Gamepad source that uses `gilrs` library. See [module docs](self) for more info.
+pub struct GilrsGamepadSource {
+    gilrs: Gilrs,
+}
+
+impl GilrsGamepadSource {
+    /// Tries to create a new gamepad source. Fails if the platform is not supported or the
+    /// platform-specific backend could not be initialized.
+    pub fn new() -> Result<Self, Box<::gilrs::Error>> {
+        let gilrs = Gilrs::new().map_err(Box::new)?;
+        Ok(Self { gilrs })
+    }
+}
+
+impl GamepadSource for GilrsGamepadSource {
+    fn poll_events(&mut self, events: &mut Vec<GamepadEvent>) {
+        while let Some(event) = self.gilrs.next_event() {
+            let id = GamepadId(usize::from(event.id) as u32);
+            if let Some(event) = translate_event(id, event.event) {
+                events.push(event);
+            }
+        }
+    }
+}
+
+fn translate_button(button: Button) -> Option<GamepadButton> {
+    match button {
+        Button::South => Some(GamepadButton::South),
+        Button::East => Some(GamepadButton::East),
+        Button::North => Some(GamepadButton::North),
+        Button::West => Some(GamepadButton::West),
+        // `gilrs` calls bumpers "triggers" and triggers "second triggers".
+        Button::LeftTrigger => Some(GamepadButton::LeftBumper),
+        Button::RightTrigger => Some(GamepadButton::RightBumper),
+        Button::LeftTrigger2 => Some(GamepadButton::LeftTrigger),
+        Button::RightTrigger2 => Some(GamepadButton::RightTrigger),
+        Button::Select => Some(GamepadButton::Select),
+        Button::Start => Some(GamepadButton::Start),
+        Button::Mode => Some(GamepadButton::Mode),
+        Button::LeftThumb => Some(GamepadButton::LeftThumb),
+        Button::RightThumb => Some(GamepadButton::RightThumb),
+        Button::DPadUp => Some(GamepadButton::DPadUp),
+        Button::DPadDown => Some(GamepadButton::DPadDown),
+        Button::DPadLeft => Some(GamepadButton::DPadLeft),
+        Button::DPadRight => Some(GamepadButton::DPadRight),
+        _ => None,
+    }
+}
+
+fn translate_axis(axis: Axis) -> Option<GamepadAxis> {
+    match axis {
+        Axis::LeftStickX => Some(GamepadAxis::LeftStickX),
+        Axis::LeftStickY => Some(GamepadAxis::LeftStickY),
+        Axis::RightStickX => Some(GamepadAxis::RightStickX),
+        Axis::RightStickY => Some(GamepadAxis::RightStickY),
+        _ => None,
+    }
+}
+
+fn translate_event(id: GamepadId, event: EventType) -> Option<GamepadEvent> {
+    match event {
+        EventType::Connected => Some(GamepadEvent::Connected(id)),
+        EventType::Disconnected => Some(GamepadEvent::Disconnected(id)),
+        EventType::ButtonPressed(button, _) => {
+            translate_button(button).map(|button| GamepadEvent::ButtonPressed(id, button))
+        }
+        EventType::ButtonReleased(button, _) => {
+            translate_button(button).map(|button| GamepadEvent::ButtonReleased(id, button))
+        }
+        // Analog triggers are reported as buttons with a value.
+        EventType::ButtonChanged(Button::LeftTrigger2, value, _) => Some(
+            GamepadEvent::AxisChanged(id, GamepadAxis::LeftTrigger, value),
+        ),
+        EventType::ButtonChanged(Button::RightTrigger2, value, _) => Some(
+            GamepadEvent::AxisChanged(id, GamepadAxis::RightTrigger, value),
+        ),
+        EventType::AxisChanged(axis, value, _) => {
+            translate_axis(axis).map(|axis| GamepadEvent::AxisChanged(id, axis, value))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,1098 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Input system maps raw input (keyboard, mouse, gamepads) to named actions and axes. See
+//! [`InputSystem`] docs for more info and usage examples.
+
+pub mod gamepad;
+#[cfg(feature = "gilrs")]
+pub mod gilrs;
+
+use crate::{
+    core::{algebra::Vector2, reflect::prelude::*, visitor::prelude::*},
+    engine::input::gamepad::{
+        GamepadAxis, GamepadButton, GamepadEvent, GamepadId, GamepadSource, GamepadState,
+    },
+    event::{DeviceEvent, ElementState, Event, MouseScrollDelta, WindowEvent},
+    gui::message::{KeyCode, MouseButton},
+    keyboard::PhysicalKey,
+    utils::{translate_button, translate_key_to_ui},
+};
+use fxhash::{FxHashMap, FxHashSet};
+use std::{fmt::Debug, path::Path};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// A source of a digital (on/off) input signal.
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, Hash, Visit, Reflect, AsRefStr, EnumString, VariantNames,
+)]
+pub enum InputBinding {
+    /// A key on a keyboard. The key is identified by its physical location, so the bindings will
+    /// work the same on every keyboard layout.
+    Key(KeyCode),
+    /// A mouse button.
+    MouseButton(MouseButton),
+    /// A button of any connected gamepad.
+    GamepadButton(GamepadButton),
+    /// A gamepad axis, deflected in the given direction. The binding is considered pressed, if the
+    /// axis value is beyond the press threshold of an action.
+    GamepadAxis {
+        /// An axis of a gamepad.
+        axis: GamepadAxis,
+        /// Direction of the deflection. `true` - positive, `false` - negative.
+        positive: bool,
+    },
+}
+
+impl Default for InputBinding {
+    fn default() -> Self {
+        Self::Key(KeyCode::Unknown)
+    }
+}
+
+/// A source of an analog input signal.
+#[derive(Copy, Clone, Debug, PartialEq, Visit, Reflect, AsRefStr, EnumString, VariantNames)]
+pub enum AxisBinding {
+    /// A pair of digital sources, that produces `-1.0` when the negative source is pressed, `1.0`
+    /// when the positive source is pressed and `0.0` when both or none are pressed. Useful to
+    /// map keys (such as `A`/`D`) to an axis.
+    Buttons {
+        /// A source, that moves the axis in the negative direction.
+        negative: InputBinding,
+        /// A source, that moves the axis in the positive direction.
+        positive: InputBinding,
+    },
+    /// An axis of any connected gamepad. Dead zone of the input axis is applied to the value.
+    GamepadAxis {
+        /// An axis of a gamepad.
+        axis: GamepadAxis,
+        /// Inverts the value of the axis.
+        inverted: bool,
+    },
+    /// Mouse motion (in raw device units) since the previous frame, multiplied by the given scale.
+    MouseMotion {
+        /// Horizontal (`x`) or vertical (`y`) mouse motion.
+        axis: MouseAxis,
+        /// A multiplier for the mouse motion.
+        scale: f32,
+    },
+    /// Mouse wheel scrolling since the previous frame, multiplied by the given scale.
+    MouseWheel {
+        /// A multiplier for the mouse wheel delta.
+        scale: f32,
+    },
+}
+
+impl Default for AxisBinding {
+    fn default() -> Self {
+        Self::Buttons {
+            negative: Default::default(),
+            positive: Default::default(),
+        }
+    }
+}
+
+/// Direction of mouse motion.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum MouseAxis {
+    /// Horizontal motion.
+    #[default]
+    X,
+    /// Vertical motion.
+    Y,
+}
+
+/// Input action is a named digital signal (for example "jump" or "fire"), that could be produced
+/// by any of its bindings.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct InputAction {
+    /// Name of the action.
+    pub name: String,
+    /// A set of sources of the action. The action is considered pressed, if any of its bindings
+    /// is pressed.
+    pub bindings: Vec<InputBinding>,
+    /// A value in `[0; 1]` range, that defines how far a gamepad axis should be deflected to be
+    /// considered pressed. Default is `0.5`.
+    pub press_threshold: f32,
+}
+
+impl Default for InputAction {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            bindings: Default::default(),
+            press_threshold: 0.5,
+        }
+    }
+}
+
+impl InputAction {
+    /// Creates new action without any bindings.
+    pub fn new<N: Into<String>>(name: N) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Adds a new binding to the action.
+    pub fn with_binding(mut self, binding: InputBinding) -> Self {
+        self.bindings.push(binding);
+        self
+    }
+
+    /// Sets desired press threshold of the action.
+    pub fn with_press_threshold(mut self, threshold: f32) -> Self {
+        self.press_threshold = threshold.clamp(0.0, 1.0);
+        self
+    }
+}
+
+/// Input axis is a named analog signal (for example "move_forward" or "look_x"), that could be
+/// produced by any of its bindings.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct InputAxis {
+    /// Name of the axis.
+    pub name: String,
+    /// A set of sources of the axis. The value of the axis is the value of the binding with the
+    /// largest magnitude.
+    pub bindings: Vec<AxisBinding>,
+    /// A value in `[0; 1)` range, that defines the size of the "dead" area around the center of
+    /// gamepad sticks. Values inside the dead zone are clamped to zero, the rest are rescaled to
+    /// cover the full `[-1; 1]` range. Default is `0.15`.
+    pub dead_zone: f32,
+}
+
+impl Default for InputAxis {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            bindings: Default::default(),
+            dead_zone: 0.15,
+        }
+    }
+}
+
+impl InputAxis {
+    /// Creates new axis without any bindings.
+    pub fn new<N: Into<String>>(name: N) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Adds a new binding to the axis.
+    pub fn with_binding(mut self, binding: AxisBinding) -> Self {
+        self.bindings.push(binding);
+        self
+    }
+
+    /// Sets desired dead zone of the axis.
+    pub fn with_dead_zone(mut self, dead_zone: f32) -> Self {
+        self.dead_zone = dead_zone.clamp(0.0, 0.99);
+        self
+    }
+}
+
+/// Action set is a named group of actions and axes, that could be enabled or disabled at once. It
+/// is useful to separate different input contexts of a game, for example "gameplay", "vehicle",
+/// "menu", etc.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct InputActionSet {
+    /// Name of the set.
+    pub name: String,
+    /// Defines whether the set is enabled or not. Actions and axes of disabled sets are never
+    /// active.
+    pub enabled: bool,
+    /// A set of actions of the set.
+    pub actions: Vec<InputAction>,
+    /// A set of axes of the set.
+    pub axes: Vec<InputAxis>,
+}
+
+impl Default for InputActionSet {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            enabled: true,
+            actions: Default::default(),
+            axes: Default::default(),
+        }
+    }
+}
+
+impl InputActionSet {
+    /// Creates new enabled action set without any actions and axes.
+    pub fn new<N: Into<String>>(name: N) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Adds a new action to the set.
+    pub fn with_action(mut self, action: InputAction) -> Self {
+        self.actions.push(action);
+        self
+    }
+
+    /// Adds a new axis to the set.
+    pub fn with_axis(mut self, axis: InputAxis) -> Self {
+        self.axes.push(axis);
+        self
+    }
+
+    /// Sets whether the set is enabled or not.
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Tries to find an action by its name.
+    pub fn action(&self, name: &str) -> Option<&InputAction> {
+        self.actions.iter().find(|a| a.name == name)
+    }
+
+    /// Tries to find an action by its name.
+    pub fn action_mut(&mut self, name: &str) -> Option<&mut InputAction> {
+        self.actions.iter_mut().find(|a| a.name == name)
+    }
+
+    /// Tries to find an axis by its name.
+    pub fn axis(&self, name: &str) -> Option<&InputAxis> {
+        self.axes.iter().find(|a| a.name == name)
+    }
+
+    /// Tries to find an axis by its name.
+    pub fn axis_mut(&mut self, name: &str) -> Option<&mut InputAxis> {
+        self.axes.iter_mut().find(|a| a.name == name)
+    }
+}
+
+/// Input profile is a full set of bindings of a game. Profiles could be saved to and loaded from
+/// files, which makes it possible to store user-defined key bindings.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct InputProfile {
+    /// A set of action sets of the profile.
+    pub action_sets: Vec<InputActionSet>,
+}
+
+impl InputProfile {
+    /// Adds a new action set to the profile.
+    pub fn with_action_set(mut self, action_set: InputActionSet) -> Self {
+        self.action_sets.push(action_set);
+        self
+    }
+
+    /// Tries to find an action set by its name.
+    pub fn action_set(&self, name: &str) -> Option<&InputActionSet> {
+        self.action_sets.iter().find(|s| s.name == name)
+    }
+
+    /// Tries to find an action set by its name.
+    pub fn action_set_mut(&mut self, name: &str) -> Option<&mut InputActionSet> {
+        self.action_sets.iter_mut().find(|s| s.name == name)
+    }
+
+    /// Loads a profile from the given file.
+    pub async fn load<P: AsRef<Path>>(path: P) -> Result<Self, VisitError> {
+        let mut visitor = Visitor::load_binary(path).await?;
+        let mut profile = InputProfile::default();
+        profile.visit("InputProfile", &mut visitor)?;
+        Ok(profile)
+    }
+
+    /// Loads a profile from the given memory buffer.
+    pub fn load_from_memory(data: &[u8]) -> Result<Self, VisitError> {
+        let mut visitor = Visitor::load_from_memory(data)?;
+        let mut profile = InputProfile::default();
+        profile.visit("InputProfile", &mut visitor)?;
+        Ok(profile)
+    }
+
+    /// Saves the profile to the given file.
+    pub fn save<P: AsRef<Path>>(&mut self, path: P) -> VisitResult {
+        let mut visitor = Visitor::new();
+        self.visit("InputProfile", &mut visitor)?;
+        visitor.save_binary(path)
+    }
+
+    /// Saves the profile to a memory buffer.
+    pub fn save_to_vec(&mut self) -> Result<Vec<u8>, VisitError> {
+        let mut visitor = Visitor::new();
+        self.visit("InputProfile", &mut visitor)?;
+        visitor.save_binary_to_vec()
+    }
+}
+
+/// Defines which binding of which action should be replaced when rebinding. See
+/// [`InputSystem::begin_rebinding`] for more info.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RebindTarget {
+    /// Name of an action set.
+    pub action_set: String,
+    /// Name of an action in the set.
+    pub action: String,
+    /// Index of a binding of the action. If the index is out of bounds, a new binding will be
+    /// added instead.
+    pub binding_index: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+struct InputState {
+    keys: FxHashSet<KeyCode>,
+    mouse_buttons: FxHashSet<MouseButton>,
+    gamepads: FxHashMap<GamepadId, GamepadState>,
+    mouse_motion: Vector2<f32>,
+    mouse_wheel: f32,
+}
+
+impl InputState {
+    fn binding_value(&self, binding: &InputBinding) -> f32 {
+        match binding {
+            InputBinding::Key(key) => self.keys.contains(key) as u32 as f32,
+            InputBinding::MouseButton(button) => self.mouse_buttons.contains(button) as u32 as f32,
+            InputBinding::GamepadButton(button) => {
+                self.gamepads.values().any(|g| g.is_button_pressed(*button)) as u32 as f32
+            }
+            InputBinding::GamepadAxis { axis, positive } => {
+                let sign = if *positive { 1.0 } else { -1.0 };
+                self.gamepads
+                    .values()
+                    .map(|g| (g.axis_value(*axis) * sign).max(0.0))
+                    .fold(0.0, f32::max)
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.keys.clear();
+        self.mouse_buttons.clear();
+        self.gamepads.clear();
+        self.mouse_motion = Default::default();
+        self.mouse_wheel = 0.0;
+    }
+
+    fn is_action_pressed(&self, action: &InputAction) -> bool {
+        action.bindings.iter().any(|binding| {
+            let value = self.binding_value(binding);
+            value > 0.0 && value >= action.press_threshold
+        })
+    }
+
+    fn axis_value(&self, axis: &InputAxis) -> f32 {
+        axis.bindings
+            .iter()
+            .map(|binding| match binding {
+                AxisBinding::Buttons { negative, positive } => {
+                    self.binding_value(positive).min(1.0) - self.binding_value(negative).min(1.0)
+                }
+                AxisBinding::GamepadAxis {
+                    axis: gamepad_axis,
+                    inverted,
+                } => {
+                    let value = self
+                        .gamepads
+                        .values()
+                        .map(|g| apply_dead_zone(g.axis_value(*gamepad_axis), axis.dead_zone))
+                        .fold(0.0, max_by_magnitude);
+                    if *inverted {
+                        -value
+                    } else {
+                        value
+                    }
+                }
+                AxisBinding::MouseMotion { axis, scale } => match axis {
+                    MouseAxis::X => self.mouse_motion.x * scale,
+                    MouseAxis::Y => self.mouse_motion.y * scale,
+                },
+                AxisBinding::MouseWheel { scale } => self.mouse_wheel * scale,
+            })
+            .fold(0.0, max_by_magnitude)
+    }
+}
+
+fn max_by_magnitude(a: f32, b: f32) -> f32 {
+    if b.abs() > a.abs() {
+        b
+    } else {
+        a
+    }
+}
+
+fn apply_dead_zone(value: f32, dead_zone: f32) -> f32 {
+    let magnitude = value.abs();
+    if magnitude <= dead_zone {
+        0.0
+    } else {
+        value.signum() * ((magnitude - dead_zone) / (1.0 - dead_zone)).min(1.0)
+    }
+}
+
+/// Input system maps raw input events of keyboard, mouse and gamepads to named actions and axes.
+/// Actions and axes are grouped in action sets, that could be enabled or disabled independently,
+/// which allows you to have separate input contexts (for example - "gameplay" and "menu"). A full
+/// set of bindings is stored in an [`InputProfile`], which could be saved to a file and loaded
+/// back, so your game could let players to rebind controls.
+///
+/// The engine owns an instance of the input system and feeds it with OS events and gamepad events
+/// automatically. The state of the input is available in plugins and scripts via `ctx.input`:
+///
+/// ```rust
+/// use fyrox_impl::{
+///     core::{reflect::prelude::*, type_traits::prelude::*, visitor::prelude::*},
+///     engine::input::{
+///         AxisBinding, InputAction, InputActionSet, InputAxis, InputBinding, InputProfile,
+///     },
+///     gui::message::KeyCode,
+///     script::{ScriptContext, ScriptTrait},
+/// };
+///
+/// fn gameplay_profile() -> InputProfile {
+///     InputProfile::default().with_action_set(
+///         InputActionSet::new("gameplay")
+///             .with_action(InputAction::new("jump").with_binding(InputBinding::Key(KeyCode::Space)))
+///             .with_axis(InputAxis::new("move_x").with_binding(AxisBinding::Buttons {
+///                 negative: InputBinding::Key(KeyCode::KeyA),
+///                 positive: InputBinding::Key(KeyCode::KeyD),
+///             })),
+///     )
+/// }
+///
+/// #[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+/// #[type_uuid(id = "8d9e2a3c-64c1-4f2b-9f7e-5a1c0d3b7e21")]
+/// struct Player {}
+///
+/// impl ScriptTrait for Player {
+///     fn on_update(&mut self, ctx: &mut ScriptContext) {
+///         if ctx.input.action_just_pressed("jump") {
+///             // Jump.
+///         }
+///
+///         let horizontal_speed = ctx.input.axis_value("move_x") * 5.0;
+///     }
+/// }
+/// ```
+///
+/// The profile could be set using [`Self::set_profile`], usually it is done in [`crate::plugin::Plugin::init`].
+#[derive(Default)]
+pub struct InputSystem {
+    profile: InputProfile,
+    current: InputState,
+    previous: InputState,
+    // Every input that was active at any moment of the current frame. It is used to detect presses,
+    // that were released before the end of the frame.
+    latched: InputState,
+    gamepad_sources: Vec<Box<dyn GamepadSource>>,
+    gamepad_events: Vec<GamepadEvent>,
+    rebind_target: Option<RebindTarget>,
+    rebind_result: Option<(RebindTarget, InputBinding)>,
+}
+
+impl Debug for InputSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InputSystem")
+            .field("profile", &self.profile)
+            .field("gamepad_sources", &self.gamepad_sources.len())
+            .finish()
+    }
+}
+
+impl InputSystem {
+    /// Creates new input system with the given profile.
+    pub fn new(profile: InputProfile) -> Self {
+        Self {
+            profile,
+            ..Default::default()
+        }
+    }
+
+    /// Sets new input profile. Current state of the input devices is preserved.
+    pub fn set_profile(&mut self, profile: InputProfile) -> InputProfile {
+        std::mem::replace(&mut self.profile, profile)
+    }
+
+    /// Returns a reference to the current input profile.
+    pub fn profile(&self) -> &InputProfile {
+        &self.profile
+    }
+
+    /// Returns a reference to the current input profile. Could be used to add or modify bindings
+    /// at runtime.
+    pub fn profile_mut(&mut self) -> &mut InputProfile {
+        &mut self.profile
+    }
+
+    /// Enables or disables the action set with the given name. Returns `false` if there's no such
+    /// action set.
+    pub fn set_action_set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        if let Some(action_set) = self.profile.action_set_mut(name) {
+            action_set.enabled = enabled;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns `true` if the action set with the given name exists and is enabled.
+    pub fn is_action_set_enabled(&self, name: &str) -> bool {
+        self.profile.action_set(name).is_some_and(|s| s.enabled)
+    }
+
+    fn enabled_actions<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a InputAction> + 'a {
+        self.profile
+            .action_sets
+            .iter()
+            .filter(|s| s.enabled)
+            .filter_map(move |s| s.action(name))
+    }
+
+    /// Returns `true` if any binding of the action is pressed. The action is searched in every
+    /// enabled action set.
+    pub fn action_pressed(&self, name: &str) -> bool {
+        self.enabled_actions(name)
+            .any(|a| self.current.is_action_pressed(a))
+    }
+
+    fn is_action_pressed_in(&self, name: &str, state: &InputState) -> bool {
+        self.enabled_actions(name)
+            .any(|a| state.is_action_pressed(a))
+    }
+
+    /// Returns `true` if the action was pressed during the current frame. Short presses, that were
+    /// released before the end of the frame, are reported as well.
+    pub fn action_just_pressed(&self, name: &str) -> bool {
+        !self.is_action_pressed_in(name, &self.previous)
+            && (self.action_pressed(name) || self.is_action_pressed_in(name, &self.latched))
+    }
+
+    /// Returns `true` if the action was released during the current frame. Short presses, that were
+    /// released before the end of the frame, are reported as well.
+    pub fn action_just_released(&self, name: &str) -> bool {
+        !self.action_pressed(name)
+            && (self.is_action_pressed_in(name, &self.previous)
+                || self.is_action_pressed_in(name, &self.latched))
+    }
+
+    /// Returns the value of the axis with the given name. The axis is searched in every enabled
+    /// action set, if there's multiple axes with the same name, the value with the largest magnitude
+    /// is returned. Returns `0.0` if there's no such axis.
+    pub fn axis_value(&self, name: &str) -> f32 {
+        self.profile
+            .action_sets
+            .iter()
+            .filter(|s| s.enabled)
+            .filter_map(|s| s.axis(name))
+            .map(|a| self.current.axis_value(a))
+            .fold(0.0, max_by_magnitude)
+    }
+
+    /// Returns `true` if the given key is pressed. Prefer actions over raw keys, since the
+    /// actions could be rebound.
+    pub fn is_key_pressed(&self, key: KeyCode) -> bool {
+        self.current.keys.contains(&key)
+    }
+
+    /// Returns `true` if the given mouse button is pressed. Prefer actions over raw buttons, since
+    /// the actions could be rebound.
+    pub fn is_mouse_button_pressed(&self, button: MouseButton) -> bool {
+        self.current.mouse_buttons.contains(&button)
+    }
+
+    /// Returns mouse motion (in raw device units) since the previous frame.
+    pub fn mouse_motion(&self) -> Vector2<f32> {
+        self.current.mouse_motion
+    }
+
+    /// Returns an iterator over every connected gamepad and its state.
+    pub fn gamepads(&self) -> impl Iterator<Item = (GamepadId, &GamepadState)> {
+        self.current.gamepads.iter().map(|(id, state)| (*id, state))
+    }
+
+    /// Adds a new gamepad source. See [`GamepadSource`] docs for more info.
+    pub fn add_gamepad_source(&mut self, source: Box<dyn GamepadSource>) {
+        self.gamepad_sources.push(source);
+    }
+
+    /// Replaces the given binding of an action. Returns `false` if there's no such action set or
+    /// action. If the binding index is out of bounds, a new binding will be added.
+    pub fn rebind_action(&mut self, target: &RebindTarget, binding: InputBinding) -> bool {
+        let Some(action) = self
+            .profile
+            .action_set_mut(&target.action_set)
+            .and_then(|s| s.action_mut(&target.action))
+        else {
+            return false;
+        };
+
+        if let Some(existing) = action.bindings.get_mut(target.binding_index) {
+            *existing = binding;
+        } else {
+            action.bindings.push(binding);
+        }
+
+        true
+    }
+
+    /// Starts interactive rebinding. The next pressed key, mouse button, gamepad button or
+    /// deflected gamepad axis will replace the binding defined by the target. The input that was
+    /// captured is not passed to actions. Use [`Self::take_rebind_result`] to check whether the
+    /// rebinding has finished.
+    pub fn begin_rebinding(&mut self, target: RebindTarget) {
+        self.rebind_target = Some(target);
+        self.rebind_result = None;
+    }
+
+    /// Cancels interactive rebinding.
+    pub fn cancel_rebinding(&mut self) {
+        self.rebind_target = None;
+    }
+
+    /// Returns `true` if interactive rebinding is in progress.
+    pub fn is_rebinding(&self) -> bool {
+        self.rebind_target.is_some()
+    }
+
+    /// Returns the result of the last interactive rebinding (if any). The result is returned only
+    /// once.
+    pub fn take_rebind_result(&mut self) -> Option<(RebindTarget, InputBinding)> {
+        self.rebind_result.take()
+    }
+
+    fn try_capture(&mut self, binding: InputBinding) -> bool {
+        if let Some(target) = self.rebind_target.take() {
+            if self.rebind_action(&target, binding) {
+                self.rebind_result = Some((target, binding));
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Processes an OS event and updates the state of the keyboard and mouse. This method is
+    /// called by the engine automatically.
+    pub fn process_os_event(&mut self, event: &Event<()>) {
+        match event {
+            Event::WindowEvent { event, .. } => match event {
+                WindowEvent::KeyboardInput { event, .. } => {
+                    if let PhysicalKey::Code(code) = event.physical_key {
+                        let key = translate_key_to_ui(code);
+                        match event.state {
+                            ElementState::Pressed => {
+                                if !event.repeat && !self.try_capture(InputBinding::Key(key)) {
+                                    self.current.keys.insert(key);
+                                    self.latched.keys.insert(key);
+                                }
+                            }
+                            ElementState::Released => {
+                                self.current.keys.remove(&key);
+                            }
+                        }
+                    }
+                }
+                WindowEvent::MouseInput { state, button, .. } => {
+                    let button = translate_button(*button);
+                    match state {
+                        ElementState::Pressed => {
+                            if !self.try_capture(InputBinding::MouseButton(button)) {
+                                self.current.mouse_buttons.insert(button);
+                                self.latched.mouse_buttons.insert(button);
+                            }
+                        }
+                        ElementState::Released => {
+                            self.current.mouse_buttons.remove(&button);
+                        }
+                    }
+                }
+                WindowEvent::MouseWheel { delta, .. } => {
+                    self.current.mouse_wheel += match delta {
+                        MouseScrollDelta::LineDelta(_, y) => *y,
+                        MouseScrollDelta::PixelDelta(position) => position.y as f32,
+                    };
+                }
+                WindowEvent::Focused(false) => {
+                    // Release events won't be received when the window is not focused.
+                    self.current.keys.clear();
+                    self.current.mouse_buttons.clear();
+                }
+                _ => (),
+            },
+            Event::DeviceEvent {
+                event: DeviceEvent::MouseMotion { delta },
+                ..
+            } => {
+                self.current.mouse_motion += Vector2::new(delta.0 as f32, delta.1 as f32);
+            }
+            _ => (),
+        }
+    }
+
+    /// Applies the given gamepad event. This method could be used to feed gamepad events directly
+    /// without a gamepad source.
+    pub fn process_gamepad_event(&mut self, event: GamepadEvent) {
+        match event {
+            GamepadEvent::Connected(id) => {
+                self.current.gamepads.entry(id).or_default();
+            }
+            GamepadEvent::Disconnected(id) => {
+                self.current.gamepads.remove(&id);
+            }
+            GamepadEvent::ButtonPressed(id, button) => {
+                if !self.try_capture(InputBinding::GamepadButton(button)) {
+                    for state in [&mut self.current, &mut self.latched] {
+                        state.gamepads.entry(id).or_default().buttons.insert(button);
+                    }
+                }
+            }
+            GamepadEvent::ButtonReleased(id, button) => {
+                if let Some(gamepad) = self.current.gamepads.get_mut(&id) {
+                    gamepad.buttons.remove(&button);
+                }
+            }
+            GamepadEvent::AxisChanged(id, axis, value) => {
+                let value = value.clamp(-1.0, 1.0);
+                if value.abs() >= 0.5
+                    && self.try_capture(InputBinding::GamepadAxis {
+                        axis,
+                        positive: value > 0.0,
+                    })
+                {
+                    return;
+                }
+                self.current
+                    .gamepads
+                    .entry(id)
+                    .or_default()
+                    .axes
+                    .insert(axis, value);
+                let latched = self
+                    .latched
+                    .gamepads
+                    .entry(id)
+                    .or_default()
+                    .axes
+                    .entry(axis)
+                    .or_default();
+                *latched = max_by_magnitude(*latched, value);
+            }
+        }
+    }
+
+    /// Polls every gamepad source and applies the events. This method is called by the engine
+    /// automatically at the beginning of each update.
+    pub fn poll_gamepads(&mut self) {
+        let mut events = std::mem::take(&mut self.gamepad_events);
+        for source in self.gamepad_sources.iter_mut() {
+            source.poll_events(&mut events);
+        }
+        for event in events.drain(..) {
+            self.process_gamepad_event(event);
+        }
+        self.gamepad_events = events;
+    }
+
+    /// Finishes the current frame: remembers the current state to detect "just pressed/released"
+    /// transitions on the next frame, forgets the presses latched during the frame and resets
+    /// accumulated mouse motion and wheel. This method is called by the engine automatically at the
+    /// end of each update.
+    pub fn end_frame(&mut self) {
+        self.previous.clone_from(&self.current);
+        self.latched.clear();
+        self.current.mouse_motion = Default::default();
+        self.current.mouse_wheel = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::engine::input::{
+        gamepad::{GamepadAxis, GamepadButton, GamepadEvent, GamepadId, GamepadSource},
+        AxisBinding, InputAction, InputActionSet, InputAxis, InputBinding, InputProfile,
+        InputSystem, RebindTarget,
+    };
+    use crate::gui::message::KeyCode;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default, Clone)]
+    struct SyntheticGamepad {
+        queue: Arc<Mutex<Vec<GamepadEvent>>>,
+    }
+
+    impl SyntheticGamepad {
+        fn push(&self, event: GamepadEvent) {
+            self.queue.lock().unwrap().push(event);
+        }
+    }
+
+    impl GamepadSource for SyntheticGamepad {
+        fn poll_events(&mut self, events: &mut Vec<GamepadEvent>) {
+            events.append(&mut self.queue.lock().unwrap());
+        }
+    }
+
+    const PAD: GamepadId = GamepadId(0);
+
+    fn make_input() -> (InputSystem, SyntheticGamepad) {
+        let profile = InputProfile::default()
+            .with_action_set(
+                InputActionSet::new("gameplay")
+                    .with_action(
+                        InputAction::new("jump")
+                            .with_binding(InputBinding::Key(KeyCode::Space))
+                            .with_binding(InputBinding::GamepadButton(GamepadButton::South)),
+                    )
+                    .with_action(InputAction::new("accelerate").with_binding(
+                        InputBinding::GamepadAxis {
+                            axis: GamepadAxis::RightTrigger,
+                            positive: true,
+                        },
+                    ))
+                    .with_axis(
+                        InputAxis::new("move_x")
+                            .with_binding(AxisBinding::Buttons {
+                                negative: InputBinding::Key(KeyCode::KeyA),
+                                positive: InputBinding::Key(KeyCode::KeyD),
+                            })
+                            .with_binding(AxisBinding::GamepadAxis {
+                                axis: GamepadAxis::LeftStickX,
+                                inverted: false,
+                            })
+                            .with_dead_zone(0.2),
+                    ),
+            )
+            .with_action_set(
+                InputActionSet::new("menu").with_enabled(false).with_action(
+                    InputAction::new("confirm")
+                        .with_binding(InputBinding::GamepadButton(GamepadButton::South)),
+                ),
+            );
+        let mut input = InputSystem::new(profile);
+        let gamepad = SyntheticGamepad::default();
+        input.add_gamepad_source(Box::new(gamepad.clone()));
+        gamepad.push(GamepadEvent::Connected(PAD));
+        (input, gamepad)
+    }
+
+    fn frame(input: &mut InputSystem, gamepad: &SyntheticGamepad, events: &[GamepadEvent]) {
+        input.end_frame();
+        for event in events {
+            gamepad.push(*event);
+        }
+        input.poll_gamepads();
+    }
+
+    #[test]
+    fn test_action_transitions() {
+        let (mut input, gamepad) = make_input();
+        frame(&mut input, &gamepad, &[]);
+        assert_eq!(input.gamepads().count(), 1);
+        assert!(!input.action_pressed("jump"));
+
+        frame(
+            &mut input,
+            &gamepad,
+            &[GamepadEvent::ButtonPressed(PAD, GamepadButton::South)],
+        );
+        assert!(input.action_pressed("jump"));
+        assert!(input.action_just_pressed("jump"));
+        assert!(!input.action_just_released("jump"));
+
+        frame(&mut input, &gamepad, &[]);
+        assert!(input.action_pressed("jump"));
+        assert!(!input.action_just_pressed("jump"));
+
+        frame(
+            &mut input,
+            &gamepad,
+            &[GamepadEvent::ButtonReleased(PAD, GamepadButton::South)],
+        );
+        assert!(!input.action_pressed("jump"));
+        assert!(input.action_just_released("jump"));
+
+        frame(
+            &mut input,
+            &gamepad,
+            &[GamepadEvent::AxisChanged(
+                PAD,
+                GamepadAxis::RightTrigger,
+                0.3,
+            )],
+        );
+        assert!(!input.action_pressed("accelerate"));
+        frame(
+            &mut input,
+            &gamepad,
+            &[GamepadEvent::AxisChanged(
+                PAD,
+                GamepadAxis::RightTrigger,
+                0.8,
+            )],
+        );
+        assert!(input.action_just_pressed("accelerate"));
+
+        frame(&mut input, &gamepad, &[GamepadEvent::Disconnected(PAD)]);
+        assert!(input.action_just_released("accelerate"));
+        assert_eq!(input.gamepads().count(), 0);
+    }
+
+    #[test]
+    fn test_press_and_release_in_one_frame() {
+        let (mut input, gamepad) = make_input();
+        frame(&mut input, &gamepad, &[]);
+
+        frame(
+            &mut input,
+            &gamepad,
+            &[
+                GamepadEvent::ButtonPressed(PAD, GamepadButton::South),
+                GamepadEvent::ButtonReleased(PAD, GamepadButton::South),
+            ],
+        );
+        assert!(!input.action_pressed("jump"));
+        assert!(input.action_just_pressed("jump"));
+        assert!(input.action_just_released("jump"));
+
+        frame(&mut input, &gamepad, &[]);
+        assert!(!input.action_just_pressed("jump"));
+        assert!(!input.action_just_released("jump"));
+    }
+
+    #[test]
+    fn test_axis_dead_zone() {
+        let (mut input, gamepad) = make_input();
+        frame(
+            &mut input,
+            &gamepad,
+            &[GamepadEvent::AxisChanged(PAD, GamepadAxis::LeftStickX, 0.1)],
+        );
+        assert_eq!(input.axis_value("move_x"), 0.0);
+
+        frame(
+            &mut input,
+            &gamepad,
+            &[GamepadEvent::AxisChanged(
+                PAD,
+                GamepadAxis::LeftStickX,
+                -0.6,
+            )],
+        );
+        assert!((input.axis_value("move_x") + 0.5).abs() < 1.0e-6);
+
+        frame(
+            &mut input,
+            &gamepad,
+            &[GamepadEvent::AxisChanged(PAD, GamepadAxis::LeftStickX, 1.0)],
+        );
+        assert_eq!(input.axis_value("move_x"), 1.0);
+        assert_eq!(input.axis_value("unknown"), 0.0);
+    }
+
+    #[test]
+    fn test_action_sets() {
+        let (mut input, gamepad) = make_input();
+        frame(
+            &mut input,
+            &gamepad,
+            &[GamepadEvent::ButtonPressed(PAD, GamepadButton::South)],
+        );
+        assert!(input.action_pressed("jump"));
+        assert!(!input.action_pressed("confirm"));
+
+        assert!(input.set_action_set_enabled("gameplay", false));
+        assert!(input.set_action_set_enabled("menu", true));
+        assert!(!input.set_action_set_enabled("unknown", true));
+        assert!(!input.action_pressed("jump"));
+        assert!(input.action_pressed("confirm"));
+        assert!(input.is_action_set_enabled("menu"));
+    }
+
+    #[test]
+    fn test_interactive_rebinding() {
+        let (mut input, gamepad) = make_input();
+        let target = RebindTarget {
+            action_set: "gameplay".to_string(),
+            action: "jump".to_string(),
+            binding_index: 1,
+        };
+        input.begin_rebinding(target.clone());
+        assert!(input.is_rebinding());
+
+        frame(
+            &mut input,
+            &gamepad,
+            &[GamepadEvent::ButtonPressed(PAD, GamepadButton::North)],
+        );
+        assert!(!input.is_rebinding());
+        // Captured input must not trigger the action.
+        assert!(!input.action_pressed("jump"));
+        assert_eq!(
+            input.take_rebind_result(),
+            Some((target, InputBinding::GamepadButton(GamepadButton::North)))
+        );
+        assert_eq!(input.take_rebind_result(), None);
+
+        frame(
+            &mut input,
+            &gamepad,
+            &[
+                GamepadEvent::ButtonReleased(PAD, GamepadButton::North),
+                GamepadEvent::ButtonPressed(PAD, GamepadButton::South),
+            ],
+        );
+        assert!(!input.action_pressed("jump"));
+        frame(
+            &mut input,
+            &gamepad,
+            &[GamepadEvent::ButtonPressed(PAD, GamepadButton::North)],
+        );
+        assert!(input.action_pressed("jump"));
+    }
+
+    #[test]
+    fn test_profile_serialization() {
+        let (mut input, _) = make_input();
+        input
+            .profile_mut()
+            .action_set_mut("gameplay")
+            .unwrap()
+            .action_mut("jump")
+            .unwrap()
+            .bindings[0] = InputBinding::Key(KeyCode::KeyW);
+
+        let data = input.profile_mut().save_to_vec().unwrap();
+        let loaded = InputProfile::load_from_memory(&data).unwrap();
+        assert_eq!(&loaded, input.profile());
+    }
+}
@@ -25,6 +25,7 @@
 
 pub mod error;
 pub mod executor;
+pub mod input;
 pub mod task;
 
 mod hotreload;
@@ -43,18 +44,21 @@ use crate::{
     },
     core::{
         algebra::Vector2,
+        arrayvec::ArrayVec,
         futures::{executor::block_on, future::join_all},
         instant,
         log::Log,
         pool::Handle,
+        profiler::{self, FrameSummary},
         reflect::Reflect,
+        scope_profile,
         task::TaskPool,
         variable::try_inherit_properties,
         visitor::VisitError,
     },
-    engine::{error::EngineError, task::TaskPoolHandler},
+    engine::{error::EngineError, input::InputSystem, task::TaskPoolHandler},
     event::Event,
-    graph::{BaseSceneGraph, NodeMapping, SceneGraph},
+    graph::{BaseSceneGraph, NodeMapping, SceneGraph, SceneGraphNode},
     gui::{
         constructor::WidgetConstructorContainer,
         font::{loader::FontLoader, Font, BUILT_IN_FONT},
@@ -81,13 +85,17 @@ use crate::{
     scene::{
         base::NodeScriptMessage,
         camera::SkyBoxKind,
-        graph::{GraphUpdateSwitches, NodePool},
+        character_controller::CharacterController,
+        collider::Collider,
+        dim2,
+        graph::{physics::CollisionEvent, Graph, GraphUpdateSwitches, NodePool},
         mesh::surface::{self, SurfaceData, SurfaceDataLoader},
         navmesh,
         node::{
             constructor::{new_node_constructor_container, NodeConstructorContainer},
             Node,
         },
+        rigidbody::RigidBody,
         sound::SoundEngine,
         tilemap::{
             brush::{TileMapBrush, TileMapBrushLoader},
@@ -127,6 +135,8 @@ use winit::{
     event_loop::EventLoopWindowTarget,
     window::WindowAttributes,
 };
+#[cfg(not(target_arch = "wasm32"))]
+use {crate::renderer::framework::soft::server::SoftGraphicsServer, std::rc::Rc};
 
 /// Serialization context holds runtime type information that allows to create unknown types using
 /// their UUIDs and a respective constructors.
@@ -164,6 +174,10 @@ pub struct PerformanceStatistics {
 
     /// Amount of time spent in plugins updating.
     pub plugins_time: Duration,
+
+    /// Summary of the previous frame collected by the built-in profiler. It is empty if the
+    /// profiler does not record anything, see [`profiler`] module docs for more info.
+    pub profiler: FrameSummary,
 }
 
 impl Display for PerformanceStatistics {
@@ -172,7 +186,11 @@ impl Display for PerformanceStatistics {
             f,
             "Performance Statistics:\n\tUI: {:?}\n\tScripts: {:?}\n\tPlugins: {:?}",
             self.ui_time, self.scripts_time, self.plugins_time
-        )
+        )?;
+        if !self.profiler.scopes.is_empty() {
+            write!(f, "{}", self.profiler)?;
+        }
+        Ok(())
     }
 }
 
@@ -214,12 +232,29 @@ impl InitializedGraphicsContext {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+/// Graphics context without a window, it renders frames into an off-screen back buffer of the software graphics
+/// server. It could be used to render frames on machines without a GPU and a display (CI, tests, servers that
+/// make previews, etc.). See [`Engine::initialize_headless_graphics_context`] for more info.
+pub struct HeadlessGraphicsContext {
+    /// Current renderer.
+    pub renderer: Renderer,
+
+    /// Graphics server used by the renderer. Its back buffer contains the last rendered frame, use
+    /// [`SoftGraphicsServer::read_back_buffer`] to fetch it.
+    pub server: Rc<SoftGraphicsServer>,
+
+    params: GraphicsContextParams,
+}
+
 /// Graphics context of the engine, it could be in two main states:
 ///
 /// - [`GraphicsContext::Initialized`] - active graphics context, that is fully initialized and ready for use.
 /// - [`GraphicsContext::Uninitialized`] - suspended graphics context, that contains a set of params that could
 /// be used for further initialization.
 ///
+/// There's also [`GraphicsContext::Headless`] state, that has a renderer, but no window.
+///
 /// By default, when you creating an engine, there's no graphics context initialized. It must be initialized
 /// manually (if you need it) on [`Event::Resumed`]. On most operating systems, it is possible to initialize
 /// graphics context right after the engine was created. However Android won't allow you to do this, also on
@@ -237,6 +272,10 @@ pub enum GraphicsContext {
 
     /// Uninitialized (suspended) graphics context. See [`GraphicsContextParams`] docs for more info.
     Uninitialized(GraphicsContextParams),
+
+    /// Graphics context without a window. See [`HeadlessGraphicsContext`] docs for more info.
+    #[cfg(not(target_arch = "wasm32"))]
+    Headless(HeadlessGraphicsContext),
 }
 
 impl GraphicsContext {
@@ -259,6 +298,28 @@ impl GraphicsContext {
             panic!("Graphics context is uninitialized!")
         }
     }
+
+    /// Returns a reference to the renderer of the graphics context (either initialized or headless),
+    /// or [`None`] if the context is uninitialized.
+    pub fn renderer(&self) -> Option<&Renderer> {
+        match self {
+            GraphicsContext::Initialized(ctx) => Some(&ctx.renderer),
+            #[cfg(not(target_arch = "wasm32"))]
+            GraphicsContext::Headless(ctx) => Some(&ctx.renderer),
+            GraphicsContext::Uninitialized(_) => None,
+        }
+    }
+
+    /// Returns a reference to the renderer of the graphics context (either initialized or headless),
+    /// or [`None`] if the context is uninitialized.
+    pub fn renderer_mut(&mut self) -> Option<&mut Renderer> {
+        match self {
+            GraphicsContext::Initialized(ctx) => Some(&mut ctx.renderer),
+            #[cfg(not(target_arch = "wasm32"))]
+            GraphicsContext::Headless(ctx) => Some(&mut ctx.renderer),
+            GraphicsContext::Uninitialized(_) => None,
+        }
+    }
 }
 
 struct SceneLoadingOptions {
@@ -493,6 +554,10 @@ pub struct Engine {
 
     /// Script processor is used to run script methods in a strict order.
     pub script_processor: ScriptProcessor,
+
+    /// Input system of the engine. It maps raw input of keyboard, mouse and gamepads to named
+    /// actions and axes. See [`InputSystem`] docs for more info.
+    pub input: InputSystem,
 }
 
 /// Performs dispatch of script messages.
@@ -544,6 +609,7 @@ impl ScriptMessageDispatcher {
         elapsed_time: f32,
         message_sender: &ScriptMessageSender,
         user_interfaces: &mut UiContainer,
+        input: &mut InputSystem,
         graphics_context: &mut GraphicsContext,
         task_pool: &mut TaskPoolHandler,
     ) {
@@ -575,6 +641,7 @@ impl ScriptMessageDispatcher {
                                 task_pool,
                                 graphics_context,
                                 user_interfaces,
+                                input,
                                 script_index: 0,
                             };
 
@@ -601,6 +668,7 @@ impl ScriptMessageDispatcher {
                                     task_pool,
                                     graphics_context,
                                     user_interfaces,
+                                    input,
                                     script_index: 0,
                                 };
 
@@ -627,6 +695,7 @@ impl ScriptMessageDispatcher {
                                     task_pool,
                                     graphics_context,
                                     user_interfaces,
+                                    input,
                                     script_index: 0,
                                 };
 
@@ -652,6 +721,7 @@ impl ScriptMessageDispatcher {
                                 task_pool,
                                 graphics_context,
                                 user_interfaces,
+                                input,
                                 script_index: 0,
                             };
 
@@ -688,6 +758,15 @@ impl ScriptProcessor {
         self.scripted_scenes.iter().any(|s| s.handle == scene)
     }
 
+    /// Returns script message sender of the given scene. The scene must be registered as scripted
+    /// scene, otherwise the method returns `None`.
+    pub fn message_sender(&self, scene: Handle<Scene>) -> Option<&ScriptMessageSender> {
+        self.scripted_scenes
+            .iter()
+            .find(|s| s.handle == scene)
+            .map(|s| &s.message_sender)
+    }
+
     fn register_scripted_scene(
         &mut self,
         scene: Handle<Scene>,
@@ -715,6 +794,7 @@ impl ScriptProcessor {
         task_pool: &mut TaskPoolHandler,
         graphics_context: &mut GraphicsContext,
         user_interfaces: &mut UiContainer,
+        input: &mut InputSystem,
         dt: f32,
         elapsed_time: f32,
     ) {
@@ -787,6 +867,7 @@ impl ScriptProcessor {
                     task_pool,
                     graphics_context,
                     user_interfaces,
+                    input,
                     script_index: 0,
                 };
 
@@ -883,6 +964,48 @@ impl ScriptProcessor {
                 }
             }
 
+            // Deliver collision events of the last physics step. Events are copied, because scripts
+            // could modify the physics world.
+            let collision_events = scene
+                .graph
+                .physics
+                .collision_events()
+                .iter()
+                .chain(scene.graph.physics2d.collision_events())
+                .cloned()
+                .collect::<Vec<_>>();
+            if !collision_events.is_empty() {
+                let mut context = ScriptContext {
+                    dt,
+                    elapsed_time,
+                    plugins: PluginsRefMut(plugins),
+                    handle: Default::default(),
+                    scene,
+                    scene_handle: scripted_scene.handle,
+                    resource_manager,
+                    message_sender: &scripted_scene.message_sender,
+                    message_dispatcher: &mut scripted_scene.message_dispatcher,
+                    task_pool,
+                    graphics_context,
+                    user_interfaces,
+                    input,
+                    script_index: 0,
+                };
+
+                for event in collision_events {
+                    for event in [event, event.swapped()] {
+                        for receiver in collision_event_receivers(&context.scene.graph, &event) {
+                            context.handle = receiver;
+                            process_node_scripts(&mut context, &mut |script, context| {
+                                if script.started {
+                                    script.on_collision(&event, context);
+                                }
+                            });
+                        }
+                    }
+                }
+            }
+
             // Dispatch script messages only when everything is initialized and updated. This has to
             // be done this way, because all those methods could spawn new messages. However, if a new
             // message is spawned directly in `on_message` the dispatcher will correctly handle it
@@ -896,6 +1019,7 @@ impl ScriptProcessor {
                 elapsed_time,
                 &scripted_scene.message_sender,
                 user_interfaces,
+                input,
                 graphics_context,
                 task_pool,
             );
@@ -1123,6 +1247,63 @@ where
     true
 }
 
+/// Returns a list of nodes, that must receive the given collision event - the collider itself and
+/// its rigid body (or a character controller). The list is empty if the event is filtered out by
+/// the collider.
+fn collision_event_receivers(graph: &Graph, event: &CollisionEvent) -> ArrayVec<Handle<Node>, 2> {
+    let mut receivers = ArrayVec::new();
+
+    let Some(collider) = graph.try_get(event.collider) else {
+        return receivers;
+    };
+
+    if collider.component_ref::<CharacterController>().is_some()
+        || collider
+            .component_ref::<dim2::character_controller::CharacterController>()
+            .is_some()
+    {
+        receivers.push(event.collider);
+        return receivers;
+    }
+
+    let event_filter = if let Some(collider) = collider.component_ref::<Collider>() {
+        collider.event_filter()
+    } else if let Some(collider) = collider.component_ref::<dim2::collider::Collider>() {
+        collider.event_filter()
+    } else {
+        return receivers;
+    };
+
+    // The other collider could be already deleted, in this case the event is not filtered.
+    let other_memberships = graph.try_get(event.other).and_then(|other| {
+        other
+            .component_ref::<Collider>()
+            .map(|c| c.collision_groups().memberships)
+            .or_else(|| {
+                other
+                    .component_ref::<dim2::collider::Collider>()
+                    .map(|c| c.collision_groups().memberships)
+            })
+    });
+    if other_memberships.is_some_and(|memberships| event_filter.0 & memberships.0 == 0) {
+        return receivers;
+    }
+
+    receivers.push(event.collider);
+
+    if let Some(parent) = graph.try_get(collider.parent()) {
+        if parent.component_ref::<RigidBody>().is_some()
+            || parent
+                .component_ref::<dim2::rigidbody::RigidBody>()
+                .is_some()
+        {
+            receivers.push(collider.parent());
+        }
+    }
+
+    receivers
+}
+
 fn process_node_scripts<T, C>(context: &mut C, func: &mut T)
 where
     T: FnMut(&mut Script, &mut C),
@@ -1151,6 +1332,7 @@ pub(crate) fn process_scripts<T>(
     task_pool: &mut TaskPoolHandler,
     graphics_context: &mut GraphicsContext,
     user_interfaces: &mut UiContainer,
+    input: &mut InputSystem,
     dt: f32,
     elapsed_time: f32,
     mut func: T,
@@ -1170,6 +1352,7 @@ pub(crate) fn process_scripts<T>(
         task_pool,
         graphics_context,
         user_interfaces,
+        input,
         script_index: 0,
     };
 
@@ -1277,6 +1460,19 @@ pub(crate) fn initialize_resource_manager_loaders(
     state.loaders.set(StyleLoader);
 }
 
+fn make_input_system() -> InputSystem {
+    #[allow(unused_mut)]
+    let mut input = InputSystem::default();
+
+    #[cfg(feature = "gilrs")]
+    match input::gilrs::GilrsGamepadSource::new() {
+        Ok(source) => input.add_gamepad_source(Box::new(source)),
+        Err(err) => Log::err(format!("Unable to initialize gamepad support: {err}")),
+    }
+
+    input
+}
+
 impl Engine {
     /// Creates new instance of engine from given initialization parameters. Automatically creates all sub-systems
     /// (sound, ui, resource manager, etc.) **except** graphics context. Graphics context should be created manually
@@ -1358,6 +1554,7 @@ impl Engine {
             plugins_enabled: false,
             elapsed_time: 0.0,
             task_pool: TaskPoolHandler::new(task_pool),
+            input: make_input_system(),
         })
     }
 
@@ -1438,6 +1635,42 @@ impl Engine {
         }
     }
 
+    /// Tries to initialize a graphics context without a window. The renderer will use the software graphics server
+    /// with a back buffer of the given size, so it does not require a GPU or a display. Use [`Engine::render`] to
+    /// render a frame and [`HeadlessGraphicsContext::server`] to fetch its pixels. It will fail if the graphics
+    /// context is already initialized.
+    ///
+    /// Keep in mind, that software rendering is much slower than hardware one and it supports only a subset of
+    /// the renderer features.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn initialize_headless_graphics_context(
+        &mut self,
+        frame_size: (u32, u32),
+    ) -> Result<(), EngineError> {
+        if let GraphicsContext::Uninitialized(params) = &self.graphics_context {
+            let server = SoftGraphicsServer::new(frame_size)?;
+            let renderer =
+                Renderer::with_server(&self.resource_manager, server.clone(), frame_size)?;
+
+            let (width, height) = server.frame_size();
+            for ui in self.user_interfaces.iter_mut() {
+                ui.set_screen_size(Vector2::new(width as f32, height as f32));
+            }
+
+            self.graphics_context = GraphicsContext::Headless(HeadlessGraphicsContext {
+                renderer,
+                server,
+                params: params.clone(),
+            });
+
+            Ok(())
+        } else {
+            Err(EngineError::Custom(
+                "Graphics context is already initialized!".to_string(),
+            ))
+        }
+    }
+
     /// Tries to destroy current graphics context. It will succeed only if the `graphics_context` is fully initialized.
     /// The method will try to save all possible runtime changes of the window, so the next [`Engine::initialize_graphics_context`]
     /// will result in the almost exact copy of the context that was made before destruction.
@@ -1481,6 +1714,13 @@ impl Engine {
 
             Ok(())
         } else {
+            #[cfg(not(target_arch = "wasm32"))]
+            if let GraphicsContext::Headless(ref ctx) = self.graphics_context {
+                self.graphics_context = GraphicsContext::Uninitialized(ctx.params.clone());
+
+                return Ok(());
+            }
+
             Err(EngineError::Custom(
                 "Graphics context is already destroyed!".to_string(),
             ))
@@ -1490,8 +1730,8 @@ impl Engine {
     /// Adjust size of the frame to be rendered. Must be called after the window size changes.
     /// Will update the renderer and GL context frame size.
     pub fn set_frame_size(&mut self, new_size: (u32, u32)) -> Result<(), FrameworkError> {
-        if let GraphicsContext::Initialized(ctx) = &mut self.graphics_context {
-            ctx.renderer.set_frame_size(new_size)?;
+        if let Some(renderer) = self.graphics_context.renderer_mut() {
+            renderer.set_frame_size(new_size)?;
         }
 
         Ok(())
@@ -1524,8 +1764,10 @@ impl Engine {
         lag: &mut f32,
         switches: FxHashMap<Handle<Scene>, GraphUpdateSwitches>,
     ) {
+        self.end_profiler_frame();
+        scope_profile!("Engine::update");
         self.handle_async_scene_loading(dt, lag, window_target);
-        self.pre_update(dt, window_target, lag, switches);
+        self.pre_update_internal(dt, window_target, lag, switches);
         self.post_update(dt, &Default::default(), lag, window_target);
         self.handle_plugins_hot_reloading(dt, window_target, lag, |_| {});
     }
@@ -1587,6 +1829,7 @@ impl Engine {
                             async_scene_loader: &mut self.async_scene_loader,
                             window_target: Some(window_target),
                             task_pool: &mut self.task_pool,
+                            input: &mut self.input,
                         };
 
                         for plugin in self.plugins.iter_mut() {
@@ -1620,6 +1863,7 @@ impl Engine {
                     async_scene_loader: &mut self.async_scene_loader,
                     window_target: Some(window_target),
                     task_pool: &mut self.task_pool,
+                    input: &mut self.input,
                 };
 
                 match loading_result.result {
@@ -1751,16 +1995,42 @@ impl Engine {
         lag: &mut f32,
         switches: FxHashMap<Handle<Scene>, GraphUpdateSwitches>,
     ) {
+        self.end_profiler_frame();
+        self.pre_update_internal(dt, window_target, lag, switches);
+    }
+
+    // Must be called outside any profiling scope, otherwise the scope will span two frames.
+    fn end_profiler_frame(&mut self) {
+        if profiler::is_recording() {
+            self.performance_statistics.profiler = profiler::end_frame();
+        }
+    }
+
+    fn pre_update_internal(
+        &mut self,
+        dt: f32,
+        window_target: &EventLoopWindowTarget<()>,
+        lag: &mut f32,
+        switches: FxHashMap<Handle<Scene>, GraphUpdateSwitches>,
+    ) {
+        scope_profile!("Engine::pre_update");
         self.resource_manager.state().update(dt);
         self.handle_model_events();
-
-        let window_size = if let GraphicsContext::Initialized(ctx) = &mut self.graphics_context {
-            let inner_size = ctx.window.inner_size();
-            let window_size = Vector2::new(inner_size.width as f32, inner_size.height as f32);
-            ctx.renderer.update_caches(dt);
-            window_size
-        } else {
-            Vector2::new(1.0, 1.0)
+        self.input.poll_gamepads();
+
+        let window_size = match &mut self.graphics_context {
+            GraphicsContext::Initialized(ctx) => {
+                let inner_size = ctx.window.inner_size();
+                let window_size = Vector2::new(inner_size.width as f32, inner_size.height as f32);
+                ctx.renderer.update_caches(dt);
+                window_size
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            GraphicsContext::Headless(ctx) => {
+                ctx.renderer.update_caches(dt);
+                ctx.renderer.get_frame_bounds()
+            }
+            GraphicsContext::Uninitialized(_) => Vector2::new(1.0, 1.0),
         };
 
         for (handle, scene) in self.scenes.pair_iter_mut().filter(|(_, s)| *s.enabled) {
@@ -1799,10 +2069,21 @@ impl Engine {
         lag: &mut f32,
         window_target: &EventLoopWindowTarget<()>,
     ) {
-        if let GraphicsContext::Initialized(ref ctx) = self.graphics_context {
-            let inner_size = ctx.window.inner_size();
-            let window_size = Vector2::new(inner_size.width as f32, inner_size.height as f32);
+        scope_profile!("Engine::post_update");
+        let window_size = match self.graphics_context {
+            GraphicsContext::Initialized(ref ctx) => {
+                let inner_size = ctx.window.inner_size();
+                Some(Vector2::new(
+                    inner_size.width as f32,
+                    inner_size.height as f32,
+                ))
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            GraphicsContext::Headless(ref ctx) => Some(ctx.renderer.get_frame_bounds()),
+            GraphicsContext::Uninitialized(_) => None,
+        };
 
+        if let Some(window_size) = window_size {
             let time = instant::Instant::now();
             for ui in self.user_interfaces.iter_mut() {
                 ui.update(window_size, dt, ui_update_switches);
@@ -1812,6 +2093,8 @@ impl Engine {
 
             self.post_update_plugins(dt, window_target, lag);
         }
+
+        self.input.end_frame();
     }
 
     /// Returns true if the scene is registered for script processing.
@@ -1826,6 +2109,7 @@ impl Engine {
     }
 
     fn handle_scripts(&mut self, dt: f32) {
+        scope_profile!("Engine::handle_scripts");
         let time = instant::Instant::now();
 
         self.script_processor.handle_scripts(
@@ -1835,6 +2119,7 @@ impl Engine {
             &mut self.task_pool,
             &mut self.graphics_context,
             &mut self.user_interfaces,
+            &mut self.input,
             dt,
             self.elapsed_time,
         );
@@ -1869,6 +2154,7 @@ impl Engine {
                         async_scene_loader: &mut self.async_scene_loader,
                         window_target: Some(window_target),
                         task_pool: &mut self.task_pool,
+                        input: &mut self.input,
                     },
                 )
             } else if let Some(node_task_handler) = self.task_pool.pop_node_task_handler(result.id)
@@ -1902,6 +2188,7 @@ impl Engine {
                                         message_sender: &scripted_scene.message_sender,
                                         message_dispatcher: &mut scripted_scene.message_dispatcher,
                                         task_pool: &mut self.task_pool,
+                                        input: &mut self.input,
                                         graphics_context: &mut self.graphics_context,
                                         user_interfaces: &mut self.user_interfaces,
                                         script_index: node_task_handler.script_index,
@@ -1941,6 +2228,7 @@ impl Engine {
         window_target: &EventLoopWindowTarget<()>,
         lag: &mut f32,
     ) {
+        scope_profile!("Engine::update_plugins");
         let time = instant::Instant::now();
 
         if self.plugins_enabled {
@@ -1963,6 +2251,7 @@ impl Engine {
                 async_scene_loader: &mut self.async_scene_loader,
                 window_target: Some(window_target),
                 task_pool: &mut self.task_pool,
+                input: &mut self.input,
             };
 
             for plugin in self.plugins.iter_mut() {
@@ -1996,6 +2285,7 @@ impl Engine {
                         async_scene_loader: &mut self.async_scene_loader,
                         window_target: Some(window_target),
                         task_pool: &mut self.task_pool,
+                        input: &mut self.input,
                     };
 
                     for plugin in self.plugins.iter_mut() {
@@ -2014,6 +2304,7 @@ impl Engine {
         window_target: &EventLoopWindowTarget<()>,
         lag: &mut f32,
     ) {
+        scope_profile!("Engine::post_update_plugins");
         let time = instant::Instant::now();
 
         if self.plugins_enabled {
@@ -2032,6 +2323,7 @@ impl Engine {
                 async_scene_loader: &mut self.async_scene_loader,
                 window_target: Some(window_target),
                 task_pool: &mut self.task_pool,
+                input: &mut self.input,
             };
 
             for plugin in self.plugins.iter_mut() {
@@ -2049,6 +2341,8 @@ impl Engine {
         window_target: &EventLoopWindowTarget<()>,
         lag: &mut f32,
     ) {
+        self.input.process_os_event(event);
+
         if self.plugins_enabled {
             for plugin in self.plugins.iter_mut() {
                 plugin.on_os_event(
@@ -2068,6 +2362,7 @@ impl Engine {
                         async_scene_loader: &mut self.async_scene_loader,
                         window_target: Some(window_target),
                         task_pool: &mut self.task_pool,
+                        input: &mut self.input,
                     },
                 );
             }
@@ -2097,6 +2392,7 @@ impl Engine {
                     async_scene_loader: &mut self.async_scene_loader,
                     window_target: Some(window_target),
                     task_pool: &mut self.task_pool,
+                    input: &mut self.input,
                 });
             }
         }
@@ -2125,6 +2421,7 @@ impl Engine {
                     async_scene_loader: &mut self.async_scene_loader,
                     window_target: Some(window_target),
                     task_pool: &mut self.task_pool,
+                    input: &mut self.input,
                 });
             }
         }
@@ -2153,6 +2450,7 @@ impl Engine {
                     async_scene_loader: &mut self.async_scene_loader,
                     window_target: Some(window_target),
                     task_pool: &mut self.task_pool,
+                    input: &mut self.input,
                 });
             }
         }
@@ -2189,6 +2487,7 @@ impl Engine {
                     &mut self.task_pool,
                     &mut self.graphics_context,
                     &mut self.user_interfaces,
+                    &mut self.input,
                     dt,
                     self.elapsed_time,
                     |script, context| {
@@ -2238,21 +2537,36 @@ impl Engine {
             ui.draw();
         }
 
-        if let GraphicsContext::Initialized(ref mut ctx) = self.graphics_context {
-            // Process queued messages from scene nodes before rendering, this is mandatory to prevent
-            // "teleportation" bug (when an object is drawn at (0,0,0) for one frame and on the one
-            // draws where it should be).
-            for scene in self.scenes.iter_mut() {
-                scene.graph.process_node_messages(None);
-            }
+        if let GraphicsContext::Uninitialized(_) = self.graphics_context {
+            return Ok(());
+        }
 
-            ctx.renderer.render_and_swap_buffers(
-                &self.scenes,
-                self.user_interfaces
-                    .iter()
-                    .map(|ui| ui.get_drawing_context()),
-                &ctx.window,
-            )?;
+        // Process queued messages from scene nodes before rendering, this is mandatory to prevent
+        // "teleportation" bug (when an object is drawn at (0,0,0) for one frame and on the one
+        // draws where it should be).
+        for scene in self.scenes.iter_mut() {
+            scene.graph.process_node_messages(None);
+        }
+
+        let drawing_contexts = self
+            .user_interfaces
+            .iter()
+            .map(|ui| ui.get_drawing_context());
+
+        match self.graphics_context {
+            GraphicsContext::Initialized(ref mut ctx) => {
+                ctx.renderer.render_and_swap_buffers(
+                    &self.scenes,
+                    drawing_contexts,
+                    &ctx.window,
+                )?;
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            GraphicsContext::Headless(ref mut ctx) => {
+                ctx.renderer
+                    .render_offscreen(&self.scenes, drawing_contexts)?;
+            }
+            GraphicsContext::Uninitialized(_) => (),
         }
 
         Ok(())
@@ -2288,6 +2602,7 @@ impl Engine {
                             async_scene_loader: &mut self.async_scene_loader,
                             window_target,
                             task_pool: &mut self.task_pool,
+                            input: &mut self.input,
                         },
                     );
                 }
@@ -2311,6 +2626,7 @@ impl Engine {
                         async_scene_loader: &mut self.async_scene_loader,
                         window_target,
                         task_pool: &mut self.task_pool,
+                        input: &mut self.input,
                     });
                 }
             }
@@ -2521,11 +2837,11 @@ impl Engine {
         }
 
         // Unload custom render passes (if any).
-        if let GraphicsContext::Initialized(ref mut graphics_context) = self.graphics_context {
-            let render_passes = graphics_context.renderer.render_passes().to_vec();
+        if let Some(renderer) = self.graphics_context.renderer_mut() {
+            let render_passes = renderer.render_passes().to_vec();
             for render_pass in render_passes {
                 if render_pass.borrow().source_type_id() == plugin_type_id {
-                    graphics_context.renderer.remove_render_pass(render_pass);
+                    renderer.remove_render_pass(render_pass);
                 }
             }
         }
@@ -2616,6 +2932,7 @@ impl Engine {
             async_scene_loader: &mut self.async_scene_loader,
             window_target: Some(window_target),
             task_pool: &mut self.task_pool,
+            input: &mut self.input,
         });
 
         Log::info(format!("Plugin {plugin_index} was successfully reloaded!"));
@@ -2684,12 +3001,29 @@ mod test {
     use crate::{
         asset::manager::ResourceManager,
         core::{
-            pool::Handle, reflect::prelude::*, task::TaskPool, type_traits::prelude::*,
+            algebra::{Vector2, Vector3},
+            pool::Handle,
+            reflect::prelude::*,
+            task::TaskPool,
+            type_traits::prelude::*,
             visitor::prelude::*,
         },
         engine::{task::TaskPoolHandler, GraphicsContext, ScriptProcessor},
         graph::BaseSceneGraph,
-        scene::{base::BaseBuilder, node::Node, pivot::PivotBuilder, Scene, SceneContainer},
+        scene::{
+            base::BaseBuilder,
+            character_controller::{CharacterController, CharacterControllerBuilder},
+            collider::{ColliderBuilder, ColliderShape},
+            graph::{
+                physics::{CollisionEvent, CollisionEventKind},
+                Graph,
+            },
+            node::Node,
+            pivot::PivotBuilder,
+            rigidbody::{RigidBodyBuilder, RigidBodyType},
+            transform::TransformBuilder,
+            Scene, SceneContainer,
+        },
         script::{
             ScriptContext, ScriptDeinitContext, ScriptMessageContext, ScriptMessagePayload,
             ScriptTrait,
@@ -2892,6 +3226,7 @@ mod test {
                 &mut task_pool,
                 &mut gc,
                 &mut user_interfaces,
+                &mut Default::default(),
                 0.0,
                 0.0,
             );
@@ -3057,6 +3392,7 @@ mod test {
                 &mut task_pool,
                 &mut gc,
                 &mut user_interfaces,
+                &mut Default::default(),
                 0.0,
                 0.0,
             );
@@ -3320,6 +3656,7 @@ mod test {
                 &mut task_pool,
                 &mut gc,
                 &mut user_interfaces,
+                &mut Default::default(),
                 0.0,
                 0.0,
             );
@@ -3398,4 +3735,319 @@ mod test {
             }
         }
     }
+
+    #[derive(Debug, Clone, Reflect, Visit, TypeUuidProvider, ComponentProvider)]
+    #[type_uuid(id = "4d1b6b1e-2f4c-4a8e-9b0e-7c5f3e1a9d42")]
+    struct ScriptListeningToCollisions {
+        #[reflect(hidden)]
+        #[visit(skip)]
+        sender: Sender<CollisionEvent>,
+    }
+
+    impl ScriptTrait for ScriptListeningToCollisions {
+        fn on_collision(&mut self, event: &CollisionEvent, _ctx: &mut ScriptContext) {
+            self.sender.send(*event).unwrap();
+        }
+    }
+
+    fn add_static_box(
+        graph: &mut Graph,
+        position: Vector3<f32>,
+        half_extents: Vector3<f32>,
+        sensor: bool,
+    ) -> Handle<Node> {
+        let collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::cuboid(
+                half_extents.x,
+                half_extents.y,
+                half_extents.z,
+            ))
+            .with_sensor(sensor)
+            .build(graph);
+        RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(position)
+                        .build(),
+                )
+                .with_children(&[collider]),
+        )
+        .with_body_type(RigidBodyType::Static)
+        .build(graph);
+        collider
+    }
+
+    /// Runs physics and scripts of the given scene for the given amount of frames. The closure is
+    /// called before every update of the scene.
+    fn simulate_scene(scene: Scene, frames: usize, mut before_update: impl FnMut(&mut Graph)) {
+        let resource_manager = ResourceManager::new(Arc::new(Default::default()));
+        let mut scene_container = SceneContainer::new(Default::default());
+        let scene_handle = scene_container.add(scene);
+
+        let mut script_processor = ScriptProcessor::default();
+        let mut task_pool = TaskPoolHandler::new(Arc::new(TaskPool::new()));
+        let mut gc = GraphicsContext::Uninitialized(Default::default());
+        let mut user_interfaces = UiContainer::default();
+
+        script_processor.register_scripted_scene(scene_handle, &resource_manager);
+
+        let dt = 1.0 / 60.0;
+        for _ in 0..frames {
+            let graph = &mut scene_container[scene_handle].graph;
+            before_update(graph);
+            graph.update(Vector2::new(800.0, 600.0), dt, Default::default());
+            script_processor.handle_scripts(
+                &mut scene_container,
+                &mut Vec::new(),
+                &resource_manager,
+                &mut task_pool,
+                &mut gc,
+                &mut user_interfaces,
+                &mut Default::default(),
+                dt,
+                0.0,
+            );
+        }
+    }
+
+    #[test]
+    fn test_collision_events() {
+        let mut scene = Scene::new();
+
+        let (tx, rx) = mpsc::channel();
+
+        let floor = add_static_box(
+            &mut scene.graph,
+            Vector3::new(0.0, -0.5, 0.0),
+            Vector3::new(10.0, 0.5, 10.0),
+            false,
+        );
+        let sensor = add_static_box(
+            &mut scene.graph,
+            Vector3::new(0.0, 3.0, 0.0),
+            Vector3::new(1.0, 1.0, 1.0),
+            true,
+        );
+        let ball_collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::ball(0.5))
+            .build(&mut scene.graph);
+        RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 3.0, 0.0))
+                        .build(),
+                )
+                .with_children(&[ball_collider])
+                .with_script(ScriptListeningToCollisions { sender: tx }),
+        )
+        .with_body_type(RigidBodyType::Dynamic)
+        .build(&mut scene.graph);
+
+        simulate_scene(scene, 180, |_| {});
+
+        let events = rx.try_iter().collect::<Vec<_>>();
+        let expected = [
+            CollisionEvent {
+                kind: CollisionEventKind::TriggerEnter,
+                collider: ball_collider,
+                other: sensor,
+            },
+            CollisionEvent {
+                kind: CollisionEventKind::TriggerExit,
+                collider: ball_collider,
+                other: sensor,
+            },
+            CollisionEvent {
+                kind: CollisionEventKind::ContactStarted,
+                collider: ball_collider,
+                other: floor,
+            },
+        ];
+        assert_eq!(events, expected);
+    }
+
+    #[test]
+    fn test_character_hit_events() {
+        let mut scene = Scene::new();
+
+        let (tx, rx) = mpsc::channel();
+
+        let floor = add_static_box(
+            &mut scene.graph,
+            Vector3::new(0.0, -0.5, 0.0),
+            Vector3::new(10.0, 0.5, 10.0),
+            false,
+        );
+        let character = CharacterControllerBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 1.0, 0.0))
+                        .build(),
+                )
+                .with_script(ScriptListeningToCollisions { sender: tx }),
+        )
+        .with_shape(ColliderShape::ball(0.5))
+        .build(&mut scene.graph);
+
+        simulate_scene(scene, 30, |graph| {
+            graph[character]
+                .cast_mut::<CharacterController>()
+                .unwrap()
+                .move_by(Vector3::new(0.0, -0.1, 0.0));
+        });
+
+        let events = rx.try_iter().collect::<Vec<_>>();
+        // The character falls on the floor during the first frames and then hits it on every
+        // movement, each collider is reported once per movement.
+        assert!(!events.is_empty() && events.len() < 30);
+        for event in events {
+            assert_eq!(
+                event,
+                CollisionEvent {
+                    kind: CollisionEventKind::CharacterHit,
+                    collider: character,
+                    other: floor,
+                }
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(not(any(target_os = "macos", target_arch = "wasm32")))]
+    fn test_headless_rendering() {
+        use crate::{
+            asset::untyped::ResourceKind,
+            core::{algebra::Matrix4, color::Color},
+            engine::{Engine, EngineInitParams},
+            material::{Material, MaterialResource},
+            scene::{
+                camera::CameraBuilder,
+                mesh::{
+                    surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+                    MeshBuilder,
+                },
+            },
+        };
+        use std::mem::{ManuallyDrop, MaybeUninit};
+        use winit::event_loop::EventLoop;
+        // See `test_async_script_tasks` for more info about this hack.
+        #[allow(invalid_value)]
+        #[allow(clippy::uninit_assumed_init)]
+        let event_loop =
+            unsafe { ManuallyDrop::new(MaybeUninit::<EventLoop<()>>::uninit().assume_init()) };
+
+        let task_pool = Arc::new(TaskPool::default());
+        let mut engine = Engine::new(EngineInitParams {
+            graphics_context_params: Default::default(),
+            serialization_context: Arc::new(Default::default()),
+            widget_constructors: Arc::new(Default::default()),
+            resource_manager: ResourceManager::new(task_pool.clone()),
+            task_pool,
+        })
+        .unwrap();
+
+        engine
+            .initialize_headless_graphics_context((16, 8))
+            .unwrap();
+        assert!(engine
+            .initialize_headless_graphics_context((16, 8))
+            .is_err());
+
+        let back_buffer = |engine: &mut Engine| {
+            let GraphicsContext::Headless(ref mut ctx) = engine.graphics_context else {
+                unreachable!()
+            };
+            assert_eq!(ctx.server.frame_size(), (16, 8));
+            let pixels = ctx.server.read_back_buffer();
+            assert_eq!(pixels.len(), 16 * 8 * 4);
+            pixels
+                .chunks(4)
+                .map(|p| Color::from_rgba(p[0], p[1], p[2], p[3]))
+                .collect::<Vec<_>>()
+        };
+
+        engine
+            .graphics_context
+            .renderer_mut()
+            .unwrap()
+            .set_backbuffer_clear_color(Color::BLUE);
+
+        // Empty frame.
+        engine.update(1.0 / 60.0, &event_loop, &mut 0.0, Default::default());
+        engine.render().unwrap();
+        assert!(back_buffer(&mut engine).iter().all(|c| *c == Color::BLUE));
+
+        // A red quad right in front of the camera.
+        let mut scene = Scene::new();
+        CameraBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
+        let mut material = Material::standard();
+        material.set_property("diffuseColor", Color::RED);
+        MeshBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 0.0, 2.0))
+                    .build(),
+            ),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+            ResourceKind::Embedded,
+            SurfaceData::make_quad(&Matrix4::identity()),
+        ))
+        .with_material(MaterialResource::new_ok(ResourceKind::Embedded, material))
+        .build()])
+        .build(&mut scene.graph);
+        scene.graph.update_hierarchical_data();
+        let scene_handle = engine.scenes.add(scene);
+
+        engine.update(1.0 / 60.0, &event_loop, &mut 0.0, Default::default());
+        engine.render().unwrap();
+
+        // The scene frame covers the entire back buffer.
+        assert!(back_buffer(&mut engine).iter().all(|c| *c != Color::BLUE));
+
+        // The software server does not run lighting and post-effect shaders, so the output of
+        // the geometry pass is checked instead.
+        let expected = [
+            "................",
+            "................",
+            "................",
+            ".......##.......",
+            ".......##.......",
+            "................",
+            "................",
+            "................",
+        ];
+        let renderer = engine.graphics_context.renderer().unwrap();
+        let diffuse = renderer.scene_data_map[&scene_handle]
+            .gbuffer
+            .diffuse_texture()
+            .borrow()
+            .read_pixels();
+        assert_eq!(diffuse.len(), 16 * 8 * 4);
+        for (y, row) in expected.iter().enumerate() {
+            for (x, symbol) in row.chars().enumerate() {
+                let offset = (y * 16 + x) * 4;
+                let expected_color = if symbol == '#' {
+                    [255, 0, 0, 255]
+                } else {
+                    [0, 0, 0, 0]
+                };
+                assert_eq!(
+                    diffuse[offset..offset + 4],
+                    expected_color,
+                    "pixel at {x}:{y}"
+                );
+            }
+        }
+        assert!(renderer.get_statistics().geometry.draw_calls > 0);
+
+        engine.destroy_graphics_context().unwrap();
+        assert!(matches!(
+            engine.graphics_context,
+            GraphicsContext::Uninitialized(_)
+        ));
+    }
 }
@@ -248,13 +248,14 @@
 //!
 //! Property group. Provided for each rendered surface instance.
 //!
-//! | Name                 | Type       | Description                                 |
-//! |----------------------|------------|---------------------------------------------|
-//! | worldMatrix          | `mat4`     | Local-to-world transformation.              |
-//! | worldViewProjection  | `mat4`     | Local-to-clip-space transform.              |
-//! | blendShapesCount     | `int`      | Total amount of blend shapes.               |
-//! | useSkeletalAnimation | `bool`     | Whether skinned meshes is rendering or not. |
-//! | blendShapesWeights   | `vec4[32]` | Blend shape weights.                        |
+//! | Name                    | Type       | Description                                                           |
+//! |-------------------------|------------|-----------------------------------------------------------------------|
+//! | worldMatrix             | `mat4`     | Local-to-world transformation.                                        |
+//! | worldViewProjection     | `mat4`     | Local-to-clip-space transform.                                        |
+//! | blendShapesCount        | `int`      | Total amount of blend shapes.                                         |
+//! | useSkeletalAnimation    | `bool`     | Whether skinned meshes is rendering or not.                           |
+//! | blendShapesWeights      | `vec4[32]` | Blend shape weights.                                                  |
+//! | prevWorldViewProjection | `mat4`     | Local-to-clip-space transform of the previous frame (without jitter). |
 //!
 //! ### `fyrox_boneMatrices`
 //!
@@ -264,6 +265,16 @@
 //! |----------|-------------|---------------|
 //! | matrices | `mat4[256]` | Bone matrices |
 //!
+//! ### `fyrox_prevBoneMatrices`
+//!
+//! Property group. Provided for each rendered surface, that has skeletal animation. Contains bone
+//! matrices of the previous frame, that can be used to calculate motion vectors. Falls back to the
+//! current bone matrices, if there's no previous state.
+//!
+//! | Name     | Type        | Description                         |
+//! |----------|-------------|-------------------------------------|
+//! | matrices | `mat4[256]` | Bone matrices of the previous frame |
+//!
 //!
 //! ### `fyrox_cameraData`
 //!
@@ -280,6 +291,7 @@
 //! | zNear                | `float`    | Near clipping plane location.                    |
 //! | zFar                 | `float`    | Far clipping plane location.                     |
 //! | zRange               | `float`    | `zFar - zNear`                                   |
+//! | jitter               | `vec2`     | Sub-pixel offset (in NDC) of the projection.     |
 //!
 //! ### `fyrox_lightData`
 //!
@@ -617,6 +629,7 @@ impl ShaderDefinition {
                         ShaderProperty::new("zNear", Float(0.0)),
                         ShaderProperty::new("zFar", Float(0.0)),
                         ShaderProperty::new("zRange", Float(0.0)),
+                        ShaderProperty::new("jitter", Vector2(Default::default())),
                     ]);
                 }
                 "fyrox_lightData" => {
@@ -681,9 +694,13 @@ impl ShaderDefinition {
                                 max_len: Self::MAX_BLEND_SHAPE_WEIGHT_GROUPS,
                             },
                         ),
+                        ShaderProperty::new(
+                            "prevWorldViewProjection",
+                            Matrix4(algebra::Matrix4::identity()),
+                        ),
                     ]);
                 }
-                "fyrox_boneMatrices" => {
+                "fyrox_boneMatrices" | "fyrox_prevBoneMatrices" => {
                     properties.clear();
                     properties.extend([ShaderProperty::new(
                         "matrices",
@@ -112,6 +112,13 @@
             ]),
             binding: 5
         ),
+        (
+            name: "fyrox_prevBoneMatrices",
+            kind: PropertyGroup([
+                // Autogenerated
+            ]),
+            binding: 6
+        ),
     ],
 
     passes: [
@@ -153,10 +160,13 @@
                 out vec3 tangent;
                 out vec3 binormal;
                 out vec2 secondTexCoord;
+                out vec4 currentClipPosition;
+                out vec4 previousClipPosition;
 
                 void main()
                 {
                     vec4 localPosition = vec4(0);
+                    vec4 prevLocalPosition = vec4(0);
                     vec3 localNormal = vec3(0);
                     vec3 localTangent = vec3(0);
 
@@ -189,6 +199,16 @@
                         localPosition += m2 * inputPosition * boneWeights.z;
                         localPosition += m3 * inputPosition * boneWeights.w;
 
+                        mat4 p0 = fyrox_prevBoneMatrices.matrices[i0];
+                        mat4 p1 = fyrox_prevBoneMatrices.matrices[i1];
+                        mat4 p2 = fyrox_prevBoneMatrices.matrices[i2];
+                        mat4 p3 = fyrox_prevBoneMatrices.matrices[i3];
+
+                        prevLocalPosition += p0 * inputPosition * boneWeights.x;
+                        prevLocalPosition += p1 * inputPosition * boneWeights.y;
+                        prevLocalPosition += p2 * inputPosition * boneWeights.z;
+                        prevLocalPosition += p3 * inputPosition * boneWeights.w;
+
                         localNormal += mat3(m0) * inputNormal * boneWeights.x;
                         localNormal += mat3(m1) * inputNormal * boneWeights.y;
                         localNormal += mat3(m2) * inputNormal * boneWeights.z;
@@ -202,6 +222,7 @@
                     else
                     {
                         localPosition = inputPosition;
+                        prevLocalPosition = inputPosition;
                         localNormal = inputNormal;
                         localTangent = inputTangent;
                     }
@@ -215,6 +236,8 @@
                     secondTexCoord = vertexSecondTexCoord;
 
                     gl_Position = fyrox_instanceData.worldViewProjection * localPosition;
+                    currentClipPosition = gl_Position;
+                    previousClipPosition = fyrox_instanceData.prevWorldViewProjection * prevLocalPosition;
                 }
                 "#,
             fragment_shader:
@@ -224,6 +247,7 @@
                 layout(location = 2) out vec4 outAmbient;
                 layout(location = 3) out vec4 outMaterial;
                 layout(location = 4) out uint outDecalMask;
+                layout(location = 5) out vec2 outVelocity;
 
                 in vec3 position;
                 in vec3 normal;
@@ -231,6 +255,8 @@
                 in vec3 tangent;
                 in vec3 binormal;
                 in vec2 secondTexCoord;
+                in vec4 currentClipPosition;
+                in vec4 previousClipPosition;
 
                 void main()
                 {
@@ -271,6 +297,8 @@
                     outAmbient.a = 1.0;
 
                     outDecalMask = properties.layerIndex;
+
+                    outVelocity = S_ComputeVelocity(currentClipPosition, previousClipPosition, fyrox_cameraData.jitter);
                 }
                 "#,
         ),
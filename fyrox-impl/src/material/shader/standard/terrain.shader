@@ -169,6 +169,8 @@
                 out vec3 tangent;
                 out vec3 binormal;
                 out vec2 secondTexCoord;
+                out vec4 currentClipPosition;
+                out vec4 previousClipPosition;
 
                 void main()
                 {
@@ -197,6 +199,8 @@
                     position = vec3(fyrox_instanceData.worldMatrix * finalVertexPosition);
                     secondTexCoord = vertexSecondTexCoord;
                     gl_Position = fyrox_instanceData.worldViewProjection * finalVertexPosition;
+                    currentClipPosition = gl_Position;
+                    previousClipPosition = fyrox_instanceData.prevWorldViewProjection * finalVertexPosition;
                 }
                 "#,
             fragment_shader:
@@ -206,6 +210,8 @@
                 layout(location = 2) out vec4 outAmbient;
                 layout(location = 3) out vec4 outMaterial;
                 layout(location = 4) out uint outDecalMask;
+                // Alpha is used only to prevent layer blending from affecting motion vectors.
+                layout(location = 5) out vec4 outVelocity;
 
                 in vec3 position;
                 in vec3 normal;
@@ -213,6 +219,8 @@
                 in vec3 tangent;
                 in vec3 binormal;
                 in vec2 secondTexCoord;
+                in vec4 currentClipPosition;
+                in vec4 previousClipPosition;
 
                 void main()
                 {
@@ -250,6 +258,8 @@
 
                     outDecalMask = properties.layerIndex;
 
+                    outVelocity = vec4(S_ComputeVelocity(currentClipPosition, previousClipPosition, fyrox_cameraData.jitter), 0.0, 1.0);
+
                     float mask = texture(maskTexture, texCoord).r;
 
                     outColor.a = mask;
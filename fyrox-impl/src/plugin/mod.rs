@@ -32,8 +32,8 @@ use crate::{
         visitor::{Visit, VisitError},
     },
     engine::{
-        task::TaskPoolHandler, AsyncSceneLoader, GraphicsContext, PerformanceStatistics,
-        ScriptProcessor, SerializationContext,
+        input::InputSystem, task::TaskPoolHandler, AsyncSceneLoader, GraphicsContext,
+        PerformanceStatistics, ScriptProcessor, SerializationContext,
     },
     event::Event,
     gui::{
@@ -185,6 +185,10 @@ pub struct PluginContext<'a, 'b> {
 
     /// Task pool for asynchronous task management.
     pub task_pool: &'a mut TaskPoolHandler,
+
+    /// Input system of the engine. It could be used to query the state of input actions and axes,
+    /// switch action sets, rebind actions, etc. See [`InputSystem`] docs for more info.
+    pub input: &'a mut InputSystem,
 }
 
 /// Base plugin automatically implements type casting for plugins.
@@ -19,7 +19,7 @@
 // SOFTWARE.
 
 use crate::{
-    core::{math::Rect, sstorage::ImmutableString},
+    core::{math::Rect, scope_profile, sstorage::ImmutableString},
     renderer::{
         bloom::blur::GaussianBlur,
         cache::uniform::UniformBufferCache,
@@ -109,6 +109,7 @@ impl BloomRenderer {
         hdr_scene_frame: Rc<RefCell<dyn GpuTexture>>,
         uniform_buffer_cache: &mut UniformBufferCache,
     ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!("BloomRenderer::render");
         let mut stats = RenderPassStatistics::default();
 
         let viewport = Rect::new(0, 0, self.width as i32, self.height as i32);
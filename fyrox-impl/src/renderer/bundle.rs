@@ -25,7 +25,7 @@
 use crate::{
     asset::untyped::ResourceKind,
     core::{
-        algebra::{Matrix4, Vector3, Vector4},
+        algebra::{Matrix4, Vector2, Vector3, Vector4},
         arrayvec::ArrayVec,
         color,
         color::Color,
@@ -184,6 +184,59 @@ pub struct RenderDataBundle {
     /// A render path of the bundle.
     pub render_path: RenderPath,
     sort_index: u64,
+    /// State of each instance in the previous frame. Could be empty, if there's no motion history
+    /// applied to the bundle (see [`RenderDataBundleStorage::apply_motion_history`]).
+    previous_instances: Vec<PreviousInstanceData>,
+}
+
+/// State of a surface instance in the previous frame, that is used to calculate motion vectors.
+struct PreviousInstanceData {
+    /// World-view-projection matrix (without jitter) of the previous frame.
+    world_view_projection: Matrix4<f32>,
+    /// Bone matrices of the previous frame. Empty, if the instance is not skinned or there's no
+    /// previous state (current bone matrices will be used instead).
+    bone_matrices: Vec<Matrix4<f32>>,
+}
+
+#[derive(Copy, Clone, Hash, PartialEq, Eq)]
+struct MotionInstanceKey {
+    data: u64,
+    material: u64,
+    node_handle: Handle<Node>,
+    index: usize,
+}
+
+struct MotionInstanceState {
+    world_transform: Matrix4<f32>,
+    bone_matrices: Vec<Matrix4<f32>>,
+}
+
+/// Motion history keeps the state of every rendered surface instance of an observer from the
+/// previous frame. It is used to produce per-pixel motion vectors (velocity buffer), that are
+/// needed for temporal effects such as temporal anti-aliasing or motion blur. Each observer
+/// (camera) must have its own history.
+#[derive(Default)]
+pub struct MotionHistory {
+    view_projection: Option<Matrix4<f32>>,
+    instances: FxHashMap<MotionInstanceKey, MotionInstanceState>,
+}
+
+impl MotionHistory {
+    /// Returns `true` if the history contains the data of the previous frame.
+    pub fn is_valid(&self) -> bool {
+        self.view_projection.is_some()
+    }
+
+    /// Returns view-projection matrix (without jitter) of the previous frame, if any.
+    pub fn view_projection(&self) -> Option<Matrix4<f32>> {
+        self.view_projection
+    }
+
+    /// Resets the history. Motion vectors of the next frame will contain zeros.
+    pub fn reset(&mut self) {
+        self.view_projection = None;
+        self.instances.clear();
+    }
 }
 
 impl Debug for RenderDataBundle {
@@ -204,6 +257,9 @@ pub struct InstanceUniformData {
     pub instance_block: UniformBlockLocation,
     /// Bone matrices block location. Could be [`None`], if there's no bone matrices.
     pub bone_matrices_block: Option<UniformBlockLocation>,
+    /// Bone matrices of the previous frame block location. Could be [`None`], if there's no
+    /// previous bone matrices or the shader does not use them.
+    pub prev_bone_matrices_block: Option<UniformBlockLocation>,
 }
 
 /// Describes where to the actual uniform data is located in the memory backed by the uniform
@@ -334,6 +390,21 @@ fn write_shader_values<T: ByteStorage>(
     }
 }
 
+fn write_bone_matrices(
+    bone_matrices: &[Matrix4<f32>],
+    uniform_memory_allocator: &mut UniformMemoryAllocator,
+) -> UniformBlockLocation {
+    const INIT: Matrix4<f32> = Matrix4::new(
+        0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+    );
+    let mut matrices = [INIT; ShaderDefinition::MAX_BONE_MATRICES];
+    const SIZE: usize = ShaderDefinition::MAX_BONE_MATRICES * size_of::<Matrix4<f32>>();
+    let count = bone_matrices.len().min(ShaderDefinition::MAX_BONE_MATRICES);
+    matrices[0..count].copy_from_slice(&bone_matrices[0..count]);
+
+    uniform_memory_allocator.allocate(StaticUniformBuffer::<SIZE>::new().with_slice(&matrices))
+}
+
 impl RenderDataBundle {
     /// Writes all the required uniform data of the bundle to uniform memory allocator.
     pub fn write_uniforms(
@@ -384,38 +455,54 @@ impl RenderDataBundle {
             .with(&render_context.ambient_light.as_frgba());
         let light_data_block = render_context.uniform_memory_allocator.allocate(light_data);
 
+        // Previous bone matrices are quite heavy, upload them only if they're actually used.
+        let uses_prev_bone_matrices = shader
+            .definition
+            .resources
+            .iter()
+            .any(|resource| resource.name.as_str() == "fyrox_prevBoneMatrices");
+
         // Upload instance uniforms.
         let mut instance_blocks = Vec::with_capacity(self.instances.len());
-        for instance in self.instances.iter() {
+        for (i, instance) in self.instances.iter().enumerate() {
+            let world_view_projection = view_projection_matrix * instance.world_transform;
+            let previous = self.previous_instances.get(i);
+            let prev_world_view_projection =
+                previous.map_or(world_view_projection, |p| p.world_view_projection);
+
             let instance_buffer = StaticUniformBuffer::<1024>::new()
                 .with(&instance.world_transform)
-                .with(&(view_projection_matrix * instance.world_transform))
+                .with(&world_view_projection)
                 .with(&(instance.blend_shapes_weights.len() as i32))
                 .with(&(!instance.bone_matrices.is_empty()))
                 .with_slice_with_max_size(
                     &instance.blend_shapes_weights,
                     ShaderDefinition::MAX_BLEND_SHAPE_WEIGHT_GROUPS,
-                );
+                )
+                .with(&prev_world_view_projection);
 
             let mut instance_uniform_data = InstanceUniformData {
                 instance_block: render_context
                     .uniform_memory_allocator
                     .allocate(instance_buffer),
                 bone_matrices_block: None,
+                prev_bone_matrices_block: None,
             };
 
             if !instance.bone_matrices.is_empty() {
-                const INIT: Matrix4<f32> = Matrix4::new(
-                    0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
-                );
-                let mut matrices = [INIT; ShaderDefinition::MAX_BONE_MATRICES];
-                const SIZE: usize = ShaderDefinition::MAX_BONE_MATRICES * size_of::<Matrix4<f32>>();
-                matrices[0..instance.bone_matrices.len()].copy_from_slice(&instance.bone_matrices);
-
-                let bone_matrices_block = render_context
-                    .uniform_memory_allocator
-                    .allocate(StaticUniformBuffer::<SIZE>::new().with_slice(&matrices));
-                instance_uniform_data.bone_matrices_block = Some(bone_matrices_block);
+                instance_uniform_data.bone_matrices_block = Some(write_bone_matrices(
+                    &instance.bone_matrices,
+                    render_context.uniform_memory_allocator,
+                ));
+
+                if uses_prev_bone_matrices {
+                    if let Some(previous) = previous.filter(|p| !p.bone_matrices.is_empty()) {
+                        instance_uniform_data.prev_bone_matrices_block = Some(write_bone_matrices(
+                            &previous.bone_matrices,
+                            render_context.uniform_memory_allocator,
+                        ));
+                    }
+                }
             }
 
             instance_blocks.push(instance_uniform_data);
@@ -608,6 +695,33 @@ impl RenderDataBundle {
                             }
                         }
                     }
+                    "fyrox_prevBoneMatrices" => {
+                        // Use current bone matrices if there's no previous ones, this way the
+                        // motion will be calculated using the instance transform only.
+                        match uniform_data
+                            .prev_bone_matrices_block
+                            .or(uniform_data.bone_matrices_block)
+                        {
+                            Some(block) => {
+                                instance_bindings.push(
+                                    render_context
+                                        .uniform_memory_allocator
+                                        .block_to_binding(block, resource_definition.binding),
+                                );
+                            }
+                            None => {
+                                instance_bindings.push(ResourceBinding::Buffer {
+                                    buffer: &*render_context
+                                        .fallback_resources
+                                        .bone_matrices_stub_uniform_buffer,
+                                    binding: BufferLocation::Explicit {
+                                        binding: resource_definition.binding,
+                                    },
+                                    data_usage: Default::default(),
+                                });
+                            }
+                        }
+                    }
                     _ => (),
                 };
             }
@@ -721,6 +835,7 @@ pub struct RenderDataBundleStorage {
     /// A sorted list of bundles.
     pub bundles: Vec<RenderDataBundle>,
     pub light_sources: Vec<LightSource>,
+    jitter: Vector2<f32>,
 }
 
 pub struct RenderDataBundleStorageOptions {
@@ -742,6 +857,7 @@ impl RenderDataBundleStorage {
             observer_info,
             bundles: Default::default(),
             light_sources: Default::default(),
+            jitter: Default::default(),
         }
     }
 
@@ -761,6 +877,7 @@ impl RenderDataBundleStorage {
             observer_info: observer_info.clone(),
             bundles: Vec::with_capacity(capacity),
             light_sources: Default::default(),
+            jitter: Default::default(),
         };
 
         let frustum = Frustum::from_view_projection_matrix(
@@ -873,6 +990,73 @@ impl RenderDataBundleStorage {
         self.bundles.sort_unstable_by_key(|b| b.sort_index);
     }
 
+    /// Returns sub-pixel offset (in NDC units) of the projection matrix of the observer.
+    pub fn jitter(&self) -> Vector2<f32> {
+        self.jitter
+    }
+
+    /// Matches every surface instance in the storage with its state from the previous frame and
+    /// then replaces the history with the current state. `view_projection` is a view-projection
+    /// matrix of the observer without `jitter`, where `jitter` is a sub-pixel offset (in NDC units)
+    /// that was applied to the projection matrix of the observer. Instances that were not rendered
+    /// in the previous frame are treated as static objects, so they will move only with the
+    /// observer. Previous state is available in shaders using `prevWorldViewProjection` field of
+    /// `fyrox_instanceData` and `fyrox_prevBoneMatrices` property group.
+    pub fn apply_motion_history(
+        &mut self,
+        history: &mut MotionHistory,
+        view_projection: Matrix4<f32>,
+        jitter: Vector2<f32>,
+    ) {
+        self.jitter = jitter;
+
+        let prev_view_projection = history.view_projection.unwrap_or(view_projection);
+        let mut instances =
+            FxHashMap::with_capacity_and_hasher(history.instances.len(), FxBuildHasher::default());
+        let mut occurrences = FxHashMap::<(u64, u64, Handle<Node>), usize>::default();
+        for bundle in self.bundles.iter_mut() {
+            let data = bundle.data.key();
+            let material = bundle.material.key();
+            bundle.previous_instances.clear();
+            for instance in bundle.instances.iter() {
+                // The same surface could be rendered multiple times by a single node.
+                let occurrence = occurrences
+                    .entry((data, material, instance.node_handle))
+                    .or_default();
+                let key = MotionInstanceKey {
+                    data,
+                    material,
+                    node_handle: instance.node_handle,
+                    index: *occurrence,
+                };
+                *occurrence += 1;
+
+                let previous = match history.instances.remove(&key) {
+                    Some(state) => PreviousInstanceData {
+                        world_view_projection: prev_view_projection * state.world_transform,
+                        bone_matrices: state.bone_matrices,
+                    },
+                    None => PreviousInstanceData {
+                        world_view_projection: prev_view_projection * instance.world_transform,
+                        bone_matrices: Default::default(),
+                    },
+                };
+                bundle.previous_instances.push(previous);
+
+                instances.insert(
+                    key,
+                    MotionInstanceState {
+                        world_transform: instance.world_transform,
+                        bone_matrices: instance.bone_matrices.clone(),
+                    },
+                );
+            }
+        }
+
+        history.instances = instances;
+        history.view_projection = Some(view_projection);
+    }
+
     pub fn write_global_uniform_blocks(
         &self,
         render_context: &mut BundleRenderContext,
@@ -944,7 +1128,8 @@ impl RenderDataBundleStorage {
             .with(&camera_side)
             .with(&self.observer_info.z_near)
             .with(&self.observer_info.z_far)
-            .with(&(self.observer_info.z_far - self.observer_info.z_near));
+            .with(&(self.observer_info.z_far - self.observer_info.z_near))
+            .with(&self.jitter);
         let camera_block = render_context
             .uniform_memory_allocator
             .allocate(camera_uniforms);
@@ -1087,6 +1272,7 @@ impl RenderDataBundleStorageTrait for RenderDataBundleStorage {
                 render_path,
                 // Temporary buffer lives one frame.
                 time_to_live: TimeToLive(0.0),
+                previous_instances: Default::default(),
             });
             self.bundles.last_mut().unwrap()
         };
@@ -1128,6 +1314,7 @@ impl RenderDataBundleStorageTrait for RenderDataBundleStorage {
                 material: material.clone(),
                 render_path,
                 time_to_live: Default::default(),
+                previous_instances: Default::default(),
             });
             self.bundles.last_mut().unwrap()
         };
@@ -1135,3 +1322,115 @@ impl RenderDataBundleStorageTrait for RenderDataBundleStorage {
         bundle.instances.push(instance_data)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::untyped::ResourceKind,
+        core::{
+            algebra::{Matrix4, Vector2, Vector3},
+            pool::Handle,
+        },
+        material::{Material, MaterialResource},
+        renderer::{
+            bundle::{
+                MotionHistory, ObserverInfo, RenderDataBundleStorage, RenderDataBundleStorageTrait,
+                SurfaceInstanceData,
+            },
+            framework::ElementRange,
+        },
+        scene::mesh::{
+            surface::{SurfaceData, SurfaceResource},
+            RenderPath,
+        },
+    };
+
+    fn translation(x: f32) -> Matrix4<f32> {
+        Matrix4::new_translation(&Vector3::new(x, 0.0, 0.0))
+    }
+
+    fn make_storage(
+        data: &SurfaceResource,
+        material: &MaterialResource,
+        transforms: &[Matrix4<f32>],
+    ) -> RenderDataBundleStorage {
+        let mut storage = RenderDataBundleStorage::new_empty(ObserverInfo::default());
+        for transform in transforms {
+            storage.push(
+                data,
+                material,
+                RenderPath::Deferred,
+                0,
+                SurfaceInstanceData {
+                    world_transform: *transform,
+                    bone_matrices: Default::default(),
+                    blend_shapes_weights: Default::default(),
+                    element_range: ElementRange::Full,
+                    node_handle: Handle::new(1, 1),
+                },
+            );
+        }
+        storage
+    }
+
+    fn previous_transforms(storage: &RenderDataBundleStorage) -> Vec<Matrix4<f32>> {
+        storage.bundles[0]
+            .previous_instances
+            .iter()
+            .map(|instance| instance.world_view_projection)
+            .collect()
+    }
+
+    #[test]
+    fn test_apply_motion_history() {
+        let data = SurfaceResource::new_ok(
+            ResourceKind::Embedded,
+            SurfaceData::make_cube(Matrix4::identity()),
+        );
+        let material = MaterialResource::new_ok(ResourceKind::Embedded, Material::standard());
+        let mut history = MotionHistory::default();
+        let jitter = Vector2::new(0.25, -0.5);
+
+        // There's no history, previous state must match the current one.
+        let mut storage = make_storage(&data, &material, &[translation(1.0)]);
+        storage.apply_motion_history(&mut history, translation(10.0), jitter);
+        assert_eq!(storage.jitter(), jitter);
+        assert_eq!(
+            previous_transforms(&storage),
+            [translation(10.0) * translation(1.0)]
+        );
+        assert!(history.is_valid());
+        assert_eq!(history.view_projection(), Some(translation(10.0)));
+
+        // The node is rendered twice now, the second instance has no previous state.
+        let mut storage = make_storage(&data, &material, &[translation(2.0), translation(3.0)]);
+        storage.apply_motion_history(&mut history, translation(20.0), Vector2::default());
+        assert_eq!(
+            previous_transforms(&storage),
+            [
+                translation(10.0) * translation(1.0),
+                translation(10.0) * translation(3.0)
+            ]
+        );
+
+        // Each instance must be matched with its own state.
+        let mut storage = make_storage(&data, &material, &[translation(4.0), translation(5.0)]);
+        storage.apply_motion_history(&mut history, translation(30.0), Vector2::default());
+        assert_eq!(
+            previous_transforms(&storage),
+            [
+                translation(20.0) * translation(2.0),
+                translation(20.0) * translation(3.0)
+            ]
+        );
+
+        history.reset();
+        assert!(!history.is_valid());
+        let mut storage = make_storage(&data, &material, &[translation(6.0)]);
+        storage.apply_motion_history(&mut history, translation(40.0), Vector2::default());
+        assert_eq!(
+            previous_transforms(&storage),
+            [translation(40.0) * translation(6.0)]
+        );
+    }
+}
@@ -28,6 +28,7 @@ use crate::{
     core::{
         algebra::{Matrix4, Vector3},
         math::Rect,
+        scope_profile,
         sstorage::ImmutableString,
     },
     renderer::{
@@ -165,6 +166,7 @@ impl DebugRenderer {
         framebuffer: &mut dyn FrameBuffer,
         view_projection: Matrix4<f32>,
     ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!("DebugRenderer::render");
         let mut statistics = RenderPassStatistics::default();
 
         let uniform_buffer =
@@ -21,7 +21,7 @@
 //! Forward renderer is used to render transparent meshes and meshes with custom blending options.
 
 use crate::{
-    core::{color::Color, math::Rect, sstorage::ImmutableString},
+    core::{color::Color, math::Rect, scope_profile, sstorage::ImmutableString},
     renderer::{
         bundle::{BundleRenderContext, RenderDataBundleStorage},
         cache::{shader::ShaderCache, texture::TextureCache, uniform::UniformMemoryAllocator},
@@ -65,6 +65,7 @@ impl ForwardRenderer {
         &self,
         args: ForwardRenderContext,
     ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!("ForwardRenderer::render");
         let mut statistics = RenderPassStatistics::default();
 
         let ForwardRenderContext {
@@ -19,7 +19,7 @@
 // SOFTWARE.
 
 use crate::{
-    core::{algebra::Vector2, math::Rect, sstorage::ImmutableString},
+    core::{algebra::Vector2, math::Rect, scope_profile, sstorage::ImmutableString},
     renderer::make_viewport_matrix,
     renderer::{
         cache::uniform::UniformBufferCache,
@@ -85,6 +85,7 @@ impl FxaaRenderer {
         frame_buffer: &mut dyn FrameBuffer,
         uniform_buffer_cache: &mut UniformBufferCache,
     ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!("FxaaRenderer::render");
         let mut statistics = RenderPassStatistics::default();
 
         let frame_matrix = make_viewport_matrix(viewport);
@@ -25,6 +25,8 @@
 //! RT2: RGBA16F - Ambient light + emission (both in xyz)
 //! RT3: RGBA8 - Metallic (x) + Roughness (y) + Ambient Occlusion (z)
 //! RT4: R8UI - Decal mask (x)
+//! RT5: RG16F - Screen-space velocity (xy), difference between current and previous positions of
+//! a fragment in texture coordinates.
 //!
 //! Every alpha channel is used for layer blending for terrains. This is inefficient, but for
 //! now I don't know better solution.
@@ -34,6 +36,7 @@ use crate::{
         algebra::{Matrix4, Vector2},
         color::Color,
         math::Rect,
+        scope_profile,
         sstorage::ImmutableString,
     },
     renderer::{
@@ -135,6 +138,10 @@ impl GBuffer {
                     kind: AttachmentKind::Color,
                     texture: server.create_2d_render_target(PixelKind::R8UI, width, height)?,
                 },
+                Attachment {
+                    kind: AttachmentKind::Color,
+                    texture: server.create_2d_render_target(PixelKind::RG16F, width, height)?,
+                },
             ],
         )?;
 
@@ -196,10 +203,15 @@ impl GBuffer {
         self.framebuffer.color_attachments()[4].texture.clone()
     }
 
+    pub fn velocity_texture(&self) -> Rc<RefCell<dyn GpuTexture>> {
+        self.framebuffer.color_attachments()[5].texture.clone()
+    }
+
     pub(crate) fn fill(
         &mut self,
         args: GBufferRenderContext,
     ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!("GBuffer::fill");
         let mut statistics = RenderPassStatistics::default();
 
         let GBufferRenderContext {
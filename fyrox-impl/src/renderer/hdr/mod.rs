@@ -23,7 +23,7 @@ use crate::{
         algebra::{Matrix4, Vector2},
         color::Color,
         math::Rect,
-        transmute_slice, value_as_u8_slice,
+        scope_profile, transmute_slice, value_as_u8_slice,
     },
     renderer::{
         cache::{texture::TextureCache, uniform::UniformBufferCache},
@@ -448,6 +448,7 @@ impl HighDynamicRangeRenderer {
         texture_cache: &mut TextureCache,
         uniform_buffer_cache: &mut UniformBufferCache,
     ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!("HighDynamicRangeRenderer::render");
         let mut stats = RenderPassStatistics::default();
         stats +=
             self.calculate_frame_luminance(hdr_scene_frame.clone(), quad, uniform_buffer_cache)?;
@@ -24,6 +24,7 @@ use crate::{
         algebra::{Matrix4, Point3, UnitQuaternion, Vector2, Vector3},
         color::Color,
         math::{frustum::Frustum, Matrix4Ext, Rect, TriangleDefinition},
+        scope_profile,
     },
     renderer::{
         bundle::{LightSourceKind, RenderDataBundleStorage},
@@ -277,6 +278,7 @@ impl DeferredLightRenderer {
         &mut self,
         args: DeferredRendererContext,
     ) -> Result<(RenderPassStatistics, LightingStatistics), FrameworkError> {
+        scope_profile!("DeferredLightRenderer::render");
         let mut pass_stats = RenderPassStatistics::default();
         let mut light_stats = LightingStatistics::default();
 
@@ -50,6 +50,7 @@ mod shadow;
 mod skybox_shader;
 mod ssao;
 mod stats;
+mod taa;
 
 use crate::{
     asset::{event::ResourceEvent, manager::ResourceManager},
@@ -62,11 +63,12 @@ use crate::{
         math::Rect,
         pool::Handle,
         reflect::prelude::*,
+        scope_profile,
         sstorage::ImmutableString,
         uuid_provider,
     },
     engine::{error::EngineError, GraphicsContextParams},
-    graph::SceneGraph,
+    graph::{BaseSceneGraph, SceneGraph},
     gui::draw::DrawingContext,
     material::shader::{Shader, ShaderDefinition, ShaderResource, ShaderResourceExtension},
     renderer::{
@@ -101,11 +103,12 @@ use crate::{
         gbuffer::{GBuffer, GBufferRenderContext},
         hdr::HighDynamicRangeRenderer,
         light::{DeferredLightRenderer, DeferredRendererContext},
+        taa::{TaaRenderer, TemporalHistory},
         ui_renderer::{UiRenderContext, UiRenderer},
         visibility::VisibilityCache,
     },
     resource::texture::{Texture, TextureKind, TextureResource},
-    scene::{camera::Camera, mesh::surface::SurfaceData, Scene, SceneContainer},
+    scene::{camera::Camera, mesh::surface::SurfaceData, node::Node, Scene, SceneContainer},
 };
 use fxhash::FxHashMap;
 use lazy_static::lazy_static;
@@ -242,6 +245,11 @@ pub struct QualitySettings {
     /// Whether to use Fast Approximate AntiAliasing or not.
     pub fxaa: bool,
 
+    /// Whether to use Temporal AntiAliasing or not. It could be combined with FXAA, but usually
+    /// it is enough to use just one of them. Disabled by default.
+    #[serde(default)]
+    pub use_taa: bool,
+
     /// Whether to use Parallax Mapping or not.
     pub use_parallax_mapping: bool,
 
@@ -290,6 +298,7 @@ impl QualitySettings {
             spot_shadow_map_precision: ShadowMapPrecision::Full,
 
             fxaa: true,
+            use_taa: false,
 
             use_bloom: true,
 
@@ -326,6 +335,7 @@ impl QualitySettings {
             spot_shadow_map_precision: ShadowMapPrecision::Full,
 
             fxaa: true,
+            use_taa: false,
 
             use_bloom: true,
 
@@ -367,6 +377,7 @@ impl QualitySettings {
             spot_shadow_map_precision: ShadowMapPrecision::Half,
 
             fxaa: true,
+            use_taa: false,
 
             use_bloom: true,
 
@@ -408,6 +419,7 @@ impl QualitySettings {
             spot_shadow_map_precision: ShadowMapPrecision::Half,
 
             fxaa: false,
+            use_taa: false,
 
             use_bloom: false,
 
@@ -506,6 +518,9 @@ pub struct AssociatedSceneData {
 
     /// Rendering statistics for a scene.
     pub statistics: SceneStatistics,
+
+    /// Temporal state (motion history, jitter, etc.) of every camera of the scene.
+    pub temporal_histories: FxHashMap<Handle<Node>, TemporalHistory>,
 }
 
 impl AssociatedSceneData {
@@ -589,6 +604,7 @@ impl AssociatedSceneData {
             ldr_scene_framebuffer,
             ldr_temp_framebuffer,
             statistics: Default::default(),
+            temporal_histories: Default::default(),
         })
     }
 
@@ -715,6 +731,7 @@ pub struct Renderer {
     geometry_cache: GeometryCache,
     forward_renderer: ForwardRenderer,
     fxaa_renderer: FxaaRenderer,
+    taa_renderer: TaaRenderer,
     texture_event_receiver: Receiver<ResourceEvent>,
     shader_event_receiver: Receiver<ResourceEvent>,
     // TextureId -> FrameBuffer mapping. This mapping is used for temporal frame buffers
@@ -941,6 +958,29 @@ impl Renderer {
         window_target: &EventLoopWindowTarget<()>,
         window_builder: WindowBuilder,
     ) -> Result<(Window, Self), EngineError> {
+        let (window, server) = GlGraphicsServer::new(
+            params.vsync,
+            params.msaa_sample_count,
+            window_target,
+            window_builder,
+        )?;
+
+        let frame_size = (window.inner_size().width, window.inner_size().height);
+
+        let renderer = Self::with_server(resource_manager, server, frame_size)?;
+
+        Ok((window, renderer))
+    }
+
+    /// Creates a new renderer that uses the given graphics server. This method could be used to
+    /// create a renderer without a window, for example with
+    /// [`SoftGraphicsServer`](crate::renderer::framework::soft::server::SoftGraphicsServer) to
+    /// render frames in headless environments (CI, tests, etc.).
+    pub fn with_server(
+        resource_manager: &ResourceManager,
+        server: SharedGraphicsServer,
+        frame_size: (u32, u32),
+    ) -> Result<Self, EngineError> {
         let settings = QualitySettings::default();
 
         let (texture_event_sender, texture_event_receiver) = std::sync::mpsc::channel();
@@ -957,18 +997,9 @@ impl Renderer {
             .event_broadcaster
             .add(shader_event_sender);
 
-        let (window, server) = GlGraphicsServer::new(
-            params.vsync,
-            params.msaa_sample_count,
-            window_target,
-            window_builder,
-        )?;
-
         let caps = server.capabilities();
         Log::info(format!("Graphics Server Capabilities\n{caps}",));
 
-        let frame_size = (window.inner_size().width, window.inner_size().height);
-
         let mut shader_cache = ShaderCache::default();
 
         for shader in ShaderResource::standard_shaders() {
@@ -1115,6 +1146,7 @@ impl Renderer {
             forward_renderer: ForwardRenderer::new(),
             ui_frame_buffers: Default::default(),
             fxaa_renderer: FxaaRenderer::new(&*server)?,
+            taa_renderer: TaaRenderer::new(&*server)?,
             statistics: Statistics::default(),
             shader_event_receiver,
             texture_event_receiver,
@@ -1126,7 +1158,7 @@ impl Renderer {
             uniform_memory_allocator,
         };
 
-        Ok((window, renderer))
+        Ok(renderer)
     }
 
     /// Adds a custom render pass.
@@ -1365,6 +1397,7 @@ impl Renderer {
         scene: &Scene,
         dt: f32,
     ) -> Result<&AssociatedSceneData, FrameworkError> {
+        scope_profile!("Renderer::render_scene");
         let graph = &scene.graph;
 
         let backbuffer_width = self.frame_size.0 as f32;
@@ -1448,14 +1481,32 @@ impl Renderer {
 
             let viewport = camera.viewport_pixels(frame_size);
 
-            let bundle_storage = RenderDataBundleStorage::from_graph(
+            let temporal_history = scene_associated_data
+                .temporal_histories
+                .entry(camera_handle)
+                .or_default();
+
+            // Shift the projection by a sub-pixel offset each frame, so temporal anti-aliasing
+            // could accumulate more samples per pixel over time.
+            let jitter = if self.quality_settings.use_taa {
+                temporal_history.next_jitter(viewport)
+            } else {
+                Vector2::default()
+            };
+            let view_projection = camera.view_projection_matrix();
+            let prev_view_projection = temporal_history.motion.view_projection();
+            let is_taa_history_valid = temporal_history.is_frame_valid;
+
+            let mut bundle_storage = RenderDataBundleStorage::from_graph(
                 graph,
                 ObserverInfo {
                     observer_position: camera.global_position(),
                     z_near: camera.projection().z_near(),
                     z_far: camera.projection().z_far(),
                     view_matrix: camera.view_matrix(),
-                    projection_matrix: camera.projection_matrix(),
+                    projection_matrix: Matrix4::new_translation(&Vector3::new(
+                        jitter.x, jitter.y, 0.0,
+                    )) * camera.projection_matrix(),
                 },
                 GBUFFER_PASS_NAME.clone(),
                 RenderDataBundleStorageOptions {
@@ -1463,6 +1514,12 @@ impl Renderer {
                 },
             );
 
+            bundle_storage.apply_motion_history(
+                &mut temporal_history.motion,
+                view_projection,
+                jitter,
+            );
+
             server.set_polygon_fill_mode(
                 PolygonFace::FrontAndBack,
                 scene.rendering_options.polygon_rasterization_mode,
@@ -1593,6 +1650,72 @@ impl Renderer {
                 &mut self.uniform_buffer_cache,
             )?;
 
+            // Apply TAA if needed.
+            if self.quality_settings.use_taa {
+                let reprojection = prev_view_projection
+                    .filter(|_| is_taa_history_valid)
+                    .map(|prev| prev * view_projection.try_inverse().unwrap_or_default());
+
+                let history_frame_texture = scene_associated_data
+                    .temporal_histories
+                    .entry(camera_handle)
+                    .or_default()
+                    .history_frame_buffer(
+                        server,
+                        scene_associated_data.gbuffer.width as usize,
+                        scene_associated_data.gbuffer.height as usize,
+                    )?
+                    .color_attachments()[0]
+                    .texture
+                    .clone();
+
+                scene_associated_data.statistics += self.taa_renderer.render(
+                    viewport,
+                    scene_associated_data.ldr_scene_frame_texture(),
+                    history_frame_texture,
+                    &scene_associated_data.gbuffer,
+                    reprojection,
+                    &mut *scene_associated_data.ldr_temp_framebuffer,
+                    &mut self.uniform_buffer_cache,
+                )?;
+
+                // Resolved frame is the final frame and the history of the camera for the next frame
+                // at the same time.
+                let history_frame_buffer = scene_associated_data
+                    .temporal_histories
+                    .get(&camera_handle)
+                    .and_then(|history| history.frame_buffer.as_deref());
+                for frame_buffer in std::iter::once(&*scene_associated_data.ldr_scene_framebuffer)
+                    .chain(history_frame_buffer)
+                {
+                    scene_associated_data.ldr_temp_framebuffer.blit_to(
+                        frame_buffer,
+                        viewport.x(),
+                        viewport.y(),
+                        viewport.x() + viewport.w(),
+                        viewport.y() + viewport.h(),
+                        viewport.x(),
+                        viewport.y(),
+                        viewport.x() + viewport.w(),
+                        viewport.y() + viewport.h(),
+                        true,
+                        false,
+                        false,
+                    );
+                }
+            }
+
+            if let Some(temporal_history) = scene_associated_data
+                .temporal_histories
+                .get_mut(&camera_handle)
+            {
+                temporal_history.is_frame_valid = self.quality_settings.use_taa;
+                if !self.quality_settings.use_taa {
+                    // Release the history, it will be re-created once TAA is enabled again.
+                    temporal_history.frame_buffer = None;
+                }
+            }
+
             // Apply FXAA if needed.
             if self.quality_settings.fxaa {
                 scene_associated_data.statistics += self.fxaa_renderer.render(
@@ -1650,6 +1773,11 @@ impl Renderer {
             }
         }
 
+        // Remove temporal state of deleted cameras.
+        scene_associated_data
+            .temporal_histories
+            .retain(|handle, _| graph.is_valid_handle(*handle));
+
         self.visibility_cache.update(graph);
 
         // Optionally render everything into back buffer.
@@ -1676,6 +1804,7 @@ impl Renderer {
         scenes: &SceneContainer,
         drawing_contexts: impl Iterator<Item = &'a DrawingContext>,
     ) -> Result<(), FrameworkError> {
+        scope_profile!("Renderer::render_frame");
         if self.frame_size.0 == 0 || self.frame_size.1 == 0 {
             return Ok(());
         }
@@ -1746,6 +1875,22 @@ impl Renderer {
         Ok(())
     }
 
+    /// Renders the given scenes and user interfaces into the back buffer of the graphics server
+    /// without presenting it to a window. It is useful for renderers created with
+    /// [`Self::with_server`], the back buffer content could then be fetched from the server.
+    pub fn render_offscreen<'a>(
+        &mut self,
+        scenes: &SceneContainer,
+        drawing_contexts: impl Iterator<Item = &'a DrawingContext>,
+    ) -> Result<(), FrameworkError> {
+        self.render_frame(scenes, drawing_contexts)?;
+        self.statistics.end_frame();
+        self.graphics_server().swap_buffers()?;
+        self.statistics.finalize();
+        self.statistics.pipeline = self.server.pipeline_statistics();
+        Ok(())
+    }
+
     pub(crate) fn render_and_swap_buffers<'a>(
         &mut self,
         scenes: &SceneContainer,
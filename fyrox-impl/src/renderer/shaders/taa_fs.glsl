@@ -0,0 +1,79 @@
+// Temporal anti-aliasing. Accumulates jittered frames over time by blending current frame with
+// reprojected history, history color is clamped to the color range of the current pixel
+// neighborhood to reduce ghosting.
+
+uniform sampler2D currentTexture;
+uniform sampler2D historyTexture;
+uniform sampler2D velocityTexture;
+uniform sampler2D depthTexture;
+
+layout (std140) uniform Uniforms {
+    mat4 worldViewProjection;
+    // Transforms current NDC position (without jitter) into previous clip space position.
+    mat4 reprojectionMatrix;
+    // Viewport of the camera in pixels (x, y, width, height).
+    vec4 viewport;
+    vec2 inverseFrameSize;
+    float blendFactor;
+    bool historyValid;
+};
+
+out vec4 FragColor;
+
+void main()
+{
+    vec2 pixel = gl_FragCoord.xy;
+    vec2 texCoord = pixel * inverseFrameSize;
+
+    vec3 current = texture(currentTexture, texCoord).rgb;
+
+    if (!historyValid) {
+        FragColor = vec4(current, 1.0);
+        return;
+    }
+
+    // Gather color range of the neighborhood and find the closest fragment in it. Velocity of the
+    // closest fragment is used to keep edges of moving objects sharp.
+    vec3 minColor = current;
+    vec3 maxColor = current;
+    float closestDepth = 1.0;
+    vec2 closestOffset = vec2(0.0);
+    for (int y = -1; y <= 1; ++y) {
+        for (int x = -1; x <= 1; ++x) {
+            vec2 offset = vec2(float(x), float(y));
+            vec2 sampleTexCoord = texCoord + offset * inverseFrameSize;
+
+            vec3 color = texture(currentTexture, sampleTexCoord).rgb;
+            minColor = min(minColor, color);
+            maxColor = max(maxColor, color);
+
+            float depth = texture(depthTexture, sampleTexCoord).r;
+            if (depth < closestDepth) {
+                closestDepth = depth;
+                closestOffset = offset;
+            }
+        }
+    }
+
+    vec2 velocity;
+    if (closestDepth >= 1.0) {
+        // There's no geometry in G-Buffer (sky, background, etc.), reproject using camera motion only.
+        vec2 ndc = (pixel - viewport.xy) / viewport.zw * 2.0 - 1.0;
+        vec4 previousClipPosition = reprojectionMatrix * vec4(ndc, 1.0, 1.0);
+        velocity = (ndc - previousClipPosition.xy / previousClipPosition.w) * 0.5;
+    } else {
+        velocity = texture(velocityTexture, texCoord + closestOffset * inverseFrameSize).xy;
+    }
+
+    vec2 historyPixel = pixel - velocity * viewport.zw;
+    if (any(lessThan(historyPixel, viewport.xy)) || any(greaterThanEqual(historyPixel, viewport.xy + viewport.zw))) {
+        // History is outside of the viewport, the fragment has just became visible.
+        FragColor = vec4(current, 1.0);
+        return;
+    }
+
+    vec3 history = texture(historyTexture, historyPixel * inverseFrameSize).rgb;
+    history = clamp(history, minColor, maxColor);
+
+    FragColor = vec4(mix(history, current, blendFactor), 1.0);
+}
@@ -0,0 +1,15 @@
+layout (location = 0) in vec3 vertexPosition;
+
+layout (std140) uniform Uniforms {
+    mat4 worldViewProjection;
+    mat4 reprojectionMatrix;
+    vec4 viewport;
+    vec2 inverseFrameSize;
+    float blendFactor;
+    bool historyValid;
+};
+
+void main()
+{
+    gl_Position = worldViewProjection * vec4(vertexPosition, 1.0);
+}
@@ -23,6 +23,7 @@ use crate::{
         algebra::{Matrix4, Point3, Vector2, Vector3},
         color::Color,
         math::{aabb::AxisAlignedBoundingBox, frustum::Frustum, Rect},
+        scope_profile,
     },
     renderer::{
         bundle::{
@@ -144,6 +145,7 @@ impl CsmRenderer {
         &mut self,
         ctx: CsmRenderContext,
     ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!("CsmRenderer::render");
         let mut stats = RenderPassStatistics::default();
 
         let CsmRenderContext {
@@ -23,6 +23,7 @@ use crate::{
         algebra::{Matrix4, Point3, Vector3},
         color::Color,
         math::Rect,
+        scope_profile,
     },
     renderer::{
         bundle::{
@@ -182,6 +183,7 @@ impl PointShadowMapRenderer {
         &mut self,
         args: PointShadowMapRenderContext,
     ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!("PointShadowMapRenderer::render");
         let mut statistics = RenderPassStatistics::default();
 
         let PointShadowMapRenderContext {
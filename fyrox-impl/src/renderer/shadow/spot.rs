@@ -23,6 +23,7 @@ use crate::{
         algebra::{Matrix4, Vector3},
         color::Color,
         math::Rect,
+        scope_profile,
     },
     renderer::{
         bundle::{
@@ -131,6 +132,7 @@ impl SpotShadowMapRenderer {
         fallback_resources: &FallbackResources,
         uniform_memory_allocator: &mut UniformMemoryAllocator,
     ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!("SpotShadowMapRenderer::render");
         let mut statistics = RenderPassStatistics::default();
 
         let framebuffer = &mut *self.cascades[cascade];
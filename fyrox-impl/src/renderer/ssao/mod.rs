@@ -24,6 +24,7 @@ use crate::{
         algebra::{Matrix3, Matrix4, Vector2, Vector3},
         color::Color,
         math::{lerpf, Rect},
+        scope_profile,
         sstorage::ImmutableString,
     },
     rand::Rng,
@@ -195,6 +196,7 @@ impl ScreenSpaceAmbientOcclusionRenderer {
         view_matrix: Matrix3<f32>,
         uniform_buffer_cache: &mut UniformBufferCache,
     ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!("ScreenSpaceAmbientOcclusionRenderer::render");
         let mut stats = RenderPassStatistics::default();
 
         let viewport = Rect::new(0, 0, self.width, self.height);
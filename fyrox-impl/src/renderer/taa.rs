@@ -0,0 +1,302 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Temporal anti-aliasing (TAA). Camera projection is jittered by a sub-pixel offset each frame,
+//! and the jittered frames are accumulated over time using reprojected history of the previous
+//! frames. Reprojection uses the velocity buffer from G-Buffer; history color is clamped to the
+//! color range of the neighborhood of each pixel to reduce ghosting.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector2, Vector4},
+        math::Rect,
+        scope_profile,
+        sstorage::ImmutableString,
+    },
+    renderer::{
+        bundle::MotionHistory,
+        cache::uniform::UniformBufferCache,
+        framework::{
+            buffer::BufferUsage,
+            error::FrameworkError,
+            framebuffer::{
+                Attachment, AttachmentKind, BufferLocation, FrameBuffer, ResourceBindGroup,
+                ResourceBinding,
+            },
+            geometry_buffer::GeometryBuffer,
+            gpu_program::{GpuProgram, UniformLocation},
+            gpu_texture::{
+                GpuTexture, GpuTextureDescriptor, GpuTextureKind, MagnificationFilter,
+                MinificationFilter, PixelKind,
+            },
+            server::GraphicsServer,
+            uniform::StaticUniformBuffer,
+            DrawParameters, ElementRange, GeometryBufferExt,
+        },
+        gbuffer::GBuffer,
+        make_viewport_matrix, RenderPassStatistics,
+    },
+    scene::mesh::surface::SurfaceData,
+};
+use std::{cell::RefCell, rc::Rc};
+
+/// Amount of unique jitter offsets, after which the sequence repeats.
+const JITTER_SEQUENCE_LENGTH: usize = 8;
+
+/// Weight of the current frame in the accumulated result.
+const BLEND_FACTOR: f32 = 0.1;
+
+struct TaaShader {
+    pub program: Box<dyn GpuProgram>,
+    pub uniform_buffer_binding: usize,
+    pub current_texture: UniformLocation,
+    pub history_texture: UniformLocation,
+    pub velocity_texture: UniformLocation,
+    pub depth_texture: UniformLocation,
+}
+
+impl TaaShader {
+    pub fn new(server: &dyn GraphicsServer) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/taa_fs.glsl");
+        let vertex_source = include_str!("shaders/taa_vs.glsl");
+
+        let program = server.create_program("TAAShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            uniform_buffer_binding: program
+                .uniform_block_index(&ImmutableString::new("Uniforms"))?,
+            current_texture: program.uniform_location(&ImmutableString::new("currentTexture"))?,
+            history_texture: program.uniform_location(&ImmutableString::new("historyTexture"))?,
+            velocity_texture: program.uniform_location(&ImmutableString::new("velocityTexture"))?,
+            depth_texture: program.uniform_location(&ImmutableString::new("depthTexture"))?,
+            program,
+        })
+    }
+}
+
+/// Temporal state of a camera.
+#[derive(Default)]
+pub struct TemporalHistory {
+    /// Motion history of the camera, that is used to calculate motion vectors.
+    pub motion: MotionHistory,
+    /// Index of the current frame, that is used to select projection jitter.
+    pub frame_index: usize,
+    /// Whether the history frame contains valid data for the camera or not.
+    pub is_frame_valid: bool,
+    /// Accumulated frame of the camera. It is created on demand, when the camera is rendered with
+    /// temporal anti-aliasing. Every camera has its own history, so cameras with overlapping
+    /// viewports do not mix their frames.
+    pub frame_buffer: Option<Box<dyn FrameBuffer>>,
+}
+
+impl TemporalHistory {
+    /// Returns a sub-pixel offset (in NDC units) for the projection matrix of the camera for the
+    /// current frame and advances the frame index.
+    pub fn next_jitter(&mut self, viewport: Rect<i32>) -> Vector2<f32> {
+        let index = self.frame_index % JITTER_SEQUENCE_LENGTH + 1;
+        self.frame_index = self.frame_index.wrapping_add(1);
+        Vector2::new(
+            (2.0 * halton(index, 2) - 1.0) / viewport.w().max(1) as f32,
+            (2.0 * halton(index, 3) - 1.0) / viewport.h().max(1) as f32,
+        )
+    }
+
+    /// Returns the frame buffer with accumulated frame of the camera, creates it if needed. The
+    /// size of the frame buffer must match the size of the scene frame.
+    pub fn history_frame_buffer(
+        &mut self,
+        server: &dyn GraphicsServer,
+        width: usize,
+        height: usize,
+    ) -> Result<&dyn FrameBuffer, FrameworkError> {
+        if self.frame_buffer.is_none() {
+            let texture = server.create_texture(GpuTextureDescriptor {
+                kind: GpuTextureKind::Rectangle { width, height },
+                pixel_kind: PixelKind::RGBA8,
+                min_filter: MinificationFilter::Linear,
+                mag_filter: MagnificationFilter::Linear,
+                mip_count: 1,
+                s_wrap_mode: Default::default(),
+                t_wrap_mode: Default::default(),
+                r_wrap_mode: Default::default(),
+                anisotropy: 1.0,
+                data: None,
+            })?;
+
+            self.frame_buffer = Some(server.create_frame_buffer(
+                None,
+                vec![Attachment {
+                    kind: AttachmentKind::Color,
+                    texture,
+                }],
+            )?);
+            self.is_frame_valid = false;
+        }
+
+        Ok(&**self.frame_buffer.as_ref().unwrap())
+    }
+}
+
+/// Returns `index`-th element of Halton low-discrepancy sequence with the given base.
+fn halton(mut index: usize, base: usize) -> f32 {
+    let mut result = 0.0;
+    let mut fraction = 1.0;
+    while index > 0 {
+        fraction /= base as f32;
+        result += fraction * (index % base) as f32;
+        index /= base;
+    }
+    result
+}
+
+pub struct TaaRenderer {
+    shader: TaaShader,
+    quad: Box<dyn GeometryBuffer>,
+}
+
+impl TaaRenderer {
+    pub fn new(server: &dyn GraphicsServer) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            shader: TaaShader::new(server)?,
+            quad: <dyn GeometryBuffer>::from_surface_data(
+                &SurfaceData::make_unit_xy_quad(),
+                BufferUsage::StaticDraw,
+                server,
+            )?,
+        })
+    }
+
+    /// Resolves the current (jittered) frame using the history frame and writes the result in
+    /// the given frame buffer. `reprojection` is a matrix that transforms current NDC position into
+    /// previous clip space position (both without jitter), it should be [`None`] if there's no
+    /// valid history.
+    pub(crate) fn render(
+        &self,
+        viewport: Rect<i32>,
+        frame_texture: Rc<RefCell<dyn GpuTexture>>,
+        history_texture: Rc<RefCell<dyn GpuTexture>>,
+        gbuffer: &GBuffer,
+        reprojection: Option<Matrix4<f32>>,
+        frame_buffer: &mut dyn FrameBuffer,
+        uniform_buffer_cache: &mut UniformBufferCache,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!("TaaRenderer::render");
+        let mut statistics = RenderPassStatistics::default();
+
+        let frame_matrix = make_viewport_matrix(viewport);
+        let velocity_texture = gbuffer.velocity_texture();
+        let depth_texture = gbuffer.depth();
+
+        statistics += frame_buffer.draw(
+            &*self.quad,
+            viewport,
+            &*self.shader.program,
+            &DrawParameters {
+                cull_face: None,
+                color_write: Default::default(),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: Default::default(),
+                scissor_box: None,
+            },
+            &[ResourceBindGroup {
+                bindings: &[
+                    ResourceBinding::texture(&frame_texture, &self.shader.current_texture),
+                    ResourceBinding::texture(&history_texture, &self.shader.history_texture),
+                    ResourceBinding::texture(&velocity_texture, &self.shader.velocity_texture),
+                    ResourceBinding::texture(&depth_texture, &self.shader.depth_texture),
+                    ResourceBinding::Buffer {
+                        buffer: uniform_buffer_cache.write(
+                            StaticUniformBuffer::<512>::new()
+                                .with(&frame_matrix)
+                                .with(&reprojection.unwrap_or_default())
+                                .with(&Vector4::new(
+                                    viewport.x() as f32,
+                                    viewport.y() as f32,
+                                    viewport.w() as f32,
+                                    viewport.h() as f32,
+                                ))
+                                .with(&Vector2::new(
+                                    1.0 / gbuffer.width as f32,
+                                    1.0 / gbuffer.height as f32,
+                                ))
+                                .with(&BLEND_FACTOR)
+                                .with(&reprojection.is_some()),
+                        )?,
+                        binding: BufferLocation::Auto {
+                            shader_location: self.shader.uniform_buffer_binding,
+                        },
+                        data_usage: Default::default(),
+                    },
+                ],
+            }],
+            ElementRange::Full,
+        )?;
+
+        Ok(statistics)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector2, math::Rect},
+        renderer::taa::{halton, TemporalHistory, JITTER_SEQUENCE_LENGTH},
+    };
+
+    #[test]
+    fn test_halton() {
+        assert_eq!(halton(0, 2), 0.0);
+        assert_eq!(halton(1, 2), 0.5);
+        assert_eq!(halton(2, 2), 0.25);
+        assert_eq!(halton(3, 2), 0.75);
+        assert!((halton(1, 3) - 1.0 / 3.0).abs() < 1.0e-6);
+        assert!((halton(2, 3) - 2.0 / 3.0).abs() < 1.0e-6);
+        assert!((halton(4, 3) - 4.0 / 9.0).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn test_jitter_sequence() {
+        let viewport = Rect::new(0, 0, 100, 50);
+        let mut history = TemporalHistory::default();
+
+        let sequence = (0..JITTER_SEQUENCE_LENGTH)
+            .map(|_| history.next_jitter(viewport))
+            .collect::<Vec<_>>();
+        assert_eq!(history.frame_index, JITTER_SEQUENCE_LENGTH);
+        assert_eq!(sequence[0].x, 0.0);
+        assert!((sequence[0].y + 1.0 / 150.0).abs() < 1.0e-6);
+
+        // Offsets are unique and do not exceed a pixel.
+        for (i, jitter) in sequence.iter().enumerate() {
+            assert!(jitter.x.abs() < 1.0 / 100.0 && jitter.y.abs() < 1.0 / 50.0);
+            assert!(!sequence[..i].contains(jitter));
+        }
+
+        // The sequence repeats.
+        assert_eq!(history.next_jitter(viewport), sequence[0]);
+
+        // Empty viewport must not produce infinite offsets.
+        let jitter = history.next_jitter(Rect::new(0, 0, 0, 0));
+        assert!(jitter.x.is_finite() && jitter.y.is_finite());
+        assert_ne!(jitter, Vector2::default());
+    }
+}
@@ -27,6 +27,7 @@ use crate::{
         algebra::{Matrix4, Vector2, Vector4},
         color::Color,
         math::Rect,
+        scope_profile,
         sstorage::ImmutableString,
     },
     gui::{
@@ -170,6 +171,7 @@ impl UiRenderer {
         &mut self,
         args: UiRenderContext,
     ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!("UiRenderer::render");
         let UiRenderContext {
             server,
             viewport,
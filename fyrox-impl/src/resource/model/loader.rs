@@ -49,7 +49,7 @@ pub struct ModelLoader {
 
 impl ResourceLoader for ModelLoader {
     fn extensions(&self) -> &[&str] {
-        &["rgs", "fbx"]
+        &["rgs", "fbx", "tmx"]
     }
 
     fn data_type_uuid(&self) -> Uuid {
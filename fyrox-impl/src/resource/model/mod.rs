@@ -59,7 +59,12 @@ use crate::{
     graph::{BaseSceneGraph, NodeHandleMap, NodeMapping, PrefabData, SceneGraph, SceneGraphNode},
     resource::fbx::{self, error::FbxError},
     scene::{
-        animation::Animation, base::SceneNodeId, graph::Graph, node::Node, transform::Transform,
+        animation::Animation,
+        base::SceneNodeId,
+        graph::Graph,
+        node::Node,
+        tilemap::tiled::{self, TiledError},
+        transform::Transform,
         Scene, SceneLoader,
     },
 };
@@ -740,6 +745,8 @@ pub enum ModelLoadError {
     NotSupported(String),
     /// An error occurred while loading FBX file.
     Fbx(FbxError),
+    /// An error occurred while loading Tiled map.
+    Tiled(TiledError),
 }
 
 impl Display for ModelLoadError {
@@ -752,6 +759,7 @@ impl Display for ModelLoadError {
                 write!(f, "Model format is not supported: {v}")
             }
             ModelLoadError::Fbx(v) => v.fmt(f),
+            ModelLoadError::Tiled(v) => v.fmt(f),
         }
     }
 }
@@ -762,6 +770,12 @@ impl From<FbxError> for ModelLoadError {
     }
 }
 
+impl From<TiledError> for ModelLoadError {
+    fn from(e: TiledError) -> Self {
+        ModelLoadError::Tiled(e)
+    }
+}
+
 impl From<VisitError> for ModelLoadError {
     fn from(e: VisitError) -> Self {
         ModelLoadError::Visit(e)
@@ -808,6 +822,16 @@ impl Model {
                 // any persistent unique ids, and we have to use names.
                 (scene, NodeMapping::UseNames)
             }
+            "tmx" => {
+                let mut scene = Scene::new();
+                if let Some(filename) = path.as_ref().file_name() {
+                    let root = scene.graph.get_root();
+                    scene.graph[root].set_name(filename.to_string_lossy());
+                }
+                tiled::load_tmx_to_scene(&mut scene, resource_manager, io, path.as_ref()).await?;
+                // Tiled layers do not have unique ids either.
+                (scene, NodeMapping::UseNames)
+            }
             // Scene can be used directly as model resource. Such scenes can be created in
             // Fyroxed.
             "rgs" => (
@@ -27,6 +27,7 @@ use crate::{
         math::aabb::AxisAlignedBoundingBox,
         pool::Handle,
         reflect::prelude::*,
+        scope_profile,
         type_traits::prelude::*,
         uuid::{uuid, Uuid},
         variable::InheritableVariable,
@@ -311,6 +312,7 @@ impl NodeTrait for AnimationBlendingStateMachine {
     }
 
     fn update(&mut self, context: &mut UpdateContext) {
+        scope_profile!("AnimationBlendingStateMachine::update");
         if let Some(animation_player) = context
             .nodes
             .try_borrow_mut(*self.animation_player)
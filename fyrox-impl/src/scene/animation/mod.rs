@@ -28,6 +28,7 @@ use crate::{
         math::aabb::AxisAlignedBoundingBox,
         pool::Handle,
         reflect::prelude::*,
+        scope_profile,
         type_traits::prelude::*,
         uuid::{uuid, Uuid},
         variable::InheritableVariable,
@@ -345,6 +346,7 @@ impl NodeTrait for AnimationPlayer {
     }
 
     fn update(&mut self, context: &mut UpdateContext) {
+        scope_profile!("AnimationPlayer::update");
         if self.auto_apply {
             self.animations
                 .get_value_mut_silent()
@@ -579,6 +579,24 @@ pub struct Collider {
     #[reflect(setter = "set_restitution_combine_rule")]
     pub(crate) restitution_combine_rule: InheritableVariable<CoefficientCombineRule>,
 
+    #[visit(optional)]
+    #[reflect(
+        min_value = 0.0,
+        step = 0.05,
+        setter = "set_sound_occlusion_factor",
+        description = "Defines how much the collider occludes sounds. 0.0 - the collider is transparent \
+        for sounds, 1.0 - default occlusion, larger values could be used for thick walls."
+    )]
+    pub(crate) sound_occlusion_factor: InheritableVariable<f32>,
+
+    #[visit(optional)]
+    #[reflect(
+        setter = "set_event_filter",
+        description = "Collision events (contacts and triggers) are reported to scripts only if \
+        collision groups memberships of the other collider intersect with this mask."
+    )]
+    pub(crate) event_filter: InheritableVariable<BitMask>,
+
     #[visit(skip)]
     #[reflect(hidden)]
     pub(crate) native: Cell<ColliderHandle>,
@@ -597,6 +615,8 @@ impl Default for Collider {
             solver_groups: Default::default(),
             friction_combine_rule: Default::default(),
             restitution_combine_rule: Default::default(),
+            sound_occlusion_factor: InheritableVariable::new_modified(1.0),
+            event_filter: InheritableVariable::new_modified(BitMask(u32::MAX)),
             native: Cell::new(ColliderHandle::invalid()),
         }
     }
@@ -629,6 +649,8 @@ impl Clone for Collider {
             solver_groups: self.solver_groups.clone(),
             friction_combine_rule: self.friction_combine_rule.clone(),
             restitution_combine_rule: self.restitution_combine_rule.clone(),
+            sound_occlusion_factor: self.sound_occlusion_factor.clone(),
+            event_filter: self.event_filter.clone(),
             // Do not copy. The copy will have its own native representation (for example - Rapier's collider)
             native: Cell::new(ColliderHandle::invalid()),
         }
@@ -821,6 +843,32 @@ impl Collider {
         *self.restitution_combine_rule
     }
 
+    /// Sets how much the collider occludes sounds, that are behind it. 0.0 means that the collider is
+    /// transparent for sounds, 1.0 - default occlusion, larger values could be used for thick walls.
+    /// See [`crate::scene::sound::occlusion::SoundOcclusion`] docs for more info.
+    pub fn set_sound_occlusion_factor(&mut self, factor: f32) -> f32 {
+        self.sound_occlusion_factor
+            .set_value_and_mark_modified(factor.max(0.0))
+    }
+
+    /// Returns the sound occlusion factor of the collider.
+    pub fn sound_occlusion_factor(&self) -> f32 {
+        *self.sound_occlusion_factor
+    }
+
+    /// Sets a mask, that is used to filter collision events (see
+    /// [`crate::scene::graph::physics::CollisionEvent`]) of the collider. An event is reported to
+    /// scripts of the collider (and its rigid body) only if collision groups memberships of the
+    /// other collider intersect with the mask. By default, the mask allows every event.
+    pub fn set_event_filter(&mut self, filter: BitMask) -> BitMask {
+        self.event_filter.set_value_and_mark_modified(filter)
+    }
+
+    /// Returns current collision events filter of the collider.
+    pub fn event_filter(&self) -> BitMask {
+        *self.event_filter
+    }
+
     /// Returns an iterator that yields contact information for the collider.
     /// Contacts checks between two regular colliders
     pub fn contacts<'a>(
@@ -974,6 +1022,8 @@ pub struct ColliderBuilder {
     solver_groups: InteractionGroups,
     friction_combine_rule: CoefficientCombineRule,
     restitution_combine_rule: CoefficientCombineRule,
+    sound_occlusion_factor: f32,
+    event_filter: BitMask,
 }
 
 impl ColliderBuilder {
@@ -990,6 +1040,8 @@ impl ColliderBuilder {
             solver_groups: Default::default(),
             friction_combine_rule: Default::default(),
             restitution_combine_rule: Default::default(),
+            sound_occlusion_factor: 1.0,
+            event_filter: BitMask(u32::MAX),
         }
     }
 
@@ -1047,6 +1099,18 @@ impl ColliderBuilder {
         self
     }
 
+    /// Sets desired sound occlusion factor. See [`Collider::set_sound_occlusion_factor`] for more info.
+    pub fn with_sound_occlusion_factor(mut self, factor: f32) -> Self {
+        self.sound_occlusion_factor = factor;
+        self
+    }
+
+    /// Sets desired collision events filter. See [`Collider::set_event_filter`] for more info.
+    pub fn with_event_filter(mut self, filter: BitMask) -> Self {
+        self.event_filter = filter;
+        self
+    }
+
     /// Creates collider node, but does not add it to a graph.
     pub fn build_collider(self) -> Collider {
         Collider {
@@ -1060,6 +1124,8 @@ impl ColliderBuilder {
             solver_groups: self.solver_groups.into(),
             friction_combine_rule: self.friction_combine_rule.into(),
             restitution_combine_rule: self.restitution_combine_rule.into(),
+            sound_occlusion_factor: self.sound_occlusion_factor.into(),
+            event_filter: self.event_filter.into(),
             native: Cell::new(ColliderHandle::invalid()),
         }
     }
@@ -0,0 +1,639 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Kinematic character controller for 2D physics. See [`CharacterController`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector2, Vector3},
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        collider::InteractionGroups,
+        dim2::collider::ColliderShape,
+        graph::Graph,
+        node::{constructor::NodeConstructor, Node, NodeTrait, UpdateContext},
+    },
+};
+use fyrox_graph::{constructor::ConstructorProvider, BaseSceneGraph};
+use std::ops::{Deref, DerefMut};
+
+/// Information about a contact of a character with some collider, that happened during the last
+/// movement of the character.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CharacterCollision {
+    /// A handle of the collider hit by the character.
+    pub collider: Handle<Node>,
+    /// World-space position of the character at the moment of the hit.
+    pub position: Vector2<f32>,
+    /// A part of the desired translation, that was already applied when the hit happened.
+    pub translation_applied: Vector2<f32>,
+    /// A part of the desired translation, that was still waiting to be applied when the hit
+    /// happened.
+    pub translation_remaining: Vector2<f32>,
+    /// World-space normal of the surface of the collider at the contact point.
+    pub normal: Vector2<f32>,
+}
+
+/// Result of a single movement of a character.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct CharacterMovement {
+    /// The translation (in world coordinates), that was actually applied to the character.
+    pub translation: Vector2<f32>,
+    /// Whether the character touches the ground after the movement.
+    pub grounded: bool,
+    /// Whether the character is sliding down a slope that is too steep to climb.
+    pub is_sliding_down_slope: bool,
+}
+
+/// Character controller is a kinematic "body", that moves its shape through the 2D physics world
+/// using shape casts. It does not simulate any dynamics (it is not affected by forces, gravity, etc.),
+/// instead it takes a desired translation and moves as far as possible, sliding along obstacles,
+/// climbing stairs and snapping to the ground. This is what most of games need for player characters
+/// and NPCs, instead of dynamic rigid bodies.
+///
+/// ## How to use
+///
+/// Create the controller, attach your character's model to it as a child and call
+/// [`Self::move_by`] from a script on each frame. The translation is accumulated and applied on
+/// the next update of the scene graph. Gravity must be applied manually, it is a part of the
+/// desired translation:
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::algebra::Vector2,
+/// #     scene::dim2::character_controller::CharacterController,
+/// # };
+/// fn move_character(controller: &mut CharacterController, input: Vector2<f32>, dt: f32) {
+///     let gravity = Vector2::new(0.0, -9.81);
+///     controller.move_by((input * 5.0 + gravity) * dt);
+///     if controller.is_grounded() {
+///         // Can jump.
+///     }
+/// }
+/// ```
+///
+/// Results of the last movement could be fetched using [`Self::last_movement`] and
+/// [`Self::collisions`] methods. The collisions are the list of every collider the character
+/// touched during the last movement. Every hit collider is also reported to the scripts of the
+/// controller (and to the scripts of the hit collider and its rigid body) as a collision event
+/// with [`crate::scene::graph::physics::CollisionEventKind::CharacterHit`] kind, so the contacts
+/// could be handled in [`crate::script::ScriptTrait::on_collision`] (for example to open a door
+/// or to push a box).
+///
+/// ## Limitations
+///
+/// Controller is not a part of the physics world, so other rigid bodies and character controllers
+/// do not collide with it. Optionally, the controller can push dynamic rigid bodies it runs into,
+/// see [`Self::set_mass`].
+#[derive(Visit, Reflect, Clone, Debug, ComponentProvider)]
+#[visit(optional)]
+pub struct CharacterController {
+    base: Base,
+
+    #[reflect(setter = "set_shape")]
+    shape: InheritableVariable<ColliderShape>,
+
+    #[reflect(setter = "set_collision_groups")]
+    collision_groups: InheritableVariable<InteractionGroups>,
+
+    #[reflect(min_value = 0.001, step = 0.005)]
+    #[reflect(setter = "set_offset")]
+    offset: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_slide")]
+    slide: InheritableVariable<bool>,
+
+    #[reflect(min_value = 0.0, max_value = 1.571, step = 0.01)]
+    #[reflect(setter = "set_max_slope_climb_angle")]
+    max_slope_climb_angle: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, max_value = 1.571, step = 0.01)]
+    #[reflect(setter = "set_min_slope_slide_angle")]
+    min_slope_slide_angle: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, step = 0.05)]
+    #[reflect(setter = "set_step_height")]
+    step_height: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, step = 0.05)]
+    #[reflect(setter = "set_step_min_width")]
+    step_min_width: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_step_on_dynamic_bodies")]
+    step_on_dynamic_bodies: InheritableVariable<bool>,
+
+    #[reflect(min_value = 0.0, step = 0.05)]
+    #[reflect(setter = "set_snap_to_ground")]
+    snap_to_ground: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, step = 0.05)]
+    #[reflect(setter = "set_mass")]
+    mass: InheritableVariable<f32>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    desired_translation: Vector2<f32>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    last_movement: CharacterMovement,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    collisions: Vec<CharacterCollision>,
+}
+
+impl Default for CharacterController {
+    fn default() -> Self {
+        Self {
+            base: Default::default(),
+            shape: InheritableVariable::new_modified(ColliderShape::capsule_y(0.5, 0.3)),
+            collision_groups: Default::default(),
+            offset: InheritableVariable::new_modified(0.01),
+            slide: InheritableVariable::new_modified(true),
+            max_slope_climb_angle: InheritableVariable::new_modified(45.0f32.to_radians()),
+            min_slope_slide_angle: InheritableVariable::new_modified(30.0f32.to_radians()),
+            step_height: InheritableVariable::new_modified(0.3),
+            step_min_width: InheritableVariable::new_modified(0.2),
+            step_on_dynamic_bodies: InheritableVariable::new_modified(true),
+            snap_to_ground: InheritableVariable::new_modified(0.2),
+            mass: Default::default(),
+            desired_translation: Default::default(),
+            last_movement: Default::default(),
+            collisions: Default::default(),
+        }
+    }
+}
+
+impl Deref for CharacterController {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for CharacterController {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for CharacterController {
+    fn type_uuid() -> Uuid {
+        uuid!("5699b7ac-e8ad-45cb-9e11-24f3e2da5b47")
+    }
+}
+
+impl CharacterController {
+    /// Sets new shape of the character. Only convex shapes are supported, trimesh and height field
+    /// shapes are ignored. Returns old shape.
+    pub fn set_shape(&mut self, shape: ColliderShape) -> ColliderShape {
+        self.shape.set_value_and_mark_modified(shape)
+    }
+
+    /// Returns current shape of the character.
+    pub fn shape(&self) -> &ColliderShape {
+        &self.shape
+    }
+
+    /// Sets new collision groups, that will be used to filter colliders the character can collide
+    /// with. Returns old groups.
+    pub fn set_collision_groups(&mut self, groups: InteractionGroups) -> InteractionGroups {
+        self.collision_groups.set_value_and_mark_modified(groups)
+    }
+
+    /// Returns current collision groups of the character.
+    pub fn collision_groups(&self) -> InteractionGroups {
+        *self.collision_groups
+    }
+
+    /// Sets a small gap, that will be preserved between the character and its surroundings. It
+    /// must not be zero to keep the movement numerically stable. Returns old offset.
+    pub fn set_offset(&mut self, offset: f32) -> f32 {
+        self.offset.set_value_and_mark_modified(offset)
+    }
+
+    /// Returns current offset of the character.
+    pub fn offset(&self) -> f32 {
+        *self.offset
+    }
+
+    /// Defines whether the character should slide along obstacles or stop when hitting them.
+    /// Returns old value.
+    pub fn set_slide(&mut self, slide: bool) -> bool {
+        self.slide.set_value_and_mark_modified(slide)
+    }
+
+    /// Returns `true` if the character slides along obstacles, `false` - otherwise.
+    pub fn slide(&self) -> bool {
+        *self.slide
+    }
+
+    /// Sets maximum angle (in radians) between the floor and the up vector, that the character is
+    /// able to climb. Returns old value.
+    pub fn set_max_slope_climb_angle(&mut self, angle: f32) -> f32 {
+        self.max_slope_climb_angle
+            .set_value_and_mark_modified(angle)
+    }
+
+    /// Returns maximum angle (in radians) of a slope, that the character is able to climb.
+    pub fn max_slope_climb_angle(&self) -> f32 {
+        *self.max_slope_climb_angle
+    }
+
+    /// Sets minimum angle (in radians) between the floor and the up vector, at which the character
+    /// starts to slide down. Returns old value.
+    pub fn set_min_slope_slide_angle(&mut self, angle: f32) -> f32 {
+        self.min_slope_slide_angle
+            .set_value_and_mark_modified(angle)
+    }
+
+    /// Returns minimum angle (in radians) of a slope at which the character starts to slide down.
+    pub fn min_slope_slide_angle(&self) -> f32 {
+        *self.min_slope_slide_angle
+    }
+
+    /// Sets maximum height of a step (stairs, curbs, etc.), that the character can step over
+    /// automatically. Zero disables stepping. Returns old value.
+    pub fn set_step_height(&mut self, height: f32) -> f32 {
+        self.step_height.set_value_and_mark_modified(height)
+    }
+
+    /// Returns maximum height of a step, that the character can step over automatically.
+    pub fn step_height(&self) -> f32 {
+        *self.step_height
+    }
+
+    /// Sets minimum width of free space, that must be available after stepping on a step. Returns
+    /// old value.
+    pub fn set_step_min_width(&mut self, width: f32) -> f32 {
+        self.step_min_width.set_value_and_mark_modified(width)
+    }
+
+    /// Returns minimum width of free space, that must be available after stepping on a step.
+    pub fn step_min_width(&self) -> f32 {
+        *self.step_min_width
+    }
+
+    /// Defines whether the character can step on dynamic rigid bodies or not. Returns old value.
+    pub fn set_step_on_dynamic_bodies(&mut self, enabled: bool) -> bool {
+        self.step_on_dynamic_bodies
+            .set_value_and_mark_modified(enabled)
+    }
+
+    /// Returns `true` if the character can step on dynamic rigid bodies, `false` - otherwise.
+    pub fn step_on_dynamic_bodies(&self) -> bool {
+        *self.step_on_dynamic_bodies
+    }
+
+    /// Sets maximum distance to the ground, at which the character will be snapped to it. It keeps
+    /// the character on the ground when it walks down a slope or stairs. Zero disables snapping.
+    /// Returns old value.
+    pub fn set_snap_to_ground(&mut self, distance: f32) -> f32 {
+        self.snap_to_ground.set_value_and_mark_modified(distance)
+    }
+
+    /// Returns maximum distance to the ground, at which the character will be snapped to it.
+    pub fn snap_to_ground(&self) -> f32 {
+        *self.snap_to_ground
+    }
+
+    /// Sets mass of the character, that is used to push dynamic rigid bodies the character runs
+    /// into. Zero means that the character does not push anything. Returns old value.
+    pub fn set_mass(&mut self, mass: f32) -> f32 {
+        self.mass.set_value_and_mark_modified(mass)
+    }
+
+    /// Returns mass of the character.
+    pub fn mass(&self) -> f32 {
+        *self.mass
+    }
+
+    /// Adds the given translation (in world coordinates) to the desired translation of the
+    /// character. The character will be moved on the next update of the scene graph.
+    pub fn move_by(&mut self, translation: Vector2<f32>) {
+        self.desired_translation += translation;
+    }
+
+    /// Returns the translation, that will be applied to the character on the next update of the
+    /// scene graph.
+    pub fn desired_translation(&self) -> Vector2<f32> {
+        self.desired_translation
+    }
+
+    /// Returns the result of the last movement of the character.
+    pub fn last_movement(&self) -> CharacterMovement {
+        self.last_movement
+    }
+
+    /// Returns `true` if the character was touching the ground after the last movement. Keep in
+    /// mind, that the ground is detected only while the character is moving, so gravity must be
+    /// applied constantly.
+    pub fn is_grounded(&self) -> bool {
+        self.last_movement.grounded
+    }
+
+    /// Returns `true` if the character is sliding down a slope, that is too steep to climb.
+    pub fn is_sliding_down_slope(&self) -> bool {
+        self.last_movement.is_sliding_down_slope
+    }
+
+    /// Returns a list of collisions, that happened during the last movement of the character.
+    pub fn collisions(&self) -> &[CharacterCollision] {
+        &self.collisions
+    }
+}
+
+impl ConstructorProvider<Node, Graph> for CharacterController {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>()
+            .with_variant("Character Controller 2D", |_| {
+                CharacterControllerBuilder::new(
+                    BaseBuilder::new().with_name("Character Controller 2D"),
+                )
+                .build_node()
+                .into()
+            })
+            .with_group("Physics")
+    }
+}
+
+impl NodeTrait for CharacterController {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.world_bounding_box()
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        let desired_translation = std::mem::take(&mut self.desired_translation);
+
+        if !self.is_globally_enabled() {
+            self.last_movement = Default::default();
+            self.collisions.clear();
+            return;
+        }
+
+        let mut collisions = std::mem::take(&mut self.collisions);
+        self.last_movement = context.physics2d.move_character(
+            self,
+            context.nodes,
+            desired_translation,
+            context.dt,
+            &mut collisions,
+        );
+        self.collisions = collisions;
+
+        // Controller can be root node of a scene, in this case it does not have a parent.
+        let parent_transform = context
+            .nodes
+            .try_borrow(self.parent)
+            .map(|p| p.global_transform())
+            .unwrap_or_else(Matrix4::identity);
+        let local_translation = parent_transform
+            .try_inverse()
+            .unwrap_or_else(Matrix4::identity)
+            .transform_vector(&self.last_movement.translation.to_homogeneous());
+        if local_translation != Vector3::zeros() {
+            self.local_transform_mut().offset(local_translation);
+        }
+    }
+}
+
+/// Allows you to create character controller in declarative manner.
+pub struct CharacterControllerBuilder {
+    base_builder: BaseBuilder,
+    shape: ColliderShape,
+    collision_groups: InteractionGroups,
+    offset: f32,
+    slide: bool,
+    max_slope_climb_angle: f32,
+    min_slope_slide_angle: f32,
+    step_height: f32,
+    step_min_width: f32,
+    step_on_dynamic_bodies: bool,
+    snap_to_ground: f32,
+    mass: f32,
+}
+
+impl CharacterControllerBuilder {
+    /// Creates new character controller builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        let default = CharacterController::default();
+        Self {
+            base_builder,
+            shape: default.shape().clone(),
+            collision_groups: default.collision_groups(),
+            offset: default.offset(),
+            slide: default.slide(),
+            max_slope_climb_angle: default.max_slope_climb_angle(),
+            min_slope_slide_angle: default.min_slope_slide_angle(),
+            step_height: default.step_height(),
+            step_min_width: default.step_min_width(),
+            step_on_dynamic_bodies: default.step_on_dynamic_bodies(),
+            snap_to_ground: default.snap_to_ground(),
+            mass: default.mass(),
+        }
+    }
+
+    /// Sets desired shape of the character.
+    pub fn with_shape(mut self, shape: ColliderShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Sets desired collision groups of the character.
+    pub fn with_collision_groups(mut self, groups: InteractionGroups) -> Self {
+        self.collision_groups = groups;
+        self
+    }
+
+    /// Sets desired gap between the character and its surroundings.
+    pub fn with_offset(mut self, offset: f32) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Sets whether the character should slide along obstacles or not.
+    pub fn with_slide(mut self, slide: bool) -> Self {
+        self.slide = slide;
+        self
+    }
+
+    /// Sets desired maximum angle (in radians) of a slope, that the character is able to climb.
+    pub fn with_max_slope_climb_angle(mut self, angle: f32) -> Self {
+        self.max_slope_climb_angle = angle;
+        self
+    }
+
+    /// Sets desired minimum angle (in radians) of a slope at which the character starts to slide
+    /// down.
+    pub fn with_min_slope_slide_angle(mut self, angle: f32) -> Self {
+        self.min_slope_slide_angle = angle;
+        self
+    }
+
+    /// Sets desired maximum height of a step, that the character can step over automatically.
+    pub fn with_step_height(mut self, height: f32) -> Self {
+        self.step_height = height;
+        self
+    }
+
+    /// Sets desired minimum width of free space, that must be available after stepping on a step.
+    pub fn with_step_min_width(mut self, width: f32) -> Self {
+        self.step_min_width = width;
+        self
+    }
+
+    /// Sets whether the character can step on dynamic rigid bodies or not.
+    pub fn with_step_on_dynamic_bodies(mut self, enabled: bool) -> Self {
+        self.step_on_dynamic_bodies = enabled;
+        self
+    }
+
+    /// Sets desired maximum distance to the ground, at which the character will be snapped to it.
+    pub fn with_snap_to_ground(mut self, distance: f32) -> Self {
+        self.snap_to_ground = distance;
+        self
+    }
+
+    /// Sets desired mass of the character.
+    pub fn with_mass(mut self, mass: f32) -> Self {
+        self.mass = mass;
+        self
+    }
+
+    /// Creates new character controller node.
+    pub fn build_node(self) -> Node {
+        Node::new(CharacterController {
+            base: self.base_builder.build_base(),
+            shape: self.shape.into(),
+            collision_groups: self.collision_groups.into(),
+            offset: self.offset.into(),
+            slide: self.slide.into(),
+            max_slope_climb_angle: self.max_slope_climb_angle.into(),
+            min_slope_slide_angle: self.min_slope_slide_angle.into(),
+            step_height: self.step_height.into(),
+            step_min_width: self.step_min_width.into(),
+            step_on_dynamic_bodies: self.step_on_dynamic_bodies.into(),
+            snap_to_ground: self.snap_to_ground.into(),
+            mass: self.mass.into(),
+            desired_translation: Default::default(),
+            last_movement: Default::default(),
+            collisions: Default::default(),
+        })
+    }
+
+    /// Creates new character controller node and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Vector2, Vector3},
+            pool::Handle,
+        },
+        scene::{
+            base::BaseBuilder,
+            dim2::{
+                character_controller::{CharacterController, CharacterControllerBuilder},
+                collider::{ColliderBuilder, ColliderShape},
+                rigidbody::RigidBodyBuilder,
+            },
+            graph::Graph,
+            node::Node,
+            rigidbody::RigidBodyType,
+            transform::TransformBuilder,
+        },
+    };
+
+    fn add_box(
+        graph: &mut Graph,
+        position: Vector2<f32>,
+        half_extents: Vector2<f32>,
+    ) -> Handle<Node> {
+        let collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::cuboid(half_extents.x, half_extents.y))
+            .build(graph);
+        RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(position.to_homogeneous())
+                        .build(),
+                )
+                .with_children(&[collider]),
+        )
+        .with_body_type(RigidBodyType::Static)
+        .build(graph);
+        collider
+    }
+
+    #[test]
+    fn test_character_2d_movement() {
+        let mut graph = Graph::new();
+        let floor = add_box(&mut graph, Vector2::new(0.0, -0.5), Vector2::new(20.0, 0.5));
+        add_box(&mut graph, Vector2::new(3.0, 2.0), Vector2::new(0.1, 2.0));
+        let character = CharacterControllerBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 2.0, 0.0))
+                    .build(),
+            ),
+        )
+        .with_shape(ColliderShape::capsule_y(0.5, 0.3))
+        .build(&mut graph);
+        graph.update(Vector2::new(800.0, 600.0), 1.0 / 60.0, Default::default());
+
+        for _ in 0..60 {
+            graph[character]
+                .cast_mut::<CharacterController>()
+                .unwrap()
+                .move_by(Vector2::new(0.1, -0.1));
+            graph.update(Vector2::new(800.0, 600.0), 1.0 / 60.0, Default::default());
+        }
+
+        let controller = graph[character].cast::<CharacterController>().unwrap();
+        let position = **controller.local_transform().position();
+        assert!((position.x - 2.59).abs() < 0.02, "{position}");
+        assert!((position.y - 0.81).abs() < 0.02, "{position}");
+        assert!(controller.is_grounded());
+        assert!(controller.collisions().iter().any(|c| c.collider == floor));
+    }
+}
@@ -36,7 +36,7 @@ use crate::{
     },
     scene::{
         base::{Base, BaseBuilder},
-        collider::InteractionGroups,
+        collider::{BitMask, InteractionGroups},
         dim2::{
             physics::{ContactPair, IntersectionPair, PhysicsWorld},
             rigidbody::RigidBody,
@@ -296,9 +296,21 @@ pub struct Collider {
     #[reflect(setter = "set_restitution_combine_rule")]
     pub(crate) restitution_combine_rule: InheritableVariable<CoefficientCombineRule>,
 
+    #[visit(optional)]
+    #[reflect(
+        setter = "set_event_filter",
+        description = "Collision events (contacts and triggers) are reported to scripts only if \
+        collision groups memberships of the other collider intersect with this mask."
+    )]
+    pub(crate) event_filter: InheritableVariable<BitMask>,
+
     #[visit(skip)]
     #[reflect(hidden)]
     pub(crate) native: Cell<ColliderHandle>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub(crate) shape_source_hash: Cell<u64>,
 }
 
 impl Default for Collider {
@@ -314,7 +326,9 @@ impl Default for Collider {
             solver_groups: Default::default(),
             friction_combine_rule: Default::default(),
             restitution_combine_rule: Default::default(),
+            event_filter: InheritableVariable::new_modified(BitMask(u32::MAX)),
             native: Cell::new(ColliderHandle::invalid()),
+            shape_source_hash: Default::default(),
         }
     }
 }
@@ -346,8 +360,10 @@ impl Clone for Collider {
             solver_groups: self.solver_groups.clone(),
             friction_combine_rule: self.friction_combine_rule.clone(),
             restitution_combine_rule: self.restitution_combine_rule.clone(),
+            event_filter: self.event_filter.clone(),
             // Do not copy. The copy will have its own native representation.
             native: Cell::new(ColliderHandle::invalid()),
+            shape_source_hash: Default::default(),
         }
     }
 }
@@ -538,6 +554,19 @@ impl Collider {
         *self.restitution_combine_rule
     }
 
+    /// Sets a mask, that is used to filter collision events (see
+    /// [`crate::scene::graph::physics::CollisionEvent`]) of the collider. An event is reported to
+    /// scripts of the collider (and its rigid body) only if collision groups memberships of the
+    /// other collider intersect with the mask. By default, the mask allows every event.
+    pub fn set_event_filter(&mut self, filter: BitMask) -> BitMask {
+        self.event_filter.set_value_and_mark_modified(filter)
+    }
+
+    /// Returns current collision events filter of the collider.
+    pub fn event_filter(&self) -> BitMask {
+        *self.event_filter
+    }
+
     /// Returns an iterator that yields contact information for the collider.
     /// Contacts checks between two regular colliders
     pub fn contacts<'a>(
@@ -693,6 +722,7 @@ pub struct ColliderBuilder {
     solver_groups: InteractionGroups,
     friction_combine_rule: CoefficientCombineRule,
     restitution_combine_rule: CoefficientCombineRule,
+    event_filter: BitMask,
 }
 
 impl ColliderBuilder {
@@ -709,6 +739,7 @@ impl ColliderBuilder {
             solver_groups: Default::default(),
             friction_combine_rule: Default::default(),
             restitution_combine_rule: Default::default(),
+            event_filter: BitMask(u32::MAX),
         }
     }
 
@@ -766,6 +797,12 @@ impl ColliderBuilder {
         self
     }
 
+    /// Sets desired collision events filter. See [`Collider::set_event_filter`] for more info.
+    pub fn with_event_filter(mut self, filter: BitMask) -> Self {
+        self.event_filter = filter;
+        self
+    }
+
     /// Creates collider node, but does not add it to a graph.
     pub fn build_collider(self) -> Collider {
         Collider {
@@ -779,7 +816,9 @@ impl ColliderBuilder {
             solver_groups: self.solver_groups.into(),
             friction_combine_rule: self.friction_combine_rule.into(),
             restitution_combine_rule: self.restitution_combine_rule.into(),
+            event_filter: self.event_filter.into(),
             native: Cell::new(ColliderHandle::invalid()),
+            shape_source_hash: Default::default(),
         }
     }
 
@@ -797,15 +836,20 @@ impl ColliderBuilder {
 #[cfg(test)]
 mod test {
 
+    use crate::asset::untyped::ResourceKind;
     use crate::core::algebra::Vector2;
     use crate::scene::{
         base::BaseBuilder,
         dim2::{
-            collider::{ColliderBuilder, ColliderShape},
+            collider::{ColliderBuilder, ColliderShape, GeometrySource, TileMapShape},
             rigidbody::RigidBodyBuilder,
         },
         graph::Graph,
         rigidbody::RigidBodyType,
+        tilemap::{
+            tileset::{TileCollider, TileDefinition, TileSet, TileSetResource},
+            Tile, TileMap, TileMapBuilder,
+        },
     };
 
     #[test]
@@ -865,4 +909,62 @@ mod test {
                 .count()
         );
     }
+
+    #[test]
+    fn test_tile_map_collider_follows_tiles() {
+        let mut graph = Graph::new();
+
+        let mut tile_set = TileSet::default();
+        let solid = tile_set.add_tile(TileDefinition {
+            collider: TileCollider::Rectangle,
+            ..Default::default()
+        });
+        let tile_map = TileMapBuilder::new(BaseBuilder::new())
+            .with_tile_set(TileSetResource::new_ok(ResourceKind::Embedded, tile_set))
+            .with_tiles(
+                [Vector2::new(0, 0), Vector2::new(1, 0)]
+                    .into_iter()
+                    .map(|position| Tile {
+                        position,
+                        definition_handle: solid,
+                    })
+                    .collect(),
+            )
+            .build(&mut graph);
+
+        let collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::TileMap(TileMapShape {
+                tile_map: GeometrySource(tile_map),
+            }))
+            .build(&mut graph);
+        RigidBodyBuilder::new(BaseBuilder::new().with_children(&[collider]))
+            .with_body_type(RigidBodyType::Static)
+            .build(&mut graph);
+
+        let triangle_count = |graph: &Graph| {
+            let native = graph[collider].as_collider2d().native.get();
+            graph.physics2d.colliders[native]
+                .shape()
+                .as_trimesh()
+                .unwrap()
+                .indices()
+                .len()
+        };
+
+        // need to call two times for the physics engine to execute
+        graph.update(Vector2::new(800.0, 600.0), 1.0, Default::default());
+        graph.update(Vector2::new(800.0, 600.0), 1.0, Default::default());
+        // Adjacent tiles are merged in a single rectangle.
+        assert_eq!(triangle_count(&graph), 2);
+
+        graph[tile_map]
+            .cast_mut::<TileMap>()
+            .unwrap()
+            .insert_tile(Tile {
+                position: Vector2::new(5, 5),
+                definition_handle: solid,
+            });
+        graph.update(Vector2::new(800.0, 600.0), 1.0, Default::default());
+        assert_eq!(triangle_count(&graph), 4);
+    }
 }
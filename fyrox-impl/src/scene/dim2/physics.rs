@@ -22,7 +22,6 @@
 
 use crate::{
     core::{
-        algebra::Point3,
         algebra::{
             Isometry2, Isometry3, Matrix4, Point2, Rotation3, Translation2, Translation3,
             UnitComplex, UnitQuaternion, UnitVector2, Vector2, Vector3,
@@ -34,30 +33,41 @@ use crate::{
         parking_lot::Mutex,
         pool::Handle,
         reflect::prelude::*,
+        scope_profile,
         variable::{InheritableVariable, VariableFlags},
         visitor::prelude::*,
         BiDirHashMap,
     },
+    fxhash::FxHashMap,
     graph::{BaseSceneGraph, SceneGraphNode},
     scene::{
         self,
         collider::{self},
         debug::SceneDrawingContext,
         dim2::{
-            self, collider::ColliderShape, collider::TileMapShape, joint::JointLocalFrames,
-            joint::JointParams, rigidbody::ApplyAction,
+            self,
+            character_controller::{CharacterCollision, CharacterController, CharacterMovement},
+            collider::ColliderShape,
+            collider::TileMapShape,
+            joint::JointLocalFrames,
+            joint::JointParams,
+            rigidbody::ApplyAction,
         },
         graph::{
             isometric_global_transform,
-            physics::{FeatureId, IntegrationParameters, PhysicsPerformanceStatistics},
+            physics::{
+                CollisionEvent, CollisionEventKind, FeatureId, IntegrationParameters,
+                PhysicsPerformanceStatistics,
+            },
             Graph, NodePool,
         },
         node::{Node, NodeTrait},
-        tilemap::{tileset::TileCollider, TileMap},
+        tilemap::TileMap,
     },
 };
 pub use rapier2d::geometry::shape::*;
 use rapier2d::{
+    control::{CharacterAutostep, CharacterLength, KinematicCharacterController},
     dynamics::{
         CCDSolver, GenericJoint, GenericJointBuilder, ImpulseJointHandle, ImpulseJointSet,
         IslandManager, JointAxesMask, JointAxis, MultibodyJointHandle, MultibodyJointSet,
@@ -65,11 +75,11 @@ use rapier2d::{
         RigidBodyType,
     },
     geometry::{
-        Collider, ColliderBuilder, ColliderHandle, ColliderSet, Cuboid, DefaultBroadPhase,
-        InteractionGroups, NarrowPhase, Ray, SharedShape,
+        Collider, ColliderBuilder, ColliderHandle, ColliderSet, CollisionEventFlags, Cuboid,
+        DefaultBroadPhase, InteractionGroups, NarrowPhase, Ray, SharedShape,
     },
     parry::query::ShapeCastOptions,
-    pipeline::{DebugRenderPipeline, EventHandler, PhysicsPipeline, QueryPipeline},
+    pipeline::{ActiveEvents, DebugRenderPipeline, EventHandler, PhysicsPipeline, QueryPipeline},
 };
 use std::{
     cell::RefCell,
@@ -130,6 +140,33 @@ impl<const CAP: usize> QueryResultsStorage for ArrayVec<Intersection, CAP> {
     }
 }
 
+#[derive(Default)]
+struct CollisionEventCollector {
+    events: Mutex<Vec<rapier2d::geometry::CollisionEvent>>,
+}
+
+impl EventHandler for CollisionEventCollector {
+    fn handle_collision_event(
+        &self,
+        _bodies: &RigidBodySet,
+        _colliders: &ColliderSet,
+        event: rapier2d::geometry::CollisionEvent,
+        _contact_pair: Option<&rapier2d::geometry::ContactPair>,
+    ) {
+        self.events.lock().push(event);
+    }
+
+    fn handle_contact_force_event(
+        &self,
+        _dt: f32,
+        _bodies: &RigidBodySet,
+        _colliders: &ColliderSet,
+        _contact_pair: &rapier2d::geometry::ContactPair,
+        _total_force_magnitude: f32,
+    ) {
+    }
+}
+
 /// A ray intersection result.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Intersection {
@@ -332,9 +369,6 @@ fn tile_map_to_collider_shape(
         .try_borrow(tile_map_handle)?
         .component_ref::<TileMap>()?;
 
-    let tile_set_resource = tile_map.tile_set()?.data_ref();
-    let tile_set = tile_set_resource.as_loaded_ref()?;
-
     let tile_scale = tile_map.tile_scale();
     let global_transform = owner_inv_transform
         * tile_map.global_transform()
@@ -343,46 +377,8 @@ fn tile_map_to_collider_shape(
     let mut vertices = Vec::new();
     let mut triangles = Vec::new();
 
-    for tile in tile_map.tiles().values() {
-        let Some(tile_definition) = tile_set.tiles.try_borrow(tile.definition_handle) else {
-            continue;
-        };
-
-        match tile_definition.collider {
-            TileCollider::None => {}
-            TileCollider::Rectangle => {
-                let origin = vertices.len() as u32;
-
-                let position = tile.position.cast::<f32>().to_homogeneous();
-                vertices.push(
-                    global_transform
-                        .transform_point(&Point3::from(position))
-                        .xy(),
-                );
-                vertices.push(
-                    global_transform
-                        .transform_point(&Point3::from(position + Vector3::new(1.0, 0.0, 0.0)))
-                        .xy(),
-                );
-                vertices.push(
-                    global_transform
-                        .transform_point(&Point3::from(position + Vector3::new(1.0, 1.0, 0.0)))
-                        .xy(),
-                );
-                vertices.push(
-                    global_transform
-                        .transform_point(&Point3::from(position + Vector3::new(0.0, 1.0, 0.0)))
-                        .xy(),
-                );
-
-                triangles.push([origin, origin + 1, origin + 2]);
-                triangles.push([origin, origin + 2, origin + 3]);
-            }
-            TileCollider::Mesh => {
-                // TODO: Add image-to-mesh conversion.
-            }
-        }
-    }
+    // TODO: Add image-to-mesh conversion for TileCollider::Mesh.
+    tile_map.collect_collider_geometry(&global_transform, &mut vertices, &mut triangles);
 
     if triangles.is_empty() {
         None
@@ -391,6 +387,19 @@ fn tile_map_to_collider_shape(
     }
 }
 
+// Calculates a hash of the data, that is used to build a collider shape from other scene nodes.
+// Changes of the hash mean that the shape must be rebuilt.
+fn collider_shape_source_hash(shape: &ColliderShape, nodes: &NodePool) -> u64 {
+    match shape {
+        ColliderShape::TileMap(tile_map_shape) => nodes
+            .try_borrow(tile_map_shape.tile_map.0)
+            .and_then(|node| node.component_ref::<TileMap>())
+            .map(|tile_map| tile_map.collider_geometry_hash())
+            .unwrap_or_default(),
+        _ => 0,
+    }
+}
+
 // Converts descriptor in a shared shape.
 fn collider_shape_into_native_shape(
     shape: &ColliderShape,
@@ -497,7 +506,15 @@ pub struct PhysicsWorld {
     // Event handler collects info about contacts and proximity events.
     #[visit(skip)]
     #[reflect(hidden)]
-    event_handler: Box<dyn EventHandler>,
+    event_collector: CollisionEventCollector,
+    // Owners of every collider. Owners of removed colliders are kept until the end of the next
+    // step, so the events of removed colliders can be resolved too.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    collider_owners: FxHashMap<ColliderHandle, Handle<Node>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    collision_events: Vec<CollisionEvent>,
     #[visit(skip)]
     #[reflect(hidden)]
     query: RefCell<QueryPipeline>,
@@ -609,7 +626,9 @@ impl PhysicsWorld {
                 set: MultibodyJointSet::new(),
                 map: Default::default(),
             },
-            event_handler: Box::new(()),
+            event_collector: Default::default(),
+            collider_owners: Default::default(),
+            collision_events: Default::default(),
             query: RefCell::new(Default::default()),
             performance_statistics: Default::default(),
             debug_render_pipeline: Default::default(),
@@ -617,6 +636,7 @@ impl PhysicsWorld {
     }
 
     pub(crate) fn update(&mut self, dt: f32) {
+        scope_profile!("PhysicsWorld2D::update");
         let time = instant::Instant::now();
 
         if *self.enabled {
@@ -666,10 +686,49 @@ impl PhysicsWorld {
                 // so we keep updating it manually.
                 None,
                 &(),
-                &*self.event_handler,
+                &self.event_collector,
             );
         }
 
+        self.collision_events.clear();
+        for event in self.event_collector.events.lock().drain(..) {
+            let (collider1, collider2, kind) = match event {
+                rapier2d::geometry::CollisionEvent::Started(collider1, collider2, flags) => (
+                    collider1,
+                    collider2,
+                    if flags.contains(CollisionEventFlags::SENSOR) {
+                        CollisionEventKind::TriggerEnter
+                    } else {
+                        CollisionEventKind::ContactStarted
+                    },
+                ),
+                rapier2d::geometry::CollisionEvent::Stopped(collider1, collider2, flags) => (
+                    collider1,
+                    collider2,
+                    if flags.contains(CollisionEventFlags::SENSOR) {
+                        CollisionEventKind::TriggerExit
+                    } else {
+                        CollisionEventKind::ContactStopped
+                    },
+                ),
+            };
+            if let (Some(collider), Some(other)) = (
+                self.collider_owners.get(&collider1),
+                self.collider_owners.get(&collider2),
+            ) {
+                self.collision_events.push(CollisionEvent {
+                    kind,
+                    collider: *collider,
+                    other: *other,
+                });
+            }
+        }
+        if self.collider_owners.len() != self.colliders.len() {
+            let colliders = &self.colliders;
+            self.collider_owners
+                .retain(|handle, _| colliders.contains(*handle));
+        }
+
         self.performance_statistics.step_time += instant::Instant::now() - time;
     }
 
@@ -696,8 +755,17 @@ impl PhysicsWorld {
         mut collider: Collider,
     ) -> ColliderHandle {
         collider.user_data = owner.encode_to_u128();
-        self.colliders
-            .insert_with_parent(collider, parent_body, &mut self.bodies)
+        let handle = self
+            .colliders
+            .insert_with_parent(collider, parent_body, &mut self.bodies);
+        self.collider_owners.insert(handle, owner);
+        handle
+    }
+
+    /// Returns a list of collision events, that were generated during the last physics step. See
+    /// [`CollisionEvent`] docs for more info.
+    pub fn collision_events(&self) -> &[CollisionEvent] {
+        &self.collision_events
     }
 
     pub(crate) fn remove_collider(&mut self, handle: ColliderHandle) -> bool {
@@ -890,6 +958,115 @@ impl PhysicsWorld {
             })
     }
 
+    /// Moves the shape of the character controller by the desired translation, using a series of
+    /// shape casts. Fills the given list with the collisions, that happened during the movement.
+    pub(crate) fn move_character(
+        &mut self,
+        character: &CharacterController,
+        nodes: &NodePool,
+        desired_translation: Vector2<f32>,
+        dt: f32,
+        collisions: &mut Vec<CharacterCollision>,
+    ) -> CharacterMovement {
+        collisions.clear();
+
+        let global_transform = character.global_transform();
+        let Some(shape) = collider_shape_into_native_shape(
+            character.shape(),
+            global_transform
+                .try_inverse()
+                .unwrap_or_else(Matrix4::identity),
+            nodes,
+        ) else {
+            return Default::default();
+        };
+
+        let controller = KinematicCharacterController {
+            up: Vector2::y_axis(),
+            offset: CharacterLength::Absolute(character.offset().max(f32::EPSILON)),
+            slide: character.slide(),
+            autostep: (character.step_height() > 0.0).then(|| CharacterAutostep {
+                max_height: CharacterLength::Absolute(character.step_height()),
+                min_width: CharacterLength::Absolute(character.step_min_width()),
+                include_dynamic_bodies: character.step_on_dynamic_bodies(),
+            }),
+            max_slope_climb_angle: character.max_slope_climb_angle(),
+            min_slope_slide_angle: character.min_slope_slide_angle(),
+            snap_to_ground: (character.snap_to_ground() > 0.0)
+                .then(|| CharacterLength::Absolute(character.snap_to_ground())),
+            ..Default::default()
+        };
+
+        let groups = character.collision_groups();
+        let filter = rapier2d::pipeline::QueryFilter::new()
+            .exclude_sensors()
+            .groups(InteractionGroups::new(
+                u32_to_group(groups.memberships.0),
+                u32_to_group(groups.filter.0),
+            ));
+
+        let mut query = self.query.borrow_mut();
+        query.update(&self.colliders);
+
+        let mut native_collisions = Vec::new();
+        let movement = controller.move_shape(
+            dt,
+            &self.bodies,
+            &self.colliders,
+            &query,
+            &*shape,
+            &isometry_from_global_transform(&global_transform),
+            desired_translation,
+            filter,
+            |collision| native_collisions.push(collision),
+        );
+
+        if character.mass() > 0.0 {
+            controller.solve_character_collision_impulses(
+                dt,
+                &mut self.bodies,
+                &self.colliders,
+                &query,
+                &*shape,
+                character.mass(),
+                &native_collisions,
+                filter,
+            );
+        }
+
+        collisions.extend(native_collisions.iter().filter_map(|collision| {
+            let collider = self.colliders.get(collision.handle)?;
+            Some(CharacterCollision {
+                collider: Handle::decode_from_u128(collider.user_data),
+                position: collision.character_pos.translation.vector,
+                translation_applied: collision.translation_applied,
+                translation_remaining: collision.translation_remaining,
+                normal: *collision.hit.normal1,
+            })
+        }));
+
+        // Report every collider hit by the character once, so the scripts could react on the hits
+        // in `on_collision`.
+        for (i, collision) in collisions.iter().enumerate() {
+            if collisions[..i]
+                .iter()
+                .all(|prev| prev.collider != collision.collider)
+            {
+                self.collision_events.push(CollisionEvent {
+                    kind: CollisionEventKind::CharacterHit,
+                    collider: character.handle(),
+                    other: collision.collider,
+                });
+            }
+        }
+
+        CharacterMovement {
+            translation: movement.translation,
+            grounded: movement.grounded,
+            is_sliding_down_slope: movement.is_sliding_down_slope,
+        }
+    }
+
     pub(crate) fn set_rigid_body_position(
         &mut self,
         rigid_body: &scene::dim2::rigidbody::RigidBody,
@@ -1105,7 +1282,10 @@ impl PhysicsWorld {
             return;
         }
 
-        let anything_changed = collider_node.needs_sync_model();
+        let source_hash = collider_shape_source_hash(collider_node.shape(), nodes);
+        let source_changed = collider_node.shape_source_hash.replace(source_hash) != source_hash;
+
+        let anything_changed = collider_node.needs_sync_model() || source_changed;
 
         // Important notes!
         // 1) The collider node may lack backing native physics collider in case if it
@@ -1143,19 +1323,23 @@ impl PhysicsWorld {
                         .restitution_combine_rule
                         .try_sync_model(|v| native.set_restitution_combine_rule(v.into()));
                     let mut remove_collider = false;
-                    collider_node.shape.try_sync_model(|v| {
+                    // The shape must be rebuilt when either the shape itself or its source data
+                    // (for example, tiles of a tile map) is changed.
+                    if collider_node.shape.try_sync_model(|_| ()) || source_changed {
                         let inv_global_transform = isometric_global_transform(nodes, handle)
                             .try_inverse()
                             .unwrap_or_default();
 
-                        if let Some(shape) =
-                            collider_shape_into_native_shape(&v, inv_global_transform, nodes)
-                        {
+                        if let Some(shape) = collider_shape_into_native_shape(
+                            collider_node.shape(),
+                            inv_global_transform,
+                            nodes,
+                        ) {
                             native.set_shape(shape);
                         } else {
                             remove_collider = true;
                         }
-                    });
+                    }
                     if remove_collider {
                         self.remove_collider(collider_node.native.get());
                         collider_node.native.set(ColliderHandle::invalid());
@@ -1197,7 +1381,8 @@ impl PhysicsWorld {
                             u32_to_group(collider_node.solver_groups().memberships.0),
                             u32_to_group(collider_node.solver_groups().filter.0),
                         ))
-                        .sensor(collider_node.is_sensor());
+                        .sensor(collider_node.is_sensor())
+                        .active_events(ActiveEvents::COLLISION_EVENTS);
 
                     if let Some(density) = collider_node.density() {
                         builder = builder.density(density);
@@ -52,6 +52,7 @@ use crate::{
         math::{aabb::AxisAlignedBoundingBox, Matrix4Ext},
         pool::{ErasedHandle, Handle, MultiBorrowContext, Pool, Ticket},
         reflect::prelude::*,
+        scope_profile,
         visitor::{Visit, VisitResult, Visitor},
     },
     graph::{AbstractSceneGraph, AbstractSceneNode, BaseSceneGraph, NodeHandleMap, SceneGraph},
@@ -926,6 +927,7 @@ impl Graph {
     /// This method could be slow for large graph. You should call it only when absolutely needed.
     #[inline]
     pub fn update_hierarchical_data(&mut self) {
+        scope_profile!("Graph::update_hierarchical_data");
         self.update_hierarchical_data_for_descendants(self.root);
     }
 
@@ -1055,6 +1057,7 @@ impl Graph {
     }
 
     fn sync_native(&mut self, switches: &GraphUpdateSwitches) {
+        scope_profile!("Graph::sync_native");
         let mut sync_context = SyncContext {
             nodes: &self.pool,
             physics: &mut self.physics,
@@ -1113,6 +1116,7 @@ impl Graph {
     /// Update switches allows you to disable update for parts of the update pipeline, it could be useful for editors
     /// where you need to have preview mode to update only specific set of nodes, etc.
     pub fn update(&mut self, frame_size: Vector2<f32>, dt: f32, switches: GraphUpdateSwitches) {
+        scope_profile!("Graph::update");
         self.sound_context.state().pause(switches.paused);
 
         if switches.paused {
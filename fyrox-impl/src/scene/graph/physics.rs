@@ -33,13 +33,15 @@ use crate::{
         parking_lot::Mutex,
         pool::Handle,
         reflect::prelude::*,
-        uuid_provider,
+        scope_profile, uuid_provider,
         variable::{InheritableVariable, VariableFlags},
         visitor::prelude::*,
         BiDirHashMap,
     },
+    fxhash::FxHashMap,
     scene::{
         self,
+        character_controller::{CharacterCollision, CharacterController, CharacterMovement},
         collider::{self, ColliderShape, GeometrySource},
         debug::SceneDrawingContext,
         graph::{isometric_global_transform, Graph, NodePool},
@@ -55,17 +57,18 @@ use crate::{
     utils::raw_mesh::{RawMeshBuilder, RawVertex},
 };
 use rapier3d::{
+    control::{CharacterAutostep, CharacterLength, KinematicCharacterController},
     dynamics::{
         CCDSolver, GenericJoint, GenericJointBuilder, ImpulseJointHandle, ImpulseJointSet,
         IslandManager, JointAxesMask, MultibodyJointHandle, MultibodyJointSet, RigidBody,
         RigidBodyActivation, RigidBodyBuilder, RigidBodyHandle, RigidBodySet, RigidBodyType,
     },
     geometry::{
-        Collider, ColliderBuilder, ColliderHandle, ColliderSet, Cuboid, DefaultBroadPhase,
-        InteractionGroups, NarrowPhase, Ray, SharedShape,
+        Collider, ColliderBuilder, ColliderHandle, ColliderSet, CollisionEventFlags, Cuboid,
+        DefaultBroadPhase, InteractionGroups, NarrowPhase, Ray, SharedShape,
     },
     parry::{query::ShapeCastOptions, shape::HeightField},
-    pipeline::{DebugRenderPipeline, EventHandler, PhysicsPipeline, QueryPipeline},
+    pipeline::{ActiveEvents, DebugRenderPipeline, EventHandler, PhysicsPipeline, QueryPipeline},
     prelude::{HeightFieldCellStatus, JointAxis},
 };
 use std::{
@@ -202,6 +205,82 @@ impl PhysicsPerformanceStatistics {
     }
 }
 
+/// A kind of a collision event. See [`CollisionEvent`] docs for more info.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CollisionEventKind {
+    /// Two colliders started touching each other.
+    ContactStarted,
+    /// Two colliders stopped touching each other.
+    ContactStopped,
+    /// A collider entered a sensor (or a sensor entered another collider).
+    TriggerEnter,
+    /// A collider left a sensor (or a sensor left another collider).
+    TriggerExit,
+    /// A character controller hit a collider while moving. Unlike other kinds, this event is
+    /// generated on every movement of the character, that touches the collider. The character
+    /// controller is the first "collider" of such event.
+    CharacterHit,
+}
+
+/// Collision event is generated by a physics world (both 2D and 3D) when two colliders start or
+/// stop touching each other. If one of the colliders is a sensor, trigger events are generated
+/// instead of contact events. Character controllers generate [`CollisionEventKind::CharacterHit`]
+/// events for every collider they hit while moving. A list of events of the last physics step could be fetched using
+/// [`PhysicsWorld::collision_events`], but usually it is much easier to handle the events in
+/// [`crate::script::ScriptTrait::on_collision`], which is called for every script of each collider
+/// (and its rigid body) taking part in a collision.
+///
+/// Events are generated only for colliders, that are attached to rigid bodies and that have
+/// compatible collision groups. Each collider could also filter out unwanted events using
+/// [`crate::scene::collider::Collider::set_event_filter`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CollisionEvent {
+    /// Kind of the event.
+    pub kind: CollisionEventKind,
+    /// A handle of the first collider.
+    pub collider: Handle<Node>,
+    /// A handle of the second collider.
+    pub other: Handle<Node>,
+}
+
+impl CollisionEvent {
+    /// Returns the same event, but from the point of view of the other collider.
+    pub fn swapped(self) -> Self {
+        Self {
+            kind: self.kind,
+            collider: self.other,
+            other: self.collider,
+        }
+    }
+}
+
+#[derive(Default)]
+struct CollisionEventCollector {
+    events: Mutex<Vec<rapier3d::geometry::CollisionEvent>>,
+}
+
+impl EventHandler for CollisionEventCollector {
+    fn handle_collision_event(
+        &self,
+        _bodies: &RigidBodySet,
+        _colliders: &ColliderSet,
+        event: rapier3d::geometry::CollisionEvent,
+        _contact_pair: Option<&rapier3d::geometry::ContactPair>,
+    ) {
+        self.events.lock().push(event);
+    }
+
+    fn handle_contact_force_event(
+        &self,
+        _dt: f32,
+        _bodies: &RigidBodySet,
+        _colliders: &ColliderSet,
+        _contact_pair: &rapier3d::geometry::ContactPair,
+        _total_force_magnitude: f32,
+    ) {
+    }
+}
+
 /// A ray intersection result.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Intersection {
@@ -1007,7 +1086,15 @@ pub struct PhysicsWorld {
     // Event handler collects info about contacts and proximity events.
     #[visit(skip)]
     #[reflect(hidden)]
-    event_handler: Box<dyn EventHandler>,
+    event_collector: CollisionEventCollector,
+    // Owners of every collider. Owners of removed colliders are kept until the end of the next
+    // step, so the events of removed colliders can be resolved too.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    collider_owners: FxHashMap<ColliderHandle, Handle<Node>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    collision_events: Vec<CollisionEvent>,
     #[visit(skip)]
     #[reflect(hidden)]
     query: RefCell<QueryPipeline>,
@@ -1120,7 +1207,9 @@ impl PhysicsWorld {
                 set: MultibodyJointSet::new(),
                 map: Default::default(),
             },
-            event_handler: Box::new(()),
+            event_collector: Default::default(),
+            collider_owners: Default::default(),
+            collision_events: Default::default(),
             query: RefCell::new(Default::default()),
             performance_statistics: Default::default(),
             debug_render_pipeline: Default::default(),
@@ -1128,6 +1217,7 @@ impl PhysicsWorld {
     }
 
     pub(super) fn update(&mut self, dt: f32) {
+        scope_profile!("PhysicsWorld::update");
         let time = instant::Instant::now();
 
         if *self.enabled {
@@ -1177,10 +1267,49 @@ impl PhysicsWorld {
                 // so we keep updating it manually.
                 None,
                 &(),
-                &*self.event_handler,
+                &self.event_collector,
             );
         }
 
+        self.collision_events.clear();
+        for event in self.event_collector.events.lock().drain(..) {
+            let (collider1, collider2, kind) = match event {
+                rapier3d::geometry::CollisionEvent::Started(collider1, collider2, flags) => (
+                    collider1,
+                    collider2,
+                    if flags.contains(CollisionEventFlags::SENSOR) {
+                        CollisionEventKind::TriggerEnter
+                    } else {
+                        CollisionEventKind::ContactStarted
+                    },
+                ),
+                rapier3d::geometry::CollisionEvent::Stopped(collider1, collider2, flags) => (
+                    collider1,
+                    collider2,
+                    if flags.contains(CollisionEventFlags::SENSOR) {
+                        CollisionEventKind::TriggerExit
+                    } else {
+                        CollisionEventKind::ContactStopped
+                    },
+                ),
+            };
+            if let (Some(collider), Some(other)) = (
+                self.collider_owners.get(&collider1),
+                self.collider_owners.get(&collider2),
+            ) {
+                self.collision_events.push(CollisionEvent {
+                    kind,
+                    collider: *collider,
+                    other: *other,
+                });
+            }
+        }
+        if self.collider_owners.len() != self.colliders.len() {
+            let colliders = &self.colliders;
+            self.collider_owners
+                .retain(|handle, _| colliders.contains(*handle));
+        }
+
         self.performance_statistics.step_time += instant::Instant::now() - time;
     }
 
@@ -1207,8 +1336,17 @@ impl PhysicsWorld {
         mut collider: Collider,
     ) -> ColliderHandle {
         collider.user_data = owner.encode_to_u128();
-        self.colliders
-            .insert_with_parent(collider, parent_body, &mut self.bodies)
+        let handle = self
+            .colliders
+            .insert_with_parent(collider, parent_body, &mut self.bodies);
+        self.collider_owners.insert(handle, owner);
+        handle
+    }
+
+    /// Returns a list of collision events, that were generated during the last physics step. See
+    /// [`CollisionEvent`] docs for more info.
+    pub fn collision_events(&self) -> &[CollisionEvent] {
+        &self.collision_events
     }
 
     pub(crate) fn remove_collider(&mut self, handle: ColliderHandle) -> bool {
@@ -1308,6 +1446,58 @@ impl PhysicsWorld {
         );
     }
 
+    /// Casts multiple rays at once and writes the closest intersection of each ray into the given
+    /// `results` buffer (`None` means that the respective ray does not intersect anything). This
+    /// method is much faster than consecutive [`Self::cast_ray`] calls, because internal
+    /// acceleration structure is updated only once. `sort_results` flag of the options is ignored.
+    pub fn cast_rays_closest(
+        &self,
+        rays: &[RayCastOptions],
+        results: &mut Vec<Option<Intersection>>,
+    ) {
+        let time = instant::Instant::now();
+
+        let mut query = self.query.borrow_mut();
+
+        query.update(&self.colliders);
+
+        results.clear();
+        results.extend(rays.iter().map(|opts| {
+            let ray = Ray::new(
+                opts.ray_origin,
+                opts.ray_direction
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or_default(),
+            );
+            query
+                .cast_ray_and_get_normal(
+                    &self.bodies,
+                    &self.colliders,
+                    &ray,
+                    opts.max_len,
+                    true,
+                    rapier3d::pipeline::QueryFilter::new().groups(InteractionGroups::new(
+                        u32_to_group(opts.groups.memberships.0),
+                        u32_to_group(opts.groups.filter.0),
+                    )),
+                )
+                .map(|(handle, intersection)| Intersection {
+                    collider: Handle::decode_from_u128(
+                        self.colliders.get(handle).unwrap().user_data,
+                    ),
+                    normal: intersection.normal,
+                    position: ray.point_at(intersection.time_of_impact),
+                    feature: intersection.feature.into(),
+                    toi: intersection.time_of_impact,
+                })
+        }));
+
+        self.performance_statistics.total_ray_cast_time.set(
+            self.performance_statistics.total_ray_cast_time.get()
+                + (instant::Instant::now() - time),
+        );
+    }
+
     /// Casts a shape at a constant linear velocity and retrieve the first collider it hits.
     ///
     /// This is similar to ray-casting except that we are casting a whole shape instead of just a
@@ -1401,6 +1591,116 @@ impl PhysicsWorld {
             })
     }
 
+    /// Moves the shape of the character controller by the desired translation, using a series of
+    /// shape casts. Fills the given list with the collisions, that happened during the movement.
+    pub(crate) fn move_character(
+        &mut self,
+        character: &CharacterController,
+        nodes: &NodePool,
+        desired_translation: Vector3<f32>,
+        dt: f32,
+        collisions: &mut Vec<CharacterCollision>,
+    ) -> CharacterMovement {
+        collisions.clear();
+
+        let global_transform = character.global_transform();
+        let Some(shape) = collider_shape_into_native_shape(
+            character.shape(),
+            global_transform
+                .try_inverse()
+                .unwrap_or_else(Matrix4::identity),
+            character.handle(),
+            nodes,
+        ) else {
+            return Default::default();
+        };
+
+        let controller = KinematicCharacterController {
+            up: Vector3::y_axis(),
+            offset: CharacterLength::Absolute(character.offset().max(f32::EPSILON)),
+            slide: character.slide(),
+            autostep: (character.step_height() > 0.0).then(|| CharacterAutostep {
+                max_height: CharacterLength::Absolute(character.step_height()),
+                min_width: CharacterLength::Absolute(character.step_min_width()),
+                include_dynamic_bodies: character.step_on_dynamic_bodies(),
+            }),
+            max_slope_climb_angle: character.max_slope_climb_angle(),
+            min_slope_slide_angle: character.min_slope_slide_angle(),
+            snap_to_ground: (character.snap_to_ground() > 0.0)
+                .then(|| CharacterLength::Absolute(character.snap_to_ground())),
+            ..Default::default()
+        };
+
+        let groups = character.collision_groups();
+        let filter = rapier3d::pipeline::QueryFilter::new()
+            .exclude_sensors()
+            .groups(InteractionGroups::new(
+                u32_to_group(groups.memberships.0),
+                u32_to_group(groups.filter.0),
+            ));
+
+        let mut query = self.query.borrow_mut();
+        query.update(&self.colliders);
+
+        let mut native_collisions = Vec::new();
+        let movement = controller.move_shape(
+            dt,
+            &self.bodies,
+            &self.colliders,
+            &query,
+            &*shape,
+            &isometry_from_global_transform(&global_transform),
+            desired_translation,
+            filter,
+            |collision| native_collisions.push(collision),
+        );
+
+        if character.mass() > 0.0 {
+            controller.solve_character_collision_impulses(
+                dt,
+                &mut self.bodies,
+                &self.colliders,
+                &query,
+                &*shape,
+                character.mass(),
+                &native_collisions,
+                filter,
+            );
+        }
+
+        collisions.extend(native_collisions.iter().filter_map(|collision| {
+            let collider = self.colliders.get(collision.handle)?;
+            Some(CharacterCollision {
+                collider: Handle::decode_from_u128(collider.user_data),
+                position: collision.character_pos.translation.vector,
+                translation_applied: collision.translation_applied,
+                translation_remaining: collision.translation_remaining,
+                normal: *collision.hit.normal1,
+            })
+        }));
+
+        // Report every collider hit by the character once, so the scripts could react on the hits
+        // in `on_collision`.
+        for (i, collision) in collisions.iter().enumerate() {
+            if collisions[..i]
+                .iter()
+                .all(|prev| prev.collider != collision.collider)
+            {
+                self.collision_events.push(CollisionEvent {
+                    kind: CollisionEventKind::CharacterHit,
+                    collider: character.handle(),
+                    other: collision.collider,
+                });
+            }
+        }
+
+        CharacterMovement {
+            translation: movement.translation,
+            grounded: movement.grounded,
+            is_sliding_down_slope: movement.is_sliding_down_slope,
+        }
+    }
+
     pub(crate) fn set_rigid_body_position(
         &mut self,
         rigid_body: &scene::rigidbody::RigidBody,
@@ -1732,7 +2032,8 @@ impl PhysicsWorld {
                             u32_to_group(collider_node.solver_groups().memberships.0),
                             u32_to_group(collider_node.solver_groups().filter.0),
                         ))
-                        .sensor(collider_node.is_sensor());
+                        .sensor(collider_node.is_sensor())
+                        .active_events(ActiveEvents::COLLISION_EVENTS);
 
                     if let Some(density) = collider_node.density() {
                         builder = builder.density(density);
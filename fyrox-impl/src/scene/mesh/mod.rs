@@ -532,7 +532,7 @@ impl Mesh {
     }
 }
 
-fn extend_aabb_from_vertex_buffer(
+pub(crate) fn extend_aabb_from_vertex_buffer(
     vertex_buffer: &VertexBuffer,
     bounding_box: &mut AxisAlignedBoundingBox,
 ) {
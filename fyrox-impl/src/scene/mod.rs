@@ -28,6 +28,7 @@ pub mod accel;
 pub mod animation;
 pub mod base;
 pub mod camera;
+pub mod character_controller;
 pub mod collider;
 pub mod debug;
 pub mod decal;
@@ -41,6 +42,7 @@ pub mod node;
 pub mod particle_system;
 pub mod pivot;
 pub mod ragdoll;
+pub mod replication;
 pub mod rigidbody;
 pub mod sound;
 pub mod sprite;
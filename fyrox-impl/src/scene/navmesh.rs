@@ -40,7 +40,10 @@ use crate::{
         graph::Graph,
         node::{Node, NodeTrait},
     },
-    utils::navmesh::Navmesh,
+    utils::navmesh::{
+        generator::{NavmeshGenerationSettings, NavmeshGenerator},
+        Navmesh,
+    },
 };
 use fyrox_core::algebra::Vector3;
 use fyrox_core::math::TriangleDefinition;
@@ -102,6 +105,34 @@ impl Visit for Container {
 /// }
 /// ```
 ///
+/// ## Automatic generation
+///
+/// Navigational mesh could also be generated automatically from the geometry of the scene. Specify
+/// a set of nodes whose geometry (including all descendant nodes) should be used and the generation
+/// settings (agent size, maximum slope, etc.), and then generate the navmesh. See [`NavmeshGenerator`]
+/// docs for more info.
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::pool::Handle,
+/// #     scene::{base::BaseBuilder, graph::Graph, navmesh::NavigationalMeshBuilder, node::Node},
+/// #     utils::navmesh::generator::NavmeshGenerationSettings,
+/// # };
+/// fn create_navmesh(graph: &mut Graph, level: Handle<Node>) -> Handle<Node> {
+///     NavigationalMeshBuilder::new(BaseBuilder::new())
+///         .with_generation_sources(vec![level])
+///         .with_generation_settings(NavmeshGenerationSettings {
+///             agent_radius: 0.5,
+///             ..Default::default()
+///         })
+///         .with_generated_navmesh(graph)
+///         .build(graph)
+/// }
+/// ```
+///
+/// Existing navigational mesh could be regenerated using [`NavigationalMesh::generate_navmesh`]
+/// method, for example, when the level has changed.
+///
 /// ## Agents
 ///
 /// Navigational mesh agent helps you to build paths along the surface of a navigational mesh and follow it. Agents can be
@@ -159,6 +190,13 @@ pub struct NavigationalMesh {
     base: Base,
     #[reflect(read_only)]
     navmesh: InheritableVariable<Container>,
+    /// Settings that will be used for automatic generation of the navigational mesh.
+    #[visit(optional)]
+    generation_settings: InheritableVariable<NavmeshGenerationSettings>,
+    /// A set of nodes whose geometry (including all descendant nodes) will be used for automatic
+    /// generation of the navigational mesh.
+    #[visit(optional)]
+    generation_sources: InheritableVariable<Vec<Handle<Node>>>,
 }
 
 impl TypeUuidProvider for NavigationalMesh {
@@ -250,12 +288,67 @@ impl NavigationalMesh {
     pub fn navmesh(&self) -> Arc<RwLock<Navmesh>> {
         self.navmesh.0.clone()
     }
+
+    /// Sets new settings for automatic navmesh generation. Returns old settings.
+    pub fn set_generation_settings(
+        &mut self,
+        settings: NavmeshGenerationSettings,
+    ) -> NavmeshGenerationSettings {
+        self.generation_settings
+            .set_value_and_mark_modified(settings)
+    }
+
+    /// Returns current settings for automatic navmesh generation.
+    pub fn generation_settings(&self) -> &NavmeshGenerationSettings {
+        &self.generation_settings
+    }
+
+    /// Sets a new set of nodes whose geometry will be used for automatic navmesh generation.
+    /// Returns old set of nodes.
+    pub fn set_generation_sources(&mut self, sources: Vec<Handle<Node>>) -> Vec<Handle<Node>> {
+        self.generation_sources.set_value_and_mark_modified(sources)
+    }
+
+    /// Returns a set of nodes whose geometry will be used for automatic navmesh generation.
+    pub fn generation_sources(&self) -> &[Handle<Node>] {
+        &self.generation_sources
+    }
+
+    /// Generates new navigational mesh from the geometry of generation sources using current
+    /// generation settings. This method does not modify the current navmesh, use
+    /// [`Self::navmesh_mut`] to replace it with the generated one:
+    ///
+    /// ```rust
+    /// # use fyrox_impl::scene::{graph::Graph, navmesh::NavigationalMesh, node::Node};
+    /// # use fyrox_impl::core::pool::Handle;
+    /// fn regenerate_navmesh(graph: &mut Graph, handle: Handle<Node>) {
+    ///     let navmesh = graph[handle].as_navigational_mesh().generate_navmesh(graph);
+    ///     *graph[handle].as_navigational_mesh_mut().navmesh_mut() = navmesh;
+    /// }
+    /// ```
+    pub fn generate_navmesh(&self, graph: &Graph) -> Navmesh {
+        generate(graph, &self.generation_sources, &self.generation_settings)
+    }
+}
+
+fn generate(
+    graph: &Graph,
+    sources: &[Handle<Node>],
+    settings: &NavmeshGenerationSettings,
+) -> Navmesh {
+    let mut generator = NavmeshGenerator::new(settings.clone());
+    for source in sources {
+        generator.add_node(graph, *source);
+    }
+    generator.generate()
 }
 
 /// Creates navigational meshes and adds them to a scene graph.
 pub struct NavigationalMeshBuilder {
     base_builder: BaseBuilder,
     navmesh: Navmesh,
+    generation_settings: NavmeshGenerationSettings,
+    generation_sources: Vec<Handle<Node>>,
 }
 
 impl NavigationalMeshBuilder {
@@ -264,6 +357,8 @@ impl NavigationalMeshBuilder {
         Self {
             base_builder,
             navmesh: Default::default(),
+            generation_settings: Default::default(),
+            generation_sources: Default::default(),
         }
     }
 
@@ -273,12 +368,33 @@ impl NavigationalMeshBuilder {
         self
     }
 
+    /// Sets the settings for automatic navmesh generation.
+    pub fn with_generation_settings(mut self, settings: NavmeshGenerationSettings) -> Self {
+        self.generation_settings = settings;
+        self
+    }
+
+    /// Sets a set of nodes whose geometry will be used for automatic navmesh generation.
+    pub fn with_generation_sources(mut self, sources: Vec<Handle<Node>>) -> Self {
+        self.generation_sources = sources;
+        self
+    }
+
+    /// Generates the navigational mesh from the geometry of generation sources using current
+    /// generation settings. See [`NavmeshGenerator`] docs for more info.
+    pub fn with_generated_navmesh(mut self, graph: &Graph) -> Self {
+        self.navmesh = generate(graph, &self.generation_sources, &self.generation_settings);
+        self
+    }
+
     fn build_navigational_mesh(self) -> NavigationalMesh {
         NavigationalMesh {
             base: self.base_builder.build_base(),
             navmesh: InheritableVariable::new_modified(Container(Arc::new(RwLock::new(
                 self.navmesh,
             )))),
+            generation_settings: self.generation_settings.into(),
+            generation_sources: self.generation_sources.into(),
         }
     }
 
@@ -51,6 +51,7 @@ pub type NodeConstructorContainer = GraphNodeConstructorContainer<Node, Graph>;
 pub fn new_node_constructor_container() -> NodeConstructorContainer {
     let container = NodeConstructorContainer::default();
 
+    container.add::<dim2::character_controller::CharacterController>();
     container.add::<dim2::collider::Collider>();
     container.add::<dim2::joint::Joint>();
     container.add::<Rectangle>();
@@ -75,6 +76,7 @@ pub fn new_node_constructor_container() -> NodeConstructorContainer {
     container.add::<NavigationalMesh>();
     container.add::<Ragdoll>();
     container.add::<TileMap>();
+    container.add::<scene::character_controller::CharacterController>();
 
     container
 }
@@ -0,0 +1,125 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Contains settings of particle collisions with the physics world. See [`ParticleCollision`] docs
+//! for more info.
+
+use crate::{
+    core::{reflect::prelude::*, type_traits::prelude::*, uuid::Uuid, visitor::prelude::*},
+    scene::collider::InteractionGroups,
+};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Defines what happens with a particle when it collides with a collider.
+#[derive(
+    Default,
+    Copy,
+    Clone,
+    PartialOrd,
+    PartialEq,
+    Eq,
+    Ord,
+    Hash,
+    Debug,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+    TypeUuidProvider,
+)]
+#[type_uuid(id = "8f3cd5e4-8a4e-44a4-a1b6-5d2d0f7d2e61")]
+pub enum ParticleCollisionResponse {
+    /// A particle bounces off the surface it has collided with. The outgoing velocity is defined
+    /// by restitution and friction coefficients of [`ParticleCollision`]. Zero restitution makes
+    /// particles slide along the surface. This mode is suitable for sparks, debris, etc.
+    #[default]
+    Bounce,
+    /// A particle dies immediately on contact. This mode is suitable for rain drops, bullets
+    /// trails, etc.
+    Kill,
+}
+
+/// Settings of particle collisions with the colliders of the 3D physics world. Collisions are
+/// detected by casting a ray along the path traveled by each particle during the update tick, so
+/// particles do not tunnel through thin geometry even at high speeds.
+///
+/// # Performance
+///
+/// Every alive particle requires a single ray cast per update tick, which could be quite expensive
+/// for particle systems with thousands of particles. Use collision groups to limit the set of
+/// colliders, that will be checked.
+#[derive(Debug, Clone, PartialEq, Visit, Reflect)]
+pub struct ParticleCollision {
+    /// Whether the collisions are enabled or not.
+    pub enabled: bool,
+    /// Defines what happens with a particle when it collides with a collider.
+    pub response: ParticleCollisionResponse,
+    /// Defines how much of the normal velocity a particle keeps after a bounce. 0.0 - no bounce at
+    /// all, 1.0 - perfectly elastic bounce.
+    #[reflect(min_value = 0.0, max_value = 1.0)]
+    pub restitution: f32,
+    /// Defines how much of the tangential velocity a particle loses after a collision. 0.0 - no
+    /// friction, 1.0 - a particle stops moving along the surface.
+    #[reflect(min_value = 0.0, max_value = 1.0)]
+    pub friction: f32,
+    /// Collision groups, that define a set of colliders the particles could collide with.
+    pub groups: InteractionGroups,
+}
+
+impl Default for ParticleCollision {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            response: Default::default(),
+            restitution: 0.5,
+            friction: 0.1,
+            groups: Default::default(),
+        }
+    }
+}
+
+impl ParticleCollision {
+    /// Creates new enabled collision settings with the given response.
+    pub fn new(response: ParticleCollisionResponse) -> Self {
+        Self {
+            enabled: true,
+            response,
+            ..Default::default()
+        }
+    }
+
+    /// Sets restitution coefficient in builder manner.
+    pub fn with_restitution(mut self, restitution: f32) -> Self {
+        self.restitution = restitution.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets friction coefficient in builder manner.
+    pub fn with_friction(mut self, friction: f32) -> Self {
+        self.friction = friction.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets collision groups in builder manner.
+    pub fn with_groups(mut self, groups: InteractionGroups) -> Self {
+        self.groups = groups;
+        self
+    }
+}
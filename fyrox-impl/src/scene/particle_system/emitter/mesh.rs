@@ -0,0 +1,415 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Mesh emitter spawns particles on the surface of a mesh node. It supports skinned meshes, in this
+//! case the particles are spawned on the deformed surface. Can be used to create burning
+//! characters, dissolving props, spell effects, etc.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, Vector3},
+        numeric_range::RangeExt,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::Uuid,
+        visitor::prelude::*,
+    },
+    rand::Rng,
+    scene::{
+        graph::NodePool,
+        mesh::{
+            buffer::{VertexAttributeUsage, VertexReadTrait},
+            Mesh,
+        },
+        node::Node,
+        particle_system::{
+            emitter::{
+                base::{BaseEmitter, BaseEmitterBuilder},
+                Emit, Emitter,
+            },
+            particle::Particle,
+            ParticleSystemRng,
+        },
+    },
+};
+use std::ops::{Deref, DerefMut, Range};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Defines where the particles will be spawned on a mesh.
+#[derive(
+    Default,
+    Copy,
+    Clone,
+    PartialOrd,
+    PartialEq,
+    Eq,
+    Ord,
+    Hash,
+    Debug,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+    TypeUuidProvider,
+)]
+#[type_uuid(id = "a4e6c5b2-3f1d-4e8a-9b7c-1d2e3f4a5b6c")]
+pub enum MeshEmitterMode {
+    /// Particles are spawned uniformly on the triangles of the mesh. Larger triangles receive
+    /// proportionally more particles.
+    #[default]
+    Triangles,
+    /// Particles are spawned on the vertices of the mesh.
+    Vertices,
+}
+
+/// A sampled surface of a mesh in the local coordinates of a particle system.
+#[derive(Debug, Default, Clone)]
+struct MeshEmitterGeometry {
+    positions: Vec<Vector3<f32>>,
+    normals: Vec<Vector3<f32>>,
+    triangles: Vec<[u32; 3]>,
+    cumulative_areas: Vec<f32>,
+    // Transforms used to build the geometry, used to check whether the geometry must be rebuilt.
+    mesh_transform: Matrix4<f32>,
+    particle_system_transform: Matrix4<f32>,
+    is_skinned: bool,
+}
+
+impl MeshEmitterGeometry {
+    fn sample(
+        &self,
+        mode: MeshEmitterMode,
+        rng: &mut ParticleSystemRng,
+    ) -> Option<(Vector3<f32>, Vector3<f32>)> {
+        match mode {
+            MeshEmitterMode::Triangles => {
+                let total_area = *self.cumulative_areas.last()?;
+                let value = (0.0..total_area).random(rng);
+                let index = self
+                    .cumulative_areas
+                    .partition_point(|area| *area < value)
+                    .min(self.triangles.len() - 1);
+                let [a, b, c] = self.triangles[index].map(|i| i as usize);
+
+                // Uniform sampling of a triangle.
+                let r1 = rng.gen::<f32>().sqrt();
+                let r2 = rng.gen::<f32>();
+                let (u, v, w) = (1.0 - r1, r1 * (1.0 - r2), r1 * r2);
+
+                let position =
+                    self.positions[a] * u + self.positions[b] * v + self.positions[c] * w;
+                let normal = (self.normals[a] * u + self.normals[b] * v + self.normals[c] * w)
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or_default();
+                Some((position, normal))
+            }
+            MeshEmitterMode::Vertices => {
+                if self.positions.is_empty() {
+                    None
+                } else {
+                    let index = rng.gen_range(0..self.positions.len());
+                    Some((self.positions[index], self.normals[index]))
+                }
+            }
+        }
+    }
+}
+
+/// See module docs.
+#[derive(Debug, Clone, Visit, Reflect)]
+pub struct MeshEmitter {
+    emitter: BaseEmitter,
+    /// A handle of a mesh node, that will be used as the source of the surface.
+    mesh: Handle<Node>,
+    /// Defines where the particles will be spawned on the mesh.
+    mode: MeshEmitterMode,
+    /// Range of initial speed of a particle along the surface normal. It is added to the initial
+    /// velocity defined by the base emitter.
+    normal_velocity: Range<f32>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    geometry: Box<MeshEmitterGeometry>,
+}
+
+impl PartialEq for MeshEmitter {
+    fn eq(&self, other: &Self) -> bool {
+        self.emitter == other.emitter
+            && self.mesh == other.mesh
+            && self.mode == other.mode
+            && self.normal_velocity == other.normal_velocity
+    }
+}
+
+impl Deref for MeshEmitter {
+    type Target = BaseEmitter;
+
+    fn deref(&self) -> &Self::Target {
+        &self.emitter
+    }
+}
+
+impl DerefMut for MeshEmitter {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.emitter
+    }
+}
+
+impl Default for MeshEmitter {
+    fn default() -> Self {
+        Self {
+            emitter: Default::default(),
+            mesh: Default::default(),
+            mode: Default::default(),
+            normal_velocity: 0.0..0.0,
+            geometry: Default::default(),
+        }
+    }
+}
+
+impl MeshEmitter {
+    /// Creates new mesh emitter, that spawns the particles on the surface of the given mesh.
+    pub fn new(emitter: BaseEmitter, mesh: Handle<Node>, mode: MeshEmitterMode) -> Self {
+        Self {
+            emitter,
+            mesh,
+            mode,
+            ..Default::default()
+        }
+    }
+
+    /// Returns a handle of current source mesh.
+    pub fn mesh(&self) -> Handle<Node> {
+        self.mesh
+    }
+
+    /// Sets new source mesh.
+    pub fn set_mesh(&mut self, mesh: Handle<Node>) {
+        self.mesh = mesh;
+        *self.geometry = Default::default();
+    }
+
+    /// Returns current emission mode.
+    pub fn mode(&self) -> MeshEmitterMode {
+        self.mode
+    }
+
+    /// Sets new emission mode.
+    pub fn set_mode(&mut self, mode: MeshEmitterMode) {
+        self.mode = mode;
+    }
+
+    /// Returns current range of initial speed along the surface normal.
+    pub fn normal_velocity_range(&self) -> Range<f32> {
+        self.normal_velocity.clone()
+    }
+
+    /// Sets new range of initial speed along the surface normal.
+    pub fn set_normal_velocity_range(&mut self, range: Range<f32>) {
+        self.normal_velocity = range;
+    }
+
+    /// Returns `true` if the emitter has a surface to spawn the particles on.
+    pub fn has_geometry(&self) -> bool {
+        !self.geometry.positions.is_empty()
+    }
+
+    /// Updates internal copy of the mesh surface. Static meshes are re-sampled only if the
+    /// transformation of the mesh or the particle system has changed, skinned meshes are re-sampled
+    /// every call. There is no need to call it manually, it will be automatically called by the
+    /// particle system.
+    pub fn update_geometry(&mut self, nodes: &NodePool, particle_system_transform: &Matrix4<f32>) {
+        let Some(mesh) = nodes
+            .try_borrow(self.mesh)
+            .and_then(|node| node.cast::<Mesh>())
+        else {
+            *self.geometry = Default::default();
+            return;
+        };
+
+        let mesh_transform = mesh.global_transform();
+        if self.has_geometry()
+            && !self.geometry.is_skinned
+            && self.geometry.mesh_transform == mesh_transform
+            && self.geometry.particle_system_transform == *particle_system_transform
+        {
+            return;
+        }
+
+        let Some(inv_particle_system_transform) = particle_system_transform.try_inverse() else {
+            return;
+        };
+
+        let mut geometry = MeshEmitterGeometry {
+            mesh_transform,
+            particle_system_transform: *particle_system_transform,
+            ..Default::default()
+        };
+
+        for surface in mesh.surfaces() {
+            let data = surface.data();
+            let data = data.data_ref();
+
+            let bone_matrices = surface
+                .bones()
+                .iter()
+                .map(|bone| {
+                    nodes
+                        .try_borrow(*bone)
+                        .map(|bone| bone.global_transform() * bone.inv_bind_pose_transform())
+                        .unwrap_or_else(Matrix4::identity)
+                })
+                .collect::<Vec<_>>();
+            geometry.is_skinned |= !bone_matrices.is_empty();
+
+            let vertex_offset = geometry.positions.len() as u32;
+
+            for view in data.vertex_buffer.iter() {
+                let Ok(position) = view.read_3_f32(VertexAttributeUsage::Position) else {
+                    break;
+                };
+                let normal = view
+                    .read_3_f32(VertexAttributeUsage::Normal)
+                    .unwrap_or_else(|_| Vector3::y());
+
+                // Calculate world-space position and normal of the vertex.
+                let (world_position, world_normal) = if bone_matrices.is_empty() {
+                    (
+                        mesh_transform
+                            .transform_point(&Point3::from(position))
+                            .coords,
+                        mesh_transform.transform_vector(&normal),
+                    )
+                } else {
+                    let (Ok(bone_indices), Ok(bone_weights)) = (
+                        view.read_4_u8(VertexAttributeUsage::BoneIndices),
+                        view.read_4_f32(VertexAttributeUsage::BoneWeight),
+                    ) else {
+                        break;
+                    };
+                    let mut world_position = Vector3::default();
+                    let mut world_normal = Vector3::default();
+                    for (&bone_index, &weight) in bone_indices.iter().zip(bone_weights.iter()) {
+                        if let Some(bone_matrix) = bone_matrices.get(bone_index as usize) {
+                            world_position += bone_matrix
+                                .transform_point(&Point3::from(position))
+                                .coords
+                                .scale(weight);
+                            world_normal += bone_matrix.transform_vector(&normal).scale(weight);
+                        }
+                    }
+                    (world_position, world_normal)
+                };
+
+                geometry.positions.push(
+                    inv_particle_system_transform
+                        .transform_point(&Point3::from(world_position))
+                        .coords,
+                );
+                geometry.normals.push(
+                    inv_particle_system_transform
+                        .transform_vector(&world_normal)
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or_default(),
+                );
+            }
+
+            let vertex_count = geometry.positions.len() as u32;
+            for triangle in data.geometry_buffer.iter() {
+                let indices = triangle.0.map(|i| i + vertex_offset);
+                if indices.iter().all(|i| *i < vertex_count) {
+                    geometry.triangles.push(indices);
+                }
+            }
+        }
+
+        let mut total_area = 0.0;
+        for [a, b, c] in geometry.triangles.iter() {
+            let a = geometry.positions[*a as usize];
+            let b = geometry.positions[*b as usize];
+            let c = geometry.positions[*c as usize];
+            total_area += (b - a).cross(&(c - a)).norm() * 0.5;
+            geometry.cumulative_areas.push(total_area);
+        }
+
+        *self.geometry = geometry;
+    }
+}
+
+impl Emit for MeshEmitter {
+    fn emit(&self, particle: &mut Particle, rng: &mut ParticleSystemRng) {
+        self.emitter.emit(particle, rng);
+        particle.position = self.position();
+        if let Some((position, normal)) = self.geometry.sample(self.mode, rng) {
+            particle.position += position;
+            particle.velocity += normal.scale(self.normal_velocity.random(rng));
+        }
+    }
+}
+
+/// Mesh emitter builder allows you to construct mesh emitter in declarative manner.
+/// This is typical implementation of Builder pattern.
+pub struct MeshEmitterBuilder {
+    base: BaseEmitterBuilder,
+    mesh: Handle<Node>,
+    mode: MeshEmitterMode,
+    normal_velocity: Range<f32>,
+}
+
+impl MeshEmitterBuilder {
+    /// Creates new mesh emitter builder.
+    pub fn new(base: BaseEmitterBuilder) -> Self {
+        Self {
+            base,
+            mesh: Default::default(),
+            mode: Default::default(),
+            normal_velocity: 0.0..0.0,
+        }
+    }
+
+    /// Sets desired source mesh.
+    pub fn with_mesh(mut self, mesh: Handle<Node>) -> Self {
+        self.mesh = mesh;
+        self
+    }
+
+    /// Sets desired emission mode.
+    pub fn with_mode(mut self, mode: MeshEmitterMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets desired range of initial speed along the surface normal.
+    pub fn with_normal_velocity_range(mut self, range: Range<f32>) -> Self {
+        self.normal_velocity = range;
+        self
+    }
+
+    /// Creates new mesh emitter.
+    pub fn build(self) -> Emitter {
+        Emitter::Mesh(MeshEmitter {
+            emitter: self.base.build(),
+            mesh: self.mesh,
+            mode: self.mode,
+            normal_velocity: self.normal_velocity,
+            geometry: Default::default(),
+        })
+    }
+}
@@ -26,10 +26,10 @@ use crate::scene::particle_system::emitter::base::BaseEmitterBuilder;
 use crate::scene::particle_system::emitter::sphere::SphereEmitterBuilder;
 use crate::{
     core::{
-        algebra::{Point3, Vector2, Vector3},
+        algebra::{Matrix4, Point3, Vector2, Vector3},
         color::Color,
         color_gradient::ColorGradient,
-        math::{aabb::AxisAlignedBoundingBox, TriangleDefinition},
+        math::{aabb::AxisAlignedBoundingBox, curve::Curve, TriangleDefinition},
         pool::Handle,
         reflect::prelude::*,
         type_traits::prelude::*,
@@ -43,13 +43,19 @@ use crate::{
     renderer::{self, bundle::RenderContext},
     scene::{
         base::{Base, BaseBuilder},
-        graph::Graph,
+        graph::{
+            physics::{PhysicsWorld, RayCastOptions},
+            Graph,
+        },
         mesh::{buffer::VertexTrait, RenderPath},
         node::{Node, NodeTrait, RdcControlFlow, UpdateContext},
         particle_system::{
+            collision::{ParticleCollision, ParticleCollisionResponse},
             draw::Vertex,
             emitter::{Emit, Emitter},
             particle::Particle,
+            sub_emitter::{SubEmitter, SubEmitterTrigger},
+            trail::ParticleTrail,
         },
     },
 };
@@ -62,9 +68,12 @@ use std::{
 };
 use strum_macros::{AsRefStr, EnumString, VariantNames};
 
+pub mod collision;
 pub(crate) mod draw;
 pub mod emitter;
 pub mod particle;
+pub mod sub_emitter;
+pub mod trail;
 
 /// Pseudo-random numbers generator for particle systems.
 #[derive(Debug, Clone, Reflect)]
@@ -147,6 +156,25 @@ impl Visit for ParticleSystemRng {
 /// Particle system can contain multiple particle emitters, each emitter has its own
 /// set of properties and it defines law of change of particle parameters over time.
 ///
+/// # Over-lifetime curves
+///
+/// Besides the color gradient, size, velocity and rotation speed of particles could be modulated
+/// over their lifetime using curves. Horizontal axis of a curve is normalized lifetime of a
+/// particle (0.0 - the particle was just spawned, 1.0 - the particle is about to die) and vertical
+/// axis is a multiplier for the respective value. An empty curve means no modulation.
+///
+/// # Sub-emitters and trails
+///
+/// Sub-emitters spawn bursts of particles when particles of the main emitters are born or die,
+/// see [`SubEmitter`] docs for more info. Every particle could also leave a ribbon trail behind
+/// it, see [`ParticleTrail`] docs for more info.
+///
+/// # Collisions
+///
+/// Particles could collide with the colliders of the 3D physics world, bouncing off the surfaces
+/// or dying on contact. Collisions are disabled by default, see [`ParticleCollision`] docs and
+/// [`ParticleSystem::set_collision`] for more info.
+///
 /// # Performance
 ///
 /// In general particle system can be considered as heavy visual effect, but total impact
@@ -237,6 +265,36 @@ pub struct ParticleSystem {
     #[reflect(setter = "set_color_over_lifetime_gradient")]
     color_over_lifetime: InheritableVariable<ColorGradient>,
 
+    #[reflect(
+        setter = "set_size_over_lifetime",
+        description = "A curve that defines size multiplier of particles over their normalized \
+        lifetime. Empty curve means no modulation."
+    )]
+    size_over_lifetime: InheritableVariable<Curve>,
+
+    #[reflect(
+        setter = "set_velocity_over_lifetime",
+        description = "A curve that defines velocity multiplier of particles over their normalized \
+        lifetime. Empty curve means no modulation."
+    )]
+    velocity_over_lifetime: InheritableVariable<Curve>,
+
+    #[reflect(
+        setter = "set_rotation_speed_over_lifetime",
+        description = "A curve that defines rotation speed multiplier of particles over their \
+        normalized lifetime. Empty curve means no modulation."
+    )]
+    rotation_speed_over_lifetime: InheritableVariable<Curve>,
+
+    /// List of sub-emitters of the particle system.
+    pub sub_emitters: InheritableVariable<Vec<SubEmitter>>,
+
+    #[reflect(
+        setter = "set_trail",
+        description = "Defines ribbon trails of the particles."
+    )]
+    trail: InheritableVariable<ParticleTrail>,
+
     #[reflect(setter = "play")]
     is_playing: InheritableVariable<bool>,
 
@@ -261,9 +319,31 @@ pub struct ParticleSystem {
     )]
     coordinate_system: InheritableVariable<CoordinateSystem>,
 
+    #[reflect(
+        setter = "set_collision",
+        description = "Defines how particles collide with the colliders of the physics world."
+    )]
+    collision: InheritableVariable<ParticleCollision>,
+
     rng: ParticleSystemRng,
 }
 
+/// A particle event (birth or death), that triggers sub-emitters.
+struct ParticleEvent {
+    position: Vector3<f32>,
+    velocity: Vector3<f32>,
+}
+
+/// Returns a multiplier defined by the given curve at the given normalized lifetime. Empty curve
+/// means no modulation.
+fn curve_factor(curve: &Curve, k: f32) -> f32 {
+    if curve.is_empty() {
+        1.0
+    } else {
+        curve.value_at(k)
+    }
+}
+
 /// Coordinate system for particles generated by a particle system.
 #[derive(
     Default,
@@ -316,6 +396,18 @@ impl Visit for ParticleSystem {
         let _ = self
             .coordinate_system
             .visit("CoordinateSystem", &mut region);
+        let _ = self.collision.visit("Collision", &mut region);
+        let _ = self
+            .size_over_lifetime
+            .visit("SizeOverLifetime", &mut region);
+        let _ = self
+            .velocity_over_lifetime
+            .visit("VelocityOverLifetime", &mut region);
+        let _ = self
+            .rotation_speed_over_lifetime
+            .visit("RotationSpeedOverLifetime", &mut region);
+        let _ = self.sub_emitters.visit("SubEmitters", &mut region);
+        let _ = self.trail.visit("Trail", &mut region);
 
         // Backward compatibility.
         if region.is_reading() {
@@ -386,6 +478,51 @@ impl ParticleSystem {
             .set_value_and_mark_modified(gradient)
     }
 
+    /// Sets new curve, that defines size multiplier of particles over their normalized lifetime.
+    /// Empty curve means no modulation.
+    pub fn set_size_over_lifetime(&mut self, curve: Curve) -> Curve {
+        self.size_over_lifetime.set_value_and_mark_modified(curve)
+    }
+
+    /// Returns current size over lifetime curve.
+    pub fn size_over_lifetime(&self) -> &Curve {
+        &self.size_over_lifetime
+    }
+
+    /// Sets new curve, that defines velocity multiplier of particles over their normalized lifetime.
+    /// Empty curve means no modulation.
+    pub fn set_velocity_over_lifetime(&mut self, curve: Curve) -> Curve {
+        self.velocity_over_lifetime
+            .set_value_and_mark_modified(curve)
+    }
+
+    /// Returns current velocity over lifetime curve.
+    pub fn velocity_over_lifetime(&self) -> &Curve {
+        &self.velocity_over_lifetime
+    }
+
+    /// Sets new curve, that defines rotation speed multiplier of particles over their normalized
+    /// lifetime. Empty curve means no modulation.
+    pub fn set_rotation_speed_over_lifetime(&mut self, curve: Curve) -> Curve {
+        self.rotation_speed_over_lifetime
+            .set_value_and_mark_modified(curve)
+    }
+
+    /// Returns current rotation speed over lifetime curve.
+    pub fn rotation_speed_over_lifetime(&self) -> &Curve {
+        &self.rotation_speed_over_lifetime
+    }
+
+    /// Sets new trail settings. See [`ParticleTrail`] docs for more info.
+    pub fn set_trail(&mut self, trail: ParticleTrail) -> ParticleTrail {
+        self.trail.set_value_and_mark_modified(trail)
+    }
+
+    /// Returns current trail settings.
+    pub fn trail(&self) -> &ParticleTrail {
+        &self.trail
+    }
+
     /// Plays or pauses the particle system. Paused particle system remains in "frozen" state
     /// until played again again. You can manually reset state of the system by calling [`Self::clear_particles`].
     pub fn play(&mut self, is_playing: bool) -> bool {
@@ -434,12 +571,155 @@ impl ParticleSystem {
         &self.material
     }
 
-    fn tick(&mut self, dt: f32) {
+    /// Sets new collision settings for the particles. See [`ParticleCollision`] docs for more info.
+    pub fn set_collision(&mut self, collision: ParticleCollision) -> ParticleCollision {
+        self.collision.set_value_and_mark_modified(collision)
+    }
+
+    /// Returns current collision settings of the particles.
+    pub fn collision(&self) -> &ParticleCollision {
+        &self.collision
+    }
+
+    fn kill_particle(&mut self, index: usize, deaths: &mut Vec<ParticleEvent>) {
+        let emitter_count = self.emitters.len();
+        let particle = &mut self.particles[index];
+        if !particle.alive {
+            return;
+        }
+        if (particle.emitter_index as usize) < emitter_count {
+            deaths.push(ParticleEvent {
+                position: particle.position,
+                velocity: particle.velocity,
+            });
+        }
+        particle.alive = false;
+        particle.lifetime = particle.initial_lifetime;
+        particle.trail.clear();
+        self.free_particles.push(index as u32);
+        if let Some(emitter) = self
+            .emitters
+            .get_value_mut_and_mark_modified()
+            .get_mut(particle.emitter_index as usize)
+        {
+            emitter.alive_particles = emitter.alive_particles.saturating_sub(1);
+        }
+    }
+
+    fn add_particle(&mut self, particle: Particle) {
+        if let Some(free_index) = self.free_particles.pop() {
+            self.particles[free_index as usize] = particle;
+        } else {
+            self.particles.push(particle);
+        }
+    }
+    fn resolve_collisions(
+        &mut self,
+        physics: &PhysicsWorld,
+        previous_positions: &[(usize, Vector3<f32>)],
+        deaths: &mut Vec<ParticleEvent>,
+    ) {
+        // Offset from a surface to prevent particles from getting stuck inside colliders.
+        const SKIN: f32 = 0.001;
+
+        let (to_world, to_local) = if *self.coordinate_system == CoordinateSystem::Local {
+            let global_transform = self.global_transform();
+            let Some(inv_global_transform) = global_transform.try_inverse() else {
+                return;
+            };
+            (global_transform, inv_global_transform)
+        } else {
+            (Matrix4::identity(), Matrix4::identity())
+        };
+
+        let mut indices = Vec::with_capacity(previous_positions.len());
+        let mut rays = Vec::with_capacity(previous_positions.len());
+        for (index, previous_position) in previous_positions {
+            let begin = to_world.transform_point(&Point3::from(*previous_position));
+            let end = to_world.transform_point(&Point3::from(self.particles[*index].position));
+            let direction = end - begin;
+            let length = direction.norm();
+            if length > f32::EPSILON {
+                indices.push(*index);
+                rays.push(RayCastOptions {
+                    ray_origin: begin,
+                    ray_direction: direction,
+                    max_len: length,
+                    groups: self.collision.groups,
+                    sort_results: false,
+                });
+            }
+        }
+
+        let mut results = Vec::new();
+        physics.cast_rays_closest(&rays, &mut results);
+
+        for (index, intersection) in indices.into_iter().zip(results) {
+            let Some(intersection) = intersection else {
+                continue;
+            };
+
+            match self.collision.response {
+                ParticleCollisionResponse::Kill => self.kill_particle(index, deaths),
+                ParticleCollisionResponse::Bounce => {
+                    let particle = &mut self.particles[index];
+                    let normal = intersection
+                        .normal
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or_default();
+                    let velocity = to_world.transform_vector(&particle.velocity);
+                    let normal_velocity = normal.scale(velocity.dot(&normal));
+                    let tangent_velocity = velocity - normal_velocity;
+                    let new_velocity = tangent_velocity.scale(1.0 - self.collision.friction)
+                        - normal_velocity.scale(self.collision.restitution);
+                    let new_position = intersection.position.coords + normal.scale(SKIN);
+                    particle.velocity = to_local.transform_vector(&new_velocity);
+                    particle.position =
+                        to_local.transform_point(&Point3::from(new_position)).coords;
+                }
+            }
+        }
+    }
+
+    fn spawn_sub_emitter_particles(
+        &mut self,
+        trigger: SubEmitterTrigger,
+        events: &[ParticleEvent],
+    ) {
+        if events.is_empty() {
+            return;
+        }
+
+        let emitter_count = self.emitters.len();
+        for (sub_emitter_index, sub_emitter) in self.sub_emitters.clone().iter().enumerate() {
+            if sub_emitter.trigger != trigger {
+                continue;
+            }
+
+            for event in events {
+                for _ in 0..sub_emitter.count {
+                    let mut particle = Particle {
+                        emitter_index: (emitter_count + sub_emitter_index) as u32,
+                        ..Particle::default()
+                    };
+                    sub_emitter.emitter.emit(&mut particle, &mut self.rng);
+                    particle.position += event.position;
+                    particle.velocity += event.velocity.scale(sub_emitter.inherit_velocity);
+                    self.add_particle(particle);
+                }
+            }
+        }
+    }
+
+    fn tick(&mut self, dt: f32, physics: Option<&PhysicsWorld>) {
         for emitter in self.emitters.get_value_mut_silent().iter_mut() {
             emitter.tick(dt);
         }
 
         let global_transform = self.global_transform();
+        let has_sub_emitters = !self.sub_emitters.is_empty();
+        let mut births = Vec::new();
+        let mut deaths = Vec::new();
 
         for (i, emitter) in self.emitters.get_value_mut_silent().iter_mut().enumerate() {
             for _ in 0..emitter.particles_to_spawn {
@@ -454,6 +734,12 @@ impl ParticleSystem {
                         .transform_point(&particle.position.into())
                         .coords;
                 }
+                if has_sub_emitters {
+                    births.push(ParticleEvent {
+                        position: particle.position,
+                        velocity: particle.velocity,
+                    });
+                }
                 if let Some(free_index) = self.free_particles.pop() {
                     self.particles[free_index as usize] = particle;
                 } else {
@@ -464,10 +750,21 @@ impl ParticleSystem {
 
         let acceleration_offset = self.acceleration.scale(dt * dt);
 
+        let physics = physics.filter(|_| self.collision.enabled);
+        let mut previous_positions = Vec::new();
+        let emitter_count = self.emitters.len();
+        let trail = self.trail.enabled.then_some(&*self.trail);
+
         for (i, particle) in self.particles.iter_mut().enumerate() {
             if particle.alive {
                 particle.lifetime += dt;
                 if particle.lifetime >= particle.initial_lifetime {
+                    if has_sub_emitters && (particle.emitter_index as usize) < emitter_count {
+                        deaths.push(ParticleEvent {
+                            position: particle.position,
+                            velocity: particle.velocity,
+                        });
+                    }
                     self.free_particles.push(i as u32);
                     if let Some(emitter) = self
                         .emitters
@@ -478,20 +775,49 @@ impl ParticleSystem {
                     }
                     particle.alive = false;
                     particle.lifetime = particle.initial_lifetime;
+                    particle.trail.clear();
                 } else {
+                    if physics.is_some() {
+                        previous_positions.push((i, particle.position));
+                    }
+
+                    let k = particle.lifetime / particle.initial_lifetime;
+
                     particle.velocity += acceleration_offset;
-                    particle.position += particle.velocity;
+                    particle.position +=
+                        particle.velocity * curve_factor(&self.velocity_over_lifetime, k);
                     particle.size += particle.size_modifier * dt;
                     if particle.size < 0.0 {
                         particle.size = 0.0;
                     }
-                    particle.rotation += particle.rotation_speed * dt;
+                    particle.rotation += particle.rotation_speed
+                        * curve_factor(&self.rotation_speed_over_lifetime, k)
+                        * dt;
 
-                    let k = particle.lifetime / particle.initial_lifetime;
                     particle.color = self.color_over_lifetime.get_color(k);
+
+                    if let Some(trail) = trail {
+                        if particle.trail.back().map_or(true, |last| {
+                            last.metric_distance(&particle.position) >= trail.min_point_distance
+                        }) {
+                            particle.trail.push_back(particle.position);
+                        }
+                        while particle.trail.len() > trail.max_points.max(2) as usize {
+                            particle.trail.pop_front();
+                        }
+                    }
                 }
             }
         }
+
+        if let Some(physics) = physics {
+            self.resolve_collisions(physics, &previous_positions, &mut deaths);
+        }
+
+        if has_sub_emitters {
+            self.spawn_sub_emitter_particles(SubEmitterTrigger::Birth, &births);
+            self.spawn_sub_emitter_particles(SubEmitterTrigger::Death, &deaths);
+        }
     }
 
     /// Simulates particle system for the given `time` with given time step (`dt`). `dt` is usually `1.0 / 60.0`.
@@ -503,7 +829,7 @@ impl ParticleSystem {
 
         let mut t = 0.0;
         while t < time {
-            self.tick(dt);
+            self.tick(dt, None);
             t += dt;
         }
     }
@@ -534,6 +860,107 @@ impl ParticleSystem {
         *self.coordinate_system
     }
 
+    fn particle_size(&self, particle: &Particle) -> f32 {
+        particle.size
+            * curve_factor(
+                &self.size_over_lifetime,
+                particle.lifetime / particle.initial_lifetime,
+            )
+    }
+
+    /// Builds camera-facing ribbons for particle trails. Vertices are in world space, indices of
+    /// the triangles start from zero.
+    fn build_trails(
+        &self,
+        global_transform: &Matrix4<f32>,
+        observer_position: Vector3<f32>,
+        alpha_factor: f32,
+    ) -> (Vec<Vertex>, Vec<TriangleDefinition>) {
+        let mut vertices = Vec::new();
+        let mut triangles = Vec::new();
+
+        if !self.trail.enabled {
+            return (vertices, triangles);
+        }
+
+        let mut points = Vec::new();
+        for particle in self.particles.iter() {
+            if !particle.alive || particle.trail.is_empty() {
+                continue;
+            }
+
+            points.clear();
+            points.extend(
+                particle
+                    .trail
+                    .iter()
+                    .chain(
+                        // Trail ends at the current position of the particle.
+                        (particle.trail.back() != Some(&particle.position))
+                            .then_some(&particle.position),
+                    )
+                    .map(|point| {
+                        if *self.coordinate_system == CoordinateSystem::Local {
+                            global_transform
+                                .transform_point(&Point3::from(*point))
+                                .coords
+                        } else {
+                            *point
+                        }
+                    }),
+            );
+            if points.len() < 2 {
+                continue;
+            }
+
+            let half_width = 0.5 * self.trail.width * self.particle_size(particle);
+            let last = points.len() - 1;
+            let start_index = vertices.len() as u32;
+            for (i, point) in points.iter().enumerate() {
+                let direction = points[(i + 1).min(last)] - points[i.saturating_sub(1)];
+                let side = direction
+                    .cross(&(observer_position - point))
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or_default()
+                    .scale(half_width);
+                let t = i as f32 / last as f32;
+                let mut alpha = particle.color.a as f32 * alpha_factor;
+                if self.trail.fade_out {
+                    alpha *= t;
+                }
+                let color = Color::from_rgba(
+                    particle.color.r,
+                    particle.color.g,
+                    particle.color.b,
+                    alpha as u8,
+                );
+                // Zero size disables billboarding in the shader.
+                vertices.push(Vertex {
+                    position: point - side,
+                    tex_coord: Vector2::new(t, 0.0),
+                    size: 0.0,
+                    rotation: 0.0,
+                    color,
+                });
+                vertices.push(Vertex {
+                    position: point + side,
+                    tex_coord: Vector2::new(t, 1.0),
+                    size: 0.0,
+                    rotation: 0.0,
+                    color,
+                });
+            }
+
+            for i in 0..last as u32 {
+                let a = start_index + i * 2;
+                triangles.push(TriangleDefinition([a, a + 1, a + 3]));
+                triangles.push(TriangleDefinition([a, a + 3, a + 2]));
+            }
+        }
+
+        (vertices, triangles)
+    }
+
     fn is_distance_clipped(&self, point: &Vector3<f32>) -> bool {
         point.metric_distance(&self.global_position())
             > (*self.visible_distance + Self::FADEOUT_MARGIN)
@@ -581,7 +1008,14 @@ impl NodeTrait for ParticleSystem {
         let dt = context.dt;
 
         if *self.is_playing {
-            self.tick(dt);
+            let global_transform = self.global_transform();
+            for emitter in self.emitters.get_value_mut_silent().iter_mut() {
+                if let Emitter::Mesh(mesh_emitter) = emitter {
+                    mesh_emitter.update_geometry(context.nodes, &global_transform);
+                }
+            }
+
+            self.tick(dt, Some(context.physics));
         }
     }
 
@@ -637,6 +1071,13 @@ impl NodeTrait for ParticleSystem {
         let global_transform = self.global_transform();
         let sort_index = ctx.calculate_sorting_index(self.global_position());
 
+        let (trail_vertices, trail_triangles) = self.build_trails(
+            &global_transform,
+            ctx.observer_info.observer_position,
+            particle_alpha_factor,
+        );
+        let trail_base_index = (sorted_particles.len() * 4) as u32;
+
         ctx.storage.push_triangles(
             Vertex::layout(),
             &self.material,
@@ -662,47 +1103,54 @@ impl NodeTrait for ParticleSystem {
                         particle.color.b,
                         alpha,
                     );
+                    let size = self.particle_size(particle);
 
                     [
                         Vertex {
                             position,
                             tex_coord: Vector2::default(),
-                            size: particle.size,
+                            size,
                             rotation: particle.rotation,
                             color,
                         },
                         Vertex {
                             position,
                             tex_coord: Vector2::new(1.0, 0.0),
-                            size: particle.size,
+                            size,
                             rotation: particle.rotation,
                             color,
                         },
                         Vertex {
                             position,
                             tex_coord: Vector2::new(1.0, 1.0),
-                            size: particle.size,
+                            size,
                             rotation: particle.rotation,
                             color,
                         },
                         Vertex {
                             position,
                             tex_coord: Vector2::new(0.0, 1.0),
-                            size: particle.size,
+                            size,
                             rotation: particle.rotation,
                             color,
                         },
                     ]
                 });
 
-                let triangles = (0..sorted_particles.len()).flat_map(|i| {
-                    let base_index = (i * 4) as u32;
+                let vertices = vertices.chain(trail_vertices.iter().cloned());
 
-                    [
-                        TriangleDefinition([base_index, base_index + 1, base_index + 2]),
-                        TriangleDefinition([base_index, base_index + 2, base_index + 3]),
-                    ]
-                });
+                let triangles = (0..sorted_particles.len())
+                    .flat_map(|i| {
+                        let base_index = (i * 4) as u32;
+
+                        [
+                            TriangleDefinition([base_index, base_index + 1, base_index + 2]),
+                            TriangleDefinition([base_index, base_index + 2, base_index + 3]),
+                        ]
+                    })
+                    .chain(trail_triangles.iter().map(|triangle| {
+                        TriangleDefinition(triangle.0.map(|index| index + trail_base_index))
+                    }));
 
                 let start_vertex_index = vertex_buffer.vertex_count();
 
@@ -733,6 +1181,12 @@ pub struct ParticleSystemBuilder {
     rng: ParticleSystemRng,
     visible_distance: f32,
     coordinate_system: CoordinateSystem,
+    collision: ParticleCollision,
+    size_over_lifetime: Curve,
+    velocity_over_lifetime: Curve,
+    rotation_speed_over_lifetime: Curve,
+    sub_emitters: Vec<SubEmitter>,
+    trail: ParticleTrail,
 }
 
 impl ParticleSystemBuilder {
@@ -752,6 +1206,12 @@ impl ParticleSystemBuilder {
             rng: ParticleSystemRng::default(),
             visible_distance: 30.0,
             coordinate_system: Default::default(),
+            collision: Default::default(),
+            size_over_lifetime: Default::default(),
+            velocity_over_lifetime: Default::default(),
+            rotation_speed_over_lifetime: Default::default(),
+            sub_emitters: Default::default(),
+            trail: Default::default(),
         }
     }
 
@@ -811,6 +1271,42 @@ impl ParticleSystemBuilder {
         self
     }
 
+    /// Sets the desired collision settings for particles.
+    pub fn with_collision(mut self, collision: ParticleCollision) -> Self {
+        self.collision = collision;
+        self
+    }
+
+    /// Sets the desired size over lifetime curve.
+    pub fn with_size_over_lifetime(mut self, curve: Curve) -> Self {
+        self.size_over_lifetime = curve;
+        self
+    }
+
+    /// Sets the desired velocity over lifetime curve.
+    pub fn with_velocity_over_lifetime(mut self, curve: Curve) -> Self {
+        self.velocity_over_lifetime = curve;
+        self
+    }
+
+    /// Sets the desired rotation speed over lifetime curve.
+    pub fn with_rotation_speed_over_lifetime(mut self, curve: Curve) -> Self {
+        self.rotation_speed_over_lifetime = curve;
+        self
+    }
+
+    /// Sets the desired sub-emitters.
+    pub fn with_sub_emitters(mut self, sub_emitters: Vec<SubEmitter>) -> Self {
+        self.sub_emitters = sub_emitters;
+        self
+    }
+
+    /// Sets the desired trail settings.
+    pub fn with_trail(mut self, trail: ParticleTrail) -> Self {
+        self.trail = trail;
+        self
+    }
+
     fn build_particle_system(self) -> ParticleSystem {
         ParticleSystem {
             base: self.base_builder.build_base(),
@@ -824,6 +1320,12 @@ impl ParticleSystemBuilder {
             rng: self.rng,
             visible_distance: self.visible_distance.into(),
             coordinate_system: self.coordinate_system.into(),
+            collision: self.collision.into(),
+            size_over_lifetime: self.size_over_lifetime.into(),
+            velocity_over_lifetime: self.velocity_over_lifetime.into(),
+            rotation_speed_over_lifetime: self.rotation_speed_over_lifetime.into(),
+            sub_emitters: self.sub_emitters.into(),
+            trail: self.trail.into(),
         }
     }
 
@@ -837,3 +1339,354 @@ impl ParticleSystemBuilder {
         graph.add_node(self.build_node())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Matrix4, Vector2, Vector3},
+            math::{
+                curve::{Curve, CurveKey, CurveKeyKind},
+                TriangleDefinition,
+            },
+            pool::Handle,
+        },
+        scene::{
+            base::BaseBuilder,
+            collider::{ColliderBuilder, ColliderShape},
+            graph::Graph,
+            mesh::{
+                buffer::{TriangleBuffer, VertexBuffer},
+                surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+                vertex::AnimatedVertex,
+                MeshBuilder,
+            },
+            node::Node,
+            particle_system::{
+                collision::{ParticleCollision, ParticleCollisionResponse},
+                emitter::{
+                    base::BaseEmitterBuilder,
+                    mesh::{MeshEmitterBuilder, MeshEmitterMode},
+                    sphere::SphereEmitterBuilder,
+                },
+                particle::Particle,
+                sub_emitter::{SubEmitter, SubEmitterTrigger},
+                trail::ParticleTrail,
+                ParticleSystem, ParticleSystemBuilder,
+            },
+            pivot::PivotBuilder,
+            rigidbody::{RigidBodyBuilder, RigidBodyType},
+            transform::TransformBuilder,
+        },
+    };
+    use fyrox_graph::{BaseSceneGraph, SceneGraph};
+    use fyrox_resource::untyped::ResourceKind;
+
+    fn simulate(collision: ParticleCollision) -> Graph {
+        let mut graph = Graph::new();
+        let ground = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::cuboid(5.0, 0.1, 5.0))
+            .build(&mut graph);
+        RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, -0.1, 0.0))
+                        .build(),
+                )
+                .with_children(&[ground]),
+        )
+        .with_body_type(RigidBodyType::Static)
+        .build(&mut graph);
+        ParticleSystemBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 2.0, 0.0))
+                    .build(),
+            ),
+        )
+        .with_particles(vec![Particle::default()
+            .with_velocity(Vector3::new(0.01, 0.0, 0.0))
+            .with_initial_lifetime(100.0)])
+        .with_collision(collision)
+        .build(&mut graph);
+
+        for _ in 0..180 {
+            graph.update(Vector2::new(800.0, 600.0), 1.0 / 60.0, Default::default());
+        }
+
+        graph
+    }
+
+    fn particle(graph: &Graph) -> &Particle {
+        let (_, particle_system) = graph
+            .find_component::<ParticleSystem>(graph.get_root())
+            .unwrap();
+        &particle_system.particles()[0]
+    }
+
+    #[test]
+    fn test_particles_without_collision() {
+        let graph = simulate(ParticleCollision::default());
+        let particle = particle(&graph);
+        assert!(particle.alive);
+        assert!(particle.position.y + 2.0 < -1.0);
+    }
+
+    #[test]
+    fn test_particles_bounce() {
+        let graph = simulate(
+            ParticleCollision::new(ParticleCollisionResponse::Bounce)
+                .with_restitution(0.3)
+                .with_friction(0.5),
+        );
+        let particle = particle(&graph);
+        assert!(particle.alive);
+        let world_y = particle.position.y + 2.0;
+        assert!((0.0..0.5).contains(&world_y), "{world_y}");
+        // Friction must slow down the particle.
+        assert!(particle.velocity.x.abs() < 0.01);
+    }
+
+    #[test]
+    fn test_particles_kill() {
+        let graph = simulate(ParticleCollision::new(ParticleCollisionResponse::Kill));
+        assert!(!particle(&graph).alive);
+    }
+
+    fn moving_particle() -> Particle {
+        Particle::default()
+            .with_velocity(Vector3::new(0.1, 0.0, 0.0))
+            .with_initial_lifetime(100.0)
+    }
+
+    fn build_without_gravity(builder: ParticleSystemBuilder) -> ParticleSystem {
+        builder
+            .with_acceleration(Vector3::default())
+            .build_particle_system()
+    }
+
+    #[test]
+    fn test_over_lifetime_curves() {
+        let zero = Curve::from(vec![CurveKey::new(0.0, 0.0, CurveKeyKind::Constant)]);
+        let mut particle_system = build_without_gravity(
+            ParticleSystemBuilder::new(BaseBuilder::new())
+                .with_particles(vec![moving_particle().with_rotation_speed(1.0)])
+                .with_velocity_over_lifetime(zero.clone())
+                .with_rotation_speed_over_lifetime(zero.clone())
+                .with_size_over_lifetime(zero),
+        );
+        for _ in 0..10 {
+            particle_system.tick(0.1, None);
+        }
+        let particle = &particle_system.particles()[0];
+        assert_eq!(particle.position, Vector3::default());
+        assert_eq!(particle.rotation, 0.0);
+        assert_eq!(particle_system.particle_size(particle), 0.0);
+
+        // Empty curves must not affect particles.
+        let mut particle_system = build_without_gravity(
+            ParticleSystemBuilder::new(BaseBuilder::new()).with_particles(vec![moving_particle()]),
+        );
+        for _ in 0..10 {
+            particle_system.tick(0.1, None);
+        }
+        let particle = &particle_system.particles()[0];
+        assert!((particle.position.x - 1.0).abs() < 1.0e-5);
+        assert_eq!(particle_system.particle_size(particle), particle.size);
+    }
+
+    #[test]
+    fn test_sub_emitters() {
+        let mut particle_system = build_without_gravity(
+            ParticleSystemBuilder::new(BaseBuilder::new())
+                .with_emitters(vec![SphereEmitterBuilder::new(
+                    BaseEmitterBuilder::new()
+                        .with_max_particles(2)
+                        .with_spawn_rate(100)
+                        .with_lifetime_range(0.25..0.3)
+                        .resurrect_particles(false),
+                )
+                .build()])
+                .with_sub_emitters(vec![
+                    SubEmitter::new(
+                        SubEmitterTrigger::Birth,
+                        SphereEmitterBuilder::new(
+                            BaseEmitterBuilder::new().with_lifetime_range(10.0..11.0),
+                        )
+                        .build(),
+                        1,
+                    ),
+                    SubEmitter::new(
+                        SubEmitterTrigger::Death,
+                        SphereEmitterBuilder::new(
+                            BaseEmitterBuilder::new().with_lifetime_range(10.0..11.0),
+                        )
+                        .build(),
+                        3,
+                    ),
+                ]),
+        );
+
+        let count = |particle_system: &ParticleSystem, emitter_index: u32| {
+            particle_system
+                .particles()
+                .iter()
+                .filter(|p| p.alive && p.emitter_index == emitter_index)
+                .count()
+        };
+
+        particle_system.tick(0.1, None);
+        assert_eq!(count(&particle_system, 0), 2);
+        assert_eq!(count(&particle_system, 1), 2);
+        assert_eq!(count(&particle_system, 2), 0);
+
+        for _ in 0..5 {
+            particle_system.tick(0.1, None);
+        }
+        assert_eq!(count(&particle_system, 0), 0);
+        assert_eq!(count(&particle_system, 1), 2);
+        assert_eq!(count(&particle_system, 2), 6);
+    }
+
+    #[test]
+    fn test_trails() {
+        let mut particle_system = build_without_gravity(
+            ParticleSystemBuilder::new(BaseBuilder::new())
+                .with_particles(vec![moving_particle()])
+                .with_trail(ParticleTrail::new(4).with_min_point_distance(0.05)),
+        );
+        for _ in 0..10 {
+            particle_system.tick(0.1, None);
+        }
+        let particle = &particle_system.particles()[0];
+        assert_eq!(particle.trail().len(), 4);
+        assert_eq!(particle.trail().back(), Some(&particle.position));
+
+        let (vertices, triangles) =
+            particle_system.build_trails(&Matrix4::identity(), Vector3::new(0.0, 0.0, -10.0), 1.0);
+        assert_eq!(vertices.len(), 8);
+        assert_eq!(triangles.len(), 6);
+        // The tail must be faded out.
+        assert_eq!(vertices[0].color.a, 0);
+        assert_eq!(vertices[7].color.a, 255);
+    }
+
+    fn emit_from_mesh(
+        graph: &mut Graph,
+        mesh: Handle<Node>,
+        mode: MeshEmitterMode,
+    ) -> Vec<Particle> {
+        let particle_system = build_without_gravity(
+            ParticleSystemBuilder::new(BaseBuilder::new()).with_emitters(vec![
+                MeshEmitterBuilder::new(
+                    BaseEmitterBuilder::new()
+                        .with_max_particles(50)
+                        .with_spawn_rate(5000)
+                        .with_lifetime_range(10.0..11.0)
+                        .with_x_velocity_range(0.0..0.0)
+                        .with_y_velocity_range(0.0..0.0)
+                        .with_z_velocity_range(0.0..0.0),
+                )
+                .with_mesh(mesh)
+                .with_mode(mode)
+                .with_normal_velocity_range(0.01..0.01)
+                .build(),
+            ]),
+        );
+        let particle_system = graph.add_node(Node::new(particle_system));
+
+        graph.update(Vector2::new(800.0, 600.0), 0.01, Default::default());
+
+        let particles = graph[particle_system]
+            .cast::<ParticleSystem>()
+            .unwrap()
+            .particles()
+            .iter()
+            .filter(|p| p.alive)
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(particles.len(), 50);
+        particles
+    }
+
+    #[test]
+    fn test_mesh_emitter() {
+        let mut graph = Graph::new();
+        let mesh = MeshBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 0.0, 3.0))
+                    .with_local_scale(Vector3::new(2.0, 2.0, 2.0))
+                    .build(),
+            ),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+            ResourceKind::Embedded,
+            SurfaceData::make_quad(&Matrix4::identity()),
+        ))
+        .build()])
+        .build(&mut graph);
+
+        for particle in emit_from_mesh(&mut graph, mesh, MeshEmitterMode::Triangles) {
+            assert!(particle.position.x.abs() <= 1.0 + 1.0e-4);
+            assert!(particle.position.y.abs() <= 1.0 + 1.0e-4);
+            assert!((particle.position.z - 3.0).abs() <= 0.01 + 1.0e-4);
+            // Particles must move along the normal of the quad.
+            assert!((particle.velocity - Vector3::new(0.0, 0.0, -0.01)).norm() < 1.0e-4);
+        }
+
+        for particle in emit_from_mesh(&mut graph, mesh, MeshEmitterMode::Vertices) {
+            assert!((particle.position.x.abs() - 1.0).abs() < 1.0e-4);
+            assert!((particle.position.y.abs() - 1.0).abs() < 1.0e-4);
+        }
+    }
+
+    #[test]
+    fn test_skinned_mesh_emitter() {
+        let mut graph = Graph::new();
+        let bone = PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 5.0, 0.0))
+                    .build(),
+            ),
+        )
+        .build(&mut graph);
+
+        let vertex = |position: Vector3<f32>| AnimatedVertex {
+            position,
+            normal: Vector3::y(),
+            bone_weights: [1.0, 0.0, 0.0, 0.0],
+            bone_indices: [0, 0, 0, 0],
+            ..Default::default()
+        };
+        let data = SurfaceData::new(
+            VertexBuffer::new(
+                3,
+                vec![
+                    vertex(Vector3::new(0.0, 0.0, 0.0)),
+                    vertex(Vector3::new(1.0, 0.0, 0.0)),
+                    vertex(Vector3::new(0.0, 0.0, 1.0)),
+                ],
+            )
+            .unwrap(),
+            TriangleBuffer::new(vec![TriangleDefinition([0, 1, 2])]),
+        );
+        let mesh = MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+                ResourceKind::Embedded,
+                data,
+            ))
+            .with_bones(vec![bone])
+            .build()])
+            .build(&mut graph);
+
+        // Particles must be spawned on the deformed surface.
+        for particle in emit_from_mesh(&mut graph, mesh, MeshEmitterMode::Triangles) {
+            assert!((particle.position.y - 5.0).abs() <= 0.01 + 1.0e-4);
+            assert!(particle.position.x >= -1.0e-4 && particle.position.z >= -1.0e-4);
+            assert!(particle.position.x + particle.position.z <= 1.0 + 1.0e-4);
+        }
+    }
+}
@@ -22,7 +22,7 @@
 //! position, velocity, size, lifetime, etc.
 
 use crate::core::{algebra::Vector3, color::Color, visitor::prelude::*};
-use std::cell::Cell;
+use std::{cell::Cell, collections::VecDeque};
 
 /// See module docs.
 #[derive(Clone, Debug, Visit)]
@@ -53,6 +53,8 @@ pub struct Particle {
     pub(super) lifetime: f32,
     #[visit(skip)]
     pub(super) sqr_distance_to_camera: Cell<f32>,
+    #[visit(skip)]
+    pub(super) trail: VecDeque<Vector3<f32>>,
 }
 
 impl Default for Particle {
@@ -70,6 +72,7 @@ impl Default for Particle {
             emitter_index: 0,
             color: Color::WHITE,
             sqr_distance_to_camera: Cell::new(0.0),
+            trail: Default::default(),
         }
     }
 }
@@ -117,6 +120,12 @@ impl Particle {
         self
     }
 
+    /// Returns previous positions of the particle, that form its trail (from the tail to the
+    /// particle). The trail is empty if the trails are disabled in the particle system.
+    pub fn trail(&self) -> &VecDeque<Vector3<f32>> {
+        &self.trail
+    }
+
     /// Sets new color in builder manner.
     pub fn with_color(mut self, color: Color) -> Self {
         self.color = color;
@@ -0,0 +1,110 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Sub-emitters spawn bursts of particles when particles of the main emitters are born or die. See
+//! [`SubEmitter`] docs for more info.
+
+use crate::{
+    core::{reflect::prelude::*, type_traits::prelude::*, uuid::Uuid, visitor::prelude::*},
+    scene::particle_system::emitter::Emitter,
+};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Defines an event of a parent particle, that triggers a sub-emitter.
+#[derive(
+    Default,
+    Copy,
+    Clone,
+    PartialOrd,
+    PartialEq,
+    Eq,
+    Ord,
+    Hash,
+    Debug,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+    TypeUuidProvider,
+)]
+#[type_uuid(id = "0c3a7c8e-9f55-4b8f-b1b5-2b9d5f3c0e47")]
+pub enum SubEmitterTrigger {
+    /// A sub-emitter is triggered when a parent particle is spawned. Could be used to create
+    /// muzzle flashes, sparks at the spawn point, etc.
+    Birth,
+    /// A sub-emitter is triggered when a parent particle dies (either because of its lifetime or
+    /// because of a collision). Could be used to create explosions of fireworks, splashes of rain
+    /// drops, etc.
+    #[default]
+    Death,
+}
+
+/// Sub-emitter is an emitter, that spawns a burst of particles at the position of a particle of the
+/// main emitters of a particle system, when the particle is born or dies. Particles spawned by
+/// sub-emitters do not trigger sub-emitters by themselves.
+///
+/// The emitter of a sub-emitter is used only to initialize the particles, its spawn rate and the
+/// maximum amount of particles are ignored, use [`SubEmitter::count`] to define the amount of
+/// particles spawned per event. Positions of the spawned particles are relative to the position of
+/// the parent particle.
+#[derive(Debug, Clone, PartialEq, Visit, Reflect, TypeUuidProvider)]
+#[type_uuid(id = "5b7f0e0a-2c1d-4d3b-9a8e-6f2c4b1d7e93")]
+pub struct SubEmitter {
+    /// An event of a parent particle, that triggers the sub-emitter.
+    pub trigger: SubEmitterTrigger,
+    /// An emitter that will be used to initialize new particles.
+    pub emitter: Emitter,
+    /// Amount of particles spawned per event.
+    pub count: u32,
+    /// A fraction of the velocity of a parent particle, that will be added to the velocity of new
+    /// particles. 0.0 - velocity is not inherited, 1.0 - full velocity is inherited.
+    #[reflect(min_value = 0.0, max_value = 1.0)]
+    pub inherit_velocity: f32,
+}
+
+impl Default for SubEmitter {
+    fn default() -> Self {
+        Self {
+            trigger: Default::default(),
+            emitter: Default::default(),
+            count: 10,
+            inherit_velocity: 0.0,
+        }
+    }
+}
+
+impl SubEmitter {
+    /// Creates new sub-emitter with the given trigger, emitter and amount of particles per event.
+    pub fn new(trigger: SubEmitterTrigger, emitter: Emitter, count: u32) -> Self {
+        Self {
+            trigger,
+            emitter,
+            count,
+            inherit_velocity: 0.0,
+        }
+    }
+
+    /// Sets a fraction of the velocity of a parent particle, that will be added to the velocity of
+    /// new particles.
+    pub fn with_inherit_velocity(mut self, inherit_velocity: f32) -> Self {
+        self.inherit_velocity = inherit_velocity;
+        self
+    }
+}
@@ -0,0 +1,84 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Ribbon trails of particles. See [`ParticleTrail`] docs for more info.
+
+use crate::core::{reflect::prelude::*, visitor::prelude::*};
+
+/// Settings of ribbon trails of particles. When enabled, every particle remembers a set of its
+/// previous positions and a camera-facing ribbon is drawn through them. Trails use the material of
+/// the particle system, the texture is stretched along the trail: U coordinate goes from the tail
+/// to the particle and V coordinate goes across the trail.
+#[derive(Debug, Clone, PartialEq, Visit, Reflect)]
+pub struct ParticleTrail {
+    /// Whether the trails are enabled or not.
+    pub enabled: bool,
+    /// Maximum amount of points in a trail. Larger values produce longer and smoother trails.
+    #[reflect(min_value = 2.0)]
+    pub max_points: u32,
+    /// Minimum distance a particle should travel before a new point will be added to its trail.
+    #[reflect(min_value = 0.0, step = 0.01)]
+    pub min_point_distance: f32,
+    /// Width of a trail relative to the size of its particle.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub width: f32,
+    /// If set, transparency of a trail goes linearly from the particle to zero at the tail.
+    pub fade_out: bool,
+}
+
+impl Default for ParticleTrail {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_points: 16,
+            min_point_distance: 0.1,
+            width: 1.0,
+            fade_out: true,
+        }
+    }
+}
+
+impl ParticleTrail {
+    /// Creates new enabled trail settings with the given maximum amount of points.
+    pub fn new(max_points: u32) -> Self {
+        Self {
+            enabled: true,
+            max_points,
+            ..Default::default()
+        }
+    }
+
+    /// Sets minimum distance between trail points in builder manner.
+    pub fn with_min_point_distance(mut self, min_point_distance: f32) -> Self {
+        self.min_point_distance = min_point_distance;
+        self
+    }
+
+    /// Sets relative width of a trail in builder manner.
+    pub fn with_width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Sets whether the trail should fade out to its tail or not.
+    pub fn with_fade_out(mut self, fade_out: bool) -> Self {
+        self.fade_out = fade_out;
+        self
+    }
+}
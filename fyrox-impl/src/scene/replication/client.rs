@@ -0,0 +1,296 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Client side of the scene replication. See [`ReplicationClient`] docs for more info.
+
+use crate::{
+    asset::manager::ResourceManager,
+    core::{log::Log, net::NetStream},
+    graph::BaseSceneGraph,
+    resource::model::{Model, ModelResource, ModelResourceExtension},
+    scene::{
+        base::SceneNodeId,
+        replication::{
+            write_property, ClientId, ClientMessage, MessagePacket, MessageTarget, RemoteMessage,
+            RemoteMessageRegistry, Replication, ServerMessage, SpawnData, TransformState,
+        },
+        transform::Transform,
+        Scene,
+    },
+    script::ScriptMessageSender,
+};
+use fxhash::FxHashMap;
+use std::{
+    collections::VecDeque,
+    io::{self, ErrorKind},
+    net::ToSocketAddrs,
+};
+
+const MAX_SAMPLES: usize = 64;
+
+struct Entity {
+    replication: Replication,
+    samples: VecDeque<(f32, TransformState)>,
+}
+
+impl Entity {
+    fn sample(&mut self, time: f32) -> Option<TransformState> {
+        // Discard samples, that will never be used again.
+        while self.samples.len() > 2 && self.samples[1].0 <= time {
+            self.samples.pop_front();
+        }
+
+        let (first_time, first) = self.samples.front()?;
+        if time <= *first_time {
+            return Some(first.clone());
+        }
+
+        match self.samples.get(1) {
+            Some((second_time, second)) if time < *second_time => {
+                let t = (time - first_time) / (second_time - first_time);
+                Some(first.interpolate(second, t))
+            }
+            _ => self.samples.back().map(|(_, state)| state.clone()),
+        }
+    }
+}
+
+/// Replication client receives the state of replicated nodes from a [`super::ReplicationServer`]
+/// and applies it to its own copy of the scene. It also spawns and removes prefab instances
+/// when the server tells it to do so.
+///
+/// Transforms of replicated nodes are interpolated between the two snapshots that surround the
+/// current time of the client, which is delayed for [`Self::interpolation_delay`] seconds from
+/// the latest received snapshot. This hides jitter of network delivery and low snapshot rate at
+/// the cost of a small delay. Keep in mind, that replicated nodes are moved directly, so if
+/// a replicated node is a rigid body, it should be kinematic on the client.
+///
+/// The client must be updated every frame by calling [`Self::update`], usually this is done in
+/// [`crate::plugin::Plugin::update`].
+pub struct ReplicationClient {
+    stream: NetStream,
+    id: Option<ClientId>,
+    entities: FxHashMap<SceneNodeId, Entity>,
+    incoming: VecDeque<ServerMessage>,
+    pending_spawn: Option<(SpawnData, ModelResource)>,
+    latest_time: Option<f32>,
+    time: f32,
+    /// Delay (in seconds) of the interpolated state of nodes from the latest received state.
+    /// Should be larger than snapshot interval of the server. Default is 0.1 seconds.
+    pub interpolation_delay: f32,
+    /// A set of message types, that could be received from the server.
+    pub remote_messages: RemoteMessageRegistry,
+}
+
+impl ReplicationClient {
+    /// Connects to a server with the given address.
+    pub fn connect<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        Ok(Self {
+            stream: NetStream::connect(address)?,
+            id: None,
+            entities: Default::default(),
+            incoming: Default::default(),
+            pending_spawn: None,
+            latest_time: None,
+            time: 0.0,
+            interpolation_delay: 0.1,
+            remote_messages: Default::default(),
+        })
+    }
+
+    /// Returns an id of the client assigned by the server. It is `None` until the server accepts
+    /// the connection.
+    pub fn id(&self) -> Option<ClientId> {
+        self.id
+    }
+
+    /// Returns `true` if the node with the given id is replicated.
+    pub fn is_replicated(&self, id: SceneNodeId) -> bool {
+        self.entities.contains_key(&id)
+    }
+
+    /// Sends the given message to the server.
+    pub fn send_message<T: RemoteMessage>(
+        &mut self,
+        target: MessageTarget,
+        payload: &T,
+    ) -> io::Result<()> {
+        let packet = MessagePacket::new(target, payload)
+            .map_err(|err| io::Error::new(ErrorKind::Other, err))?;
+        self.stream.send_message(&ClientMessage::Message(packet))
+    }
+
+    /// Receives messages from the server and applies them to the given scene. Messages from the
+    /// server are delivered to the scripts using the given message sender.
+    pub fn update(
+        &mut self,
+        scene: &mut Scene,
+        resource_manager: &ResourceManager,
+        message_sender: &ScriptMessageSender,
+        dt: f32,
+    ) {
+        self.stream
+            .process_input::<ServerMessage>(|message| self.incoming.push_back(message));
+
+        loop {
+            // Messages are processed strictly in order, so every message that comes after a spawn
+            // message must wait until the prefab is loaded.
+            if let Some((_, resource)) = self.pending_spawn.as_ref() {
+                if resource.is_loading() {
+                    break;
+                }
+                if let Some((data, resource)) = self.pending_spawn.take() {
+                    Self::spawn(scene, data, resource);
+                }
+            }
+
+            let Some(message) = self.incoming.pop_front() else {
+                break;
+            };
+
+            self.handle_message(message, scene, resource_manager, message_sender);
+        }
+
+        if let Some(latest_time) = self.latest_time {
+            self.time += dt;
+            // Keep the time of the client close to the time of the server, this compensates
+            // clock drift and large delivery delays.
+            let target_time = latest_time - self.interpolation_delay;
+            if (self.time - target_time).abs() > self.interpolation_delay {
+                self.time = target_time;
+            }
+            self.time = self.time.min(latest_time);
+
+            for (id, entity) in self.entities.iter_mut() {
+                if let Some(state) = entity.sample(self.time) {
+                    if let Some((_, node)) = scene.graph.node_by_id_mut(*id) {
+                        state.apply(node.local_transform_mut());
+                    }
+                }
+            }
+        }
+    }
+
+    fn spawn(scene: &mut Scene, data: SpawnData, resource: ModelResource) {
+        if !resource.is_ok() {
+            Log::err(format!(
+                "Unable to spawn an instance of {}: the prefab failed to load!",
+                data.resource.display()
+            ));
+            return;
+        }
+
+        let ids = data.ids.into_iter().collect::<FxHashMap<_, _>>();
+        let mut transform = Transform::default();
+        data.transform.apply(&mut transform);
+        let instance = resource
+            .begin_instantiation(scene)
+            .with_ids(&ids)
+            .with_transform(transform)
+            .finish();
+
+        if let Some(parent) = data
+            .parent
+            .and_then(|parent| scene.graph.id_to_node_handle(parent).cloned())
+        {
+            scene.graph.link_nodes(instance, parent);
+        }
+    }
+
+    fn handle_message(
+        &mut self,
+        message: ServerMessage,
+        scene: &mut Scene,
+        resource_manager: &ResourceManager,
+        message_sender: &ScriptMessageSender,
+    ) {
+        match message {
+            ServerMessage::Welcome { client_id } => {
+                self.id = Some(client_id);
+            }
+            ServerMessage::Spawn(data) => {
+                let resource = resource_manager.request::<Model>(&data.resource);
+                self.pending_spawn = Some((data, resource));
+            }
+            ServerMessage::Replicate { id, replication } => {
+                self.entities
+                    .entry(id)
+                    .or_insert_with(|| Entity {
+                        replication: Default::default(),
+                        samples: Default::default(),
+                    })
+                    .replication = replication;
+            }
+            ServerMessage::Despawn { id } => {
+                self.entities.remove(&id);
+                if let Some(handle) = scene.graph.id_to_node_handle(id).cloned() {
+                    scene.graph.remove_node(handle);
+                }
+            }
+            ServerMessage::Snapshot { time, nodes } => {
+                let mut nodes = nodes
+                    .into_iter()
+                    .map(|delta| (delta.id, delta))
+                    .collect::<FxHashMap<_, _>>();
+
+                for (id, entity) in self.entities.iter_mut() {
+                    let delta = nodes.remove(id);
+
+                    if entity.replication.transform {
+                        // Every snapshot adds a sample, so the interpolation knows that the
+                        // node stays still if its transform hasn't changed.
+                        let state = delta
+                            .as_ref()
+                            .and_then(|delta| delta.transform.clone())
+                            .or_else(|| entity.samples.back().map(|(_, state)| state.clone()));
+                        if let Some(state) = state {
+                            entity.samples.push_back((time, state));
+                            if entity.samples.len() > MAX_SAMPLES {
+                                entity.samples.pop_front();
+                            }
+                        }
+                    }
+
+                    if let Some(delta) = delta {
+                        if let Some((_, node)) = scene.graph.node_by_id_mut(*id) {
+                            for (index, value) in delta.properties {
+                                if let Some(property) =
+                                    entity.replication.properties.get(index as usize)
+                                {
+                                    write_property(node, property, value);
+                                }
+                            }
+                        }
+                    }
+                }
+
+                if self.latest_time.is_none() {
+                    self.time = time - self.interpolation_delay;
+                }
+                self.latest_time = Some(time);
+            }
+            ServerMessage::Message(packet) => self.remote_messages.deliver(
+                packet,
+                |id| scene.graph.id_to_node_handle(id).cloned(),
+                message_sender,
+            ),
+        }
+    }
+}
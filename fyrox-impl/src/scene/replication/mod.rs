@@ -0,0 +1,690 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Scene replication is used to synchronize the state of a scene between a server and a set of
+//! clients in multiplayer games with client-server model. See [`ReplicationServer`] and
+//! [`ReplicationClient`] docs for more info.
+//!
+//! The server is authoritative - it decides what happens in the scene and periodically sends
+//! snapshots of the state of replicated nodes to every connected client. Snapshots are delta
+//! compressed: only the values, that were changed since the previous snapshot sent to a particular
+//! client, are transmitted. Nodes are identified by their [`SceneNodeId`], which is the same on
+//! the server and the clients, because it is stored in scene files and it is transferred when
+//! a prefab instance is spawned by the server (see [`ReplicationServer::spawn`]).
+//!
+//! Clients and the server could also exchange script messages over the network. Such messages must
+//! be serializable and registered in [`RemoteMessageRegistry`] on the receiving side. When received,
+//! a message is delivered to scripts as an ordinary script message.
+
+use crate::{
+    core::{
+        algebra::{UnitQuaternion, Vector2, Vector3, Vector4},
+        color::Color,
+        log::Log,
+        pool::Handle,
+        reflect::{prelude::*, ResolvePath},
+        uuid::Uuid,
+        TypeUuidProvider,
+    },
+    scene::{base::SceneNodeId, node::Node, transform::Transform},
+    script::{
+        ScriptMessage, ScriptMessageKind, ScriptMessagePayload, ScriptMessageSender, ScriptTrait,
+    },
+};
+use fxhash::FxHashMap;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    fmt::{Display, Formatter},
+    path::PathBuf,
+};
+
+mod client;
+mod server;
+
+pub use client::ReplicationClient;
+pub use server::ReplicationServer;
+
+/// Unique identifier of a client connected to a [`ReplicationServer`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ClientId(pub u32);
+
+impl Display for ClientId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Client {}", self.0)
+    }
+}
+
+/// A property of a node, that should be replicated.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReplicatedProperty {
+    /// A property of the node itself. The path has the same format as the one used by
+    /// [`ResolvePath`], for example `base.visibility`.
+    Node {
+        /// Path to the property.
+        path: String,
+    },
+    /// A property of a script of the node.
+    Script {
+        /// Type uuid of the script.
+        script: Uuid,
+        /// Path to the property inside the script.
+        path: String,
+    },
+}
+
+/// Replication settings of a node. Defines what parts of the node will be sent to clients.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Replication {
+    /// Whether the local transform of the node should be replicated or not. Transform is
+    /// interpolated on clients.
+    pub transform: bool,
+    /// A set of replicated properties. Properties are not interpolated on clients.
+    pub properties: Vec<ReplicatedProperty>,
+}
+
+impl Replication {
+    /// Creates replication settings with transform replication enabled.
+    pub fn transform() -> Self {
+        Self {
+            transform: true,
+            properties: Default::default(),
+        }
+    }
+
+    /// Sets whether the local transform of the node should be replicated or not.
+    pub fn with_transform(mut self, transform: bool) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    /// Adds a property of the node to the set of replicated properties.
+    pub fn with_node_property<P: Into<String>>(mut self, path: P) -> Self {
+        self.properties
+            .push(ReplicatedProperty::Node { path: path.into() });
+        self
+    }
+
+    /// Adds a property of a script of the given type to the set of replicated properties.
+    pub fn with_script_property<S, P>(mut self, path: P) -> Self
+    where
+        S: ScriptTrait + TypeUuidProvider,
+        P: Into<String>,
+    {
+        self.properties.push(ReplicatedProperty::Script {
+            script: S::type_uuid(),
+            path: path.into(),
+        });
+        self
+    }
+}
+
+/// A value of a replicated property. Only the types listed here could be replicated.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ReplicatedValue {
+    /// `bool` value.
+    Bool(bool),
+    /// `i32` value.
+    I32(i32),
+    /// `u32` value.
+    U32(u32),
+    /// `i64` value.
+    I64(i64),
+    /// `u64` value.
+    U64(u64),
+    /// `f32` value.
+    F32(f32),
+    /// `f64` value.
+    F64(f64),
+    /// `String` value.
+    String(String),
+    /// `Vector2<f32>` value.
+    Vector2(Vector2<f32>),
+    /// `Vector3<f32>` value.
+    Vector3(Vector3<f32>),
+    /// `Vector4<f32>` value.
+    Vector4(Vector4<f32>),
+    /// `UnitQuaternion<f32>` value.
+    UnitQuaternion(UnitQuaternion<f32>),
+    /// `Color` value stored as RGBA.
+    Color([u8; 4]),
+}
+
+macro_rules! define_value_conversion {
+    ($($variant:ident($ty:ty)),*) => {
+        impl ReplicatedValue {
+            /// Tries to create a replicated value from the given reflected value. Returns `None`
+            /// if the type of the value is not supported.
+            pub fn from_reflect(value: &dyn Reflect) -> Option<Self> {
+                let mut result = None;
+                value.as_any(&mut |any| {
+                    $(
+                        if let Some(value) = any.downcast_ref::<$ty>() {
+                            result = Some(Self::$variant(value.clone()));
+                            return;
+                        }
+                    )*
+                    if let Some(color) = any.downcast_ref::<Color>() {
+                        result = Some(Self::Color([color.r, color.g, color.b, color.a]));
+                    }
+                });
+                result
+            }
+
+            /// Converts the value into a boxed reflected value.
+            pub fn into_reflect(self) -> Box<dyn Reflect> {
+                match self {
+                    $(Self::$variant(value) => Box::new(value),)*
+                    Self::Color([r, g, b, a]) => Box::new(Color::from_rgba(r, g, b, a)),
+                }
+            }
+        }
+    };
+}
+
+define_value_conversion!(
+    Bool(bool),
+    I32(i32),
+    U32(u32),
+    I64(i64),
+    U64(u64),
+    F32(f32),
+    F64(f64),
+    String(String),
+    Vector2(Vector2<f32>),
+    Vector3(Vector3<f32>),
+    Vector4(Vector4<f32>),
+    UnitQuaternion(UnitQuaternion<f32>)
+);
+
+/// Local transform of a replicated node.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TransformState {
+    /// Local position.
+    pub position: Vector3<f32>,
+    /// Local rotation.
+    pub rotation: UnitQuaternion<f32>,
+    /// Local scale.
+    pub scale: Vector3<f32>,
+}
+
+impl TransformState {
+    /// Takes the state of the given transform.
+    pub fn from_transform(transform: &Transform) -> Self {
+        Self {
+            position: **transform.position(),
+            rotation: **transform.rotation(),
+            scale: **transform.scale(),
+        }
+    }
+
+    /// Applies the state to the given transform.
+    pub fn apply(&self, transform: &mut Transform) {
+        transform
+            .set_position(self.position)
+            .set_rotation(self.rotation)
+            .set_scale(self.scale);
+    }
+
+    /// Interpolates between two states.
+    pub fn interpolate(&self, other: &Self, t: f32) -> Self {
+        Self {
+            position: self.position.lerp(&other.position, t),
+            rotation: self.rotation.nlerp(&other.rotation, t),
+            scale: self.scale.lerp(&other.scale, t),
+        }
+    }
+}
+
+fn read_property(node: &Node, property: &ReplicatedProperty) -> Option<ReplicatedValue> {
+    let mut result = None;
+    let mut func = |value: Result<&dyn Reflect, _>| {
+        if let Ok(value) = value {
+            result = ReplicatedValue::from_reflect(value);
+        }
+    };
+    match property {
+        ReplicatedProperty::Node { path } => node.resolve_path(path, &mut func),
+        ReplicatedProperty::Script { script, path } => {
+            if let Some(script) = node.scripts().find(|s| s.id() == *script) {
+                script.resolve_path(path, &mut func)
+            }
+        }
+    }
+    result
+}
+
+fn write_property(node: &mut Node, property: &ReplicatedProperty, value: ReplicatedValue) {
+    let mut value = Some(value);
+    let mut func = |field: Result<&mut dyn Reflect, _>| {
+        if let (Ok(field), Some(value)) = (field, value.take()) {
+            if field.set(value.into_reflect()).is_err() {
+                Log::err(format!(
+                    "Unable to set a value of replicated property {property:?}: type mismatch!"
+                ));
+            }
+        }
+    };
+    match property {
+        ReplicatedProperty::Node { path } => node.resolve_path_mut(path, &mut func),
+        ReplicatedProperty::Script { script, path } => {
+            if let Some(script) = node.scripts_mut().find(|s| s.id() == *script) {
+                script.resolve_path_mut(path, &mut func)
+            }
+        }
+    }
+}
+
+/// A message, that could be sent over the network and then delivered to scripts. Such messages
+/// must be registered in a [`RemoteMessageRegistry`] of the receiving side. This trait is
+/// implemented automatically for every suitable type.
+pub trait RemoteMessage:
+    ScriptMessagePayload + TypeUuidProvider + Serialize + DeserializeOwned
+{
+}
+
+impl<T> RemoteMessage for T where
+    T: ScriptMessagePayload + TypeUuidProvider + Serialize + DeserializeOwned
+{
+}
+
+/// Defines who should receive a remote message on the receiving side.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MessageTarget {
+    /// The message will be delivered to the scripts of a node with the given id.
+    Node(SceneNodeId),
+    /// The message will be delivered to every script, that is subscribed to messages of this
+    /// type.
+    Global,
+}
+
+type MessageDeserializer = fn(&[u8]) -> bincode::Result<Box<dyn ScriptMessagePayload>>;
+
+/// A set of message types, that could be received over the network.
+#[derive(Default)]
+pub struct RemoteMessageRegistry {
+    deserializers: FxHashMap<Uuid, MessageDeserializer>,
+}
+
+impl RemoteMessageRegistry {
+    /// Registers a new message type.
+    pub fn register<T: RemoteMessage>(&mut self) {
+        self.deserializers.insert(T::type_uuid(), |data| {
+            Ok(Box::new(bincode::deserialize::<T>(data)?))
+        });
+    }
+
+    /// Checks if a message type with the given type uuid is registered.
+    pub fn is_registered(&self, type_uuid: &Uuid) -> bool {
+        self.deserializers.contains_key(type_uuid)
+    }
+
+    fn deliver(
+        &self,
+        packet: MessagePacket,
+        resolve: impl FnOnce(SceneNodeId) -> Option<Handle<Node>>,
+        message_sender: &ScriptMessageSender,
+    ) {
+        let Some(deserializer) = self.deserializers.get(&packet.type_uuid) else {
+            Log::err(format!(
+                "Unable to deliver a remote message of type {}: the type is not registered!",
+                packet.type_uuid
+            ));
+            return;
+        };
+
+        let kind = match packet.target {
+            MessageTarget::Node(id) => match resolve(id) {
+                Some(handle) => ScriptMessageKind::Targeted(handle),
+                None => {
+                    Log::warn(format!(
+                        "Unable to deliver a remote message: no node with {id:?} id!"
+                    ));
+                    return;
+                }
+            },
+            MessageTarget::Global => ScriptMessageKind::Global,
+        };
+
+        match deserializer(&packet.data) {
+            Ok(payload) => message_sender.send(ScriptMessage { payload, kind }),
+            Err(err) => Log::err(format!("Unable to deserialize a remote message: {err:?}")),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct MessagePacket {
+    target: MessageTarget,
+    type_uuid: Uuid,
+    data: Vec<u8>,
+}
+
+impl MessagePacket {
+    fn new<T: RemoteMessage>(target: MessageTarget, payload: &T) -> bincode::Result<Self> {
+        Ok(Self {
+            target,
+            type_uuid: T::type_uuid(),
+            data: bincode::serialize(payload)?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct SpawnData {
+    resource: PathBuf,
+    ids: Vec<(Handle<Node>, SceneNodeId)>,
+    parent: Option<SceneNodeId>,
+    transform: TransformState,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct NodeDelta {
+    id: SceneNodeId,
+    transform: Option<TransformState>,
+    properties: Vec<(u16, ReplicatedValue)>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+enum ServerMessage {
+    Welcome {
+        client_id: ClientId,
+    },
+    Spawn(SpawnData),
+    Replicate {
+        id: SceneNodeId,
+        replication: Replication,
+    },
+    Despawn {
+        id: SceneNodeId,
+    },
+    Snapshot {
+        time: f32,
+        nodes: Vec<NodeDelta>,
+    },
+    Message(MessagePacket),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+enum ClientMessage {
+    Message(MessagePacket),
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::manager::ResourceManager,
+        core::{
+            algebra::Vector3,
+            futures::executor::block_on,
+            pool::Handle,
+            reflect::prelude::*,
+            task::TaskPool,
+            type_traits::prelude::*,
+            uuid::{uuid, Uuid},
+            visitor::prelude::*,
+        },
+        engine::{Engine, EngineInitParams},
+        graph::SceneGraph,
+        resource::model::Model,
+        scene::{
+            base::{BaseBuilder, SceneNodeId},
+            pivot::PivotBuilder,
+            replication::{MessageTarget, Replication, ReplicationClient, ReplicationServer},
+            transform::TransformBuilder,
+            Scene,
+        },
+        script::{ScriptContext, ScriptMessageContext, ScriptMessagePayload, ScriptTrait},
+    };
+    use serde::{Deserialize, Serialize};
+    use std::{fs, mem::ManuallyDrop, mem::MaybeUninit, path::Path, sync::Arc, time::Duration};
+    use winit::event_loop::EventLoop;
+
+    #[derive(Debug, Serialize, Deserialize, TypeUuidProvider)]
+    #[type_uuid(id = "6a3e0a5b-3e1c-4c53-9a2f-5f5c2f0f8f11")]
+    struct Ping(u32);
+
+    #[derive(Clone, Debug, Default, Reflect, Visit, TypeUuidProvider, ComponentProvider)]
+    #[type_uuid(id = "1f0b8a54-0d0a-4e09-8cb2-0a1b9e7c6d21")]
+    struct Player {
+        health: f32,
+        last_ping: u32,
+    }
+
+    impl ScriptTrait for Player {
+        fn on_start(&mut self, ctx: &mut ScriptContext) {
+            ctx.message_dispatcher.subscribe_to::<Ping>(ctx.handle);
+        }
+
+        fn on_message(
+            &mut self,
+            message: &mut dyn ScriptMessagePayload,
+            _ctx: &mut ScriptMessageContext,
+        ) {
+            if let Some(Ping(value)) = message.downcast_ref::<Ping>() {
+                self.last_ping = *value;
+            }
+        }
+    }
+
+    const PLAYER_ID: SceneNodeId = SceneNodeId(uuid!("c1a5d0b2-53f1-4a53-a3c7-38f0b3f1a001"));
+
+    fn make_engine() -> Engine {
+        let task_pool = Arc::new(TaskPool::default());
+        let mut engine = Engine::new(EngineInitParams {
+            graphics_context_params: Default::default(),
+            serialization_context: Arc::new(Default::default()),
+            widget_constructors: Arc::new(Default::default()),
+            resource_manager: ResourceManager::new(task_pool.clone()),
+            task_pool,
+        })
+        .unwrap();
+        engine.enable_plugins(None, true, None);
+
+        let mut scene = Scene::new();
+        PivotBuilder::new(
+            BaseBuilder::new()
+                .with_instance_id(PLAYER_ID)
+                .with_script(Player {
+                    health: 100.0,
+                    last_ping: 0,
+                }),
+        )
+        .build(&mut scene.graph);
+        let scene = engine.scenes.add(scene);
+        engine.register_scripted_scene(scene);
+
+        engine
+    }
+
+    fn player(engine: &Engine) -> &Player {
+        let scene = engine.scenes.iter().next().unwrap();
+        let (_, node) = scene.graph.node_by_id(PLAYER_ID).unwrap();
+        node.try_get_script::<Player>().unwrap()
+    }
+
+    #[test]
+    #[cfg(not(target_os = "macos"))] // Event loop hack does not work on macOS.
+    fn test_loopback_replication() {
+        // See `test_async_script_tasks` for the explanation of this hack.
+        #[allow(invalid_value)]
+        #[allow(clippy::uninit_assumed_init)]
+        let event_loop =
+            unsafe { ManuallyDrop::new(MaybeUninit::<EventLoop<()>>::uninit().assume_init()) };
+
+        if !Path::new("test_output").exists() {
+            fs::create_dir_all("test_output").unwrap();
+        }
+        let prefab_path = Path::new("test_output/replication_prefab.rgs");
+        {
+            let mut prefab = Scene::new();
+            let child =
+                PivotBuilder::new(BaseBuilder::new().with_name("Child")).build(&mut prefab.graph);
+            PivotBuilder::new(BaseBuilder::new().with_name("Root").with_children(&[child]))
+                .build(&mut prefab.graph);
+            let mut visitor = Visitor::new();
+            prefab.save("Scene", &mut visitor).unwrap();
+            visitor.save_binary(prefab_path).unwrap();
+        }
+
+        let mut server_engine = make_engine();
+        let mut client_engine = make_engine();
+        let server_scene = server_engine.scenes.pair_iter().next().unwrap().0;
+        let client_scene = client_engine.scenes.pair_iter().next().unwrap().0;
+
+        let mut server = ReplicationServer::bind("127.0.0.1:0").unwrap();
+        server.remote_messages.register::<Ping>();
+        let mut client = ReplicationClient::connect(server.local_address().unwrap()).unwrap();
+        client.remote_messages.register::<Ping>();
+
+        let player_handle = *server_engine.scenes[server_scene]
+            .graph
+            .id_to_node_handle(PLAYER_ID)
+            .unwrap();
+        server.replicate(
+            &server_engine.scenes[server_scene],
+            player_handle,
+            Replication::transform().with_script_property::<Player, _>("health"),
+        );
+
+        let run_until =
+            |server: &mut ReplicationServer,
+             client: &mut ReplicationClient,
+             server_engine: &mut Engine,
+             client_engine: &mut Engine,
+             condition: &dyn Fn(&Engine, &Engine) -> bool| {
+                let dt = 1.0 / 60.0;
+                let mut lag = 0.0;
+                for _ in 0..1000 {
+                    let sender = server_engine
+                        .script_processor
+                        .message_sender(server_scene)
+                        .unwrap()
+                        .clone();
+                    server.update(&server_engine.scenes[server_scene], &sender, dt);
+                    server_engine.update(dt, &event_loop, &mut lag, Default::default());
+
+                    let sender = client_engine
+                        .script_processor
+                        .message_sender(client_scene)
+                        .unwrap()
+                        .clone();
+                    client.update(
+                        &mut client_engine.scenes[client_scene],
+                        &client_engine.resource_manager,
+                        &sender,
+                        dt,
+                    );
+                    client_engine.update(dt, &event_loop, &mut lag, Default::default());
+
+                    if condition(server_engine, client_engine) {
+                        return true;
+                    }
+
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+                false
+            };
+
+        // Transform and script property replication.
+        {
+            let scene = &mut server_engine.scenes[server_scene];
+            scene.graph[player_handle]
+                .local_transform_mut()
+                .set_position(Vector3::new(1.0, 2.0, 3.0));
+            scene.graph[player_handle]
+                .try_get_script_mut::<Player>()
+                .unwrap()
+                .health = 50.0;
+        }
+        assert!(run_until(
+            &mut server,
+            &mut client,
+            &mut server_engine,
+            &mut client_engine,
+            &|_, client_engine| {
+                let scene = client_engine.scenes.iter().next().unwrap();
+                let (_, node) = scene.graph.node_by_id(PLAYER_ID).unwrap();
+                **node.local_transform().position() == Vector3::new(1.0, 2.0, 3.0)
+                    && player(client_engine).health == 50.0
+            }
+        ));
+        assert_eq!(client.id(), server.clients().next());
+
+        // Prefab instance spawning.
+        let prefab =
+            block_on(server_engine.resource_manager.request::<Model>(prefab_path)).unwrap();
+        let instance = server.spawn(
+            &mut server_engine.scenes[server_scene],
+            &prefab,
+            Handle::NONE,
+            TransformBuilder::new()
+                .with_local_position(Vector3::new(5.0, 0.0, 0.0))
+                .build(),
+            Replication::transform(),
+        );
+        let (instance_id, child_id) = {
+            let graph = &server_engine.scenes[server_scene].graph;
+            let child = graph.find_by_name(instance, "Child").unwrap().0;
+            (graph[instance].instance_id(), graph[child].instance_id())
+        };
+        assert!(run_until(
+            &mut server,
+            &mut client,
+            &mut server_engine,
+            &mut client_engine,
+            &|_, client_engine| {
+                let graph = &client_engine.scenes.iter().next().unwrap().graph;
+                graph.node_by_id(child_id).is_some()
+                    && graph.node_by_id(instance_id).is_some_and(|(_, node)| {
+                        **node.local_transform().position() == Vector3::new(5.0, 0.0, 0.0)
+                    })
+            }
+        ));
+
+        // Remote messages in both directions.
+        server.broadcast_message(MessageTarget::Node(PLAYER_ID), &Ping(42));
+        client
+            .send_message(MessageTarget::Node(PLAYER_ID), &Ping(7))
+            .unwrap();
+        assert!(run_until(
+            &mut server,
+            &mut client,
+            &mut server_engine,
+            &mut client_engine,
+            &|server_engine, client_engine| {
+                player(server_engine).last_ping == 7 && player(client_engine).last_ping == 42
+            }
+        ));
+
+        // Despawning.
+        server.despawn(&mut server_engine.scenes[server_scene], instance);
+        assert!(run_until(
+            &mut server,
+            &mut client,
+            &mut server_engine,
+            &mut client_engine,
+            &|_, client_engine| {
+                let graph = &client_engine.scenes.iter().next().unwrap().graph;
+                graph.node_by_id(instance_id).is_none() && graph.node_by_id(child_id).is_none()
+            }
+        ));
+        assert!(!client.is_replicated(instance_id));
+    }
+}
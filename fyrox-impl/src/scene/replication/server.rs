@@ -0,0 +1,478 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Server side of the scene replication. See [`ReplicationServer`] docs for more info.
+
+use crate::{
+    asset::untyped::ResourceKind,
+    core::{
+        log::Log,
+        net::{NetListener, NetStream},
+        pool::Handle,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    resource::model::{ModelResource, ModelResourceExtension},
+    scene::{
+        base::SceneNodeId,
+        node::Node,
+        replication::{
+            read_property, ClientId, ClientMessage, MessagePacket, MessageTarget, NodeDelta,
+            RemoteMessage, RemoteMessageRegistry, ReplicatedValue, Replication, ServerMessage,
+            SpawnData, TransformState,
+        },
+        transform::Transform,
+        Scene,
+    },
+    script::ScriptMessageSender,
+};
+use fxhash::FxHashMap;
+use std::{
+    io,
+    net::{SocketAddr, ToSocketAddrs},
+    path::PathBuf,
+};
+
+#[derive(Clone, Default, PartialEq)]
+struct NodeState {
+    transform: Option<TransformState>,
+    properties: Vec<Option<ReplicatedValue>>,
+}
+
+impl NodeState {
+    fn delta(&self, id: SceneNodeId, baseline: &mut NodeState) -> Option<NodeDelta> {
+        let transform = if self.transform != baseline.transform {
+            self.transform.clone()
+        } else {
+            None
+        };
+
+        baseline
+            .properties
+            .resize(self.properties.len(), Default::default());
+        let properties = self
+            .properties
+            .iter()
+            .zip(baseline.properties.iter())
+            .enumerate()
+            .filter_map(|(index, (value, old_value))| {
+                if value.is_some() && value != old_value {
+                    value.clone().map(|value| (index as u16, value))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        baseline.clone_from(self);
+
+        if transform.is_none() && properties.is_empty() {
+            None
+        } else {
+            Some(NodeDelta {
+                id,
+                transform,
+                properties,
+            })
+        }
+    }
+}
+
+struct SpawnInfo {
+    resource: PathBuf,
+    ids: Vec<(Handle<Node>, SceneNodeId)>,
+}
+
+struct Entity {
+    id: SceneNodeId,
+    replication: Replication,
+    spawn: Option<SpawnInfo>,
+}
+
+impl Entity {
+    fn capture(&self, node: &Node) -> NodeState {
+        NodeState {
+            transform: if self.replication.transform {
+                Some(TransformState::from_transform(node.local_transform()))
+            } else {
+                None
+            },
+            properties: self
+                .replication
+                .properties
+                .iter()
+                .map(|property| read_property(node, property))
+                .collect(),
+        }
+    }
+
+    fn announce(&self, scene: &Scene, outgoing: &mut Vec<ServerMessage>) {
+        if let Some(spawn) = self.spawn.as_ref() {
+            if let Some((_, node)) = scene.graph.node_by_id(self.id) {
+                let parent = node.parent();
+                outgoing.push(ServerMessage::Spawn(SpawnData {
+                    resource: spawn.resource.clone(),
+                    ids: spawn.ids.clone(),
+                    parent: if parent == scene.graph.get_root() {
+                        None
+                    } else {
+                        scene.graph.try_get(parent).map(|p| p.instance_id())
+                    },
+                    transform: TransformState::from_transform(node.local_transform()),
+                }));
+            }
+        }
+
+        outgoing.push(ServerMessage::Replicate {
+            id: self.id,
+            replication: self.replication.clone(),
+        });
+    }
+}
+
+struct Connection {
+    id: ClientId,
+    stream: NetStream,
+    baseline: FxHashMap<SceneNodeId, NodeState>,
+    outgoing: Vec<ServerMessage>,
+}
+
+/// Replication server is the authoritative side of the scene replication. It accepts connections
+/// from [`super::ReplicationClient`]s and periodically sends snapshots of the replicated nodes to
+/// them. Only the nodes, that were explicitly marked as replicated, are sent to clients, see
+/// [`Self::replicate`] and [`Self::spawn`].
+///
+/// The server must be updated every frame by calling [`Self::update`], usually this is done in
+/// [`crate::plugin::Plugin::update`].
+///
+/// ```rust,no_run
+/// # use fyrox_impl::{
+/// #     core::pool::Handle,
+/// #     scene::{node::Node, replication::{Replication, ReplicationServer}, Scene},
+/// #     script::ScriptMessageSender,
+/// # };
+/// fn run(scene: &mut Scene, player: Handle<Node>, message_sender: &ScriptMessageSender) {
+///     let mut server = ReplicationServer::bind("127.0.0.1:10000").unwrap();
+///
+///     // Replicate the transform of the player.
+///     server.replicate(scene, player, Replication::transform());
+///
+///     loop {
+///         server.update(scene, message_sender, 1.0 / 60.0);
+///     }
+/// }
+/// ```
+pub struct ReplicationServer {
+    listener: NetListener,
+    connections: Vec<Connection>,
+    entities: Vec<Entity>,
+    next_client_id: u32,
+    time: f32,
+    snapshot_timer: f32,
+    /// Time interval (in seconds) between two snapshots. Default is 1/20 of a second.
+    pub snapshot_interval: f32,
+    /// A set of message types, that could be received from clients.
+    pub remote_messages: RemoteMessageRegistry,
+}
+
+impl ReplicationServer {
+    /// Creates a new server, that listens for incoming connections on the given address.
+    pub fn bind<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        Ok(Self {
+            listener: NetListener::bind(address)?,
+            connections: Default::default(),
+            entities: Default::default(),
+            next_client_id: 0,
+            time: 0.0,
+            snapshot_timer: 0.0,
+            snapshot_interval: 1.0 / 20.0,
+            remote_messages: Default::default(),
+        })
+    }
+
+    /// Returns the address the server is listening on.
+    pub fn local_address(&self) -> io::Result<SocketAddr> {
+        self.listener.local_address()
+    }
+
+    /// Returns an iterator over the ids of every connected client.
+    pub fn clients(&self) -> impl Iterator<Item = ClientId> + '_ {
+        self.connections.iter().map(|c| c.id)
+    }
+
+    /// Returns `true` if the node with the given id is replicated.
+    pub fn is_replicated(&self, id: SceneNodeId) -> bool {
+        self.entities.iter().any(|e| e.id == id)
+    }
+
+    /// Marks the given node as replicated. The node must exist on the clients as well, it is
+    /// guaranteed if the node is stored in a scene file, that is loaded by both the server and
+    /// the clients. Use [`Self::spawn`] to create nodes, that exist only on the server. Calling
+    /// this method for an already replicated node changes its replication settings.
+    pub fn replicate(&mut self, scene: &Scene, node: Handle<Node>, replication: Replication) {
+        let Some(node_ref) = scene.graph.try_get(node) else {
+            Log::err(format!("Unable to replicate node {node}: no such node!"));
+            return;
+        };
+
+        let id = node_ref.instance_id();
+        if let Some(entity) = self.entities.iter_mut().find(|e| e.id == id) {
+            entity.replication = replication;
+            for connection in self.connections.iter_mut() {
+                connection.baseline.remove(&id);
+                connection.outgoing.push(ServerMessage::Replicate {
+                    id,
+                    replication: entity.replication.clone(),
+                });
+            }
+        } else {
+            self.add_entity(
+                scene,
+                Entity {
+                    id,
+                    replication,
+                    spawn: None,
+                },
+            );
+        }
+    }
+
+    /// Marks the given prefab instance as replicated. Clients will instantiate the same prefab
+    /// with the same ids of nodes. The prefab must be stored in a file, that is available to the
+    /// clients.
+    pub fn replicate_instance(
+        &mut self,
+        scene: &Scene,
+        root: Handle<Node>,
+        replication: Replication,
+    ) {
+        let Some(root_ref) = scene.graph.try_get(root) else {
+            Log::err(format!(
+                "Unable to replicate instance {root}: no such node!"
+            ));
+            return;
+        };
+
+        let Some(resource) = root_ref.resource() else {
+            Log::err(format!(
+                "Unable to replicate instance {root}: the node is not a prefab instance!"
+            ));
+            return;
+        };
+
+        let ResourceKind::External(path) = resource.kind() else {
+            Log::err(format!(
+                "Unable to replicate instance {root}: embedded prefabs are not supported!"
+            ));
+            return;
+        };
+
+        let ids = scene
+            .graph
+            .traverse_iter(root)
+            .filter(|(_, node)| {
+                node.original_handle_in_resource().is_some()
+                    && node.resource().as_ref() == Some(&resource)
+            })
+            .map(|(_, node)| (node.original_handle_in_resource(), node.instance_id()))
+            .collect();
+
+        self.add_entity(
+            scene,
+            Entity {
+                id: root_ref.instance_id(),
+                replication,
+                spawn: Some(SpawnInfo {
+                    resource: path,
+                    ids,
+                }),
+            },
+        );
+    }
+
+    /// Instantiates the given prefab, attaches the instance to the given parent (if any) and marks
+    /// the instance as replicated (see [`Self::replicate_instance`]).
+    pub fn spawn(
+        &mut self,
+        scene: &mut Scene,
+        prefab: &ModelResource,
+        parent: Handle<Node>,
+        transform: Transform,
+        replication: Replication,
+    ) -> Handle<Node> {
+        let instance = prefab
+            .begin_instantiation(scene)
+            .with_transform(transform)
+            .finish();
+        if parent.is_some() {
+            scene.graph.link_nodes(instance, parent);
+        }
+        self.replicate_instance(scene, instance, replication);
+        instance
+    }
+
+    /// Removes the given replicated node (with its descendants) from the scene and from the
+    /// clients.
+    pub fn despawn(&mut self, scene: &mut Scene, node: Handle<Node>) {
+        if let Some(node_ref) = scene.graph.try_get(node) {
+            let id = node_ref.instance_id();
+            scene.graph.remove_node(node);
+            self.remove_entity(id);
+        }
+    }
+
+    /// Sends the given message to the given client.
+    pub fn send_message_to<T: RemoteMessage>(
+        &mut self,
+        client: ClientId,
+        target: MessageTarget,
+        payload: &T,
+    ) {
+        if let Some(connection) = self.connections.iter_mut().find(|c| c.id == client) {
+            match MessagePacket::new(target, payload) {
+                Ok(packet) => connection.outgoing.push(ServerMessage::Message(packet)),
+                Err(err) => Log::err(format!("Unable to serialize a remote message: {err:?}")),
+            }
+        }
+    }
+
+    /// Sends the given message to every connected client.
+    pub fn broadcast_message<T: RemoteMessage>(&mut self, target: MessageTarget, payload: &T) {
+        match MessagePacket::new(target, payload) {
+            Ok(packet) => {
+                for connection in self.connections.iter_mut() {
+                    connection
+                        .outgoing
+                        .push(ServerMessage::Message(packet.clone()));
+                }
+            }
+            Err(err) => Log::err(format!("Unable to serialize a remote message: {err:?}")),
+        }
+    }
+
+    fn add_entity(&mut self, scene: &Scene, entity: Entity) {
+        for connection in self.connections.iter_mut() {
+            entity.announce(scene, &mut connection.outgoing);
+        }
+        self.entities.push(entity);
+    }
+
+    fn remove_entity(&mut self, id: SceneNodeId) {
+        if let Some(position) = self.entities.iter().position(|e| e.id == id) {
+            self.entities.remove(position);
+            for connection in self.connections.iter_mut() {
+                connection.baseline.remove(&id);
+                connection.outgoing.push(ServerMessage::Despawn { id });
+            }
+        }
+    }
+
+    /// Accepts new connections, handles messages from clients, sends snapshots of the replicated
+    /// nodes and queued messages to clients. Messages from clients are delivered to the scripts
+    /// using the given message sender.
+    pub fn update(&mut self, scene: &Scene, message_sender: &ScriptMessageSender, dt: f32) {
+        for stream in self.listener.accept_connections() {
+            let id = ClientId(self.next_client_id);
+            self.next_client_id += 1;
+
+            Log::info(format!(
+                "{id} connected from {}.",
+                stream.string_peer_address()
+            ));
+
+            let mut outgoing = vec![ServerMessage::Welcome { client_id: id }];
+            for entity in self.entities.iter() {
+                entity.announce(scene, &mut outgoing);
+            }
+
+            self.connections.push(Connection {
+                id,
+                stream,
+                baseline: Default::default(),
+                outgoing,
+            });
+        }
+
+        for connection in self.connections.iter_mut() {
+            connection
+                .stream
+                .process_input::<ClientMessage>(|message| match message {
+                    ClientMessage::Message(packet) => self.remote_messages.deliver(
+                        packet,
+                        |id| scene.graph.id_to_node_handle(id).cloned(),
+                        message_sender,
+                    ),
+                });
+        }
+
+        let removed = self
+            .entities
+            .iter()
+            .filter(|e| scene.graph.node_by_id(e.id).is_none())
+            .map(|e| e.id)
+            .collect::<Vec<_>>();
+        for id in removed {
+            self.remove_entity(id);
+        }
+
+        self.time += dt;
+        self.snapshot_timer -= dt;
+        if self.snapshot_timer <= 0.0 {
+            self.snapshot_timer = self.snapshot_interval;
+
+            let states = self
+                .entities
+                .iter()
+                .filter_map(|entity| {
+                    scene
+                        .graph
+                        .node_by_id(entity.id)
+                        .map(|(_, node)| (entity.id, entity.capture(node)))
+                })
+                .collect::<Vec<_>>();
+
+            for connection in self.connections.iter_mut() {
+                let nodes = states
+                    .iter()
+                    .filter_map(|(id, state)| {
+                        state.delta(*id, connection.baseline.entry(*id).or_default())
+                    })
+                    .collect();
+
+                connection.outgoing.push(ServerMessage::Snapshot {
+                    time: self.time,
+                    nodes,
+                });
+            }
+        }
+
+        self.connections.retain_mut(|connection| {
+            for message in connection.outgoing.drain(..) {
+                if let Err(err) = connection.stream.send_message(&message) {
+                    Log::err(format!(
+                        "Unable to send a message to {}, it will be disconnected. Reason: {err}",
+                        connection.id
+                    ));
+                    return false;
+                }
+            }
+            true
+        });
+    }
+}
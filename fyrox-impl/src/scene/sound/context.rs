@@ -22,6 +22,7 @@
 
 use crate::{
     core::{
+        algebra::Vector3,
         log::{Log, MessageKind},
         pool::Handle,
         visitor::prelude::*,
@@ -162,8 +163,17 @@ impl SoundContext {
         }
     }
 
+    pub(crate) fn listener_position(&self) -> Vector3<f32> {
+        self.native.state().listener().position()
+    }
+
     pub(crate) fn sync_with_sound(&self, sound: &mut Sound) {
         if let Some(source) = self.native.state().try_get_source_mut(sound.native.get()) {
+            let (gain, cutoff_frequency_hz) = sound
+                .occlusion()
+                .gain_and_cutoff_frequency(sound.occlusion_amount);
+            source.set_occlusion(gain, cutoff_frequency_hz);
+
             // Sync back.
             sound.status.set_value_silent(source.status());
             sound
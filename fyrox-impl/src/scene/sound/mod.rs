@@ -58,6 +58,7 @@ pub use fyrox_sound::{
 };
 
 use crate::scene::node::constructor::NodeConstructor;
+use crate::scene::sound::occlusion::SoundOcclusion;
 use crate::scene::Scene;
 use fyrox_graph::constructor::ConstructorProvider;
 use fyrox_graph::BaseSceneGraph;
@@ -71,6 +72,7 @@ use std::{
 
 pub mod context;
 pub mod listener;
+pub mod occlusion;
 
 /// Sound source.
 #[derive(Visit, Reflect, Debug, ComponentProvider)]
@@ -126,6 +128,18 @@ pub struct Sound {
     )]
     audio_bus: InheritableVariable<String>,
 
+    #[visit(optional)]
+    #[reflect(
+        setter = "set_occlusion",
+        description = "Occlusion settings of the sound. Occlusion makes the sound quieter and muffled when \
+        there are obstacles between the sound and the listener."
+    )]
+    occlusion: InheritableVariable<SoundOcclusion>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub(crate) occlusion_amount: f32,
+
     #[reflect(hidden)]
     #[visit(skip)]
     pub(crate) native: Cell<Handle<SoundSource>>,
@@ -162,6 +176,8 @@ impl Default for Sound {
             playback_time: Default::default(),
             spatial_blend: InheritableVariable::new_modified(1.0),
             audio_bus: InheritableVariable::new_modified(AudioBusGraph::PRIMARY_BUS.to_string()),
+            occlusion: InheritableVariable::new_modified(Default::default()),
+            occlusion_amount: 0.0,
             native: Default::default(),
         }
     }
@@ -184,6 +200,8 @@ impl Clone for Sound {
             playback_time: self.playback_time.clone(),
             spatial_blend: self.spatial_blend.clone(),
             audio_bus: self.audio_bus.clone(),
+            occlusion: self.occlusion.clone(),
+            occlusion_amount: self.occlusion_amount,
             // Do not copy. The copy will have its own native representation.
             native: Default::default(),
         }
@@ -386,6 +404,23 @@ impl Sound {
     pub fn audio_bus(&self) -> &str {
         &self.audio_bus
     }
+
+    /// Sets new occlusion settings of the sound. See [`SoundOcclusion`] docs for more info.
+    pub fn set_occlusion(&mut self, occlusion: SoundOcclusion) -> SoundOcclusion {
+        self.occlusion.set_value_and_mark_modified(occlusion)
+    }
+
+    /// Returns current occlusion settings of the sound.
+    pub fn occlusion(&self) -> &SoundOcclusion {
+        &self.occlusion
+    }
+
+    /// Returns current amount of occlusion of the sound. It is the sum of occlusion factors of all
+    /// colliders between the sound and the listener, smoothed over time. Always zero if the occlusion
+    /// is disabled.
+    pub fn occlusion_amount(&self) -> f32 {
+        self.occlusion_amount
+    }
 }
 
 impl ConstructorProvider<Node, Graph> for Sound {
@@ -448,6 +483,24 @@ impl NodeTrait for Sound {
     }
 
     fn update(&mut self, context: &mut UpdateContext) {
+        if self.occlusion.enabled {
+            let target_amount = self.occlusion.calculate_amount(
+                context.sound_context.listener_position(),
+                self.global_position(),
+                context.physics,
+                context.nodes,
+            );
+            let max_step = self.occlusion.smoothing_speed * context.dt;
+            if max_step > 0.0 {
+                self.occlusion_amount +=
+                    (target_amount - self.occlusion_amount).clamp(-max_step, max_step);
+            } else {
+                self.occlusion_amount = target_amount;
+            }
+        } else {
+            self.occlusion_amount = 0.0;
+        }
+
         context.sound_context.sync_with_sound(self);
     }
 
@@ -489,6 +542,7 @@ pub struct SoundBuilder {
     playback_time: Duration,
     spatial_blend: f32,
     audio_bus: String,
+    occlusion: SoundOcclusion,
 }
 
 impl SoundBuilder {
@@ -509,6 +563,7 @@ impl SoundBuilder {
             spatial_blend: 1.0,
             playback_time: Default::default(),
             audio_bus: AudioBusGraph::PRIMARY_BUS.to_string(),
+            occlusion: Default::default(),
         }
     }
 
@@ -577,6 +632,11 @@ impl SoundBuilder {
         fn with_audio_bus(audio_bus: String)
     );
 
+    define_with!(
+        /// Sets desired occlusion settings. See [`Sound::set_occlusion`] for more info.
+        fn with_occlusion(occlusion: SoundOcclusion)
+    );
+
     /// Creates a new [`Sound`] node.
     #[must_use]
     pub fn build_sound(self) -> Sound {
@@ -595,6 +655,8 @@ impl SoundBuilder {
             playback_time: self.playback_time.as_secs_f32().into(),
             spatial_blend: self.spatial_blend.into(),
             audio_bus: self.audio_bus.into(),
+            occlusion: self.occlusion.into(),
+            occlusion_amount: 0.0,
             native: Default::default(),
         }
     }
@@ -0,0 +1,246 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Sound occlusion and obstruction. See [`SoundOcclusion`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Point3, Vector3},
+        arrayvec::ArrayVec,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    scene::{
+        collider::{Collider, InteractionGroups},
+        graph::{
+            physics::{Intersection, PhysicsWorld, RayCastOptions},
+            NodePool,
+        },
+    },
+};
+
+/// Sound occlusion settings of a sound source. Occlusion simulates obstacles (walls, doors, etc.) between a
+/// sound source and the listener. When enabled, the engine casts rays from the listener to the sound source
+/// in the physics world on each frame, and every collider hit by a ray attenuates and muffles (using a
+/// low-pass filter) the sound. The amount of occlusion each collider adds is defined by
+/// [`Collider::sound_occlusion_factor`], so thin walls could occlude less than thick ones.
+///
+/// If [`Self::obstruction_radius`] is non-zero, four additional rays are cast to the points around the
+/// sound source and the resulting occlusion is averaged. This way a sound, that is only partially
+/// hidden behind an obstacle (for example - behind a corner) is obstructed, but not fully occluded.
+///
+/// Occlusion uses 3D physics only, colliders of 2D physics are ignored.
+#[derive(Debug, Clone, PartialEq, Visit, Reflect)]
+pub struct SoundOcclusion {
+    /// Whether the occlusion is enabled or not.
+    pub enabled: bool,
+
+    /// Collision groups that will be used to filter colliders that can occlude the sound.
+    pub collision_groups: InteractionGroups,
+
+    /// Gain multiplier for every unit of occlusion. For example, 0.5 means that a sound behind two
+    /// walls with default occlusion factor will have 0.25 gain.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub attenuation: f32,
+
+    /// Cutoff frequency (in Hz) of the low-pass filter for one unit of occlusion. Every next unit of
+    /// occlusion lowers the cutoff frequency further.
+    #[reflect(min_value = 20.0, max_value = 22050.0)]
+    pub cutoff_frequency_hz: f32,
+
+    /// Radius of an imaginary sphere around the source, that is used to calculate partial obstruction.
+    /// Zero means that only one ray is cast.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub obstruction_radius: f32,
+
+    /// How fast (in units per second) the occlusion changes. Prevents sudden jumps of volume when an
+    /// obstacle appears or disappears. Zero means that the occlusion changes instantly.
+    #[reflect(min_value = 0.0, step = 0.5)]
+    pub smoothing_speed: f32,
+}
+
+impl Default for SoundOcclusion {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            collision_groups: Default::default(),
+            attenuation: 0.5,
+            cutoff_frequency_hz: 2500.0,
+            obstruction_radius: 0.0,
+            smoothing_speed: 8.0,
+        }
+    }
+}
+
+impl SoundOcclusion {
+    /// Highest possible cutoff frequency of the low-pass filter, it is used when there's no occlusion.
+    pub const MAX_CUTOFF_FREQUENCY_HZ: f32 = 22050.0;
+
+    /// Calculates the amount of occlusion between the given points. The amount is the sum of
+    /// [`Collider::sound_occlusion_factor`] of every collider, that was hit by the rays from the listener
+    /// to the source (averaged over all rays, if [`Self::obstruction_radius`] is non-zero).
+    pub fn calculate_amount(
+        &self,
+        listener: Vector3<f32>,
+        source: Vector3<f32>,
+        physics: &PhysicsWorld,
+        nodes: &NodePool,
+    ) -> f32 {
+        let mut targets = ArrayVec::<Vector3<f32>, 5>::new();
+        targets.push(source);
+
+        if self.obstruction_radius > 0.0 {
+            let direction = (source - listener)
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(Vector3::z);
+            let up = if direction.y.abs() > 0.99 {
+                Vector3::x()
+            } else {
+                Vector3::y()
+            };
+            let side = direction
+                .cross(&up)
+                .normalize()
+                .scale(self.obstruction_radius);
+            let up = side
+                .cross(&direction)
+                .normalize()
+                .scale(self.obstruction_radius);
+            targets.extend([source + side, source - side, source + up, source - up]);
+        }
+
+        let mut total = 0.0;
+        let mut intersections = ArrayVec::<Intersection, 16>::new();
+        for target in targets.iter() {
+            let ray = target - listener;
+            physics.cast_ray(
+                RayCastOptions {
+                    ray_origin: Point3::from(listener),
+                    ray_direction: ray,
+                    max_len: ray.norm(),
+                    groups: self.collision_groups,
+                    sort_results: false,
+                },
+                &mut intersections,
+            );
+
+            for intersection in intersections.iter() {
+                if let Some(collider) = nodes
+                    .try_borrow(intersection.collider)
+                    .and_then(|node| node.cast::<Collider>())
+                {
+                    if !collider.is_sensor() {
+                        total += collider.sound_occlusion_factor();
+                    }
+                }
+            }
+        }
+
+        total / targets.len() as f32
+    }
+
+    /// Returns the gain and the cutoff frequency of the low-pass filter for the given amount of occlusion.
+    pub fn gain_and_cutoff_frequency(&self, amount: f32) -> (f32, f32) {
+        let gain = self.attenuation.clamp(0.0, 1.0).powf(amount);
+        let cutoff_ratio =
+            (self.cutoff_frequency_hz / Self::MAX_CUTOFF_FREQUENCY_HZ).clamp(0.0, 1.0);
+        let cutoff_frequency = Self::MAX_CUTOFF_FREQUENCY_HZ * cutoff_ratio.powf(amount);
+        (gain, cutoff_frequency)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Vector2, Vector3},
+        scene::{
+            base::BaseBuilder,
+            collider::{ColliderBuilder, ColliderShape},
+            graph::Graph,
+            rigidbody::{RigidBodyBuilder, RigidBodyType},
+            sound::{occlusion::SoundOcclusion, Sound, SoundBuilder},
+            transform::TransformBuilder,
+        },
+    };
+
+    fn add_wall(graph: &mut Graph, z: f32, sound_occlusion_factor: f32) {
+        let collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::cuboid(5.0, 5.0, 0.1))
+            .with_sound_occlusion_factor(sound_occlusion_factor)
+            .build(graph);
+        RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 0.0, z))
+                        .build(),
+                )
+                .with_children(&[collider]),
+        )
+        .with_body_type(RigidBodyType::Static)
+        .build(graph);
+    }
+
+    #[test]
+    fn test_occlusion_gain_and_cutoff() {
+        let occlusion = SoundOcclusion {
+            attenuation: 0.5,
+            cutoff_frequency_hz: 2205.0,
+            ..Default::default()
+        };
+
+        let (gain, cutoff) = occlusion.gain_and_cutoff_frequency(0.0);
+        assert_eq!(gain, 1.0);
+        assert_eq!(cutoff, SoundOcclusion::MAX_CUTOFF_FREQUENCY_HZ);
+
+        let (gain, cutoff) = occlusion.gain_and_cutoff_frequency(2.0);
+        assert_eq!(gain, 0.25);
+        assert!((cutoff - 220.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_sound_occlusion_by_walls() {
+        let mut graph = Graph::new();
+
+        add_wall(&mut graph, 3.0, 1.0);
+        add_wall(&mut graph, 6.0, 0.5);
+
+        // The listener is at the origin by default.
+        let sound = SoundBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 0.0, 10.0))
+                    .build(),
+            ),
+        )
+        .with_occlusion(SoundOcclusion {
+            enabled: true,
+            smoothing_speed: 0.0,
+            ..Default::default()
+        })
+        .build(&mut graph);
+
+        graph.update(Vector2::new(800.0, 600.0), 1.0, Default::default());
+        graph.update(Vector2::new(800.0, 600.0), 1.0, Default::default());
+
+        let amount = graph[sound].cast::<Sound>().unwrap().occlusion_amount();
+        assert!((amount - 1.5).abs() < 1.0e-5);
+    }
+}
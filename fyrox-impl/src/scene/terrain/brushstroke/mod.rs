@@ -771,6 +771,11 @@ pub enum BrushTarget {
     },
     /// Modifies the terrain's holes
     HoleMask,
+    /// Draws on the density mask of a given foliage layer
+    FoliageMask {
+        /// The number of the foliage layer to modify
+        layer: usize,
+    },
 }
 
 uuid_provider!(BrushTarget = "461c1be7-189e-44ee-b8fd-00b8fdbc668f");
@@ -1012,6 +1017,17 @@ impl ChunkData {
             std::mem::swap(a, b);
         }
     }
+    /// Swap the content of this data with the content of the given chunk's foliage density mask.
+    pub fn swap_foliage_mask(&mut self, chunk: &mut Chunk, layer: usize) {
+        let mut data_ref = chunk.foliage_masks[layer].data_ref();
+        if !self.verify_texture_size(&data_ref) {
+            return;
+        }
+        let mut modify = data_ref.modify();
+        for (a, b) in modify.data_mut().iter_mut().zip(self.content.iter_mut()) {
+            std::mem::swap(a, b);
+        }
+    }
     /// Swap the height data of the a chunk from the list with the height data in this object.
     /// The given list of chunks will be searched to find the chunk that matches `grid_position`.
     pub fn swap_height_from_list(&mut self, chunks: &mut [Chunk]) {
@@ -1042,4 +1058,15 @@ impl ChunkData {
             }
         }
     }
+    /// Swap the foliage density mask data of a particular foliage layer of a chunk from the list with
+    /// the data in this object. The given list of chunks will be searched to find the chunk that matches
+    /// `grid_position`.
+    pub fn swap_foliage_mask_from_list(&mut self, chunks: &mut [Chunk], layer: usize) {
+        for c in chunks {
+            if c.grid_position == self.grid_position {
+                self.swap_foliage_mask(c, layer);
+                break;
+            }
+        }
+    }
 }
@@ -0,0 +1,688 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Foliage and detail objects (grass, rocks, trees, etc.) scattered over terrains. See [`FoliageLayer`]
+//! docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, UnitQuaternion, Vector2, Vector3},
+        math::aabb::AxisAlignedBoundingBox,
+        rand::{rngs::StdRng, Rng, SeedableRng},
+        reflect::prelude::*,
+        uuid_provider,
+        visitor::prelude::*,
+    },
+    fxhash::{FxHashMap, FxHasher},
+    renderer::{
+        self,
+        bundle::{RenderContext, SurfaceInstanceData},
+        framework::ElementRange,
+    },
+    resource::texture::TextureResource,
+    scene::{
+        mesh::{extend_aabb_from_vertex_buffer, surface::Surface, RenderPath},
+        terrain::{create_layer_mask, Chunk, Terrain},
+    },
+};
+use std::{
+    cell::RefCell,
+    f32::consts::TAU,
+    fmt::{Debug, Formatter},
+    hash::Hasher,
+};
+
+/// Foliage layer defines a kind of small objects (grass, flowers, rocks, trees, etc.), that are
+/// scattered over a terrain in large quantities. Instead of creating a scene node per object, the
+/// terrain generates positions of the objects procedurally and renders them using instancing, so
+/// every surface of the layer is drawn in a few draw calls.
+///
+/// ## Placement
+///
+/// Every chunk of a terrain has a density mask per foliage layer, which could be painted in the
+/// editor exactly like layer masks (see [`crate::scene::terrain::BrushTarget::FoliageMask`]). The
+/// chunk is divided into cells of `1 / sqrt(density)` meters, and every cell could contain one
+/// object at a random position inside the cell. The probability of the object to appear is defined
+/// by the density mask. After that, the object must satisfy height and slope rules of the layer.
+/// Placement is fully deterministic - the same seed and the same masks always produce the same
+/// objects.
+///
+/// ## Fading
+///
+/// Objects that are further than [`Self::fade_end_distance`] from the observer are not rendered.
+/// Objects between [`Self::fade_start_distance`] and [`Self::fade_end_distance`] are smoothly
+/// scaled down to prevent sudden popping.
+#[derive(Debug, Clone, Visit, Reflect, PartialEq)]
+pub struct FoliageLayer {
+    /// Name of the layer. It is used only to distinguish the layers in the editor.
+    pub name: String,
+
+    /// Surfaces of every object of the layer.
+    pub surfaces: Vec<Surface>,
+
+    /// Maximum amount of objects per square meter, it is reached when the density mask is fully
+    /// opaque.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub density: f32,
+
+    /// Seed of the random generator, that is used to scatter the objects.
+    pub seed: u32,
+
+    /// Minimum random scale of an object.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub min_scale: f32,
+
+    /// Maximum random scale of an object.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub max_scale: f32,
+
+    /// If set, every object will be randomly rotated around vertical axis.
+    pub random_rotation: bool,
+
+    /// If set, every object will be aligned with the normal of the terrain surface.
+    pub align_to_normal: bool,
+
+    /// Minimum slope angle (in degrees) of the terrain surface at which an object can be placed.
+    #[reflect(min_value = 0.0, max_value = 90.0)]
+    pub min_slope: f32,
+
+    /// Maximum slope angle (in degrees) of the terrain surface at which an object can be placed.
+    #[reflect(min_value = 0.0, max_value = 90.0)]
+    pub max_slope: f32,
+
+    /// Minimum height (in local coordinates of the terrain) at which an object can be placed.
+    pub min_height: f32,
+
+    /// Maximum height (in local coordinates of the terrain) at which an object can be placed.
+    pub max_height: f32,
+
+    /// Distance from the observer at which objects start to fade out.
+    #[reflect(min_value = 0.0)]
+    pub fade_start_distance: f32,
+
+    /// Distance from the observer at which objects disappear completely.
+    #[reflect(min_value = 0.0)]
+    pub fade_end_distance: f32,
+
+    /// Whether the objects of the layer cast shadows or not.
+    pub cast_shadows: bool,
+}
+
+uuid_provider!(FoliageLayer = "1d8b732b-0ffa-419e-bcf9-03a8e7a14d90");
+
+impl Default for FoliageLayer {
+    fn default() -> Self {
+        Self {
+            name: "Foliage".to_string(),
+            surfaces: Default::default(),
+            density: 1.0,
+            seed: 0,
+            min_scale: 0.8,
+            max_scale: 1.2,
+            random_rotation: true,
+            align_to_normal: false,
+            min_slope: 0.0,
+            max_slope: 30.0,
+            min_height: -10000.0,
+            max_height: 10000.0,
+            fade_start_distance: 40.0,
+            fade_end_distance: 50.0,
+            cast_shadows: false,
+        }
+    }
+}
+
+impl FoliageLayer {
+    /// Maximum amount of placement cells along each side of a chunk. It limits the amount of
+    /// objects per chunk for extreme densities.
+    pub const MAX_CELLS_PER_CHUNK_SIDE: u32 = 512;
+
+    fn placement_hash(&self, hasher: &mut FxHasher) {
+        for value in [
+            self.density,
+            self.min_scale,
+            self.max_scale,
+            self.min_slope,
+            self.max_slope,
+            self.min_height,
+            self.max_height,
+        ] {
+            hasher.write_u32(value.to_bits());
+        }
+        hasher.write_u32(self.seed);
+        hasher.write_u8(self.random_rotation as u8);
+        hasher.write_u8(self.align_to_normal as u8);
+        for surface in self.surfaces.iter() {
+            hasher.write_u64(surface.data_ref().key());
+        }
+    }
+}
+
+/// A single object of a foliage layer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FoliageInstance {
+    /// Position of the object in local coordinates of the terrain.
+    pub position: Vector3<f32>,
+    /// Full transform of the object in local coordinates of the terrain.
+    pub transform: Matrix4<f32>,
+}
+
+#[derive(Default)]
+struct ChunkFoliage {
+    key: Option<u64>,
+    instances: Vec<FoliageInstance>,
+    /// Local bounding box of all the objects.
+    bounds: AxisAlignedBoundingBox,
+}
+
+/// Cached foliage instances of every chunk. Instances are re-generated only when anything, that
+/// affects their placement, has changed.
+#[derive(Default)]
+pub(crate) struct FoliageCache {
+    chunks: RefCell<FxHashMap<(Vector2<i32>, usize), ChunkFoliage>>,
+}
+
+impl Clone for FoliageCache {
+    fn clone(&self) -> Self {
+        // The cache will be re-created on demand.
+        Default::default()
+    }
+}
+
+impl Debug for FoliageCache {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "FoliageCache")
+    }
+}
+
+fn texture_hash(texture: &TextureResource, hasher: &mut FxHasher) {
+    hasher.write_u64(texture.key());
+    hasher.write_u64(texture.data_ref().modifications_count());
+}
+
+fn sample_bilinear(sample: impl Fn(i32, i32) -> f32, x: f32, y: f32) -> f32 {
+    let x0 = x.floor();
+    let y0 = y.floor();
+    let u = x - x0;
+    let v = y - y0;
+    let (x0, y0) = (x0 as i32, y0 as i32);
+    let top = sample(x0, y0) * (1.0 - u) + sample(x0 + 1, y0) * u;
+    let bottom = sample(x0, y0 + 1) * (1.0 - u) + sample(x0 + 1, y0 + 1) * u;
+    top * (1.0 - v) + bottom * v
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    if edge1 <= edge0 {
+        return if x < edge0 { 0.0 } else { 1.0 };
+    }
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+impl Terrain {
+    /// Sets new foliage layers of the terrain. Density masks of chunks are created or removed to
+    /// match the new amount of layers, new masks are empty.
+    pub fn set_foliage_layers(&mut self, layers: Vec<FoliageLayer>) -> Vec<FoliageLayer> {
+        let count = layers.len();
+        let mask_size = *self.mask_size;
+        for chunk in self.chunks.iter_mut() {
+            chunk.foliage_masks.truncate(count);
+            while chunk.foliage_masks.len() < count {
+                chunk
+                    .foliage_masks
+                    .push(create_layer_mask(mask_size.x, mask_size.y, 0));
+            }
+        }
+        self.foliage_layers.set_value_and_mark_modified(layers)
+    }
+
+    /// Returns a reference to a slice with foliage layers of the terrain.
+    pub fn foliage_layers(&self) -> &[FoliageLayer] {
+        &self.foliage_layers
+    }
+
+    /// Returns a mutable reference to a slice with foliage layers of the terrain.
+    pub fn foliage_layers_mut(&mut self) -> &mut [FoliageLayer] {
+        self.foliage_layers.get_value_mut_and_mark_modified()
+    }
+
+    /// Adds new foliage layer to the terrain. `masks` is a list of density masks for each chunk,
+    /// missing masks will be created empty.
+    pub fn add_foliage_layer(&mut self, layer: FoliageLayer, masks: Vec<TextureResource>) {
+        self.insert_foliage_layer(layer, masks, self.foliage_layers.len())
+    }
+
+    /// Inserts the foliage layer at the given index together with its density masks for each chunk.
+    pub fn insert_foliage_layer(
+        &mut self,
+        layer: FoliageLayer,
+        mut masks: Vec<TextureResource>,
+        index: usize,
+    ) {
+        self.foliage_layers
+            .get_value_mut_and_mark_modified()
+            .insert(index, layer);
+
+        for chunk in self.chunks.iter_mut().rev() {
+            let mask = masks
+                .pop()
+                .unwrap_or_else(|| create_layer_mask(self.mask_size.x, self.mask_size.y, 0));
+            chunk.foliage_masks.insert(index, mask);
+        }
+    }
+
+    /// Removes a foliage layer at the given index together with its respective density masks from
+    /// each chunk.
+    pub fn remove_foliage_layer(&mut self, index: usize) -> (FoliageLayer, Vec<TextureResource>) {
+        let layer = self
+            .foliage_layers
+            .get_value_mut_and_mark_modified()
+            .remove(index);
+        let masks = self
+            .chunks
+            .iter_mut()
+            .map(|chunk| chunk.foliage_masks.remove(index))
+            .collect();
+        (layer, masks)
+    }
+
+    /// Removes last foliage layer together with its respective density masks from each chunk.
+    pub fn pop_foliage_layer(&mut self) -> Option<(FoliageLayer, Vec<TextureResource>)> {
+        if self.foliage_layers.is_empty() {
+            None
+        } else {
+            Some(self.remove_foliage_layer(self.foliage_layers.len() - 1))
+        }
+    }
+
+    fn foliage_key(&self, chunk: &Chunk, layer: &FoliageLayer, layer_index: usize) -> u64 {
+        let mut hasher = FxHasher::default();
+        layer.placement_hash(&mut hasher);
+        hasher.write_u32(self.chunk_size.x.to_bits());
+        hasher.write_u32(self.chunk_size.y.to_bits());
+        if let Some(heightmap) = chunk.heightmap.as_ref() {
+            texture_hash(heightmap, &mut hasher);
+        }
+        if let Some(hole_mask) = chunk.hole_mask.as_ref() {
+            texture_hash(hole_mask, &mut hasher);
+        }
+        if let Some(mask) = chunk.foliage_masks.get(layer_index) {
+            texture_hash(mask, &mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// Generates objects of the given foliage layer for the chunk at the given grid position. The
+    /// result is deterministic, see [`FoliageLayer`] docs for more info about placement rules.
+    /// Returns an empty list if there's no such chunk or foliage layer.
+    pub fn generate_foliage(
+        &self,
+        grid_position: Vector2<i32>,
+        layer_index: usize,
+    ) -> Vec<FoliageInstance> {
+        let mut instances = Vec::new();
+
+        let (Some(chunk), Some(layer)) = (
+            self.find_chunk(grid_position),
+            self.foliage_layers.get(layer_index),
+        ) else {
+            return instances;
+        };
+        let Some(mask) = chunk.foliage_masks.get(layer_index) else {
+            return instances;
+        };
+        if layer.density <= 0.0 {
+            return instances;
+        }
+
+        let chunk_size = *self.chunk_size;
+        let cells = chunk_size.map(|s| {
+            ((s * layer.density.sqrt()).ceil() as u32)
+                .clamp(1, FoliageLayer::MAX_CELLS_PER_CHUNK_SIDE)
+        });
+        let cell_size = Vector2::new(chunk_size.x / cells.x as f32, chunk_size.y / cells.y as f32);
+
+        let mask_size = *self.mask_size;
+        let mask_data = mask.data_ref();
+        let mask_pixels = mask_data.data();
+        let mask_value = |x: i32, y: i32| {
+            let x = x.clamp(0, mask_size.x as i32 - 1) as u32;
+            let y = y.clamp(0, mask_size.y as i32 - 1) as u32;
+            mask_pixels[(y * mask_size.x + x) as usize] as f32 / 255.0
+        };
+
+        let heights = chunk.height_data();
+        let vertices = chunk.height_map_size.map(|s| s as i32 - 3);
+        let height_value = |x: i32, y: i32| {
+            heights[Vector2::new(x.clamp(-1, vertices.x + 1), y.clamp(-1, vertices.y + 1))]
+        };
+        let height_cell = Vector2::new(
+            chunk_size.x / vertices.x as f32,
+            chunk_size.y / vertices.y as f32,
+        );
+
+        let hole_size = self.hole_mask_size();
+        let hole_data = chunk.hole_mask.as_ref().map(|m| m.data_ref());
+
+        let mut hasher = FxHasher::default();
+        hasher.write_u32(layer.seed);
+        hasher.write_i32(grid_position.x);
+        hasher.write_i32(grid_position.y);
+        hasher.write_usize(layer_index);
+        let mut rng = StdRng::seed_from_u64(hasher.finish());
+
+        let (min_scale, max_scale) = (
+            layer.min_scale.min(layer.max_scale),
+            layer.min_scale.max(layer.max_scale),
+        );
+
+        for cy in 0..cells.y {
+            for cx in 0..cells.x {
+                // Always take the same amount of random numbers per cell, so changing the mask in one
+                // place does not move objects in other places.
+                let offset = Vector2::new(rng.gen::<f32>(), rng.gen::<f32>());
+                let probability = rng.gen::<f32>();
+                let scale = min_scale + (max_scale - min_scale) * rng.gen::<f32>();
+                let angle = rng.gen::<f32>() * TAU;
+
+                // Position relative to the chunk origin.
+                let p = Vector2::new(
+                    (cx as f32 + offset.x) * cell_size.x,
+                    (cy as f32 + offset.y) * cell_size.y,
+                );
+
+                let density = sample_bilinear(
+                    mask_value,
+                    p.x / chunk_size.x * mask_size.x as f32 - 0.5,
+                    p.y / chunk_size.y * mask_size.y as f32 - 0.5,
+                );
+                if probability >= density {
+                    continue;
+                }
+
+                if let Some(hole_data) = hole_data.as_ref() {
+                    let hx =
+                        ((p.x / chunk_size.x * hole_size.x as f32) as u32).min(hole_size.x - 1);
+                    let hy =
+                        ((p.y / chunk_size.y * hole_size.y as f32) as u32).min(hole_size.y - 1);
+                    if hole_data.data()[(hy * hole_size.x + hx) as usize] < 128 {
+                        continue;
+                    }
+                }
+
+                let vx = p.x / height_cell.x;
+                let vy = p.y / height_cell.y;
+                let height = sample_bilinear(height_value, vx, vy);
+                if height < layer.min_height || height > layer.max_height {
+                    continue;
+                }
+
+                let dx = (sample_bilinear(height_value, vx + 1.0, vy)
+                    - sample_bilinear(height_value, vx - 1.0, vy))
+                    / (2.0 * height_cell.x);
+                let dz = (sample_bilinear(height_value, vx, vy + 1.0)
+                    - sample_bilinear(height_value, vx, vy - 1.0))
+                    / (2.0 * height_cell.y);
+                let normal = Vector3::new(-dx, 1.0, -dz).normalize();
+                let slope = normal.y.clamp(-1.0, 1.0).acos().to_degrees();
+                if slope < layer.min_slope || slope > layer.max_slope {
+                    continue;
+                }
+
+                let position = Vector3::new(chunk.position.x + p.x, height, chunk.position.z + p.y);
+                let mut rotation = if layer.random_rotation {
+                    UnitQuaternion::from_axis_angle(&Vector3::y_axis(), angle)
+                } else {
+                    UnitQuaternion::identity()
+                };
+                if layer.align_to_normal {
+                    if let Some(alignment) =
+                        UnitQuaternion::rotation_between(&Vector3::y(), &normal)
+                    {
+                        rotation = alignment * rotation;
+                    }
+                }
+
+                instances.push(FoliageInstance {
+                    position,
+                    transform: Matrix4::new_translation(&position)
+                        * rotation.to_homogeneous()
+                        * Matrix4::new_scaling(scale),
+                });
+            }
+        }
+
+        instances
+    }
+
+    fn foliage_bounds(
+        layer: &FoliageLayer,
+        instances: &[FoliageInstance],
+    ) -> AxisAlignedBoundingBox {
+        let mut mesh_bounds = AxisAlignedBoundingBox::default();
+        for surface in layer.surfaces.iter() {
+            let data = surface.data_ref().data_ref();
+            extend_aabb_from_vertex_buffer(&data.vertex_buffer, &mut mesh_bounds);
+        }
+
+        let mut bounds = AxisAlignedBoundingBox::default();
+        for instance in instances {
+            bounds.add_box(mesh_bounds.transform(&instance.transform));
+        }
+        bounds
+    }
+
+    pub(super) fn collect_foliage_render_data(&self, ctx: &mut RenderContext) {
+        let is_shadow_pass = renderer::is_shadow_pass(ctx.render_pass_name);
+        let global_transform = self.global_transform();
+        let observer_position = ctx.observer_info.observer_position;
+        let mut cache = self.foliage_cache.chunks.borrow_mut();
+
+        for (layer_index, layer) in self.foliage_layers.iter().enumerate() {
+            if layer.surfaces.is_empty() || (is_shadow_pass && !layer.cast_shadows) {
+                continue;
+            }
+
+            for chunk in self.chunks.iter() {
+                let key = self.foliage_key(chunk, layer, layer_index);
+                let entry = cache.entry((chunk.grid_position, layer_index)).or_default();
+                if entry.key != Some(key) {
+                    entry.instances = self.generate_foliage(chunk.grid_position, layer_index);
+                    entry.bounds = Self::foliage_bounds(layer, &entry.instances);
+                    entry.key = Some(key);
+                }
+
+                if entry.instances.is_empty() {
+                    continue;
+                }
+
+                let world_bounds = entry.bounds.transform(&global_transform);
+                if ctx
+                    .frustum
+                    .is_some_and(|f| !f.is_intersects_aabb(&world_bounds))
+                {
+                    continue;
+                }
+                let closest_point = observer_position
+                    .sup(&world_bounds.min)
+                    .inf(&world_bounds.max);
+                if closest_point.metric_distance(&observer_position) >= layer.fade_end_distance {
+                    continue;
+                }
+
+                for instance in entry.instances.iter() {
+                    let position = global_transform
+                        .transform_point(&Point3::from(instance.position))
+                        .coords;
+                    let distance = position.metric_distance(&observer_position);
+                    let fade = 1.0
+                        - smoothstep(layer.fade_start_distance, layer.fade_end_distance, distance);
+                    if fade <= 0.0 {
+                        continue;
+                    }
+
+                    let world_transform =
+                        global_transform * instance.transform * Matrix4::new_scaling(fade);
+
+                    for surface in layer.surfaces.iter() {
+                        ctx.storage.push(
+                            surface.data_ref(),
+                            surface.material(),
+                            RenderPath::Deferred,
+                            surface.material().key(),
+                            SurfaceInstanceData {
+                                world_transform,
+                                bone_matrices: Default::default(),
+                                blend_shapes_weights: Default::default(),
+                                element_range: ElementRange::Full,
+                                node_handle: self.handle(),
+                            },
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector2,
+        scene::{
+            base::BaseBuilder,
+            terrain::{foliage::FoliageLayer, io::TerrainImage, Terrain, TerrainBuilder},
+        },
+    };
+
+    fn make_terrain(layer: FoliageLayer) -> Terrain {
+        let node = TerrainBuilder::new(BaseBuilder::new())
+            .with_chunk_size(Vector2::new(16.0, 16.0))
+            .with_width_chunks(0..2)
+            .with_length_chunks(0..2)
+            .with_height_map_size(Vector2::new(11, 11))
+            .with_mask_size(Vector2::new(8, 8))
+            .with_foliage_layers(vec![layer])
+            .build_node();
+        node.cast::<Terrain>().unwrap().clone()
+    }
+
+    fn fill_foliage_mask(terrain: &mut Terrain, value: u8) {
+        for chunk in terrain.chunks_mut() {
+            let mut data = chunk.foliage_masks[0].data_ref();
+            let mut modifier = data.modify();
+            modifier
+                .data_mut_of_type::<u8>()
+                .unwrap()
+                .iter_mut()
+                .for_each(|v| *v = value);
+        }
+    }
+
+    fn instance_count(terrain: &Terrain) -> usize {
+        terrain
+            .chunks_ref()
+            .iter()
+            .map(|c| terrain.generate_foliage(c.grid_position(), 0).len())
+            .sum()
+    }
+
+    #[test]
+    fn test_foliage_generation_is_deterministic() {
+        let mut terrain = make_terrain(FoliageLayer {
+            density: 2.0,
+            ..Default::default()
+        });
+        assert_eq!(instance_count(&terrain), 0);
+
+        fill_foliage_mask(&mut terrain, 255);
+        let a = terrain.generate_foliage(Vector2::new(1, 1), 0);
+        let b = terrain.generate_foliage(Vector2::new(1, 1), 0);
+        assert_eq!(a, b);
+        // Fully opaque mask fills every cell of the chunk.
+        assert_eq!(a.len(), 23 * 23);
+        for instance in a.iter() {
+            assert!((16.0..=32.0).contains(&instance.position.x));
+            assert!((16.0..=32.0).contains(&instance.position.z));
+        }
+        assert_ne!(a, terrain.generate_foliage(Vector2::new(0, 1), 0));
+
+        fill_foliage_mask(&mut terrain, 128);
+        let half = instance_count(&terrain);
+        assert!(half > 0 && half < 4 * 23 * 23);
+    }
+
+    #[test]
+    fn test_foliage_placement_rules() {
+        let mut terrain = make_terrain(FoliageLayer {
+            min_height: 1.0,
+            ..Default::default()
+        });
+        fill_foliage_mask(&mut terrain, 255);
+        // The terrain is flat at zero height.
+        assert_eq!(instance_count(&terrain), 0);
+
+        terrain.foliage_layers_mut()[0].min_height = -1.0;
+        assert_eq!(instance_count(&terrain), 4 * 16 * 16);
+
+        // Steep ramp along X axis, ~63 degrees.
+        let image = TerrainImage {
+            width: 2,
+            height: 2,
+            pixels: vec![0.0, 1.0, 0.0, 1.0],
+        };
+        terrain.set_height_map_image(&image, 0.0..64.0);
+        assert_eq!(instance_count(&terrain), 0);
+
+        terrain.foliage_layers_mut()[0].min_slope = 45.0;
+        terrain.foliage_layers_mut()[0].max_slope = 90.0;
+        terrain.foliage_layers_mut()[0].max_height = 100.0;
+        assert_eq!(instance_count(&terrain), 4 * 16 * 16);
+    }
+
+    #[test]
+    fn test_add_remove_foliage_layers() {
+        let mut terrain = make_terrain(FoliageLayer::default());
+        terrain.add_foliage_layer(
+            FoliageLayer {
+                name: "Rocks".to_string(),
+                ..Default::default()
+            },
+            Vec::new(),
+        );
+        assert_eq!(terrain.foliage_layers().len(), 2);
+        assert!(terrain
+            .chunks_ref()
+            .iter()
+            .all(|c| c.foliage_masks.len() == 2));
+
+        let (layer, masks) = terrain.remove_foliage_layer(0);
+        assert_eq!(layer.name, "Foliage");
+        assert_eq!(masks.len(), 4);
+        assert_eq!(terrain.foliage_layers()[0].name, "Rocks");
+
+        terrain.set_foliage_layers(Vec::new());
+        assert!(terrain
+            .chunks_ref()
+            .iter()
+            .all(|c| c.foliage_masks.is_empty()));
+        assert!(terrain.pop_foliage_layer().is_none());
+    }
+}
@@ -0,0 +1,738 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Terrain-wide height generators and simulation passes. See [`TerrainGenerator`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::Vector2,
+        rand::{rngs::StdRng, Rng, SeedableRng},
+        reflect::prelude::*,
+        uuid_provider,
+    },
+    scene::terrain::Terrain,
+};
+use std::f32::consts::{SQRT_2, TAU};
+
+/// Kind of a noise, that is used by [`NoiseGenerator`].
+#[derive(Copy, Clone, Default, PartialEq, Eq, Reflect, Debug)]
+pub enum NoiseKind {
+    /// Fractal Brownian motion - a sum of a few octaves of gradient noise. Produces smooth rolling
+    /// hills. Values are in `[-1; 1]` range.
+    #[default]
+    Fbm,
+    /// Ridged multi-fractal noise. Produces sharp mountain ridges. Values are in `[0; 1]` range.
+    Ridged,
+}
+
+uuid_provider!(NoiseKind = "48ff3b6d-0784-4515-bc63-8998a1bb8a3f");
+
+/// Fills the height map of a terrain with fractal noise.
+#[derive(Clone, PartialEq, Reflect, Debug)]
+pub struct NoiseGenerator {
+    /// Kind of the noise.
+    pub kind: NoiseKind,
+    /// Seed of the noise. The same seed always produces the same terrain.
+    pub seed: u32,
+    /// Frequency of the first octave, in cycles per meter.
+    #[reflect(min_value = 0.0, step = 0.001)]
+    pub frequency: f32,
+    /// Amount of octaves of the noise. More octaves add more fine details.
+    #[reflect(min_value = 1.0, max_value = 16.0)]
+    pub octaves: u32,
+    /// Frequency multiplier for each next octave.
+    #[reflect(min_value = 1.0, step = 0.1)]
+    pub lacunarity: f32,
+    /// Amplitude multiplier for each next octave.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub gain: f32,
+    /// Height (in meters) of the resulting noise.
+    #[reflect(step = 0.1)]
+    pub amplitude: f32,
+    /// Height (in meters) that will be added to every sample of the noise.
+    #[reflect(step = 0.1)]
+    pub offset: f32,
+    /// If set, the noise will be added to existing heights instead of replacing them.
+    pub additive: bool,
+}
+
+uuid_provider!(NoiseGenerator = "37a3c810-3061-4e28-affb-4d400ad19e57");
+
+impl Default for NoiseGenerator {
+    fn default() -> Self {
+        Self {
+            kind: Default::default(),
+            seed: 0,
+            frequency: 0.02,
+            octaves: 6,
+            lacunarity: 2.0,
+            gain: 0.5,
+            amplitude: 10.0,
+            offset: 0.0,
+            additive: false,
+        }
+    }
+}
+
+fn hash(x: i32, y: i32, seed: u32) -> u32 {
+    let mut h = seed.wrapping_mul(0x9E37_79B9)
+        ^ (x as u32).wrapping_mul(0x85EB_CA6B)
+        ^ (y as u32).wrapping_mul(0xC2B2_AE35);
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7FEB_352D);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846C_A68B);
+    h ^ (h >> 16)
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// Classic 2D gradient noise with random unit gradients in lattice points. Output is in `[-1; 1]` range.
+fn gradient_noise(position: Vector2<f32>, seed: u32) -> f32 {
+    let x0 = position.x.floor();
+    let y0 = position.y.floor();
+    let (ix, iy) = (x0 as i32, y0 as i32);
+    let (fx, fy) = (position.x - x0, position.y - y0);
+
+    let dot = |gx: i32, gy: i32, dx: f32, dy: f32| {
+        let angle = hash(ix + gx, iy + gy, seed) as f32 * (TAU / u32::MAX as f32);
+        angle.cos() * dx + angle.sin() * dy
+    };
+
+    let n00 = dot(0, 0, fx, fy);
+    let n10 = dot(1, 0, fx - 1.0, fy);
+    let n01 = dot(0, 1, fx, fy - 1.0);
+    let n11 = dot(1, 1, fx - 1.0, fy - 1.0);
+
+    let u = fade(fx);
+    let v = fade(fy);
+
+    (lerp(lerp(n00, n10, u), lerp(n01, n11, u), v) * SQRT_2).clamp(-1.0, 1.0)
+}
+
+impl NoiseGenerator {
+    /// Samples the noise at the given position (in local coordinates of a terrain). The result is
+    /// normalized, see [`NoiseKind`] docs for value ranges. [`Self::amplitude`] and [`Self::offset`]
+    /// are not applied.
+    pub fn sample(&self, position: Vector2<f32>) -> f32 {
+        let mut frequency = self.frequency;
+        let mut amplitude = 1.0;
+        let mut weight = 1.0;
+        let mut sum = 0.0;
+        let mut total_amplitude = 0.0;
+
+        for octave in 0..self.octaves.max(1) {
+            let noise = gradient_noise(position * frequency, self.seed.wrapping_add(octave));
+
+            match self.kind {
+                NoiseKind::Fbm => sum += noise * amplitude,
+                NoiseKind::Ridged => {
+                    let ridge = 1.0 - noise.abs();
+                    let ridge = ridge * ridge * weight;
+                    // Ridges of higher octaves are visible only on the ridges of previous octaves.
+                    weight = ridge.clamp(0.0, 1.0);
+                    sum += ridge * amplitude;
+                }
+            }
+
+            total_amplitude += amplitude;
+            amplitude *= self.gain;
+            frequency *= self.lacunarity;
+        }
+
+        if total_amplitude > 0.0 {
+            sum / total_amplitude
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns height of the terrain at the given position (in local coordinates of a terrain).
+    pub fn height_at(&self, position: Vector2<f32>) -> f32 {
+        self.offset + self.amplitude * self.sample(position)
+    }
+}
+
+/// Thermal erosion moves material from steep slopes down to their feet, until every slope becomes
+/// less steep than the talus angle. It simulates crumbling of rocks and produces scree slopes.
+#[derive(Clone, PartialEq, Reflect, Debug)]
+pub struct ThermalErosion {
+    /// Amount of simulation steps.
+    #[reflect(min_value = 0.0)]
+    pub iterations: u32,
+    /// Tangent of the talus angle - the maximum slope (height difference per meter) that is
+    /// considered stable.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub talus: f32,
+    /// Fraction of the unstable material that is moved on each step.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub strength: f32,
+}
+
+uuid_provider!(ThermalErosion = "3b69e777-a1d6-489a-ba36-49f204bc73da");
+
+impl Default for ThermalErosion {
+    fn default() -> Self {
+        Self {
+            iterations: 50,
+            talus: 0.7,
+            strength: 0.5,
+        }
+    }
+}
+
+/// Hydraulic erosion simulates water droplets, that run down the slopes of a terrain. Each droplet
+/// picks up sediment when it accelerates and deposits it when it slows down or evaporates. It carves
+/// valleys and gullies and produces smooth deposits at the bottom of slopes.
+#[derive(Clone, PartialEq, Reflect, Debug)]
+pub struct HydraulicErosion {
+    /// Seed of the random generator, that is used to spawn droplets.
+    pub seed: u32,
+    /// Total amount of droplets.
+    #[reflect(min_value = 0.0)]
+    pub droplets: u32,
+    /// Maximum amount of steps of each droplet.
+    #[reflect(min_value = 1.0)]
+    pub max_lifetime: u32,
+    /// Defines how much a droplet keeps its direction, instead of following the slope.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub inertia: f32,
+    /// Multiplier for the amount of sediment, that a droplet can carry.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub sediment_capacity: f32,
+    /// Minimum amount of sediment, that a droplet can carry even on flat surfaces.
+    #[reflect(min_value = 0.0, step = 0.001)]
+    pub min_sediment_capacity: f32,
+    /// Fraction of the free capacity of a droplet, that is eroded on each step.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub erode_speed: f32,
+    /// Fraction of the excess sediment of a droplet, that is deposited on each step.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub deposit_speed: f32,
+    /// Fraction of the water of a droplet, that evaporates on each step.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub evaporate_speed: f32,
+    /// Defines how fast droplets accelerate on slopes.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub gravity: f32,
+}
+
+uuid_provider!(HydraulicErosion = "1dfbab0a-6fe6-4ef9-b6d0-17b88a9a2f13");
+
+impl Default for HydraulicErosion {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            droplets: 50_000,
+            max_lifetime: 30,
+            inertia: 0.05,
+            sediment_capacity: 4.0,
+            min_sediment_capacity: 0.01,
+            erode_speed: 0.3,
+            deposit_speed: 0.3,
+            evaporate_speed: 0.01,
+            gravity: 4.0,
+        }
+    }
+}
+
+/// A generator or a simulation pass, that modifies height maps of all chunks of a terrain at once.
+/// Use [`Self::apply`] to run it.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::scene::terrain::{
+/// #     generator::{HydraulicErosion, NoiseGenerator, NoiseKind, TerrainGenerator},
+/// #     Terrain,
+/// # };
+/// fn make_mountains(terrain: &mut Terrain) {
+///     TerrainGenerator::Noise(NoiseGenerator {
+///         kind: NoiseKind::Ridged,
+///         seed: 42,
+///         amplitude: 50.0,
+///         ..Default::default()
+///     })
+///     .apply(terrain);
+///
+///     TerrainGenerator::HydraulicErosion(HydraulicErosion::default()).apply(terrain);
+/// }
+/// ```
+#[derive(Clone, PartialEq, Reflect, Debug)]
+pub enum TerrainGenerator {
+    /// See [`NoiseGenerator`].
+    Noise(NoiseGenerator),
+    /// See [`ThermalErosion`].
+    ThermalErosion(ThermalErosion),
+    /// See [`HydraulicErosion`].
+    HydraulicErosion(HydraulicErosion),
+}
+
+uuid_provider!(TerrainGenerator = "2ad42e32-d95c-430b-b271-d15ed75bdda9");
+
+impl Default for TerrainGenerator {
+    fn default() -> Self {
+        Self::Noise(Default::default())
+    }
+}
+
+impl TerrainGenerator {
+    /// Modifies height maps of every chunk of the given terrain.
+    pub fn apply(&self, terrain: &mut Terrain) {
+        match self {
+            TerrainGenerator::Noise(noise) => terrain.generate_noise(noise),
+            TerrainGenerator::ThermalErosion(erosion) => terrain.apply_thermal_erosion(erosion),
+            TerrainGenerator::HydraulicErosion(erosion) => terrain.apply_hydraulic_erosion(erosion),
+        }
+    }
+}
+
+/// Heights of every vertex of a terrain, stored in a single grid without duplicated chunk edges.
+pub(super) struct HeightGrid {
+    /// Position of the first vertex of the grid in height pixel coordinates.
+    pub(super) origin: Vector2<i32>,
+    pub(super) width: usize,
+    pub(super) length: usize,
+    pub(super) heights: Vec<f32>,
+}
+
+impl HeightGrid {
+    pub(super) fn from_terrain(terrain: &Terrain) -> Self {
+        let cells = terrain.height_map_size().map(|s| s as i32 - 3);
+        let width_chunks = terrain.width_chunks();
+        let length_chunks = terrain.length_chunks();
+        let origin = Vector2::new(width_chunks.start * cells.x, length_chunks.start * cells.y);
+        let width = (width_chunks.len() as i32 * cells.x + 1) as usize;
+        let length = (length_chunks.len() as i32 * cells.y + 1) as usize;
+
+        let mut heights = vec![0.0; width * length];
+        for chunk in terrain.chunks_ref() {
+            let data = chunk.height_data();
+            let chunk_origin = chunk.grid_position().component_mul(&cells) - origin;
+            for y in 0..=cells.y {
+                for x in 0..=cells.x {
+                    let gx = (chunk_origin.x + x) as usize;
+                    let gy = (chunk_origin.y + y) as usize;
+                    if gx < width && gy < length {
+                        heights[gy * width + gx] = data[Vector2::new(x, y)];
+                    }
+                }
+            }
+        }
+
+        Self {
+            origin,
+            width,
+            length,
+            heights,
+        }
+    }
+
+    pub(super) fn write_to_terrain(&self, terrain: &mut Terrain) {
+        if self.heights.is_empty() {
+            return;
+        }
+        let scale = terrain.height_grid_scale();
+        terrain.for_each_height_map_pixel(|height, position| {
+            // Margin pixels are outside of the grid at the borders of the terrain, clamping gives
+            // them the height of the closest edge.
+            let x = ((position.x / scale.x).round() as i32 - self.origin.x)
+                .clamp(0, self.width as i32 - 1) as usize;
+            let y = ((position.y / scale.y).round() as i32 - self.origin.y)
+                .clamp(0, self.length as i32 - 1) as usize;
+            *height = self.heights[y * self.width + x];
+        });
+    }
+
+    pub(super) fn index(&self, x: usize, y: usize) -> usize {
+        y * self.width + x
+    }
+
+    /// Bilinearly interpolated height and its gradient at the given position in cells. The position
+    /// must be inside the grid.
+    fn height_and_gradient(&self, position: Vector2<f32>) -> (f32, Vector2<f32>) {
+        let x = position.x as usize;
+        let y = position.y as usize;
+        let u = position.x - x as f32;
+        let v = position.y - y as f32;
+
+        let h00 = self.heights[self.index(x, y)];
+        let h10 = self.heights[self.index(x + 1, y)];
+        let h01 = self.heights[self.index(x, y + 1)];
+        let h11 = self.heights[self.index(x + 1, y + 1)];
+
+        let gradient = Vector2::new(
+            (h10 - h00) * (1.0 - v) + (h11 - h01) * v,
+            (h01 - h00) * (1.0 - u) + (h11 - h10) * u,
+        );
+        let height =
+            h00 * (1.0 - u) * (1.0 - v) + h10 * u * (1.0 - v) + h01 * (1.0 - u) * v + h11 * u * v;
+
+        (height, gradient)
+    }
+
+    /// Adds the given amount to four vertices around the given position, proportionally to their
+    /// bilinear weights.
+    fn add_bilinear(&mut self, position: Vector2<f32>, amount: f32) {
+        let x = position.x as usize;
+        let y = position.y as usize;
+        let u = position.x - x as f32;
+        let v = position.y - y as f32;
+
+        let i00 = self.index(x, y);
+        let i10 = self.index(x + 1, y);
+        let i01 = self.index(x, y + 1);
+        let i11 = self.index(x + 1, y + 1);
+        self.heights[i00] += amount * (1.0 - u) * (1.0 - v);
+        self.heights[i10] += amount * u * (1.0 - v);
+        self.heights[i01] += amount * (1.0 - u) * v;
+        self.heights[i11] += amount * u * v;
+    }
+
+    fn erode_thermal(&mut self, erosion: &ThermalErosion, cell_size: Vector2<f32>) {
+        let neighbours = [
+            (-1, 0, cell_size.x),
+            (1, 0, cell_size.x),
+            (0, -1, cell_size.y),
+            (0, 1, cell_size.y),
+        ];
+
+        let mut delta = vec![0.0; self.heights.len()];
+        for _ in 0..erosion.iterations {
+            delta.iter_mut().for_each(|d| *d = 0.0);
+
+            for y in 0..self.length {
+                for x in 0..self.width {
+                    let i = self.index(x, y);
+                    let height = self.heights[i];
+
+                    let mut lower = [(0, 0.0); 4];
+                    let mut lower_count = 0;
+                    let mut total_difference = 0.0;
+                    let mut max_excess = 0.0f32;
+                    for (dx, dy, distance) in neighbours {
+                        let nx = x as i32 + dx;
+                        let ny = y as i32 + dy;
+                        if nx < 0 || ny < 0 || nx >= self.width as i32 || ny >= self.length as i32 {
+                            continue;
+                        }
+                        let j = self.index(nx as usize, ny as usize);
+                        let difference = height - self.heights[j];
+                        let excess = difference - erosion.talus * distance;
+                        if excess > 0.0 {
+                            lower[lower_count] = (j, difference);
+                            lower_count += 1;
+                            total_difference += difference;
+                            max_excess = max_excess.max(excess);
+                        }
+                    }
+
+                    if lower_count > 0 {
+                        // Half of the excess makes both heights equally unstable, so the material
+                        // never flows back and forth between two vertices.
+                        let amount = erosion.strength * max_excess * 0.5;
+                        delta[i] -= amount;
+                        for &(j, difference) in &lower[..lower_count] {
+                            delta[j] += amount * difference / total_difference;
+                        }
+                    }
+                }
+            }
+
+            for (height, delta) in self.heights.iter_mut().zip(delta.iter()) {
+                *height += *delta;
+            }
+        }
+    }
+
+    fn erode_hydraulic(&mut self, erosion: &HydraulicErosion) {
+        if self.width < 2 || self.length < 2 {
+            return;
+        }
+
+        let max = Vector2::new((self.width - 1) as f32, (self.length - 1) as f32);
+        let mut rng = StdRng::seed_from_u64(erosion.seed as u64);
+
+        for _ in 0..erosion.droplets {
+            let mut position = Vector2::new(rng.gen_range(0.0..max.x), rng.gen_range(0.0..max.y));
+            let mut direction = Vector2::<f32>::zeros();
+            let mut speed = 1.0f32;
+            let mut water = 1.0f32;
+            let mut sediment = 0.0f32;
+
+            for _ in 0..erosion.max_lifetime {
+                let (height, gradient) = self.height_and_gradient(position);
+
+                direction = direction * erosion.inertia - gradient * (1.0 - erosion.inertia);
+                let Some(normalized) = direction.try_normalize(f32::EPSILON) else {
+                    // Flat surface, the droplet has nowhere to go.
+                    self.add_bilinear(position, sediment);
+                    break;
+                };
+                direction = normalized;
+
+                let new_position = position + direction;
+                if new_position.x < 0.0
+                    || new_position.y < 0.0
+                    || new_position.x >= max.x
+                    || new_position.y >= max.y
+                {
+                    break;
+                }
+
+                let delta_height = self.height_and_gradient(new_position).0 - height;
+                let capacity = (-delta_height * speed * water * erosion.sediment_capacity)
+                    .max(erosion.min_sediment_capacity);
+
+                if sediment > capacity || delta_height > 0.0 {
+                    // Going uphill fills the pit behind the droplet, otherwise the droplet drops the
+                    // excess sediment.
+                    let amount = if delta_height > 0.0 {
+                        delta_height.min(sediment)
+                    } else {
+                        (sediment - capacity) * erosion.deposit_speed
+                    };
+                    sediment -= amount;
+                    self.add_bilinear(position, amount);
+                } else {
+                    // Never erode deeper than the height difference, otherwise the droplet digs holes.
+                    let amount = ((capacity - sediment) * erosion.erode_speed).min(-delta_height);
+                    sediment += amount;
+                    self.add_bilinear(position, -amount);
+                }
+
+                speed = (speed * speed - delta_height * erosion.gravity)
+                    .max(0.0)
+                    .sqrt();
+                water *= 1.0 - erosion.evaporate_speed;
+                position = new_position;
+            }
+        }
+    }
+}
+
+impl Terrain {
+    /// Makes chunk edges and margins consistent after all height maps were modified at once and
+    /// rebuilds quad trees of all chunks.
+    pub(super) fn finish_height_map_generation(&mut self) {
+        let grid_positions = self
+            .chunks_ref()
+            .iter()
+            .map(|c| c.grid_position())
+            .collect::<Vec<_>>();
+        for grid_position in grid_positions.iter() {
+            self.align_chunk_edges(*grid_position);
+        }
+        for grid_position in grid_positions.iter() {
+            self.align_chunk_margins(*grid_position);
+        }
+        self.update_quad_trees();
+    }
+
+    /// Fills height maps of every chunk using the given noise generator.
+    pub fn generate_noise(&mut self, noise: &NoiseGenerator) {
+        self.for_each_height_map_pixel(|height, position| {
+            let value = noise.height_at(position);
+            if noise.additive {
+                *height += value;
+            } else {
+                *height = value;
+            }
+        });
+        self.finish_height_map_generation();
+    }
+
+    /// Runs thermal erosion simulation over the entire terrain.
+    pub fn apply_thermal_erosion(&mut self, erosion: &ThermalErosion) {
+        let mut grid = HeightGrid::from_terrain(self);
+        grid.erode_thermal(erosion, self.height_grid_scale());
+        grid.write_to_terrain(self);
+        self.finish_height_map_generation();
+    }
+
+    /// Runs hydraulic erosion simulation over the entire terrain.
+    pub fn apply_hydraulic_erosion(&mut self, erosion: &HydraulicErosion) {
+        let mut grid = HeightGrid::from_terrain(self);
+        grid.erode_hydraulic(erosion);
+        grid.write_to_terrain(self);
+        self.finish_height_map_generation();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector2,
+        scene::{
+            base::BaseBuilder,
+            terrain::{
+                generator::{
+                    HeightGrid, HydraulicErosion, NoiseGenerator, NoiseKind, TerrainGenerator,
+                    ThermalErosion,
+                },
+                Terrain, TerrainBuilder,
+            },
+        },
+    };
+
+    fn make_terrain() -> Terrain {
+        let node = TerrainBuilder::new(BaseBuilder::new())
+            .with_chunk_size(Vector2::new(16.0, 16.0))
+            .with_width_chunks(-1..1)
+            .with_length_chunks(0..2)
+            .with_height_map_size(Vector2::new(19, 19))
+            .build_node();
+        node.cast::<Terrain>().unwrap().clone()
+    }
+
+    fn max_slope(grid: &HeightGrid) -> f32 {
+        let mut max = 0.0f32;
+        for y in 0..grid.length {
+            for x in 0..grid.width - 1 {
+                let i = grid.index(x, y);
+                max = max.max((grid.heights[i] - grid.heights[i + 1]).abs());
+            }
+        }
+        max
+    }
+
+    fn assert_edges_match(terrain: &Terrain) {
+        let size = terrain.height_map_size().map(|s| s as i32 - 3);
+        for chunk in terrain.chunks_ref() {
+            let right = chunk.grid_position() + Vector2::new(1, 0);
+            if let Some(other) = terrain.find_chunk(right) {
+                let a = chunk.height_data();
+                let b = other.height_data();
+                for y in 0..=size.y {
+                    assert_eq!(a[Vector2::new(size.x, y)], b[Vector2::new(0, y)]);
+                    assert_eq!(a[Vector2::new(size.x + 1, y)], b[Vector2::new(1, y)]);
+                    assert_eq!(a[Vector2::new(size.x - 1, y)], b[Vector2::new(-1, y)]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_noise_is_deterministic() {
+        let noise = NoiseGenerator {
+            seed: 123,
+            frequency: 0.1,
+            ..Default::default()
+        };
+        let other = NoiseGenerator {
+            seed: 124,
+            ..noise.clone()
+        };
+
+        let mut same = true;
+        for i in 0..64 {
+            let position = Vector2::new(i as f32 * 0.37, i as f32 * 1.13);
+            let value = noise.sample(position);
+            assert_eq!(value, noise.sample(position));
+            assert!((-1.0..=1.0).contains(&value));
+            same &= value == other.sample(position);
+        }
+        assert!(!same);
+    }
+
+    #[test]
+    fn test_ridged_noise_range() {
+        let noise = NoiseGenerator {
+            kind: NoiseKind::Ridged,
+            frequency: 0.2,
+            ..Default::default()
+        };
+        for i in 0..256 {
+            let value = noise.sample(Vector2::new(i as f32 * 0.71, i as f32 * -0.29));
+            assert!((0.0..=1.0).contains(&value));
+        }
+    }
+
+    #[test]
+    fn test_noise_generation_aligns_chunks() {
+        let mut terrain = make_terrain();
+        TerrainGenerator::Noise(NoiseGenerator {
+            frequency: 0.1,
+            ..Default::default()
+        })
+        .apply(&mut terrain);
+
+        let grid = HeightGrid::from_terrain(&terrain);
+        assert_eq!(grid.width, 33);
+        assert_eq!(grid.length, 33);
+        assert!(grid.heights.iter().any(|h| *h != 0.0));
+        assert_edges_match(&terrain);
+    }
+
+    #[test]
+    fn test_thermal_erosion_reduces_slopes() {
+        let mut terrain = make_terrain();
+        terrain.generate_noise(&NoiseGenerator {
+            frequency: 0.15,
+            amplitude: 20.0,
+            ..Default::default()
+        });
+
+        let before = HeightGrid::from_terrain(&terrain);
+        terrain.apply_thermal_erosion(&ThermalErosion {
+            iterations: 100,
+            ..Default::default()
+        });
+        let after = HeightGrid::from_terrain(&terrain);
+
+        assert!(max_slope(&after) < max_slope(&before));
+        // Thermal erosion only moves the material.
+        let sum_before = before.heights.iter().sum::<f32>();
+        let sum_after = after.heights.iter().sum::<f32>();
+        assert!((sum_before - sum_after).abs() < 0.01 * sum_before.abs().max(1.0));
+        assert_edges_match(&terrain);
+    }
+
+    #[test]
+    fn test_hydraulic_erosion() {
+        let mut terrain = make_terrain();
+        terrain.generate_noise(&NoiseGenerator {
+            frequency: 0.1,
+            amplitude: 10.0,
+            ..Default::default()
+        });
+        let mut other = terrain.clone();
+
+        let erosion = HydraulicErosion {
+            droplets: 2000,
+            ..Default::default()
+        };
+        let before = HeightGrid::from_terrain(&terrain);
+        terrain.apply_hydraulic_erosion(&erosion);
+        other.apply_hydraulic_erosion(&erosion);
+        let after = HeightGrid::from_terrain(&terrain);
+
+        assert!(after.heights.iter().all(|h| h.is_finite()));
+        assert_ne!(before.heights, after.heights);
+        assert_eq!(after.heights, HeightGrid::from_terrain(&other).heights);
+        assert_edges_match(&terrain);
+    }
+}
@@ -0,0 +1,616 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Import and export of terrain height maps and layer masks. It allows to exchange terrain data with
+//! external terrain generators (World Machine, Gaea, etc.). See [`TerrainImage`] docs for more info.
+
+use crate::{
+    core::algebra::Vector2,
+    scene::terrain::{generator::HeightGrid, Terrain},
+};
+use image::{DynamicImage, ImageBuffer, ImageError, ImageFormat, Luma, Rgb, Rgba, RgbaImage};
+use std::{
+    fmt::{Display, Formatter},
+    io::Cursor,
+    ops::Range,
+    path::Path,
+};
+
+/// An error that may occur during import or export of terrain data.
+#[derive(Debug)]
+pub enum TerrainImageError {
+    /// An i/o error has occurred.
+    Io(std::io::Error),
+    /// Unable to decode or encode an image.
+    Image(ImageError),
+    /// Size of a RAW file (in bytes) does not correspond to a square image of 16-bit pixels.
+    InvalidRawSize(usize),
+    /// The file has an extension, that does not correspond to any of supported formats.
+    UnsupportedFormat(String),
+    /// The terrain does not have a layer with the given index.
+    NoSuchLayer(usize),
+}
+
+impl Display for TerrainImageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(v) => {
+                write!(f, "An i/o error has occurred {v:?}")
+            }
+            Self::Image(v) => {
+                write!(f, "Image error: {v}")
+            }
+            Self::InvalidRawSize(v) => {
+                write!(
+                    f,
+                    "{v} bytes does not correspond to any square image of 16-bit pixels"
+                )
+            }
+            Self::UnsupportedFormat(v) => {
+                write!(f, "Unsupported terrain image format: {v}")
+            }
+            Self::NoSuchLayer(v) => {
+                write!(f, "There is no layer with index {v}")
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for TerrainImageError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<ImageError> for TerrainImageError {
+    fn from(e: ImageError) -> Self {
+        Self::Image(e)
+    }
+}
+
+/// A format of a file with terrain data.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TerrainImageFormat {
+    /// Headerless square array of little-endian 16-bit unsigned integers. It is the default export
+    /// format of most terrain generators. Usually it has `.raw` or `.r16` extension.
+    Raw16,
+    /// 16-bit grayscale PNG image. 8-bit images can be imported as well.
+    Png16,
+    /// OpenEXR image with 32-bit floating point pixels. Values are not clamped to `[0; 1]` range.
+    Exr,
+}
+
+impl TerrainImageFormat {
+    /// Tries to find the format by the extension of the given path.
+    pub fn from_path(path: &Path) -> Result<Self, TerrainImageError> {
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "raw" | "r16" => Ok(Self::Raw16),
+            "png" => Ok(Self::Png16),
+            "exr" => Ok(Self::Exr),
+            _ => Err(TerrainImageError::UnsupportedFormat(extension)),
+        }
+    }
+}
+
+/// A single-channel image with normalized values, that is used to exchange height maps and layer
+/// masks with external tools. Row `y` of the image corresponds to the `y` coordinate of the height
+/// (or mask) grid of a terrain, so the first row is the row with the smallest local `y`.
+///
+/// Images of any size could be imported into a terrain - they will be resampled to fit all chunks
+/// of the terrain.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TerrainImage {
+    /// Width of the image in pixels.
+    pub width: u32,
+    /// Height of the image in pixels.
+    pub height: u32,
+    /// Pixels of the image, row by row. Values are normally in `[0; 1]` range.
+    pub pixels: Vec<f32>,
+}
+
+impl TerrainImage {
+    /// Decodes an image in the given format.
+    pub fn decode(bytes: &[u8], format: TerrainImageFormat) -> Result<Self, TerrainImageError> {
+        match format {
+            TerrainImageFormat::Raw16 => {
+                let count = bytes.len() / 2;
+                let side = (count as f64).sqrt() as usize;
+                if bytes.len() % 2 != 0 || side * side != count {
+                    return Err(TerrainImageError::InvalidRawSize(bytes.len()));
+                }
+                Ok(Self {
+                    width: side as u32,
+                    height: side as u32,
+                    pixels: bytes
+                        .chunks_exact(2)
+                        .map(|p| u16::from_le_bytes([p[0], p[1]]) as f32 / u16::MAX as f32)
+                        .collect(),
+                })
+            }
+            TerrainImageFormat::Png16 => {
+                let image = image::load_from_memory_with_format(bytes, ImageFormat::Png)?;
+                let image = image.to_luma16();
+                Ok(Self {
+                    width: image.width(),
+                    height: image.height(),
+                    pixels: image
+                        .pixels()
+                        .map(|p| p.0[0] as f32 / u16::MAX as f32)
+                        .collect(),
+                })
+            }
+            TerrainImageFormat::Exr => {
+                let image = image::load_from_memory_with_format(bytes, ImageFormat::OpenExr)?;
+                let image = image.to_luma32f();
+                Ok(Self {
+                    width: image.width(),
+                    height: image.height(),
+                    pixels: image.into_raw(),
+                })
+            }
+        }
+    }
+
+    /// Encodes the image in the given format. Integer formats clamp values to `[0; 1]` range.
+    pub fn encode(&self, format: TerrainImageFormat) -> Result<Vec<u8>, TerrainImageError> {
+        let to_u16 = |v: f32| (v.clamp(0.0, 1.0) * u16::MAX as f32).round() as u16;
+        match format {
+            TerrainImageFormat::Raw16 => Ok(self
+                .pixels
+                .iter()
+                .flat_map(|v| to_u16(*v).to_le_bytes())
+                .collect()),
+            TerrainImageFormat::Png16 => {
+                let image =
+                    ImageBuffer::<Luma<u16>, _>::from_fn(self.width, self.height, |x, y| {
+                        Luma([to_u16(self.pixel(x, y))])
+                    });
+                let mut bytes = Cursor::new(Vec::new());
+                DynamicImage::ImageLuma16(image).write_to(&mut bytes, ImageFormat::Png)?;
+                Ok(bytes.into_inner())
+            }
+            TerrainImageFormat::Exr => {
+                // OpenEXR encoder does not support single-channel images.
+                let image = ImageBuffer::<Rgb<f32>, _>::from_fn(self.width, self.height, |x, y| {
+                    let v = self.pixel(x, y);
+                    Rgb([v, v, v])
+                });
+                let mut bytes = Cursor::new(Vec::new());
+                DynamicImage::ImageRgb32F(image).write_to(&mut bytes, ImageFormat::OpenExr)?;
+                Ok(bytes.into_inner())
+            }
+        }
+    }
+
+    /// Loads an image from the given file. The format is defined by the extension of the file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, TerrainImageError> {
+        let path = path.as_ref();
+        let format = TerrainImageFormat::from_path(path)?;
+        Self::decode(&std::fs::read(path)?, format)
+    }
+
+    /// Saves the image to the given file. The format is defined by the extension of the file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), TerrainImageError> {
+        let path = path.as_ref();
+        let format = TerrainImageFormat::from_path(path)?;
+        std::fs::write(path, self.encode(format)?)?;
+        Ok(())
+    }
+
+    fn pixel(&self, x: u32, y: u32) -> f32 {
+        self.pixels[(y * self.width + x) as usize]
+    }
+
+    /// Bilinearly interpolated value at the given position in pixels. The position is clamped to
+    /// the bounds of the image.
+    pub fn sample(&self, position: Vector2<f32>) -> f32 {
+        if self.width == 0 || self.height == 0 {
+            return 0.0;
+        }
+        let x = position.x.clamp(0.0, (self.width - 1) as f32);
+        let y = position.y.clamp(0.0, (self.height - 1) as f32);
+        let x0 = x as u32;
+        let y0 = y as u32;
+        let x1 = (x0 + 1).min(self.width - 1);
+        let y1 = (y0 + 1).min(self.height - 1);
+        let u = x - x0 as f32;
+        let v = y - y0 as f32;
+        let top = self.pixel(x0, y0) * (1.0 - u) + self.pixel(x1, y0) * u;
+        let bottom = self.pixel(x0, y1) * (1.0 - u) + self.pixel(x1, y1) * u;
+        top * (1.0 - v) + bottom * v
+    }
+}
+
+fn normalize(value: f32, range: &Range<f32>) -> f32 {
+    let length = range.end - range.start;
+    if length == 0.0 {
+        0.0
+    } else {
+        (value - range.start) / length
+    }
+}
+
+impl Terrain {
+    /// Returns the lowest and the highest height of the terrain.
+    pub fn height_range(&self) -> Range<f32> {
+        let grid = HeightGrid::from_terrain(self);
+        grid.heights
+            .iter()
+            .fold(None, |range: Option<Range<f32>>, h| match range {
+                None => Some(*h..*h),
+                Some(range) => Some(range.start.min(*h)..range.end.max(*h)),
+            })
+            .unwrap_or(0.0..0.0)
+    }
+
+    /// Creates an image, that contains heights of every vertex of the terrain. Heights are mapped
+    /// from the given range to `[0; 1]` range. Chunk edges are not duplicated, so the size of the
+    /// image is `chunks * (height_map_size - 3) + 1` along each axis.
+    pub fn height_map_image(&self, height_range: Range<f32>) -> TerrainImage {
+        let grid = HeightGrid::from_terrain(self);
+        TerrainImage {
+            width: grid.width as u32,
+            height: grid.length as u32,
+            pixels: grid
+                .heights
+                .iter()
+                .map(|h| normalize(*h, &height_range))
+                .collect(),
+        }
+    }
+
+    /// Sets heights of every vertex of the terrain from the given image, values of the image are
+    /// mapped from `[0; 1]` range to the given height range. The image is stretched over all chunks
+    /// of the terrain, its corner pixels correspond to the corner vertices of the terrain.
+    pub fn set_height_map_image(&mut self, image: &TerrainImage, height_range: Range<f32>) {
+        let mut grid = HeightGrid::from_terrain(self);
+        let scale = Vector2::new(
+            image.width.saturating_sub(1) as f32 / grid.width.saturating_sub(1).max(1) as f32,
+            image.height.saturating_sub(1) as f32 / grid.length.saturating_sub(1).max(1) as f32,
+        );
+        for y in 0..grid.length {
+            for x in 0..grid.width {
+                let value = image.sample(Vector2::new(x as f32 * scale.x, y as f32 * scale.y));
+                let index = grid.index(x, y);
+                grid.heights[index] =
+                    height_range.start + value * (height_range.end - height_range.start);
+            }
+        }
+        grid.write_to_terrain(self);
+        self.finish_height_map_generation();
+    }
+
+    /// Loads a height map from the given file and applies it to the terrain using
+    /// [`Self::set_height_map_image`].
+    pub fn import_height_map<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        height_range: Range<f32>,
+    ) -> Result<(), TerrainImageError> {
+        let image = TerrainImage::load(path)?;
+        self.set_height_map_image(&image, height_range);
+        Ok(())
+    }
+
+    /// Saves the height map of the terrain to the given file, see [`Self::height_map_image`] for
+    /// more info. Use [`Self::height_range`] to export the full range of heights of the terrain.
+    pub fn export_height_map<P: AsRef<Path>>(
+        &self,
+        path: P,
+        height_range: Range<f32>,
+    ) -> Result<(), TerrainImageError> {
+        self.height_map_image(height_range).save(path)
+    }
+
+    fn mask_grid_size(&self) -> Vector2<usize> {
+        let mask_size = self.mask_size();
+        Vector2::new(
+            self.width_chunks().len() * mask_size.x as usize,
+            self.length_chunks().len() * mask_size.y as usize,
+        )
+    }
+
+    /// Creates an image, that contains the blending mask of the given layer for all chunks of the
+    /// terrain. The size of the image is `chunks * mask_size` along each axis.
+    pub fn layer_mask_image(&self, layer: usize) -> Result<TerrainImage, TerrainImageError> {
+        if layer >= self.layers().len() {
+            return Err(TerrainImageError::NoSuchLayer(layer));
+        }
+        let size = self.mask_grid_size();
+        let mask_size = self.mask_size().map(|s| s as usize);
+        let origin = self.chunk_mask_pos_origin(Vector2::new(
+            self.width_chunks().start,
+            self.length_chunks().start,
+        ));
+        let mut pixels = vec![0.0; size.x * size.y];
+        for chunk in self.chunks_ref() {
+            let chunk_origin = self.chunk_mask_pos_origin(chunk.grid_position()) - origin;
+            let data = chunk.layer_masks[layer].data_ref();
+            let mask = data.data_of_type::<u8>().unwrap();
+            for y in 0..mask_size.y {
+                for x in 0..mask_size.x {
+                    let gx = chunk_origin.x as usize + x;
+                    let gy = chunk_origin.y as usize + y;
+                    pixels[gy * size.x + gx] = mask[y * mask_size.x + x] as f32 / u8::MAX as f32;
+                }
+            }
+        }
+        Ok(TerrainImage {
+            width: size.x as u32,
+            height: size.y as u32,
+            pixels,
+        })
+    }
+
+    /// Sets the blending mask of the given layer from the given image. The image is stretched over
+    /// all chunks of the terrain.
+    pub fn set_layer_mask_image(
+        &mut self,
+        layer: usize,
+        image: &TerrainImage,
+    ) -> Result<(), TerrainImageError> {
+        if layer >= self.layers().len() {
+            return Err(TerrainImageError::NoSuchLayer(layer));
+        }
+        let size = self.mask_grid_size();
+        let mask_size = self.mask_size().map(|s| s as usize);
+        let origin = self.chunk_mask_pos_origin(Vector2::new(
+            self.width_chunks().start,
+            self.length_chunks().start,
+        ));
+        // Mask pixels are sampled at their centers.
+        let scale = Vector2::new(
+            image.width as f32 / size.x.max(1) as f32,
+            image.height as f32 / size.y.max(1) as f32,
+        );
+        let chunk_origins = self
+            .chunks_ref()
+            .iter()
+            .map(|c| self.chunk_mask_pos_origin(c.grid_position()) - origin)
+            .collect::<Vec<_>>();
+        for (chunk, chunk_origin) in self.chunks_mut().iter_mut().zip(chunk_origins) {
+            let mut data = chunk.layer_masks[layer].data_ref();
+            let mut modifier = data.modify();
+            let mask = modifier.data_mut_of_type::<u8>().unwrap();
+            for y in 0..mask_size.y {
+                for x in 0..mask_size.x {
+                    let gx = (chunk_origin.x as usize + x) as f32 + 0.5;
+                    let gy = (chunk_origin.y as usize + y) as f32 + 0.5;
+                    let value = image.sample(Vector2::new(gx * scale.x - 0.5, gy * scale.y - 0.5));
+                    mask[y * mask_size.x + x] =
+                        (value.clamp(0.0, 1.0) * u8::MAX as f32).round() as u8;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads a blending mask of the given layer from the given file, see
+    /// [`Self::set_layer_mask_image`] for more info.
+    pub fn import_layer_mask<P: AsRef<Path>>(
+        &mut self,
+        layer: usize,
+        path: P,
+    ) -> Result<(), TerrainImageError> {
+        let image = TerrainImage::load(path)?;
+        self.set_layer_mask_image(layer, &image)
+    }
+
+    /// Saves the blending mask of the given layer to the given file, see [`Self::layer_mask_image`]
+    /// for more info.
+    pub fn export_layer_mask<P: AsRef<Path>>(
+        &self,
+        layer: usize,
+        path: P,
+    ) -> Result<(), TerrainImageError> {
+        self.layer_mask_image(layer)?.save(path)
+    }
+
+    /// Loads blending masks of up to four layers, starting from the given one, from the channels of
+    /// the given image (splat map). Channels, that do not have a corresponding layer are ignored.
+    pub fn import_splat_map<P: AsRef<Path>>(
+        &mut self,
+        first_layer: usize,
+        path: P,
+    ) -> Result<(), TerrainImageError> {
+        let image = image::open(path)?.to_rgba32f();
+        for channel in 0..4 {
+            let layer = first_layer + channel;
+            if layer >= self.layers().len() {
+                break;
+            }
+            let mask = TerrainImage {
+                width: image.width(),
+                height: image.height(),
+                pixels: image.pixels().map(|p| p.0[channel]).collect(),
+            };
+            self.set_layer_mask_image(layer, &mask)?;
+        }
+        Ok(())
+    }
+
+    /// Saves blending masks of up to four layers, starting from the given one, to the channels of
+    /// an RGBA image (splat map). Channels, that do not have a corresponding layer are set to zero.
+    pub fn export_splat_map<P: AsRef<Path>>(
+        &self,
+        first_layer: usize,
+        path: P,
+    ) -> Result<(), TerrainImageError> {
+        let size = self.mask_grid_size();
+        let masks = (0..4)
+            .map(|channel| self.layer_mask_image(first_layer + channel).ok())
+            .collect::<Vec<_>>();
+        let image = RgbaImage::from_fn(size.x as u32, size.y as u32, |x, y| {
+            let mut pixel = [0; 4];
+            for (channel, mask) in masks.iter().enumerate() {
+                if let Some(mask) = mask {
+                    pixel[channel] = (mask.pixel(x, y) * u8::MAX as f32).round() as u8;
+                }
+            }
+            Rgba(pixel)
+        });
+        image.save(path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector2,
+        scene::{
+            base::BaseBuilder,
+            terrain::{
+                io::{TerrainImage, TerrainImageError, TerrainImageFormat},
+                Layer, Terrain, TerrainBuilder,
+            },
+        },
+    };
+    use std::path::Path;
+
+    fn make_terrain() -> Terrain {
+        let node = TerrainBuilder::new(BaseBuilder::new())
+            .with_chunk_size(Vector2::new(16.0, 16.0))
+            .with_width_chunks(0..2)
+            .with_length_chunks(0..2)
+            .with_height_map_size(Vector2::new(11, 11))
+            .with_mask_size(Vector2::new(8, 8))
+            .with_layers(vec![Layer::default(), Layer::default()])
+            .build_node();
+        node.cast::<Terrain>().unwrap().clone()
+    }
+
+    fn gradient_image(width: u32, height: u32) -> TerrainImage {
+        TerrainImage {
+            width,
+            height,
+            pixels: (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x + y) as f32 / (width + height - 2) as f32))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let image = gradient_image(5, 5);
+        for format in [
+            TerrainImageFormat::Raw16,
+            TerrainImageFormat::Png16,
+            TerrainImageFormat::Exr,
+        ] {
+            let bytes = image.encode(format).unwrap();
+            let decoded = TerrainImage::decode(&bytes, format).unwrap();
+            assert_eq!(decoded.width, 5);
+            assert_eq!(decoded.height, 5);
+            for (a, b) in image.pixels.iter().zip(decoded.pixels.iter()) {
+                assert!((a - b).abs() < 1.0e-4);
+            }
+        }
+
+        assert!(matches!(
+            TerrainImage::decode(&[0; 6], TerrainImageFormat::Raw16),
+            Err(TerrainImageError::InvalidRawSize(6))
+        ));
+        assert_eq!(
+            TerrainImageFormat::from_path(Path::new("heights.R16")).unwrap(),
+            TerrainImageFormat::Raw16
+        );
+    }
+
+    #[test]
+    fn test_height_map_round_trip() {
+        let mut terrain = make_terrain();
+        // 2 chunks with 8 cells each.
+        let image = gradient_image(17, 17);
+        terrain.set_height_map_image(&image, 10.0..30.0);
+
+        assert_eq!(terrain.height_range(), 10.0..30.0);
+        assert_eq!(terrain.get_height(Vector2::new(0, 0)), Some(10.0));
+
+        let exported = terrain.height_map_image(10.0..30.0);
+        assert_eq!(exported.width, 17);
+        assert_eq!(exported.height, 17);
+        for (a, b) in image.pixels.iter().zip(exported.pixels.iter()) {
+            assert!((a - b).abs() < 1.0e-5);
+        }
+    }
+
+    #[test]
+    fn test_height_map_resampling() {
+        let mut terrain = make_terrain();
+        terrain.set_height_map_image(&gradient_image(33, 33), 0.0..1.0);
+        let exported = terrain.height_map_image(0.0..1.0);
+        let expected = gradient_image(17, 17);
+        for (a, b) in expected.pixels.iter().zip(exported.pixels.iter()) {
+            assert!((a - b).abs() < 1.0e-5);
+        }
+    }
+
+    #[test]
+    fn test_layer_mask_round_trip() {
+        let mut terrain = make_terrain();
+        let image = TerrainImage {
+            width: 16,
+            height: 16,
+            pixels: (0..256).map(|i| ((i % 5) * 51) as f32 / 255.0).collect(),
+        };
+        terrain.set_layer_mask_image(1, &image).unwrap();
+        assert_eq!(terrain.layer_mask_image(1).unwrap(), image);
+        assert!(matches!(
+            terrain.set_layer_mask_image(2, &image),
+            Err(TerrainImageError::NoSuchLayer(2))
+        ));
+    }
+
+    #[test]
+    fn test_file_import_export() {
+        let dir = Path::new("test_output/terrain_io");
+        std::fs::create_dir_all(dir).unwrap();
+
+        let mut terrain = make_terrain();
+        terrain.set_height_map_image(&gradient_image(17, 17), 0.0..100.0);
+        let mut other = make_terrain();
+        for name in ["heights.r16", "heights.png", "heights.exr"] {
+            let path = dir.join(name);
+            terrain.export_height_map(&path, 0.0..100.0).unwrap();
+            other.import_height_map(&path, 0.0..100.0).unwrap();
+            let a = terrain.height_map_image(0.0..100.0);
+            let b = other.height_map_image(0.0..100.0);
+            for (a, b) in a.pixels.iter().zip(b.pixels.iter()) {
+                assert!((a - b).abs() < 1.0e-4);
+            }
+        }
+
+        let mask = TerrainImage {
+            width: 16,
+            height: 16,
+            pixels: (0..256).map(|i| ((i % 3) * 85) as f32 / 255.0).collect(),
+        };
+        terrain.set_layer_mask_image(0, &mask).unwrap();
+        let path = dir.join("splat.png");
+        terrain.export_splat_map(0, &path).unwrap();
+        other.import_splat_map(0, &path).unwrap();
+        assert_eq!(other.layer_mask_image(0).unwrap(), mask);
+    }
+}
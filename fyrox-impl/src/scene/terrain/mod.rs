@@ -54,7 +54,11 @@ use crate::{
         graph::Graph,
         mesh::RenderPath,
         node::{Node, NodeTrait},
-        terrain::{geometry::TerrainGeometry, quadtree::QuadTree},
+        terrain::{
+            foliage::{FoliageCache, FoliageLayer},
+            geometry::TerrainGeometry,
+            quadtree::QuadTree,
+        },
         Scene,
     },
 };
@@ -73,7 +77,10 @@ use std::{
 };
 
 pub mod brushstroke;
+pub mod foliage;
+pub mod generator;
 mod geometry;
+pub mod io;
 mod quadtree;
 
 use crate::scene::node::constructor::NodeConstructor;
@@ -364,6 +371,9 @@ pub struct Chunk {
     /// Layer blending masks of the chunk.
     #[reflect(hidden)]
     pub layer_masks: Vec<TextureResource>,
+    /// Density masks of foliage layers of the chunk.
+    #[reflect(hidden)]
+    pub foliage_masks: Vec<TextureResource>,
     #[reflect(hidden)]
     height_map_modifications_count: u64,
 }
@@ -376,6 +386,7 @@ impl PartialEq for Chunk {
             && self.height_map_size == other.height_map_size
             && self.grid_position == other.grid_position
             && self.layer_masks == other.layer_masks
+            && self.foliage_masks == other.foliage_masks
     }
 }
 
@@ -395,6 +406,11 @@ impl Clone for Chunk {
                 .iter()
                 .map(|m| m.deep_clone())
                 .collect::<Vec<_>>(),
+            foliage_masks: self
+                .foliage_masks
+                .iter()
+                .map(|m| m.deep_clone())
+                .collect::<Vec<_>>(),
             quad_tree: Mutex::new(make_quad_tree(
                 &self.heightmap,
                 self.height_map_size,
@@ -451,6 +467,7 @@ impl Visit for Chunk {
                 self.physical_size.visit("PhysicalSize", &mut region)?;
                 self.height_map_size.visit("HeightMapSize", &mut region)?;
                 self.layer_masks.visit("LayerMasks", &mut region)?;
+                let _ = self.foliage_masks.visit("FoliageMasks", &mut region);
                 self.grid_position.visit("GridPosition", &mut region)?;
                 // Set position to have the value implied by grid_position
                 if region.is_reading() {
@@ -487,6 +504,7 @@ impl Default for Chunk {
             block_size: Vector2::new(32, 32),
             grid_position: Default::default(),
             layer_masks: Default::default(),
+            foliage_masks: Default::default(),
             height_map_modifications_count: 0,
         }
     }
@@ -1047,6 +1065,9 @@ pub struct Terrain {
     #[reflect(setter = "set_layers")]
     layers: InheritableVariable<Vec<Layer>>,
 
+    #[reflect(setter = "set_foliage_layers")]
+    foliage_layers: InheritableVariable<Vec<FoliageLayer>>,
+
     /// Size of the chunk, in meters.
     /// This value becomes the [Chunk::physical_size] of newly created chunks.
     #[reflect(
@@ -1128,6 +1149,9 @@ pub struct Terrain {
     /// all the chunks of the height map.
     #[reflect(hidden)]
     geometry: TerrainGeometry,
+
+    #[reflect(hidden)]
+    foliage_cache: FoliageCache,
 }
 
 impl Default for Terrain {
@@ -1136,6 +1160,7 @@ impl Default for Terrain {
             base: Default::default(),
             holes_enabled: false,
             layers: Default::default(),
+            foliage_layers: Default::default(),
             chunk_size: Vector2::new(16.0, 16.0).into(),
             width_chunks: Default::default(),
             length_chunks: Default::default(),
@@ -1146,6 +1171,7 @@ impl Default for Terrain {
             bounding_box_dirty: Cell::new(true),
             bounding_box: Cell::new(Default::default()),
             geometry: Default::default(),
+            foliage_cache: Default::default(),
         }
     }
 }
@@ -1253,6 +1279,7 @@ impl Visit for Terrain {
                 self.base.visit("Base", &mut region)?;
                 let _ = self.holes_enabled.visit("HolesEnabled", &mut region);
                 self.layers.visit("Layers", &mut region)?;
+                let _ = self.foliage_layers.visit("FoliageLayers", &mut region);
                 self.chunk_size.visit("ChunkSize", &mut region)?;
                 self.width_chunks.visit("WidthChunks", &mut region)?;
                 self.length_chunks.visit("LengthChunks", &mut region)?;
@@ -1723,6 +1750,11 @@ impl Terrain {
                                 )
                             })
                             .collect::<Vec<_>>(),
+                        foliage_masks: self
+                            .foliage_layers
+                            .iter()
+                            .map(|_| create_layer_mask(self.mask_size.x, self.mask_size.y, 0))
+                            .collect::<Vec<_>>(),
                     };
                     created_chunks.push(new_chunk.grid_position);
                     new_chunk
@@ -1870,6 +1902,18 @@ impl Terrain {
         Some(mask_data[index])
     }
 
+    /// Return the value of the density mask of a foliage layer at the given mask pixel position.
+    pub fn get_foliage_mask(&self, position: Vector2<i32>, layer: usize) -> Option<u8> {
+        let chunk_pos = self.chunk_containing_mask_pos(position);
+        let chunk = self.find_chunk(chunk_pos)?;
+        let origin = self.chunk_mask_pos_origin(chunk_pos);
+        let pos = (position - origin).map(|x| x as usize);
+        let index = pos.y * self.mask_size.x as usize + pos.x;
+        let texture_data = chunk.foliage_masks.get(layer)?.data_ref();
+        let mask_data = texture_data.data();
+        Some(mask_data[index])
+    }
+
     /// Return the value of the layer mask at the given mask pixel position.
     pub fn get_hole_mask(&self, position: Vector2<i32>) -> Option<u8> {
         let chunk_pos = self.chunk_containing_hole_pos(position);
@@ -1900,7 +1944,9 @@ impl Terrain {
     pub fn interpolate_value(&self, position: Vector2<f32>, target: BrushTarget) -> f32 {
         let grid_square = match target {
             BrushTarget::HeightMap => self.get_height_grid_square(position),
-            BrushTarget::LayerMask { .. } => self.get_mask_grid_square(position),
+            BrushTarget::LayerMask { .. } | BrushTarget::FoliageMask { .. } => {
+                self.get_mask_grid_square(position)
+            }
             BrushTarget::HoleMask => self.get_hole_grid_square(position),
         };
         let p = grid_square.grid_position;
@@ -1930,6 +1976,12 @@ impl Terrain {
                 self.get_layer_mask(p10, layer).unwrap_or(0) as f32 / 255.0,
                 self.get_layer_mask(p11, layer).unwrap_or(0) as f32 / 255.0,
             ),
+            BrushTarget::FoliageMask { layer } => (
+                self.get_foliage_mask(p00, layer).unwrap_or(0) as f32 / 255.0,
+                self.get_foliage_mask(p01, layer).unwrap_or(0) as f32 / 255.0,
+                self.get_foliage_mask(p10, layer).unwrap_or(0) as f32 / 255.0,
+                self.get_foliage_mask(p11, layer).unwrap_or(0) as f32 / 255.0,
+            ),
             BrushTarget::HoleMask => (
                 self.get_hole_mask(p00).unwrap_or(0) as f32 / 255.0,
                 self.get_hole_mask(p01).unwrap_or(0) as f32 / 255.0,
@@ -2286,7 +2338,11 @@ impl Terrain {
         new_size = new_size.sup(&Vector2::repeat(1));
 
         for chunk in self.chunks.iter_mut() {
-            for mask in chunk.layer_masks.iter_mut() {
+            for mask in chunk
+                .layer_masks
+                .iter_mut()
+                .chain(chunk.foliage_masks.iter_mut())
+            {
                 let data = mask.data_ref();
                 let new_mask = resize_u8(data.data().to_vec(), *self.mask_size, new_size);
                 let new_mask_texture = TextureResource::from_bytes(
@@ -2361,12 +2417,13 @@ impl Terrain {
     pub fn texture_data(&self, target: BrushTarget) -> TerrainTextureData {
         let chunk_size = match target {
             BrushTarget::HeightMap => self.height_map_size(),
-            BrushTarget::LayerMask { .. } => self.mask_size(),
+            BrushTarget::LayerMask { .. } | BrushTarget::FoliageMask { .. } => self.mask_size(),
             BrushTarget::HoleMask => self.hole_mask_size(),
         };
         let kind = match target {
             BrushTarget::HeightMap => TerrainTextureKind::Height,
             BrushTarget::LayerMask { .. } => TerrainTextureKind::Mask,
+            BrushTarget::FoliageMask { .. } => TerrainTextureKind::Mask,
             BrushTarget::HoleMask => TerrainTextureKind::Mask,
         };
         let resources: FxHashMap<Vector2<i32>, TextureResource> = match target {
@@ -2393,6 +2450,11 @@ impl Terrain {
                 .iter()
                 .map(|c| (c.grid_position(), c.layer_masks[layer].clone()))
                 .collect(),
+            BrushTarget::FoliageMask { layer } => self
+                .chunks_ref()
+                .iter()
+                .map(|c| (c.grid_position(), c.foliage_masks[layer].clone()))
+                .collect(),
         };
         TerrainTextureData {
             chunk_size,
@@ -2426,12 +2488,16 @@ impl Terrain {
         };
         let position = match stroke.brush().target {
             BrushTarget::HeightMap => self.local_to_height_pixel(position),
-            BrushTarget::LayerMask { .. } => self.local_to_mask_pixel(position),
+            BrushTarget::LayerMask { .. } | BrushTarget::FoliageMask { .. } => {
+                self.local_to_mask_pixel(position)
+            }
             BrushTarget::HoleMask => self.local_to_hole_pixel(position),
         };
         let scale = match stroke.brush().target {
             BrushTarget::HeightMap => self.height_grid_scale(),
-            BrushTarget::LayerMask { .. } => self.mask_grid_scale(),
+            BrushTarget::LayerMask { .. } | BrushTarget::FoliageMask { .. } => {
+                self.mask_grid_scale()
+            }
             BrushTarget::HoleMask => self.hole_grid_scale(),
         };
         stroke.stamp(position, scale, value);
@@ -2453,17 +2519,23 @@ impl Terrain {
         };
         let start = match stroke.brush().target {
             BrushTarget::HeightMap => self.local_to_height_pixel(start),
-            BrushTarget::LayerMask { .. } => self.local_to_mask_pixel(start),
+            BrushTarget::LayerMask { .. } | BrushTarget::FoliageMask { .. } => {
+                self.local_to_mask_pixel(start)
+            }
             BrushTarget::HoleMask => self.local_to_hole_pixel(start),
         };
         let end = match stroke.brush().target {
             BrushTarget::HeightMap => self.local_to_height_pixel(end),
-            BrushTarget::LayerMask { .. } => self.local_to_mask_pixel(end),
+            BrushTarget::LayerMask { .. } | BrushTarget::FoliageMask { .. } => {
+                self.local_to_mask_pixel(end)
+            }
             BrushTarget::HoleMask => self.local_to_hole_pixel(end),
         };
         let scale = match stroke.brush().target {
             BrushTarget::HeightMap => self.height_grid_scale(),
-            BrushTarget::LayerMask { .. } => self.mask_grid_scale(),
+            BrushTarget::LayerMask { .. } | BrushTarget::FoliageMask { .. } => {
+                self.mask_grid_scale()
+            }
             BrushTarget::HoleMask => self.hole_grid_scale(),
         };
         stroke.smear(start, end, scale, value);
@@ -2708,6 +2780,8 @@ impl NodeTrait for Terrain {
             }
         }
 
+        self.collect_foliage_render_data(ctx);
+
         RdcControlFlow::Continue
     }
 
@@ -2757,9 +2831,10 @@ pub struct TerrainBuilder {
     height_map_size: Vector2<u32>,
     block_size: Vector2<u32>,
     layers: Vec<Layer>,
+    foliage_layers: Vec<FoliageLayer>,
 }
 
-fn create_layer_mask(width: u32, height: u32, value: u8) -> TextureResource {
+pub(crate) fn create_layer_mask(width: u32, height: u32, value: u8) -> TextureResource {
     let mask = TextureResource::from_bytes(
         TextureKind::Rectangle { width, height },
         TexturePixelKind::R8,
@@ -2789,6 +2864,7 @@ impl TerrainBuilder {
             height_map_size: Vector2::new(257, 257),
             block_size: Vector2::new(33, 33),
             layers: Default::default(),
+            foliage_layers: Default::default(),
         }
     }
 
@@ -2834,6 +2910,13 @@ impl TerrainBuilder {
         self
     }
 
+    /// Sets desired foliage layers that will be used for the terrain. Density masks of every layer
+    /// will be empty.
+    pub fn with_foliage_layers(mut self, foliage_layers: Vec<FoliageLayer>) -> Self {
+        self.foliage_layers = foliage_layers;
+        self
+    }
+
     /// Sets desired block size. Block - is a smallest renderable piece of terrain which will be used for
     /// level-of-detail functionality.
     pub fn with_block_size(mut self, block_size: Vector2<u32>) -> Self {
@@ -2888,6 +2971,11 @@ impl TerrainBuilder {
                             )
                         })
                         .collect::<Vec<_>>(),
+                    foliage_masks: self
+                        .foliage_layers
+                        .iter()
+                        .map(|_| create_layer_mask(self.mask_size.x, self.mask_size.y, 0))
+                        .collect::<Vec<_>>(),
                     block_size: self.block_size,
                 };
 
@@ -2900,6 +2988,7 @@ impl TerrainBuilder {
             base: self.base_builder.build_base(),
             holes_enabled: self.holes_enabled,
             layers: self.layers.into(),
+            foliage_layers: self.foliage_layers.into(),
             chunks: chunks.into(),
             bounding_box_dirty: Cell::new(true),
             bounding_box: Default::default(),
@@ -2909,6 +2998,7 @@ impl TerrainBuilder {
             length_chunks: self.length_chunks.into(),
             geometry: TerrainGeometry::new(self.block_size),
             block_size: self.block_size.into(),
+            foliage_cache: Default::default(),
         };
         Node::new(terrain)
     }
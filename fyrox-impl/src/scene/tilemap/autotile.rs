@@ -0,0 +1,375 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Rule-based auto-tiling (terrains). A terrain is a group of tiles of a tile set, where each tile
+//! has a mask, that defines which of its neighbours belong to the same terrain. When painting with
+//! a terrain, the tile map picks the tile whose mask matches the actual neighbourhood, so edges and
+//! corners are selected automatically. See [`Terrain`] docs for more info.
+
+use crate::{
+    core::{algebra::Vector2, reflect::prelude::*, type_traits::prelude::*, visitor::prelude::*},
+    scene::tilemap::tileset::{TileDefinitionHandle, TileSet},
+};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Defines how the mask of a tile is calculated from its neighbours.
+#[derive(
+    Copy,
+    Clone,
+    Hash,
+    PartialEq,
+    Eq,
+    Debug,
+    Default,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+    TypeUuidProvider,
+)]
+#[type_uuid(id = "0d3f9a53-7c1e-4f2b-8a6e-2b5c9e4d1f70")]
+pub enum TerrainKind {
+    /// Only corners are taken into account (16 tiles for a complete set). A corner is set if all
+    /// three neighbours around the corner belong to the terrain.
+    #[default]
+    Corners2x2,
+    /// All eight neighbours are taken into account (47 tiles for a complete set, also known as
+    /// "blob" tile set). A corner is set only if both adjacent sides are set, so the corner can be
+    /// ignored otherwise.
+    Blob3x3,
+}
+
+impl TerrainKind {
+    /// Returns the bits of [`TerrainMask`], that are used by the terrain kind.
+    pub fn relevant_bits(self) -> u8 {
+        match self {
+            TerrainKind::Corners2x2 => {
+                TerrainMask::TOP_LEFT_BIT
+                    | TerrainMask::TOP_RIGHT_BIT
+                    | TerrainMask::BOTTOM_RIGHT_BIT
+                    | TerrainMask::BOTTOM_LEFT_BIT
+            }
+            TerrainKind::Blob3x3 => u8::MAX,
+        }
+    }
+}
+
+/// Terrain is a named group of tiles, that are connected with each other using auto-tiling rules.
+/// Tiles are added to a terrain using [`super::tileset::TileDefinition::terrain`] field.
+#[derive(Clone, Default, Debug, PartialEq, Reflect, Visit, TypeUuidProvider)]
+#[type_uuid(id = "9e62c1b4-5a7d-4c3e-b0f8-7d1a2e6c4b95")]
+pub struct Terrain {
+    /// Name of the terrain.
+    pub name: String,
+    /// Kind of the auto-tiling rules of the terrain.
+    pub kind: TerrainKind,
+}
+
+/// A set of neighbours of a tile, that belong to the same terrain as the tile. Top direction
+/// corresponds to positive Y axis of a tile map.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash, Reflect, Visit, TypeUuidProvider)]
+#[type_uuid(id = "4b0a7e2f-93c6-4d58-a1e7-c5f2d8b3a609")]
+pub struct TerrainMask {
+    /// Top-left neighbour.
+    pub top_left: bool,
+    /// Top neighbour.
+    pub top: bool,
+    /// Top-right neighbour.
+    pub top_right: bool,
+    /// Right neighbour.
+    pub right: bool,
+    /// Bottom-right neighbour.
+    pub bottom_right: bool,
+    /// Bottom neighbour.
+    pub bottom: bool,
+    /// Bottom-left neighbour.
+    pub bottom_left: bool,
+    /// Left neighbour.
+    pub left: bool,
+}
+
+impl TerrainMask {
+    /// Top-left neighbour bit.
+    pub const TOP_LEFT_BIT: u8 = 1 << 0;
+    /// Top neighbour bit.
+    pub const TOP_BIT: u8 = 1 << 1;
+    /// Top-right neighbour bit.
+    pub const TOP_RIGHT_BIT: u8 = 1 << 2;
+    /// Right neighbour bit.
+    pub const RIGHT_BIT: u8 = 1 << 3;
+    /// Bottom-right neighbour bit.
+    pub const BOTTOM_RIGHT_BIT: u8 = 1 << 4;
+    /// Bottom neighbour bit.
+    pub const BOTTOM_BIT: u8 = 1 << 5;
+    /// Bottom-left neighbour bit.
+    pub const BOTTOM_LEFT_BIT: u8 = 1 << 6;
+    /// Left neighbour bit.
+    pub const LEFT_BIT: u8 = 1 << 7;
+
+    /// Offsets of the neighbours in the same order as the bits.
+    pub const OFFSETS: [Vector2<i32>; 8] = [
+        Vector2::new(-1, 1),
+        Vector2::new(0, 1),
+        Vector2::new(1, 1),
+        Vector2::new(1, 0),
+        Vector2::new(1, -1),
+        Vector2::new(0, -1),
+        Vector2::new(-1, -1),
+        Vector2::new(-1, 0),
+    ];
+
+    fn fields(&self) -> [bool; 8] {
+        [
+            self.top_left,
+            self.top,
+            self.top_right,
+            self.right,
+            self.bottom_right,
+            self.bottom,
+            self.bottom_left,
+            self.left,
+        ]
+    }
+
+    /// Creates the mask from a set of bits.
+    pub fn from_bits(bits: u8) -> Self {
+        let bit = |b: u8| bits & b != 0;
+        Self {
+            top_left: bit(Self::TOP_LEFT_BIT),
+            top: bit(Self::TOP_BIT),
+            top_right: bit(Self::TOP_RIGHT_BIT),
+            right: bit(Self::RIGHT_BIT),
+            bottom_right: bit(Self::BOTTOM_RIGHT_BIT),
+            bottom: bit(Self::BOTTOM_BIT),
+            bottom_left: bit(Self::BOTTOM_LEFT_BIT),
+            left: bit(Self::LEFT_BIT),
+        }
+    }
+
+    /// Returns the mask as a set of bits.
+    pub fn bits(&self) -> u8 {
+        self.fields()
+            .iter()
+            .enumerate()
+            .fold(0, |bits, (i, set)| bits | ((*set as u8) << i))
+    }
+
+    /// Calculates the mask of a tile at the given position using the given predicate, that checks
+    /// whether a tile at a position belongs to the terrain or not.
+    pub fn calculate<F>(kind: TerrainKind, position: Vector2<i32>, mut belongs: F) -> Self
+    where
+        F: FnMut(Vector2<i32>) -> bool,
+    {
+        let mut neighbours = [false; 8];
+        for (neighbour, offset) in neighbours.iter_mut().zip(Self::OFFSETS) {
+            *neighbour = belongs(position + offset);
+        }
+        let [top_left, top, top_right, right, bottom_right, bottom, bottom_left, left] = neighbours;
+
+        match kind {
+            TerrainKind::Corners2x2 => Self {
+                top_left: top_left && top && left,
+                top_right: top_right && top && right,
+                bottom_right: bottom_right && bottom && right,
+                bottom_left: bottom_left && bottom && left,
+                ..Default::default()
+            },
+            TerrainKind::Blob3x3 => Self {
+                top_left: top_left && top && left,
+                top,
+                top_right: top_right && top && right,
+                right,
+                bottom_right: bottom_right && bottom && right,
+                bottom,
+                bottom_left: bottom_left && bottom && left,
+                left,
+            },
+        }
+    }
+}
+
+/// Defines which terrain a tile belongs to and how the tile is connected with its neighbours.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash, Reflect, Visit, TypeUuidProvider)]
+#[type_uuid(id = "c71e4d0a-2f8b-4a96-b3d5-8e0f6a1c7b24")]
+pub struct TileTerrain {
+    /// Index of the terrain in [`TileSet::terrains`].
+    pub terrain: u32,
+    /// Neighbours of the tile, that belong to the same terrain.
+    pub mask: TerrainMask,
+}
+
+impl TileSet {
+    /// Returns the terrain index of the given tile definition (if any).
+    pub fn terrain_of(&self, handle: TileDefinitionHandle) -> Option<u32> {
+        self.tiles
+            .try_borrow(handle)
+            .and_then(|tile| tile.terrain)
+            .map(|terrain| terrain.terrain)
+    }
+
+    /// Searches for a tile of the given terrain, that fits the given mask the best. Tiles with exact
+    /// match are preferred, if there's no such tile, then the tile with the most matching
+    /// neighbours is returned. If there are multiple tiles with the same mask, one of them is
+    /// selected using the given seed, which allows adding some variety to the terrain.
+    pub fn find_terrain_tile(
+        &self,
+        terrain: u32,
+        mask: TerrainMask,
+        seed: u32,
+    ) -> Option<TileDefinitionHandle> {
+        let relevant_bits = self.terrains.get(terrain as usize)?.kind.relevant_bits();
+        let bits = mask.bits() & relevant_bits;
+
+        let mut best_score = None;
+        let mut candidates = Vec::new();
+        for (handle, tile) in self.tiles.pair_iter() {
+            let Some(tile_terrain) = tile.terrain.filter(|t| t.terrain == terrain) else {
+                continue;
+            };
+            let score = (!(tile_terrain.mask.bits() ^ bits) & relevant_bits).count_ones();
+            if best_score.map_or(true, |best| score > best) {
+                best_score = Some(score);
+                candidates.clear();
+            }
+            if best_score == Some(score) {
+                candidates.push(handle);
+            }
+        }
+
+        if candidates.is_empty() {
+            None
+        } else {
+            Some(candidates[seed as usize % candidates.len()])
+        }
+    }
+}
+
+/// Calculates a seed for tile variations from the tile position.
+pub(crate) fn position_seed(position: Vector2<i32>) -> u32 {
+    (position.x as u32)
+        .wrapping_mul(73856093)
+        .wrapping_add((position.y as u32).wrapping_mul(19349663))
+        >> 4
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector2,
+        scene::tilemap::{
+            autotile::{Terrain, TerrainKind, TerrainMask, TileTerrain},
+            tileset::{TileDefinition, TileDefinitionHandle, TileSet},
+            Tiles,
+        },
+    };
+
+    fn make_tile_set(kind: TerrainKind, masks: impl Iterator<Item = u8>) -> TileSet {
+        let mut tile_set = TileSet::default();
+        tile_set.terrains.push(Terrain {
+            name: "Grass".to_string(),
+            kind,
+        });
+        for bits in masks {
+            tile_set.add_tile(TileDefinition {
+                terrain: Some(TileTerrain {
+                    terrain: 0,
+                    mask: TerrainMask::from_bits(bits),
+                }),
+                ..Default::default()
+            });
+        }
+        tile_set
+    }
+
+    fn mask_at(tiles: &Tiles, tile_set: &TileSet, x: i32, y: i32) -> Option<u8> {
+        let handle: TileDefinitionHandle = tiles.definition_at(Vector2::new(x, y))?;
+        Some(tile_set.tiles[handle].terrain?.mask.bits())
+    }
+
+    #[test]
+    fn test_mask_bits() {
+        for bits in 0..=u8::MAX {
+            assert_eq!(TerrainMask::from_bits(bits).bits(), bits);
+        }
+        assert!(TerrainMask::from_bits(TerrainMask::BOTTOM_LEFT_BIT).bottom_left);
+    }
+
+    #[test]
+    fn test_corners_terrain() {
+        let corners = TerrainKind::Corners2x2.relevant_bits();
+        let tile_set = make_tile_set(
+            TerrainKind::Corners2x2,
+            (0..=u8::MAX).filter(|bits| bits & !corners == 0),
+        );
+        assert_eq!(tile_set.tiles.alive_count(), 16);
+
+        let mut tiles = Tiles::default();
+        for y in 0..3 {
+            for x in 0..3 {
+                tiles.draw_terrain(Vector2::new(x, y), 0, &tile_set);
+            }
+        }
+
+        assert_eq!(mask_at(&tiles, &tile_set, 1, 1), Some(corners));
+        assert_eq!(
+            mask_at(&tiles, &tile_set, 0, 0),
+            Some(TerrainMask::TOP_RIGHT_BIT)
+        );
+        assert_eq!(
+            mask_at(&tiles, &tile_set, 1, 2),
+            Some(TerrainMask::BOTTOM_LEFT_BIT | TerrainMask::BOTTOM_RIGHT_BIT)
+        );
+
+        // Erasing must update the neighbours.
+        tiles.erase_terrain(Vector2::new(1, 1), &tile_set);
+        for y in 0..3 {
+            for x in 0..3 {
+                if (x, y) != (1, 1) {
+                    assert_eq!(mask_at(&tiles, &tile_set, x, y), Some(0));
+                }
+            }
+        }
+        assert_eq!(mask_at(&tiles, &tile_set, 1, 1), None);
+    }
+
+    #[test]
+    fn test_blob_terrain() {
+        // Incomplete rule set - only isolated, horizontal and vertical pieces.
+        let horizontal = TerrainMask::LEFT_BIT | TerrainMask::RIGHT_BIT;
+        let vertical = TerrainMask::TOP_BIT | TerrainMask::BOTTOM_BIT;
+        let tile_set = make_tile_set(TerrainKind::Blob3x3, [0, horizontal, vertical].into_iter());
+
+        let mut tiles = Tiles::default();
+        for x in 0..3 {
+            tiles.draw_terrain(Vector2::new(x, 0), 0, &tile_set);
+        }
+        assert_eq!(mask_at(&tiles, &tile_set, 1, 0), Some(horizontal));
+
+        // Diagonal neighbours without adjacent sides must be ignored.
+        tiles.draw_terrain(Vector2::new(5, 5), 0, &tile_set);
+        tiles.draw_terrain(Vector2::new(6, 6), 0, &tile_set);
+        assert_eq!(mask_at(&tiles, &tile_set, 5, 5), Some(0));
+
+        let mask = TerrainMask::calculate(TerrainKind::Blob3x3, Vector2::new(5, 5), |p| {
+            p == Vector2::new(6, 6)
+        });
+        assert_eq!(mask.bits(), 0);
+    }
+}
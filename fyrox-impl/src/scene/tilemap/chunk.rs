@@ -0,0 +1,629 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Chunked storage of tiles. Tiles of a tile map are split in square chunks of fixed size, each
+//! chunk tracks its bounds and its revision. This allows to cull invisible parts of large tile maps
+//! and to rebuild render and collider geometry only for the chunks that were actually changed. See
+//! [`TileChunk`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point2, Point3, Vector2, Vector3},
+        math::{aabb::AxisAlignedBoundingBox, Rect, TriangleDefinition},
+    },
+    material::MaterialResource,
+    scene::{
+        dim2::rectangle::RectangleVertex,
+        tilemap::{
+            tileset::{TileCollider, TileSet},
+            Tile, Tiles,
+        },
+    },
+};
+use fxhash::FxHashMap;
+use std::{
+    cell::RefCell,
+    fmt::{Debug, Formatter},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+static REVISION: AtomicU64 = AtomicU64::new(1);
+
+/// Returns new unique revision number. Revisions are global, so a revision number uniquely
+/// identifies the content of a chunk, even if the chunk was cloned or replaced.
+pub(crate) fn next_revision() -> u64 {
+    REVISION.fetch_add(1, Ordering::Relaxed)
+}
+
+pub(crate) fn rect_to_aabb(rect: Rect<i32>) -> AxisAlignedBoundingBox {
+    let min = rect.position.cast::<f32>().to_homogeneous();
+    let max = (rect.position + rect.size).cast::<f32>().to_homogeneous();
+    AxisAlignedBoundingBox::from_min_max(min, max)
+}
+
+/// Chunk is a square block of [`TileChunk::SIZE`]x[`TileChunk::SIZE`] tiles. Chunks are created
+/// and destroyed automatically by [`Tiles`] container, when tiles are added or removed.
+#[derive(Clone, Debug, Default)]
+pub struct TileChunk {
+    tiles: FxHashMap<Vector2<i32>, Tile>,
+    bounds: Rect<i32>,
+    revision: u64,
+}
+
+impl PartialEq for TileChunk {
+    fn eq(&self, other: &Self) -> bool {
+        self.tiles == other.tiles
+    }
+}
+
+impl TileChunk {
+    /// Size of a chunk (in tiles) along each axis.
+    pub const SIZE: i32 = 16;
+
+    /// Calculates position of a chunk (in chunk coordinates), that contains a tile at the given
+    /// position.
+    #[inline]
+    pub fn position_of(tile_position: Vector2<i32>) -> Vector2<i32> {
+        Vector2::new(
+            tile_position.x.div_euclid(Self::SIZE),
+            tile_position.y.div_euclid(Self::SIZE),
+        )
+    }
+
+    /// Returns an iterator over the tiles of the chunk.
+    #[inline]
+    pub fn tiles(&self) -> impl Iterator<Item = &Tile> {
+        self.tiles.values()
+    }
+
+    /// Tries to fetch a tile at the given position (in grid coordinates).
+    #[inline]
+    pub fn get(&self, position: Vector2<i32>) -> Option<&Tile> {
+        self.tiles.get(&position)
+    }
+
+    /// Returns total amount of tiles in the chunk.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Returns `true` if the chunk has no tiles.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// Returns tight bounding rectangle of the tiles of the chunk (in grid coordinates).
+    #[inline]
+    pub fn bounds(&self) -> Rect<i32> {
+        self.bounds
+    }
+
+    /// Returns current revision of the chunk. Revision changes on every modification of the chunk.
+    #[inline]
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Calculates local bounding box of the chunk.
+    #[inline]
+    pub fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        rect_to_aabb(self.bounds)
+    }
+
+    pub(super) fn insert(&mut self, tile: Tile) -> Option<Tile> {
+        let min = tile.position;
+        let max = tile.position + Vector2::repeat(1);
+        if self.tiles.is_empty() {
+            self.bounds = Rect::from_points(min, max);
+        } else {
+            self.bounds.push(min);
+            self.bounds.push(max);
+        }
+        self.revision = next_revision();
+        self.tiles.insert(tile.position, tile)
+    }
+
+    pub(super) fn remove(&mut self, position: Vector2<i32>) -> Option<Tile> {
+        let tile = self.tiles.remove(&position)?;
+        self.revision = next_revision();
+        self.bounds = Rect::default();
+        let mut tiles = self.tiles.keys();
+        if let Some(first) = tiles.next() {
+            self.bounds = Rect::from_points(*first, first + Vector2::repeat(1));
+            for position in tiles {
+                self.bounds.push(*position);
+                self.bounds.push(position + Vector2::repeat(1));
+            }
+        }
+        Some(tile)
+    }
+}
+
+/// A set of tile quads that use the same material.
+pub(crate) struct TileRenderBatch {
+    pub material: MaterialResource,
+    pub vertices: Vec<RectangleVertex>,
+    pub triangles: Vec<TriangleDefinition>,
+}
+
+impl TileRenderBatch {
+    /// Creates render batches for the given tiles. Vertices are in the local space of the tile map.
+    pub fn build<'a>(tiles: impl Iterator<Item = &'a Tile>, tile_set: &TileSet) -> Vec<Self> {
+        let mut batches = Vec::<Self>::new();
+        let mut batch_indices = FxHashMap::<u64, usize>::default();
+
+        for tile in tiles {
+            let Some(tile_definition) = tile_set.tiles.try_borrow(tile.definition_handle) else {
+                continue;
+            };
+
+            let index = *batch_indices
+                .entry(tile_definition.material.key())
+                .or_insert_with(|| {
+                    batches.push(Self {
+                        material: tile_definition.material.clone(),
+                        vertices: Default::default(),
+                        triangles: Default::default(),
+                    });
+                    batches.len() - 1
+                });
+            let batch = &mut batches[index];
+
+            let position = tile.position.cast::<f32>().to_homogeneous();
+            let uv_rect = &tile_definition.uv_rect;
+            let start = batch.vertices.len() as u32;
+
+            for (offset, tex_coord) in [
+                (Vector3::new(0.0, 1.0, 0.0), uv_rect.right_top_corner()),
+                (Vector3::new(1.0, 1.0, 0.0), uv_rect.left_top_corner()),
+                (Vector3::new(1.0, 0.0, 0.0), uv_rect.left_bottom_corner()),
+                (Vector3::new(0.0, 0.0, 0.0), uv_rect.right_bottom_corner()),
+            ] {
+                batch.vertices.push(RectangleVertex {
+                    position: position + offset,
+                    tex_coord,
+                    color: tile_definition.color,
+                });
+            }
+
+            batch.triangles.extend([
+                TriangleDefinition([start, start + 1, start + 2]),
+                TriangleDefinition([start + 2, start + 3, start]),
+            ]);
+        }
+
+        batches
+    }
+}
+
+#[derive(Default)]
+struct ChunkRenderData {
+    revision: u64,
+    tile_set_revision: u64,
+    batches: Vec<TileRenderBatch>,
+}
+
+/// Cached render geometry of the chunks of a tile map.
+#[derive(Default)]
+pub(crate) struct TileMapRenderCache {
+    chunks: RefCell<FxHashMap<Vector2<i32>, ChunkRenderData>>,
+}
+
+impl Debug for TileMapRenderCache {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TileMapRenderCache")
+    }
+}
+
+impl Clone for TileMapRenderCache {
+    fn clone(&self) -> Self {
+        // Do not copy, the cache will be rebuilt on demand.
+        Self::default()
+    }
+}
+
+impl TileMapRenderCache {
+    /// Updates cached geometry of the chunks, that were changed since the last call, and calls
+    /// the given closure for every batch of every chunk that passes the given filter. Geometry is
+    /// cached in the local space of the tile map, so moving the tile map does not invalidate it.
+    pub fn visit_batches(
+        &self,
+        tiles: &Tiles,
+        tile_set: &TileSet,
+        mut filter: impl FnMut(&TileChunk) -> bool,
+        mut func: impl FnMut(&TileRenderBatch),
+    ) {
+        let mut chunks = self.chunks.borrow_mut();
+        chunks.retain(|position, _| tiles.chunk(*position).is_some());
+
+        for (position, chunk) in tiles.chunks() {
+            if !filter(chunk) {
+                continue;
+            }
+
+            let data = chunks.entry(*position).or_default();
+            if data.revision != chunk.revision() || data.tile_set_revision != tile_set.revision() {
+                *data = ChunkRenderData {
+                    revision: chunk.revision(),
+                    tile_set_revision: tile_set.revision(),
+                    batches: TileRenderBatch::build(chunk.tiles(), tile_set),
+                };
+            }
+
+            for batch in data.batches.iter() {
+                func(batch)
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct ChunkColliderData {
+    revision: u64,
+    tile_set_revision: u64,
+    vertices: Vec<Vector2<f32>>,
+    triangles: Vec<[u32; 3]>,
+}
+
+impl ChunkColliderData {
+    fn new(chunk: &TileChunk, tile_set: &TileSet) -> Self {
+        let mut solid = chunk
+            .tiles()
+            .filter(|tile| {
+                tile_set
+                    .tiles
+                    .try_borrow(tile.definition_handle)
+                    .is_some_and(|definition| definition.collider == TileCollider::Rectangle)
+            })
+            .map(|tile| tile.position)
+            .collect::<Vec<_>>();
+        solid.sort_unstable_by_key(|position| (position.y, position.x));
+
+        let mut vertices = Vec::new();
+        let mut triangles = Vec::new();
+
+        // Merge adjacent tiles in each row in a single rectangle to reduce the amount of triangles.
+        let mut i = 0;
+        while i < solid.len() {
+            let start = solid[i];
+            let mut end = start.x + 1;
+            i += 1;
+            while i < solid.len() && solid[i].y == start.y && solid[i].x == end {
+                end += 1;
+                i += 1;
+            }
+
+            let origin = vertices.len() as u32;
+            let (x0, x1) = (start.x as f32, end as f32);
+            let (y0, y1) = (start.y as f32, start.y as f32 + 1.0);
+            vertices.extend([
+                Vector2::new(x0, y0),
+                Vector2::new(x1, y0),
+                Vector2::new(x1, y1),
+                Vector2::new(x0, y1),
+            ]);
+            triangles.push([origin, origin + 1, origin + 2]);
+            triangles.push([origin, origin + 2, origin + 3]);
+        }
+
+        Self {
+            revision: chunk.revision(),
+            tile_set_revision: tile_set.revision(),
+            vertices,
+            triangles,
+        }
+    }
+}
+
+/// Cached collider geometry of the chunks of a tile map.
+#[derive(Default)]
+pub(crate) struct TileMapColliderCache {
+    chunks: RefCell<FxHashMap<Vector2<i32>, ChunkColliderData>>,
+}
+
+impl Debug for TileMapColliderCache {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TileMapColliderCache")
+    }
+}
+
+impl Clone for TileMapColliderCache {
+    fn clone(&self) -> Self {
+        // Do not copy, the cache will be rebuilt on demand.
+        Self::default()
+    }
+}
+
+impl TileMapColliderCache {
+    /// Rebuilds collider geometry of the chunks, that were changed since the last call, and
+    /// writes the geometry of all chunks in the given buffers. Vertices are transformed using the
+    /// given matrix.
+    pub fn collect(
+        &self,
+        tiles: &Tiles,
+        tile_set: &TileSet,
+        transform: &Matrix4<f32>,
+        vertices: &mut Vec<Point2<f32>>,
+        triangles: &mut Vec<[u32; 3]>,
+    ) {
+        let mut chunks = self.chunks.borrow_mut();
+        chunks.retain(|position, _| tiles.chunk(*position).is_some());
+
+        for (position, chunk) in tiles.chunks() {
+            let data = chunks.entry(*position).or_default();
+            if data.revision != chunk.revision() || data.tile_set_revision != tile_set.revision() {
+                *data = ChunkColliderData::new(chunk, tile_set);
+            }
+
+            let origin = vertices.len() as u32;
+            vertices.extend(data.vertices.iter().map(|vertex| {
+                transform
+                    .transform_point(&Point3::new(vertex.x, vertex.y, 0.0))
+                    .xy()
+            }));
+            triangles.extend(
+                data.triangles
+                    .iter()
+                    .map(|triangle| triangle.map(|index| index + origin)),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::untyped::ResourceKind,
+        core::{
+            algebra::{Matrix4, Vector2},
+            math::Rect,
+            pool::Handle,
+            visitor::prelude::*,
+        },
+        material::{Material, MaterialResource},
+        scene::tilemap::{
+            chunk::{TileChunk, TileMapColliderCache, TileMapRenderCache},
+            tileset::{TileCollider, TileDefinition, TileSet},
+            Tile, Tiles,
+        },
+    };
+
+    fn tile(x: i32, y: i32) -> Tile {
+        Tile {
+            position: Vector2::new(x, y),
+            definition_handle: Handle::new(1, 1),
+        }
+    }
+
+    #[test]
+    fn test_chunked_storage() {
+        assert_eq!(
+            TileChunk::position_of(Vector2::new(0, 15)),
+            Vector2::new(0, 0)
+        );
+        assert_eq!(
+            TileChunk::position_of(Vector2::new(16, 0)),
+            Vector2::new(1, 0)
+        );
+        assert_eq!(
+            TileChunk::position_of(Vector2::new(-1, -16)),
+            Vector2::new(-1, -1)
+        );
+        assert_eq!(
+            TileChunk::position_of(Vector2::new(-17, 0)),
+            Vector2::new(-2, 0)
+        );
+
+        let mut tiles = [tile(0, 0), tile(3, 2), tile(-1, -1), tile(-2, 5)]
+            .into_iter()
+            .collect::<Tiles>();
+        assert_eq!(tiles.len(), 4);
+        assert_eq!(tiles.chunks().count(), 3);
+        assert_eq!(tiles.bounding_rect(), Rect::new(-2, -1, 6, 7));
+        assert_eq!(
+            tiles.chunk(Vector2::new(0, 0)).unwrap().bounds(),
+            Rect::new(0, 0, 4, 3)
+        );
+
+        // Replacing a tile does not change the amount of tiles.
+        assert!(tiles.insert(tile(3, 2)).is_some());
+        assert_eq!(tiles.len(), 4);
+
+        // Only the modified chunk changes its revision.
+        let untouched_revision = tiles.chunk(Vector2::new(-1, -1)).unwrap().revision();
+        let touched_revision = tiles.chunk(Vector2::new(0, 0)).unwrap().revision();
+        assert!(tiles.remove(Vector2::new(3, 2)).is_some());
+        assert_eq!(
+            tiles.chunk(Vector2::new(-1, -1)).unwrap().revision(),
+            untouched_revision
+        );
+        let chunk = tiles.chunk(Vector2::new(0, 0)).unwrap();
+        assert_ne!(chunk.revision(), touched_revision);
+        assert_eq!(chunk.bounds(), Rect::new(0, 0, 1, 1));
+
+        // Empty chunks are removed.
+        assert!(tiles.remove(Vector2::new(-1, -1)).is_some());
+        assert!(tiles.chunk(Vector2::new(-1, -1)).is_none());
+        assert!(tiles.remove(Vector2::new(-1, -1)).is_none());
+        assert_eq!(tiles.len(), 2);
+        assert!(tiles.contains(Vector2::new(-2, 5)));
+        assert!(!tiles.contains(Vector2::new(-1, -1)));
+    }
+
+    #[test]
+    fn test_tiles_visit() {
+        let mut tiles = (-20..20).map(|x| tile(x, x * 2)).collect::<Tiles>();
+
+        let mut visitor = Visitor::new();
+        tiles.visit("Tiles", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let mut visitor = Visitor::load_from_memory(&data).unwrap();
+        let mut loaded = Tiles::default();
+        loaded.visit("Tiles", &mut visitor).unwrap();
+
+        assert_eq!(loaded, tiles);
+        assert_eq!(loaded.len(), 40);
+        assert_eq!(loaded.bounding_rect(), tiles.bounding_rect());
+    }
+
+    fn make_tile_set() -> TileSet {
+        let material = MaterialResource::new_ok(ResourceKind::Embedded, Material::standard_2d());
+        let mut tile_set = TileSet::default();
+        for collider in [TileCollider::Rectangle, TileCollider::None] {
+            tile_set.add_tile(TileDefinition {
+                material: material.clone(),
+                uv_rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+                collider,
+                ..Default::default()
+            });
+        }
+        tile_set
+    }
+
+    #[test]
+    fn test_render_cache() {
+        let tile_set = make_tile_set();
+        let solid = tile_set.tiles.handle_from_index(0);
+        let mut tiles = [Vector2::new(0, 0), Vector2::new(1, 0), Vector2::new(20, 0)]
+            .into_iter()
+            .map(|position| Tile {
+                position,
+                definition_handle: solid,
+            })
+            .collect::<Tiles>();
+
+        let cache = TileMapRenderCache::default();
+        let mut vertex_count = 0;
+        cache.visit_batches(
+            &tiles,
+            &tile_set,
+            |_| true,
+            |batch| vertex_count += batch.vertices.len(),
+        );
+        // Same material - one batch per chunk.
+        assert_eq!(vertex_count, 12);
+
+        let cached_revision = |position| cache.chunks.borrow()[&position].revision;
+        let untouched_revision = cached_revision(Vector2::new(1, 0));
+
+        tiles.remove(Vector2::new(1, 0));
+        let mut batch_count = 0;
+        cache.visit_batches(
+            &tiles,
+            &tile_set,
+            |chunk| chunk.bounds().position.x < 16,
+            |_| batch_count += 1,
+        );
+        // The second chunk is culled and not updated.
+        assert_eq!(batch_count, 1);
+        assert_eq!(cached_revision(Vector2::new(1, 0)), untouched_revision);
+        assert_eq!(
+            cache.chunks.borrow()[&Vector2::new(0, 0)].batches[0]
+                .vertices
+                .len(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_render_cache_invalidation() {
+        let mut tile_set = make_tile_set();
+        let solid = tile_set.tiles.handle_from_index(0);
+        let tiles = [Tile {
+            position: Vector2::new(0, 0),
+            definition_handle: solid,
+        }]
+        .into_iter()
+        .collect::<Tiles>();
+
+        let cache = TileMapRenderCache::default();
+        let cached_revision = || cache.chunks.borrow()[&Vector2::new(0, 0)].tile_set_revision;
+
+        cache.visit_batches(&tiles, &tile_set, |_| true, |_| {});
+        let revision = tile_set.revision();
+        assert_eq!(cached_revision(), revision);
+
+        tile_set.tiles[solid].uv_rect = Rect::new(0.5, 0.5, 0.5, 0.5);
+        tile_set.mark_modified();
+        assert_ne!(tile_set.revision(), revision);
+        // Another tile set never has the same revision.
+        assert_ne!(make_tile_set().revision(), tile_set.revision());
+
+        cache.visit_batches(&tiles, &tile_set, |_| true, |_| {});
+        assert_eq!(cached_revision(), tile_set.revision());
+        assert_eq!(
+            cache.chunks.borrow()[&Vector2::new(0, 0)].batches[0].vertices[0].tex_coord,
+            Vector2::new(1.0, 0.5)
+        );
+    }
+
+    #[test]
+    fn test_collider_cache_merges_rows() {
+        let tile_set = make_tile_set();
+        let solid = tile_set.tiles.handle_from_index(0);
+        let empty = tile_set.tiles.handle_from_index(1);
+        let mut tiles = [
+            (Vector2::new(0, 0), solid),
+            (Vector2::new(1, 0), solid),
+            (Vector2::new(2, 0), solid),
+            (Vector2::new(3, 0), empty),
+            (Vector2::new(4, 0), solid),
+            (Vector2::new(0, 1), solid),
+        ]
+        .into_iter()
+        .map(|(position, definition_handle)| Tile {
+            position,
+            definition_handle,
+        })
+        .collect::<Tiles>();
+
+        let cache = TileMapColliderCache::default();
+        let mut vertices = Vec::new();
+        let mut triangles = Vec::new();
+        cache.collect(
+            &tiles,
+            &tile_set,
+            &Matrix4::identity(),
+            &mut vertices,
+            &mut triangles,
+        );
+        // Three rectangles: [0..3]x[0..1], [4..5]x[0..1], [0..1]x[1..2].
+        assert_eq!(vertices.len(), 12);
+        assert_eq!(triangles.len(), 6);
+        assert_eq!(vertices[1].x, 3.0);
+
+        tiles.insert(Tile {
+            position: Vector2::new(3, 0),
+            definition_handle: solid,
+        });
+        vertices.clear();
+        triangles.clear();
+        cache.collect(
+            &tiles,
+            &tile_set,
+            &Matrix4::identity(),
+            &mut vertices,
+            &mut triangles,
+        );
+        assert_eq!(triangles.len(), 4);
+    }
+}
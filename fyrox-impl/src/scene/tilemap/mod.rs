@@ -21,14 +21,17 @@
 //! Tile map is a 2D "image", made out of a small blocks called tiles. Tile maps used in 2D games to
 //! build game worlds quickly and easily. See [`TileMap`] docs for more info and usage examples.
 
+pub mod autotile;
 pub mod brush;
+pub mod chunk;
+pub mod tiled;
 pub mod tileset;
 
 use crate::scene::node::constructor::NodeConstructor;
 use crate::{
     core::{
-        algebra::{Vector2, Vector3},
-        math::{aabb::AxisAlignedBoundingBox, Rect, TriangleDefinition},
+        algebra::{Matrix4, Point2, Vector2, Vector3},
+        math::{aabb::AxisAlignedBoundingBox, Rect},
         pool::Handle,
         reflect::prelude::*,
         type_traits::prelude::*,
@@ -45,15 +48,20 @@ use crate::{
         mesh::{buffer::VertexTrait, RenderPath},
         node::{Node, NodeTrait, RdcControlFlow},
         tilemap::{
+            autotile::{position_seed, TerrainMask},
             brush::{TileMapBrush, TileMapBrushResource},
-            tileset::{TileDefinitionHandle, TileSetResource},
+            chunk::{TileChunk, TileMapColliderCache, TileMapRenderCache, TileRenderBatch},
+            tileset::{TileDefinitionHandle, TileSet, TileSetResource},
         },
         Scene,
     },
 };
-use fxhash::{FxHashMap, FxHashSet};
+use fxhash::{FxHashMap, FxHashSet, FxHasher};
 use fyrox_graph::constructor::ConstructorProvider;
-use std::ops::{Deref, DerefMut};
+use std::{
+    hash::Hasher,
+    ops::{Deref, DerefMut},
+};
 
 struct BresenhamLineIter {
     dx: i32,
@@ -133,21 +141,51 @@ pub struct Tile {
     pub definition_handle: TileDefinitionHandle,
 }
 
-/// A set of tiles.
-#[derive(Clone, Reflect, Debug, Default, PartialEq)]
-pub struct Tiles(FxHashMap<Vector2<i32>, Tile>);
+/// A set of tiles. Tiles are stored in chunks (see [`TileChunk`] docs), so very large maps could be
+/// rendered and edited efficiently.
+#[derive(Clone, Reflect, Debug, Default)]
+pub struct Tiles {
+    #[reflect(hidden)]
+    chunks: FxHashMap<Vector2<i32>, TileChunk>,
+    #[reflect(hidden)]
+    count: usize,
+    #[reflect(hidden)]
+    revision: u64,
+}
+
+impl PartialEq for Tiles {
+    fn eq(&self, other: &Self) -> bool {
+        self.chunks == other.chunks
+    }
+}
 
 impl Visit for Tiles {
     fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
-        self.0.visit(name, visitor)
+        // Tiles are saved as a flat map, so the format does not depend on the chunk size.
+        let mut tiles = self
+            .iter()
+            .map(|tile| (tile.position, tile.clone()))
+            .collect::<FxHashMap<_, _>>();
+        tiles.visit(name, visitor)?;
+
+        if visitor.is_reading() {
+            self.clear();
+            for tile in tiles.into_values() {
+                self.insert(tile);
+            }
+        }
+
+        Ok(())
     }
 }
 
-impl Deref for Tiles {
-    type Target = FxHashMap<Vector2<i32>, Tile>;
-
-    fn deref(&self) -> &Self::Target {
-        &self.0
+impl FromIterator<Tile> for Tiles {
+    fn from_iter<T: IntoIterator<Item = Tile>>(iter: T) -> Self {
+        let mut tiles = Self::default();
+        for tile in iter {
+            tiles.insert(tile);
+        }
+        tiles
     }
 }
 
@@ -155,21 +193,69 @@ impl Tiles {
     /// Calculates bounding rectangle in grid coordinates.
     #[inline]
     pub fn bounding_rect(&self) -> Rect<i32> {
-        if self.0.is_empty() {
+        let mut chunks = self.chunks.values();
+        let Some(first) = chunks.next() else {
             return Rect::default();
+        };
+
+        let mut rect = first.bounds();
+        for chunk in chunks {
+            rect.push(chunk.bounds().left_top_corner());
+            rect.push(chunk.bounds().right_bottom_corner());
         }
+        rect
+    }
 
-        let mut min = Vector2::repeat(i32::MAX);
-        let mut max = Vector2::repeat(i32::MIN);
+    /// Returns total amount of tiles.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.count
+    }
 
-        for tile in self.0.values() {
-            min = tile.position.inf(&min);
+    /// Returns `true` if there are no tiles.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
 
-            let right_bottom_corner = tile.position + Vector2::repeat(1);
-            max = right_bottom_corner.sup(&max);
-        }
+    /// Tries to fetch a tile at the given position.
+    #[inline]
+    pub fn get(&self, position: Vector2<i32>) -> Option<&Tile> {
+        self.chunks
+            .get(&TileChunk::position_of(position))
+            .and_then(|chunk| chunk.get(position))
+    }
 
-        Rect::from_points(min, max)
+    /// Returns `true` if there is a tile at the given position.
+    #[inline]
+    pub fn contains(&self, position: Vector2<i32>) -> bool {
+        self.get(position).is_some()
+    }
+
+    /// Returns an iterator over all tiles. The order of the tiles is unspecified.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &Tile> {
+        self.chunks.values().flat_map(|chunk| chunk.tiles())
+    }
+
+    /// Returns an iterator over all chunks and their positions (in chunk coordinates).
+    #[inline]
+    pub fn chunks(&self) -> impl Iterator<Item = (&Vector2<i32>, &TileChunk)> {
+        self.chunks.iter()
+    }
+
+    /// Tries to fetch a chunk at the given position (in chunk coordinates). See
+    /// [`TileChunk::position_of`] to convert tile position to chunk position.
+    #[inline]
+    pub fn chunk(&self, position: Vector2<i32>) -> Option<&TileChunk> {
+        self.chunks.get(&position)
+    }
+
+    /// Returns current revision of the container. Revision changes on every modification of the
+    /// tiles.
+    #[inline]
+    pub fn revision(&self) -> u64 {
+        self.revision
     }
 
     /// Draws on the tile map using the given brush.
@@ -195,25 +281,44 @@ impl Tiles {
     /// the new one (if any).
     #[inline]
     pub fn insert(&mut self, tile: Tile) -> Option<Tile> {
-        self.0.insert(tile.position, tile)
+        self.revision = chunk::next_revision();
+        let previous = self
+            .chunks
+            .entry(TileChunk::position_of(tile.position))
+            .or_default()
+            .insert(tile);
+        if previous.is_none() {
+            self.count += 1;
+        }
+        previous
     }
 
     /// Tries to remove a tile at the given position.
     #[inline]
     pub fn remove(&mut self, position: Vector2<i32>) -> Option<Tile> {
-        self.0.remove(&position)
+        let chunk_position = TileChunk::position_of(position);
+        let chunk = self.chunks.get_mut(&chunk_position)?;
+        let tile = chunk.remove(position)?;
+        if chunk.is_empty() {
+            self.chunks.remove(&chunk_position);
+        }
+        self.count -= 1;
+        self.revision = chunk::next_revision();
+        Some(tile)
     }
 
     /// Clears the tile container.
     #[inline]
     pub fn clear(&mut self) {
-        self.0.clear();
+        self.chunks.clear();
+        self.count = 0;
+        self.revision = chunk::next_revision();
     }
 
     /// Tries to fetch tile definition index at the given point.
     #[inline]
     pub fn definition_at(&self, point: Vector2<i32>) -> Option<TileDefinitionHandle> {
-        self.0.get(&point).map(|tile| tile.definition_handle)
+        self.get(point).map(|tile| tile.definition_handle)
     }
 
     /// Fills the tile map at the given point using random tiles from the given brush. This method
@@ -425,6 +530,72 @@ impl Tiles {
             );
         }
     }
+
+    /// Paints a tile of the given terrain at the given position. The actual tile is selected
+    /// automatically, depending on the neighbouring tiles of the same terrain. Neighbouring tiles
+    /// are updated as well, so the edges and the corners of the terrain are always correct.
+    #[inline]
+    pub fn draw_terrain(&mut self, position: Vector2<i32>, terrain: u32, tile_set: &TileSet) {
+        let Some(definition_handle) =
+            tile_set.find_terrain_tile(terrain, TerrainMask::default(), 0)
+        else {
+            return;
+        };
+        self.insert(Tile {
+            position,
+            definition_handle,
+        });
+        self.update_terrain_around(position, tile_set);
+    }
+
+    /// Erases a tile at the given position and updates neighbouring terrain tiles (if any).
+    #[inline]
+    pub fn erase_terrain(&mut self, position: Vector2<i32>, tile_set: &TileSet) {
+        self.remove(position);
+        self.update_terrain_around(position, tile_set);
+    }
+
+    /// Re-selects terrain tiles at the given position and at its neighbours.
+    #[inline]
+    pub fn update_terrain_around(&mut self, position: Vector2<i32>, tile_set: &TileSet) {
+        self.update_terrain(
+            std::iter::once(position).chain(TerrainMask::OFFSETS.map(|offset| position + offset)),
+            tile_set,
+        );
+    }
+
+    /// Re-selects terrain tiles at the given positions, so they match their neighbours. Positions
+    /// without tiles or with tiles, that do not belong to any terrain, are ignored.
+    #[inline]
+    pub fn update_terrain<I>(&mut self, positions: I, tile_set: &TileSet)
+    where
+        I: IntoIterator<Item = Vector2<i32>>,
+    {
+        for position in positions {
+            let Some(terrain) = self
+                .definition_at(position)
+                .and_then(|handle| tile_set.terrain_of(handle))
+            else {
+                continue;
+            };
+            let Some(kind) = tile_set.terrains.get(terrain as usize).map(|t| t.kind) else {
+                continue;
+            };
+            let mask = TerrainMask::calculate(kind, position, |neighbour| {
+                self.definition_at(neighbour)
+                    .and_then(|handle| tile_set.terrain_of(handle))
+                    == Some(terrain)
+            });
+            if let Some(definition_handle) =
+                tile_set.find_terrain_tile(terrain, mask, position_seed(position))
+            {
+                self.insert(Tile {
+                    position,
+                    definition_handle,
+                });
+            }
+        }
+    }
 }
 
 /// Tile map is a 2D "image", made out of a small blocks called tiles. Tile maps used in 2D games to
@@ -465,6 +636,7 @@ impl Tiles {
 ///         color: Color::BROWN,
 ///         position: Default::default(),
 ///         properties: vec![],
+///         terrain: None,
 ///     });
 ///     let grass_tile = tile_set.add_tile(TileDefinition {
 ///         material,
@@ -473,6 +645,7 @@ impl Tiles {
 ///         color: Color::GREEN,
 ///         position: Default::default(),
 ///         properties: vec![],
+///         terrain: None,
 ///     });
 ///     let tile_set = TileSetResource::new_ok(ResourceKind::Embedded, tile_set);
 ///
@@ -518,6 +691,12 @@ pub struct TileMap {
     #[reflect(read_only)]
     #[visit(skip)]
     pub overlay_tiles: InheritableVariable<Tiles>,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    render_cache: TileMapRenderCache,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    collider_cache: TileMapColliderCache,
 }
 
 impl TileMap {
@@ -570,6 +749,25 @@ impl TileMap {
         self.tiles.remove(position)
     }
 
+    /// Paints a tile of the given terrain (index in [`TileSet::terrains`]) at the given position.
+    /// See [`Tiles::draw_terrain`] for more info. Does nothing if the tile set is not loaded.
+    #[inline]
+    pub fn draw_terrain(&mut self, position: Vector2<i32>, terrain: u32) {
+        if let Some(tile_set) = self.tile_set.as_ref().filter(|t| t.is_ok()).cloned() {
+            self.tiles
+                .draw_terrain(position, terrain, &tile_set.data_ref());
+        }
+    }
+
+    /// Erases a tile at the given position and updates neighbouring terrain tiles. See
+    /// [`Tiles::erase_terrain`] for more info. Does nothing if the tile set is not loaded.
+    #[inline]
+    pub fn erase_terrain(&mut self, position: Vector2<i32>) {
+        if let Some(tile_set) = self.tile_set.as_ref().filter(|t| t.is_ok()).cloned() {
+            self.tiles.erase_terrain(position, &tile_set.data_ref());
+        }
+    }
+
     /// Returns active brush of the tile map.
     #[inline]
     pub fn active_brush(&self) -> Option<TileMapBrushResource> {
@@ -600,6 +798,41 @@ impl TileMap {
         self.tiles.bounding_rect()
     }
 
+    /// Calculates a hash of everything that affects collider geometry of the tile map, except its
+    /// transform. It is used by physics to rebuild colliders only when the tiles were changed.
+    pub(crate) fn collider_geometry_hash(&self) -> u64 {
+        let mut hasher = FxHasher::default();
+        hasher.write_u64(self.tiles.revision());
+        hasher.write_u32(self.tile_scale.x.to_bits());
+        hasher.write_u32(self.tile_scale.y.to_bits());
+        if let Some(tile_set) = self.tile_set.as_ref() {
+            hasher.write_u64(tile_set.key());
+            if let Some(tile_set) = tile_set.data_ref().as_loaded_ref() {
+                hasher.write_u64(tile_set.revision());
+            }
+        }
+        hasher.finish()
+    }
+
+    /// Writes collider geometry of the tile map to the given buffers. Geometry is cached per chunk
+    /// and rebuilt only for the chunks that were changed since the last call.
+    pub(crate) fn collect_collider_geometry(
+        &self,
+        transform: &Matrix4<f32>,
+        vertices: &mut Vec<Point2<f32>>,
+        triangles: &mut Vec<[u32; 3]>,
+    ) {
+        let Some(tile_set_resource) = self.tile_set.as_ref() else {
+            return;
+        };
+        let tile_set = tile_set_resource.data_ref();
+        let Some(tile_set) = tile_set.as_loaded_ref() else {
+            return;
+        };
+        self.collider_cache
+            .collect(&self.tiles, tile_set, transform, vertices, triangles)
+    }
+
     /// Calculates grid-space position (tile coordinates) from world-space. Could be used to find
     /// tile coordinates from arbitrary point in world space. It is especially useful, if the tile
     /// map is rotated or shifted.
@@ -631,6 +864,8 @@ impl Default for TileMap {
             brushes: Default::default(),
             active_brush: Default::default(),
             overlay_tiles: Default::default(),
+            render_cache: Default::default(),
+            collider_cache: Default::default(),
         }
     }
 }
@@ -663,12 +898,7 @@ impl ConstructorProvider<Node, Graph> for TileMap {
 
 impl NodeTrait for TileMap {
     fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
-        let rect = self.bounding_rect();
-
-        let min_pos = rect.position.cast::<f32>().to_homogeneous();
-        let max_pos = (rect.position + rect.size).cast::<f32>().to_homogeneous();
-
-        AxisAlignedBoundingBox::from_min_max(min_pos, max_pos)
+        chunk::rect_to_aabb(self.bounding_rect())
     }
 
     fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
@@ -699,70 +929,57 @@ impl NodeTrait for TileMap {
 
         let tile_set = tile_set_resource.data_ref();
 
-        for tiles in [&self.tiles, &self.overlay_tiles] {
-            for tile in tiles.values() {
-                let Some(tile_definition) = tile_set.tiles.try_borrow(tile.definition_handle)
-                else {
-                    continue;
-                };
-
-                let global_transform = self.global_transform();
-
-                let position = tile.position.cast::<f32>().to_homogeneous();
-
-                let vertices = [
-                    RectangleVertex {
-                        position: global_transform
-                            .transform_point(&(position + Vector3::new(0.0, 1.0, 0.0)).into())
-                            .coords,
-                        tex_coord: tile_definition.uv_rect.right_top_corner(),
-                        color: tile_definition.color,
-                    },
-                    RectangleVertex {
-                        position: global_transform
-                            .transform_point(&(position + Vector3::new(1.0, 1.0, 0.0)).into())
-                            .coords,
-                        tex_coord: tile_definition.uv_rect.left_top_corner(),
-                        color: tile_definition.color,
-                    },
-                    RectangleVertex {
-                        position: global_transform
-                            .transform_point(&(position + Vector3::new(1.00, 0.0, 0.0)).into())
-                            .coords,
-                        tex_coord: tile_definition.uv_rect.left_bottom_corner(),
-                        color: tile_definition.color,
-                    },
-                    RectangleVertex {
-                        position: global_transform
-                            .transform_point(&(position + Vector3::new(0.0, 0.0, 0.0)).into())
-                            .coords,
-                        tex_coord: tile_definition.uv_rect.right_bottom_corner(),
-                        color: tile_definition.color,
-                    },
-                ];
-
-                let triangles = [TriangleDefinition([0, 1, 2]), TriangleDefinition([2, 3, 0])];
-
-                let sort_index = ctx.calculate_sorting_index(self.global_position());
-
-                ctx.storage.push_triangles(
-                    RectangleVertex::layout(),
-                    &tile_definition.material,
-                    RenderPath::Forward,
-                    sort_index,
-                    self.handle(),
-                    &mut move |mut vertex_buffer, mut triangle_buffer| {
-                        let start_vertex_index = vertex_buffer.vertex_count();
-
-                        vertex_buffer.push_vertices(&vertices).unwrap();
-
-                        triangle_buffer.push_triangles_iter_with_offset(
-                            start_vertex_index,
-                            triangles.into_iter(),
-                        );
-                    },
-                );
-            }
+        let global_transform = self.global_transform();
+        let sort_index = ctx.calculate_sorting_index(self.global_position());
+        let handle = self.handle();
+        let frustum = ctx.frustum.filter(|_| self.frustum_culling());
+        let storage = &mut *ctx.storage;
+
+        let mut push_batch = |batch: &TileRenderBatch| {
+            storage.push_triangles(
+                RectangleVertex::layout(),
+                &batch.material,
+                RenderPath::Forward,
+                sort_index,
+                handle,
+                &mut |mut vertex_buffer, mut triangle_buffer| {
+                    let start_vertex_index = vertex_buffer.vertex_count();
+
+                    vertex_buffer
+                        .push_vertices_transform(&batch.vertices, |vertex| RectangleVertex {
+                            position: global_transform
+                                .transform_point(&vertex.position.into())
+                                .coords,
+                            ..*vertex
+                        })
+                        .unwrap();
+
+                    triangle_buffer.push_triangles_iter_with_offset(
+                        start_vertex_index,
+                        batch.triangles.iter().cloned(),
+                    );
+                },
+            );
+        };
+
+        // Geometry of the chunks is cached in local space and rebuilt only when the chunk or the
+        // tile set is changed, chunks outside the frustum are skipped.
+        self.render_cache.visit_batches(
+            &self.tiles,
+            &tile_set,
+            |chunk| {
+                frustum.map_or(true, |frustum| {
+                    frustum.is_intersects_aabb(
+                        &chunk.local_bounding_box().transform(&global_transform),
+                    )
+                })
+            },
+            &mut push_batch,
+        );
+
+        // Overlay tiles are changing very frequently, there's no need to cache them.
+        for batch in TileRenderBatch::build(self.overlay_tiles.iter(), &tile_set) {
+            push_batch(&batch);
         }
 
         RdcControlFlow::Continue
@@ -835,6 +1052,8 @@ impl TileMapBuilder {
             brushes: self.brushes.into(),
             active_brush: Default::default(),
             overlay_tiles: Default::default(),
+            render_cache: Default::default(),
+            collider_cache: Default::default(),
         })
     }
 
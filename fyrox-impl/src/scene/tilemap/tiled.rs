@@ -0,0 +1,879 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Importer for tile sets (`.tsx`) and maps (`.tmx`) made in [Tiled](https://www.mapeditor.org/)
+//! map editor. Tile sets are imported as [`TileSet`] resources, maps are imported as model
+//! resources (prefabs) with one [`super::TileMap`] node per layer.
+//!
+//! ## Limitations
+//!
+//! - Only orthogonal maps are supported, other orientations are imported as orthogonal.
+//! - Flipped and rotated tiles are imported without flipping or rotation.
+//! - Object layers and image layers are ignored.
+//! - `zstd` compression of tile data is not supported.
+//! - If a map uses more than one tile set, then all of them are merged into a single embedded
+//!   tile set, because a tile map can have only one tile set.
+
+use crate::{
+    asset::{io::ResourceIo, manager::ResourceManager, untyped::ResourceKind},
+    core::{
+        algebra::{Vector2, Vector3},
+        io::FileLoadError,
+        log::Log,
+        math::Rect,
+        pool::Handle,
+    },
+    material::{Material, MaterialResource},
+    resource::texture::Texture,
+    scene::{
+        base::{BaseBuilder, Property, PropertyValue},
+        node::Node,
+        pivot::PivotBuilder,
+        tilemap::{
+            tileset::{
+                TileCollider, TileDefinition, TileDefinitionHandle, TileSet, TileSetResource,
+            },
+            Tile, TileMapBuilder, Tiles,
+        },
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+use base64::Engine;
+use fxhash::FxHashMap;
+use std::{
+    fmt::{Display, Formatter},
+    path::{Component, Path, PathBuf},
+    str::FromStr,
+};
+use xml::reader::{EventReader, XmlEvent};
+
+/// An error that may occur during Tiled map or tile set import.
+#[derive(Debug)]
+pub enum TiledError {
+    /// An i/o error has occurred.
+    Io(FileLoadError),
+    /// The file is not a valid XML document.
+    Xml(xml::reader::Error),
+    /// The file is a valid XML document, but its content is not a valid Tiled map or tile set.
+    InvalidFormat(String),
+}
+
+impl Display for TiledError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(v) => {
+                write!(f, "A file load error has occurred {v:?}")
+            }
+            Self::Xml(v) => {
+                write!(f, "Failed to parse XML document. Reason: {v}")
+            }
+            Self::InvalidFormat(v) => {
+                write!(f, "Invalid Tiled file. Reason: {v}")
+            }
+        }
+    }
+}
+
+impl From<FileLoadError> for TiledError {
+    fn from(e: FileLoadError) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<xml::reader::Error> for TiledError {
+    fn from(e: xml::reader::Error) -> Self {
+        Self::Xml(e)
+    }
+}
+
+/// A tiny DOM, Tiled files are small enough to be loaded in memory completely.
+#[derive(Default, Debug)]
+struct Element {
+    name: String,
+    attributes: FxHashMap<String, String>,
+    children: Vec<Element>,
+    text: String,
+}
+
+impl Element {
+    fn parse(bytes: &[u8]) -> Result<Self, TiledError> {
+        let mut stack: Vec<Element> = vec![Element::default()];
+        for event in EventReader::new(bytes) {
+            match event? {
+                XmlEvent::StartElement {
+                    name, attributes, ..
+                } => stack.push(Element {
+                    name: name.local_name,
+                    attributes: attributes
+                        .into_iter()
+                        .map(|attribute| (attribute.name.local_name, attribute.value))
+                        .collect(),
+                    ..Default::default()
+                }),
+                XmlEvent::EndElement { .. } => {
+                    if let Some(element) = stack.pop() {
+                        if let Some(parent) = stack.last_mut() {
+                            parent.children.push(element);
+                        }
+                    }
+                }
+                XmlEvent::Characters(text) | XmlEvent::CData(text) => {
+                    if let Some(element) = stack.last_mut() {
+                        element.text.push_str(&text);
+                    }
+                }
+                _ => (),
+            }
+        }
+        stack
+            .pop()
+            .and_then(|document| document.children.into_iter().next())
+            .ok_or_else(|| TiledError::InvalidFormat("Empty document".to_string()))
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(|value| value.as_str())
+    }
+
+    fn parse_attribute<T: FromStr>(&self, name: &str) -> Option<T> {
+        self.attribute(name).and_then(|value| value.parse().ok())
+    }
+
+    fn required_attribute<T: FromStr>(&self, name: &str) -> Result<T, TiledError> {
+        self.parse_attribute(name).ok_or_else(|| {
+            TiledError::InvalidFormat(format!(
+                "Element {} has missing or invalid attribute {name}",
+                self.name
+            ))
+        })
+    }
+
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+}
+
+/// Resolves a path, that is relative to the given directory, removing `.` and `..` components
+/// where possible, so the resource manager will see the same path for the same file.
+fn resolve_path(directory: &Path, relative: &str) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in directory.join(relative).components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir => {
+                if matches!(result.components().next_back(), Some(Component::Normal(_))) {
+                    result.pop();
+                } else {
+                    result.push("..");
+                }
+            }
+            component => result.push(component),
+        }
+    }
+    result
+}
+
+/// Converts `<properties>` element of a Tiled object to a set of properties. Integer and object
+/// properties are converted to [`PropertyValue::I32`], float properties to [`PropertyValue::F32`],
+/// boolean properties to [`PropertyValue::U8`] (0 or 1), everything else to
+/// [`PropertyValue::String`].
+fn read_properties(element: &Element) -> Vec<Property> {
+    let Some(properties) = element.child("properties") else {
+        return Default::default();
+    };
+
+    properties
+        .children("property")
+        .filter_map(|property| {
+            let name = property.attribute("name")?.to_string();
+            let value = property
+                .attribute("value")
+                .map(|value| value.to_string())
+                .unwrap_or_else(|| property.text.clone());
+            let value = match property.attribute("type").unwrap_or("string") {
+                "int" | "object" => PropertyValue::I32(value.parse().ok()?),
+                "float" => PropertyValue::F32(value.parse().ok()?),
+                "bool" => PropertyValue::U8((value == "true") as u8),
+                _ => PropertyValue::String(value),
+            };
+            Some(Property { name, value })
+        })
+        .collect()
+}
+
+fn make_material(resource_manager: &ResourceManager, image_path: PathBuf) -> MaterialResource {
+    let mut material = Material::standard_2d();
+    material.bind(
+        "diffuseTexture",
+        resource_manager.request::<Texture>(image_path),
+    );
+    MaterialResource::new_ok(ResourceKind::Embedded, material)
+}
+
+/// Selects a collider for a tile using its collision shapes. A single rectangle, that covers
+/// the entire tile, is imported as [`TileCollider::Rectangle`], any other shapes are imported as
+/// [`TileCollider::Mesh`].
+fn read_collider(tile: &Element, tile_size: Vector2<f32>) -> TileCollider {
+    let Some(object_group) = tile.child("objectgroup") else {
+        return TileCollider::None;
+    };
+
+    let objects = object_group.children("object").collect::<Vec<_>>();
+    match objects.as_slice() {
+        [] => TileCollider::None,
+        [object] => {
+            let is_rectangle = object.children.is_empty();
+            let value = |name: &str| object.parse_attribute::<f32>(name).unwrap_or_default();
+            let covers_tile = value("x").abs() < 0.5
+                && value("y").abs() < 0.5
+                && (value("width") - tile_size.x).abs() < 0.5
+                && (value("height") - tile_size.y).abs() < 0.5;
+            if is_rectangle && covers_tile {
+                TileCollider::Rectangle
+            } else {
+                TileCollider::Mesh
+            }
+        }
+        _ => TileCollider::Mesh,
+    }
+}
+
+/// A tile set imported from Tiled, with the mapping between Tiled tile ids and tile definitions.
+struct ImportedTileSet {
+    tile_set: TileSet,
+    handles: FxHashMap<u32, TileDefinitionHandle>,
+}
+
+impl ImportedTileSet {
+    fn from_element(
+        element: &Element,
+        directory: &Path,
+        resource_manager: &ResourceManager,
+    ) -> Result<Self, TiledError> {
+        if element.name != "tileset" {
+            return Err(TiledError::InvalidFormat(format!(
+                "Expected tileset element, got {}",
+                element.name
+            )));
+        }
+
+        let tile_width = element.required_attribute::<u32>("tilewidth")?;
+        let tile_height = element.required_attribute::<u32>("tileheight")?;
+        let tile_size = Vector2::new(tile_width as f32, tile_height as f32);
+        let margin = element.parse_attribute::<u32>("margin").unwrap_or_default();
+        let spacing = element
+            .parse_attribute::<u32>("spacing")
+            .unwrap_or_default();
+
+        let mut definitions = Vec::<(u32, TileDefinition)>::new();
+
+        if let Some(image) = element.child("image") {
+            // Tiles are stored in a single atlas.
+            let source = image.required_attribute::<String>("source")?;
+            let image_size = Vector2::new(
+                image.required_attribute::<u32>("width")?,
+                image.required_attribute::<u32>("height")?,
+            );
+            let columns = element
+                .parse_attribute::<u32>("columns")
+                .unwrap_or_else(|| {
+                    (image_size.x.saturating_sub(margin) + spacing) / (tile_width + spacing).max(1)
+                });
+            let tile_count = element
+                .parse_attribute::<u32>("tilecount")
+                .unwrap_or_else(|| {
+                    let rows = (image_size.y.saturating_sub(margin) + spacing)
+                        / (tile_height + spacing).max(1);
+                    rows * columns
+                });
+            if columns == 0 || image_size.x == 0 || image_size.y == 0 {
+                return Err(TiledError::InvalidFormat(
+                    "Tile set image is empty".to_string(),
+                ));
+            }
+
+            let material = make_material(resource_manager, resolve_path(directory, &source));
+
+            for id in 0..tile_count {
+                let column = id % columns;
+                let row = id / columns;
+                let x = margin + column * (tile_width + spacing);
+                let y = margin + row * (tile_height + spacing);
+                definitions.push((
+                    id,
+                    TileDefinition {
+                        material: material.clone(),
+                        uv_rect: Rect::new(
+                            x as f32 / image_size.x as f32,
+                            y as f32 / image_size.y as f32,
+                            tile_width as f32 / image_size.x as f32,
+                            tile_height as f32 / image_size.y as f32,
+                        ),
+                        collider: TileCollider::None,
+                        position: Vector2::new(column as i32, row as i32),
+                        ..Default::default()
+                    },
+                ));
+            }
+        } else {
+            // Collection of images, every tile has its own image.
+            for (i, tile) in element.children("tile").enumerate() {
+                let id = tile.required_attribute::<u32>("id")?;
+                let Some(source) = tile
+                    .child("image")
+                    .and_then(|image| image.attribute("source"))
+                else {
+                    continue;
+                };
+                definitions.push((
+                    id,
+                    TileDefinition {
+                        material: make_material(resource_manager, resolve_path(directory, source)),
+                        uv_rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+                        collider: TileCollider::None,
+                        position: Vector2::new(i as i32, 0),
+                        ..Default::default()
+                    },
+                ));
+            }
+        }
+
+        // Apply per-tile data.
+        for tile in element.children("tile") {
+            let id = tile.required_attribute::<u32>("id")?;
+            if let Some((_, definition)) = definitions.iter_mut().find(|(i, _)| *i == id) {
+                definition.properties = read_properties(tile);
+                definition.collider = read_collider(tile, tile_size);
+            }
+        }
+
+        let mut tile_set = TileSet::default();
+        let handles = definitions
+            .into_iter()
+            .map(|(id, definition)| (id, tile_set.add_tile(definition)))
+            .collect();
+
+        Ok(Self { tile_set, handles })
+    }
+
+    async fn load(
+        path: &Path,
+        resource_manager: &ResourceManager,
+        io: &dyn ResourceIo,
+    ) -> Result<Self, TiledError> {
+        let bytes = io.load_file(path).await?;
+        let element = Element::parse(&bytes)?;
+        Self::from_element(
+            &element,
+            path.parent().unwrap_or(Path::new("")),
+            resource_manager,
+        )
+    }
+}
+
+/// Loads a Tiled tile set (`.tsx`) from the given path.
+pub async fn load_tsx(
+    path: &Path,
+    resource_manager: ResourceManager,
+    io: &dyn ResourceIo,
+) -> Result<TileSet, TiledError> {
+    Ok(ImportedTileSet::load(path, &resource_manager, io)
+        .await?
+        .tile_set)
+}
+
+/// Decodes the content of `<data>` or `<chunk>` element to a list of global tile ids.
+fn decode_tile_data(
+    data: &Element,
+    encoding: Option<&str>,
+    compression: Option<&str>,
+) -> Result<Vec<u32>, TiledError> {
+    match encoding {
+        None => Ok(data
+            .children("tile")
+            .map(|tile| tile.parse_attribute("gid").unwrap_or_default())
+            .collect()),
+        Some("csv") => data
+            .text
+            .split(',')
+            .map(|gid| gid.trim())
+            .filter(|gid| !gid.is_empty())
+            .map(|gid| {
+                gid.parse()
+                    .map_err(|_| TiledError::InvalidFormat(format!("Invalid tile id {gid}")))
+            })
+            .collect(),
+        Some("base64") => {
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(data.text.trim())
+                .map_err(|e| TiledError::InvalidFormat(e.to_string()))?;
+            let bytes = match compression {
+                None => bytes,
+                Some("zlib") => {
+                    inflate::inflate_bytes_zlib(&bytes).map_err(TiledError::InvalidFormat)?
+                }
+                Some("gzip") => gunzip(&bytes)?,
+                Some(compression) => {
+                    return Err(TiledError::InvalidFormat(format!(
+                        "Unsupported compression {compression}"
+                    )))
+                }
+            };
+            Ok(bytes
+                .chunks_exact(4)
+                .map(|gid| u32::from_le_bytes([gid[0], gid[1], gid[2], gid[3]]))
+                .collect())
+        }
+        Some(encoding) => Err(TiledError::InvalidFormat(format!(
+            "Unsupported encoding {encoding}"
+        ))),
+    }
+}
+
+/// Decompresses gzip data. Only the header is parsed, the checksum is ignored.
+fn gunzip(bytes: &[u8]) -> Result<Vec<u8>, TiledError> {
+    const FHCRC: u8 = 1 << 1;
+    const FEXTRA: u8 = 1 << 2;
+    const FNAME: u8 = 1 << 3;
+    const FCOMMENT: u8 = 1 << 4;
+
+    let invalid = || TiledError::InvalidFormat("Invalid gzip data".to_string());
+
+    if bytes.len() < 18 || bytes[0] != 0x1f || bytes[1] != 0x8b || bytes[2] != 8 {
+        return Err(invalid());
+    }
+    let flags = bytes[3];
+    let mut offset = 10;
+    if flags & FEXTRA != 0 {
+        let length = u16::from_le_bytes([bytes[offset], bytes[offset + 1]]) as usize;
+        offset += 2 + length;
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            let end = bytes.get(offset..).ok_or_else(invalid)?;
+            offset += end.iter().position(|b| *b == 0).ok_or_else(invalid)? + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        offset += 2;
+    }
+    let compressed = bytes.get(offset..bytes.len() - 8).ok_or_else(invalid)?;
+    inflate::inflate_bytes(compressed).map_err(TiledError::InvalidFormat)
+}
+
+/// Tiled uses the highest bits of global tile ids to store flipping flags.
+const GID_FLAGS_MASK: u32 = 0xF000_0000;
+
+struct MapContext<'a> {
+    // Sorted by first global id.
+    tile_sets: Vec<(u32, FxHashMap<u32, TileDefinitionHandle>)>,
+    tile_set_resource: TileSetResource,
+    tile_size: Vector2<f32>,
+    has_flipped_tiles: bool,
+    scene: &'a mut Scene,
+}
+
+impl MapContext<'_> {
+    fn definition(&self, gid: u32) -> Option<TileDefinitionHandle> {
+        let (first_gid, handles) = self
+            .tile_sets
+            .iter()
+            .rev()
+            .find(|(first_gid, _)| *first_gid <= gid)?;
+        handles.get(&(gid - first_gid)).cloned()
+    }
+
+    fn add_tiles(&mut self, tiles: &mut Tiles, origin: Vector2<i32>, width: u32, gids: &[u32]) {
+        if width == 0 {
+            return;
+        }
+        for (i, gid) in gids.iter().enumerate() {
+            if *gid & GID_FLAGS_MASK != 0 {
+                self.has_flipped_tiles = true;
+            }
+            let Some(definition_handle) = self.definition(*gid & !GID_FLAGS_MASK) else {
+                continue;
+            };
+            let x = origin.x + (i as u32 % width) as i32;
+            let y = origin.y + (i as u32 / width) as i32;
+            tiles.insert(Tile {
+                // Tiled uses Y-down coordinate system.
+                position: Vector2::new(x, -y),
+                definition_handle,
+            });
+        }
+    }
+
+    fn base_builder(&self, layer: &Element) -> BaseBuilder {
+        let offset = Vector2::new(
+            layer.parse_attribute::<f32>("offsetx").unwrap_or_default() / self.tile_size.x,
+            -layer.parse_attribute::<f32>("offsety").unwrap_or_default() / self.tile_size.y,
+        );
+        BaseBuilder::new()
+            .with_name(layer.attribute("name").unwrap_or_default())
+            .with_visibility(layer.attribute("visible") != Some("0"))
+            .with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(offset.x, offset.y, 0.0))
+                    .build(),
+            )
+    }
+
+    fn import_tile_layer(&mut self, layer: &Element) -> Result<Handle<Node>, TiledError> {
+        let mut tiles = Tiles::default();
+        if let Some(data) = layer.child("data") {
+            let encoding = data.attribute("encoding");
+            let compression = data.attribute("compression");
+            if data.child("chunk").is_some() {
+                // Infinite maps store tiles in chunks.
+                for chunk in data.children("chunk") {
+                    let gids = decode_tile_data(chunk, encoding, compression)?;
+                    let origin = Vector2::new(
+                        chunk.required_attribute("x")?,
+                        chunk.required_attribute("y")?,
+                    );
+                    self.add_tiles(
+                        &mut tiles,
+                        origin,
+                        chunk.required_attribute("width")?,
+                        &gids,
+                    );
+                }
+            } else {
+                let gids = decode_tile_data(data, encoding, compression)?;
+                self.add_tiles(
+                    &mut tiles,
+                    Vector2::default(),
+                    layer.required_attribute("width")?,
+                    &gids,
+                );
+            }
+        }
+
+        let handle = TileMapBuilder::new(self.base_builder(layer))
+            .with_tile_set(self.tile_set_resource.clone())
+            .with_tiles(tiles)
+            .build(&mut self.scene.graph);
+        self.scene.graph[handle].set_properties(read_properties(layer));
+        Ok(handle)
+    }
+
+    fn import_layers(&mut self, parent: &Element) -> Result<Vec<Handle<Node>>, TiledError> {
+        let mut layers = Vec::new();
+        for child in parent.children.iter() {
+            match child.name.as_str() {
+                "layer" => layers.push(self.import_tile_layer(child)?),
+                "group" => {
+                    let children = self.import_layers(child)?;
+                    let group =
+                        PivotBuilder::new(self.base_builder(child).with_children(&children))
+                            .build(&mut self.scene.graph);
+                    self.scene.graph[group].set_properties(read_properties(child));
+                    layers.push(group);
+                }
+                _ => (),
+            }
+        }
+        Ok(layers)
+    }
+}
+
+/// Loads a Tiled map (`.tmx`) to the given scene. Every tile layer of the map is imported as a
+/// separate tile map node, groups of layers are imported as pivots.
+pub async fn load_tmx_to_scene(
+    scene: &mut Scene,
+    resource_manager: ResourceManager,
+    io: &dyn ResourceIo,
+    path: &Path,
+) -> Result<(), TiledError> {
+    let bytes = io.load_file(path).await?;
+    let map = Element::parse(&bytes)?;
+    if map.name != "map" {
+        return Err(TiledError::InvalidFormat(format!(
+            "Expected map element, got {}",
+            map.name
+        )));
+    }
+
+    if let Some(orientation) = map.attribute("orientation").filter(|o| *o != "orthogonal") {
+        Log::warn(format!(
+            "{} map orientation is not supported, the map {} will be imported as orthogonal.",
+            orientation,
+            path.display()
+        ));
+    }
+
+    let directory = path.parent().unwrap_or(Path::new(""));
+
+    let mut tile_sets = Vec::new();
+    for tile_set in map.children("tileset") {
+        let first_gid = tile_set.required_attribute::<u32>("firstgid")?;
+        let (imported, external_path) = if let Some(source) = tile_set.attribute("source") {
+            let tile_set_path = resolve_path(directory, source);
+            (
+                ImportedTileSet::load(&tile_set_path, &resource_manager, io).await?,
+                Some(tile_set_path),
+            )
+        } else {
+            (
+                ImportedTileSet::from_element(tile_set, directory, &resource_manager)?,
+                None,
+            )
+        };
+        tile_sets.push((first_gid, imported, external_path));
+    }
+    tile_sets.sort_by_key(|(first_gid, _, _)| *first_gid);
+
+    let (tile_set_resource, tile_sets) = if tile_sets.len() == 1 {
+        let (first_gid, imported, external_path) = tile_sets.pop().unwrap();
+        let resource = match external_path {
+            Some(external_path) => resource_manager.request::<TileSet>(external_path),
+            None => TileSetResource::new_ok(ResourceKind::Embedded, imported.tile_set),
+        };
+        (resource, vec![(first_gid, imported.handles)])
+    } else {
+        // Merge all tile sets into one.
+        let mut merged = TileSet::default();
+        let mut mapping = Vec::new();
+        for (first_gid, mut imported, _) in tile_sets {
+            let handles = imported
+                .handles
+                .into_iter()
+                .filter_map(|(id, handle)| {
+                    let definition = imported.tile_set.tiles.try_free(handle)?;
+                    Some((id, merged.add_tile(definition)))
+                })
+                .collect();
+            mapping.push((first_gid, handles));
+        }
+        (
+            TileSetResource::new_ok(ResourceKind::Embedded, merged),
+            mapping,
+        )
+    };
+
+    let mut context = MapContext {
+        tile_sets,
+        tile_set_resource,
+        tile_size: Vector2::new(
+            map.required_attribute::<f32>("tilewidth")?,
+            map.required_attribute::<f32>("tileheight")?,
+        ),
+        has_flipped_tiles: false,
+        scene,
+    };
+    context.import_layers(&map)?;
+
+    if context.has_flipped_tiles {
+        Log::warn(format!(
+            "The map {} contains flipped or rotated tiles, the flipping and rotation is ignored.",
+            path.display()
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::{io::FsResourceIo, manager::ResourceManager},
+        core::{algebra::Vector2, futures::executor::block_on},
+        engine::{self, SerializationContext},
+        graph::SceneGraph,
+        scene::{
+            base::PropertyValue,
+            tilemap::{
+                tiled::{decode_tile_data, load_tmx_to_scene, load_tsx, Element},
+                tileset::{TileCollider, TileSet},
+                TileMap,
+            },
+            Scene,
+        },
+    };
+    use std::{
+        path::{Path, PathBuf},
+        sync::Arc,
+    };
+
+    const TSX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<tileset version="1.10" name="terrain" tilewidth="16" tileheight="16" tilecount="4" columns="2">
+ <image source="terrain.png" width="32" height="32"/>
+ <tile id="1">
+  <properties>
+   <property name="kind" value="water"/>
+   <property name="damage" type="int" value="5"/>
+   <property name="slippery" type="bool" value="true"/>
+  </properties>
+  <objectgroup draworder="index">
+   <object id="1" x="0" y="0" width="16" height="16"/>
+  </objectgroup>
+ </tile>
+ <tile id="3">
+  <objectgroup draworder="index">
+   <object id="1" x="0" y="0">
+    <polygon points="0,0 16,16 0,16"/>
+   </object>
+  </objectgroup>
+ </tile>
+</tileset>
+"#;
+
+    const TMX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" renderorder="right-down" width="2" height="2" tilewidth="16" tileheight="16" infinite="0">
+ <tileset firstgid="1" source="terrain.tsx"/>
+ <layer id="1" name="Ground" width="2" height="2">
+  <properties>
+   <property name="z" type="float" value="1.5"/>
+  </properties>
+  <data encoding="csv">
+1,2,
+0,4
+</data>
+ </layer>
+ <group id="2" name="Group" offsetx="32" offsety="16">
+  <layer id="3" name="Details" width="2" height="2" visible="0">
+   <data encoding="base64">AQAAAAIAAAAAAAAAAwAAAA==</data>
+  </layer>
+ </group>
+</map>
+"#;
+
+    fn make_resource_manager() -> ResourceManager {
+        let resource_manager = ResourceManager::new(Arc::new(Default::default()));
+        engine::initialize_resource_manager_loaders(
+            &resource_manager,
+            Arc::new(SerializationContext::new()),
+        );
+        resource_manager
+    }
+
+    fn write_test_files(name: &str) -> PathBuf {
+        let directory = Path::new("test_output/tiled").join(name);
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(directory.join("terrain.tsx"), TSX).unwrap();
+        std::fs::write(directory.join("map.tmx"), TMX).unwrap();
+        directory
+    }
+
+    #[test]
+    fn test_decode_compressed_tile_data() {
+        let zlib = Element {
+            text: "eJxjZGBgYGKAAGYgBgAARAAH".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            decode_tile_data(&zlib, Some("base64"), Some("zlib")).unwrap(),
+            vec![1, 2, 0, 3]
+        );
+
+        let gzip = Element {
+            text: "H4sIAAAAAAACA2NkYGBgYoAAZiAGALXrXbwQAAAA".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            decode_tile_data(&gzip, Some("base64"), Some("gzip")).unwrap(),
+            vec![1, 2, 0, 3]
+        );
+
+        assert!(decode_tile_data(&gzip, Some("base64"), Some("zstd")).is_err());
+    }
+
+    #[test]
+    fn test_load_tsx() {
+        let directory = write_test_files("tsx");
+        let resource_manager = make_resource_manager();
+        let tile_set = block_on(load_tsx(
+            &directory.join("terrain.tsx"),
+            resource_manager,
+            &FsResourceIo,
+        ))
+        .unwrap();
+
+        let tiles = tile_set.tiles.iter().collect::<Vec<_>>();
+        assert_eq!(tiles.len(), 4);
+
+        assert_eq!(tiles[1].position, Vector2::new(1, 0));
+        assert_eq!(tiles[1].uv_rect.position, Vector2::new(0.5, 0.0));
+        assert_eq!(tiles[1].uv_rect.size, Vector2::new(0.5, 0.5));
+        assert_eq!(tiles[1].collider, TileCollider::Rectangle);
+        assert_eq!(tiles[1].properties.len(), 3);
+        assert_eq!(tiles[1].properties[0].name, "kind");
+        assert_eq!(
+            tiles[1].properties[0].value,
+            PropertyValue::String("water".to_string())
+        );
+        assert_eq!(tiles[1].properties[1].value, PropertyValue::I32(5));
+        assert_eq!(tiles[1].properties[2].value, PropertyValue::U8(1));
+
+        assert_eq!(tiles[0].collider, TileCollider::None);
+        assert_eq!(tiles[3].collider, TileCollider::Mesh);
+        assert_eq!(tiles[3].position, Vector2::new(1, 1));
+    }
+
+    #[test]
+    fn test_load_tmx() {
+        let directory = write_test_files("tmx");
+        let resource_manager = make_resource_manager();
+        let mut scene = Scene::new();
+        block_on(load_tmx_to_scene(
+            &mut scene,
+            resource_manager.clone(),
+            &FsResourceIo,
+            &directory.join("map.tmx"),
+        ))
+        .unwrap();
+
+        let (ground_handle, ground) = scene.graph.find_by_name_from_root("Ground").unwrap();
+        let ground = ground.cast::<TileMap>().unwrap();
+        assert_eq!(ground.tiles().len(), 3);
+        for position in [Vector2::new(0, 0), Vector2::new(1, 0), Vector2::new(1, -1)] {
+            assert!(ground.tiles().get(position).is_some());
+        }
+        assert!(ground.tiles().get(Vector2::new(0, -1)).is_none());
+        assert_eq!(ground.properties.len(), 1);
+        assert_eq!(ground.properties[0].value, PropertyValue::F32(1.5));
+
+        let (group_handle, group) = scene.graph.find_by_name_from_root("Group").unwrap();
+        assert_eq!(
+            **group.local_transform().position(),
+            crate::core::algebra::Vector3::new(2.0, -1.0, 0.0)
+        );
+        let (details_handle, details) = scene.graph.find_by_name_from_root("Details").unwrap();
+        assert_eq!(details.parent(), group_handle);
+        assert!(!details.visibility());
+        assert_eq!(details.cast::<TileMap>().unwrap().tiles().len(), 3);
+
+        assert_ne!(ground_handle, details_handle);
+
+        // A single external tile set is shared between the map and the tile set file.
+        let tile_set = ground.tile_set().cloned().unwrap();
+        assert_eq!(
+            tile_set,
+            resource_manager.request::<TileSet>(directory.join("terrain.tsx"))
+        );
+        let tile_set = block_on(tile_set).unwrap();
+        assert_eq!(tile_set.data_ref().tiles.alive_count(), 4);
+    }
+}
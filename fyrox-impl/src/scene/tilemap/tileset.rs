@@ -41,6 +41,10 @@ use crate::{
         visitor::prelude::*,
     },
     material::MaterialResource,
+    scene::tilemap::{
+        autotile::{Terrain, TileTerrain},
+        chunk, tiled,
+    },
 };
 use fxhash::FxHashSet;
 use std::{
@@ -135,6 +139,9 @@ pub struct TileDefinition {
     /// tiles, such surface type (for example, lava, ice, dirt, etc.), physics properties and so
     /// on.
     pub properties: Vec<Property>,
+    /// Terrain of the tile. Terrain tiles are selected automatically when painting with a
+    /// terrain, see [`Terrain`] docs for more info.
+    pub terrain: Option<TileTerrain>,
 }
 
 /// An alias for `Handle<TileDefinition>`.
@@ -143,19 +150,52 @@ pub type TileDefinitionHandle = Handle<TileDefinition>;
 /// Tile set is a special storage for tile descriptions. It is a sort of database, that contains
 /// descriptions (definitions) for tiles. Such approach allows you to change appearance of all tiles
 /// of particular kind at once.
-#[derive(Clone, Default, Debug, Reflect, Visit, TypeUuidProvider, ComponentProvider)]
+#[derive(Clone, Debug, Reflect, Visit, TypeUuidProvider, ComponentProvider)]
 #[type_uuid(id = "7b7e057b-a41e-4150-ab3b-0ae99f4024f0")]
 pub struct TileSet {
     /// Tiles of the tile set.
     pub tiles: Pool<TileDefinition>,
+    /// Terrains of the tile set. Tiles reference the terrains by their indices.
+    #[visit(optional)]
+    pub terrains: Vec<Terrain>,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    revision: u64,
+}
+
+impl Default for TileSet {
+    fn default() -> Self {
+        Self {
+            tiles: Default::default(),
+            terrains: Default::default(),
+            revision: chunk::next_revision(),
+        }
+    }
 }
 
 impl TileSet {
     /// Adds a tile definition to the tile set and returns its handle.
     pub fn add_tile(&mut self, tile: TileDefinition) -> TileDefinitionHandle {
+        self.mark_modified();
         self.tiles.spawn(tile)
     }
 
+    /// Returns current revision of the tile set. Revisions are unique across all tile sets, the
+    /// revision changes when the tile set is modified by [`Self::add_tile`] or when
+    /// [`Self::mark_modified`] is called. Tile maps use it to invalidate their cached geometry.
+    #[inline]
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Changes the revision of the tile set. Must be called after direct modifications of
+    /// [`Self::tiles`] or [`Self::terrains`], otherwise tile maps that use the tile set could
+    /// keep showing outdated tiles.
+    #[inline]
+    pub fn mark_modified(&mut self) {
+        self.revision = chunk::next_revision();
+    }
+
     /// Load a tile set resource from the specific file path.
     pub async fn from_file(
         path: &Path,
@@ -223,6 +263,9 @@ impl ResourceData for TileSet {
     }
 
     fn save(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        if path.extension().is_some_and(|ext| ext == "tsx") {
+            return Err("Tile sets imported from Tiled cannot be saved back to .tsx files.".into());
+        }
         let mut visitor = Visitor::new();
         self.visit("TileSet", &mut visitor)?;
         visitor.save_binary(path)?;
@@ -245,7 +288,7 @@ pub struct TileSetLoader {
 
 impl ResourceLoader for TileSetLoader {
     fn extensions(&self) -> &[&str] {
-        &["tileset"]
+        &["tileset", "tsx"]
     }
 
     fn data_type_uuid(&self) -> Uuid {
@@ -255,9 +298,15 @@ impl ResourceLoader for TileSetLoader {
     fn load(&self, path: PathBuf, io: Arc<dyn ResourceIo>) -> BoxedLoaderFuture {
         let resource_manager = self.resource_manager.clone();
         Box::pin(async move {
-            let tile_set = TileSet::from_file(&path, resource_manager, io.as_ref())
-                .await
-                .map_err(LoadError::new)?;
+            let tile_set = if path.extension().is_some_and(|ext| ext == "tsx") {
+                tiled::load_tsx(&path, resource_manager, io.as_ref())
+                    .await
+                    .map_err(LoadError::new)?
+            } else {
+                TileSet::from_file(&path, resource_manager, io.as_ref())
+                    .await
+                    .map_err(LoadError::new)?
+            };
             Ok(LoaderPayload::new(tile_set))
         })
     }
@@ -33,11 +33,11 @@ use crate::{
         visitor::{Visit, VisitResult, Visitor},
         TypeUuidProvider,
     },
-    engine::{task::TaskPoolHandler, GraphicsContext, ScriptMessageDispatcher},
+    engine::{input::InputSystem, task::TaskPoolHandler, GraphicsContext, ScriptMessageDispatcher},
     event::Event,
     gui::UiContainer,
     plugin::{Plugin, PluginContainer},
-    scene::{base::NodeScriptMessage, node::Node, Scene},
+    scene::{base::NodeScriptMessage, graph::physics::CollisionEvent, node::Node, Scene},
 };
 use std::{
     any::{Any, TypeId},
@@ -396,6 +396,10 @@ pub struct ScriptContext<'a, 'b, 'c> {
     /// get a reference to it.
     pub user_interfaces: &'a mut UiContainer,
 
+    /// Input system of the engine. Use it to query the state of input actions and axes, for example
+    /// `ctx.input.action_pressed("jump")`. See [`InputSystem`] docs for more info.
+    pub input: &'a mut InputSystem,
+
     /// Index of the script. Never save this index, it is only valid while this context exists!
     pub script_index: usize,
 }
@@ -472,6 +476,10 @@ pub struct ScriptMessageContext<'a, 'b, 'c> {
     /// get a reference to it.
     pub user_interfaces: &'a mut UiContainer,
 
+    /// Input system of the engine. Use it to query the state of input actions and axes, for example
+    /// `ctx.input.action_pressed("jump")`. See [`InputSystem`] docs for more info.
+    pub input: &'a mut InputSystem,
+
     /// Index of the script. Never save this index, it is only valid while this context exists!
     pub script_index: usize,
 }
@@ -604,6 +612,43 @@ pub trait ScriptTrait: BaseScript + ComponentProvider {
     /// [`crate::engine::executor::Executor::set_desired_update_rate`] method.
     fn on_update(&mut self, #[allow(unused_variables)] ctx: &mut ScriptContext) {}
 
+    /// Called when a collider starts or stops touching some other collider. The method is called
+    /// for scripts of both colliders taking part in a collision, as well as for scripts of their
+    /// rigid bodies. [`CollisionEvent::collider`] is always the collider that belongs to this
+    /// node (or to this rigid body), [`CollisionEvent::other`] is the collider it collided with.
+    /// The method is called after [`ScriptTrait::on_update`] of every script, events of both 2D
+    /// and 3D physics are delivered. Character controllers receive
+    /// [`crate::scene::graph::physics::CollisionEventKind::CharacterHit`] events for the colliders
+    /// they hit, in this case [`CollisionEvent::collider`] is the character controller itself.
+    /// See [`CollisionEvent`] docs for more info.
+    ///
+    /// ```rust
+    /// # use fyrox_impl::{
+    /// #     core::{reflect::prelude::*, type_traits::prelude::*, visitor::prelude::*},
+    /// #     graph::BaseSceneGraph,
+    /// #     scene::graph::physics::{CollisionEvent, CollisionEventKind},
+    /// #     script::{ScriptContext, ScriptTrait},
+    /// # };
+    /// #[derive(Visit, Reflect, Clone, Default, Debug, TypeUuidProvider, ComponentProvider)]
+    /// #[type_uuid(id = "0b6bd4d9-6ad3-4a2e-93d5-d2c2e6c8a1a5")]
+    /// struct Pickup {}
+    ///
+    /// impl ScriptTrait for Pickup {
+    ///     fn on_collision(&mut self, event: &CollisionEvent, ctx: &mut ScriptContext) {
+    ///         if event.kind == CollisionEventKind::TriggerEnter {
+    ///             // Something entered the pickup, remove it.
+    ///             ctx.scene.graph.remove_node(ctx.handle);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    fn on_collision(
+        &mut self,
+        #[allow(unused_variables)] event: &CollisionEvent,
+        #[allow(unused_variables)] ctx: &mut ScriptContext,
+    ) {
+    }
+
     /// Allows you to react to certain script messages. It could be used for communication between scripts; to
     /// bypass borrowing issues. If you need to receive messages of a particular type, you must subscribe to a type
     /// explicitly. Usually it is done in [`ScriptTrait::on_start`] method:
@@ -0,0 +1,296 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Blackboard is a per-tree storage of named values, that could be used by the nodes of a behavior
+//! tree to share data between each other. [`BlackboardCondition`] node could be used to check
+//! blackboard values directly from the tree, without writing any custom leaf nodes.
+
+use crate::{
+    core::{algebra::Vector3, pool::Handle, visitor::prelude::*},
+    utils::behavior::{BehaviorNode, BehaviorTree},
+};
+use fxhash::FxHashMap;
+use std::cmp::Ordering;
+
+/// A value that could be stored in a [`Blackboard`]. Floating-point values are compared bitwise,
+/// use [`Self::compare`] to compare them numerically.
+#[derive(Debug, Visit, Clone)]
+pub enum BlackboardValue {
+    /// A boolean value.
+    Bool(bool),
+    /// An integer value.
+    Integer(i64),
+    /// A floating-point value.
+    Number(f32),
+    /// A string value.
+    String(String),
+    /// A 3D vector.
+    Vector3(Vector3<f32>),
+}
+
+impl PartialEq for BlackboardValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Bool(a), Self::Bool(b)) => a == b,
+            (Self::Integer(a), Self::Integer(b)) => a == b,
+            (Self::Number(a), Self::Number(b)) => a.to_bits() == b.to_bits(),
+            (Self::String(a), Self::String(b)) => a == b,
+            (Self::Vector3(a), Self::Vector3(b)) => a.map(f32::to_bits) == b.map(f32::to_bits),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for BlackboardValue {}
+
+impl Default for BlackboardValue {
+    fn default() -> Self {
+        Self::Bool(false)
+    }
+}
+
+impl BlackboardValue {
+    /// Compares two values. Integers and numbers can be compared with each other, other values
+    /// can be compared only with the values of the same kind. Vectors and booleans could only be
+    /// checked for equality, so `None` will be returned if they're not equal.
+    pub fn compare(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Self::Integer(a), Self::Integer(b)) => Some(a.cmp(b)),
+            (Self::Integer(a), Self::Number(b)) => (*a as f64).partial_cmp(&(*b as f64)),
+            (Self::Number(a), Self::Integer(b)) => (*a as f64).partial_cmp(&(*b as f64)),
+            (Self::Number(a), Self::Number(b)) => a.partial_cmp(b),
+            (Self::String(a), Self::String(b)) => Some(a.cmp(b)),
+            (Self::Bool(a), Self::Bool(b)) if a == b => Some(Ordering::Equal),
+            (Self::Vector3(a), Self::Vector3(b)) if a == b => Some(Ordering::Equal),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for BlackboardValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<i64> for BlackboardValue {
+    fn from(value: i64) -> Self {
+        Self::Integer(value)
+    }
+}
+
+impl From<f32> for BlackboardValue {
+    fn from(value: f32) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<String> for BlackboardValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<&str> for BlackboardValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_owned())
+    }
+}
+
+impl From<Vector3<f32>> for BlackboardValue {
+    fn from(value: Vector3<f32>) -> Self {
+        Self::Vector3(value)
+    }
+}
+
+/// Blackboard is a named storage of values, that is shared across all nodes of a behavior tree.
+#[derive(Debug, Default, PartialEq, Eq, Visit, Clone)]
+pub struct Blackboard {
+    values: FxHashMap<String, BlackboardValue>,
+}
+
+impl Blackboard {
+    /// Sets a new value for the given key and returns the previous value (if any).
+    pub fn set<K, V>(&mut self, key: K, value: V) -> Option<BlackboardValue>
+    where
+        K: Into<String>,
+        V: Into<BlackboardValue>,
+    {
+        self.values.insert(key.into(), value.into())
+    }
+
+    /// Returns a reference to the value of the given key (if any).
+    pub fn get(&self, key: &str) -> Option<&BlackboardValue> {
+        self.values.get(key)
+    }
+
+    /// Returns a mutable reference to the value of the given key (if any).
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut BlackboardValue> {
+        self.values.get_mut(key)
+    }
+
+    /// Returns a boolean value of the given key. `None` will be returned if there's no such key
+    /// or the value has different type.
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        match self.get(key)? {
+            BlackboardValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns an integer value of the given key. `None` will be returned if there's no such key
+    /// or the value has different type.
+    pub fn get_integer(&self, key: &str) -> Option<i64> {
+        match self.get(key)? {
+            BlackboardValue::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns a floating-point value of the given key. Integer values will be converted to
+    /// floating-point numbers. `None` will be returned if there's no such key or the value has
+    /// different type.
+    pub fn get_number(&self, key: &str) -> Option<f32> {
+        match self.get(key)? {
+            BlackboardValue::Number(value) => Some(*value),
+            BlackboardValue::Integer(value) => Some(*value as f32),
+            _ => None,
+        }
+    }
+
+    /// Returns a string value of the given key. `None` will be returned if there's no such key
+    /// or the value has different type.
+    pub fn get_string(&self, key: &str) -> Option<&str> {
+        match self.get(key)? {
+            BlackboardValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns a vector value of the given key. `None` will be returned if there's no such key
+    /// or the value has different type.
+    pub fn get_vector3(&self, key: &str) -> Option<Vector3<f32>> {
+        match self.get(key)? {
+            BlackboardValue::Vector3(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the blackboard has a value with the given key, `false` - otherwise.
+    pub fn contains(&self, key: &str) -> bool {
+        self.values.contains_key(key)
+    }
+
+    /// Removes a value with the given key and returns it (if any).
+    pub fn remove(&mut self, key: &str) -> Option<BlackboardValue> {
+        self.values.remove(key)
+    }
+
+    /// Removes every value from the blackboard.
+    pub fn clear(&mut self) {
+        self.values.clear()
+    }
+
+    /// Returns an iterator over key-value pairs of the blackboard.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &BlackboardValue)> {
+        self.values.iter()
+    }
+}
+
+/// Defines how a blackboard value will be checked by [`BlackboardCondition`].
+#[derive(Debug, Default, PartialEq, Visit, Eq, Clone, Copy)]
+pub enum Comparison {
+    /// The value must be equal to the reference value.
+    #[default]
+    Equal,
+    /// The value must not be equal to the reference value.
+    NotEqual,
+    /// The value must be less than the reference value.
+    Less,
+    /// The value must be less or equal than the reference value.
+    LessOrEqual,
+    /// The value must be greater than the reference value.
+    Greater,
+    /// The value must be greater or equal than the reference value.
+    GreaterOrEqual,
+    /// The blackboard must have a value with the given key, reference value is ignored.
+    IsSet,
+    /// The blackboard must not have a value with the given key, reference value is ignored.
+    IsNotSet,
+}
+
+/// A node that checks a value in the blackboard of the tree. It returns [`super::Status::Success`]
+/// if the condition is met and [`super::Status::Failure`] otherwise. It never returns
+/// [`super::Status::Running`], which makes it a good first child of a composite node with conditional
+/// aborts (see [`super::composite::AbortMode`]).
+#[derive(Debug, Default, PartialEq, Eq, Visit, Clone)]
+pub struct BlackboardCondition {
+    /// A key of the value in the blackboard.
+    pub key: String,
+    /// A comparison that will be used to check the value.
+    pub comparison: Comparison,
+    /// A reference value to compare with.
+    pub value: BlackboardValue,
+}
+
+impl BlackboardCondition {
+    /// Creates new blackboard condition.
+    pub fn new<K, V>(key: K, comparison: Comparison, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<BlackboardValue>,
+    {
+        Self {
+            key: key.into(),
+            comparison,
+            value: value.into(),
+        }
+    }
+
+    /// Checks the condition using the given blackboard.
+    pub fn check(&self, blackboard: &Blackboard) -> bool {
+        let Some(value) = blackboard.get(&self.key) else {
+            return self.comparison == Comparison::IsNotSet;
+        };
+
+        let ordering = value.compare(&self.value);
+        match self.comparison {
+            Comparison::Equal => ordering == Some(Ordering::Equal),
+            Comparison::NotEqual => ordering != Some(Ordering::Equal),
+            Comparison::Less => ordering == Some(Ordering::Less),
+            Comparison::LessOrEqual => {
+                matches!(ordering, Some(Ordering::Less | Ordering::Equal))
+            }
+            Comparison::Greater => ordering == Some(Ordering::Greater),
+            Comparison::GreaterOrEqual => {
+                matches!(ordering, Some(Ordering::Greater | Ordering::Equal))
+            }
+            Comparison::IsSet => true,
+            Comparison::IsNotSet => false,
+        }
+    }
+
+    /// Adds self to given behavior tree and returns handle to self.
+    pub fn add_to<B>(self, tree: &mut BehaviorTree<B>) -> Handle<BehaviorNode<B>>
+    where
+        B: Clone + 'static,
+    {
+        tree.add_node(BehaviorNode::BlackboardCondition(self))
+    }
+}
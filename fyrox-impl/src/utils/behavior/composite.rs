@@ -19,11 +19,17 @@
 // SOFTWARE.
 
 //! Composite node is a container for children nodes. Composite node could be either
-//! `Sequence` or `Selector`. `Sequence` node will execute children nodes consecutively
+//! `Sequence`, `Selector`, `RandomSelector` or `Parallel`. `Sequence` node will execute children nodes consecutively
 //! until `Status::Failure` is returned from any descendant node. In other words `Sequence`
 //! implement AND logical function. `Selector` node will execute children until `Status::Success`
 //! is returned from any descendant node. In other worlds `Selector` implement OR logical
-//! function.
+//! function. `RandomSelector` is the same as `Selector`, but it executes its children in random
+//! order. `Parallel` node executes all its children on every tick and its result is defined by
+//! [`ParallelPolicy`].
+//!
+//! If a child node returns `Status::Running`, the composite node will continue execution from this
+//! child on the next tick. [`AbortMode`] allows to re-evaluate previous children while some
+//! child is running.
 
 use crate::{
     core::{pool::Handle, visitor::prelude::*},
@@ -41,6 +47,12 @@ pub enum CompositeNodeKind {
     /// is returned from any descendant node. In other worlds `Selector` implement OR logical
     /// function.
     Selector,
+    /// `Parallel` node executes all its children on every tick, until the result is defined by
+    /// the [`ParallelPolicy`] of the node.
+    Parallel,
+    /// `RandomSelector` node works the same as `Selector`, but it shuffles its children every time
+    /// it starts execution.
+    RandomSelector,
 }
 
 impl Default for CompositeNodeKind {
@@ -49,6 +61,49 @@ impl Default for CompositeNodeKind {
     }
 }
 
+/// Defines when a `Parallel` composite node finishes its execution.
+#[derive(Debug, Default, PartialEq, Visit, Eq, Clone, Copy)]
+pub enum ParallelPolicy {
+    /// The node succeeds when all its children have succeeded and fails as soon as any child
+    /// fails.
+    #[default]
+    RequireAll,
+    /// The node succeeds as soon as any child succeeds and fails when all its children have failed.
+    RequireOne,
+}
+
+/// Conditional abort mode of a composite node. It defines whether a running branch of the tree
+/// could be interrupted if some condition has changed. Conditions are usually expressed as the
+/// first children of a `Sequence` node (for example, [`super::blackboard::BlackboardCondition`]).
+#[derive(Debug, Default, PartialEq, Visit, Eq, Clone, Copy)]
+pub enum AbortMode {
+    /// The node does not re-evaluate anything.
+    #[default]
+    None,
+    /// `Sequence` node only. When the node resumes execution of a running child, it re-evaluates
+    /// all the children before the running one. If any of them fails, the running child is aborted
+    /// and the sequence fails.
+    SelfBranch,
+    /// When the node is a child of a `Selector` (or `RandomSelector`) and a lower priority sibling
+    /// (the one that goes after it) is running, the node will be re-evaluated on every tick. If it
+    /// does not fail, the running sibling is aborted and the execution continues from this node.
+    LowerPriority,
+    /// Combination of [`AbortMode::SelfBranch`] and [`AbortMode::LowerPriority`].
+    Both,
+}
+
+impl AbortMode {
+    /// Returns `true` if the mode re-evaluates the children of the node itself.
+    pub fn aborts_self(self) -> bool {
+        matches!(self, Self::SelfBranch | Self::Both)
+    }
+
+    /// Returns `true` if the mode re-evaluates the node while its lower priority sibling is running.
+    pub fn aborts_lower_priority(self) -> bool {
+        matches!(self, Self::LowerPriority | Self::Both)
+    }
+}
+
 /// See module docs.
 #[derive(Debug, PartialEq, Visit, Eq, Clone)]
 pub struct CompositeNode<B>
@@ -59,6 +114,12 @@ where
     pub children: Vec<Handle<BehaviorNode<B>>>,
     /// Current kind of the node.
     pub kind: CompositeNodeKind,
+    /// Conditional abort mode of the node.
+    #[visit(optional)]
+    pub abort: AbortMode,
+    /// Defines when a `Parallel` node finishes its execution. Ignored by other kinds.
+    #[visit(optional)]
+    pub policy: ParallelPolicy,
 }
 
 impl<B> Default for CompositeNode<B>
@@ -69,6 +130,8 @@ where
         Self {
             children: Default::default(),
             kind: Default::default(),
+            abort: Default::default(),
+            policy: Default::default(),
         }
     }
 }
@@ -79,25 +142,43 @@ where
 {
     /// Creates new composite node of given kind and set of children nodes.
     pub fn new(kind: CompositeNodeKind, children: Vec<Handle<BehaviorNode<B>>>) -> Self {
-        Self { children, kind }
+        Self {
+            children,
+            kind,
+            abort: Default::default(),
+            policy: Default::default(),
+        }
     }
 
     /// Creates new sequence composite node with a set of children nodes.
     pub fn new_sequence(children: Vec<Handle<BehaviorNode<B>>>) -> Self {
-        Self {
-            children,
-            kind: CompositeNodeKind::Sequence,
-        }
+        Self::new(CompositeNodeKind::Sequence, children)
     }
 
     /// Creates new selector composite node with a set of children nodes.
     pub fn new_selector(children: Vec<Handle<BehaviorNode<B>>>) -> Self {
+        Self::new(CompositeNodeKind::Selector, children)
+    }
+
+    /// Creates new random selector composite node with a set of children nodes.
+    pub fn new_random_selector(children: Vec<Handle<BehaviorNode<B>>>) -> Self {
+        Self::new(CompositeNodeKind::RandomSelector, children)
+    }
+
+    /// Creates new parallel composite node with the given policy and a set of children nodes.
+    pub fn new_parallel(policy: ParallelPolicy, children: Vec<Handle<BehaviorNode<B>>>) -> Self {
         Self {
-            children,
-            kind: CompositeNodeKind::Selector,
+            policy,
+            ..Self::new(CompositeNodeKind::Parallel, children)
         }
     }
 
+    /// Sets conditional abort mode of the node.
+    pub fn with_abort(mut self, abort: AbortMode) -> Self {
+        self.abort = abort;
+        self
+    }
+
     /// Adds self to the tree and return handle to self.
     pub fn add_to(self, tree: &mut BehaviorTree<B>) -> Handle<BehaviorNode<B>> {
         tree.add_node(BehaviorNode::Composite(self))
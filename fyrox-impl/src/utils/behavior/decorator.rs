@@ -0,0 +1,216 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Decorators are nodes with a single child, that modify the way the child is executed or the
+//! status it returns. See docs for each node for more info.
+
+use crate::{
+    core::{pool::Handle, visitor::prelude::*},
+    utils::behavior::{BehaviorNode, BehaviorTree},
+};
+
+/// A node, that executes its child the given number of times. Each successful execution of the child
+/// takes at least one tick of the tree. Repetition stops with [`super::Status::Failure`] as soon as
+/// the child fails, otherwise [`super::Status::Success`] is returned when the child succeeded the
+/// given number of times.
+#[derive(Debug, PartialEq, Visit, Eq, Clone)]
+pub struct Repeat<B>
+where
+    B: Clone,
+{
+    /// A handle of child node, that will be repeated.
+    pub child: Handle<BehaviorNode<B>>,
+    /// Total amount of repetitions. Zero means that the child will be repeated infinitely.
+    pub count: u32,
+}
+
+impl<B> Default for Repeat<B>
+where
+    B: Clone,
+{
+    fn default() -> Self {
+        Self {
+            child: Default::default(),
+            count: 0,
+        }
+    }
+}
+
+impl<B> Repeat<B>
+where
+    B: Clone + 'static,
+{
+    /// Creates new repeat node with the given child and amount of repetitions. Zero `count`
+    /// means that the child will be repeated infinitely.
+    pub fn new(child: Handle<BehaviorNode<B>>, count: u32) -> Self {
+        Self { child, count }
+    }
+
+    /// Adds self to given behavior tree and returns handle to self.
+    pub fn add_to(self, tree: &mut BehaviorTree<B>) -> Handle<BehaviorNode<B>> {
+        tree.add_node(BehaviorNode::Repeat(self))
+    }
+}
+
+/// A node, that re-executes its child if it fails, until the given amount of attempts is exhausted.
+/// Each attempt takes at least one tick of the tree. It returns [`super::Status::Success`] as soon
+/// as the child succeeds and [`super::Status::Failure`] if every attempt has failed.
+#[derive(Debug, PartialEq, Visit, Eq, Clone)]
+pub struct Retry<B>
+where
+    B: Clone,
+{
+    /// A handle of child node, that will be retried.
+    pub child: Handle<BehaviorNode<B>>,
+    /// Total amount of attempts. Zero means that the child will be retried until it succeeds.
+    pub attempts: u32,
+}
+
+impl<B> Default for Retry<B>
+where
+    B: Clone,
+{
+    fn default() -> Self {
+        Self {
+            child: Default::default(),
+            attempts: 0,
+        }
+    }
+}
+
+impl<B> Retry<B>
+where
+    B: Clone + 'static,
+{
+    /// Creates new retry node with the given child and amount of attempts. Zero `attempts` means
+    /// that the child will be retried until it succeeds.
+    pub fn new(child: Handle<BehaviorNode<B>>, attempts: u32) -> Self {
+        Self { child, attempts }
+    }
+
+    /// Adds self to given behavior tree and returns handle to self.
+    pub fn add_to(self, tree: &mut BehaviorTree<B>) -> Handle<BehaviorNode<B>> {
+        tree.add_node(BehaviorNode::Retry(self))
+    }
+}
+
+/// A node, that aborts its child and returns [`super::Status::Failure`] if the child is running
+/// longer than the given amount of time. Time is measured using [`BehaviorTree::update`].
+#[derive(Debug, Visit, Clone)]
+pub struct Timeout<B>
+where
+    B: Clone,
+{
+    /// A handle of child node, the execution time of which is limited.
+    pub child: Handle<BehaviorNode<B>>,
+    /// Maximum execution time of the child (in seconds).
+    pub duration: f32,
+}
+
+// Durations are compared bitwise, so the node could be `Eq`.
+impl<B> PartialEq for Timeout<B>
+where
+    B: Clone,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.child == other.child && self.duration.to_bits() == other.duration.to_bits()
+    }
+}
+
+impl<B> Eq for Timeout<B> where B: Clone {}
+
+impl<B> Default for Timeout<B>
+where
+    B: Clone,
+{
+    fn default() -> Self {
+        Self {
+            child: Default::default(),
+            duration: 1.0,
+        }
+    }
+}
+
+impl<B> Timeout<B>
+where
+    B: Clone + 'static,
+{
+    /// Creates new timeout node with the given child and maximum execution time (in seconds).
+    pub fn new(child: Handle<BehaviorNode<B>>, duration: f32) -> Self {
+        Self { child, duration }
+    }
+
+    /// Adds self to given behavior tree and returns handle to self.
+    pub fn add_to(self, tree: &mut BehaviorTree<B>) -> Handle<BehaviorNode<B>> {
+        tree.add_node(BehaviorNode::Timeout(self))
+    }
+}
+
+/// A node, that prevents its child from being executed again for the given amount of time after
+/// the child has finished its execution. While cooling down, the node returns
+/// [`super::Status::Failure`]. Time is measured using [`BehaviorTree::update`].
+#[derive(Debug, Visit, Clone)]
+pub struct Cooldown<B>
+where
+    B: Clone,
+{
+    /// A handle of child node, that will be executed.
+    pub child: Handle<BehaviorNode<B>>,
+    /// Cooldown time (in seconds).
+    pub duration: f32,
+}
+
+// Durations are compared bitwise, so the node could be `Eq`.
+impl<B> PartialEq for Cooldown<B>
+where
+    B: Clone,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.child == other.child && self.duration.to_bits() == other.duration.to_bits()
+    }
+}
+
+impl<B> Eq for Cooldown<B> where B: Clone {}
+
+impl<B> Default for Cooldown<B>
+where
+    B: Clone,
+{
+    fn default() -> Self {
+        Self {
+            child: Default::default(),
+            duration: 1.0,
+        }
+    }
+}
+
+impl<B> Cooldown<B>
+where
+    B: Clone + 'static,
+{
+    /// Creates new cooldown node with the given child and cooldown time (in seconds).
+    pub fn new(child: Handle<BehaviorNode<B>>, duration: f32) -> Self {
+        Self { child, duration }
+    }
+
+    /// Adds self to given behavior tree and returns handle to self.
+    pub fn add_to(self, tree: &mut BehaviorTree<B>) -> Handle<BehaviorNode<B>> {
+        tree.add_node(BehaviorNode::Cooldown(self))
+    }
+}
@@ -26,8 +26,14 @@
 //! games. The main concept is in its name. Tree is a set of connected nodes, where each node could
 //! have single parent and zero or more children nodes. Execution path of the tree is defined by the
 //! actions of the nodes. Behavior tree has a set of hard coded nodes as well as leaf nodes with
-//! user-defined logic. Hard coded nodes are: Sequence, Selector, Leaf. Leaf is special - it has
-//! custom method `tick` that can contain any logic you want.
+//! user-defined logic. Hard coded nodes are: Sequence, Selector, RandomSelector, Parallel (see
+//! [`composite`]), Inverter, Repeat, Retry, Timeout, Cooldown (see [`decorator`]), BlackboardCondition
+//! and Leaf. Leaf is special - it has custom method `tick` that can contain any logic you want.
+//!
+//! Nodes that return [`Status::Running`] are resumed on the next tick, instead of re-evaluating
+//! the whole tree from the root. Composite nodes could re-evaluate conditions of the running
+//! branches using conditional aborts (see [`composite::AbortMode`]). Every tree has its own
+//! [`blackboard::Blackboard`] which could be used to share data between the nodes.
 //!
 //! For more info see:
 //! - [Wikipedia article](https://en.wikipedia.org/wiki/Behavior_tree_(artificial_intelligence,_robotics_and_control))
@@ -36,24 +42,32 @@
 use crate::{
     core::{
         pool::{Handle, Pool},
+        rand::{seq::SliceRandom, thread_rng},
         visitor::prelude::*,
     },
     utils::behavior::{
-        composite::{CompositeNode, CompositeNodeKind},
+        blackboard::{Blackboard, BlackboardCondition},
+        composite::{CompositeNode, CompositeNodeKind, ParallelPolicy},
+        decorator::{Cooldown, Repeat, Retry, Timeout},
         inverter::Inverter,
         leaf::LeafNode,
     },
 };
+use fxhash::FxHashMap;
 use std::{
+    cell::{Cell, Ref, RefCell},
     fmt::Debug,
     ops::{Index, IndexMut},
 };
 
+pub mod blackboard;
 pub mod composite;
+pub mod decorator;
 pub mod inverter;
 pub mod leaf;
 
 /// Status of execution of behavior tree node.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Status {
     /// Action was successful.
     Success,
@@ -72,6 +86,20 @@ pub trait Behavior<'a>: Visit + Default + PartialEq + Debug + Clone {
     /// the current execution path of the behavior tree it belongs
     /// to.
     fn tick(&mut self, context: &mut Self::Context) -> Status;
+
+    /// The same as [`Self::tick`], but also gives access to the blackboard of the tree. This is
+    /// the method that the tree actually calls, by default it just calls [`Self::tick`].
+    fn tick_with_blackboard(
+        &mut self,
+        context: &mut Self::Context,
+        _blackboard: &mut Blackboard,
+    ) -> Status {
+        self.tick(context)
+    }
+
+    /// A function that will be called if the behavior was running and then was interrupted
+    /// by some other node (for example, because of a conditional abort or a timeout).
+    fn on_abort(&mut self, _context: &mut Self::Context) {}
 }
 
 /// Root node of the tree.
@@ -104,13 +132,23 @@ where
     Unknown,
     /// Root node of the tree.
     Root(RootNode<B>),
-    /// Composite (sequence or selector) node of the tree.
+    /// Composite (sequence, selector, random selector or parallel) node of the tree.
     Composite(CompositeNode<B>),
     /// A node with custom logic.
     Leaf(LeafNode<B>),
     /// A node, that inverts its child state ([`Status::Failure`] becomes [`Status::Success`] and vice versa, [`Status::Running`] remains
     /// unchanged)
     Inverter(Inverter<B>),
+    /// A node, that repeats its child the given number of times.
+    Repeat(Repeat<B>),
+    /// A node, that retries its child if it fails.
+    Retry(Retry<B>),
+    /// A node, that limits the execution time of its child.
+    Timeout(Timeout<B>),
+    /// A node, that prevents its child from being executed too often.
+    Cooldown(Cooldown<B>),
+    /// A node, that checks a value in the blackboard of the tree.
+    BlackboardCondition(BlackboardCondition),
 }
 
 impl<B> Default for BehaviorNode<B>
@@ -122,6 +160,48 @@ where
     }
 }
 
+impl<B> BehaviorNode<B>
+where
+    B: Clone,
+{
+    /// Returns a list of children handles of the node.
+    pub fn children(&self) -> Vec<Handle<BehaviorNode<B>>> {
+        match self {
+            BehaviorNode::Unknown
+            | BehaviorNode::Leaf(_)
+            | BehaviorNode::BlackboardCondition(_) => Vec::new(),
+            BehaviorNode::Root(root) => vec![root.child],
+            BehaviorNode::Composite(composite) => composite.children.clone(),
+            BehaviorNode::Inverter(inverter) => vec![inverter.child],
+            BehaviorNode::Repeat(repeat) => vec![repeat.child],
+            BehaviorNode::Retry(retry) => vec![retry.child],
+            BehaviorNode::Timeout(timeout) => vec![timeout.child],
+            BehaviorNode::Cooldown(cooldown) => vec![cooldown.child],
+        }
+    }
+}
+
+/// Runtime state of a node, that is used to resume execution of running nodes.
+#[derive(Debug, Default, PartialEq, Clone)]
+struct NodeState {
+    running: bool,
+    child_index: usize,
+    order: Vec<usize>,
+    results: Vec<Option<Status>>,
+    counter: u32,
+    start_time: f32,
+    cooldown_end: f32,
+}
+
+impl NodeState {
+    fn finish(&mut self) {
+        *self = Self {
+            cooldown_end: self.cooldown_end,
+            ..Default::default()
+        };
+    }
+}
+
 /// See module docs.
 #[derive(Debug, PartialEq, Visit, Clone)]
 pub struct BehaviorTree<B>
@@ -130,6 +210,12 @@ where
 {
     nodes: Pool<BehaviorNode<B>>,
     root: Handle<BehaviorNode<B>>,
+    #[visit(optional)]
+    blackboard: RefCell<Blackboard>,
+    #[visit(skip)]
+    time: Cell<f32>,
+    #[visit(skip)]
+    states: RefCell<FxHashMap<Handle<BehaviorNode<B>>, NodeState>>,
 }
 
 impl<B> Default for BehaviorTree<B>
@@ -140,6 +226,9 @@ where
         Self {
             nodes: Default::default(),
             root: Default::default(),
+            blackboard: Default::default(),
+            time: Default::default(),
+            states: Default::default(),
         }
     }
 }
@@ -154,7 +243,11 @@ where
         let root = nodes.spawn(BehaviorNode::Root(RootNode {
             child: Default::default(),
         }));
-        Self { nodes, root }
+        Self {
+            nodes,
+            root,
+            ..Default::default()
+        }
     }
 
     /// Adds a node to the tree, returns its handle.
@@ -171,6 +264,248 @@ where
         }
     }
 
+    /// Returns a handle of the root node of the tree.
+    pub fn root(&self) -> Handle<BehaviorNode<B>> {
+        self.root
+    }
+
+    /// Returns a reference to the blackboard of the tree.
+    pub fn blackboard(&self) -> Ref<'_, Blackboard> {
+        self.blackboard.borrow()
+    }
+
+    /// Returns a mutable reference to the blackboard of the tree.
+    pub fn blackboard_mut(&mut self) -> &mut Blackboard {
+        self.blackboard.get_mut()
+    }
+
+    /// Returns the total time (in seconds) accumulated by [`Self::update`].
+    pub fn time(&self) -> f32 {
+        self.time.get()
+    }
+
+    /// Returns `true` if the node with the given handle was running after the last tick.
+    pub fn is_running(&self, handle: Handle<BehaviorNode<B>>) -> bool {
+        self.states
+            .borrow()
+            .get(&handle)
+            .is_some_and(|state| state.running)
+    }
+
+    fn state(&self, handle: Handle<BehaviorNode<B>>) -> NodeState {
+        self.states
+            .borrow()
+            .get(&handle)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn set_state(&self, handle: Handle<BehaviorNode<B>>, state: NodeState) {
+        self.states.borrow_mut().insert(handle, state);
+    }
+
+    fn set_running(&self, handle: Handle<BehaviorNode<B>>, running: bool) {
+        self.states.borrow_mut().entry(handle).or_default().running = running;
+    }
+
+    fn abort_recursive<'a, Ctx>(&self, handle: Handle<BehaviorNode<B>>, context: &mut Ctx)
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        let Some(node) = self.nodes.try_borrow(handle) else {
+            return;
+        };
+
+        let mut state = self.state(handle);
+        if !state.running {
+            return;
+        }
+        state.finish();
+        self.set_state(handle, state);
+
+        if let BehaviorNode::Leaf(leaf) = node {
+            if let Some(behavior) = leaf.behavior.as_ref() {
+                behavior.borrow_mut().on_abort(context);
+            }
+        }
+
+        for child in node.children() {
+            self.abort_recursive(child, context);
+        }
+    }
+
+    fn finish_node(
+        &self,
+        handle: Handle<BehaviorNode<B>>,
+        mut state: NodeState,
+        status: Status,
+    ) -> Status {
+        if status == Status::Running {
+            state.running = true;
+        } else {
+            state.finish();
+        }
+        self.set_state(handle, state);
+        status
+    }
+
+    fn tick_sequence<'a, Ctx>(
+        &self,
+        handle: Handle<BehaviorNode<B>>,
+        composite: &CompositeNode<B>,
+        context: &mut Ctx,
+    ) -> Status
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        let mut state = self.state(handle);
+        if !state.running {
+            state.child_index = 0;
+        } else if composite.abort.aborts_self() {
+            // Re-evaluate the children before the running one, the running child must be aborted
+            // if any of them fails.
+            for i in 0..state.child_index.min(composite.children.len()) {
+                let child = composite.children[i];
+                match self.tick_recursive(child, context) {
+                    Status::Success => (),
+                    Status::Running => self.abort_recursive(child, context),
+                    Status::Failure => {
+                        if let Some(running) = composite.children.get(state.child_index) {
+                            self.abort_recursive(*running, context);
+                        }
+                        return self.finish_node(handle, state, Status::Failure);
+                    }
+                }
+            }
+        }
+
+        while let Some(child) = composite.children.get(state.child_index) {
+            match self.tick_recursive(*child, context) {
+                Status::Success => state.child_index += 1,
+                status => return self.finish_node(handle, state, status),
+            }
+        }
+
+        self.finish_node(handle, state, Status::Success)
+    }
+
+    fn tick_selector<'a, Ctx>(
+        &self,
+        handle: Handle<BehaviorNode<B>>,
+        composite: &CompositeNode<B>,
+        context: &mut Ctx,
+    ) -> Status
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        let mut state = self.state(handle);
+        if !state.running {
+            state.child_index = 0;
+            state.order = (0..composite.children.len()).collect();
+            if composite.kind == CompositeNodeKind::RandomSelector {
+                state.order.shuffle(&mut thread_rng());
+            }
+        } else {
+            // Re-evaluate higher priority children, that have lower priority abort mode. If any
+            // of them does not fail, the running child is aborted.
+            for i in 0..state.child_index.min(state.order.len()) {
+                let Some(child) = composite.children.get(state.order[i]).cloned() else {
+                    continue;
+                };
+                if !matches!(self.nodes.try_borrow(child), Some(BehaviorNode::Composite(c)) if c.abort.aborts_lower_priority())
+                {
+                    continue;
+                }
+                let status = self.tick_recursive(child, context);
+                if status != Status::Failure {
+                    if let Some(running) = state
+                        .order
+                        .get(state.child_index)
+                        .and_then(|index| composite.children.get(*index))
+                    {
+                        self.abort_recursive(*running, context);
+                    }
+                    state.child_index = i;
+                    return self.finish_node(handle, state, status);
+                }
+            }
+        }
+
+        while let Some(child) = state
+            .order
+            .get(state.child_index)
+            .and_then(|index| composite.children.get(*index))
+        {
+            match self.tick_recursive(*child, context) {
+                Status::Failure => state.child_index += 1,
+                status => return self.finish_node(handle, state, status),
+            }
+        }
+
+        self.finish_node(handle, state, Status::Failure)
+    }
+
+    fn tick_parallel<'a, Ctx>(
+        &self,
+        handle: Handle<BehaviorNode<B>>,
+        composite: &CompositeNode<B>,
+        context: &mut Ctx,
+    ) -> Status
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        let mut state = self.state(handle);
+        if !state.running || state.results.len() != composite.children.len() {
+            state.results = vec![None; composite.children.len()];
+        }
+
+        for (child, result) in composite.children.iter().zip(state.results.iter_mut()) {
+            if result.is_none() {
+                match self.tick_recursive(*child, context) {
+                    Status::Running => (),
+                    status => *result = Some(status),
+                }
+            }
+        }
+
+        let count = |status| {
+            state
+                .results
+                .iter()
+                .filter(|result| **result == Some(status))
+                .count()
+        };
+        let total = composite.children.len();
+        let status = match composite.policy {
+            ParallelPolicy::RequireAll => {
+                if count(Status::Failure) > 0 {
+                    Status::Failure
+                } else if count(Status::Success) == total {
+                    Status::Success
+                } else {
+                    Status::Running
+                }
+            }
+            ParallelPolicy::RequireOne => {
+                if count(Status::Success) > 0 {
+                    Status::Success
+                } else if count(Status::Failure) == total {
+                    Status::Failure
+                } else {
+                    Status::Running
+                }
+            }
+        };
+
+        if status != Status::Running {
+            for child in composite.children.iter() {
+                self.abort_recursive(*child, context);
+            }
+        }
+
+        self.finish_node(handle, state, status)
+    }
+
     fn tick_recursive<'a, Ctx>(&self, handle: Handle<BehaviorNode<B>>, context: &mut Ctx) -> Status
     where
         B: Behavior<'a, Context = Ctx>,
@@ -184,45 +519,88 @@ where
                 }
             }
             BehaviorNode::Composite(ref composite) => match composite.kind {
-                CompositeNodeKind::Sequence => {
-                    let mut all_succeeded = true;
-                    for child in composite.children.iter() {
-                        match self.tick_recursive(*child, context) {
-                            Status::Failure => {
-                                all_succeeded = false;
-                                break;
-                            }
-                            Status::Running => {
-                                return Status::Running;
-                            }
-                            _ => (),
-                        }
-                    }
-                    if all_succeeded {
-                        Status::Success
-                    } else {
-                        Status::Failure
-                    }
-                }
-                CompositeNodeKind::Selector => {
-                    for child in composite.children.iter() {
-                        match self.tick_recursive(*child, context) {
-                            Status::Success => return Status::Success,
-                            Status::Running => return Status::Running,
-                            _ => (),
-                        }
-                    }
-                    Status::Failure
+                CompositeNodeKind::Sequence => self.tick_sequence(handle, composite, context),
+                CompositeNodeKind::Selector | CompositeNodeKind::RandomSelector => {
+                    self.tick_selector(handle, composite, context)
                 }
+                CompositeNodeKind::Parallel => self.tick_parallel(handle, composite, context),
             },
             BehaviorNode::Leaf(ref leaf) => {
-                leaf.behavior.as_ref().unwrap().borrow_mut().tick(context)
+                let status = leaf
+                    .behavior
+                    .as_ref()
+                    .unwrap()
+                    .borrow_mut()
+                    .tick_with_blackboard(context, &mut self.blackboard.borrow_mut());
+                self.set_running(handle, status == Status::Running);
+                status
             }
             BehaviorNode::Inverter(ref inverter) => {
-                match self.tick_recursive(inverter.child, context) {
+                let status = match self.tick_recursive(inverter.child, context) {
                     Status::Success => Status::Failure,
                     Status::Failure => Status::Success,
                     Status::Running => Status::Running,
+                };
+                self.set_running(handle, status == Status::Running);
+                status
+            }
+            BehaviorNode::Repeat(ref repeat) => {
+                let mut state = self.state(handle);
+                let status = match self.tick_recursive(repeat.child, context) {
+                    Status::Success => {
+                        state.counter += 1;
+                        if repeat.count != 0 && state.counter >= repeat.count {
+                            Status::Success
+                        } else {
+                            Status::Running
+                        }
+                    }
+                    status => status,
+                };
+                self.finish_node(handle, state, status)
+            }
+            BehaviorNode::Retry(ref retry) => {
+                let mut state = self.state(handle);
+                let status = match self.tick_recursive(retry.child, context) {
+                    Status::Failure => {
+                        state.counter += 1;
+                        if retry.attempts != 0 && state.counter >= retry.attempts {
+                            Status::Failure
+                        } else {
+                            Status::Running
+                        }
+                    }
+                    status => status,
+                };
+                self.finish_node(handle, state, status)
+            }
+            BehaviorNode::Timeout(ref timeout) => {
+                let mut state = self.state(handle);
+                if !state.running {
+                    state.start_time = self.time.get();
+                } else if self.time.get() - state.start_time >= timeout.duration {
+                    self.abort_recursive(timeout.child, context);
+                    return self.finish_node(handle, state, Status::Failure);
+                }
+                let status = self.tick_recursive(timeout.child, context);
+                self.finish_node(handle, state, status)
+            }
+            BehaviorNode::Cooldown(ref cooldown) => {
+                let mut state = self.state(handle);
+                if !state.running && self.time.get() < state.cooldown_end {
+                    return Status::Failure;
+                }
+                let status = self.tick_recursive(cooldown.child, context);
+                if status != Status::Running {
+                    state.cooldown_end = self.time.get() + cooldown.duration;
+                }
+                self.finish_node(handle, state, status)
+            }
+            BehaviorNode::BlackboardCondition(ref condition) => {
+                if condition.check(&self.blackboard.borrow()) {
+                    Status::Success
+                } else {
+                    Status::Failure
                 }
             }
             BehaviorNode::Unknown => {
@@ -241,13 +619,37 @@ where
         self.nodes.try_borrow_mut(handle)
     }
 
-    /// Performs a single update tick with given context.
+    /// Performs a single update tick with given context. Nodes that returned [`Status::Running`]
+    /// on the previous tick will continue their execution, instead of starting from scratch.
+    /// This method does not advance the time of the tree, use [`Self::update`] if the tree has
+    /// time-dependent nodes (such as [`decorator::Timeout`] or [`decorator::Cooldown`]).
     pub fn tick<'a, Ctx>(&self, context: &mut Ctx) -> Status
     where
         B: Behavior<'a, Context = Ctx>,
     {
         self.tick_recursive(self.root, context)
     }
+
+    /// Advances the time of the tree by the given amount of seconds and performs a single
+    /// update tick with given context.
+    pub fn update<'a, Ctx>(&self, dt: f32, context: &mut Ctx) -> Status
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        self.time.set(self.time.get() + dt);
+        self.tick(context)
+    }
+
+    /// Aborts every running node of the tree, so the next tick will start execution from
+    /// scratch.
+    pub fn abort<'a, Ctx>(&self, context: &mut Ctx)
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        if let BehaviorNode::Root(root) = &self.nodes[self.root] {
+            self.abort_recursive(root.child, context);
+        }
+    }
 }
 
 impl<B: Clone + 'static> Index<Handle<BehaviorNode<B>>> for BehaviorTree<B> {
@@ -305,16 +707,95 @@ where
     Inverter::new(child).add_to(tree)
 }
 
+/// Creates a new random selector.
+pub fn random_selector<B, const N: usize>(
+    children: [Handle<BehaviorNode<B>>; N],
+    tree: &mut BehaviorTree<B>,
+) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+{
+    CompositeNode::new_random_selector(children.to_vec()).add_to(tree)
+}
+
+/// Creates a new parallel node.
+pub fn parallel<B, const N: usize>(
+    policy: ParallelPolicy,
+    children: [Handle<BehaviorNode<B>>; N],
+    tree: &mut BehaviorTree<B>,
+) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+{
+    CompositeNode::new_parallel(policy, children.to_vec()).add_to(tree)
+}
+
+/// Creates a new repeat node.
+pub fn repeat<B>(
+    child: Handle<BehaviorNode<B>>,
+    count: u32,
+    tree: &mut BehaviorTree<B>,
+) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+{
+    Repeat::new(child, count).add_to(tree)
+}
+
+/// Creates a new retry node.
+pub fn retry<B>(
+    child: Handle<BehaviorNode<B>>,
+    attempts: u32,
+    tree: &mut BehaviorTree<B>,
+) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+{
+    Retry::new(child, attempts).add_to(tree)
+}
+
+/// Creates a new timeout node.
+pub fn timeout<B>(
+    child: Handle<BehaviorNode<B>>,
+    duration: f32,
+    tree: &mut BehaviorTree<B>,
+) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+{
+    Timeout::new(child, duration).add_to(tree)
+}
+
+/// Creates a new cooldown node.
+pub fn cooldown<B>(
+    child: Handle<BehaviorNode<B>>,
+    duration: f32,
+    tree: &mut BehaviorTree<B>,
+) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+{
+    Cooldown::new(child, duration).add_to(tree)
+}
+
 #[cfg(test)]
 mod test {
     use crate::{
-        core::{futures::executor::block_on, visitor::prelude::*},
+        core::{futures::executor::block_on, pool::Handle, visitor::prelude::*},
         utils::behavior::{
+            blackboard::Blackboard,
+            blackboard::{BlackboardCondition, Comparison},
+            composite::{AbortMode, ParallelPolicy},
             composite::{CompositeNode, CompositeNodeKind},
+            cooldown,
+            decorator::Timeout,
+            leaf,
             leaf::LeafNode,
-            Behavior, BehaviorTree, Status,
+            parallel, random_selector, repeat, retry, selector, sequence, timeout, Behavior,
+            BehaviorNode, BehaviorTree, Status,
         },
     };
+    use fxhash::FxHashMap;
     use std::{env, fs::File, io::Write, path::PathBuf};
 
     #[derive(Debug, PartialEq, Default, Visit, Clone)]
@@ -489,4 +970,256 @@ mod test {
 
         assert_eq!(saved_tree, loaded_tree);
     }
+
+    #[derive(Debug, PartialEq, Eq, Default, Visit, Clone)]
+    enum Action {
+        #[default]
+        None,
+        Succeed(u32),
+        Fail(u32),
+        RunFor(u32, u32),
+        SetFlag(u32),
+    }
+
+    #[derive(Default)]
+    struct Log {
+        ticks: FxHashMap<u32, u32>,
+        progress: FxHashMap<u32, u32>,
+        aborted: Vec<u32>,
+    }
+
+    impl Log {
+        fn ticks(&self, id: u32) -> u32 {
+            self.ticks.get(&id).cloned().unwrap_or_default()
+        }
+    }
+
+    impl Behavior<'_> for Action {
+        type Context = Log;
+
+        fn tick(&mut self, _context: &mut Self::Context) -> Status {
+            unreachable!()
+        }
+
+        fn tick_with_blackboard(
+            &mut self,
+            context: &mut Self::Context,
+            blackboard: &mut Blackboard,
+        ) -> Status {
+            let id = match self {
+                Action::None => unreachable!(),
+                Action::Succeed(id)
+                | Action::Fail(id)
+                | Action::RunFor(id, _)
+                | Action::SetFlag(id) => *id,
+            };
+            *context.ticks.entry(id).or_default() += 1;
+            match self {
+                Action::Succeed(_) => Status::Success,
+                Action::Fail(_) => Status::Failure,
+                Action::RunFor(id, count) => {
+                    let progress = context.progress.entry(*id).or_default();
+                    *progress += 1;
+                    if *progress >= *count {
+                        *progress = 0;
+                        Status::Success
+                    } else {
+                        Status::Running
+                    }
+                }
+                Action::SetFlag(_) => {
+                    blackboard.set("flag", true);
+                    Status::Success
+                }
+                Action::None => unreachable!(),
+            }
+        }
+
+        fn on_abort(&mut self, context: &mut Self::Context) {
+            if let Action::RunFor(id, _) = self {
+                context.progress.remove(id);
+                context.aborted.push(*id);
+            }
+        }
+    }
+
+    fn tree_with_entry(
+        f: impl FnOnce(&mut BehaviorTree<Action>) -> Handle<BehaviorNode<Action>>,
+    ) -> BehaviorTree<Action> {
+        let mut tree = BehaviorTree::new();
+        let entry = f(&mut tree);
+        tree.set_entry_node(entry);
+        tree
+    }
+
+    #[test]
+    fn test_running_resumption() {
+        let tree = tree_with_entry(|tree| {
+            let a = leaf(Action::Succeed(0), tree);
+            let b = leaf(Action::RunFor(1, 3), tree);
+            sequence([a, b], tree)
+        });
+
+        let mut log = Log::default();
+        assert_eq!(tree.tick(&mut log), Status::Running);
+        assert_eq!(tree.tick(&mut log), Status::Running);
+        assert_eq!(tree.tick(&mut log), Status::Success);
+        // The first child must not be re-evaluated while the second one is running.
+        assert_eq!(log.ticks(0), 1);
+        assert_eq!(log.ticks(1), 3);
+    }
+
+    #[test]
+    fn test_parallel() {
+        let tree = tree_with_entry(|tree| {
+            let a = leaf(Action::RunFor(0, 2), tree);
+            let b = leaf(Action::RunFor(1, 4), tree);
+            parallel(ParallelPolicy::RequireAll, [a, b], tree)
+        });
+        let mut log = Log::default();
+        for _ in 0..3 {
+            assert_eq!(tree.tick(&mut log), Status::Running);
+        }
+        assert_eq!(tree.tick(&mut log), Status::Success);
+        assert_eq!(log.ticks(0), 2);
+        assert_eq!(log.ticks(1), 4);
+
+        let tree = tree_with_entry(|tree| {
+            let a = leaf(Action::RunFor(0, 2), tree);
+            let b = leaf(Action::RunFor(1, 4), tree);
+            parallel(ParallelPolicy::RequireOne, [a, b], tree)
+        });
+        let mut log = Log::default();
+        assert_eq!(tree.tick(&mut log), Status::Running);
+        assert_eq!(tree.tick(&mut log), Status::Success);
+        assert_eq!(log.aborted, vec![1]);
+    }
+
+    #[test]
+    fn test_random_selector() {
+        let tree = tree_with_entry(|tree| {
+            let a = leaf(Action::Fail(0), tree);
+            let b = leaf(Action::Fail(1), tree);
+            let c = leaf(Action::Succeed(2), tree);
+            random_selector([a, b, c], tree)
+        });
+        let mut log = Log::default();
+        for _ in 0..20 {
+            assert_eq!(tree.tick(&mut log), Status::Success);
+        }
+        assert_eq!(log.ticks(2), 20);
+        assert!(log.ticks(0) <= 20 && log.ticks(1) <= 20);
+    }
+
+    #[test]
+    fn test_repeat_and_retry() {
+        let tree = tree_with_entry(|tree| {
+            let a = leaf(Action::Succeed(0), tree);
+            repeat(a, 3, tree)
+        });
+        let mut log = Log::default();
+        assert_eq!(tree.tick(&mut log), Status::Running);
+        assert_eq!(tree.tick(&mut log), Status::Running);
+        assert_eq!(tree.tick(&mut log), Status::Success);
+        assert_eq!(tree.tick(&mut log), Status::Running);
+        assert_eq!(log.ticks(0), 4);
+
+        let tree = tree_with_entry(|tree| {
+            let a = leaf(Action::Fail(0), tree);
+            retry(a, 2, tree)
+        });
+        let mut log = Log::default();
+        assert_eq!(tree.tick(&mut log), Status::Running);
+        assert_eq!(tree.tick(&mut log), Status::Failure);
+        assert_eq!(log.ticks(0), 2);
+    }
+
+    #[test]
+    fn test_timeout_and_cooldown() {
+        let tree = tree_with_entry(|tree| {
+            let a = leaf(Action::RunFor(0, 100), tree);
+            timeout(a, 1.0, tree)
+        });
+        let mut log = Log::default();
+        for _ in 0..3 {
+            assert_eq!(tree.update(0.4, &mut log), Status::Running);
+        }
+        assert_eq!(tree.update(0.4, &mut log), Status::Failure);
+        assert_eq!(log.aborted, vec![0]);
+
+        let tree = tree_with_entry(|tree| {
+            let a = leaf(Action::Succeed(0), tree);
+            cooldown(a, 1.0, tree)
+        });
+        let mut log = Log::default();
+        assert_eq!(tree.update(0.4, &mut log), Status::Success);
+        assert_eq!(tree.update(0.4, &mut log), Status::Failure);
+        assert_eq!(tree.update(0.4, &mut log), Status::Failure);
+        assert_eq!(tree.update(0.4, &mut log), Status::Success);
+        assert_eq!(log.ticks(0), 2);
+    }
+
+    #[test]
+    fn test_conditional_aborts() {
+        let mut tree = tree_with_entry(|tree| {
+            let condition =
+                BlackboardCondition::new("enemy", Comparison::IsSet, false).add_to(tree);
+            let attack = leaf(Action::RunFor(1, 100), tree);
+            let combat = CompositeNode::new_sequence(vec![condition, attack])
+                .with_abort(AbortMode::Both)
+                .add_to(tree);
+            let patrol = leaf(Action::RunFor(2, 100), tree);
+            selector([combat, patrol], tree)
+        });
+
+        let mut log = Log::default();
+        assert_eq!(tree.tick(&mut log), Status::Running);
+        assert_eq!(log.ticks(2), 1);
+
+        // Higher priority branch must abort the running patrol.
+        tree.blackboard_mut().set("enemy", true);
+        assert_eq!(tree.tick(&mut log), Status::Running);
+        assert_eq!(log.aborted, vec![2]);
+        assert_eq!(log.ticks(1), 1);
+
+        // The combat branch must abort itself when the condition is no longer met.
+        tree.blackboard_mut().remove("enemy");
+        assert_eq!(tree.tick(&mut log), Status::Running);
+        assert_eq!(log.aborted, vec![2, 1]);
+        assert_eq!(log.ticks(2), 2);
+    }
+
+    #[test]
+    fn test_blackboard() {
+        let tree = tree_with_entry(|tree| {
+            let set = leaf(Action::SetFlag(0), tree);
+            let check = BlackboardCondition::new("flag", Comparison::Equal, true).add_to(tree);
+            sequence([set, check], tree)
+        });
+        let mut log = Log::default();
+        assert!(!tree.blackboard().contains("flag"));
+        assert_eq!(tree.tick(&mut log), Status::Success);
+        assert_eq!(tree.blackboard().get_bool("flag"), Some(true));
+    }
+
+    #[test]
+    fn test_node_equality() {
+        fn assert_eq_impl<T: Eq>() {}
+        assert_eq_impl::<BehaviorNode<Action>>();
+
+        let timeout =
+            |duration| BehaviorNode::<Action>::Timeout(Timeout::new(Handle::NONE, duration));
+        assert_eq!(timeout(1.0), timeout(1.0));
+        assert_ne!(timeout(1.0), timeout(2.0));
+
+        let condition = |value: f32| {
+            BehaviorNode::<Action>::BlackboardCondition(BlackboardCondition::new(
+                "value",
+                Comparison::Less,
+                value,
+            ))
+        };
+        assert_eq!(condition(1.0), condition(1.0));
+        assert_ne!(condition(1.0), condition(2.0));
+    }
 }
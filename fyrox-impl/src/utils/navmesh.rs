@@ -46,6 +46,9 @@ use fxhash::{FxBuildHasher, FxHashMap};
 use fyrox_core::math::octree::{Octree, OctreeNode};
 use std::ops::{Deref, DerefMut};
 
+pub mod crowd;
+pub mod generator;
+
 #[derive(Clone, Debug, Default, Visit)]
 struct Vertex {
     triangle_index: usize,
@@ -541,6 +544,38 @@ impl Navmesh {
         }
     }
 
+    // Same as `query_closest`, but checks the given triangle and its neighbours first. Falls back
+    // to the full search if the query point does not lie above/below any of these triangles.
+    fn query_closest_near(
+        &self,
+        query_point: Vector3<f32>,
+        triangle: usize,
+    ) -> Option<(Vector3<f32>, usize)> {
+        if let Some(vertex) = self.graph.vertices.get(triangle) {
+            let mut closest = None;
+            let mut closest_distance = f32::MAX;
+            self.query_closest_internal(
+                &mut closest,
+                &mut closest_distance,
+                std::iter::once(triangle).chain(vertex.neighbours.iter().map(|n| *n as usize)),
+                query_point,
+            );
+            if let Some((point, index)) = closest {
+                let triangle = &self.triangles[index];
+                let vertices = [
+                    self.vertices[triangle[0] as usize],
+                    self.vertices[triangle[1] as usize],
+                    self.vertices[triangle[2] as usize],
+                ];
+                if math::is_point_inside_triangle(&point, &vertices) {
+                    return closest;
+                }
+            }
+        }
+
+        self.query_closest(query_point)
+    }
+
     /// Creates a temporary modification context which allows you to modify the navmesh. When the
     /// modification context is dropped, it recalculates navigation graph automatically.
     pub fn modify(&mut self) -> NavmeshModificationContext {
@@ -651,6 +686,8 @@ impl Navmesh {
 
 /// Navmesh agent is a "pathfinding unit" that performs navigation on a mesh. It is designed to
 /// cover most of simple use cases when you need to build and follow some path from point A to point B.
+/// Agents move independently and do not avoid each other, use [`crowd::Crowd`] if you need a
+/// group of agents that avoid collisions with each other.
 #[derive(Visit, Clone, Debug)]
 #[visit(optional)]
 pub struct NavmeshAgent {
@@ -893,6 +930,72 @@ impl NavmeshAgent {
         Ok(PathKind::Full)
     }
 
+    // Calculates a velocity that moves the agent towards its steering target with the agent's
+    // speed. Intermediate points of the path are considered reached when the agent is closer than
+    // its radius to them.
+    fn preferred_velocity(
+        &mut self,
+        dt: f32,
+        navmesh: &Navmesh,
+    ) -> Result<Vector3<f32>, PathError> {
+        if self.path_dirty {
+            self.calculate_path(navmesh, self.position, self.target)?;
+            self.path_dirty = false;
+        }
+
+        let arrival_distance = self.radius.max(self.speed * dt);
+        while (self.current as usize + 2) < self.path.len() {
+            let waypoint = self.path[self.current as usize + 1];
+            if (waypoint.xz() - self.position.xz()).norm() <= arrival_distance {
+                self.current += 1;
+            } else {
+                break;
+            }
+        }
+
+        let Some(waypoint) = self.steering_target() else {
+            return Ok(Vector3::default());
+        };
+
+        let delta = waypoint - self.position;
+        let distance = delta.norm();
+        if distance <= f32::EPSILON {
+            return Ok(Vector3::default());
+        }
+
+        let speed = if self.current as usize + 2 >= self.path.len() {
+            // Slow down near the destination point to prevent overshooting.
+            self.speed.min(distance / dt.max(f32::EPSILON))
+        } else {
+            self.speed
+        };
+
+        Ok(delta.scale(speed / distance))
+    }
+
+    // Moves the agent with the given velocity and keeps it on the navmesh. Returns the index of the
+    // triangle the agent is on.
+    fn move_with_velocity(
+        &mut self,
+        velocity: Vector3<f32>,
+        dt: f32,
+        navmesh: &Navmesh,
+        triangle: Option<usize>,
+    ) -> Option<usize> {
+        let desired_position = self.position + velocity.scale(dt);
+        let closest = match triangle {
+            Some(triangle) => navmesh.query_closest_near(desired_position, triangle),
+            None => navmesh.query_closest(desired_position),
+        };
+        if let Some((position, triangle)) = closest {
+            self.position = position;
+            Some(triangle)
+        } else {
+            self.position = desired_position;
+            None
+        }
+    }
+
     /// Returns current steering target which in most cases next path point from which
     /// agent is close to.
     pub fn steering_target(&self) -> Option<Vector3<f32>> {
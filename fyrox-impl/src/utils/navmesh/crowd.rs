@@ -0,0 +1,701 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Crowd simulation for navmesh agents. See [`Crowd`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Vector2, Vector3},
+        pool::{Handle, Pool},
+        visitor::prelude::*,
+    },
+    utils::navmesh::{Navmesh, NavmeshAgent},
+};
+use fxhash::FxHashMap;
+use std::ops::{Deref, DerefMut};
+
+/// Crowd agent is a navmesh agent that avoids collisions with other agents of the same [`Crowd`].
+/// It has a maximum speed, that limits the velocity the agent may use to avoid collisions (the
+/// speed of the inner [`NavmeshAgent`] is used as preferred speed), and a priority. Agents with
+/// higher priority take less responsibility for collision avoidance, so agents with lower priority
+/// give way to them.
+#[derive(Clone, Debug, Visit)]
+#[visit(optional)]
+pub struct CrowdAgent {
+    agent: NavmeshAgent,
+    max_speed: f32,
+    priority: f32,
+    velocity: Vector3<f32>,
+    #[visit(skip)]
+    triangle: Option<usize>,
+}
+
+impl Default for CrowdAgent {
+    fn default() -> Self {
+        Self::new(NavmeshAgent::default())
+    }
+}
+
+impl Deref for CrowdAgent {
+    type Target = NavmeshAgent;
+
+    fn deref(&self) -> &Self::Target {
+        &self.agent
+    }
+}
+
+impl DerefMut for CrowdAgent {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.agent
+    }
+}
+
+impl CrowdAgent {
+    /// Creates new crowd agent from the given navmesh agent. Maximum speed of the crowd agent
+    /// is set to the speed of the navmesh agent, priority is set to 1.0.
+    pub fn new(agent: NavmeshAgent) -> Self {
+        Self {
+            max_speed: agent.speed(),
+            agent,
+            priority: 1.0,
+            velocity: Default::default(),
+            triangle: None,
+        }
+    }
+
+    /// Sets new maximum speed of the agent. The agent may move faster than its preferred speed
+    /// (see [`NavmeshAgent::set_speed`]) to avoid collisions, but never faster than the maximum
+    /// speed.
+    pub fn set_max_speed(&mut self, max_speed: f32) {
+        self.max_speed = max_speed.max(0.0);
+    }
+
+    /// Returns current maximum speed of the agent.
+    pub fn max_speed(&self) -> f32 {
+        self.max_speed
+    }
+
+    /// Sets new priority of the agent. Agents with higher priority take less responsibility for
+    /// collision avoidance. For example, if one agent has priority 3.0 and another one 1.0, then
+    /// the first agent will do a quarter of the avoidance maneuver and the second one - the rest.
+    pub fn set_priority(&mut self, priority: f32) {
+        self.priority = priority.max(0.0);
+    }
+
+    /// Returns current priority of the agent.
+    pub fn priority(&self) -> f32 {
+        self.priority
+    }
+
+    /// Returns current velocity of the agent.
+    pub fn velocity(&self) -> Vector3<f32> {
+        self.velocity
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Line {
+    point: Vector2<f32>,
+    direction: Vector2<f32>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct AgentState {
+    position: Vector2<f32>,
+    velocity: Vector2<f32>,
+    preferred_velocity: Vector2<f32>,
+    radius: f32,
+    max_speed: f32,
+    priority: f32,
+}
+
+fn det(a: Vector2<f32>, b: Vector2<f32>) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+const EPSILON: f32 = 0.00001;
+const SYMMETRY_BREAKING_ANGLE: f32 = 0.01;
+
+// Finds a point on the line `line_index` closest to the optimal velocity that satisfies all
+// previous constraints and lies within the circle of the given radius.
+fn linear_program_1(
+    lines: &[Line],
+    line_index: usize,
+    radius: f32,
+    optimal_velocity: Vector2<f32>,
+    optimize_direction: bool,
+    result: &mut Vector2<f32>,
+) -> bool {
+    let line = lines[line_index];
+    let dot = line.point.dot(&line.direction);
+    let discriminant = dot * dot + radius * radius - line.point.norm_squared();
+    if discriminant < 0.0 {
+        // Maximum speed circle fully invalidates the line.
+        return false;
+    }
+
+    let sqrt_discriminant = discriminant.sqrt();
+    let mut t_left = -dot - sqrt_discriminant;
+    let mut t_right = -dot + sqrt_discriminant;
+
+    for other in &lines[..line_index] {
+        let denominator = det(line.direction, other.direction);
+        let numerator = det(other.direction, line.point - other.point);
+
+        if denominator.abs() <= EPSILON {
+            // Lines are (almost) parallel.
+            if numerator < 0.0 {
+                return false;
+            }
+            continue;
+        }
+
+        let t = numerator / denominator;
+        if denominator >= 0.0 {
+            t_right = t_right.min(t);
+        } else {
+            t_left = t_left.max(t);
+        }
+
+        if t_left > t_right {
+            return false;
+        }
+    }
+
+    let t = if optimize_direction {
+        if optimal_velocity.dot(&line.direction) > 0.0 {
+            t_right
+        } else {
+            t_left
+        }
+    } else {
+        line.direction
+            .dot(&(optimal_velocity - line.point))
+            .clamp(t_left, t_right)
+    };
+
+    *result = line.point + line.direction.scale(t);
+
+    true
+}
+
+// Finds a velocity closest to the optimal velocity that satisfies all the constraints. Returns
+// the index of the line at which the search has failed or the amount of lines on success.
+fn linear_program_2(
+    lines: &[Line],
+    radius: f32,
+    optimal_velocity: Vector2<f32>,
+    optimize_direction: bool,
+    result: &mut Vector2<f32>,
+) -> usize {
+    *result = if optimize_direction {
+        optimal_velocity.scale(radius)
+    } else if optimal_velocity.norm_squared() > radius * radius {
+        optimal_velocity.normalize().scale(radius)
+    } else {
+        optimal_velocity
+    };
+
+    for (i, line) in lines.iter().enumerate() {
+        if det(line.direction, line.point - *result) > 0.0 {
+            // The result does not satisfy the constraint.
+            let previous = *result;
+            if !linear_program_1(
+                lines,
+                i,
+                radius,
+                optimal_velocity,
+                optimize_direction,
+                result,
+            ) {
+                *result = previous;
+                return i;
+            }
+        }
+    }
+
+    lines.len()
+}
+
+// Used when the constraints cannot be satisfied, finds a velocity that minimizes the maximum
+// penetration into the constraints.
+fn linear_program_3(lines: &[Line], begin_line: usize, radius: f32, result: &mut Vector2<f32>) {
+    let mut distance = 0.0;
+    let mut projected_lines = Vec::new();
+
+    for i in begin_line..lines.len() {
+        let line = lines[i];
+        if det(line.direction, line.point - *result) <= distance {
+            continue;
+        }
+
+        projected_lines.clear();
+        for other in &lines[..i] {
+            let determinant = det(line.direction, other.direction);
+            let point = if determinant.abs() <= EPSILON {
+                if line.direction.dot(&other.direction) > 0.0 {
+                    // Lines are in the same direction.
+                    continue;
+                }
+                (line.point + other.point).scale(0.5)
+            } else {
+                line.point
+                    + line
+                        .direction
+                        .scale(det(other.direction, line.point - other.point) / determinant)
+            };
+            let Some(direction) = (other.direction - line.direction).try_normalize(EPSILON) else {
+                continue;
+            };
+            projected_lines.push(Line { point, direction });
+        }
+
+        let previous = *result;
+        if linear_program_2(
+            &projected_lines,
+            radius,
+            Vector2::new(-line.direction.y, line.direction.x),
+            true,
+            result,
+        ) < projected_lines.len()
+        {
+            // This should in principle not happen, the result is by definition already in the
+            // feasible region of this linear program. If it fails, it is due to small
+            // floating point errors, and the current result is kept.
+            *result = previous;
+        }
+
+        distance = det(line.direction, line.point - *result);
+    }
+}
+
+/// Crowd is a set of navmesh agents that move on the same navmesh and avoid collisions with each
+/// other. It uses Optimal Reciprocal Collision Avoidance (ORCA) algorithm: each agent computes a
+/// set of velocities that are guaranteed to be collision-free with its neighbours for some time
+/// (see [`Crowd::set_time_horizon`]) and selects the one closest to the velocity that moves it
+/// along its path. All computations are done on the XZ plane, agents are kept on the surface of
+/// the navmesh.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{algebra::Vector3, pool::Handle},
+/// #     utils::navmesh::{
+/// #         crowd::{Crowd, CrowdAgent},
+/// #         Navmesh, NavmeshAgentBuilder,
+/// #     },
+/// # };
+/// fn spawn_units(crowd: &mut Crowd, count: usize) -> Vec<Handle<CrowdAgent>> {
+///     (0..count)
+///         .map(|i| {
+///             let mut agent = CrowdAgent::new(
+///                 NavmeshAgentBuilder::new()
+///                     .with_position(Vector3::new(i as f32, 0.0, 0.0))
+///                     .with_speed(2.0)
+///                     .build(),
+///             );
+///             agent.set_max_speed(3.0);
+///             crowd.add_agent(agent)
+///         })
+///         .collect()
+/// }
+///
+/// fn update_units(crowd: &mut Crowd, navmesh: &Navmesh, target: Vector3<f32>, dt: f32) {
+///     for agent in crowd.agents_mut() {
+///         agent.set_target(target);
+///     }
+///     crowd.update(dt, navmesh);
+/// }
+/// ```
+#[derive(Clone, Debug, Visit)]
+#[visit(optional)]
+pub struct Crowd {
+    agents: Pool<CrowdAgent>,
+    time_horizon: f32,
+    neighbour_distance: f32,
+    max_neighbours: u32,
+}
+
+impl Default for Crowd {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Crowd {
+    /// Creates new empty crowd.
+    pub fn new() -> Self {
+        Self {
+            agents: Default::default(),
+            time_horizon: 2.0,
+            neighbour_distance: 5.0,
+            max_neighbours: 10,
+        }
+    }
+
+    /// Adds new agent to the crowd and returns its handle.
+    pub fn add_agent(&mut self, agent: CrowdAgent) -> Handle<CrowdAgent> {
+        self.agents.spawn(agent)
+    }
+
+    /// Removes the agent from the crowd and returns it.
+    pub fn remove_agent(&mut self, handle: Handle<CrowdAgent>) -> CrowdAgent {
+        self.agents.free(handle)
+    }
+
+    /// Tries to borrow an agent by its handle.
+    pub fn try_get_agent(&self, handle: Handle<CrowdAgent>) -> Option<&CrowdAgent> {
+        self.agents.try_borrow(handle)
+    }
+
+    /// Tries to borrow an agent by its handle.
+    pub fn try_get_agent_mut(&mut self, handle: Handle<CrowdAgent>) -> Option<&mut CrowdAgent> {
+        self.agents.try_borrow_mut(handle)
+    }
+
+    /// Returns an iterator over all agents of the crowd.
+    pub fn agents(&self) -> impl Iterator<Item = &CrowdAgent> {
+        self.agents.iter()
+    }
+
+    /// Returns an iterator over all agents of the crowd.
+    pub fn agents_mut(&mut self) -> impl Iterator<Item = &mut CrowdAgent> {
+        self.agents.iter_mut()
+    }
+
+    /// Returns total amount of agents in the crowd.
+    pub fn agent_count(&self) -> usize {
+        self.agents.alive_count() as usize
+    }
+
+    /// Sets the amount of time (in seconds) for which computed velocities are guaranteed to be
+    /// collision-free. Larger values make agents react to each other earlier, but make them less
+    /// free in their movements. Default is 2 seconds.
+    pub fn set_time_horizon(&mut self, time_horizon: f32) {
+        self.time_horizon = time_horizon.max(EPSILON);
+    }
+
+    /// Returns current time horizon (in seconds). See [`Self::set_time_horizon`] for more info.
+    pub fn time_horizon(&self) -> f32 {
+        self.time_horizon
+    }
+
+    /// Sets the maximum distance (in meters) at which agents take each other into account.
+    /// Default is 5 meters.
+    pub fn set_neighbour_distance(&mut self, distance: f32) {
+        self.neighbour_distance = distance.max(EPSILON);
+    }
+
+    /// Returns current neighbour distance (in meters).
+    pub fn neighbour_distance(&self) -> f32 {
+        self.neighbour_distance
+    }
+
+    /// Sets the maximum amount of the closest neighbours each agent takes into account. Default
+    /// is 10.
+    pub fn set_max_neighbours(&mut self, max_neighbours: u32) {
+        self.max_neighbours = max_neighbours;
+    }
+
+    /// Returns current maximum amount of neighbours.
+    pub fn max_neighbours(&self) -> u32 {
+        self.max_neighbours
+    }
+
+    /// Performs a single simulation step: updates paths of the agents (if needed), calculates
+    /// collision-free velocities and moves the agents. Agents that failed to find a path stay
+    /// in place, but other agents still can push them aside.
+    pub fn update(&mut self, dt: f32, navmesh: &Navmesh) {
+        if dt <= 0.0 {
+            return;
+        }
+
+        let states = self
+            .agents
+            .iter_mut()
+            .map(|agent| {
+                let preferred_velocity = agent
+                    .agent
+                    .preferred_velocity(dt, navmesh)
+                    .unwrap_or_default();
+                AgentState {
+                    position: agent.position().xz(),
+                    velocity: agent.velocity.xz(),
+                    preferred_velocity: preferred_velocity.xz(),
+                    radius: agent.radius(),
+                    max_speed: agent.max_speed,
+                    priority: agent.priority,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let new_velocities = self.compute_velocities(&states, dt);
+
+        for (agent, velocity) in self.agents.iter_mut().zip(new_velocities) {
+            let old_position = agent.position();
+            agent.triangle = agent.agent.move_with_velocity(
+                Vector3::new(velocity.x, 0.0, velocity.y),
+                dt,
+                navmesh,
+                agent.triangle,
+            );
+            agent.velocity = (agent.position() - old_position).scale(1.0 / dt);
+        }
+    }
+
+    fn compute_velocities(&self, states: &[AgentState], dt: f32) -> Vec<Vector2<f32>> {
+        // Use a uniform grid to find neighbours quickly.
+        let cell_size = self.neighbour_distance;
+        let cell_of = |position: Vector2<f32>| {
+            (
+                (position.x / cell_size).floor() as i32,
+                (position.y / cell_size).floor() as i32,
+            )
+        };
+        let mut grid = FxHashMap::<(i32, i32), Vec<usize>>::default();
+        for (i, state) in states.iter().enumerate() {
+            grid.entry(cell_of(state.position)).or_default().push(i);
+        }
+
+        let inv_time_horizon = 1.0 / self.time_horizon;
+        let inv_dt = 1.0 / dt;
+        let neighbour_distance_sqr = self.neighbour_distance * self.neighbour_distance;
+        let mut neighbours = Vec::new();
+        let mut lines = Vec::new();
+
+        states
+            .iter()
+            .enumerate()
+            .map(|(i, agent)| {
+                neighbours.clear();
+                let (cx, cz) = cell_of(agent.position);
+                for x in cx - 1..=cx + 1 {
+                    for z in cz - 1..=cz + 1 {
+                        for &j in grid.get(&(x, z)).into_iter().flatten() {
+                            let distance_sqr = (states[j].position - agent.position).norm_squared();
+                            if j != i && distance_sqr < neighbour_distance_sqr {
+                                neighbours.push((distance_sqr, j));
+                            }
+                        }
+                    }
+                }
+                neighbours.sort_by(|a, b| a.0.total_cmp(&b.0));
+                neighbours.truncate(self.max_neighbours as usize);
+
+                lines.clear();
+                for &(distance_sqr, j) in neighbours.iter() {
+                    let other = &states[j];
+                    let relative_position = other.position - agent.position;
+                    let relative_velocity = agent.velocity - other.velocity;
+                    let combined_radius = agent.radius + other.radius;
+                    let combined_radius_sqr = combined_radius * combined_radius;
+
+                    let (direction, u) = if distance_sqr > combined_radius_sqr {
+                        // No collision, the vector from the cutoff center to the relative velocity.
+                        let w = relative_velocity - relative_position.scale(inv_time_horizon);
+                        let w_length_sqr = w.norm_squared();
+                        let dot = w.dot(&relative_position);
+
+                        if dot < 0.0 && dot * dot > combined_radius_sqr * w_length_sqr {
+                            // Project on the cutoff circle.
+                            let w_length = w_length_sqr.sqrt();
+                            let unit_w = w.scale(1.0 / w_length);
+                            (
+                                Vector2::new(unit_w.y, -unit_w.x),
+                                unit_w.scale(combined_radius * inv_time_horizon - w_length),
+                            )
+                        } else {
+                            // Project on the legs.
+                            let leg = (distance_sqr - combined_radius_sqr).sqrt();
+                            let direction = if det(relative_position, w) > 0.0 {
+                                Vector2::new(
+                                    relative_position.x * leg
+                                        - relative_position.y * combined_radius,
+                                    relative_position.x * combined_radius
+                                        + relative_position.y * leg,
+                                )
+                                .scale(1.0 / distance_sqr)
+                            } else {
+                                -Vector2::new(
+                                    relative_position.x * leg
+                                        + relative_position.y * combined_radius,
+                                    -relative_position.x * combined_radius
+                                        + relative_position.y * leg,
+                                )
+                                .scale(1.0 / distance_sqr)
+                            };
+                            let dot = relative_velocity.dot(&direction);
+                            (direction, direction.scale(dot) - relative_velocity)
+                        }
+                    } else {
+                        // Agents are colliding, resolve the collision in a single step.
+                        let w = relative_velocity - relative_position.scale(inv_dt);
+                        let w_length = w.norm();
+                        let unit_w = if w_length > EPSILON {
+                            w.scale(1.0 / w_length)
+                        } else {
+                            Vector2::new(1.0, 0.0)
+                        };
+                        (
+                            Vector2::new(unit_w.y, -unit_w.x),
+                            unit_w.scale(combined_radius * inv_dt - w_length),
+                        )
+                    };
+
+                    // Agents with higher priority take less responsibility.
+                    let total_priority = agent.priority + other.priority;
+                    let responsibility = if total_priority > 0.0 {
+                        other.priority / total_priority
+                    } else {
+                        0.5
+                    };
+
+                    lines.push(Line {
+                        point: agent.velocity + u.scale(responsibility),
+                        direction,
+                    });
+                }
+
+                // Slightly rotate the preferred velocity to prevent deadlocks in perfectly
+                // symmetric cases (such as two agents moving towards each other).
+                let preferred_velocity = if lines.is_empty() {
+                    agent.preferred_velocity
+                } else {
+                    let (sin, cos) = SYMMETRY_BREAKING_ANGLE.sin_cos();
+                    let v = agent.preferred_velocity;
+                    Vector2::new(v.x * cos - v.y * sin, v.x * sin + v.y * cos)
+                };
+
+                let mut velocity = Vector2::default();
+                let failed_line = linear_program_2(
+                    &lines,
+                    agent.max_speed,
+                    preferred_velocity,
+                    false,
+                    &mut velocity,
+                );
+                if failed_line < lines.len() {
+                    linear_program_3(&lines, failed_line, agent.max_speed, &mut velocity);
+                }
+                velocity
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector3, math::TriangleDefinition},
+        utils::navmesh::{
+            crowd::{Crowd, CrowdAgent},
+            Navmesh, NavmeshAgentBuilder,
+        },
+    };
+
+    fn make_navmesh() -> Navmesh {
+        Navmesh::new(
+            vec![TriangleDefinition([0, 1, 2]), TriangleDefinition([0, 2, 3])],
+            vec![
+                Vector3::new(-10.0, 0.0, 10.0),
+                Vector3::new(10.0, 0.0, 10.0),
+                Vector3::new(10.0, 0.0, -10.0),
+                Vector3::new(-10.0, 0.0, -10.0),
+            ],
+        )
+    }
+
+    fn make_agent(position: Vector3<f32>, target: Vector3<f32>, priority: f32) -> CrowdAgent {
+        let mut agent = NavmeshAgentBuilder::new()
+            .with_position(position)
+            .with_target(target)
+            .with_speed(1.0)
+            .build();
+        agent.set_radius(0.5);
+        let mut agent = CrowdAgent::new(agent);
+        agent.set_max_speed(2.0);
+        agent.set_priority(priority);
+        agent
+    }
+
+    #[test]
+    fn test_head_on_avoidance() {
+        let navmesh = make_navmesh();
+        let mut crowd = Crowd::new();
+        let a = crowd.add_agent(make_agent(
+            Vector3::new(-5.0, 0.0, 0.0),
+            Vector3::new(5.0, 0.0, 0.0),
+            1.0,
+        ));
+        let b = crowd.add_agent(make_agent(
+            Vector3::new(5.0, 0.0, 0.0),
+            Vector3::new(-5.0, 0.0, 0.0),
+            1.0,
+        ));
+
+        let mut min_distance = f32::MAX;
+        for _ in 0..1200 {
+            crowd.update(1.0 / 60.0, &navmesh);
+            let distance = crowd
+                .try_get_agent(a)
+                .unwrap()
+                .position()
+                .metric_distance(&crowd.try_get_agent(b).unwrap().position());
+            min_distance = min_distance.min(distance);
+        }
+
+        // Agents must not intersect and both must reach their targets.
+        assert!(min_distance >= 0.95, "{min_distance}");
+        let a = crowd.try_get_agent(a).unwrap();
+        let b = crowd.try_get_agent(b).unwrap();
+        assert!(a.position().metric_distance(&a.target()) < 0.1);
+        assert!(b.position().metric_distance(&b.target()) < 0.1);
+    }
+
+    #[test]
+    fn test_priority() {
+        let navmesh = make_navmesh();
+        let mut crowd = Crowd::new();
+        let a = crowd.add_agent(make_agent(
+            Vector3::new(-5.0, 0.0, 0.0),
+            Vector3::new(5.0, 0.0, 0.0),
+            10.0,
+        ));
+        let b = crowd.add_agent(make_agent(
+            Vector3::new(5.0, 0.0, 0.0),
+            Vector3::new(-5.0, 0.0, 0.0),
+            1.0,
+        ));
+
+        let mut max_deviation_a = 0.0f32;
+        let mut max_deviation_b = 0.0f32;
+        for _ in 0..1200 {
+            crowd.update(1.0 / 60.0, &navmesh);
+            max_deviation_a =
+                max_deviation_a.max(crowd.try_get_agent(a).unwrap().position().z.abs());
+            max_deviation_b =
+                max_deviation_b.max(crowd.try_get_agent(b).unwrap().position().z.abs());
+        }
+
+        // The agent with lower priority must give way.
+        assert!(max_deviation_b > max_deviation_a * 2.0);
+    }
+}
@@ -0,0 +1,1596 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Automatic navigational mesh generation from scene geometry. See [`NavmeshGenerator`] docs for
+//! more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, Vector3},
+        log::Log,
+        math::{Matrix4Ext, TriangleDefinition},
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    scene::{
+        collider::{Collider, ColliderShape},
+        graph::Graph,
+        mesh::{
+            buffer::{VertexAttributeUsage, VertexReadTrait},
+            Mesh,
+        },
+        node::Node,
+        terrain::Terrain,
+    },
+    utils::navmesh::Navmesh,
+};
+use fxhash::FxHashMap;
+use fyrox_graph::{BaseSceneGraph, SceneGraph};
+
+/// Settings of automatic navmesh generation.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct NavmeshGenerationSettings {
+    /// Horizontal size of a voxel (in meters). Smaller values give more precise navmesh at the
+    /// cost of increased generation time.
+    #[reflect(min_value = 0.01, step = 0.05)]
+    pub cell_size: f32,
+    /// Vertical size of a voxel (in meters).
+    #[reflect(min_value = 0.01, step = 0.05)]
+    pub cell_height: f32,
+    /// Radius of agents that will walk on the navmesh. Walkable area will be shrunk by this
+    /// value, so agents won't intersect walls.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub agent_radius: f32,
+    /// Height of agents that will walk on the navmesh. Places with lower ceiling are excluded.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub agent_height: f32,
+    /// Maximum slope angle (in radians) of walkable surfaces.
+    #[reflect(min_value = 0.0, max_value = 1.5707963, step = 0.05)]
+    pub max_slope: f32,
+    /// Maximum height of a step (in meters), that agents are able to climb.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub step_height: f32,
+    /// Minimal amount of cells that an isolated walkable area must have. Smaller areas are
+    /// discarded.
+    pub min_region_area: u32,
+    /// Maximum distance (in meters) that simplified edges of the navmesh may deviate from the
+    /// voxelized outline.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub max_edge_error: f32,
+    /// Maximum size of a single polygon of the navmesh (in cells). Smaller values make the
+    /// navmesh follow height changes of the surface more closely.
+    #[reflect(min_value = 1.0)]
+    pub tile_size: u32,
+}
+
+impl Default for NavmeshGenerationSettings {
+    fn default() -> Self {
+        Self {
+            cell_size: 0.25,
+            cell_height: 0.1,
+            agent_radius: 0.4,
+            agent_height: 1.8,
+            max_slope: 45.0f32.to_radians(),
+            step_height: 0.4,
+            min_region_area: 16,
+            max_edge_error: 0.3,
+            tile_size: 32,
+        }
+    }
+}
+
+const MAX_HEIGHT: i32 = i32::MAX / 4;
+const NOT_CONNECTED: u32 = u32::MAX;
+const DIR_OFFSETS: [(i32, i32); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+#[derive(Copy, Clone, Debug)]
+struct Span {
+    min: i32,
+    max: i32,
+    walkable: bool,
+}
+
+// A set of solid spans in each column of the voxel grid.
+struct Heightfield {
+    width: i32,
+    depth: i32,
+    origin: Vector3<f32>,
+    cell_size: f32,
+    cell_height: f32,
+    columns: Vec<Vec<Span>>,
+}
+
+// Splits a convex polygon by an axis-aligned plane. Returns two parts of the polygon - the one
+// that lies "below" the plane and the one that lies "above" the plane.
+fn divide_polygon(
+    polygon: &[Vector3<f32>],
+    value: f32,
+    axis: usize,
+) -> (Vec<Vector3<f32>>, Vec<Vector3<f32>>) {
+    let mut below = Vec::new();
+    let mut above = Vec::new();
+    let count = polygon.len();
+    for i in 0..count {
+        let j = (i + count - 1) % count;
+        let dj = value - polygon[j][axis];
+        let di = value - polygon[i][axis];
+        if (dj >= 0.0) != (di >= 0.0) {
+            let t = dj / (dj - di);
+            let point = polygon[j] + (polygon[i] - polygon[j]).scale(t);
+            below.push(point);
+            above.push(point);
+            if di > 0.0 {
+                below.push(polygon[i]);
+            } else if di < 0.0 {
+                above.push(polygon[i]);
+            }
+        } else {
+            if di >= 0.0 {
+                below.push(polygon[i]);
+                if di != 0.0 {
+                    continue;
+                }
+            }
+            above.push(polygon[i]);
+        }
+    }
+    (below, above)
+}
+
+impl Heightfield {
+    fn new(width: i32, depth: i32, origin: Vector3<f32>, cell_size: f32, cell_height: f32) -> Self {
+        Self {
+            width,
+            depth,
+            origin,
+            cell_size,
+            cell_height,
+            columns: vec![Vec::new(); (width * depth) as usize],
+        }
+    }
+
+    fn column(&self, x: i32, z: i32) -> &[Span] {
+        &self.columns[(z * self.width + x) as usize]
+    }
+
+    fn add_span(&mut self, x: i32, z: i32, mut new: Span, merge_threshold: i32) {
+        let column = &mut self.columns[(z * self.width + x) as usize];
+        let mut i = 0;
+        while i < column.len() {
+            let span = column[i];
+            if span.min > new.max {
+                break;
+            }
+            if span.max < new.min {
+                i += 1;
+                continue;
+            }
+            // Merge overlapping spans, the top-most surface defines walkability.
+            new.walkable = if (span.max - new.max).abs() <= merge_threshold {
+                new.walkable || span.walkable
+            } else if span.max > new.max {
+                span.walkable
+            } else {
+                new.walkable
+            };
+            new.min = new.min.min(span.min);
+            new.max = new.max.max(span.max);
+            column.remove(i);
+        }
+        column.insert(i, new);
+    }
+
+    fn rasterize_triangle(
+        &mut self,
+        triangle: &[Vector3<f32>; 3],
+        walkable: bool,
+        merge_threshold: i32,
+    ) {
+        let inv_cell_size = 1.0 / self.cell_size;
+        let min = triangle[0].inf(&triangle[1]).inf(&triangle[2]);
+        let max = triangle[0].sup(&triangle[1]).sup(&triangle[2]);
+
+        let z0 =
+            (((min.z - self.origin.z) * inv_cell_size).floor() as i32).clamp(0, self.depth - 1);
+        let z1 =
+            (((max.z - self.origin.z) * inv_cell_size).floor() as i32).clamp(0, self.depth - 1);
+
+        let mut rest = triangle.to_vec();
+        for z in z0..=z1 {
+            let row_end = self.origin.z + (z + 1) as f32 * self.cell_size;
+            let (row, remaining) = divide_polygon(&rest, row_end, 2);
+            rest = remaining;
+            if row.len() < 3 {
+                continue;
+            }
+
+            let (row_min_x, row_max_x) = row
+                .iter()
+                .fold((f32::MAX, f32::MIN), |(a, b), v| (a.min(v.x), b.max(v.x)));
+            let x0 = (((row_min_x - self.origin.x) * inv_cell_size).floor() as i32)
+                .clamp(0, self.width - 1);
+            let x1 = (((row_max_x - self.origin.x) * inv_cell_size).floor() as i32)
+                .clamp(0, self.width - 1);
+
+            let mut row_rest = row;
+            for x in x0..=x1 {
+                let column_end = self.origin.x + (x + 1) as f32 * self.cell_size;
+                let (cell, remaining) = divide_polygon(&row_rest, column_end, 0);
+                row_rest = remaining;
+                if cell.len() < 3 {
+                    continue;
+                }
+
+                let (cell_min_y, cell_max_y) = cell
+                    .iter()
+                    .fold((f32::MAX, f32::MIN), |(a, b), v| (a.min(v.y), b.max(v.y)));
+                let min = (((cell_min_y - self.origin.y) / self.cell_height).floor() as i32)
+                    .clamp(0, MAX_HEIGHT - 1);
+                let max = (((cell_max_y - self.origin.y) / self.cell_height).ceil() as i32)
+                    .clamp(min + 1, MAX_HEIGHT);
+                self.add_span(x, z, Span { min, max, walkable }, merge_threshold);
+            }
+        }
+    }
+
+    // Allows agents to walk over low obstacles, such as curbs and stairs.
+    fn filter_low_hanging_obstacles(&mut self, walkable_climb: i32) {
+        for column in self.columns.iter_mut() {
+            let mut previous: Option<Span> = None;
+            for span in column.iter_mut() {
+                let walkable = span.walkable;
+                if let Some(previous) = previous {
+                    if !walkable && previous.walkable && span.max - previous.max <= walkable_climb {
+                        span.walkable = true;
+                    }
+                }
+                previous = Some(Span { walkable, ..*span });
+            }
+        }
+    }
+
+    // Removes walkable spans near ledges, which cannot be climbed down or up.
+    fn filter_ledges(&mut self, walkable_height: i32, walkable_climb: i32) {
+        let mut unwalkable = Vec::new();
+        for z in 0..self.depth {
+            for x in 0..self.width {
+                let column = self.column(x, z);
+                for (i, span) in column.iter().enumerate() {
+                    if !span.walkable {
+                        continue;
+                    }
+
+                    let floor = span.max;
+                    let ceiling = column.get(i + 1).map_or(MAX_HEIGHT, |s| s.min);
+                    let mut lowest_difference = MAX_HEIGHT;
+                    let mut lowest_traversable = floor;
+                    let mut highest_traversable = floor;
+
+                    'directions: for (dx, dz) in DIR_OFFSETS {
+                        let (nx, nz) = (x + dx, z + dz);
+                        if nx < 0 || nz < 0 || nx >= self.width || nz >= self.depth {
+                            lowest_difference = -walkable_climb - 1;
+                            break;
+                        }
+
+                        let neighbour_column = self.column(nx, nz);
+
+                        // Check for an open space below the first span of the neighbour column.
+                        let neighbour_ceiling =
+                            neighbour_column.first().map_or(MAX_HEIGHT, |s| s.min);
+                        if ceiling.min(neighbour_ceiling) - floor >= walkable_height {
+                            lowest_difference = -walkable_climb - 1;
+                            break;
+                        }
+
+                        for (k, neighbour) in neighbour_column.iter().enumerate() {
+                            let neighbour_floor = neighbour.max;
+                            let neighbour_ceiling =
+                                neighbour_column.get(k + 1).map_or(MAX_HEIGHT, |s| s.min);
+                            if ceiling.min(neighbour_ceiling) - floor.max(neighbour_floor)
+                                < walkable_height
+                            {
+                                continue;
+                            }
+
+                            let difference = neighbour_floor - floor;
+                            lowest_difference = lowest_difference.min(difference);
+                            if difference.abs() <= walkable_climb {
+                                lowest_traversable = lowest_traversable.min(neighbour_floor);
+                                highest_traversable = highest_traversable.max(neighbour_floor);
+                            } else if difference < -walkable_climb {
+                                break 'directions;
+                            }
+                        }
+                    }
+
+                    if lowest_difference < -walkable_climb
+                        || highest_traversable - lowest_traversable > walkable_climb
+                    {
+                        unwalkable.push(((z * self.width + x) as usize, i));
+                    }
+                }
+            }
+        }
+
+        for (column, span) in unwalkable {
+            self.columns[column][span].walkable = false;
+        }
+    }
+
+    // Removes walkable spans that do not have enough free space above them.
+    fn filter_low_height_spans(&mut self, walkable_height: i32) {
+        for column in self.columns.iter_mut() {
+            for i in 0..column.len() {
+                let ceiling = column.get(i + 1).map_or(MAX_HEIGHT, |s| s.min);
+                if ceiling - column[i].max < walkable_height {
+                    column[i].walkable = false;
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct CompactSpan {
+    y: i32,
+    height: i32,
+    connections: [u32; 4],
+    region: u32,
+    walkable: bool,
+}
+
+// A set of walkable spans with links between them.
+struct CompactHeightfield {
+    width: i32,
+    depth: i32,
+    // Index of the first span and the amount of spans in each cell.
+    cells: Vec<(usize, usize)>,
+    spans: Vec<CompactSpan>,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Sweep {
+    id: u32,
+    neighbour: Option<u32>,
+    count: usize,
+    shared: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct ContourPoint {
+    x: i32,
+    y: i32,
+    z: i32,
+    region: u32,
+}
+
+impl CompactHeightfield {
+    fn new(heightfield: &Heightfield, walkable_height: i32, walkable_climb: i32) -> Self {
+        let mut cells = Vec::with_capacity(heightfield.columns.len());
+        let mut spans = Vec::new();
+        for column in heightfield.columns.iter() {
+            let start = spans.len();
+            for (i, span) in column.iter().enumerate() {
+                if span.walkable {
+                    let ceiling = column.get(i + 1).map_or(MAX_HEIGHT, |s| s.min);
+                    spans.push(CompactSpan {
+                        y: span.max,
+                        height: ceiling - span.max,
+                        connections: [NOT_CONNECTED; 4],
+                        region: 0,
+                        walkable: true,
+                    });
+                }
+            }
+            cells.push((start, spans.len() - start));
+        }
+
+        let mut compact = Self {
+            width: heightfield.width,
+            depth: heightfield.depth,
+            cells,
+            spans,
+        };
+
+        for z in 0..compact.depth {
+            for x in 0..compact.width {
+                for i in compact.cell_spans(x, z) {
+                    for (dir, (dx, dz)) in DIR_OFFSETS.into_iter().enumerate() {
+                        let (nx, nz) = (x + dx, z + dz);
+                        if nx < 0 || nz < 0 || nx >= compact.width || nz >= compact.depth {
+                            continue;
+                        }
+                        let span = &compact.spans[i];
+                        let (y, top) = (span.y, span.y + span.height);
+                        let neighbour = compact.cell_spans(nx, nz).find(|k| {
+                            let other = &compact.spans[*k];
+                            let bottom = y.max(other.y);
+                            let ceiling = top.min(other.y + other.height);
+                            ceiling - bottom >= walkable_height
+                                && (other.y - y).abs() <= walkable_climb
+                        });
+                        if let Some(neighbour) = neighbour {
+                            compact.spans[i].connections[dir] = neighbour as u32;
+                        }
+                    }
+                }
+            }
+        }
+
+        compact
+    }
+
+    fn cell_spans(&self, x: i32, z: i32) -> std::ops::Range<usize> {
+        let (start, count) = self.cells[(z * self.width + x) as usize];
+        start..start + count
+    }
+
+    fn neighbour(&self, span: usize, dir: usize) -> Option<usize> {
+        let connection = self.spans[span].connections[dir];
+        (connection != NOT_CONNECTED).then_some(connection as usize)
+    }
+
+    // Removes all links to and from unwalkable spans.
+    fn disconnect_unwalkable(&mut self) {
+        for i in 0..self.spans.len() {
+            for dir in 0..4 {
+                if let Some(neighbour) = self.neighbour(i, dir) {
+                    if !self.spans[i].walkable || !self.spans[neighbour].walkable {
+                        self.spans[i].connections[dir] = NOT_CONNECTED;
+                    }
+                }
+            }
+        }
+    }
+
+    // Shrinks walkable area by the given radius (in cells) using chamfer distance transform.
+    fn erode(&mut self, radius: i32) {
+        if radius <= 0 {
+            return;
+        }
+
+        let mut distance = vec![u8::MAX; self.spans.len()];
+        for (i, span) in self.spans.iter().enumerate() {
+            if span.connections.contains(&NOT_CONNECTED) {
+                distance[i] = 0;
+            }
+        }
+
+        let relax = |distance: &mut [u8], i: usize, dir: usize, diagonal_dir: usize| {
+            if let Some(a) = self.neighbour(i, dir) {
+                distance[i] = distance[i].min(distance[a].saturating_add(2));
+                if let Some(b) = self.neighbour(a, diagonal_dir) {
+                    distance[i] = distance[i].min(distance[b].saturating_add(3));
+                }
+            }
+        };
+
+        for z in 0..self.depth {
+            for x in 0..self.width {
+                for i in self.cell_spans(x, z) {
+                    relax(&mut distance, i, 0, 3);
+                    relax(&mut distance, i, 3, 2);
+                }
+            }
+        }
+
+        for z in (0..self.depth).rev() {
+            for x in (0..self.width).rev() {
+                for i in self.cell_spans(x, z) {
+                    relax(&mut distance, i, 2, 1);
+                    relax(&mut distance, i, 1, 0);
+                }
+            }
+        }
+
+        let threshold = (radius * 2).min(u8::MAX as i32) as u8;
+        for (span, distance) in self.spans.iter_mut().zip(distance) {
+            if distance < threshold {
+                span.walkable = false;
+            }
+        }
+
+        self.disconnect_unwalkable();
+    }
+
+    // Removes isolated walkable areas that are smaller than the given amount of cells.
+    fn remove_small_islands(&mut self, min_area: usize) {
+        let mut visited = vec![false; self.spans.len()];
+        let mut stack = Vec::new();
+        let mut island = Vec::new();
+        for start in 0..self.spans.len() {
+            if visited[start] || !self.spans[start].walkable {
+                continue;
+            }
+
+            island.clear();
+            stack.push(start);
+            visited[start] = true;
+            while let Some(i) = stack.pop() {
+                island.push(i);
+                for dir in 0..4 {
+                    if let Some(neighbour) = self.neighbour(i, dir) {
+                        if !visited[neighbour] {
+                            visited[neighbour] = true;
+                            stack.push(neighbour);
+                        }
+                    }
+                }
+            }
+
+            if island.len() < min_area {
+                for &i in island.iter() {
+                    self.spans[i].walkable = false;
+                }
+            }
+        }
+
+        self.disconnect_unwalkable();
+    }
+
+    // Splits walkable area into a set of monotone regions (regions without holes). Regions never
+    // cross borders of tiles of the given size.
+    fn build_regions(&mut self, tile_size: i32) {
+        let tile_size = tile_size.max(1);
+        let mut next_id = 1;
+        let mut sweeps = Vec::<Sweep>::new();
+        let mut neighbour_counts = Vec::<usize>::new();
+
+        for z in 0..self.depth {
+            sweeps.clear();
+            sweeps.push(Sweep::default());
+            neighbour_counts.clear();
+            neighbour_counts.resize(next_id as usize, 0);
+
+            for x in 0..self.width {
+                for i in self.cell_spans(x, z) {
+                    if !self.spans[i].walkable {
+                        continue;
+                    }
+
+                    // Continue the sweep of the previous span in the row.
+                    let mut sweep_index = 0;
+                    if x % tile_size != 0 {
+                        if let Some(a) = self.neighbour(i, 0) {
+                            sweep_index = self.spans[a].region as usize;
+                        }
+                    }
+                    if sweep_index == 0 {
+                        sweeps.push(Sweep::default());
+                        sweep_index = sweeps.len() - 1;
+                    }
+
+                    // Check whether the sweep can be merged with a region of the previous row.
+                    if z % tile_size != 0 {
+                        if let Some(a) = self.neighbour(i, 3) {
+                            let region = self.spans[a].region;
+                            if region != 0 {
+                                let sweep = &mut sweeps[sweep_index];
+                                if !sweep.shared && sweep.neighbour.map_or(true, |n| n == region) {
+                                    sweep.neighbour = Some(region);
+                                    sweep.count += 1;
+                                    neighbour_counts[region as usize] += 1;
+                                } else {
+                                    sweep.shared = true;
+                                }
+                            }
+                        }
+                    }
+
+                    self.spans[i].region = sweep_index as u32;
+                }
+            }
+
+            for sweep in sweeps.iter_mut().skip(1) {
+                match sweep.neighbour {
+                    Some(neighbour)
+                        if !sweep.shared && neighbour_counts[neighbour as usize] == sweep.count =>
+                    {
+                        sweep.id = neighbour;
+                    }
+                    _ => {
+                        sweep.id = next_id;
+                        next_id += 1;
+                    }
+                }
+            }
+
+            for x in 0..self.width {
+                for i in self.cell_spans(x, z) {
+                    if self.spans[i].walkable {
+                        self.spans[i].region = sweeps[self.spans[i].region as usize].id;
+                    }
+                }
+            }
+        }
+    }
+
+    fn region_of_neighbour(&self, span: usize, dir: usize) -> u32 {
+        self.neighbour(span, dir)
+            .map_or(0, |neighbour| self.spans[neighbour].region)
+    }
+
+    // Calculates height of a corner of a span so neighbouring contours will have the same height
+    // at shared vertices.
+    fn corner_height(&self, span: usize, dir: usize) -> i32 {
+        let next_dir = (dir + 1) & 3;
+        let mut height = self.spans[span].y;
+        if let Some(a) = self.neighbour(span, dir) {
+            height = height.max(self.spans[a].y);
+            if let Some(b) = self.neighbour(a, next_dir) {
+                height = height.max(self.spans[b].y);
+            }
+        }
+        if let Some(a) = self.neighbour(span, next_dir) {
+            height = height.max(self.spans[a].y);
+            if let Some(b) = self.neighbour(a, dir) {
+                height = height.max(self.spans[b].y);
+            }
+        }
+        height
+    }
+
+    fn walk_contour(
+        &self,
+        mut x: i32,
+        mut z: i32,
+        mut i: usize,
+        flags: &mut [u8],
+    ) -> Vec<ContourPoint> {
+        let mut points = Vec::new();
+        let mut dir = flags[i].trailing_zeros() as usize;
+        let start_dir = dir;
+        let start = i;
+
+        for _ in 0..self.spans.len() * 4 + 4 {
+            if flags[i] & (1 << dir) != 0 {
+                let (px, pz) = match dir {
+                    0 => (x, z + 1),
+                    1 => (x + 1, z + 1),
+                    2 => (x + 1, z),
+                    _ => (x, z),
+                };
+                points.push(ContourPoint {
+                    x: px,
+                    y: self.corner_height(i, dir),
+                    z: pz,
+                    region: self.region_of_neighbour(i, dir),
+                });
+                flags[i] &= !(1 << dir);
+                dir = (dir + 1) & 3;
+            } else {
+                let Some(neighbour) = self.neighbour(i, dir) else {
+                    break;
+                };
+                let (dx, dz) = DIR_OFFSETS[dir];
+                x += dx;
+                z += dz;
+                i = neighbour;
+                dir = (dir + 3) & 3;
+            }
+
+            if i == start && dir == start_dir {
+                break;
+            }
+        }
+
+        points
+    }
+
+    fn build_contours(&self, max_error: f32) -> Vec<Vec<[i32; 3]>> {
+        let mut flags = vec![0u8; self.spans.len()];
+        for (i, span) in self.spans.iter().enumerate() {
+            if !span.walkable || span.region == 0 {
+                continue;
+            }
+            let mut same_region = 0;
+            for dir in 0..4 {
+                if self.region_of_neighbour(i, dir) == span.region {
+                    same_region |= 1 << dir;
+                }
+            }
+            flags[i] = same_region ^ 0xf;
+        }
+
+        let mut contours = Vec::new();
+        for z in 0..self.depth {
+            for x in 0..self.width {
+                for i in self.cell_spans(x, z) {
+                    if flags[i] == 0 {
+                        continue;
+                    }
+                    let raw = self.walk_contour(x, z, i, &mut flags);
+                    let contour = simplify_contour(&raw, max_error);
+                    if contour.len() >= 3 {
+                        contours.push(contour);
+                    }
+                }
+            }
+        }
+        contours
+    }
+}
+
+fn distance_to_segment_sqr(p: &ContourPoint, a: (i32, i32), b: (i32, i32)) -> f32 {
+    let (px, pz) = (p.x as f32, p.z as f32);
+    let (ax, az) = (a.0 as f32, a.1 as f32);
+    let (dx, dz) = (b.0 as f32 - ax, b.1 as f32 - az);
+    let length_sqr = dx * dx + dz * dz;
+    let t = if length_sqr > 0.0 {
+        (((px - ax) * dx + (pz - az) * dz) / length_sqr).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let (ex, ez) = (ax + t * dx - px, az + t * dz - pz);
+    ex * ex + ez * ez
+}
+
+// Simplifies raw contour, keeping all the points where the neighbouring region changes (so
+// contours of adjacent regions share the same vertices) and tessellating wall edges until the
+// error becomes less than the given threshold (in cells).
+fn simplify_contour(raw: &[ContourPoint], max_error: f32) -> Vec<[i32; 3]> {
+    let count = raw.len();
+    let mut simplified = Vec::<usize>::new();
+
+    if raw.iter().any(|p| p.region != 0) {
+        for i in 0..count {
+            if raw[i].region != raw[(i + 1) % count].region {
+                simplified.push(i);
+            }
+        }
+    }
+
+    if simplified.is_empty() {
+        let lower_left = (0..count).min_by_key(|i| (raw[*i].x, raw[*i].z));
+        let upper_right = (0..count).max_by_key(|i| (raw[*i].x, raw[*i].z));
+        if let (Some(lower_left), Some(upper_right)) = (lower_left, upper_right) {
+            simplified.push(lower_left);
+            simplified.push(upper_right);
+        }
+    }
+
+    let max_error_sqr = max_error * max_error;
+    let mut i = 0;
+    while i < simplified.len() {
+        let a = simplified[i];
+        let b = simplified[(i + 1) % simplified.len()];
+
+        // Traverse the segment in lexicographic order, so the result does not depend on the
+        // direction of the segment.
+        let (pa, pb) = ((raw[a].x, raw[a].z), (raw[b].x, raw[b].z));
+        let (mut current, step, end, pa, pb) = if pb > pa {
+            ((a + 1) % count, 1, b, pa, pb)
+        } else {
+            ((b + count - 1) % count, count - 1, a, pb, pa)
+        };
+
+        let mut max_distance = 0.0;
+        let mut max_index = None;
+        // Tessellate only wall edges, shared edges must stay straight.
+        if raw[current].region == 0 {
+            while current != end {
+                let distance = distance_to_segment_sqr(&raw[current], pa, pb);
+                if distance > max_distance {
+                    max_distance = distance;
+                    max_index = Some(current);
+                }
+                current = (current + step) % count;
+            }
+        }
+
+        match max_index {
+            Some(index) if max_distance > max_error_sqr => simplified.insert(i + 1, index),
+            _ => i += 1,
+        }
+    }
+
+    let mut contour = simplified
+        .into_iter()
+        .map(|i| [raw[i].x, raw[i].y, raw[i].z])
+        .collect::<Vec<_>>();
+
+    // Remove degenerate segments.
+    let mut i = 0;
+    while contour.len() > 1 && i < contour.len() {
+        let next = (i + 1) % contour.len();
+        if contour[i][0] == contour[next][0] && contour[i][2] == contour[next][2] {
+            contour.remove(next);
+        } else {
+            i += 1;
+        }
+    }
+
+    contour
+}
+
+fn area2(a: &[i32; 3], b: &[i32; 3], c: &[i32; 3]) -> i64 {
+    (b[0] - a[0]) as i64 * (c[2] - a[2]) as i64 - (c[0] - a[0]) as i64 * (b[2] - a[2]) as i64
+}
+
+// Triangulates a simple polygon using ear clipping. Output triangles have negative area (in the
+// terms of `area2`), which means that they're facing up.
+fn triangulate(polygon: &[[i32; 3]]) -> Vec<[usize; 3]> {
+    let mut indices = (0..polygon.len()).collect::<Vec<_>>();
+
+    let signed_area = (0..polygon.len())
+        .map(|i| {
+            let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+            a[0] as i64 * b[2] as i64 - b[0] as i64 * a[2] as i64
+        })
+        .sum::<i64>();
+    if signed_area > 0 {
+        indices.reverse();
+    }
+
+    let same_position =
+        |a: usize, b: usize| polygon[a][0] == polygon[b][0] && polygon[a][2] == polygon[b][2];
+
+    let mut triangles = Vec::new();
+    while indices.len() > 3 {
+        let count = indices.len();
+        let mut best = None;
+        let mut best_length = i64::MAX;
+        for k in 0..count {
+            let (prev, current, next) = (
+                indices[(k + count - 1) % count],
+                indices[k],
+                indices[(k + 1) % count],
+            );
+            let (a, b, c) = (&polygon[prev], &polygon[current], &polygon[next]);
+            if area2(a, b, c) >= 0 {
+                continue;
+            }
+
+            let blocked = indices.iter().any(|&other| {
+                if same_position(other, prev)
+                    || same_position(other, current)
+                    || same_position(other, next)
+                {
+                    return false;
+                }
+                let p = &polygon[other];
+                area2(a, b, p) <= 0 && area2(b, c, p) <= 0 && area2(c, a, p) <= 0
+            });
+            if blocked {
+                continue;
+            }
+
+            let (dx, dz) = ((c[0] - a[0]) as i64, (c[2] - a[2]) as i64);
+            let length = dx * dx + dz * dz;
+            if length < best_length {
+                best_length = length;
+                best = Some(k);
+            }
+        }
+
+        let Some(k) = best else {
+            break;
+        };
+
+        triangles.push([
+            indices[(k + count - 1) % count],
+            indices[k],
+            indices[(k + 1) % count],
+        ]);
+        indices.remove(k);
+    }
+
+    if let [a, b, c] = indices[..] {
+        if area2(&polygon[a], &polygon[b], &polygon[c]) < 0 {
+            triangles.push([a, b, c]);
+        }
+    }
+
+    triangles
+}
+
+/// Navmesh generator creates navigational meshes from arbitrary scene geometry. It uses an
+/// approach similar to the one used in [Recast](https://github.com/recastnavigation/recastnavigation):
+///
+/// 1) Input triangles are voxelized into a set of solid spans; spans that have walkable slope are
+/// marked as walkable.
+/// 2) Walkable spans that do not have enough free space above them or located near ledges are
+/// filtered out, low obstacles (less than the step height) are marked as walkable.
+/// 3) Walkable area is shrunk by agent radius, small isolated areas are removed.
+/// 4) Walkable area is split into simple regions, outlines of each region are traced, simplified
+/// and triangulated.
+///
+/// The generator accepts geometry of meshes, terrains and colliders. Keep in mind, that the
+/// quality of the resulting navmesh and the generation time highly depend on the size of a cell
+/// (see [`NavmeshGenerationSettings`]).
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::pool::Handle,
+/// #     scene::{graph::Graph, node::Node},
+/// #     utils::navmesh::{
+/// #         generator::{NavmeshGenerationSettings, NavmeshGenerator},
+/// #         Navmesh,
+/// #     },
+/// # };
+/// fn generate_navmesh(graph: &Graph, level: Handle<Node>) -> Navmesh {
+///     let mut generator = NavmeshGenerator::new(NavmeshGenerationSettings {
+///         agent_radius: 0.5,
+///         ..Default::default()
+///     });
+///     // Collect geometry of the level node and all its descendants.
+///     generator.add_node(graph, level);
+///     generator.generate()
+/// }
+/// ```
+#[derive(Default, Clone, Debug)]
+pub struct NavmeshGenerator {
+    settings: NavmeshGenerationSettings,
+    triangles: Vec<[Vector3<f32>; 3]>,
+}
+
+impl NavmeshGenerator {
+    /// Maximum amount of cells of the voxel grid (its width multiplied by its depth). Generation of
+    /// larger navmeshes would consume too much memory, use larger [`NavmeshGenerationSettings::cell_size`]
+    /// in this case.
+    pub const MAX_GRID_CELLS: u64 = 2048 * 2048;
+
+    /// Creates new navmesh generator with the given settings.
+    pub fn new(settings: NavmeshGenerationSettings) -> Self {
+        Self {
+            settings,
+            triangles: Default::default(),
+        }
+    }
+
+    /// Returns current generation settings.
+    pub fn settings(&self) -> &NavmeshGenerationSettings {
+        &self.settings
+    }
+
+    /// Returns a set of input triangles (in world space).
+    pub fn triangles(&self) -> &[[Vector3<f32>; 3]] {
+        &self.triangles
+    }
+
+    /// Adds a triangle (in world space) to the input geometry. Triangles with counter-clockwise
+    /// winding (when looking at their front side) and with normals pointing up are considered
+    /// walkable, if their slope is less than [`NavmeshGenerationSettings::max_slope`].
+    pub fn add_triangle(&mut self, triangle: [Vector3<f32>; 3]) {
+        self.triangles.push(triangle);
+    }
+
+    /// Adds geometry of every surface of the given mesh to the input geometry.
+    pub fn add_mesh(&mut self, mesh: &Mesh) {
+        let transform = mesh.global_transform();
+        let mirrored = is_mirroring(&transform);
+        for surface in mesh.surfaces() {
+            let data = surface.data();
+            let data = data.data_ref();
+            let vertex_buffer = &data.vertex_buffer;
+            let position = |index: u32| {
+                vertex_buffer
+                    .get(index as usize)
+                    .and_then(|v| v.read_3_f32(VertexAttributeUsage::Position).ok())
+                    .map(|p| transform.transform_point(&Point3::from(p)).coords)
+            };
+            for triangle in data.geometry_buffer.iter() {
+                if let (Some(a), Some(b), Some(c)) = (
+                    position(triangle[0]),
+                    position(triangle[1]),
+                    position(triangle[2]),
+                ) {
+                    self.triangles.push(oriented([a, b, c], mirrored));
+                }
+            }
+        }
+    }
+
+    /// Adds geometry of every chunk of the given terrain to the input geometry. Holes of the
+    /// terrain are excluded.
+    pub fn add_terrain(&mut self, terrain: &Terrain) {
+        let transform = terrain.global_transform();
+        let mirrored = is_mirroring(&transform);
+        let height_map_size = terrain.height_map_size();
+        let cell_count = height_map_size.map(|n| n.saturating_sub(3));
+        if cell_count.x == 0 || cell_count.y == 0 {
+            return;
+        }
+        let chunk_size = terrain.chunk_size();
+
+        for chunk in terrain.chunks_ref() {
+            let heightmap = chunk.heightmap().data_ref();
+            let Some(heights) = heightmap.data_of_type::<f32>() else {
+                continue;
+            };
+            let hole_mask = chunk.hole_mask().map(|mask| mask.data_ref());
+            let holes = hole_mask
+                .as_ref()
+                .and_then(|mask| mask.data_of_type::<u8>());
+
+            let origin = chunk.position();
+            let vertex = |ix: u32, iy: u32| {
+                let height = heights[((iy + 1) * height_map_size.x + ix + 1) as usize];
+                transform
+                    .transform_point(&Point3::new(
+                        origin.x + ix as f32 / cell_count.x as f32 * chunk_size.x,
+                        height,
+                        origin.z + iy as f32 / cell_count.y as f32 * chunk_size.y,
+                    ))
+                    .coords
+            };
+
+            for iy in 0..cell_count.y {
+                for ix in 0..cell_count.x {
+                    if let Some(holes) = holes {
+                        if holes
+                            .get((iy * cell_count.x + ix) as usize)
+                            .is_some_and(|value| *value < 128)
+                        {
+                            continue;
+                        }
+                    }
+
+                    let a = vertex(ix, iy);
+                    let b = vertex(ix + 1, iy);
+                    let c = vertex(ix + 1, iy + 1);
+                    let d = vertex(ix, iy + 1);
+                    self.triangles.push(oriented([a, c, b], mirrored));
+                    self.triangles.push(oriented([a, d, c], mirrored));
+                }
+            }
+        }
+    }
+
+    fn add_transformed(&mut self, transform: &Matrix4<f32>, triangles: &[[Vector3<f32>; 3]]) {
+        let mirrored = is_mirroring(transform);
+        for triangle in triangles {
+            self.triangles.push(oriented(
+                triangle.map(|v| transform.transform_point(&Point3::from(v)).coords),
+                mirrored,
+            ));
+        }
+    }
+
+    /// Adds geometry of the given collider to the input geometry. Round shapes are approximated
+    /// with polygons, geometry of trimeshes, height fields and convex polyhedra is taken from their
+    /// geometry sources.
+    pub fn add_collider(&mut self, graph: &Graph, collider: &Collider) {
+        let transform = collider.global_transform();
+        match collider.shape() {
+            ColliderShape::Ball(ball) => {
+                self.add_transformed(&transform, &make_sphere(Vector3::default(), ball.radius))
+            }
+            ColliderShape::Cylinder(cylinder) => self.add_transformed(
+                &transform,
+                &make_cylinder(
+                    Vector3::new(0.0, -cylinder.half_height, 0.0),
+                    Vector3::new(0.0, cylinder.half_height, 0.0),
+                    cylinder.radius,
+                    cylinder.radius,
+                ),
+            ),
+            ColliderShape::Cone(cone) => self.add_transformed(
+                &transform,
+                &make_cylinder(
+                    Vector3::new(0.0, -cone.half_height, 0.0),
+                    Vector3::new(0.0, cone.half_height, 0.0),
+                    cone.radius,
+                    0.0,
+                ),
+            ),
+            ColliderShape::Cuboid(cuboid) => {
+                self.add_transformed(&transform, &make_cuboid(cuboid.half_extents))
+            }
+            ColliderShape::Capsule(capsule) => {
+                let mut triangles =
+                    make_cylinder(capsule.begin, capsule.end, capsule.radius, capsule.radius);
+                triangles.extend(make_sphere(capsule.begin, capsule.radius));
+                triangles.extend(make_sphere(capsule.end, capsule.radius));
+                self.add_transformed(&transform, &triangles)
+            }
+            ColliderShape::Segment(_) => (),
+            ColliderShape::Triangle(triangle) => {
+                self.add_transformed(&transform, &[[triangle.a, triangle.b, triangle.c]])
+            }
+            ColliderShape::Trimesh(trimesh) => {
+                for source in trimesh.sources.iter() {
+                    self.add_geometry_source(graph, source.0);
+                }
+            }
+            ColliderShape::Heightfield(heightfield) => {
+                self.add_geometry_source(graph, heightfield.geometry_source.0)
+            }
+            ColliderShape::Polyhedron(polyhedron) => {
+                self.add_geometry_source(graph, polyhedron.geometry_source.0)
+            }
+        }
+    }
+
+    fn add_geometry_source(&mut self, graph: &Graph, source: Handle<Node>) {
+        if let Some(node) = graph.try_get(source) {
+            if let Some(mesh) = node.cast::<Mesh>() {
+                self.add_mesh(mesh);
+            } else if let Some(terrain) = node.cast::<Terrain>() {
+                self.add_terrain(terrain);
+            }
+        }
+    }
+
+    /// Adds geometry of the given node and all its descendants to the input geometry. Meshes,
+    /// terrains and colliders are supported, any other nodes are ignored.
+    pub fn add_node(&mut self, graph: &Graph, node: Handle<Node>) {
+        if !graph.is_valid_handle(node) {
+            return;
+        }
+
+        for (_, node) in graph.traverse_iter(node) {
+            if let Some(mesh) = node.cast::<Mesh>() {
+                self.add_mesh(mesh);
+            } else if let Some(terrain) = node.cast::<Terrain>() {
+                self.add_terrain(terrain);
+            } else if let Some(collider) = node.cast::<Collider>() {
+                self.add_collider(graph, collider);
+            }
+        }
+    }
+
+    /// Generates new navmesh from the input geometry.
+    pub fn generate(&self) -> Navmesh {
+        if self.triangles.is_empty() {
+            return Navmesh::default();
+        }
+
+        let settings = &self.settings;
+        let cell_size = settings.cell_size.max(0.01);
+        let cell_height = settings.cell_height.max(0.01);
+        let walkable_height = (settings.agent_height / cell_height).ceil() as i32;
+        let walkable_climb = (settings.step_height / cell_height).floor() as i32;
+        let walkable_radius = (settings.agent_radius / cell_size).ceil() as i32;
+        let min_slope_cos = settings.max_slope.cos();
+
+        let (min, max) = self.triangles.iter().flatten().fold(
+            (Vector3::repeat(f32::MAX), Vector3::repeat(f32::MIN)),
+            |(min, max), v| (min.inf(v), max.sup(v)),
+        );
+        let width = ((max.x - min.x) / cell_size).ceil() as u64 + 1;
+        let depth = ((max.z - min.z) / cell_size).ceil() as u64 + 1;
+        if width.saturating_mul(depth) > Self::MAX_GRID_CELLS {
+            Log::err(format!(
+                "Unable to generate a navmesh: the voxel grid is too large ({width}x{depth} cells, \
+                the limit is {} cells). Increase the cell size or reduce the input geometry.",
+                Self::MAX_GRID_CELLS
+            ));
+            return Navmesh::default();
+        }
+        let (width, depth) = (width as i32, depth as i32);
+
+        let mut heightfield = Heightfield::new(width, depth, min, cell_size, cell_height);
+        for triangle in self.triangles.iter() {
+            let normal = (triangle[1] - triangle[0]).cross(&(triangle[2] - triangle[0]));
+            let walkable = normal
+                .try_normalize(f32::EPSILON)
+                .is_some_and(|normal| normal.y >= min_slope_cos);
+            heightfield.rasterize_triangle(triangle, walkable, walkable_climb);
+        }
+        heightfield.filter_low_hanging_obstacles(walkable_climb);
+        heightfield.filter_ledges(walkable_height, walkable_climb);
+        heightfield.filter_low_height_spans(walkable_height);
+
+        let mut compact = CompactHeightfield::new(&heightfield, walkable_height, walkable_climb);
+        compact.erode(walkable_radius);
+        compact.remove_small_islands(settings.min_region_area as usize);
+        compact.build_regions(settings.tile_size.min(i32::MAX as u32) as i32);
+
+        let contours = compact.build_contours(settings.max_edge_error / cell_size);
+
+        // Merge contours into a single mesh, contours of adjacent regions share vertices.
+        let mut vertices = Vec::new();
+        let mut triangles = Vec::new();
+        let mut vertex_map = FxHashMap::<(i32, i32), Vec<(i32, u32)>>::default();
+        for contour in contours.iter() {
+            let indices = contour
+                .iter()
+                .map(|&[x, y, z]| {
+                    let candidates = vertex_map.entry((x, z)).or_default();
+                    if let Some((_, index)) = candidates.iter().find(|(h, _)| (h - y).abs() <= 2) {
+                        *index
+                    } else {
+                        let index = vertices.len() as u32;
+                        vertices.push(Vector3::new(
+                            min.x + x as f32 * cell_size,
+                            min.y + y as f32 * cell_height,
+                            min.z + z as f32 * cell_size,
+                        ));
+                        candidates.push((y, index));
+                        index
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            for [a, b, c] in triangulate(contour) {
+                let triangle = [indices[a], indices[b], indices[c]];
+                if triangle[0] != triangle[1]
+                    && triangle[1] != triangle[2]
+                    && triangle[0] != triangle[2]
+                {
+                    triangles.push(TriangleDefinition(triangle));
+                }
+            }
+        }
+
+        Navmesh::new(triangles, vertices)
+    }
+}
+
+fn make_cuboid(half_extents: Vector3<f32>) -> Vec<[Vector3<f32>; 3]> {
+    let corner = |x: f32, y: f32, z: f32| {
+        Vector3::new(x * half_extents.x, y * half_extents.y, z * half_extents.z)
+    };
+    let faces = [
+        // Top and bottom.
+        [
+            (-1.0, 1.0, -1.0),
+            (-1.0, 1.0, 1.0),
+            (1.0, 1.0, 1.0),
+            (1.0, 1.0, -1.0),
+        ],
+        [
+            (-1.0, -1.0, -1.0),
+            (1.0, -1.0, -1.0),
+            (1.0, -1.0, 1.0),
+            (-1.0, -1.0, 1.0),
+        ],
+        // Sides.
+        [
+            (-1.0, -1.0, -1.0),
+            (-1.0, -1.0, 1.0),
+            (-1.0, 1.0, 1.0),
+            (-1.0, 1.0, -1.0),
+        ],
+        [
+            (1.0, -1.0, -1.0),
+            (1.0, 1.0, -1.0),
+            (1.0, 1.0, 1.0),
+            (1.0, -1.0, 1.0),
+        ],
+        [
+            (-1.0, -1.0, -1.0),
+            (-1.0, 1.0, -1.0),
+            (1.0, 1.0, -1.0),
+            (1.0, -1.0, -1.0),
+        ],
+        [
+            (-1.0, -1.0, 1.0),
+            (1.0, -1.0, 1.0),
+            (1.0, 1.0, 1.0),
+            (-1.0, 1.0, 1.0),
+        ],
+    ];
+    let mut triangles = Vec::with_capacity(12);
+    for face in faces {
+        let [a, b, c, d] = face.map(|(x, y, z)| corner(x, y, z));
+        triangles.push([a, b, c]);
+        triangles.push([a, c, d]);
+    }
+    triangles
+}
+
+const ROUND_SHAPE_SEGMENTS: usize = 12;
+
+/// Checks whether the given transform mirrors the geometry, thus changing the winding of triangles.
+fn is_mirroring(transform: &Matrix4<f32>) -> bool {
+    transform.basis().determinant() < 0.0
+}
+
+/// Restores counter-clockwise winding of a triangle, that was transformed by a mirroring transform.
+fn oriented(triangle: [Vector3<f32>; 3], mirrored: bool) -> [Vector3<f32>; 3] {
+    let [a, b, c] = triangle;
+    if mirrored {
+        [a, c, b]
+    } else {
+        [a, b, c]
+    }
+}
+
+fn make_sphere(center: Vector3<f32>, radius: f32) -> Vec<[Vector3<f32>; 3]> {
+    let rings = ROUND_SHAPE_SEGMENTS / 2;
+    let point = |ring: usize, segment: usize| {
+        let theta = std::f32::consts::PI * ring as f32 / rings as f32;
+        let phi = std::f32::consts::TAU * segment as f32 / ROUND_SHAPE_SEGMENTS as f32;
+        center
+            + Vector3::new(
+                theta.sin() * phi.cos(),
+                theta.cos(),
+                theta.sin() * phi.sin(),
+            )
+            .scale(radius)
+    };
+    let mut triangles = Vec::new();
+    for ring in 0..rings {
+        for segment in 0..ROUND_SHAPE_SEGMENTS {
+            let a = point(ring, segment);
+            let b = point(ring, segment + 1);
+            let c = point(ring + 1, segment + 1);
+            let d = point(ring + 1, segment);
+            triangles.push([a, b, c]);
+            triangles.push([a, c, d]);
+        }
+    }
+    triangles
+}
+
+fn make_cylinder(
+    begin: Vector3<f32>,
+    end: Vector3<f32>,
+    begin_radius: f32,
+    end_radius: f32,
+) -> Vec<[Vector3<f32>; 3]> {
+    let axis = (end - begin)
+        .try_normalize(f32::EPSILON)
+        .unwrap_or_else(Vector3::y);
+    let side = if axis.x.abs() < 0.9 {
+        Vector3::x()
+    } else {
+        Vector3::z()
+    };
+    let u = axis.cross(&side).normalize();
+    let v = axis.cross(&u);
+    let ring = |center: Vector3<f32>, radius: f32, segment: usize| {
+        let angle = std::f32::consts::TAU * segment as f32 / ROUND_SHAPE_SEGMENTS as f32;
+        center + (u.scale(angle.cos()) + v.scale(angle.sin())).scale(radius)
+    };
+    let mut triangles = Vec::new();
+    for segment in 0..ROUND_SHAPE_SEGMENTS {
+        let a = ring(begin, begin_radius, segment);
+        let b = ring(begin, begin_radius, segment + 1);
+        let c = ring(end, end_radius, segment + 1);
+        let d = ring(end, end_radius, segment);
+        triangles.push([a, b, c]);
+        triangles.push([a, c, d]);
+        triangles.push([begin, b, a]);
+        triangles.push([end, d, c]);
+    }
+    triangles
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        scene::{
+            base::BaseBuilder,
+            collider::{ColliderBuilder, ColliderShape},
+            graph::Graph,
+            transform::TransformBuilder,
+        },
+        utils::navmesh::{
+            generator::{NavmeshGenerationSettings, NavmeshGenerator},
+            Navmesh,
+        },
+    };
+
+    fn add_quad(generator: &mut NavmeshGenerator, min: Vector3<f32>, max: Vector3<f32>) {
+        let a = Vector3::new(min.x, min.y, min.z);
+        let b = Vector3::new(min.x, min.y, max.z);
+        let c = Vector3::new(max.x, max.y, max.z);
+        let d = Vector3::new(max.x, max.y, min.z);
+        generator.add_triangle([a, b, c]);
+        generator.add_triangle([a, c, d]);
+    }
+
+    // Checks whether the triangles closest to the given points are connected.
+    fn is_reachable(navmesh: &Navmesh, from: Vector3<f32>, to: Vector3<f32>) -> bool {
+        let (_, from) = navmesh.query_closest(from).unwrap();
+        let (_, to) = navmesh.query_closest(to).unwrap();
+        let mut visited = vec![false; navmesh.graph.vertices.len()];
+        let mut stack = vec![from];
+        while let Some(index) = stack.pop() {
+            if index == to {
+                return true;
+            }
+            if !std::mem::replace(&mut visited[index], true) {
+                stack.extend(
+                    navmesh.graph.vertices[index]
+                        .neighbours
+                        .iter()
+                        .map(|n| *n as usize),
+                );
+            }
+        }
+        false
+    }
+
+    fn covers(navmesh: &Navmesh, x: f32, z: f32) -> bool {
+        navmesh.triangles().iter().any(|triangle| {
+            let [a, b, c] = triangle.0.map(|i| navmesh.vertices()[i as usize]);
+            let edge = |p: Vector3<f32>, q: Vector3<f32>| {
+                (q.x - p.x) * (z - p.z) - (x - p.x) * (q.z - p.z)
+            };
+            let (e0, e1, e2) = (edge(a, b), edge(b, c), edge(c, a));
+            (e0 <= 0.0 && e1 <= 0.0 && e2 <= 0.0) || (e0 >= 0.0 && e1 >= 0.0 && e2 >= 0.0)
+        })
+    }
+
+    #[test]
+    fn test_flat_plane() {
+        let mut generator = NavmeshGenerator::new(NavmeshGenerationSettings {
+            agent_radius: 0.5,
+            ..Default::default()
+        });
+        add_quad(
+            &mut generator,
+            Vector3::new(-5.0, 0.0, -5.0),
+            Vector3::new(5.0, 0.0, 5.0),
+        );
+
+        let navmesh = generator.generate();
+
+        assert!(!navmesh.triangles().is_empty());
+        for vertex in navmesh.vertices() {
+            assert!(vertex.y.abs() <= 0.1 + f32::EPSILON);
+            // Walkable area must be shrunk by the agent radius.
+            assert!(vertex.x.abs() <= 4.5 + f32::EPSILON);
+            assert!(vertex.z.abs() <= 4.5 + f32::EPSILON);
+        }
+        // All triangles must face up.
+        for triangle in navmesh.triangles() {
+            let [a, b, c] = triangle.0.map(|i| navmesh.vertices()[i as usize]);
+            assert!((b - a).cross(&(c - a)).y > 0.0);
+        }
+        assert!(covers(&navmesh, 0.0, 0.0));
+        assert!(is_reachable(
+            &navmesh,
+            Vector3::new(-4.0, 0.0, -4.0),
+            Vector3::new(4.0, 0.0, 4.0)
+        ));
+    }
+
+    #[test]
+    fn test_steep_slope_and_high_step() {
+        let mut generator = NavmeshGenerator::new(NavmeshGenerationSettings {
+            agent_radius: 0.0,
+            ..Default::default()
+        });
+        // Floor.
+        add_quad(
+            &mut generator,
+            Vector3::new(-5.0, 0.0, -5.0),
+            Vector3::new(0.0, 0.0, 5.0),
+        );
+        // Steep slope (60 degrees).
+        add_quad(
+            &mut generator,
+            Vector3::new(0.0, 0.0, -5.0),
+            Vector3::new(1.0, 1.732, 5.0),
+        );
+        // A platform which is too high to climb on.
+        add_quad(
+            &mut generator,
+            Vector3::new(1.0, 1.732, -5.0),
+            Vector3::new(5.0, 1.732, 5.0),
+        );
+
+        let navmesh = generator.generate();
+
+        assert!(covers(&navmesh, -2.5, 0.0));
+        assert!(covers(&navmesh, 3.0, 0.0));
+        assert!(!covers(&navmesh, 0.5, 0.0));
+        assert!(!is_reachable(
+            &navmesh,
+            Vector3::new(-2.5, 0.0, 0.0),
+            Vector3::new(3.0, 1.732, 0.0)
+        ));
+    }
+
+    #[test]
+    fn test_generate_from_colliders() {
+        let mut graph = Graph::new();
+        let ground = ColliderBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, -0.1, 0.0))
+                    .build(),
+            ),
+        )
+        .with_shape(ColliderShape::cuboid(5.0, 0.1, 5.0))
+        .build(&mut graph);
+        let obstacle = ColliderBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 1.0, 0.0))
+                    .build(),
+            ),
+        )
+        .with_shape(ColliderShape::cuboid(1.0, 1.0, 1.0))
+        .build(&mut graph);
+        graph.update_hierarchical_data();
+
+        let mut generator = NavmeshGenerator::new(NavmeshGenerationSettings {
+            agent_radius: 0.5,
+            ..Default::default()
+        });
+        generator.add_node(&graph, ground);
+        generator.add_node(&graph, obstacle);
+        let navmesh = generator.generate();
+
+        // The top of the obstacle is too small to be walkable, the area around the obstacle is
+        // walkable and the obstacle is circumvented.
+        assert!(!covers(&navmesh, 0.0, 0.0));
+        assert!(!covers(&navmesh, 1.3, 0.0));
+        assert!(covers(&navmesh, 2.0, 0.0));
+        assert!(covers(&navmesh, -2.0, 0.0));
+        assert!(is_reachable(
+            &navmesh,
+            Vector3::new(-3.0, 0.0, 0.0),
+            Vector3::new(3.0, 0.0, 0.0)
+        ));
+    }
+
+    #[test]
+    fn test_winding() {
+        let settings = NavmeshGenerationSettings {
+            agent_radius: 0.0,
+            ..Default::default()
+        };
+
+        // Triangles facing down (a ceiling) are not walkable.
+        let mut generator = NavmeshGenerator::new(settings.clone());
+        let [a, b, c, d] = [(-5.0, -5.0), (-5.0, 5.0), (5.0, 5.0), (5.0, -5.0)]
+            .map(|(x, z)| Vector3::new(x, 0.0, z));
+        generator.add_triangle([a, c, b]);
+        generator.add_triangle([a, d, c]);
+        assert!(generator.generate().triangles().is_empty());
+
+        // Mirroring transform must not flip the walkable surfaces.
+        let mut graph = Graph::new();
+        let ground = ColliderBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_scale(Vector3::new(-1.0, 1.0, 1.0))
+                    .build(),
+            ),
+        )
+        .with_shape(ColliderShape::cuboid(5.0, 0.1, 5.0))
+        .build(&mut graph);
+        graph.update_hierarchical_data();
+
+        let mut generator = NavmeshGenerator::new(settings);
+        generator.add_node(&graph, ground);
+        let navmesh = generator.generate();
+        assert!(covers(&navmesh, 0.0, 0.0));
+        for vertex in navmesh.vertices() {
+            assert!((vertex.y - 0.1).abs() <= 0.1 + f32::EPSILON);
+        }
+    }
+
+    #[test]
+    fn test_grid_size_limit() {
+        let mut generator = NavmeshGenerator::new(NavmeshGenerationSettings {
+            cell_size: 0.01,
+            ..Default::default()
+        });
+        add_quad(
+            &mut generator,
+            Vector3::new(-1000.0, 0.0, -1000.0),
+            Vector3::new(1000.0, 0.0, 1000.0),
+        );
+        assert!(generator.generate().triangles().is_empty());
+    }
+}
@@ -27,10 +27,12 @@ use crate::{
     core::{
         append_extension,
         futures::future::join_all,
+        instant::Instant,
         io::FileLoadError,
         log::Log,
         make_relative_path, notify,
         parking_lot::{Mutex, MutexGuard},
+        profiler,
         task::TaskPool,
         watcher::FileSystemWatcher,
         TypeUuidProvider,
@@ -751,7 +753,18 @@ impl ResourceManagerState {
         let event_broadcaster = self.event_broadcaster.clone();
         let loader_future = loader.load(path.clone(), self.resource_io.clone());
         self.task_pool.spawn_task(async move {
-            match loader_future.await {
+            let start = Instant::now();
+            let result = loader_future.await;
+            if profiler::is_recording() {
+                profiler::record_span(
+                    "ResourceManager::load",
+                    Some(path.display().to_string()),
+                    start,
+                    Instant::now(),
+                );
+            }
+
+            match result {
                 Ok(data) => {
                     let data = data.0;
 
@@ -0,0 +1,320 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! First-person controller, that moves a character using keyboard and rotates its view using mouse.
+//! See [`FirstPersonController`] docs for more info.
+
+use crate::utils::{is_in_hierarchy_of, set_global_rotation};
+use fyrox::{
+    core::{
+        algebra::{Point3, UnitQuaternion, Vector3},
+        impl_component_provider,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid_provider,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    event::{DeviceEvent, ElementState, Event, WindowEvent},
+    gui::{key::KeyBinding, message::KeyCode},
+    scene::{
+        character_controller::CharacterController,
+        graph::{
+            physics::{Intersection, RayCastOptions},
+            Graph,
+        },
+        node::Node,
+        rigidbody::RigidBody,
+    },
+    script::{ScriptContext, ScriptTrait},
+    utils,
+};
+use std::ops::Range;
+
+/// First-person controller moves a character using keyboard keys and rotates it using mouse. The
+/// script must be assigned to a [`CharacterController`] node or to a dynamic [`RigidBody`] (with
+/// locked rotations). Yaw is applied to the node itself, pitch is applied to the camera node, that
+/// should be a child of the node.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct FirstPersonController {
+    #[reflect(description = "A camera node (must be a child of the node), that will be pitched.")]
+    pub camera: InheritableVariable<Handle<Node>>,
+
+    #[reflect(description = "Current yaw of the character (in radians).")]
+    pub yaw: InheritableVariable<f32>,
+
+    #[reflect(description = "Current pitch of the camera (in radians).")]
+    pub pitch: InheritableVariable<f32>,
+
+    #[reflect(description = "Angular limit of the pitch of the camera (in radians).")]
+    pub pitch_limit: InheritableVariable<Range<f32>>,
+
+    #[reflect(description = "Mouse sensitivity.")]
+    pub sensitivity: InheritableVariable<f32>,
+
+    #[reflect(description = "Walking speed (in meters per second).", min_value = 0.0)]
+    pub walk_speed: InheritableVariable<f32>,
+
+    #[reflect(description = "Running speed (in meters per second).", min_value = 0.0)]
+    pub run_speed: InheritableVariable<f32>,
+
+    #[reflect(description = "Initial vertical speed of a jump (in meters per second).")]
+    pub jump_speed: InheritableVariable<f32>,
+
+    #[reflect(
+        description = "Gravity acceleration (in meters per second squared). It is used only for \
+        character controllers, rigid bodies use the gravity of the physics world."
+    )]
+    pub gravity: InheritableVariable<f32>,
+
+    #[reflect(
+        description = "Length of a ray, that is used to check whether a rigid body stands on the \
+        ground. The ray is cast down from the origin of the body.",
+        min_value = 0.0
+    )]
+    pub ground_probe_length: InheritableVariable<f32>,
+
+    #[reflect(description = "A key, that corresponds to forward movement.")]
+    pub move_forward_key: InheritableVariable<KeyBinding>,
+
+    #[reflect(description = "A key, that corresponds to backward movement.")]
+    pub move_backward_key: InheritableVariable<KeyBinding>,
+
+    #[reflect(description = "A key, that corresponds to left movement.")]
+    pub move_left_key: InheritableVariable<KeyBinding>,
+
+    #[reflect(description = "A key, that corresponds to right movement.")]
+    pub move_right_key: InheritableVariable<KeyBinding>,
+
+    #[reflect(description = "A key, that should be held to run.")]
+    pub run_key: InheritableVariable<KeyBinding>,
+
+    #[reflect(description = "A key, that corresponds to jumping.")]
+    pub jump_key: InheritableVariable<KeyBinding>,
+
+    #[reflect(hidden)]
+    pub vertical_velocity: f32,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub move_forward: bool,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub move_backward: bool,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub move_left: bool,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub move_right: bool,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub run: bool,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub jump_requested: bool,
+}
+
+impl Default for FirstPersonController {
+    fn default() -> Self {
+        Self {
+            camera: Default::default(),
+            yaw: Default::default(),
+            pitch: Default::default(),
+            pitch_limit: ((-89.9f32).to_radians()..89.9f32.to_radians()).into(),
+            sensitivity: 0.3.into(),
+            walk_speed: 3.0.into(),
+            run_speed: 6.0.into(),
+            jump_speed: 5.0.into(),
+            gravity: 9.81.into(),
+            ground_probe_length: 1.1.into(),
+            move_forward_key: KeyBinding::Some(KeyCode::KeyW).into(),
+            move_backward_key: KeyBinding::Some(KeyCode::KeyS).into(),
+            move_left_key: KeyBinding::Some(KeyCode::KeyA).into(),
+            move_right_key: KeyBinding::Some(KeyCode::KeyD).into(),
+            run_key: KeyBinding::Some(KeyCode::ShiftLeft).into(),
+            jump_key: KeyBinding::Some(KeyCode::Space).into(),
+            vertical_velocity: 0.0,
+            move_forward: false,
+            move_backward: false,
+            move_left: false,
+            move_right: false,
+            run: false,
+            jump_requested: false,
+        }
+    }
+}
+
+impl_component_provider!(FirstPersonController);
+uuid_provider!(FirstPersonController = "6f2b1e0d-3a8c-4d27-b0e5-9c4a7d1f8e63");
+
+impl FirstPersonController {
+    fn desired_horizontal_velocity(&self) -> Vector3<f32> {
+        let yaw = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), *self.yaw);
+        let look = yaw * Vector3::z();
+        let side = yaw * Vector3::x();
+
+        let mut direction = Vector3::default();
+        if self.move_forward {
+            direction += look;
+        }
+        if self.move_backward {
+            direction -= look;
+        }
+        if self.move_left {
+            direction += side;
+        }
+        if self.move_right {
+            direction -= side;
+        }
+
+        let speed = if self.run {
+            *self.run_speed
+        } else {
+            *self.walk_speed
+        };
+
+        direction
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_default()
+            .scale(speed)
+    }
+
+    fn has_ground_below(&self, graph: &Graph, handle: Handle<Node>) -> bool {
+        let mut intersections = Vec::<Intersection>::new();
+        graph.physics.cast_ray(
+            RayCastOptions {
+                ray_origin: Point3::from(graph[handle].global_position()),
+                ray_direction: -Vector3::y(),
+                max_len: *self.ground_probe_length,
+                groups: Default::default(),
+                sort_results: false,
+            },
+            &mut intersections,
+        );
+        intersections
+            .iter()
+            .any(|i| !is_in_hierarchy_of(graph, i.collider, handle))
+    }
+}
+
+impl ScriptTrait for FirstPersonController {
+    fn on_os_event(&mut self, event: &Event<()>, context: &mut ScriptContext) {
+        match event {
+            Event::WindowEvent {
+                event: WindowEvent::KeyboardInput { event, .. },
+                ..
+            } => {
+                let pressed = event.state == ElementState::Pressed;
+
+                for (binding, state) in [
+                    (&self.move_forward_key, &mut self.move_forward),
+                    (&self.move_backward_key, &mut self.move_backward),
+                    (&self.move_left_key, &mut self.move_left),
+                    (&self.move_right_key, &mut self.move_right),
+                    (&self.run_key, &mut self.run),
+                ] {
+                    if let KeyBinding::Some(key_code) = **binding {
+                        if utils::translate_key_from_ui(key_code) == event.physical_key {
+                            *state = pressed;
+                        }
+                    }
+                }
+
+                if let KeyBinding::Some(key_code) = *self.jump_key {
+                    if pressed
+                        && !event.repeat
+                        && utils::translate_key_from_ui(key_code) == event.physical_key
+                    {
+                        self.jump_requested = true;
+                    }
+                }
+            }
+            Event::DeviceEvent {
+                event: DeviceEvent::MouseMotion { delta, .. },
+                ..
+            } => {
+                let speed = *self.sensitivity * context.dt;
+                *self.yaw -= (delta.0 as f32) * speed;
+                *self.pitch = (*self.pitch + delta.1 as f32 * speed)
+                    .max(self.pitch_limit.start)
+                    .min(self.pitch_limit.end);
+            }
+            _ => {}
+        }
+    }
+
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        let jump_requested = std::mem::take(&mut self.jump_requested);
+        let horizontal_velocity = self.desired_horizontal_velocity();
+        let graph = &mut context.scene.graph;
+
+        set_global_rotation(
+            graph,
+            context.handle,
+            UnitQuaternion::from_axis_angle(&Vector3::y_axis(), *self.yaw),
+        );
+        if let Some(camera) = graph.try_get_mut(*self.camera) {
+            camera
+                .local_transform_mut()
+                .set_rotation(UnitQuaternion::from_axis_angle(
+                    &Vector3::x_axis(),
+                    *self.pitch,
+                ));
+        }
+
+        // Character controllers detect the ground on their own, rigid bodies need a probe.
+        let has_ground_below = graph[context.handle].cast::<RigidBody>().is_some()
+            && self.has_ground_below(graph, context.handle);
+
+        let node = &mut graph[context.handle];
+        if let Some(character) = node.cast_mut::<CharacterController>() {
+            if character.is_grounded() {
+                // Small downward velocity keeps the character snapped to the ground, so it is
+                // detected on each frame.
+                self.vertical_velocity = if jump_requested {
+                    *self.jump_speed
+                } else {
+                    -0.1
+                };
+            } else {
+                self.vertical_velocity -= *self.gravity * context.dt;
+            }
+
+            character.move_by(
+                (horizontal_velocity + Vector3::new(0.0, self.vertical_velocity, 0.0))
+                    .scale(context.dt),
+            );
+        } else if let Some(rigid_body) = node.cast_mut::<RigidBody>() {
+            let mut velocity = rigid_body.lin_vel();
+            velocity.x = horizontal_velocity.x;
+            velocity.z = horizontal_velocity.z;
+            if jump_requested && has_ground_below {
+                velocity.y = *self.jump_speed;
+            }
+            rigid_body.set_lin_vel(velocity);
+        }
+    }
+}
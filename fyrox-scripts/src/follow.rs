@@ -0,0 +1,155 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Scripts, that make a node to follow another node ([`Follow`]) or to look at it ([`LookAt`]).
+
+use crate::utils::{set_global_position, set_global_rotation, smoothing_factor};
+use fyrox::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        impl_component_provider,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid_provider,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    graph::BaseSceneGraph,
+    scene::node::Node,
+    script::{ScriptContext, ScriptTrait},
+};
+
+/// Follow script moves its node to the position of a target node (plus some offset) with optional
+/// smoothing. It could be used for simple cameras, companions, floating health bars, etc.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct Follow {
+    #[reflect(description = "A node to follow.")]
+    pub target: InheritableVariable<Handle<Node>>,
+
+    #[reflect(description = "An offset from the position of the target.")]
+    pub offset: InheritableVariable<Vector3<f32>>,
+
+    #[reflect(
+        description = "Defines whether the offset is in the local space of the target (so it rotates \
+        with the target) or in world space."
+    )]
+    pub offset_in_target_space: InheritableVariable<bool>,
+
+    #[reflect(
+        description = "Amount of time (in seconds) it takes to cover most of the distance to the \
+        desired position. Zero means instant following.",
+        min_value = 0.0
+    )]
+    pub smoothing_time: InheritableVariable<f32>,
+}
+
+impl Default for Follow {
+    fn default() -> Self {
+        Self {
+            target: Default::default(),
+            offset: Default::default(),
+            offset_in_target_space: false.into(),
+            smoothing_time: 0.1.into(),
+        }
+    }
+}
+
+impl_component_provider!(Follow);
+uuid_provider!(Follow = "c5c63b5f-0c0f-4d3d-9d5d-0b1f6bfe4a31");
+
+impl ScriptTrait for Follow {
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        let graph = &mut ctx.scene.graph;
+        let Some(target) = graph.try_get(*self.target) else {
+            return;
+        };
+
+        let offset = if *self.offset_in_target_space {
+            target.global_transform().transform_vector(&self.offset)
+        } else {
+            *self.offset
+        };
+        let desired_position = target.global_position() + offset;
+
+        let current_position = graph[ctx.handle].global_position();
+        let position = current_position.lerp(
+            &desired_position,
+            smoothing_factor(*self.smoothing_time, ctx.dt),
+        );
+        set_global_position(graph, ctx.handle, position);
+    }
+}
+
+/// Look-at script rotates its node so that its look vector (local Z axis) points at a target node.
+/// The rotation could be optionally smoothed.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct LookAt {
+    #[reflect(description = "A node to look at.")]
+    pub target: InheritableVariable<Handle<Node>>,
+
+    #[reflect(description = "Up vector (in world space) that is used to orient the node.")]
+    pub up: InheritableVariable<Vector3<f32>>,
+
+    #[reflect(
+        description = "Amount of time (in seconds) it takes to mostly turn to the target. Zero \
+        means instant rotation.",
+        min_value = 0.0
+    )]
+    pub smoothing_time: InheritableVariable<f32>,
+}
+
+impl Default for LookAt {
+    fn default() -> Self {
+        Self {
+            target: Default::default(),
+            up: Vector3::y().into(),
+            smoothing_time: 0.0.into(),
+        }
+    }
+}
+
+impl_component_provider!(LookAt);
+uuid_provider!(LookAt = "2d6f7b64-5b4f-4d8c-a5e6-4a0f0f5e2c9b");
+
+impl ScriptTrait for LookAt {
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        let graph = &mut ctx.scene.graph;
+        let Some(target) = graph.try_get(*self.target) else {
+            return;
+        };
+
+        let direction = target.global_position() - graph[ctx.handle].global_position();
+        // Facing direction cannot be calculated if the direction is collinear with the up vector.
+        if direction.cross(&self.up).norm_squared() <= f32::EPSILON {
+            return;
+        }
+
+        let desired_rotation = UnitQuaternion::face_towards(&direction, &self.up);
+        let current_rotation = graph.global_rotation(ctx.handle);
+        let rotation = current_rotation
+            .try_slerp(
+                &desired_rotation,
+                smoothing_factor(*self.smoothing_time, ctx.dt),
+                f32::EPSILON,
+            )
+            .unwrap_or(desired_rotation);
+        set_global_rotation(graph, ctx.handle, rotation);
+    }
+}
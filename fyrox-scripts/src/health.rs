@@ -0,0 +1,197 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Health component, that could be attached to any object that can be damaged or destroyed. See
+//! [`Health`] docs for more info and usage examples.
+
+use fyrox::{
+    core::{
+        impl_component_provider, pool::Handle, reflect::prelude::*, uuid_provider,
+        variable::InheritableVariable, visitor::prelude::*,
+    },
+    graph::BaseSceneGraph,
+    scene::node::Node,
+    script::{ScriptContext, ScriptMessageContext, ScriptMessagePayload, ScriptTrait},
+};
+
+/// A message, that applies damage to a node with [`Health`] script. The message must be sent to a
+/// particular node (see [`fyrox::script::ScriptMessageSender::send_to_target`]). Negative damage heals the node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DamageMessage {
+    /// Amount of damage.
+    pub amount: f32,
+    /// A node, that has dealt the damage. Could be [`Handle::NONE`].
+    pub source: Handle<Node>,
+}
+
+/// A global message, that is sent by [`Health`] script when its node dies. Subscribe to the message
+/// to implement scoring, respawning, loot dropping, etc.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeathMessage {
+    /// A node that died.
+    pub node: Handle<Node>,
+    /// A node, that has dealt the last damage. Could be [`Handle::NONE`].
+    pub killer: Handle<Node>,
+}
+
+/// Health component stores the amount of health of an object and handles [`DamageMessage`]s sent to
+/// it. When the health drops to zero, the component sends a global [`DeathMessage`] and optionally
+/// removes its node from the scene. Typical usage could be like this:
+///
+/// ```rust
+/// # use fyrox::{core::pool::Handle, scene::node::Node, script::ScriptContext};
+/// # use fyrox_scripts::health::DamageMessage;
+/// fn shoot(target: Handle<Node>, ctx: &mut ScriptContext) {
+///     ctx.message_sender.send_to_target(
+///         target,
+///         DamageMessage {
+///             amount: 25.0,
+///             source: ctx.handle,
+///         },
+///     );
+/// }
+/// ```
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct Health {
+    #[reflect(description = "Maximum amount of health.", min_value = 0.0)]
+    pub max_health: InheritableVariable<f32>,
+
+    #[reflect(description = "Current amount of health.", min_value = 0.0)]
+    pub health: InheritableVariable<f32>,
+
+    #[reflect(
+        description = "Amount of health restored per second. Dead objects do not regenerate.",
+        min_value = 0.0
+    )]
+    pub regeneration: InheritableVariable<f32>,
+
+    #[reflect(
+        description = "Amount of time (in seconds) after a hit during which the object ignores \
+        any further damage.",
+        min_value = 0.0
+    )]
+    pub invulnerability_time: InheritableVariable<f32>,
+
+    #[reflect(description = "Defines whether the node should be removed when it dies.")]
+    pub remove_on_death: InheritableVariable<bool>,
+
+    #[reflect(hidden)]
+    pub invulnerability_timer: f32,
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self {
+            max_health: 100.0.into(),
+            health: 100.0.into(),
+            regeneration: 0.0.into(),
+            invulnerability_time: 0.0.into(),
+            remove_on_death: false.into(),
+            invulnerability_timer: 0.0,
+        }
+    }
+}
+
+impl_component_provider!(Health);
+uuid_provider!(Health = "b7c1b1c4-7f8e-4b0c-8d4c-2e3f6a9d1c57");
+
+impl Health {
+    /// Returns `true` if the health has dropped to zero.
+    pub fn is_dead(&self) -> bool {
+        *self.health <= 0.0
+    }
+
+    /// Returns current health in `[0; 1]` range. Could be used to show health bars.
+    pub fn health_fraction(&self) -> f32 {
+        if *self.max_health > 0.0 {
+            (*self.health / *self.max_health).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// Restores the given amount of health. Dead objects could not be healed, use [`Self::revive`]
+    /// instead.
+    pub fn heal(&mut self, amount: f32) {
+        if !self.is_dead() {
+            self.health.set_value_and_mark_modified(
+                (*self.health + amount.max(0.0)).min(*self.max_health),
+            );
+        }
+    }
+
+    /// Restores full health of the object.
+    pub fn revive(&mut self) {
+        self.health.set_value_and_mark_modified(*self.max_health);
+        self.invulnerability_timer = 0.0;
+    }
+
+    /// Applies the given amount of damage. Returns `true` if the damage has killed the object.
+    /// Damage is ignored while the object is dead or invulnerable.
+    pub fn damage(&mut self, amount: f32) -> bool {
+        if amount < 0.0 {
+            self.heal(-amount);
+            return false;
+        }
+
+        if self.is_dead() || self.invulnerability_timer > 0.0 {
+            return false;
+        }
+
+        self.health
+            .set_value_and_mark_modified((*self.health - amount).max(0.0));
+        self.invulnerability_timer = *self.invulnerability_time;
+
+        self.is_dead()
+    }
+}
+
+impl ScriptTrait for Health {
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        ctx.message_dispatcher
+            .subscribe_to::<DamageMessage>(ctx.handle);
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        self.invulnerability_timer = (self.invulnerability_timer - ctx.dt).max(0.0);
+        if *self.regeneration > 0.0 {
+            self.heal(*self.regeneration * ctx.dt);
+        }
+    }
+
+    fn on_message(
+        &mut self,
+        message: &mut dyn ScriptMessagePayload,
+        ctx: &mut ScriptMessageContext,
+    ) {
+        if let Some(damage) = message.downcast_ref::<DamageMessage>() {
+            if self.damage(damage.amount) {
+                ctx.message_sender.send_global(DeathMessage {
+                    node: ctx.handle,
+                    killer: damage.source,
+                });
+
+                if *self.remove_on_death {
+                    ctx.scene.graph.remove_node(ctx.handle);
+                }
+            }
+        }
+    }
+}
@@ -20,10 +20,28 @@
 
 //! A set of useful scripts that can be used to in your game.
 
-use crate::camera::FlyingCameraController;
+use crate::{
+    camera::FlyingCameraController,
+    first_person::FirstPersonController,
+    follow::{Follow, LookAt},
+    health::Health,
+    motion::{Oscillator, Rotator},
+    spawner::ObjectSpawner,
+    third_person::ThirdPersonCamera,
+    trigger::TriggerVolume,
+};
 use fyrox::script::constructor::ScriptConstructorContainer;
 
 pub mod camera;
+pub mod first_person;
+pub mod follow;
+pub mod health;
+pub mod motion;
+pub mod spawner;
+pub mod third_person;
+pub mod trigger;
+
+mod utils;
 
 /// Registers every script from the crate in the given constructor container. Use it, if you want to register all
 /// available scripts at once. Typical usage could be like this:
@@ -47,4 +65,13 @@ pub mod camera;
 /// ```
 pub fn register(container: &ScriptConstructorContainer) {
     container.add::<FlyingCameraController>("Fyrox Flying Camera Controller");
+    container.add::<ThirdPersonCamera>("Fyrox Third Person Camera");
+    container.add::<FirstPersonController>("Fyrox First Person Controller");
+    container.add::<Follow>("Fyrox Follow");
+    container.add::<LookAt>("Fyrox Look At");
+    container.add::<TriggerVolume>("Fyrox Trigger Volume");
+    container.add::<ObjectSpawner>("Fyrox Object Spawner");
+    container.add::<Rotator>("Fyrox Rotator");
+    container.add::<Oscillator>("Fyrox Oscillator");
+    container.add::<Health>("Fyrox Health");
 }
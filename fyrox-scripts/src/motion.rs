@@ -0,0 +1,146 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Simple procedural motion scripts: [`Rotator`] spins a node with constant angular velocity and
+//! [`Oscillator`] moves a node back and forth along an axis. Both are useful for pickups, platforms,
+//! fans, doors and similar "decorative" objects, that do not need physics.
+
+use fyrox::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        impl_component_provider,
+        reflect::prelude::*,
+        uuid_provider,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    script::{ScriptContext, ScriptTrait},
+};
+
+/// Rotator script spins a node with constant angular velocity. The rotation is applied to the
+/// local rotation of the node, so it could be used on child nodes of moving objects.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct Rotator {
+    #[reflect(description = "Angular velocity (in degrees per second) around each local axis.")]
+    pub angular_velocity: InheritableVariable<Vector3<f32>>,
+
+    #[reflect(description = "Defines whether the rotator is active or not.")]
+    pub enabled: InheritableVariable<bool>,
+}
+
+impl Default for Rotator {
+    fn default() -> Self {
+        Self {
+            angular_velocity: Vector3::new(0.0, 90.0, 0.0).into(),
+            enabled: true.into(),
+        }
+    }
+}
+
+impl_component_provider!(Rotator);
+uuid_provider!(Rotator = "0d1bb7b4-1df3-4c5c-9a4e-53f1d8b8b6a2");
+
+impl ScriptTrait for Rotator {
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        if !*self.enabled {
+            return;
+        }
+
+        let delta = self.angular_velocity.map(|a| a.to_radians() * context.dt);
+        let rotation = UnitQuaternion::from_euler_angles(delta.x, delta.y, delta.z);
+        let transform = context.scene.graph[context.handle].local_transform_mut();
+        let new_rotation = **transform.rotation() * rotation;
+        transform.set_rotation(new_rotation);
+    }
+}
+
+/// Oscillator script moves a node back and forth along the given axis using sine wave. The motion
+/// is relative to the local position of the node at the moment when the script was started.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct Oscillator {
+    #[reflect(description = "Local axis along which the node will move. Could be non-normalized.")]
+    pub axis: InheritableVariable<Vector3<f32>>,
+
+    #[reflect(
+        description = "Maximum distance from the initial position.",
+        min_value = 0.0
+    )]
+    pub amplitude: InheritableVariable<f32>,
+
+    #[reflect(description = "Amount of full cycles per second.", min_value = 0.0)]
+    pub frequency: InheritableVariable<f32>,
+
+    #[reflect(description = "Initial phase (in degrees) of the oscillation.")]
+    pub phase: InheritableVariable<f32>,
+
+    #[reflect(hidden)]
+    pub origin: Option<Vector3<f32>>,
+
+    #[reflect(hidden)]
+    pub time: f32,
+}
+
+impl Default for Oscillator {
+    fn default() -> Self {
+        Self {
+            axis: Vector3::y().into(),
+            amplitude: 0.5.into(),
+            frequency: 0.5.into(),
+            phase: 0.0.into(),
+            origin: Default::default(),
+            time: 0.0,
+        }
+    }
+}
+
+impl_component_provider!(Oscillator);
+uuid_provider!(Oscillator = "5f0a4a53-3bd5-4b44-8b57-e3bc6c3a5c8f");
+
+impl Oscillator {
+    /// Calculates the offset from the origin at the given time.
+    pub fn offset_at(&self, time: f32) -> Vector3<f32> {
+        let angle = std::f32::consts::TAU * *self.frequency * time + self.phase.to_radians();
+        self.axis
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_default()
+            .scale(*self.amplitude * angle.sin())
+    }
+}
+
+impl ScriptTrait for Oscillator {
+    fn on_start(&mut self, context: &mut ScriptContext) {
+        // The origin is serialized, so it must not be overwritten when a saved game is loaded.
+        if self.origin.is_none() {
+            self.origin = Some(
+                **context.scene.graph[context.handle]
+                    .local_transform()
+                    .position(),
+            );
+        }
+    }
+
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        self.time += context.dt;
+        let position = self.origin.unwrap_or_default() + self.offset_at(self.time);
+        context.scene.graph[context.handle]
+            .local_transform_mut()
+            .set_position(position);
+    }
+}
@@ -0,0 +1,314 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Object spawner, that instantiates prefabs and reuses their instances. See [`ObjectSpawner`] docs
+//! for more info.
+
+use crate::utils::{set_global_position, set_global_rotation};
+use fyrox::{
+    core::{
+        impl_component_provider, pool::Handle, reflect::prelude::*, uuid_provider,
+        variable::InheritableVariable, visitor::prelude::*,
+    },
+    graph::BaseSceneGraph,
+    resource::model::{ModelResource, ModelResourceExtension},
+    scene::{node::Node, Scene},
+    script::{
+        ScriptContext, ScriptMessageContext, ScriptMessagePayload, ScriptMessageSender, ScriptTrait,
+    },
+};
+
+/// A message, that could be sent to a node with [`ObjectSpawner`] script to control it. The message
+/// must be sent to the spawner node using [`fyrox::script::ScriptMessageSender::send_to_target`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpawnerMessage {
+    /// Spawns a new instance (if the limit of instances is not reached).
+    Spawn,
+    /// Returns the given instance back to the pool. The instance will be disabled and reused later.
+    Despawn(Handle<Node>),
+}
+
+/// A message, that is sent to the root node of an instance every time it is spawned (including
+/// reuse of pooled instances). Scripts of the instance could subscribe to the message to reset
+/// their state, because `on_start` is called only once per instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpawnedMessage {
+    /// A handle of the spawner node.
+    pub spawner: Handle<Node>,
+    /// A handle of the root node of the spawned instance.
+    pub instance: Handle<Node>,
+}
+
+/// Object spawner creates instances of a prefab at the position of its node, either periodically or
+/// on request (see [`SpawnerMessage`]). The spawner keeps every instance it has created in a pool:
+/// despawned instances are disabled instead of being destroyed and then reused, which avoids costly
+/// instantiation during the gameplay. The total amount of instances is limited by the pool size.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct ObjectSpawner {
+    #[reflect(description = "A prefab to instantiate.")]
+    pub prefab: InheritableVariable<Option<ModelResource>>,
+
+    #[reflect(
+        description = "Time (in seconds) between automatic spawns. Zero disables automatic spawning.",
+        min_value = 0.0
+    )]
+    pub interval: InheritableVariable<f32>,
+
+    #[reflect(description = "Amount of instances to spawn when the spawner starts.")]
+    pub spawn_on_start: InheritableVariable<u32>,
+
+    #[reflect(description = "Maximum amount of instances (both active and pooled).")]
+    pub pool_size: InheritableVariable<u32>,
+
+    #[reflect(hidden)]
+    pub timer: f32,
+
+    #[reflect(hidden)]
+    pub instances: Vec<Handle<Node>>,
+}
+
+impl Default for ObjectSpawner {
+    fn default() -> Self {
+        Self {
+            prefab: Default::default(),
+            interval: 5.0.into(),
+            spawn_on_start: 0.into(),
+            pool_size: 16.into(),
+            timer: 0.0,
+            instances: Default::default(),
+        }
+    }
+}
+
+impl_component_provider!(ObjectSpawner);
+uuid_provider!(ObjectSpawner = "4b8e3d2a-9f61-4c0e-8a73-1d5b6c9e2f48");
+
+impl ObjectSpawner {
+    /// Returns an iterator over every active (enabled) instance.
+    pub fn active_instances<'a>(
+        &'a self,
+        graph: &'a impl BaseSceneGraph<Node = Node>,
+    ) -> impl Iterator<Item = Handle<Node>> + 'a {
+        self.instances
+            .iter()
+            .filter(|h| graph.try_get(**h).is_some_and(|n| n.is_enabled()))
+            .cloned()
+    }
+
+    /// Spawns a new instance at the position of the spawner node, or reuses a pooled one. Returns
+    /// [`Handle::NONE`] if the pool is exhausted or the prefab is not set or not loaded.
+    pub fn spawn(
+        &mut self,
+        scene: &mut Scene,
+        spawner: Handle<Node>,
+        message_sender: &ScriptMessageSender,
+    ) -> Handle<Node> {
+        let graph = &mut scene.graph;
+        self.instances.retain(|h| graph.is_valid_handle(*h));
+
+        let position = graph[spawner].global_position();
+        let rotation = graph.global_rotation(spawner);
+
+        let instance = if let Some(pooled) = self
+            .instances
+            .iter()
+            .find(|h| !graph[**h].is_enabled())
+            .cloned()
+        {
+            graph[pooled].set_enabled(true);
+            set_global_position(graph, pooled, position);
+            set_global_rotation(graph, pooled, rotation);
+            pooled
+        } else if self.instances.len() < *self.pool_size as usize {
+            match self.prefab.as_ref() {
+                Some(prefab) if prefab.is_ok() => {
+                    let instance = prefab.instantiate_at(scene, position, rotation);
+                    self.instances.push(instance);
+                    instance
+                }
+                _ => return Handle::NONE,
+            }
+        } else {
+            return Handle::NONE;
+        };
+
+        message_sender.send_to_target(instance, SpawnedMessage { spawner, instance });
+
+        instance
+    }
+
+    /// Returns the given instance back to the pool. Returns `false` if the instance does not belong
+    /// to the spawner.
+    pub fn despawn(
+        &mut self,
+        graph: &mut impl BaseSceneGraph<Node = Node>,
+        instance: Handle<Node>,
+    ) -> bool {
+        if self.instances.contains(&instance) {
+            if let Some(node) = graph.try_get_mut(instance) {
+                node.set_enabled(false);
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl ScriptTrait for ObjectSpawner {
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        ctx.message_dispatcher
+            .subscribe_to::<SpawnerMessage>(ctx.handle);
+
+        // Instances are serialized, so there's no need to spawn them again when a saved game is
+        // loaded.
+        if self.instances.is_empty() {
+            for _ in 0..*self.spawn_on_start {
+                self.spawn(ctx.scene, ctx.handle, ctx.message_sender);
+            }
+        }
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        if *self.interval <= 0.0 {
+            return;
+        }
+
+        self.timer += ctx.dt;
+        if self.timer >= *self.interval {
+            self.timer = 0.0;
+            self.spawn(ctx.scene, ctx.handle, ctx.message_sender);
+        }
+    }
+
+    fn on_message(
+        &mut self,
+        message: &mut dyn ScriptMessagePayload,
+        ctx: &mut ScriptMessageContext,
+    ) {
+        match message.downcast_ref::<SpawnerMessage>() {
+            Some(SpawnerMessage::Spawn) => {
+                self.spawn(ctx.scene, ctx.handle, ctx.message_sender);
+            }
+            Some(SpawnerMessage::Despawn(instance)) => {
+                self.despawn(&mut ctx.scene.graph, *instance);
+            }
+            None => (),
+        }
+    }
+}
+
+// Event loop hack in the test scene does not work on macOS.
+#[cfg(all(test, not(target_os = "macos")))]
+mod test {
+    use crate::{
+        spawner::{ObjectSpawner, SpawnerMessage},
+        utils::test::ScriptTestScene,
+    };
+    use fyrox::{
+        asset::untyped::ResourceKind,
+        core::{algebra::Vector3, pool::Handle},
+        graph::NodeMapping,
+        resource::model::{Model, ModelResource},
+        scene::{
+            base::BaseBuilder, node::Node, pivot::PivotBuilder, transform::TransformBuilder, Scene,
+        },
+    };
+
+    fn make_prefab() -> ModelResource {
+        let mut scene = Scene::new();
+        PivotBuilder::new(BaseBuilder::new().with_name("Instance")).build(&mut scene.graph);
+        ModelResource::new_ok(
+            ResourceKind::Embedded,
+            Model::new(NodeMapping::UseNames, scene),
+        )
+    }
+
+    fn spawner(test_scene: &ScriptTestScene, handle: Handle<Node>) -> &ObjectSpawner {
+        test_scene.graph()[handle]
+            .try_get_script::<ObjectSpawner>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_spawner_pool() {
+        let mut scene = Scene::new();
+        let position = Vector3::new(1.0, 2.0, 3.0);
+        let spawner_handle = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(position)
+                        .build(),
+                )
+                .with_script(ObjectSpawner {
+                    prefab: Some(make_prefab()).into(),
+                    interval: 0.0.into(),
+                    spawn_on_start: 2.into(),
+                    pool_size: 3.into(),
+                    ..Default::default()
+                }),
+        )
+        .build(&mut scene.graph);
+
+        let mut test_scene = ScriptTestScene::new(scene);
+        test_scene.update(0.0);
+
+        let instances = spawner(&test_scene, spawner_handle).instances.clone();
+        assert_eq!(instances.len(), 2);
+        for instance in instances.iter() {
+            assert!(test_scene.graph()[*instance].is_enabled());
+            assert_eq!(test_scene.graph()[*instance].global_position(), position);
+        }
+
+        // Despawned instance is disabled, but kept in the pool.
+        test_scene
+            .message_sender()
+            .send_to_target(spawner_handle, SpawnerMessage::Despawn(instances[0]));
+        test_scene.update(0.0);
+        assert!(!test_scene.graph()[instances[0]].is_enabled());
+        assert_eq!(
+            spawner(&test_scene, spawner_handle)
+                .active_instances(test_scene.graph())
+                .collect::<Vec<_>>(),
+            [instances[1]]
+        );
+
+        // The first spawn reuses the pooled instance, the second one creates a new instance.
+        for _ in 0..2 {
+            test_scene
+                .message_sender()
+                .send_to_target(spawner_handle, SpawnerMessage::Spawn);
+        }
+        test_scene.update(0.0);
+        let spawner_ref = spawner(&test_scene, spawner_handle);
+        assert_eq!(spawner_ref.instances.len(), 3);
+        assert_eq!(spawner_ref.instances[..2], instances);
+        assert!(test_scene.graph()[instances[0]].is_enabled());
+
+        // The pool is exhausted.
+        test_scene
+            .message_sender()
+            .send_to_target(spawner_handle, SpawnerMessage::Spawn);
+        test_scene.update(0.0);
+        let spawner_ref = spawner(&test_scene, spawner_handle);
+        assert_eq!(spawner_ref.instances.len(), 3);
+        assert_eq!(spawner_ref.active_instances(test_scene.graph()).count(), 3);
+    }
+}
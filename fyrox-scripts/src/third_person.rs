@@ -0,0 +1,219 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Third-person (orbit) camera, that rotates around a target and avoids obstacles. See
+//! [`ThirdPersonCamera`] docs for more info.
+
+use crate::utils::{
+    is_in_hierarchy_of, set_global_position, set_global_rotation, smoothing_factor,
+};
+use fyrox::{
+    core::{
+        algebra::{Point3, UnitQuaternion, Vector3},
+        impl_component_provider,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid_provider,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    event::{DeviceEvent, Event, MouseScrollDelta, WindowEvent},
+    graph::BaseSceneGraph,
+    scene::{
+        graph::physics::{Intersection, RayCastOptions},
+        node::Node,
+    },
+    script::{ScriptContext, ScriptTrait},
+};
+use std::ops::Range;
+
+/// Third-person camera script rotates its node around a target using mouse and zooms it using mouse
+/// wheel. The camera casts a ray from the target to its desired position and moves closer to the
+/// target if there's an obstacle in between, so the target is always visible. Colliders of the
+/// target (and its descendants) are ignored. Assign the script to a camera node, that is not a
+/// descendant of the target.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct ThirdPersonCamera {
+    #[reflect(description = "A node around which the camera will orbit.")]
+    pub target: InheritableVariable<Handle<Node>>,
+
+    #[reflect(
+        description = "An offset (in world space) from the position of the target to the \
+        orbit center."
+    )]
+    pub target_offset: InheritableVariable<Vector3<f32>>,
+
+    #[reflect(description = "Current yaw of the camera (in radians).")]
+    pub yaw: InheritableVariable<f32>,
+
+    #[reflect(description = "Current pitch of the camera (in radians).")]
+    pub pitch: InheritableVariable<f32>,
+
+    #[reflect(description = "Angular limit of the pitch of the camera (in radians).")]
+    pub pitch_limit: InheritableVariable<Range<f32>>,
+
+    #[reflect(description = "Mouse sensitivity.")]
+    pub sensitivity: InheritableVariable<f32>,
+
+    #[reflect(
+        description = "Desired distance from the orbit center.",
+        min_value = 0.0
+    )]
+    pub distance: InheritableVariable<f32>,
+
+    #[reflect(description = "Distance limits of the camera.")]
+    pub distance_limit: InheritableVariable<Range<f32>>,
+
+    #[reflect(
+        description = "Distance change per one step of mouse wheel.",
+        min_value = 0.0
+    )]
+    pub zoom_step: InheritableVariable<f32>,
+
+    #[reflect(
+        description = "Defines whether the camera should avoid obstacles between it and the target."
+    )]
+    pub collision: InheritableVariable<bool>,
+
+    #[reflect(
+        description = "Minimal distance between the camera and an obstacle.",
+        min_value = 0.0
+    )]
+    pub collision_margin: InheritableVariable<f32>,
+
+    #[reflect(
+        description = "Amount of time (in seconds) it takes the camera to mostly return to the \
+        desired distance after an obstacle is gone. Zero means instant return.",
+        min_value = 0.0
+    )]
+    pub return_smoothing_time: InheritableVariable<f32>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub current_distance: Option<f32>,
+}
+
+impl Default for ThirdPersonCamera {
+    fn default() -> Self {
+        Self {
+            target: Default::default(),
+            target_offset: Vector3::new(0.0, 1.5, 0.0).into(),
+            yaw: Default::default(),
+            pitch: 20.0f32.to_radians().into(),
+            pitch_limit: ((-80.0f32).to_radians()..80.0f32.to_radians()).into(),
+            sensitivity: 0.3.into(),
+            distance: 4.0.into(),
+            distance_limit: (1.0..10.0).into(),
+            zoom_step: 0.5.into(),
+            collision: true.into(),
+            collision_margin: 0.2.into(),
+            return_smoothing_time: 0.2.into(),
+            current_distance: None,
+        }
+    }
+}
+
+impl_component_provider!(ThirdPersonCamera);
+uuid_provider!(ThirdPersonCamera = "a9a0c3f8-1f73-4e4b-9b44-4e8d6d9b3a56");
+
+impl ThirdPersonCamera {
+    /// Returns the rotation of the camera defined by its yaw and pitch.
+    pub fn rotation(&self) -> UnitQuaternion<f32> {
+        UnitQuaternion::from_axis_angle(&Vector3::y_axis(), *self.yaw)
+            * UnitQuaternion::from_axis_angle(&Vector3::x_axis(), *self.pitch)
+    }
+}
+
+impl ScriptTrait for ThirdPersonCamera {
+    fn on_os_event(&mut self, event: &Event<()>, context: &mut ScriptContext) {
+        match event {
+            Event::DeviceEvent {
+                event: DeviceEvent::MouseMotion { delta, .. },
+                ..
+            } => {
+                let speed = *self.sensitivity * context.dt;
+                *self.yaw -= (delta.0 as f32) * speed;
+                *self.pitch = (*self.pitch + delta.1 as f32 * speed)
+                    .max(self.pitch_limit.start)
+                    .min(self.pitch_limit.end);
+            }
+            Event::WindowEvent {
+                event: WindowEvent::MouseWheel { delta, .. },
+                ..
+            } => {
+                let steps = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    MouseScrollDelta::PixelDelta(position) => position.y.signum() as f32,
+                };
+                *self.distance = (*self.distance - steps * *self.zoom_step)
+                    .max(self.distance_limit.start)
+                    .min(self.distance_limit.end);
+            }
+            _ => {}
+        }
+    }
+
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        let graph = &mut context.scene.graph;
+        let Some(target) = graph.try_get(*self.target) else {
+            return;
+        };
+
+        let center = target.global_position() + *self.target_offset;
+        let rotation = self.rotation();
+        // The camera looks along its local Z axis, so it must be placed "behind" the center.
+        let back = rotation * -Vector3::z();
+
+        let mut distance = *self.distance;
+        if *self.collision {
+            let mut intersections = Vec::<Intersection>::new();
+            graph.physics.cast_ray(
+                RayCastOptions {
+                    ray_origin: Point3::from(center),
+                    ray_direction: back,
+                    max_len: distance + *self.collision_margin,
+                    groups: Default::default(),
+                    sort_results: true,
+                },
+                &mut intersections,
+            );
+            if let Some(obstacle) = intersections
+                .iter()
+                .find(|i| !is_in_hierarchy_of(graph, i.collider, *self.target))
+            {
+                distance = (obstacle.toi - *self.collision_margin).max(0.0);
+            }
+        }
+
+        // Move towards the obstacle instantly, but return back smoothly to prevent jerking.
+        let current_distance = match self.current_distance {
+            Some(current) if current < distance => {
+                current
+                    + (distance - current)
+                        * smoothing_factor(*self.return_smoothing_time, context.dt)
+            }
+            _ => distance,
+        };
+        self.current_distance = Some(current_distance);
+
+        set_global_position(graph, context.handle, center + back.scale(current_distance));
+        set_global_rotation(graph, context.handle, rotation);
+    }
+}
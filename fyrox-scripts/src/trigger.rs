@@ -0,0 +1,328 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Trigger volume script notifies other scripts when objects enter or leave a sensor collider. See
+//! [`TriggerVolume`] docs for more info and usage examples.
+
+use fyrox::{
+    core::{
+        impl_component_provider, pool::Handle, reflect::prelude::*, uuid_provider,
+        variable::InheritableVariable, visitor::prelude::*,
+    },
+    graph::BaseSceneGraph,
+    scene::{
+        graph::{
+            physics::{CollisionEvent, CollisionEventKind},
+            Graph,
+        },
+        node::Node,
+    },
+    script::{ScriptContext, ScriptTrait},
+};
+
+/// A kind of [`TriggerMessage`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerMessageKind {
+    /// An object has entered the trigger volume.
+    Entered,
+    /// An object has left the trigger volume.
+    Exited,
+}
+
+/// A message, that is sent by [`TriggerVolume`] script when an object enters or leaves it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TriggerMessage {
+    /// Kind of the message.
+    pub kind: TriggerMessageKind,
+    /// A handle of the node with [`TriggerVolume`] script.
+    pub trigger: Handle<Node>,
+    /// A handle of the collider, that has entered or left the trigger volume.
+    pub other: Handle<Node>,
+}
+
+/// Trigger volume script sends [`TriggerMessage`]s when an object enters or leaves a sensor collider.
+/// The script must be assigned to a sensor collider or to a rigid body with sensor colliders (both
+/// 2D and 3D are supported). The messages are sent globally, or to a particular node if `target`
+/// is set. Any script could then subscribe to the messages:
+///
+/// ```rust
+/// # use fyrox::{
+/// #     core::{reflect::prelude::*, type_traits::prelude::*, visitor::prelude::*},
+/// #     script::{ScriptContext, ScriptMessageContext, ScriptMessagePayload, ScriptTrait},
+/// # };
+/// # use fyrox_scripts::trigger::{TriggerMessage, TriggerMessageKind};
+/// #[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+/// #[type_uuid(id = "2a6f1a3e-3b1c-47a5-8f3c-62a9c3bde8f4")]
+/// struct Door {
+///     open: bool,
+/// }
+///
+/// impl ScriptTrait for Door {
+///     fn on_start(&mut self, ctx: &mut ScriptContext) {
+///         ctx.message_dispatcher.subscribe_to::<TriggerMessage>(ctx.handle);
+///     }
+///
+///     fn on_message(
+///         &mut self,
+///         message: &mut dyn ScriptMessagePayload,
+///         _ctx: &mut ScriptMessageContext,
+///     ) {
+///         if let Some(message) = message.downcast_ref::<TriggerMessage>() {
+///             self.open = message.kind == TriggerMessageKind::Entered;
+///         }
+///     }
+/// }
+/// ```
+#[derive(Visit, Reflect, Debug, Clone, Default)]
+pub struct TriggerVolume {
+    #[reflect(
+        description = "A node, that will receive trigger messages. If not set, the messages \
+        will be sent globally."
+    )]
+    pub target: InheritableVariable<Handle<Node>>,
+
+    #[reflect(
+        description = "If not empty, only objects with the given tag will activate the trigger. \
+        The tag is checked on the collider and its rigid body."
+    )]
+    pub tag_filter: InheritableVariable<String>,
+
+    #[reflect(description = "If set, the trigger will send only the first enter message.")]
+    pub one_shot: InheritableVariable<bool>,
+
+    #[reflect(hidden)]
+    pub triggered: bool,
+
+    #[reflect(hidden)]
+    pub occupants: Vec<Handle<Node>>,
+}
+
+impl_component_provider!(TriggerVolume);
+uuid_provider!(TriggerVolume = "e3a1c2f4-6d0b-4c55-a8a6-0f7f2e4b9d13");
+
+impl TriggerVolume {
+    /// Returns `true` if there's at least one object inside the trigger volume.
+    pub fn is_occupied(&self) -> bool {
+        !self.occupants.is_empty()
+    }
+
+    fn passes_filter(&self, graph: &Graph, collider: Handle<Node>) -> bool {
+        if self.tag_filter.is_empty() {
+            return true;
+        }
+
+        let Some(collider_ref) = graph.try_get(collider) else {
+            return false;
+        };
+
+        collider_ref.tag() == self.tag_filter.as_str()
+            || graph
+                .try_get(collider_ref.parent())
+                .is_some_and(|parent| parent.tag() == self.tag_filter.as_str())
+    }
+}
+
+impl ScriptTrait for TriggerVolume {
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        // Objects could be destroyed while being inside the volume.
+        self.occupants
+            .retain(|h| ctx.scene.graph.is_valid_handle(*h));
+    }
+
+    fn on_collision(&mut self, event: &CollisionEvent, ctx: &mut ScriptContext) {
+        let kind = match event.kind {
+            CollisionEventKind::TriggerEnter => TriggerMessageKind::Entered,
+            CollisionEventKind::TriggerExit => TriggerMessageKind::Exited,
+            _ => return,
+        };
+
+        if !self.passes_filter(&ctx.scene.graph, event.other) {
+            return;
+        }
+
+        match kind {
+            TriggerMessageKind::Entered => {
+                if *self.one_shot && self.triggered {
+                    return;
+                }
+                self.triggered = true;
+                self.occupants.push(event.other);
+            }
+            TriggerMessageKind::Exited => {
+                let Some(position) = self.occupants.iter().position(|h| *h == event.other) else {
+                    return;
+                };
+                self.occupants.remove(position);
+            }
+        }
+
+        let message = TriggerMessage {
+            kind,
+            trigger: ctx.handle,
+            other: event.other,
+        };
+
+        if self.target.is_some() {
+            ctx.message_sender.send_to_target(*self.target, message);
+        } else {
+            ctx.message_sender.send_global(message);
+        }
+    }
+}
+
+// Event loop hack in the test scene does not work on macOS.
+#[cfg(all(test, not(target_os = "macos")))]
+mod test {
+    use crate::{
+        trigger::{TriggerMessage, TriggerMessageKind, TriggerVolume},
+        utils::test::ScriptTestScene,
+    };
+    use fyrox::{
+        core::{
+            algebra::Vector3, pool::Handle, reflect::prelude::*, type_traits::prelude::*,
+            visitor::prelude::*,
+        },
+        scene::{
+            base::BaseBuilder,
+            collider::{ColliderBuilder, ColliderShape},
+            node::Node,
+            pivot::PivotBuilder,
+            rigidbody::{RigidBodyBuilder, RigidBodyType},
+            transform::TransformBuilder,
+            Scene,
+        },
+        script::{ScriptContext, ScriptMessageContext, ScriptMessagePayload, ScriptTrait},
+    };
+    use std::sync::mpsc::{self, Sender};
+
+    #[derive(Debug, Clone, Reflect, Visit, TypeUuidProvider, ComponentProvider)]
+    #[type_uuid(id = "0c5b2e8f-6a1d-4f3b-9e27-8d4c1a7f5b36")]
+    struct TriggerListener {
+        #[reflect(hidden)]
+        #[visit(skip)]
+        sender: Sender<TriggerMessage>,
+    }
+
+    impl ScriptTrait for TriggerListener {
+        fn on_start(&mut self, ctx: &mut ScriptContext) {
+            ctx.message_dispatcher
+                .subscribe_to::<TriggerMessage>(ctx.handle);
+        }
+
+        fn on_message(
+            &mut self,
+            message: &mut dyn ScriptMessagePayload,
+            _ctx: &mut ScriptMessageContext,
+        ) {
+            if let Some(message) = message.downcast_ref::<TriggerMessage>() {
+                self.sender.send(message.clone()).unwrap();
+            }
+        }
+    }
+
+    // Drops a ball through a trigger volume and returns the messages of the trigger.
+    fn drop_ball(trigger: TriggerVolume, ball_tag: &str) -> (Vec<TriggerMessage>, Handle<Node>) {
+        let mut scene = Scene::new();
+        let (tx, rx) = mpsc::channel();
+
+        PivotBuilder::new(BaseBuilder::new().with_script(TriggerListener { sender: tx }))
+            .build(&mut scene.graph);
+
+        let sensor = ColliderBuilder::new(BaseBuilder::new().with_script(trigger))
+            .with_shape(ColliderShape::cuboid(1.0, 1.0, 1.0))
+            .with_sensor(true)
+            .build(&mut scene.graph);
+        RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 3.0, 0.0))
+                        .build(),
+                )
+                .with_children(&[sensor]),
+        )
+        .with_body_type(RigidBodyType::Static)
+        .build(&mut scene.graph);
+
+        let ball = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::ball(0.25))
+            .build(&mut scene.graph);
+        RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_tag(ball_tag.to_string())
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 6.0, 0.0))
+                        .build(),
+                )
+                .with_children(&[ball]),
+        )
+        .with_body_type(RigidBodyType::Dynamic)
+        .build(&mut scene.graph);
+
+        let mut test_scene = ScriptTestScene::new(scene);
+        for _ in 0..120 {
+            test_scene.update(1.0 / 60.0);
+        }
+
+        let trigger = test_scene.graph()[sensor]
+            .try_get_script::<TriggerVolume>()
+            .unwrap();
+        assert!(!trigger.is_occupied());
+
+        let messages = rx.try_iter().collect::<Vec<_>>();
+        for message in messages.iter() {
+            assert_eq!(message.trigger, sensor);
+            assert_eq!(message.other, ball);
+        }
+        (messages, ball)
+    }
+
+    fn kinds(messages: &[TriggerMessage]) -> Vec<TriggerMessageKind> {
+        messages.iter().map(|message| message.kind).collect()
+    }
+
+    #[test]
+    fn test_trigger_messages() {
+        let (messages, _) = drop_ball(TriggerVolume::default(), "");
+        assert_eq!(
+            kinds(&messages),
+            [TriggerMessageKind::Entered, TriggerMessageKind::Exited]
+        );
+    }
+
+    #[test]
+    fn test_trigger_tag_filter() {
+        let trigger = TriggerVolume {
+            tag_filter: "Player".to_string().into(),
+            ..Default::default()
+        };
+
+        let (messages, _) = drop_ball(trigger.clone(), "Enemy");
+        assert!(messages.is_empty());
+
+        // The tag is checked on the rigid body of the collider too.
+        let (messages, _) = drop_ball(trigger, "Player");
+        assert_eq!(
+            kinds(&messages),
+            [TriggerMessageKind::Entered, TriggerMessageKind::Exited]
+        );
+    }
+}
@@ -0,0 +1,164 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Helpers, that are shared across multiple scripts.
+
+use fyrox::{
+    core::{
+        algebra::{Point3, UnitQuaternion, Vector3},
+        pool::Handle,
+    },
+    graph::BaseSceneGraph,
+    scene::{graph::Graph, node::Node},
+};
+
+/// Calculates interpolation factor for exponential smoothing, that does not depend on frame rate.
+/// Zero smoothing time means that there's no smoothing at all.
+pub(crate) fn smoothing_factor(smoothing_time: f32, dt: f32) -> f32 {
+    if smoothing_time <= 0.0 {
+        1.0
+    } else {
+        1.0 - (-dt / smoothing_time).exp()
+    }
+}
+
+/// Sets local position of the node, so its global position will be equal to the given one.
+pub(crate) fn set_global_position(graph: &mut Graph, node: Handle<Node>, position: Vector3<f32>) {
+    let parent = graph[node].parent();
+    let local_position = match graph.try_get(parent) {
+        Some(parent) => match parent.global_transform().try_inverse() {
+            Some(inv_parent_transform) => {
+                inv_parent_transform
+                    .transform_point(&Point3::from(position))
+                    .coords
+            }
+            None => return,
+        },
+        None => position,
+    };
+    graph[node]
+        .local_transform_mut()
+        .set_position(local_position);
+}
+
+/// Sets local rotation of the node, so its global rotation will be equal to the given one.
+pub(crate) fn set_global_rotation(
+    graph: &mut Graph,
+    node: Handle<Node>,
+    rotation: UnitQuaternion<f32>,
+) {
+    let parent = graph[node].parent();
+    let local_rotation = if graph.is_valid_handle(parent) {
+        graph.global_rotation(parent).inverse() * rotation
+    } else {
+        rotation
+    };
+    graph[node]
+        .local_transform_mut()
+        .set_rotation(local_rotation);
+}
+
+/// Returns `true` if the node is the given ancestor or one of its descendants.
+pub(crate) fn is_in_hierarchy_of(
+    graph: &Graph,
+    node: Handle<Node>,
+    ancestor: Handle<Node>,
+) -> bool {
+    let mut current = node;
+    while let Some(node) = graph.try_get(current) {
+        if current == ancestor {
+            return true;
+        }
+        current = node.parent();
+    }
+    false
+}
+
+#[cfg(all(test, not(target_os = "macos")))]
+pub(crate) mod test {
+    use fyrox::{
+        asset::manager::ResourceManager,
+        core::{pool::Handle, task::TaskPool},
+        engine::{Engine, EngineInitParams},
+        event_loop::EventLoop,
+        scene::{graph::Graph, Scene},
+        script::ScriptMessageSender,
+    };
+    use std::{
+        mem::{ManuallyDrop, MaybeUninit},
+        sync::Arc,
+    };
+
+    /// An engine without a window, that runs scripts of a single scene.
+    pub struct ScriptTestScene {
+        engine: Engine,
+        scene: Handle<Scene>,
+        event_loop: ManuallyDrop<EventLoop<()>>,
+        lag: f32,
+    }
+
+    impl ScriptTestScene {
+        pub fn new(scene: Scene) -> Self {
+            // Tests run in random threads and EventLoop cannot be created from non-main thread.
+            // Since we don't create any windows and don't run the event loop, this should be safe.
+            #[allow(invalid_value)]
+            #[allow(clippy::uninit_assumed_init)]
+            let event_loop =
+                unsafe { ManuallyDrop::new(MaybeUninit::<EventLoop<()>>::uninit().assume_init()) };
+
+            let task_pool = Arc::new(TaskPool::default());
+            let mut engine = Engine::new(EngineInitParams {
+                graphics_context_params: Default::default(),
+                serialization_context: Arc::new(Default::default()),
+                widget_constructors: Arc::new(Default::default()),
+                resource_manager: ResourceManager::new(task_pool.clone()),
+                task_pool,
+            })
+            .unwrap();
+
+            let scene = engine.scenes.add(scene);
+            engine.register_scripted_scene(scene);
+
+            Self {
+                engine,
+                scene,
+                event_loop,
+                lag: 0.0,
+            }
+        }
+
+        /// Steps physics of the scene and runs its scripts.
+        pub fn update(&mut self, dt: f32) {
+            self.engine
+                .update(dt, &self.event_loop, &mut self.lag, Default::default());
+        }
+
+        pub fn graph(&self) -> &Graph {
+            &self.engine.scenes[self.scene].graph
+        }
+
+        pub fn message_sender(&self) -> &ScriptMessageSender {
+            self.engine
+                .script_processor
+                .message_sender(self.scene)
+                .unwrap()
+        }
+    }
+}
@@ -124,7 +124,7 @@ impl GenericBuffer {
                     (false, Default::default())
                 };
 
-                let decoder = Decoder::new(source)?;
+                let mut decoder = Decoder::new(source)?;
                 if decoder.get_channel_count() < 1 || decoder.get_channel_count() > 2 {
                     if is_memory {
                         return Err(DataSource::Memory(external_cursor));
@@ -140,11 +140,14 @@ impl GenericBuffer {
                     }
                 }
 
+                // Some decoders know their exact length only when the whole stream was decoded, so
+                // the length must be fetched after decoding.
+                let samples = decoder.by_ref().collect();
                 Ok(Self {
                     sample_rate: decoder.get_sample_rate(),
                     channel_count: decoder.get_channel_count(),
                     channel_duration_in_samples: decoder.channel_duration_in_samples(),
-                    samples: Samples(decoder.into_samples()),
+                    samples: Samples(samples),
                 })
             }
         }
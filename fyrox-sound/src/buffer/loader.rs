@@ -50,7 +50,7 @@ pub struct SoundBufferLoader {
 
 impl ResourceLoader for SoundBufferLoader {
     fn extensions(&self) -> &[&str] {
-        &["wav", "ogg"]
+        &["wav", "ogg", "flac", "mp3"]
     }
 
     fn data_type_uuid(&self) -> Uuid {
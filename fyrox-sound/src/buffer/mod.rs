@@ -66,7 +66,7 @@ pub enum DataSource {
         data: Box<dyn FileReader>,
     },
 
-    /// Data source is a memory block. Memory block must be in valid format (wav or vorbis/ogg). This variant can
+    /// Data source is a memory block. Memory block must be in valid format (wav, vorbis/ogg, flac or mp3). This variant can
     /// be used together with virtual file system.
     Memory(Cursor<Vec<u8>>),
 
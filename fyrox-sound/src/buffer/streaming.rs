@@ -207,6 +207,9 @@ impl StreamingBuffer {
     pub(crate) fn read_next_block(&mut self) {
         self.streaming_source
             .read_next_samples_block_into(&mut self.generic.samples);
+        // Length of some streams is refined while they're being decoded.
+        self.generic.channel_duration_in_samples =
+            self.streaming_source.channel_duration_in_samples();
     }
 
     #[inline]
@@ -96,6 +96,38 @@ impl PingPongBuffer {
     }
 }
 
+/// Adds the input signal of the given bus and the input signals of all its descendants (scaled by
+/// their gains) to the given sidechain buffer.
+fn mix_sidechain(
+    buses: &Pool<AudioBus>,
+    bus: Handle<AudioBus>,
+    gain: f32,
+    sidechain: &mut [(f32, f32)],
+) {
+    let Some(bus) = buses.try_borrow(bus) else {
+        return;
+    };
+    for ((left, right), (input_left, input_right)) in
+        sidechain.iter_mut().zip(bus.ping_pong_buffer.input_ref())
+    {
+        *left += *input_left * gain;
+        *right += *input_right * gain;
+    }
+    for child in bus.child_buses.iter() {
+        if let Some(child_bus) = buses.try_borrow(*child) {
+            mix_sidechain(buses, *child, gain * child_bus.gain, sidechain);
+        }
+    }
+}
+
+// An effect with the sidechain input and the bus that feeds it.
+#[derive(Debug, Clone)]
+struct SidechainLink {
+    bus: Handle<AudioBus>,
+    effect_index: usize,
+    source: Handle<AudioBus>,
+}
+
 /// Audio bus is a top-level audio processing unit. It takes data from multiple audio sources and passes their
 /// samples through a chain of effects. Output signal is then can be either sent to an audio playback device or
 /// to some other audio bus and be processed again, but with different sound effects (this can be done via
@@ -311,6 +343,16 @@ impl AudioBus {
 pub struct AudioBusGraph {
     buses: Pool<AudioBus>,
     root: Handle<AudioBus>,
+
+    // Sidechain sources are resolved by their names only when the graph has changed, so the mixer
+    // does not search for the buses on each frame.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    sidechains: Vec<SidechainLink>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    sidechains_resolved: bool,
 }
 
 impl AudioBusGraph {
@@ -323,7 +365,12 @@ impl AudioBusGraph {
         let root = AudioBus::new(Self::PRIMARY_BUS.to_string());
         let mut buses = Pool::new();
         let root = buses.spawn(root);
-        Self { buses, root }
+        Self {
+            buses,
+            root,
+            sidechains: Default::default(),
+            sidechains_resolved: false,
+        }
     }
 
     /// Adds a new audio bus to the graph and attaches it to the given parent. `parent` handle must be
@@ -344,6 +391,7 @@ impl AudioBusGraph {
     /// ```
     pub fn add_bus(&mut self, mut bus: AudioBus, parent: Handle<AudioBus>) -> Handle<AudioBus> {
         bus.parent_bus = parent;
+        self.sidechains_resolved = false;
         let bus = self.buses.spawn(bus);
         self.buses[parent].child_buses.push(bus);
         bus
@@ -367,6 +415,7 @@ impl AudioBusGraph {
     #[inline]
     pub fn link_buses(&mut self, child: Handle<AudioBus>, parent: Handle<AudioBus>) {
         self.unlink_internal(child);
+        self.sidechains_resolved = false;
         self.buses[child].parent_bus = parent;
         self.buses[parent].child_buses.push(child);
     }
@@ -385,6 +434,7 @@ impl AudioBusGraph {
     pub fn remove_bus(&mut self, handle: Handle<AudioBus>) -> AudioBus {
         assert_ne!(handle, self.root);
 
+        self.sidechains_resolved = false;
         let bus = self.buses.free(handle);
         let parent_bus = &mut self.buses[bus.parent_bus];
 
@@ -411,6 +461,7 @@ impl AudioBusGraph {
 
     /// Returns a reference to the primary audio bus.
     pub fn primary_bus_mut(&mut self) -> &mut AudioBus {
+        self.sidechains_resolved = false;
         &mut self.buses[self.root]
     }
 
@@ -421,6 +472,7 @@ impl AudioBusGraph {
 
     /// Tries to borrow an audio bus by its handle.
     pub fn try_get_bus_mut(&mut self, handle: Handle<AudioBus>) -> Option<&mut AudioBus> {
+        self.sidechains_resolved = false;
         self.buses.try_borrow_mut(handle)
     }
 
@@ -440,18 +492,21 @@ impl AudioBusGraph {
         &mut self,
         handle: Handle<AudioBus>,
     ) -> Option<(Ticket<AudioBus>, AudioBus)> {
+        self.sidechains_resolved = false;
         self.buses.try_take_reserve(handle)
     }
 
     /// Puts the audio bus back to graph on its previous place by the given ticket. See [`Pool::put_back`] method docs
     /// for more info.
     pub fn put_bus_back(&mut self, ticket: Ticket<AudioBus>, bus: AudioBus) -> Handle<AudioBus> {
+        self.sidechains_resolved = false;
         self.buses.put_back(ticket, bus)
     }
 
     /// Forget an audio bus ticket making the respective handle free again. See [`Pool::forget_ticket`] method docs for
     /// more info.
     pub fn forget_bus_ticket(&mut self, ticket: Ticket<AudioBus>) {
+        self.sidechains_resolved = false;
         self.buses.forget_ticket(ticket)
     }
 
@@ -462,6 +517,7 @@ impl AudioBusGraph {
 
     /// Returns an iterator over each audio bus in the graph.
     pub fn buses_iter_mut(&mut self) -> impl Iterator<Item = &mut AudioBus> {
+        self.sidechains_resolved = false;
         self.buses.iter_mut()
     }
 
@@ -474,6 +530,7 @@ impl AudioBusGraph {
     pub fn buses_pair_iter_mut(
         &mut self,
     ) -> impl Iterator<Item = (Handle<AudioBus>, &mut AudioBus)> {
+        self.sidechains_resolved = false;
         self.buses.pair_iter_mut()
     }
 
@@ -483,7 +540,70 @@ impl AudioBusGraph {
         }
     }
 
+    /// Marks sidechain sources as outdated, so they will be resolved by their names again on the
+    /// next mixing pass. Any mutable access to the graph does this automatically.
+    pub fn invalidate_sidechains(&mut self) {
+        self.sidechains_resolved = false;
+    }
+
+    fn resolve_sidechains(&mut self) {
+        self.sidechains.clear();
+        for (handle, bus) in self.buses.pair_iter() {
+            for (effect_index, effect) in bus.effects.iter().enumerate() {
+                if let Some(name) = effect.sidechain_bus() {
+                    if !name.is_empty() {
+                        let source = self
+                            .buses
+                            .pair_iter()
+                            .find_map(|(h, b)| if b.name == name { Some(h) } else { None })
+                            .unwrap_or_default();
+                        self.sidechains.push(SidechainLink {
+                            bus: handle,
+                            effect_index,
+                            source,
+                        });
+                    }
+                }
+            }
+        }
+        self.sidechains_resolved = true;
+    }
+
+    fn fill_sidechains(&mut self) {
+        if !self.sidechains_resolved {
+            self.resolve_sidechains();
+        }
+
+        for link in self.sidechains.iter() {
+            let Some(buffer) = self
+                .buses
+                .try_borrow_mut(link.bus)
+                .and_then(|bus| bus.effects.get_mut(link.effect_index))
+                .and_then(|effect| effect.sidechain_buffer_mut())
+            else {
+                continue;
+            };
+            // Sidechain buffer is moved out temporarily, because its source could be the same bus.
+            // Its memory is reused to prevent allocations on each frame.
+            let mut sidechain = std::mem::take(buffer);
+            sidechain.clear();
+            if let Some(source) = self.buses.try_borrow(link.source) {
+                sidechain.resize(source.ping_pong_buffer.input_ref().len(), (0.0, 0.0));
+                mix_sidechain(&self.buses, link.source, 1.0, &mut sidechain);
+            }
+            if let Some(buffer) =
+                self.buses[link.bus].effects[link.effect_index].sidechain_buffer_mut()
+            {
+                *buffer = sidechain;
+            }
+        }
+    }
+
     pub(crate) fn end_render(&mut self, output_device_buffer: &mut [(f32, f32)]) {
+        // Sidechain signals must be captured before any effects are applied, but they include the
+        // signals of child buses of the sidechain source.
+        self.fill_sidechains();
+
         let mut leafs = Vec::new();
         for (handle, bus) in self.buses.pair_iter_mut() {
             bus.apply_effects();
@@ -524,7 +644,7 @@ impl AudioBusGraph {
 mod test {
     use crate::{
         bus::{AudioBus, AudioBusGraph},
-        effects::{Attenuate, Effect},
+        effects::{compressor::Compressor, Attenuate, Effect},
     };
 
     #[test]
@@ -606,4 +726,129 @@ mod test {
 
         assert_eq!(output_buffer[0], (0.75, 0.75));
     }
+
+    #[test]
+    fn test_sidechain_ducking() {
+        let mut output_buffer = [(0.0f32, 0.0f32); 1024];
+
+        let mut graph = AudioBusGraph::new();
+
+        let mut ducker = Compressor::new();
+        ducker.set_sidechain_bus("Dialogue");
+        ducker.set_attack_time(0.0);
+        let mut music = AudioBus::new("Music".to_string());
+        music.add_effect(Effect::Compressor(ducker));
+        let music = graph.add_bus(music, graph.root);
+
+        let dialogue = AudioBus::new("Dialogue".to_string());
+        let dialogue = graph.add_bus(dialogue, graph.root);
+
+        graph.begin_render(output_buffer.len());
+
+        for (left, right) in graph.buses[music].input_buffer() {
+            *left = 0.1;
+            *right = 0.1;
+        }
+
+        for (left, right) in graph.buses[dialogue].input_buffer() {
+            *left = 1.0;
+            *right = 1.0;
+        }
+
+        graph.end_render(&mut output_buffer);
+
+        // -12 dB threshold and 4:1 ratio gives 9 dB of gain reduction for the full scale sidechain signal.
+        let (left, right) = *graph.buses[music]
+            .ping_pong_buffer
+            .input_ref()
+            .last()
+            .unwrap();
+        assert!((left - 0.0355).abs() < 1.0e-3);
+        assert!((right - 0.0355).abs() < 1.0e-3);
+    }
+
+    #[test]
+    fn test_sidechain_includes_child_buses() {
+        let mut output_buffer = [(0.0f32, 0.0f32); 64];
+
+        let mut graph = AudioBusGraph::new();
+
+        let mut ducker = Compressor::new();
+        ducker.set_sidechain_bus("Dialogue");
+        let mut music = AudioBus::new("Music".to_string());
+        music.add_effect(Effect::Compressor(ducker));
+        let music = graph.add_bus(music, graph.root);
+        let dialogue = graph.add_bus(AudioBus::new("Dialogue".to_string()), graph.root);
+        let mut npc = AudioBus::new("Npc".to_string());
+        npc.set_gain(0.5);
+        let npc = graph.add_bus(npc, dialogue);
+
+        graph.begin_render(output_buffer.len());
+        for (left, right) in graph.buses[dialogue].input_buffer() {
+            *left = 0.25;
+            *right = 0.25;
+        }
+        // Only a child bus of the sidechain source plays something.
+        for (left, right) in graph.buses[npc].input_buffer() {
+            *left = 1.0;
+            *right = 1.0;
+        }
+        graph.end_render(&mut output_buffer);
+
+        let sidechain = graph.buses[music].effects[0]
+            .sidechain_buffer_mut()
+            .unwrap();
+        assert_eq!(sidechain.len(), 64);
+        assert!(sidechain.iter().all(|sample| *sample == (0.75, 0.75)));
+    }
+
+    #[test]
+    fn test_sidechain_resolution() {
+        let mut output_buffer = [(0.0f32, 0.0f32); 256];
+
+        let mut graph = AudioBusGraph::new();
+
+        let mut ducker = Compressor::new();
+        ducker.set_sidechain_bus("Dialogue");
+        let mut music = AudioBus::new("Music".to_string());
+        music.add_effect(Effect::Compressor(ducker));
+        let music = graph.add_bus(music, graph.root);
+        let voice = graph.add_bus(AudioBus::new("Voice".to_string()), graph.root);
+
+        let mut render = |graph: &mut AudioBusGraph| {
+            graph.begin_render(output_buffer.len());
+            for (left, right) in graph.buses[voice].input_buffer() {
+                *left = 1.0;
+                *right = 1.0;
+            }
+            graph.end_render(&mut output_buffer);
+        };
+
+        // There is no bus with the given name yet.
+        render(&mut graph);
+        assert!(graph.sidechains_resolved);
+        assert_eq!(graph.sidechains.len(), 1);
+        assert!(graph.sidechains[0].source.is_none());
+
+        // Renaming must be picked up on the next mixing pass.
+        graph.try_get_bus_mut(voice).unwrap().set_name("Dialogue");
+        assert!(!graph.sidechains_resolved);
+        render(&mut graph);
+        assert_eq!(graph.sidechains[0].source, voice);
+
+        let sidechain_ptr = |graph: &mut AudioBusGraph| {
+            let buffer = graph.buses[music].effects[0]
+                .sidechain_buffer_mut()
+                .unwrap();
+            assert_eq!(buffer.len(), 256);
+            assert_eq!(buffer[0], (1.0, 1.0));
+            buffer.as_ptr()
+        };
+
+        // The sidechain buffer must be reused between mixing passes.
+        let ptr = sidechain_ptr(&mut graph);
+        render(&mut graph);
+        assert!(graph.sidechains_resolved);
+        assert_eq!(sidechain_ptr(&mut graph), ptr);
+    }
 }
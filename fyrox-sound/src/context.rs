@@ -32,6 +32,8 @@
 
 use crate::bus::AudioBusGraph;
 use crate::{
+    encoder::{WavEncoder, WavSampleFormat},
+    error::SoundError,
     listener::Listener,
     pool::Ticket,
     renderer::{render_source_default, Renderer},
@@ -44,6 +46,8 @@ use fyrox_core::{
     visitor::prelude::*,
 };
 use std::{
+    io::{Seek, Write},
+    path::Path,
     sync::{Arc, Mutex, MutexGuard},
     time::Duration,
 };
@@ -142,6 +146,9 @@ pub struct State {
     /// serialization of a sound context.
     #[reflect(hidden)]
     pub serialization_options: SerializationOptions,
+    /// Samples of the last block rendered by offline rendering, that were not returned yet.
+    #[reflect(hidden)]
+    offline_tail: Vec<(f32, f32)>,
 }
 
 impl State {
@@ -271,6 +278,9 @@ impl State {
 
     /// Returns a reference to the audio bus graph.
     pub fn bus_graph_mut(&mut self) -> &mut AudioBusGraph {
+        // The graph could be modified in any way (for example, via reflection), so sidechains must
+        // be resolved again.
+        self.bus_graph.invalidate_sidechains();
         &mut self.bus_graph
     }
 
@@ -348,6 +358,7 @@ impl SoundContext {
                 distance_model: DistanceModel::InverseDistance,
                 paused: false,
                 serialization_options: Default::default(),
+                offline_tail: Default::default(),
             }))),
         }
     }
@@ -384,6 +395,93 @@ impl SoundContext {
     pub fn is_invalid(&self) -> bool {
         self.state.is_none()
     }
+
+    /// Renders the context for the given amount of time as fast as possible and returns rendered stereo
+    /// samples. Sound sources are advanced exactly by the amount of rendered samples, so the result
+    /// depends only on the state of the context, which makes offline rendering suitable for automated
+    /// tests of audio mixes or for baking audio.
+    ///
+    /// ## Notes
+    ///
+    /// The context is rendered by blocks of fixed size. Samples of the last block, that do not fit in the
+    /// requested duration, are kept and returned first by the next call, so a sequence of calls produces
+    /// exactly the same samples as a single call with the total duration. This also means, that changes of
+    /// the context made between the calls are heard with a delay of up to one block (about 46 ms). Sound
+    /// buffers must be fully loaded before rendering, otherwise respective sound sources will be silent.
+    /// The context must not be added to a sound engine with an output device, otherwise it will be
+    /// rendered by both.
+    pub fn render_offline(&self, duration: Duration) -> Vec<(f32, f32)> {
+        let mut samples = Vec::with_capacity(Self::duration_to_frames(duration));
+        self.render_offline_blocks(duration, |block| {
+            samples.extend_from_slice(block);
+            Ok(())
+        })
+        .expect("Collecting samples cannot fail!");
+        samples
+    }
+
+    /// Renders the context for the given amount of time and writes the output in WAV format to the given
+    /// writer. See [`Self::render_offline`] docs for more info about offline rendering.
+    pub fn render_to_wav<W: Write + Seek>(
+        &self,
+        writer: W,
+        duration: Duration,
+        format: WavSampleFormat,
+    ) -> Result<(), SoundError> {
+        let mut encoder = WavEncoder::new(writer, SAMPLE_RATE, format)?;
+        self.render_offline_blocks(duration, |block| encoder.write_samples(block))?;
+        encoder.finalize()
+    }
+
+    /// Renders the context for the given amount of time and writes the output to a WAV file at the given
+    /// path. See [`Self::render_offline`] docs for more info about offline rendering.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use fyrox_sound::{context::SoundContext, encoder::WavSampleFormat};
+    /// # use std::time::Duration;
+    /// # let context = SoundContext::new();
+    /// context
+    ///     .render_to_wav_file("cutscene.wav", Duration::from_secs(10), WavSampleFormat::Pcm16)
+    ///     .unwrap();
+    /// ```
+    pub fn render_to_wav_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        duration: Duration,
+        format: WavSampleFormat,
+    ) -> Result<(), SoundError> {
+        let mut encoder = WavEncoder::create(path, format)?;
+        self.render_offline_blocks(duration, |block| encoder.write_samples(block))?;
+        encoder.finalize()
+    }
+
+    fn duration_to_frames(duration: Duration) -> usize {
+        (duration.as_secs_f64() * SAMPLE_RATE as f64).round() as usize
+    }
+
+    fn render_offline_blocks<F>(&self, duration: Duration, mut func: F) -> Result<(), SoundError>
+    where
+        F: FnMut(&[(f32, f32)]) -> Result<(), SoundError>,
+    {
+        let mut state = self.state();
+        let mut remaining = Self::duration_to_frames(duration);
+        // Continue from the samples of the last block, that were left by the previous call.
+        let mut block = std::mem::take(&mut state.offline_tail);
+        while remaining > 0 {
+            if block.is_empty() {
+                block.resize(Self::SAMPLES_PER_CHANNEL, (0.0, 0.0));
+                state.render(&mut block);
+            }
+            let count = remaining.min(block.len());
+            func(&block[..count])?;
+            block.drain(..count);
+            remaining -= count;
+        }
+        state.offline_tail = block;
+        Ok(())
+    }
 }
 
 impl Visit for State {
@@ -409,3 +507,108 @@ impl Visit for State {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        buffer::{DataSource, SoundBufferResource, SoundBufferResourceExtension},
+        context::{SoundContext, SAMPLE_RATE},
+        encoder::WavSampleFormat,
+        source::{SoundSourceBuilder, Status},
+    };
+    use hound::WavReader;
+    use std::{io::Cursor, time::Duration};
+
+    fn make_context() -> SoundContext {
+        let buffer = SoundBufferResource::new_generic(DataSource::Raw {
+            sample_rate: SAMPLE_RATE as usize,
+            channel_count: 1,
+            samples: (0..SAMPLE_RATE)
+                .map(|i| (i as f32 * 0.05).sin() * 0.5)
+                .collect(),
+        })
+        .unwrap();
+
+        let context = SoundContext::new();
+        let source = SoundSourceBuilder::new()
+            .with_buffer(buffer)
+            .with_status(Status::Playing)
+            .build()
+            .unwrap();
+        context.state().add_source(source);
+        context
+    }
+
+    #[test]
+    fn test_offline_render_is_deterministic() {
+        let duration = Duration::from_millis(250);
+
+        let samples = make_context().render_offline(duration);
+        assert_eq!(samples.len(), SAMPLE_RATE as usize / 4);
+        assert!(samples
+            .iter()
+            .any(|(left, right)| *left != 0.0 && *right != 0.0));
+
+        assert_eq!(samples, make_context().render_offline(duration));
+    }
+
+    #[test]
+    fn test_split_offline_render() {
+        // Durations are not multiples of the block size, so the blocks are split between calls.
+        let expected = make_context().render_offline(Duration::from_millis(300));
+
+        let context = make_context();
+        let mut samples = context.render_offline(Duration::from_millis(70));
+        samples.extend(context.render_offline(Duration::from_millis(30)));
+        samples.extend(context.render_offline(Duration::from_millis(200)));
+        assert_eq!(samples, expected);
+    }
+
+    #[test]
+    fn test_offline_render_to_wav() {
+        let duration = Duration::from_millis(100);
+        let expected = make_context().render_offline(duration);
+
+        let mut cursor = Cursor::new(Vec::new());
+        make_context()
+            .render_to_wav(&mut cursor, duration, WavSampleFormat::Float32)
+            .unwrap();
+
+        cursor.set_position(0);
+        let mut reader = WavReader::new(cursor).unwrap();
+        assert_eq!(reader.spec().sample_rate, SAMPLE_RATE);
+        let samples = reader
+            .samples::<f32>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(samples.len(), expected.len() * 2);
+        for (pair, (left, right)) in samples.chunks(2).zip(expected) {
+            assert_eq!(pair, [left, right]);
+        }
+    }
+
+    #[test]
+    fn test_occlusion() {
+        let duration = Duration::from_millis(100);
+        let expected = make_context().render_offline(duration);
+
+        let context = make_context();
+        for source in context.state().sources_mut().iter_mut() {
+            source.set_occlusion(0.5, f32::MAX);
+        }
+        let occluded = context.render_offline(duration);
+        for ((left, right), (expected_left, expected_right)) in occluded.into_iter().zip(expected) {
+            assert!((left - expected_left * 0.5).abs() < 1.0e-6);
+            assert!((right - expected_right * 0.5).abs() < 1.0e-6);
+        }
+
+        // Low-pass filter must suppress high frequencies.
+        let context = make_context();
+        for source in context.state().sources_mut().iter_mut() {
+            source.set_occlusion(1.0, 20.0);
+        }
+        let energy = |samples: &[(f32, f32)]| samples.iter().map(|(l, _)| l * l).sum::<f32>();
+        let muffled = context.render_offline(duration);
+        assert!(energy(&muffled) < energy(&make_context().render_offline(duration)) * 0.1);
+    }
+}
@@ -20,11 +20,16 @@
 
 use crate::{
     buffer::DataSource,
-    decoder::{vorbis::OggDecoder, wav::WavDecoder},
+    decoder::{
+        symphonia::{SymphoniaDecoder, SymphoniaFormat},
+        vorbis::OggDecoder,
+        wav::WavDecoder,
+    },
     error::SoundError,
 };
 use std::time::Duration;
 
+mod symphonia;
 mod vorbis;
 mod wav;
 
@@ -32,6 +37,8 @@ mod wav;
 pub(crate) enum Decoder {
     Wav(WavDecoder),
     Ogg(OggDecoder),
+    Flac(Box<SymphoniaDecoder>),
+    Mp3(Box<SymphoniaDecoder>),
 }
 
 impl Iterator for Decoder {
@@ -41,6 +48,7 @@ impl Iterator for Decoder {
         match self {
             Decoder::Wav(wav) => wav.next(),
             Decoder::Ogg(ogg) => ogg.next(),
+            Decoder::Flac(decoder) | Decoder::Mp3(decoder) => decoder.next(),
         }
     }
 }
@@ -57,6 +65,16 @@ impl Decoder {
             Ok(ogg_decoder) => return Ok(Decoder::Ogg(ogg_decoder)),
             Err(source) => source,
         };
+        // Try Flac/Mp3
+        let source = match SymphoniaDecoder::new(source) {
+            Ok(decoder) => {
+                return Ok(match decoder.format {
+                    SymphoniaFormat::Flac => Decoder::Flac(Box::new(decoder)),
+                    SymphoniaFormat::Mp3 => Decoder::Mp3(Box::new(decoder)),
+                })
+            }
+            Err(source) => source,
+        };
         Err(source)
     }
 
@@ -64,6 +82,7 @@ impl Decoder {
         match self {
             Decoder::Wav(wav) => wav.rewind(),
             Decoder::Ogg(ogg) => ogg.rewind(),
+            Decoder::Flac(decoder) | Decoder::Mp3(decoder) => decoder.rewind(),
         }
     }
 
@@ -71,6 +90,7 @@ impl Decoder {
         match self {
             Decoder::Wav(wav) => wav.time_seek(location),
             Decoder::Ogg(ogg) => ogg.time_seek(location),
+            Decoder::Flac(decoder) | Decoder::Mp3(decoder) => decoder.time_seek(location),
         }
     }
 
@@ -78,6 +98,7 @@ impl Decoder {
         match self {
             Decoder::Wav(wav) => wav.channel_count(),
             Decoder::Ogg(ogg) => ogg.channel_count,
+            Decoder::Flac(decoder) | Decoder::Mp3(decoder) => decoder.channel_count,
         }
     }
 
@@ -85,17 +106,15 @@ impl Decoder {
         match self {
             Decoder::Wav(wav) => wav.sample_rate(),
             Decoder::Ogg(ogg) => ogg.sample_rate,
+            Decoder::Flac(decoder) | Decoder::Mp3(decoder) => decoder.sample_rate,
         }
     }
 
-    pub fn into_samples(self) -> Vec<f32> {
-        self.collect()
-    }
-
     pub fn channel_duration_in_samples(&self) -> usize {
         match self {
             Decoder::Wav(wav) => wav.channel_duration_in_samples(),
             Decoder::Ogg(ogg) => ogg.channel_duration_in_samples(),
+            Decoder::Flac(decoder) | Decoder::Mp3(decoder) => decoder.channel_duration_in_samples(),
         }
     }
 }
@@ -0,0 +1,465 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! FLAC and MP3 decoding using `symphonia`.
+
+use crate::{buffer::DataSource, error::SoundError};
+use std::{
+    fmt::{Debug, Formatter},
+    io::{Read, Seek, SeekFrom},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use symphonia::core::{
+    audio::SampleBuffer,
+    codecs::{self, DecoderOptions, CODEC_TYPE_FLAC, CODEC_TYPE_MP3},
+    errors::Error,
+    formats::{FormatOptions, FormatReader, SeekMode, SeekTo},
+    io::{MediaSource, MediaSourceStream},
+    meta::MetadataOptions,
+    probe::Hint,
+    units::Time,
+};
+
+/// Format of a sound that can be decoded by [`SymphoniaDecoder`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum SymphoniaFormat {
+    Flac,
+    Mp3,
+}
+
+// `symphonia` requires its sources to be `Sync`, but data source is `Send` only. Mutex solves the
+// issue and also allows us to take the data source back if it has an unsupported format.
+#[derive(Clone)]
+struct SharedDataSource(Arc<Mutex<DataSource>>);
+
+impl SharedDataSource {
+    fn into_inner(self) -> DataSource {
+        Arc::try_unwrap(self.0).unwrap().into_inner().unwrap()
+    }
+}
+
+impl Read for SharedDataSource {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().read(buf)
+    }
+}
+
+impl Seek for SharedDataSource {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.0.lock().unwrap().seek(pos)
+    }
+}
+
+impl MediaSource for SharedDataSource {
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        let mut source = self.0.lock().unwrap();
+        let position = source.stream_position().ok()?;
+        let len = source.seek(SeekFrom::End(0)).ok()?;
+        source.seek(SeekFrom::Start(position)).ok()?;
+        Some(len)
+    }
+}
+
+/// Decoder for FLAC and MP3 sounds.
+pub(crate) struct SymphoniaDecoder {
+    reader: Box<dyn FormatReader>,
+    decoder: Box<dyn codecs::Decoder>,
+    track_id: u32,
+    buffer: Option<SampleBuffer<f32>>,
+    samples: Vec<f32>,
+    position: usize,
+    // Amount of frames that must be skipped to reach exact position after seeking.
+    frames_to_skip: usize,
+    pub format: SymphoniaFormat,
+    pub channel_count: usize,
+    pub sample_rate: usize,
+    // Length of the stream in frames stored in the stream header, if any.
+    n_frames: Option<usize>,
+    // Length of the stream in bytes, used to estimate the length of streams without the header
+    // information.
+    byte_len: Option<u64>,
+    // Total duration and size of the packets read so far.
+    read_frames: u64,
+    read_bytes: u64,
+    // End of the furthest packet read so far.
+    last_packet_end: usize,
+    // Exact length of the stream, it is known once the end of the stream was reached.
+    stream_end: Option<usize>,
+}
+
+impl Debug for SymphoniaDecoder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SymphoniaDecoder({:?})", self.format)
+    }
+}
+
+impl Iterator for SymphoniaDecoder {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(sample) = self.samples.get(self.position) {
+                self.position += 1;
+                return Some(*sample);
+            }
+            if !self.decode_next_packet() {
+                return None;
+            }
+        }
+    }
+}
+
+impl SymphoniaDecoder {
+    pub fn new(mut source: DataSource) -> Result<Self, DataSource> {
+        let Ok(position) = source.stream_position() else {
+            return Err(source);
+        };
+
+        let shared_source = SharedDataSource(Arc::new(Mutex::new(source)));
+
+        let stream = MediaSourceStream::new(Box::new(shared_source.clone()), Default::default());
+        match Self::from_stream(stream) {
+            Some(decoder) => Ok(decoder),
+            None => {
+                let mut source = shared_source.into_inner();
+                let _ = source.seek(SeekFrom::Start(position));
+                Err(source)
+            }
+        }
+    }
+
+    fn from_stream(stream: MediaSourceStream) -> Option<Self> {
+        let byte_len = stream.byte_len();
+        let probed = symphonia::default::get_probe()
+            .format(
+                &Hint::new(),
+                stream,
+                &FormatOptions::default(),
+                &MetadataOptions::default(),
+            )
+            .ok()?;
+        let reader = probed.format;
+
+        let track = reader.default_track()?;
+        let format = match track.codec_params.codec {
+            CODEC_TYPE_FLAC => SymphoniaFormat::Flac,
+            CODEC_TYPE_MP3 => SymphoniaFormat::Mp3,
+            _ => return None,
+        };
+        let track_id = track.id;
+        let codec_params = track.codec_params.clone();
+        let sample_rate = codec_params.sample_rate? as usize;
+
+        let decoder = symphonia::default::get_codecs()
+            .make(&codec_params, &DecoderOptions::default())
+            .ok()?;
+
+        let mut decoder = Self {
+            reader,
+            decoder,
+            track_id,
+            buffer: None,
+            samples: Vec::new(),
+            position: 0,
+            frames_to_skip: 0,
+            format,
+            channel_count: codec_params.channels.map(|c| c.count()).unwrap_or_default(),
+            sample_rate,
+            n_frames: codec_params.n_frames.map(|n| n as usize),
+            byte_len,
+            read_frames: 0,
+            read_bytes: 0,
+            last_packet_end: 0,
+            stream_end: None,
+        };
+
+        // MP3 streams does not store channel count in their headers, so it is only known when the
+        // first packet was decoded.
+        if decoder.channel_count == 0 {
+            decoder.decode_next_packet();
+        }
+
+        if decoder.channel_count == 0 {
+            None
+        } else {
+            Some(decoder)
+        }
+    }
+
+    fn decode_next_packet(&mut self) -> bool {
+        loop {
+            let packet = match self.reader.next_packet() {
+                Ok(packet) => packet,
+                Err(_) => {
+                    self.stream_end = Some(self.last_packet_end);
+                    return false;
+                }
+            };
+
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+
+            self.read_frames += packet.dur();
+            self.read_bytes += packet.buf().len() as u64;
+            self.last_packet_end = self
+                .last_packet_end
+                .max((packet.ts() + packet.dur()) as usize);
+
+            let decoded = match self.decoder.decode(&packet) {
+                Ok(decoded) => decoded,
+                // Malformed packet, skip it.
+                Err(Error::DecodeError(_)) => continue,
+                Err(_) => return false,
+            };
+
+            let spec = *decoded.spec();
+            let channel_count = spec.channels.count();
+            let required_capacity = decoded.capacity() * channel_count;
+            let buffer = match self.buffer {
+                Some(ref mut buffer) if buffer.capacity() >= required_capacity => buffer,
+                _ => self
+                    .buffer
+                    .insert(SampleBuffer::new(decoded.capacity() as u64, spec)),
+            };
+            buffer.copy_interleaved_ref(decoded);
+
+            let frame_count = buffer.len() / channel_count;
+            let skip = self.frames_to_skip.min(frame_count);
+            self.frames_to_skip -= skip;
+
+            self.samples.clear();
+            self.samples
+                .extend_from_slice(&buffer.samples()[skip * channel_count..]);
+            self.position = 0;
+
+            if self.channel_count == 0 {
+                self.channel_count = channel_count;
+            }
+
+            if !self.samples.is_empty() {
+                return true;
+            }
+        }
+    }
+
+    fn seek(&mut self, to: SeekTo) -> Result<(), SoundError> {
+        let seeked_to = self
+            .reader
+            .seek(SeekMode::Accurate, to)
+            .map_err(|_| SoundError::UnsupportedFormat)?;
+        self.decoder.reset();
+        self.samples.clear();
+        self.position = 0;
+        self.frames_to_skip = seeked_to.required_ts.saturating_sub(seeked_to.actual_ts) as usize;
+        Ok(())
+    }
+
+    pub fn rewind(&mut self) -> Result<(), SoundError> {
+        self.seek(SeekTo::TimeStamp {
+            ts: 0,
+            track_id: self.track_id,
+        })
+    }
+
+    pub fn time_seek(&mut self, location: Duration) {
+        let _ = self.seek(SeekTo::Time {
+            time: Time::from(location.as_secs_f64()),
+            track_id: Some(self.track_id),
+        });
+    }
+
+    /// Returns the length of the stream in frames. Some streams (usually MP3 without a Xing header)
+    /// do not store their length, in this case the length is estimated using the average size of
+    /// the packets read so far. The estimation becomes exact when the end of the stream is reached.
+    pub fn channel_duration_in_samples(&self) -> usize {
+        if let Some(stream_end) = self.stream_end {
+            return stream_end;
+        }
+        if let Some(n_frames) = self.n_frames {
+            return n_frames;
+        }
+        match self.byte_len {
+            Some(byte_len) if self.read_bytes > 0 => {
+                let estimation = (byte_len as u128 * self.read_frames as u128
+                    / self.read_bytes as u128) as usize;
+                estimation.max(self.last_packet_end)
+            }
+            _ => self.last_packet_end,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        buffer::{generic::GenericBuffer, DataSource},
+        decoder::{symphonia::SymphoniaFormat, Decoder},
+    };
+    use std::time::Duration;
+
+    fn crc8(data: &[u8]) -> u8 {
+        let mut crc = 0u8;
+        for byte in data {
+            crc ^= byte;
+            for _ in 0..8 {
+                crc = if crc & 0x80 != 0 {
+                    (crc << 1) ^ 0x07
+                } else {
+                    crc << 1
+                };
+            }
+        }
+        crc
+    }
+
+    fn crc16(data: &[u8]) -> u16 {
+        let mut crc = 0u16;
+        for byte in data {
+            crc ^= (*byte as u16) << 8;
+            for _ in 0..8 {
+                crc = if crc & 0x8000 != 0 {
+                    (crc << 1) ^ 0x8005
+                } else {
+                    crc << 1
+                };
+            }
+        }
+        crc
+    }
+
+    // Encodes mono 16-bit samples into a FLAC stream using verbatim (uncompressed) subframes.
+    fn encode_flac(samples: &[i16], sample_rate: u32, block_size: usize) -> Vec<u8> {
+        let mut out = b"fLaC".to_vec();
+
+        // STREAMINFO, the last metadata block.
+        out.extend_from_slice(&[0x80, 0, 0, 34]);
+        out.extend_from_slice(&(block_size as u16).to_be_bytes());
+        out.extend_from_slice(&(block_size as u16).to_be_bytes());
+        out.extend_from_slice(&[0; 6]);
+        let info = ((sample_rate as u64) << 44) | (15u64 << 36) | samples.len() as u64;
+        out.extend_from_slice(&info.to_be_bytes());
+        out.extend_from_slice(&[0; 16]);
+
+        for (frame_number, block) in samples.chunks(block_size).enumerate() {
+            assert!(frame_number < 128);
+            let mut frame = vec![0xFF, 0xF8, 0x70, 0x08, frame_number as u8];
+            frame.extend_from_slice(&(block.len() as u16 - 1).to_be_bytes());
+            frame.push(crc8(&frame));
+            // Verbatim subframe.
+            frame.push(0x02);
+            for sample in block {
+                frame.extend_from_slice(&sample.to_be_bytes());
+            }
+            frame.extend_from_slice(&crc16(&frame).to_be_bytes());
+            out.extend_from_slice(&frame);
+        }
+
+        out
+    }
+
+    #[test]
+    fn test_flac_decoding() {
+        let samples = (0..5000)
+            .map(|i| ((i as f32 * 0.05).sin() * 10000.0) as i16)
+            .collect::<Vec<_>>();
+        let data = encode_flac(&samples, 8000, 1024);
+
+        let Ok(mut decoder) = Decoder::new(DataSource::from_memory(data)) else {
+            panic!("FLAC data must be recognized");
+        };
+        assert!(matches!(decoder, Decoder::Flac(ref d) if d.format == SymphoniaFormat::Flac));
+        assert_eq!(decoder.get_channel_count(), 1);
+        assert_eq!(decoder.get_sample_rate(), 8000);
+        assert_eq!(decoder.channel_duration_in_samples(), samples.len());
+
+        let expected = |i: usize| samples[i] as f32 / 32768.0;
+
+        let decoded = decoder.by_ref().take(samples.len()).collect::<Vec<_>>();
+        assert_eq!(decoded.len(), samples.len());
+        for (i, sample) in decoded.iter().enumerate() {
+            assert!((sample - expected(i)).abs() < 1.0e-4);
+        }
+        assert_eq!(decoder.next(), None);
+
+        decoder.rewind().unwrap();
+        assert!((decoder.next().unwrap() - expected(0)).abs() < 1.0e-4);
+
+        // 0.5 second is 4000th sample, it is in the middle of a block.
+        decoder.time_seek(Duration::from_millis(500));
+        assert!((decoder.next().unwrap() - expected(4000)).abs() < 1.0e-4);
+        assert!((decoder.next().unwrap() - expected(4001)).abs() < 1.0e-4);
+        // Unknown data must be returned back.
+        let garbage = vec![1u8; 256];
+        match Decoder::new(DataSource::from_memory(garbage.clone())) {
+            Err(DataSource::Memory(cursor)) => assert_eq!(cursor.into_inner(), garbage),
+            _ => panic!("garbage must not be recognized"),
+        }
+    }
+
+    // Produces silent MPEG-1 Layer III frames: 128 kbit/s, 44100 Hz, mono, no CRC. Zero side
+    // information means that every frame decodes into 1152 silent samples.
+    fn encode_silent_mp3(frame_count: usize) -> Vec<u8> {
+        const FRAME_SIZE: usize = 144 * 128_000 / 44100;
+        let mut out = Vec::with_capacity(frame_count * FRAME_SIZE);
+        for _ in 0..frame_count {
+            out.extend_from_slice(&[0xFF, 0xFB, 0x90, 0xC0]);
+            out.resize(out.len() + FRAME_SIZE - 4, 0);
+        }
+        out
+    }
+
+    #[test]
+    fn test_mp3_decoding() {
+        let frame_count = 40;
+        let data = encode_silent_mp3(frame_count);
+
+        let Ok(mut decoder) = Decoder::new(DataSource::from_memory(data.clone())) else {
+            panic!("MP3 data must be recognized");
+        };
+        assert!(matches!(decoder, Decoder::Mp3(ref d) if d.format == SymphoniaFormat::Mp3));
+        assert_eq!(decoder.get_channel_count(), 1);
+        assert_eq!(decoder.get_sample_rate(), 44100);
+
+        // The length is not stored in the stream, it must be estimated without reading the whole
+        // stream first.
+        let mut decoded = decoder.by_ref().take(1152).collect::<Vec<_>>();
+        let estimation = decoder.channel_duration_in_samples();
+        assert!(estimation.abs_diff(frame_count * 1152) <= 1152);
+
+        decoded.extend(decoder.by_ref());
+        assert_eq!(decoded.len(), frame_count * 1152);
+        assert!(decoded.iter().all(|s| s.abs() < 1.0e-6));
+        assert_eq!(decoder.channel_duration_in_samples(), frame_count * 1152);
+
+        decoder.rewind().unwrap();
+        assert_eq!(decoder.by_ref().count(), frame_count * 1152);
+
+        let buffer = GenericBuffer::new(DataSource::from_memory(data)).unwrap();
+        assert_eq!(buffer.channel_duration_in_samples(), frame_count * 1152);
+        assert_eq!(buffer.samples().len(), frame_count * 1152);
+    }
+}
@@ -109,3 +109,23 @@ pub fn hann_window(i: usize, sample_count: usize) -> f32 {
 pub fn make_window<W: Fn(usize, usize) -> f32>(sample_count: usize, func: W) -> Vec<f32> {
     (0..sample_count).map(|i| func(i, sample_count)).collect()
 }
+
+/// Converts decibels to linear gain, for example `-6 dB` is `~0.5`.
+pub fn db_to_linear(db: f32) -> f32 {
+    10.0f32.powf(db / 20.0)
+}
+
+/// Converts linear gain to decibels. Gain values close to zero are clamped to `-200 dB`.
+pub fn linear_to_db(gain: f32) -> f32 {
+    20.0 * gain.max(1.0e-10).log10()
+}
+
+/// Calculates a coefficient of one-pole smoothing filter, that reaches ~63% of a target value in the given time.
+pub fn time_constant_coefficient(time: f32, sample_rate: u32) -> f32 {
+    let samples = time * sample_rate as f32;
+    if samples <= 0.0 {
+        0.0
+    } else {
+        (-1.0 / samples).exp()
+    }
+}
@@ -0,0 +1,216 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Chorus effect.
+
+use crate::{context::SAMPLE_RATE, effects::EffectRenderTrait};
+use fyrox_core::{reflect::prelude::*, visitor::prelude::*};
+use std::f32::consts::TAU;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+struct ModulatedDelayLine {
+    samples: Vec<f32>,
+    position: usize,
+}
+
+impl ModulatedDelayLine {
+    fn write(&mut self, sample: f32) {
+        self.samples[self.position] = sample;
+        self.position = (self.position + 1) % self.samples.len();
+    }
+
+    /// Reads a sample that was written `delay` samples ago, fractional delays are linearly interpolated.
+    fn read(&self, delay: f32) -> f32 {
+        let len = self.samples.len();
+        let delay = delay.clamp(1.0, (len - 2) as f32);
+        let whole = delay as usize;
+        let fraction = delay - whole as f32;
+        let a = self.samples[(self.position + len - whole) % len];
+        let b = self.samples[(self.position + len - whole - 1) % len];
+        a + (b - a) * fraction
+    }
+}
+
+/// Chorus effect mixes the input signal with a copy of itself, that is delayed by a slowly changing
+/// amount of time. This makes a single sound source to sound like a few slightly detuned ones.
+#[derive(Debug, Clone, PartialEq, Visit, Reflect)]
+pub struct Chorus {
+    #[reflect(
+        description = "Average delay time (in seconds) of the delayed copy of the signal.",
+        setter = "set_delay_time",
+        min_value = 0.001,
+        max_value = 0.1
+    )]
+    delay_time: f32,
+
+    #[reflect(
+        description = "Amplitude (in seconds) of the delay time modulation.",
+        setter = "set_depth",
+        min_value = 0.0,
+        max_value = 0.05
+    )]
+    depth: f32,
+
+    #[reflect(
+        description = "Frequency (in Hertz) of the delay time modulation.",
+        setter = "set_rate",
+        min_value = 0.0,
+        max_value = 20.0
+    )]
+    rate: f32,
+
+    #[reflect(
+        description = "Amount of the delayed signal in the output. 0.0 - only input signal, 1.0 - only delayed signal.",
+        setter = "set_mix",
+        min_value = 0.0,
+        max_value = 1.0
+    )]
+    mix: f32,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    phase: f32,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    left: ModulatedDelayLine,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    right: ModulatedDelayLine,
+}
+
+impl Default for Chorus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Chorus {
+    /// Creates new chorus effect with 20 ms delay time, 3 ms depth and 0.8 Hz modulation rate.
+    pub fn new() -> Self {
+        Self {
+            delay_time: 0.02,
+            depth: 0.003,
+            rate: 0.8,
+            mix: 0.5,
+            phase: 0.0,
+            left: Default::default(),
+            right: Default::default(),
+        }
+    }
+
+    /// Sets the average delay time (in seconds) of the delayed copy of the signal.
+    pub fn set_delay_time(&mut self, delay_time: f32) -> f32 {
+        std::mem::replace(&mut self.delay_time, delay_time.clamp(0.001, 0.1))
+    }
+
+    /// Returns the average delay time in seconds.
+    pub fn delay_time(&self) -> f32 {
+        self.delay_time
+    }
+
+    /// Sets the amplitude (in seconds) of the delay time modulation.
+    pub fn set_depth(&mut self, depth: f32) -> f32 {
+        std::mem::replace(&mut self.depth, depth.clamp(0.0, 0.05))
+    }
+
+    /// Returns the amplitude of the delay time modulation in seconds.
+    pub fn depth(&self) -> f32 {
+        self.depth
+    }
+
+    /// Sets the frequency (in Hertz) of the delay time modulation.
+    pub fn set_rate(&mut self, rate: f32) -> f32 {
+        std::mem::replace(&mut self.rate, rate.clamp(0.0, 20.0))
+    }
+
+    /// Returns the frequency of the delay time modulation in Hertz.
+    pub fn rate(&self) -> f32 {
+        self.rate
+    }
+
+    /// Sets the amount of the delayed signal in the output.
+    pub fn set_mix(&mut self, mix: f32) -> f32 {
+        std::mem::replace(&mut self.mix, mix.clamp(0.0, 1.0))
+    }
+
+    /// Returns the amount of the delayed signal in the output.
+    pub fn mix(&self) -> f32 {
+        self.mix
+    }
+}
+
+impl EffectRenderTrait for Chorus {
+    fn render(&mut self, input: &[(f32, f32)], output: &mut [(f32, f32)]) {
+        let sample_rate = SAMPLE_RATE as f32;
+        let len = ((self.delay_time + self.depth) * sample_rate) as usize + 3;
+        if self.left.samples.len() != len {
+            self.left = ModulatedDelayLine {
+                samples: vec![0.0; len],
+                position: 0,
+            };
+            self.right = self.left.clone();
+        }
+
+        let delay = self.delay_time * sample_rate;
+        let depth = self.depth * sample_rate;
+        let phase_step = TAU * self.rate / sample_rate;
+
+        for (&(left, right), (output_left, output_right)) in input.iter().zip(output.iter_mut()) {
+            self.left.write(left);
+            self.right.write(right);
+
+            // Channels are modulated with 90 degrees phase shift to get wider stereo image.
+            let delayed_left = self.left.read(delay + depth * self.phase.sin());
+            let delayed_right = self.right.read(delay + depth * self.phase.cos());
+
+            *output_left = left * (1.0 - self.mix) + delayed_left * self.mix;
+            *output_right = right * (1.0 - self.mix) + delayed_right * self.mix;
+
+            self.phase = (self.phase + phase_step) % TAU;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::effects::{chorus::Chorus, EffectRenderTrait};
+
+    #[test]
+    fn test_chorus_dry_and_wet() {
+        let input = vec![(1.0, -1.0); 4096];
+        let mut output = vec![(0.0, 0.0); 4096];
+
+        let mut chorus = Chorus::new();
+        chorus.set_mix(0.0);
+        chorus.render(&input, &mut output);
+        assert_eq!(input, output);
+
+        // Delayed copy of a constant signal is the same constant once the delay line is filled.
+        let mut chorus = Chorus::new();
+        chorus.set_mix(1.0);
+        chorus.render(&input, &mut output);
+        assert_eq!(output[0], (0.0, 0.0));
+        let (left, right) = *output.last().unwrap();
+        assert!((left - 1.0).abs() < 1.0e-5);
+        assert!((right + 1.0).abs() < 1.0e-5);
+    }
+}
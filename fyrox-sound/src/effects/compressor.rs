@@ -0,0 +1,436 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Dynamic range processing effects - compressor and limiter.
+//!
+//! # Sidechain
+//!
+//! Both effects could use a signal of another audio bus to control the gain reduction. This is called
+//! sidechaining and the most common use case for it is ducking - automatic reduction of the volume of
+//! music and ambient sounds while a character speaks:
+//!
+//! ```
+//! use fyrox_sound::{
+//!     bus::AudioBus,
+//!     context::SoundContext,
+//!     effects::{compressor::Compressor, Effect},
+//! };
+//!
+//! fn setup_ducking(context: &mut SoundContext) {
+//!     let mut state = context.state();
+//!     let graph = state.bus_graph_mut();
+//!     let primary = graph.primary_bus_handle();
+//!     graph.add_bus(AudioBus::new("Dialogue".to_string()), primary);
+//!
+//!     let mut music = AudioBus::new("Music".to_string());
+//!     let mut ducker = Compressor::new();
+//!     ducker.set_threshold_db(-30.0);
+//!     ducker.set_ratio(8.0);
+//!     ducker.set_sidechain_bus("Dialogue");
+//!     music.add_effect(Effect::Compressor(ducker));
+//!     graph.add_bus(music, primary);
+//! }
+//! ```
+//!
+//! Sidechain signal is the signal that was sent to the bus directly by sound sources, mixed with the
+//! signals of all its child buses (scaled by their gains). Effects of the buses are not applied to it.
+
+use crate::{
+    context::SAMPLE_RATE,
+    dsp::{db_to_linear, linear_to_db, time_constant_coefficient},
+    effects::EffectRenderTrait,
+};
+use fyrox_core::{reflect::prelude::*, visitor::prelude::*};
+
+/// Peak envelope follower with separate attack and release times.
+#[derive(Debug, Clone, PartialEq, Default)]
+struct EnvelopeFollower {
+    envelope: f32,
+}
+
+impl EnvelopeFollower {
+    fn feed(&mut self, level: f32, attack: f32, release: f32) -> f32 {
+        let coefficient = if level > self.envelope {
+            attack
+        } else {
+            release
+        };
+        self.envelope = coefficient * self.envelope + (1.0 - coefficient) * level;
+        self.envelope
+    }
+}
+
+pub(crate) fn sidechain_level(
+    sidechain: &[(f32, f32)],
+    input: (f32, f32),
+    index: usize,
+    use_sidechain: bool,
+) -> f32 {
+    let (left, right) = if use_sidechain {
+        sidechain.get(index).cloned().unwrap_or_default()
+    } else {
+        input
+    };
+    left.abs().max(right.abs())
+}
+
+/// Compressor reduces the volume of loud sounds, thus reducing the dynamic range of the signal. Once the
+/// level of the signal exceeds the threshold, the gain is reduced according to the ratio. For example,
+/// the ratio of `4.0` means that every 4 dB above the threshold will be reduced to 1 dB.
+#[derive(Debug, Clone, PartialEq, Visit, Reflect)]
+pub struct Compressor {
+    #[reflect(
+        description = "Level of the signal (in decibels) above which the compression starts.",
+        setter = "set_threshold_db",
+        max_value = 0.0
+    )]
+    threshold_db: f32,
+
+    #[reflect(
+        description = "Compression ratio. For example, the ratio of 4.0 means that every 4 dB above the threshold \
+        will be reduced to 1 dB.",
+        setter = "set_ratio",
+        min_value = 1.0
+    )]
+    ratio: f32,
+
+    #[reflect(
+        description = "Time (in seconds) that is needed for the compressor to react to a signal that exceeds \
+        the threshold.",
+        setter = "set_attack_time",
+        min_value = 0.0
+    )]
+    attack_time: f32,
+
+    #[reflect(
+        description = "Time (in seconds) that is needed for the compressor to restore the gain after the signal \
+        fell below the threshold.",
+        setter = "set_release_time",
+        min_value = 0.0
+    )]
+    release_time: f32,
+
+    #[reflect(
+        description = "Gain (in decibels) applied after the compression.",
+        setter = "set_makeup_gain_db"
+    )]
+    makeup_gain_db: f32,
+
+    #[reflect(
+        description = "Name of an audio bus, which signal controls the compression (ducking). Empty name means \
+        that the input signal is used."
+    )]
+    sidechain_bus: String,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    envelope: EnvelopeFollower,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub(crate) sidechain: Vec<(f32, f32)>,
+}
+
+impl Default for Compressor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Compressor {
+    /// Creates new compressor with -12 dB threshold and 4:1 ratio.
+    pub fn new() -> Self {
+        Self {
+            threshold_db: -12.0,
+            ratio: 4.0,
+            attack_time: 0.01,
+            release_time: 0.2,
+            makeup_gain_db: 0.0,
+            sidechain_bus: Default::default(),
+            envelope: Default::default(),
+            sidechain: Default::default(),
+        }
+    }
+
+    /// Sets the level of the signal (in decibels) above which the compression starts.
+    pub fn set_threshold_db(&mut self, threshold_db: f32) -> f32 {
+        std::mem::replace(&mut self.threshold_db, threshold_db.min(0.0))
+    }
+
+    /// Returns the threshold in decibels.
+    pub fn threshold_db(&self) -> f32 {
+        self.threshold_db
+    }
+
+    /// Sets compression ratio. The value is clamped to `[1.0; +inf]` range, 1.0 means no compression.
+    pub fn set_ratio(&mut self, ratio: f32) -> f32 {
+        std::mem::replace(&mut self.ratio, ratio.max(1.0))
+    }
+
+    /// Returns compression ratio.
+    pub fn ratio(&self) -> f32 {
+        self.ratio
+    }
+
+    /// Sets the time (in seconds) that is needed for the compressor to react to a loud signal.
+    pub fn set_attack_time(&mut self, attack_time: f32) -> f32 {
+        std::mem::replace(&mut self.attack_time, attack_time.max(0.0))
+    }
+
+    /// Returns attack time in seconds.
+    pub fn attack_time(&self) -> f32 {
+        self.attack_time
+    }
+
+    /// Sets the time (in seconds) that is needed for the compressor to restore the gain.
+    pub fn set_release_time(&mut self, release_time: f32) -> f32 {
+        std::mem::replace(&mut self.release_time, release_time.max(0.0))
+    }
+
+    /// Returns release time in seconds.
+    pub fn release_time(&self) -> f32 {
+        self.release_time
+    }
+
+    /// Sets the gain (in decibels) that will be applied after the compression. It is used to compensate
+    /// the loss of loudness.
+    pub fn set_makeup_gain_db(&mut self, makeup_gain_db: f32) -> f32 {
+        std::mem::replace(&mut self.makeup_gain_db, makeup_gain_db)
+    }
+
+    /// Returns makeup gain in decibels.
+    pub fn makeup_gain_db(&self) -> f32 {
+        self.makeup_gain_db
+    }
+
+    /// Sets the name of an audio bus, which signal will control the compression. Empty name means that
+    /// the input signal is used. See module docs for more info.
+    pub fn set_sidechain_bus<S: AsRef<str>>(&mut self, name: S) -> String {
+        std::mem::replace(&mut self.sidechain_bus, name.as_ref().to_owned())
+    }
+
+    /// Returns the name of the sidechain audio bus.
+    pub fn sidechain_bus(&self) -> &str {
+        &self.sidechain_bus
+    }
+
+    fn gain_for(&self, envelope: f32) -> f32 {
+        let over = linear_to_db(envelope) - self.threshold_db;
+        let reduction = if over > 0.0 {
+            over * (1.0 - 1.0 / self.ratio)
+        } else {
+            0.0
+        };
+        db_to_linear(self.makeup_gain_db - reduction)
+    }
+}
+
+impl EffectRenderTrait for Compressor {
+    fn render(&mut self, input: &[(f32, f32)], output: &mut [(f32, f32)]) {
+        let attack = time_constant_coefficient(self.attack_time, SAMPLE_RATE);
+        let release = time_constant_coefficient(self.release_time, SAMPLE_RATE);
+        let use_sidechain = !self.sidechain_bus.is_empty();
+
+        for (i, (&(left, right), (output_left, output_right))) in
+            input.iter().zip(output.iter_mut()).enumerate()
+        {
+            let level = sidechain_level(&self.sidechain, (left, right), i, use_sidechain);
+            let envelope = self.envelope.feed(level, attack, release);
+            let gain = self.gain_for(envelope);
+            *output_left = left * gain;
+            *output_right = right * gain;
+        }
+    }
+}
+
+/// Limiter is a compressor with infinite ratio and instant attack, it guarantees that the output signal
+/// never exceeds the ceiling level. It is usually placed at the end of the effects chain of the primary
+/// audio bus to prevent clipping.
+#[derive(Debug, Clone, PartialEq, Visit, Reflect)]
+pub struct Limiter {
+    #[reflect(
+        description = "Maximum level of the output signal in decibels.",
+        setter = "set_ceiling_db",
+        max_value = 0.0
+    )]
+    ceiling_db: f32,
+
+    #[reflect(
+        description = "Time (in seconds) that is needed for the limiter to restore the gain.",
+        setter = "set_release_time",
+        min_value = 0.0
+    )]
+    release_time: f32,
+
+    #[reflect(
+        description = "Name of an audio bus, which signal controls the limiting. Empty name means \
+        that the input signal is used."
+    )]
+    sidechain_bus: String,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    envelope: f32,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub(crate) sidechain: Vec<(f32, f32)>,
+}
+
+impl Default for Limiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Limiter {
+    /// Creates new limiter with -0.3 dB ceiling.
+    pub fn new() -> Self {
+        Self {
+            ceiling_db: -0.3,
+            release_time: 0.1,
+            sidechain_bus: Default::default(),
+            envelope: 0.0,
+            sidechain: Default::default(),
+        }
+    }
+
+    /// Sets the maximum level of the output signal in decibels.
+    pub fn set_ceiling_db(&mut self, ceiling_db: f32) -> f32 {
+        std::mem::replace(&mut self.ceiling_db, ceiling_db.min(0.0))
+    }
+
+    /// Returns the ceiling in decibels.
+    pub fn ceiling_db(&self) -> f32 {
+        self.ceiling_db
+    }
+
+    /// Sets the time (in seconds) that is needed for the limiter to restore the gain.
+    pub fn set_release_time(&mut self, release_time: f32) -> f32 {
+        std::mem::replace(&mut self.release_time, release_time.max(0.0))
+    }
+
+    /// Returns release time in seconds.
+    pub fn release_time(&self) -> f32 {
+        self.release_time
+    }
+
+    /// Sets the name of an audio bus, which signal will control the limiting. Empty name means that
+    /// the input signal is used.
+    pub fn set_sidechain_bus<S: AsRef<str>>(&mut self, name: S) -> String {
+        std::mem::replace(&mut self.sidechain_bus, name.as_ref().to_owned())
+    }
+
+    /// Returns the name of the sidechain audio bus.
+    pub fn sidechain_bus(&self) -> &str {
+        &self.sidechain_bus
+    }
+}
+
+impl EffectRenderTrait for Limiter {
+    fn render(&mut self, input: &[(f32, f32)], output: &mut [(f32, f32)]) {
+        let release = time_constant_coefficient(self.release_time, SAMPLE_RATE);
+        let ceiling = db_to_linear(self.ceiling_db);
+        let use_sidechain = !self.sidechain_bus.is_empty();
+
+        for (i, (&(left, right), (output_left, output_right))) in
+            input.iter().zip(output.iter_mut()).enumerate()
+        {
+            let level = sidechain_level(&self.sidechain, (left, right), i, use_sidechain);
+            // Instant attack, the envelope is never lower than the current level.
+            self.envelope = level.max(release * self.envelope + (1.0 - release) * level);
+            let gain = if self.envelope > ceiling {
+                ceiling / self.envelope
+            } else {
+                1.0
+            };
+            *output_left = left * gain;
+            *output_right = right * gain;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        dsp::db_to_linear,
+        effects::{
+            compressor::{Compressor, Limiter},
+            EffectRenderTrait,
+        },
+    };
+
+    #[test]
+    fn test_compressor_gain_reduction() {
+        let mut compressor = Compressor::new();
+        compressor.set_threshold_db(-20.0);
+        compressor.set_ratio(4.0);
+        compressor.set_attack_time(0.0);
+
+        // 0 dB signal is 20 dB above threshold, with 4:1 ratio output must be at -15 dB.
+        let input = vec![(1.0, 1.0); 64];
+        let mut output = vec![(0.0, 0.0); 64];
+        compressor.render(&input, &mut output);
+        let expected = db_to_linear(-15.0);
+        assert!((output[63].0 - expected).abs() < 1.0e-4);
+
+        // Signal below threshold is not changed.
+        let input = vec![(0.01, 0.01); 44100];
+        let mut output = vec![(0.0, 0.0); 44100];
+        compressor.render(&input, &mut output);
+        assert!((output[44099].0 - 0.01).abs() < 1.0e-4);
+    }
+
+    #[test]
+    fn test_compressor_sidechain() {
+        let mut compressor = Compressor::new();
+        compressor.set_threshold_db(-20.0);
+        compressor.set_ratio(f32::INFINITY);
+        compressor.set_attack_time(0.0);
+        compressor.set_sidechain_bus("Dialogue");
+
+        // Quiet input must be ducked by loud sidechain signal.
+        compressor.sidechain = vec![(1.0, 1.0); 16];
+        let input = vec![(0.05, 0.05); 16];
+        let mut output = vec![(0.0, 0.0); 16];
+        compressor.render(&input, &mut output);
+        assert!((output[15].0 - 0.005).abs() < 1.0e-4);
+    }
+
+    #[test]
+    fn test_limiter_ceiling() {
+        let mut limiter = Limiter::new();
+        limiter.set_ceiling_db(-6.0);
+        let ceiling = db_to_linear(-6.0);
+
+        let input = (0..1000)
+            .map(|i| {
+                let s = (i as f32 * 0.1).sin() * 3.0;
+                (s, -s)
+            })
+            .collect::<Vec<_>>();
+        let mut output = vec![(0.0, 0.0); input.len()];
+        limiter.render(&input, &mut output);
+        for (left, right) in output {
+            assert!(left.abs() <= ceiling + 1.0e-6);
+            assert!(right.abs() <= ceiling + 1.0e-6);
+        }
+    }
+}
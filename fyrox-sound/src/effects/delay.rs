@@ -0,0 +1,182 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Delay (echo) effect.
+
+use crate::{context::SAMPLE_RATE, dsp::DelayLine, effects::EffectRenderTrait};
+use fyrox_core::{reflect::prelude::*, visitor::prelude::*};
+
+/// Delay effect repeats the input signal after some time with decreasing amplitude, creating an echo.
+#[derive(Debug, Clone, PartialEq, Visit, Reflect)]
+pub struct Delay {
+    #[reflect(
+        description = "Time (in seconds) between echoes.",
+        setter = "set_delay_time",
+        min_value = 0.0,
+        max_value = 10.0
+    )]
+    delay_time: f32,
+
+    #[reflect(
+        description = "Amount of the delayed signal that is fed back to the delay line. Defines how fast echoes decay.",
+        setter = "set_feedback",
+        min_value = 0.0,
+        max_value = 0.99
+    )]
+    feedback: f32,
+
+    #[reflect(
+        description = "Amount of the delayed signal in the output.",
+        setter = "set_wet",
+        min_value = 0.0,
+        max_value = 1.0
+    )]
+    wet: f32,
+
+    #[reflect(
+        description = "Amount of the input signal in the output.",
+        setter = "set_dry",
+        min_value = 0.0,
+        max_value = 1.0
+    )]
+    dry: f32,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    left: DelayLine,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    right: DelayLine,
+}
+
+impl Default for Delay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Delay {
+    /// Creates new delay effect with 0.3 seconds delay time.
+    pub fn new() -> Self {
+        Self {
+            delay_time: 0.3,
+            feedback: 0.4,
+            wet: 0.5,
+            dry: 1.0,
+            left: Default::default(),
+            right: Default::default(),
+        }
+    }
+
+    /// Sets the time (in seconds) between echoes.
+    pub fn set_delay_time(&mut self, delay_time: f32) -> f32 {
+        std::mem::replace(&mut self.delay_time, delay_time.clamp(0.0, 10.0))
+    }
+
+    /// Returns the time between echoes in seconds.
+    pub fn delay_time(&self) -> f32 {
+        self.delay_time
+    }
+
+    /// Sets the amount of the delayed signal that is fed back to the delay line. The value is clamped to
+    /// `[0.0; 0.99]` range to keep the effect stable.
+    pub fn set_feedback(&mut self, feedback: f32) -> f32 {
+        std::mem::replace(&mut self.feedback, feedback.clamp(0.0, 0.99))
+    }
+
+    /// Returns the feedback coefficient.
+    pub fn feedback(&self) -> f32 {
+        self.feedback
+    }
+
+    /// Sets the amount of the delayed signal in the output.
+    pub fn set_wet(&mut self, wet: f32) -> f32 {
+        std::mem::replace(&mut self.wet, wet.clamp(0.0, 1.0))
+    }
+
+    /// Returns the amount of the delayed signal in the output.
+    pub fn wet(&self) -> f32 {
+        self.wet
+    }
+
+    /// Sets the amount of the input signal in the output.
+    pub fn set_dry(&mut self, dry: f32) -> f32 {
+        std::mem::replace(&mut self.dry, dry.clamp(0.0, 1.0))
+    }
+
+    /// Returns the amount of the input signal in the output.
+    pub fn dry(&self) -> f32 {
+        self.dry
+    }
+}
+
+impl EffectRenderTrait for Delay {
+    fn render(&mut self, input: &[(f32, f32)], output: &mut [(f32, f32)]) {
+        // Delay lines are (re)created lazily, this also handles deserialization and changes made
+        // via reflection.
+        // The last sample of a delay line was written `len + 1` samples ago.
+        let len = ((self.delay_time * SAMPLE_RATE as f32) as usize)
+            .saturating_sub(1)
+            .max(1);
+        if self.left.len() != len {
+            self.left = DelayLine::new(len);
+            self.right = DelayLine::new(len);
+        }
+
+        for (&(left, right), (output_left, output_right)) in input.iter().zip(output.iter_mut()) {
+            let delayed_left = self.left.last();
+            let delayed_right = self.right.last();
+            self.left.feed(left + delayed_left * self.feedback);
+            self.right.feed(right + delayed_right * self.feedback);
+            *output_left = left * self.dry + delayed_left * self.wet;
+            *output_right = right * self.dry + delayed_right * self.wet;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        context::SAMPLE_RATE,
+        effects::{delay::Delay, EffectRenderTrait},
+    };
+
+    #[test]
+    fn test_delay_echo() {
+        let mut delay = Delay::new();
+        delay.set_delay_time(0.01);
+        delay.set_feedback(0.5);
+        delay.set_wet(1.0);
+        delay.set_dry(0.0);
+
+        let period = (0.01 * SAMPLE_RATE as f32) as usize;
+        let mut input = vec![(0.0, 0.0); period * 3 + 1];
+        input[0] = (1.0, 1.0);
+        let mut output = vec![(0.0, 0.0); input.len()];
+        delay.render(&input, &mut output);
+
+        assert_eq!(output[period], (1.0, 1.0));
+        assert_eq!(output[2 * period], (0.5, 0.5));
+        assert_eq!(output[3 * period], (0.25, 0.25));
+        assert_eq!(output[period - 1], (0.0, 0.0));
+        assert_eq!(output[period + 1], (0.0, 0.0));
+    }
+}
@@ -0,0 +1,141 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Distortion effect.
+
+use crate::effects::EffectRenderTrait;
+use fyrox_core::{reflect::prelude::*, visitor::prelude::*};
+
+/// Distortion effect amplifies the input signal and then softly clips it, adding harmonics to the
+/// sound. It could be used to make sounds "dirty", for example for radio transmissions or to make
+/// sounds of guns more aggressive.
+#[derive(Debug, Clone, PartialEq, Visit, Reflect)]
+pub struct Distortion {
+    #[reflect(
+        description = "Amplification of the signal before clipping. The higher the value, the more distorted \
+        the output will be.",
+        setter = "set_drive",
+        min_value = 1.0,
+        max_value = 100.0
+    )]
+    drive: f32,
+
+    #[reflect(
+        description = "Amount of the distorted signal in the output. 0.0 - only input signal, 1.0 - only \
+        distorted signal.",
+        setter = "set_mix",
+        min_value = 0.0,
+        max_value = 1.0
+    )]
+    mix: f32,
+
+    #[reflect(
+        description = "Gain of the output signal.",
+        setter = "set_output_gain",
+        min_value = 0.0
+    )]
+    output_gain: f32,
+}
+
+impl Default for Distortion {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Distortion {
+    /// Creates new distortion effect.
+    pub fn new() -> Self {
+        Self {
+            drive: 4.0,
+            mix: 1.0,
+            output_gain: 1.0,
+        }
+    }
+
+    /// Sets the amplification of the signal before clipping. The value is clamped to `[1.0; 100.0]` range.
+    pub fn set_drive(&mut self, drive: f32) -> f32 {
+        std::mem::replace(&mut self.drive, drive.clamp(1.0, 100.0))
+    }
+
+    /// Returns the amplification of the signal before clipping.
+    pub fn drive(&self) -> f32 {
+        self.drive
+    }
+
+    /// Sets the amount of the distorted signal in the output.
+    pub fn set_mix(&mut self, mix: f32) -> f32 {
+        std::mem::replace(&mut self.mix, mix.clamp(0.0, 1.0))
+    }
+
+    /// Returns the amount of the distorted signal in the output.
+    pub fn mix(&self) -> f32 {
+        self.mix
+    }
+
+    /// Sets the gain of the output signal.
+    pub fn set_output_gain(&mut self, output_gain: f32) -> f32 {
+        std::mem::replace(&mut self.output_gain, output_gain.max(0.0))
+    }
+
+    /// Returns the gain of the output signal.
+    pub fn output_gain(&self) -> f32 {
+        self.output_gain
+    }
+
+    fn shape(&self, sample: f32) -> f32 {
+        // Normalized so that the full scale input gives the full scale output.
+        let distorted = (sample * self.drive).tanh() / self.drive.tanh();
+        (sample * (1.0 - self.mix) + distorted * self.mix) * self.output_gain
+    }
+}
+
+impl EffectRenderTrait for Distortion {
+    fn render(&mut self, input: &[(f32, f32)], output: &mut [(f32, f32)]) {
+        for (&(left, right), (output_left, output_right)) in input.iter().zip(output.iter_mut()) {
+            *output_left = self.shape(left);
+            *output_right = self.shape(right);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::effects::{distortion::Distortion, EffectRenderTrait};
+
+    #[test]
+    fn test_distortion_soft_clip() {
+        let input = [(1.0, -1.0), (0.1, 0.0), (2.0, -2.0)];
+        let mut output = [(0.0, 0.0); 3];
+
+        let mut distortion = Distortion::new();
+        distortion.render(&input, &mut output);
+
+        // Full scale stays full scale.
+        assert!((output[0].0 - 1.0).abs() < 1.0e-6);
+        assert!((output[0].1 + 1.0).abs() < 1.0e-6);
+        // Quiet parts are amplified.
+        assert!(output[1].0 > 0.1);
+        assert_eq!(output[1].1, 0.0);
+        // Loud parts are clipped.
+        assert!(output[2].0 < 1.1);
+        assert!(output[2].1 > -1.1);
+    }
+}
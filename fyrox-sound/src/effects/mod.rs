@@ -21,6 +21,10 @@
 //! Contins everything related to audio effects that can be applied to an audio bus.
 
 use crate::{
+    effects::chorus::Chorus,
+    effects::compressor::{Compressor, Limiter},
+    effects::delay::Delay,
+    effects::distortion::Distortion,
     effects::filter::{
         AllPassFilterEffect, BandPassFilterEffect, HighPassFilterEffect, HighShelfFilterEffect,
         LowPassFilterEffect, LowShelfFilterEffect,
@@ -30,6 +34,10 @@ use crate::{
 use fyrox_core::{reflect::prelude::*, uuid_provider, visitor::prelude::*};
 use strum_macros::{AsRefStr, EnumString, VariantNames};
 
+pub mod chorus;
+pub mod compressor;
+pub mod delay;
+pub mod distortion;
 pub mod filter;
 pub mod reverb;
 
@@ -86,6 +94,16 @@ pub enum Effect {
     LowShelfFilter(LowShelfFilterEffect),
     /// See [`HighShelfFilterEffect`] docs for more info.
     HighShelfFilter(HighShelfFilterEffect),
+    /// See [`Compressor`] docs for more info.
+    Compressor(Compressor),
+    /// See [`Limiter`] docs for more info.
+    Limiter(Limiter),
+    /// See [`Delay`] docs for more info.
+    Delay(Delay),
+    /// See [`Chorus`] docs for more info.
+    Chorus(Chorus),
+    /// See [`Distortion`] docs for more info.
+    Distortion(Distortion),
 }
 
 uuid_provider!(Effect = "fc52e441-d1ec-4881-937c-9e2e53a6d621");
@@ -96,6 +114,26 @@ impl Default for Effect {
     }
 }
 
+impl Effect {
+    /// Returns the name of the audio bus, that is used as a sidechain input of the effect. Only
+    /// effects that support sidechaining return some value here.
+    pub(crate) fn sidechain_bus(&self) -> Option<&str> {
+        match self {
+            Effect::Compressor(compressor) => Some(compressor.sidechain_bus()),
+            Effect::Limiter(limiter) => Some(limiter.sidechain_bus()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn sidechain_buffer_mut(&mut self) -> Option<&mut Vec<(f32, f32)>> {
+        match self {
+            Effect::Compressor(compressor) => Some(&mut compressor.sidechain),
+            Effect::Limiter(limiter) => Some(&mut limiter.sidechain),
+            _ => None,
+        }
+    }
+}
+
 pub(crate) trait EffectRenderTrait {
     fn render(&mut self, input: &[(f32, f32)], output: &mut [(f32, f32)]);
 }
@@ -111,6 +149,11 @@ macro_rules! static_dispatch {
             Effect::AllPassFilter(v) => v.$func($($args),*),
             Effect::LowShelfFilter(v) => v.$func($($args),*),
             Effect::HighShelfFilter(v) => v.$func($($args),*),
+            Effect::Compressor(v) => v.$func($($args),*),
+            Effect::Limiter(v) => v.$func($($args),*),
+            Effect::Delay(v) => v.$func($($args),*),
+            Effect::Chorus(v) => v.$func($($args),*),
+            Effect::Distortion(v) => v.$func($($args),*),
         }
     };
 }
@@ -0,0 +1,178 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Encoders for the output of the sound engine. At the moment only WAV format is supported. Encoders
+//! are mostly used for offline rendering, see [`crate::context::SoundContext::render_to_wav_file`]
+//! for more info.
+
+use crate::{context::SAMPLE_RATE, error::SoundError};
+use hound::{SampleFormat, WavSpec, WavWriter};
+use std::{
+    fs::File,
+    io::{BufWriter, Seek, Write},
+    path::Path,
+};
+
+/// Sample format of a WAV file.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum WavSampleFormat {
+    /// Signed 16-bit integer samples. This is the most compatible format, but samples outside of
+    /// `[-1.0; 1.0]` range will be clipped.
+    #[default]
+    Pcm16,
+    /// 32-bit floating point samples. Output of the engine is stored as is, without any loss
+    /// of precision.
+    Float32,
+}
+
+impl WavSampleFormat {
+    fn spec(self, sample_rate: u32) -> WavSpec {
+        let (bits_per_sample, sample_format) = match self {
+            WavSampleFormat::Pcm16 => (16, SampleFormat::Int),
+            WavSampleFormat::Float32 => (32, SampleFormat::Float),
+        };
+        WavSpec {
+            channels: 2,
+            sample_rate,
+            bits_per_sample,
+            sample_format,
+        }
+    }
+}
+
+/// WAV encoder writes stereo samples produced by the sound engine to any seekable destination.
+/// Encoder must be [finalized](WavEncoder::finalize) when all samples are written, otherwise
+/// the header of the file will be incorrect.
+///
+/// ## Example
+///
+/// ```no_run
+/// use fyrox_sound::encoder::{WavEncoder, WavSampleFormat};
+///
+/// let mut encoder = WavEncoder::create("sine.wav", WavSampleFormat::Pcm16).unwrap();
+/// let samples = (0..44100)
+///     .map(|i| {
+///         let sample = (i as f32 * 440.0 * std::f32::consts::TAU / 44100.0).sin();
+///         (sample, sample)
+///     })
+///     .collect::<Vec<_>>();
+/// encoder.write_samples(&samples).unwrap();
+/// encoder.finalize().unwrap();
+/// ```
+pub struct WavEncoder<W: Write + Seek> {
+    writer: WavWriter<W>,
+    format: WavSampleFormat,
+}
+
+impl WavEncoder<BufWriter<File>> {
+    /// Creates a new file at the given path and prepares it for writing samples with the sample
+    /// rate of the sound engine.
+    pub fn create<P: AsRef<Path>>(path: P, format: WavSampleFormat) -> Result<Self, SoundError> {
+        Ok(Self {
+            writer: WavWriter::create(path, format.spec(SAMPLE_RATE))?,
+            format,
+        })
+    }
+}
+
+impl<W: Write + Seek> WavEncoder<W> {
+    /// Creates a new encoder that writes samples with the given sample rate to the given writer.
+    pub fn new(writer: W, sample_rate: u32, format: WavSampleFormat) -> Result<Self, SoundError> {
+        Ok(Self {
+            writer: WavWriter::new(writer, format.spec(sample_rate))?,
+            format,
+        })
+    }
+
+    /// Returns sample format of the encoder.
+    pub fn format(&self) -> WavSampleFormat {
+        self.format
+    }
+
+    /// Writes the given interleaved stereo samples.
+    pub fn write_samples(&mut self, samples: &[(f32, f32)]) -> Result<(), SoundError> {
+        match self.format {
+            WavSampleFormat::Pcm16 => {
+                let mut writer = self.writer.get_i16_writer(2 * samples.len() as u32);
+                for &(left, right) in samples {
+                    writer.write_sample(f32_to_i16(left));
+                    writer.write_sample(f32_to_i16(right));
+                }
+                writer.flush()?;
+            }
+            WavSampleFormat::Float32 => {
+                for &(left, right) in samples {
+                    self.writer.write_sample(left)?;
+                    self.writer.write_sample(right)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the header of the file and flushes all buffered data.
+    pub fn finalize(self) -> Result<(), SoundError> {
+        Ok(self.writer.finalize()?)
+    }
+}
+
+fn f32_to_i16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16
+}
+
+#[cfg(test)]
+mod test {
+    use crate::encoder::{WavEncoder, WavSampleFormat};
+    use hound::WavReader;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_wav_encoding() {
+        let samples = [(0.0, 0.0), (1.0, -1.0), (0.5, -0.25), (2.0, -2.0)];
+
+        let mut cursor = Cursor::new(Vec::new());
+        let mut encoder = WavEncoder::new(&mut cursor, 22050, WavSampleFormat::Pcm16).unwrap();
+        encoder.write_samples(&samples).unwrap();
+        encoder.finalize().unwrap();
+
+        cursor.set_position(0);
+        let mut reader = WavReader::new(cursor).unwrap();
+        assert_eq!(reader.spec().sample_rate, 22050);
+        assert_eq!(reader.spec().channels, 2);
+        let decoded = reader
+            .samples::<i16>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, [0, 0, 32767, -32767, 16384, -8192, 32767, -32767]);
+
+        let mut cursor = Cursor::new(Vec::new());
+        let mut encoder = WavEncoder::new(&mut cursor, 44100, WavSampleFormat::Float32).unwrap();
+        encoder.write_samples(&samples).unwrap();
+        encoder.finalize().unwrap();
+
+        cursor.set_position(0);
+        let mut reader = WavReader::new(cursor).unwrap();
+        let decoded = reader
+            .samples::<f32>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, [0.0, 0.0, 1.0, -1.0, 0.5, -0.25, 2.0, -2.0]);
+    }
+}
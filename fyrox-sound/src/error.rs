@@ -33,6 +33,13 @@ pub enum DecoderError {
     Ogg(lewton::VorbisError),
 }
 
+/// Encoder specific error.
+#[derive(Debug)]
+pub enum EncoderError {
+    /// WAV specific encoder error.
+    Wav(hound::Error),
+}
+
 /// Generic error enumeration for each error in this engine.
 #[derive(Debug)]
 pub enum SoundError {
@@ -68,6 +75,10 @@ pub enum SoundError {
     /// insufficient data, etc.). Exact reason stored in inner value.
     DecoderError(DecoderError),
 
+    /// Encoder specific error, can occur when writing samples in a specific format. Exact reason
+    /// stored in inner value.
+    EncoderError(EncoderError),
+
     /// A buffer is invalid (for example it is LoadError state)
     BufferFailedToLoad,
 
@@ -93,6 +104,15 @@ impl From<lewton::VorbisError> for SoundError {
     }
 }
 
+impl From<hound::Error> for SoundError {
+    fn from(e: hound::Error) -> Self {
+        match e {
+            hound::Error::IoError(io) => SoundError::Io(io),
+            e => SoundError::EncoderError(EncoderError::Wav(e)),
+        }
+    }
+}
+
 impl Display for SoundError {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
         match self {
@@ -111,6 +131,7 @@ impl Display for SoundError {
                 write!(f, "streaming buffer in already in use")
             }
             SoundError::DecoderError(de) => write!(f, "internal decoder error: {de:?}"),
+            SoundError::EncoderError(ee) => write!(f, "internal encoder error: {ee:?}"),
             SoundError::BufferFailedToLoad => write!(f, "a buffer failed to load"),
             SoundError::BufferIsNotLoaded => write!(f, "a buffer is not loaded yet"),
         }
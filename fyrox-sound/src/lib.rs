@@ -27,6 +27,7 @@
 //! - Streaming.
 //! - Head-related transfer function support ([HRTF](https://en.wikipedia.org/wiki/Head-related_transfer_function)).
 //! - Reverb effect.
+//! - Offline rendering to WAV files.
 //!
 //! ## Examples
 //!
@@ -93,6 +94,7 @@ pub mod context;
 pub mod bus;
 pub mod dsp;
 pub mod effects;
+pub mod encoder;
 pub mod engine;
 pub mod error;
 pub mod listener;
@@ -51,7 +51,8 @@
 use crate::{
     buffer::{streaming::StreamingBuffer, SoundBuffer, SoundBufferResource},
     bus::AudioBusGraph,
-    context::DistanceModel,
+    context::{DistanceModel, SAMPLE_RATE},
+    dsp::filters::{Biquad, BiquadKind},
     error::SoundError,
     listener::Listener,
 };
@@ -158,6 +159,20 @@ pub struct SoundSource {
     #[reflect(hidden)]
     #[visit(skip)]
     pub(crate) prev_distance_gain: Option<f32>,
+    // Occlusion is calculated by an external code (usually by ray casting in a physics world) on each frame,
+    // so there's no need to save it.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    occlusion_gain: f32,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    occlusion_cutoff_frequency_hz: f32,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    last_occlusion_gain: Option<f32>,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    occlusion_filters: Option<(Biquad, Biquad)>,
 }
 
 impl Default for SoundSource {
@@ -188,6 +203,10 @@ impl Default for SoundSource {
             prev_right_samples: Default::default(),
             prev_sampling_vector: Vector3::new(0.0, 0.0, 1.0),
             prev_distance_gain: None,
+            occlusion_gain: 1.0,
+            occlusion_cutoff_frequency_hz: f32::MAX,
+            last_occlusion_gain: None,
+            occlusion_filters: None,
         }
     }
 }
@@ -509,6 +528,74 @@ impl SoundSource {
         }
     }
 
+    /// Sets occlusion parameters of the sound source. Occlusion simulates obstacles between the source and
+    /// the listener, `gain` defines how much the volume of the source is reduced (must be in `[0.0; 1.0]`
+    /// range) and `cutoff_frequency_hz` defines cutoff frequency of a low-pass filter that muffles the source.
+    /// Cutoff frequencies that are larger than the half of the sample rate disable the filter.
+    ///
+    /// Occlusion is not calculated by the sound engine itself, because it knows nothing about the geometry
+    /// of the world. Instead, it should be calculated externally (for example, by ray casting in a physics
+    /// world) and set on each frame.
+    pub fn set_occlusion(&mut self, gain: f32, cutoff_frequency_hz: f32) {
+        self.occlusion_gain = gain.clamp(0.0, 1.0);
+        self.occlusion_cutoff_frequency_hz = cutoff_frequency_hz.max(0.0);
+    }
+
+    /// Returns the gain of the occlusion. See [`Self::set_occlusion`] for more info.
+    pub fn occlusion_gain(&self) -> f32 {
+        self.occlusion_gain
+    }
+
+    /// Returns the cutoff frequency of the occlusion low-pass filter. See [`Self::set_occlusion`] for more
+    /// info.
+    pub fn occlusion_cutoff_frequency_hz(&self) -> f32 {
+        self.occlusion_cutoff_frequency_hz
+    }
+
+    fn apply_occlusion(&mut self) {
+        let nyquist_frequency = SAMPLE_RATE as f32 * 0.5;
+        if self.occlusion_cutoff_frequency_hz < nyquist_frequency {
+            let fc = self.occlusion_cutoff_frequency_hz / SAMPLE_RATE as f32;
+            let (left_filter, right_filter) =
+                self.occlusion_filters.get_or_insert_with(Default::default);
+            left_filter.tune(
+                BiquadKind::LowPass,
+                fc,
+                1.0,
+                std::f32::consts::FRAC_1_SQRT_2,
+            );
+            right_filter.tune(
+                BiquadKind::LowPass,
+                fc,
+                1.0,
+                std::f32::consts::FRAC_1_SQRT_2,
+            );
+            for (left, right) in self.frame_samples.iter_mut() {
+                *left = left_filter.feed(*left);
+                *right = right_filter.feed(*right);
+            }
+        } else {
+            self.occlusion_filters = None;
+        }
+
+        let gain = self.occlusion_gain;
+        let last_gain = self.last_occlusion_gain.replace(gain).unwrap_or(gain);
+        if last_gain != gain {
+            // Interpolate the gain to prevent clicks.
+            let step = 1.0 / self.frame_samples.len() as f32;
+            for (i, (left, right)) in self.frame_samples.iter_mut().enumerate() {
+                let k = fyrox_core::math::lerpf(last_gain, gain, i as f32 * step);
+                *left *= k;
+                *right *= k;
+            }
+        } else if gain != 1.0 {
+            for (left, right) in self.frame_samples.iter_mut() {
+                *left *= gain;
+                *right *= gain;
+            }
+        }
+    }
+
     pub(crate) fn render(&mut self, amount: usize) {
         if self.frame_samples.capacity() < amount {
             self.frame_samples = Vec::with_capacity(amount);
@@ -526,6 +613,7 @@ impl SoundSource {
         }
         // Fill the remaining part of frame_samples.
         self.frame_samples.resize(amount, (0.0, 0.0));
+        self.apply_occlusion();
     }
 
     fn render_playing(&mut self, buffer: &mut SoundBuffer, amount: usize) {
@@ -882,6 +970,7 @@ impl SoundSourceBuilder {
             prev_left_samples: Default::default(),
             prev_right_samples: Default::default(),
             bus: self.bus,
+            occlusion_filters: None,
             ..Default::default()
         };
 
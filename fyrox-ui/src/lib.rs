@@ -280,7 +280,7 @@ use crate::{
         pool::{Handle, Pool},
         reflect::prelude::*,
         uuid::uuid,
-        visitor::prelude::*,
+        visitor::{prelude::*, VisitorFormat},
     },
     core::{parking_lot::Mutex, pool::Ticket, uuid::Uuid, uuid_provider, TypeUuidProvider},
     draw::{CommandTexture, Draw, DrawingContext},
@@ -2943,9 +2943,18 @@ impl UserInterface {
     }
 
     pub fn save(&mut self, path: &Path) -> Result<Visitor, VisitError> {
+        self.save_with_format(path, VisitorFormat::Binary)
+    }
+
+    /// Same as [`Self::save`], but allows to specify the format of the file.
+    pub fn save_with_format(
+        &mut self,
+        path: &Path,
+        format: VisitorFormat,
+    ) -> Result<Visitor, VisitError> {
         let mut visitor = Visitor::new();
         self.visit("Ui", &mut visitor)?;
-        visitor.save_binary(path)?;
+        visitor.save_to_file(path, format)?;
         Ok(visitor)
     }
 